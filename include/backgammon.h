@@ -0,0 +1,108 @@
+/*
+ * C interface of the backgammon crate, built with the `ffi` feature, e.g. by
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Games are opaque handles created by bg_game_new or bg_game_from_xgid and
+ * released by bg_game_free. Strings returned by the library are UTF-8 and
+ * released by bg_string_free. Functions changing a game return BG_OK or one
+ * of the error codes below, which are stable across versions.
+ */
+#ifndef BACKGAMMON_H
+#define BACKGAMMON_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BG_OK 0
+#define BG_ERROR_ARGUMENT -1
+#define BG_ERROR_GAME_STARTED 1
+#define BG_ERROR_GAME_ENDED 2
+#define BG_ERROR_MATCH_ENDED 3
+#define BG_ERROR_SESSION_ENDED 4
+#define BG_ERROR_CUBE_RECEIVED 5
+#define BG_ERROR_DOUBLING_NOT_PERMITTED 6
+#define BG_ERROR_CUBE_VALUE_INVALID 7
+#define BG_ERROR_PLAYER_INVALID 8
+#define BG_ERROR_FIELD_BLOCKED 9
+#define BG_ERROR_FIELD_INVALID 10
+#define BG_ERROR_NOT_YOUR_TURN 11
+#define BG_ERROR_MOVE_INVALID 12
+#define BG_ERROR_MOVE_INVALID_BAR 13
+#define BG_ERROR_MOVE_INVALID_MAX_DICE 14
+#define BG_ERROR_MOVE_INVALID_LARGER_DICE 15
+#define BG_ERROR_MOVE_INVALID_BEAR_OFF 16
+#define BG_ERROR_MOVE_INVALID_PINNED 17
+#define BG_ERROR_MOVE_INVALID_HEAD 18
+#define BG_ERROR_MOVE_FIRST 19
+#define BG_ERROR_ROLL_FIRST 20
+#define BG_ERROR_DICE_INVALID 21
+#define BG_ERROR_COMMITMENT_INVALID 22
+#define BG_ERROR_DATABASE_INVALID 23
+#define BG_ERROR_SCORE_INVALID 24
+#define BG_ERROR_NOTATION_INVALID 25
+#define BG_ERROR_FORMAT_INVALID 26
+#define BG_ERROR_UNDO_INVALID 27
+#define BG_ERROR_REDO_INVALID 28
+#define BG_ERROR_VARIANT_INVALID 29
+#define BG_ERROR_POSITION_INVALID 30
+#define BG_ERROR_TIME_EXPIRED 31
+#define BG_ERROR_MOVE_INVALID_CHECKER 32
+#define BG_ERROR_MOVE_INVALID_DICE 33
+#define BG_ERROR_PAIRING_INVALID 34
+#define BG_ERROR_MATCH_NOT_ENDED 35
+
+/* A game, owned by the library */
+typedef struct bg_game bg_game;
+
+/* Creates a new game with the default rules */
+bg_game *bg_game_new(void);
+
+/* Creates a game from an XGID, or returns NULL if the XGID is invalid */
+bg_game *bg_game_from_xgid(const char *xgid);
+
+/* Releases a game */
+void bg_game_free(bg_game *game);
+
+/* Releases a string returned by the library */
+void bg_string_free(char *s);
+
+/* Returns the player whose turn it is, 0 or 1, or -1 before the opening roll */
+int bg_game_turn(const bg_game *game);
+
+/* Rolls the dices and writes their two values to dices unless it is NULL */
+int bg_game_roll(bg_game *game, uint8_t *dices);
+
+/* Sets the dices to the given values instead of rolling them */
+int bg_game_roll_with(bg_game *game, uint8_t a, uint8_t b);
+
+/* Returns the distinct legal plays in the conventional notation, one per line */
+char *bg_game_legal_moves(const bg_game *game);
+
+/* Plays the dices rolled, e.g. "24/18 13/9", only if the whole play is legal */
+int bg_game_apply(bg_game *game, const char *play);
+
+/* Offers the cube on behalf of the player whose turn it is */
+int bg_game_double(bg_game *game);
+
+/* Takes the cube offered by the player whose turn it is */
+int bg_game_take(bg_game *game);
+
+/* Passes the cube offered by the player whose turn it is, which ends the game */
+int bg_game_pass(bg_game *game);
+
+/* Returns the points won once the game has ended and writes the winner to
+ * winner unless it is NULL, or returns 0 while the game is running */
+uint64_t bg_game_result(const bg_game *game, int *winner);
+
+/* Returns the position as an XGID */
+char *bg_game_xgid(const bg_game *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BACKGAMMON_H */
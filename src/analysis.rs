@@ -0,0 +1,788 @@
+//! # Analyze Backgammon Positions
+//!
+//! Tactical metrics of positions, which are useful on their own and as features of heuristics
+//! ranking plays, and the analysis of the luck and the errors of recorded games.
+use crate::cubeful::{cube_decision, cubeful_equity, CubeContext, CubeDecision};
+use crate::eval::Evaluator;
+use crate::game::Game;
+use crate::met::Met;
+use crate::r#match::Match;
+use crate::record::{Event, GameRecord};
+use crate::rules::{Board, Cube, DiceRoll, Field, Player};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+/// Represents the shots at a single blot, i.e. the number of the 36 rolls with which the opponent
+/// can hit it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Shots {
+    /// The field of the blot, from the perspective of its owner
+    pub field: usize,
+    /// The number of the 36 rolls hitting the blot
+    pub rolls: u8,
+}
+
+impl Shots {
+    /// Returns the probability that the blot is hit
+    pub fn probability(&self) -> f64 {
+        self.rolls as f64 / 36.0
+    }
+}
+
+/// Represents the exposure of all blots of a player to the next roll of the opponent
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Exposure {
+    /// The shots at each blot, ordered by field
+    pub blots: Vec<Shots>,
+    /// The number of the 36 rolls hitting at least one blot
+    pub rolls: u8,
+}
+
+impl Exposure {
+    /// Returns the probability that at least one blot is hit
+    pub fn probability(&self) -> f64 {
+        self.rolls as f64 / 36.0
+    }
+}
+
+impl fmt::Display for Exposure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for blot in &self.blots {
+            writeln!(
+                f,
+                "Blot on point {}: {}/36 ({:.1}%)",
+                blot.field + 1,
+                blot.rolls,
+                100.0 * blot.probability()
+            )?;
+        }
+        write!(
+            f,
+            "Total: {}/36 ({:.1}%)",
+            self.rolls,
+            100.0 * self.probability()
+        )
+    }
+}
+
+/// Returns the blots of a player which the opponent hits with a legal play of the given roll
+fn hit_blots(board: &Board, player: Player, roll: DiceRoll) -> BTreeSet<usize> {
+    board
+        .plays(player.other(), &roll.dies())
+        .iter()
+        .flatten()
+        .flat_map(|m| m.hits.iter())
+        .filter_map(|field| match field {
+            Field::Point(f) => Some(23 - f),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Counts the shots the opponent has at the blots of a player.
+///
+/// All legal plays of each roll are considered, hence direct and indirect shots, combination
+/// shots, and hits while entering from the bar are counted, while hits which are only possible
+/// with an illegal play are not.
+/// ```
+/// use backgammon::analysis::exposure;
+/// use backgammon::rules::{Board, Player};
+///
+/// // no blots at the start of a game
+/// let exposure = exposure(&Board::new(), Player::Player0)?;
+/// assert!(exposure.blots.is_empty());
+/// assert_eq!(exposure.probability(), 0.0);
+/// # Ok::<(), backgammon::Error>(())
+/// ```
+pub fn exposure(board: &Board, player: Player) -> Result<Exposure, Error> {
+    if player == Player::Nobody {
+        return Err(Error::PlayerInvalid);
+    }
+    let mut blots = Vec::new();
+    for field in 0..24 {
+        if board.get_checkers(player, Field::Point(field))? == 1 {
+            blots.push(Shots { field, rolls: 0 });
+        }
+    }
+
+    let mut rolls = 0;
+    for (roll, _) in DiceRoll::all_distinct() {
+        let weight = if roll.is_double() { 1 } else { 2 };
+        let hits = hit_blots(board, player, roll);
+        if !hits.is_empty() {
+            rolls += weight;
+        }
+        for blot in blots.iter_mut().filter(|b| hits.contains(&b.field)) {
+            blot.rolls += weight;
+        }
+    }
+    Ok(Exposure { blots, rolls })
+}
+
+/// Represents the luck of a single roll, i.e. how much better or worse the roll is for the player
+/// than the average roll in the same position. Equities are cubeless and per point of the cube.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RollLuck {
+    /// The player who rolled
+    pub player: Player,
+    /// The dice values rolled
+    pub dices: (u8, u8),
+    /// The equity after the best play of the roll
+    pub equity: f64,
+    /// The equity after the best play, averaged over all possible rolls
+    pub average: f64,
+}
+
+impl RollLuck {
+    /// Returns the luck of the roll, which is positive for rolls better than the average
+    pub fn luck(&self) -> f64 {
+        self.equity - self.average
+    }
+}
+
+impl fmt::Display for RollLuck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let roll = DiceRoll(self.dices.0, self.dices.1).normalized();
+        write!(
+            f,
+            "{} rolled {}-{}: {:+.3}",
+            self.player,
+            roll.0,
+            roll.1,
+            self.luck()
+        )
+    }
+}
+
+/// Represents the luck of all rolls of a game, see [`luck`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Luck {
+    /// The luck of each roll, in the order of the game
+    pub rolls: Vec<RollLuck>,
+}
+
+impl Luck {
+    /// Returns the total luck of a player, i.e. the sum of the luck of all rolls of the player
+    pub fn total(&self, player: Player) -> f64 {
+        self.rolls
+            .iter()
+            .filter(|r| r.player == player)
+            .map(RollLuck::luck)
+            .sum()
+    }
+
+    /// Returns the luck of a player relative to the opponent, which is positive if the player
+    /// rolled better
+    pub fn balance(&self, player: Player) -> f64 {
+        self.total(player) - self.total(player.other())
+    }
+}
+
+impl fmt::Display for Luck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for roll in &self.rolls {
+            writeln!(f, "{}", roll)?;
+        }
+        write!(
+            f,
+            "Total: {} {:+.3}, {} {:+.3}",
+            Player::Player0,
+            self.total(Player::Player0),
+            Player::Player1,
+            self.total(Player::Player1)
+        )
+    }
+}
+
+/// Returns the equity of a player after the best play of a roll
+fn roll_equity(
+    board: &Board,
+    player: Player,
+    roll: DiceRoll,
+    evaluator: &(impl Evaluator + ?Sized),
+) -> Result<f64, Error> {
+    let mut best = f64::NEG_INFINITY;
+    for play in board.plays(player, &roll.dies()) {
+        let mut after = board.clone();
+        for m in &play {
+            after.apply(player, m)?;
+        }
+        let equity = evaluator
+            .evaluate(&after, player.other())?
+            .inverted()
+            .equity();
+        best = best.max(equity);
+    }
+    Ok(best)
+}
+
+/// Analyzes the luck of every roll of a recorded game.
+///
+/// The luck of a roll is the equity after its best play minus the average of this equity over
+/// all rolls, as estimated by the evaluator. The opening roll, i.e. the first roll which is not a
+/// double before any checker has been moved, is compared to the rolls without doubles only.
+/// Equal opening rolls, which nobody plays, are skipped.
+/// ```
+/// use backgammon::analysis::luck;
+/// use backgammon::eval::Heuristic;
+/// use backgammon::Game;
+///
+/// let mut g = Game::new();
+/// g.roll_with((6, 5)).unwrap();
+/// let luck = luck(g.record(), &Heuristic)?;
+/// assert_eq!(luck.rolls.len(), 1);
+/// // 6-5 is one of the best opening rolls
+/// assert!(luck.rolls[0].luck() > 0.0);
+/// # Ok::<(), backgammon::Error>(())
+/// ```
+pub fn luck(record: &GameRecord, evaluator: &(impl Evaluator + ?Sized)) -> Result<Luck, Error> {
+    let mut rolls = Vec::new();
+    let mut moved = false;
+    let mut board = record.start().clone();
+    for position in record.replay()? {
+        match position.event {
+            Some(Event::Roll { player, dices }) if player != Player::Nobody => {
+                let rolled = DiceRoll(dices.0, dices.1);
+                let opening = !moved && !rolled.is_double();
+                let mut total = 0.0;
+                let mut weights = 0.0;
+                for (roll, probability) in DiceRoll::all_distinct() {
+                    if !(opening && roll.is_double()) {
+                        total += probability * roll_equity(&board, player, roll, evaluator)?;
+                        weights += probability;
+                    }
+                }
+                rolls.push(RollLuck {
+                    player,
+                    dices,
+                    equity: roll_equity(&board, player, rolled, evaluator)?,
+                    average: total / weights,
+                });
+            }
+            Some(Event::Move { .. }) => moved = true,
+            _ => {}
+        }
+        board = position.board;
+    }
+    Ok(Luck { rolls })
+}
+
+/// Represents the errors of a player, i.e. the equity lost by the decisions made compared to the
+/// best decisions according to an evaluator, in normalized cubeful equity per point of the cube
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ErrorRate {
+    /// The number of checker plays with more than one distinct legal play
+    pub checker_decisions: u32,
+    /// The equity lost by checker plays
+    pub checker_error: f64,
+    /// The number of cube decisions with a real choice: doubles, takes, passes, and missed
+    /// doubles
+    pub cube_decisions: u32,
+    /// The equity lost by cube decisions
+    pub cube_error: f64,
+}
+
+impl ErrorRate {
+    /// Returns the number of decisions
+    pub fn decisions(&self) -> u32 {
+        self.checker_decisions + self.cube_decisions
+    }
+
+    /// Returns the equity lost by all decisions
+    pub fn error(&self) -> f64 {
+        self.checker_error + self.cube_error
+    }
+
+    /// Returns the performance rating, i.e. the average equity lost per decision times 500,
+    /// which is zero without decisions. Lower is better: world class players achieve less than
+    /// 5.
+    pub fn pr(&self) -> f64 {
+        match self.decisions() {
+            0 => 0.0,
+            decisions => 500.0 * self.error() / f64::from(decisions),
+        }
+    }
+
+    /// Adds the errors of other decisions
+    fn add(&mut self, other: &ErrorRate) {
+        self.checker_decisions += other.checker_decisions;
+        self.checker_error += other.checker_error;
+        self.cube_decisions += other.cube_decisions;
+        self.cube_error += other.cube_error;
+    }
+}
+
+impl fmt::Display for ErrorRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Checker: {} decisions, {:.3} lost; Cube: {} decisions, {:.3} lost; PR {:.1}",
+            self.checker_decisions,
+            self.checker_error,
+            self.cube_decisions,
+            self.cube_error,
+            self.pr()
+        )
+    }
+}
+
+/// Represents the errors of both players in a game or a match, see [`error_rates`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ErrorRates {
+    /// The errors of player 0
+    pub player0: ErrorRate,
+    /// The errors of player 1
+    pub player1: ErrorRate,
+}
+
+impl ErrorRates {
+    /// Returns the errors of a player
+    pub fn player(&self, player: Player) -> Result<&ErrorRate, Error> {
+        match player {
+            Player::Player0 => Ok(&self.player0),
+            Player::Player1 => Ok(&self.player1),
+            Player::Nobody => Err(Error::PlayerInvalid),
+        }
+    }
+
+    /// Adds the errors of a decision of a player
+    fn add(&mut self, player: Player, errors: &ErrorRate) {
+        match player {
+            Player::Player0 => self.player0.add(errors),
+            Player::Player1 => self.player1.add(errors),
+            Player::Nobody => {}
+        }
+    }
+}
+
+impl fmt::Display for ErrorRates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: {}", Player::Player0, self.player0)?;
+        write!(f, "{}: {}", Player::Player1, self.player1)
+    }
+}
+
+/// Represents the errors of both players in each game of a match and in the whole match, see
+/// [`match_error_rates`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MatchErrorRates {
+    /// The errors in each game
+    pub games: Vec<ErrorRates>,
+    /// The errors in all games
+    pub total: ErrorRates,
+}
+
+/// A checker play whose moves are still being collected
+struct Turn {
+    player: Player,
+    roll: DiceRoll,
+    before: Board,
+    cube: Cube,
+}
+
+/// Analyzes the decisions of both players in a recorded game
+struct Analysis<'a, E: ?Sized> {
+    evaluator: &'a E,
+    met: &'a Met,
+    /// The cube contexts of player 0 and player 1 on roll, with the cube of the game start
+    contexts: [CubeContext; 2],
+    rates: ErrorRates,
+}
+
+impl<E: Evaluator + ?Sized> Analysis<'_, E> {
+    /// Returns the cube context of a player on roll with the given cube
+    fn context(&self, player: Player, cube: &Cube) -> CubeContext {
+        let mut context = match player {
+            Player::Player1 => self.contexts[1].clone(),
+            _ => self.contexts[0].clone(),
+        };
+        context.cube = cube.clone();
+        context
+    }
+
+    /// Returns the cube decision of a player on roll, none if the player cannot double
+    fn cube(
+        &self,
+        board: &Board,
+        player: Player,
+        cube: &Cube,
+    ) -> Result<Option<CubeDecision>, Error> {
+        let evaluation = self.evaluator.evaluate(board, player)?;
+        match cube_decision(&evaluation, &self.context(player, cube), self.met) {
+            Ok(decision) => Ok(Some(decision)),
+            Err(Error::DoublingNotPermitted) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Adds an error of a cube decision
+    fn cube_error(&mut self, player: Player, error: f64) {
+        let rate = ErrorRate {
+            cube_decisions: 1,
+            cube_error: error.max(0.0),
+            ..Default::default()
+        };
+        self.rates.add(player, &rate);
+    }
+
+    /// Adds the error of a checker play, given the board after the play
+    fn checker_error(&mut self, turn: &Turn, after: &Board) -> Result<(), Error> {
+        let mut boards = HashSet::new();
+        for play in turn.before.plays(turn.player, &turn.roll.dies()) {
+            let mut board = turn.before.clone();
+            if play.iter().all(|m| board.apply(turn.player, m).is_ok()) {
+                let _ = boards.insert(board);
+            }
+        }
+        // forced plays are no decisions, and unfinished plays cannot be judged
+        if boards.len() < 2 || !boards.contains(after) {
+            return Ok(());
+        }
+        let opponent = self.context(turn.player.other(), &turn.cube);
+        let equity = |board: &Board| {
+            let evaluation = self.evaluator.evaluate(board, turn.player.other())?;
+            Ok::<f64, Error>(-cubeful_equity(&evaluation, &opponent, self.met)?)
+        };
+        let mut best = f64::NEG_INFINITY;
+        for board in &boards {
+            best = best.max(equity(board)?);
+        }
+        let rate = ErrorRate {
+            checker_decisions: 1,
+            checker_error: (best - equity(after)?).max(0.0),
+            ..Default::default()
+        };
+        self.rates.add(turn.player, &rate);
+        Ok(())
+    }
+}
+
+/// Analyzes the checker plays and cube decisions of both players in a game, as judged by an
+/// evaluator.
+///
+/// The error of a checker play is the cubeful equity of the best play minus the one of the play
+/// made. Forced plays are not counted as decisions. The error of a cube decision is the equity
+/// lost against the proper cube action, see [`cube_decision`], and is counted for doubles, takes,
+/// and passes, and for rolls without a double when doubling was proper.
+/// ```
+/// use backgammon::analysis::error_rates;
+/// use backgammon::eval::Heuristic;
+/// use backgammon::met::Met;
+/// use backgammon::Game;
+///
+/// let mut g = Game::new();
+/// g.roll_with((3, 1)).unwrap();
+/// let (best, _) = g.best_move((3, 1), &Heuristic)?;
+/// for m in &best.moves {
+///     g.apply_move(m)?;
+/// }
+///
+/// let rates = error_rates(&g, &Heuristic, &Met::default())?;
+/// assert_eq!(rates.player0.checker_decisions, 1);
+/// assert_eq!(rates.player0.pr(), 0.0);
+/// # Ok::<(), backgammon::Error>(())
+/// ```
+pub fn error_rates(
+    game: &Game,
+    evaluator: &(impl Evaluator + ?Sized),
+    met: &Met,
+) -> Result<ErrorRates, Error> {
+    let context = |player: Player| {
+        let mut context = CubeContext::from_game(game);
+        context.player = player;
+        context.away = game.away().map(|(a, b)| match player {
+            Player::Player1 => (b, a),
+            _ => (a, b),
+        });
+        context
+    };
+    let mut analysis = Analysis {
+        evaluator,
+        met,
+        contexts: [context(Player::Player0), context(Player::Player1)],
+        rates: ErrorRates::default(),
+    };
+
+    let mut turn: Option<Turn> = None;
+    let mut offer: Option<CubeDecision> = None;
+    let mut moved = false;
+    let mut previous = None;
+    for position in game.record().replay()? {
+        let event = position.event.clone();
+        let Some(before) = previous.replace(position.clone()) else {
+            continue;
+        };
+        if !matches!(event, Some(Event::Move { .. })) {
+            if let Some(turn) = turn.take() {
+                analysis.checker_error(&turn, &before.board)?;
+            }
+        }
+        match event {
+            Some(Event::Roll { player, dices }) if player != Player::Nobody => {
+                let roll = DiceRoll(dices.0, dices.1);
+                // a roll without a double, unless the player just doubled
+                let doubled = matches!(
+                    before.event,
+                    Some(Event::CubeTake { .. } | Event::Beaver { .. } | Event::Raccoon { .. })
+                );
+                if moved && !doubled {
+                    if let Some(decision) = analysis.cube(&before.board, player, &before.cube)? {
+                        if decision.double() {
+                            analysis.cube_error(player, decision.equity() - decision.no_double);
+                        }
+                    }
+                }
+                turn = Some(Turn {
+                    player,
+                    roll,
+                    before: before.board.clone(),
+                    cube: before.cube.clone(),
+                });
+            }
+            Some(Event::Move { .. }) => moved = true,
+            Some(Event::CubeOffer { player, .. }) => {
+                offer = analysis.cube(&before.board, player, &before.cube)?;
+                if let Some(decision) = offer {
+                    let doubled = decision.double_take.min(decision.double_pass);
+                    analysis.cube_error(player, decision.equity() - doubled);
+                }
+            }
+            Some(Event::CubeTake { player } | Event::Beaver { player, .. }) => {
+                if let Some(decision) = offer.take() {
+                    analysis.cube_error(player, decision.double_take - decision.double_pass);
+                }
+            }
+            Some(Event::CubeDrop { player }) => {
+                if let Some(decision) = offer.take() {
+                    analysis.cube_error(player, decision.double_pass - decision.double_take);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let (Some(turn), Some(last)) = (turn, previous) {
+        analysis.checker_error(&turn, &last.board)?;
+    }
+    Ok(analysis.rates)
+}
+
+/// Analyzes the checker plays and cube decisions of both players in each game of a match and in
+/// the whole match, see [`error_rates`]
+pub fn match_error_rates(
+    m: &Match,
+    evaluator: &(impl Evaluator + ?Sized),
+    met: &Met,
+) -> Result<MatchErrorRates, Error> {
+    let mut rates = MatchErrorRates::default();
+    for game in &m.games {
+        let game = error_rates(game, evaluator, met)?;
+        rates.total.player0.add(&game.player0);
+        rates.total.player1.add(&game.player1);
+        rates.games.push(game);
+    }
+    Ok(rates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Point;
+
+    /// A board on which player 0 has blots on the given fields and the rest of the checkers on
+    /// the given home field, and player 1 has a single checker on the given field from the
+    /// perspective of player 0 and all other checkers borne off
+    fn blots(fields: &[usize], home: usize, shooter: usize) -> Result<Board, Error> {
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        for field in fields {
+            board.set(Player::Player0, *field, 1)?;
+        }
+        board.set(Player::Player0, home, 15 - fields.len() as i8)?;
+        board.points[shooter] = Point::new(Player::Player1, 1);
+        board.off[1] = 14;
+        Ok(board)
+    }
+
+    /// Returns the number of rolls hitting any blot of player 0
+    fn exposure_rolls(board: &Board) -> Result<u8, Error> {
+        Ok(exposure(board, Player::Player0)?.rolls)
+    }
+
+    #[test]
+    fn test_distance() -> Result<(), Error> {
+        // the well-known number of shots at a blot by its distance, without any blocking points
+        for (distance, shots) in [(1, 11), (2, 12), (3, 14), (4, 15), (5, 15), (6, 17), (7, 6)] {
+            let board = blots(&[4 + distance], 0, 4)?;
+            let exposure = exposure(&board, Player::Player0)?;
+            assert_eq!(exposure.blots[0].field, 4 + distance);
+            assert_eq!(exposure.blots[0].rolls, shots, "distance {}", distance);
+            assert_eq!(exposure.rolls, shots);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_combination() -> Result<(), Error> {
+        // 6-5 is the only shot from 11 pips away
+        let exposure = exposure(&blots(&[15], 0, 4)?, Player::Player0)?;
+        assert_eq!(exposure.rolls, 2);
+        // 6-6, 4-4, and 3-3 hit from 12 pips away, but not if the 6 point and the 4 point are
+        // blocked
+        let board = blots(&[16], 0, 4)?;
+        assert_eq!(exposure_rolls(&board)?, 3);
+        let mut board = blots(&[16], 0, 4)?;
+        board.set(Player::Player0, 0, -4)?;
+        board.set(Player::Player0, 10, 2)?;
+        board.set(Player::Player0, 8, 2)?;
+        assert_eq!(exposure_rolls(&board)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_several_blots() -> Result<(), Error> {
+        let exposure = exposure(&blots(&[8, 10], 0, 4)?, Player::Player0)?;
+        assert_eq!(exposure.blots.len(), 2);
+        assert_eq!(exposure.blots[0].rolls, 15);
+        assert_eq!(exposure.blots[1].rolls, 17);
+        // rolls hitting both blots are only counted once
+        assert!(exposure.rolls > 17 && exposure.rolls < 32);
+        assert!(exposure
+            .to_string()
+            .starts_with("Blot on point 9: 15/36 (41.7%)\nBlot on point 11: 17/36"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bar() -> Result<(), Error> {
+        // every roll with a 1 enters from the bar on the blot on the ace point, except 5-1: as
+        // the 6 point is blocked, only the larger 5 can be played
+        let mut board = blots(&[0], 5, 4)?;
+        board.points[4] = Point::EMPTY;
+        board.bar[1] = 1;
+        let exposure = exposure(&board, Player::Player0)?;
+        assert_eq!(exposure.rolls, 9);
+        assert!((exposure.probability() - 9.0 / 36.0).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_luck() -> Result<(), Error> {
+        use crate::eval::Heuristic;
+
+        // player 0 wins only with 3-3, 4-4, 5-5, and 6-6, otherwise player 1 bears off
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.set(Player::Player0, 5, 2)?;
+        board.set_off(Player::Player0, 13)?;
+        board.set(Player::Player1, 0, 1)?;
+        board.set_off(Player::Player1, 14)?;
+        let mut record = GameRecord::new(board);
+        record.push(Event::Roll {
+            player: Player::Player0,
+            dices: (6, 6),
+        });
+        let luck = luck(&record, &Heuristic)?;
+        assert_eq!(luck.rolls.len(), 1);
+        assert_eq!(luck.rolls[0].player, Player::Player0);
+        assert_eq!(luck.rolls[0].equity, 1.0);
+        assert!(luck.rolls[0].average < -0.7);
+        assert!(luck.total(Player::Player0) > 1.7);
+        assert_eq!(luck.total(Player::Player1), 0.0);
+        assert_eq!(luck.balance(Player::Player1), -luck.total(Player::Player0));
+        assert!(luck.to_string().starts_with("Player 0 rolled 6-6: +1.7"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_opening_luck() -> Result<(), Error> {
+        use crate::eval::Heuristic;
+        use crate::Game;
+
+        let mut g = Game::new();
+        let _ = g.roll_with((2, 1))?;
+        g.play_die(Field::Point(12), 2)?;
+        g.play_die(Field::Point(5), 1)?;
+        let _ = g.roll_with((1, 1))?;
+        let luck = luck(g.record(), &Heuristic)?;
+        assert_eq!(luck.rolls.len(), 2);
+        assert_eq!(luck.rolls[1].player, Player::Player1);
+
+        // the opening roll is compared to the rolls without doubles
+        let board = Board::new();
+        let (mut opening, mut all) = (0.0, 0.0);
+        for (roll, probability) in DiceRoll::all_distinct() {
+            let equity = roll_equity(&board, Player::Player0, roll, &Heuristic)?;
+            all += probability * equity;
+            if !roll.is_double() {
+                opening += probability * equity / (30.0 / 36.0);
+            }
+        }
+        assert!((luck.rolls[0].average - opening).abs() < 1e-9);
+        assert!(opening < all);
+        assert!(luck.rolls[0].luck() < 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_rates() -> Result<(), Error> {
+        use crate::eval::Heuristic;
+
+        // 24/21 24/23 is a poor opening 3-1
+        let mut g = Game::new();
+        let _ = g.roll_with((3, 1))?;
+        g.play_die(Field::Point(23), 3)?;
+        g.play_die(Field::Point(23), 1)?;
+        let _ = g.roll_with((6, 5))?;
+        let (best, _) = g.best_move((6, 5), &Heuristic)?;
+        for m in &best.moves {
+            g.apply_move(m)?;
+        }
+        // a premature double, which is taken
+        g.offer_double(Player::Player0)?;
+        g.take(Player::Player1)?;
+        let _ = g.roll_with((2, 1))?;
+
+        let rates = error_rates(&g, &Heuristic, &Met::default())?;
+        assert_eq!(rates.player0.checker_decisions, 1);
+        assert!(rates.player0.checker_error > 0.05);
+        assert_eq!(rates.player0.cube_decisions, 1);
+        assert!(rates.player0.cube_error > 0.0);
+        assert!(rates.player0.pr() > 0.0);
+        assert_eq!(rates.player1.checker_decisions, 1);
+        assert_eq!(rates.player1.checker_error, 0.0);
+        assert_eq!(rates.player1.cube_decisions, 1);
+        assert_eq!(rates.player1.cube_error, 0.0);
+        assert_eq!(rates.player(Player::Player1)?.pr(), 0.0);
+        assert!(rates.player(Player::Nobody).is_err());
+        assert!(rates
+            .to_string()
+            .starts_with("Player 0: Checker: 1 decisions"));
+
+        let mut m = Match::new();
+        m.games = vec![g.clone(), g];
+        let rates = match_error_rates(&m, &Heuristic, &Met::default())?;
+        assert_eq!(rates.games.len(), 2);
+        assert_eq!(rates.total.player0.decisions(), 4);
+        assert!((rates.total.player0.pr() - rates.games[0].player0.pr()).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            exposure(&Board::new(), Player::Nobody),
+            Err(Error::PlayerInvalid)
+        ));
+    }
+}
@@ -0,0 +1,631 @@
+//! # Bearoff Databases
+use crate::eval::Evaluation;
+use crate::rules::{Board, DiceRoll, Player};
+use crate::Error;
+use std::sync::OnceLock;
+
+/// The number of points of the home board
+const HOME: usize = 6;
+
+/// The number of checkers of a player
+const CHECKERS: u8 = 15;
+
+/// The two-sided database queried by the evaluators and rollouts, once installed
+static DATABASE: OnceLock<TwoSided> = OnceLock::new();
+
+/// Returns the installed two-sided bearoff database, if any
+pub fn database() -> Option<&'static TwoSided> {
+    DATABASE.get()
+}
+
+/// The average number of pips of a roll, counting doubles four times
+pub const PIPS_PER_ROLL: f64 = 49.0 / 6.0;
+
+/// The weights of the estimated wastage: a base, per checker on the ace, two, and three point,
+/// per empty point below the highest point of the home board, per checker stacked on a point
+/// beyond the third, and per checker not borne off, fitted to the standard one-sided database
+const WASTAGE: [f64; 7] = [8.2, 2.0, 1.25, 0.75, 0.17, 0.85, -0.43];
+
+/// Holds the checkers of a player on the points of the home board, starting with the ace point
+pub type Home = [u8; HOME];
+
+/// Returns the checkers of the player in the home board, if all other checkers are borne off
+/// ```
+/// use backgammon::bearoff::home_board;
+/// use backgammon::rules::{Board, Player};
+///
+/// let board = Board::new();
+/// assert_eq!(home_board(&board, Player::Player0), None);
+/// ```
+pub fn home_board(board: &Board, player: Player) -> Option<Home> {
+    let side = board.side(player).ok()?;
+    if side.bar > 0 || side.board[HOME..].iter().any(|&n| n > 0) {
+        return None;
+    }
+    let mut home = [0; HOME];
+    home.copy_from_slice(&side.board[..HOME]);
+    Some(home)
+}
+
+/// Returns the effective pip count of a player, i.e. the expected number of rolls to bear off all
+/// checkers times the average pips of a roll. If the installed database holds the position, the
+/// expected number of rolls is looked up. Otherwise, the effective pip count is estimated in the
+/// style of Walter Trice as the pip count plus the pips wasted, which are more with checkers on
+/// the low points, with stacks, and with gaps in the home board.
+/// ```
+/// use backgammon::bearoff::effective_pip_count;
+/// use backgammon::rules::{Board, Player};
+///
+/// let board = Board::new();
+/// let epc = effective_pip_count(&board, Player::Player0).unwrap();
+/// assert!(epc > 167.0 && epc < 180.0);
+/// ```
+pub fn effective_pip_count(board: &Board, player: Player) -> Result<f64, Error> {
+    let pips = f64::from(board.pip_count(player)?);
+    if let Some(epc) = home_board(board, player)
+        .and_then(|home| database()?.one_sided().effective_pip_count(&home))
+    {
+        return Ok(epc);
+    }
+
+    let side = board.side(player)?;
+    let checkers = side.board.iter().sum::<u8>() + side.bar;
+    if checkers == 0 {
+        return Ok(0.0);
+    }
+    let home = &side.board[..HOME];
+    let highest = home.iter().rposition(|&n| n > 0).unwrap_or(0);
+    let gaps = home[..highest].iter().filter(|&&n| n == 0).count();
+    let stacked: u8 = home.iter().map(|&n| n.saturating_sub(3)).sum();
+    let features = [
+        1.0,
+        f64::from(home[0]),
+        f64::from(home[1]),
+        f64::from(home[2]),
+        gaps as f64,
+        f64::from(stacked),
+        f64::from(checkers),
+    ];
+    let wastage: f64 = features.iter().zip(WASTAGE).map(|(f, w)| f * w).sum();
+    Ok(pips + wastage.max(0.0))
+}
+
+/// Returns the pips a player is expected to waste while bearing off, i.e. the effective pip count
+/// minus the pip count, see [`effective_pip_count`]. Of two plays leaving the same pip count,
+/// the one leaving less wastage bears off more efficiently.
+/// ```
+/// use backgammon::bearoff::wastage;
+/// use backgammon::rules::{Board, Player, Point};
+///
+/// // 6-2 with four checkers each on the 6, 5, and 4 point: after bearing off from the 6 point,
+/// // 4/2 wastes less than 6/4, which leaves a stack and more gaps
+/// let mut board = Board::new();
+/// board.points = [Point::EMPTY; 24];
+/// board.points[3..6].fill(Point::new(Player::Player0, 4));
+/// board.off = [3, 15];
+/// let mut filled = board.clone();
+/// filled.points[1] = Point::new(Player::Player0, 1);
+/// filled.points[3] = Point::new(Player::Player0, 3);
+/// filled.points[5] = Point::new(Player::Player0, 3);
+/// filled.off[0] = 4;
+/// let mut stacked = board.clone();
+/// stacked.points[3] = Point::new(Player::Player0, 5);
+/// stacked.points[5] = Point::new(Player::Player0, 2);
+/// stacked.off[0] = 4;
+/// assert_eq!(filled.pip_count(Player::Player0)?, stacked.pip_count(Player::Player0)?);
+/// assert!(wastage(&filled, Player::Player0)? < wastage(&stacked, Player::Player0)?);
+/// # Ok::<(), backgammon::Error>(())
+/// ```
+pub fn wastage(board: &Board, player: Player) -> Result<f64, Error> {
+    Ok(effective_pip_count(board, player)? - f64::from(board.pip_count(player)?))
+}
+
+/// Returns the binomial coefficient of n over k
+fn binomial(n: usize, k: usize) -> usize {
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+/// Returns the index of a position in a database of the given number of points. Positions with
+/// fewer checkers come first, so that the index does not depend on the number of checkers.
+fn index(home: &Home, points: usize) -> usize {
+    let mut position = 0;
+    home[..points].iter().enumerate().fold(0, |index, (i, &n)| {
+        position += n as usize;
+        let index = index + binomial(position, i + 1);
+        position += 1;
+        index
+    })
+}
+
+/// Returns the number of pips of a position
+fn pips(home: &Home) -> usize {
+    home.iter()
+        .enumerate()
+        .map(|(i, &n)| (i + 1) * n as usize)
+        .sum()
+}
+
+/// Returns all positions with checkers on the given number of points and at most the given number
+/// of checkers
+fn positions(points: usize, checkers: u8) -> Vec<Home> {
+    let mut positions = vec![[0; HOME]];
+    for point in 0..points {
+        positions = positions
+            .into_iter()
+            .flat_map(|home| {
+                let left = checkers - home.iter().sum::<u8>();
+                (0..=left).map(move |n| {
+                    let mut home = home;
+                    home[point] = n;
+                    home
+                })
+            })
+            .collect();
+    }
+    positions
+}
+
+/// Returns the positions after playing one die
+fn step(home: &Home, die: u8) -> Vec<Home> {
+    let die = die as usize;
+    let Some(highest) = home.iter().rposition(|&n| n > 0) else {
+        return vec![*home];
+    };
+    (0..=highest)
+        .filter(|&point| home[point] > 0)
+        .filter_map(|point| {
+            let mut next = *home;
+            next[point] -= 1;
+            if point >= die {
+                next[point - die] += 1;
+                Some(next)
+            } else if point + 1 == die || point == highest {
+                Some(next)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns all positions after playing a roll
+fn plays(home: &Home, roll: DiceRoll) -> Vec<Home> {
+    let mut dies = roll.dies();
+    let mut orders = vec![dies.clone()];
+    if !roll.is_double() {
+        dies.reverse();
+        orders.push(dies);
+    }
+    let mut plays = orders
+        .into_iter()
+        .flat_map(|dies| {
+            dies.iter().fold(vec![*home], |positions, &die| {
+                let mut next: Vec<Home> = positions.iter().flat_map(|h| step(h, die)).collect();
+                next.sort_unstable();
+                next.dedup();
+                next
+            })
+        })
+        .collect::<Vec<_>>();
+    plays.sort_unstable();
+    plays.dedup();
+    plays
+}
+
+/// Holds the one-sided bearoff database, i.e. for every position of a player in the home board,
+/// the probabilities to bear off all checkers in a given number of rolls, if the player always
+/// plays to minimize the expected number of rolls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OneSided {
+    /// The number of points of the home board covered, starting with the ace point
+    points: u8,
+    /// The largest number of checkers covered
+    checkers: u8,
+    /// The probabilities to bear off in exactly 0, 1, 2,... rolls, for every position by index
+    distributions: Vec<Vec<f64>>,
+}
+
+impl OneSided {
+    /// Generate the database of all positions with up to the given number of checkers on the
+    /// given number of points, at most 6 points and 15 checkers.
+    /// ```
+    /// use backgammon::bearoff::OneSided;
+    ///
+    /// let db = OneSided::new(3, 4).unwrap();
+    /// assert_eq!(db.positions(), 35);
+    /// // one checker on the ace point is borne off with any roll
+    /// assert_eq!(db.distribution(&[1, 0, 0, 0, 0, 0]).unwrap().len(), 2);
+    /// assert!((db.expected_rolls(&[1, 0, 0, 0, 0, 0]).unwrap() - 1.0).abs() < 1e-9);
+    /// assert_eq!(db.expected_rolls(&[0, 0, 0, 5, 0, 0]), None);
+    /// ```
+    pub fn new(points: u8, checkers: u8) -> Result<Self, Error> {
+        if !(1..=HOME as u8).contains(&points) || !(1..=CHECKERS).contains(&checkers) {
+            return Err(Error::DatabaseInvalid);
+        }
+
+        let mut positions = positions(points as usize, checkers);
+        positions.sort_by_key(pips);
+        let mut distributions = vec![Vec::new(); positions.len()];
+        let mut means = vec![0.0_f64; positions.len()];
+        for home in positions {
+            let mut distribution = vec![0.0];
+            if home == [0; HOME] {
+                distribution[0] = 1.0;
+            }
+            for (roll, probability) in DiceRoll::all_distinct().filter(|_| home != [0; HOME]) {
+                // every play bears off or moves closer to the ace point, so the positions after
+                // the roll have fewer pips and are known already
+                let best = plays(&home, roll)
+                    .iter()
+                    .map(|home| index(home, points as usize))
+                    .min_by(|&a, &b| means[a].total_cmp(&means[b]))
+                    .unwrap_or_default();
+                let next: &Vec<f64> = &distributions[best];
+                if distribution.len() <= next.len() {
+                    distribution.resize(next.len() + 1, 0.0);
+                }
+                for (rolls, p) in next.iter().enumerate() {
+                    distribution[rolls + 1] += probability * p;
+                }
+            }
+            let i = index(&home, points as usize);
+            means[i] = mean(&distribution);
+            distributions[i] = distribution;
+        }
+
+        Ok(OneSided {
+            points,
+            checkers,
+            distributions,
+        })
+    }
+
+    /// Generate the standard database of up to 15 checkers on 6 points, which holds 54264
+    /// positions and takes a moment to compute
+    pub fn standard() -> Self {
+        OneSided::new(HOME as u8, CHECKERS).expect("the standard bearoff database limits are valid")
+    }
+
+    /// Returns the number of points covered, starting with the ace point
+    pub fn points(&self) -> u8 {
+        self.points
+    }
+
+    /// Returns the largest number of checkers covered
+    pub fn checkers(&self) -> u8 {
+        self.checkers
+    }
+
+    /// Returns the number of positions in the database
+    pub fn positions(&self) -> usize {
+        self.distributions.len()
+    }
+
+    /// Returns true if the database holds the position
+    pub fn contains(&self, home: &Home) -> bool {
+        home[self.points as usize..].iter().all(|&n| n == 0)
+            && home.iter().map(|&n| n as usize).sum::<usize>() <= self.checkers as usize
+    }
+
+    /// Returns the probabilities to bear off all checkers in exactly 0, 1, 2,... rolls, if the
+    /// database holds the position
+    pub fn distribution(&self, home: &Home) -> Option<&[f64]> {
+        match self.contains(home) {
+            true => Some(&self.distributions[index(home, self.points as usize)]),
+            false => None,
+        }
+    }
+
+    /// Returns the expected number of rolls to bear off all checkers, if the database holds the
+    /// position
+    pub fn expected_rolls(&self, home: &Home) -> Option<f64> {
+        self.distribution(home).map(mean)
+    }
+
+    /// Returns the expected number of rolls to bear off all checkers times the average pips of a
+    /// roll, if the database holds the position
+    pub fn effective_pip_count(&self, home: &Home) -> Option<f64> {
+        self.expected_rolls(home).map(|rolls| rolls * PIPS_PER_ROLL)
+    }
+}
+
+/// Holds the two-sided bearoff database, i.e. for every pair of positions of the one-sided
+/// database, the exact probability of the player on roll to win the race, if both players always
+/// play to maximize their probability to win.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwoSided {
+    /// The one-sided database of the same limits
+    one_sided: OneSided,
+    /// The probabilities to win, by the index of the player on roll times the number of positions
+    /// plus the index of the opponent
+    wins: Vec<f64>,
+}
+
+impl TwoSided {
+    /// Generate the database of all pairs of positions with up to the given number of checkers on
+    /// the given number of points. The number of pairs grows fast, 6 points and 6 checkers already
+    /// make 853776 pairs.
+    /// ```
+    /// use backgammon::bearoff::TwoSided;
+    ///
+    /// let db = TwoSided::new(6, 2).unwrap();
+    /// // the player on roll bears off a checker on the ace point at once
+    /// let win = db.win(&[1, 0, 0, 0, 0, 0], &[1, 0, 0, 0, 0, 0]).unwrap();
+    /// assert!((win - 1.0).abs() < 1e-9);
+    /// assert_eq!(db.win(&[3, 0, 0, 0, 0, 0], &[1, 0, 0, 0, 0, 0]), None);
+    /// ```
+    pub fn new(points: u8, checkers: u8) -> Result<Self, Error> {
+        let one_sided = OneSided::new(points, checkers)?;
+        let n = one_sided.positions();
+        let points = points as usize;
+
+        let mut homes = vec![[0; HOME]; n];
+        for home in positions(points, checkers) {
+            homes[index(&home, points)] = home;
+        }
+        let rolls: Vec<(DiceRoll, f64)> = DiceRoll::all_distinct().collect();
+        // the indexes of the positions after every roll, by position
+        let successors: Vec<Vec<Vec<usize>>> = homes
+            .iter()
+            .map(|home| {
+                rolls
+                    .iter()
+                    .map(|&(roll, _)| plays(home, roll).iter().map(|h| index(h, points)).collect())
+                    .collect()
+            })
+            .collect();
+        let mut by_pips = vec![Vec::new(); points * checkers as usize + 1];
+        for (i, home) in homes.iter().enumerate() {
+            by_pips[pips(home)].push(i);
+        }
+
+        // after every roll, the pips of both players together decrease, so the pairs are known
+        // already
+        let mut wins = vec![0.0_f64; n * n];
+        for total in 0..2 * by_pips.len() {
+            for own in total.saturating_sub(by_pips.len() - 1)..=total.min(by_pips.len() - 1) {
+                for &a in &by_pips[own] {
+                    for &b in &by_pips[total - own] {
+                        wins[a * n + b] = match (a, b) {
+                            (0, _) => 1.0,
+                            (_, 0) => 0.0,
+                            _ => successors[a]
+                                .iter()
+                                .zip(&rolls)
+                                .map(|(next, (_, probability))| {
+                                    probability
+                                        * next
+                                            .iter()
+                                            .map(|&s| match s {
+                                                0 => 1.0,
+                                                _ => 1.0 - wins[b * n + s],
+                                            })
+                                            .fold(0.0, f64::max)
+                                })
+                                .sum(),
+                        };
+                    }
+                }
+            }
+        }
+
+        Ok(TwoSided { one_sided, wins })
+    }
+
+    /// Returns the one-sided database of the same limits
+    pub fn one_sided(&self) -> &OneSided {
+        &self.one_sided
+    }
+
+    /// Returns the probability of the player on roll to win, if the database holds both positions
+    pub fn win(&self, home: &Home, opponent: &Home) -> Option<f64> {
+        if !self.one_sided.contains(home) || !self.one_sided.contains(opponent) {
+            return None;
+        }
+        let points = self.one_sided.points as usize;
+        let n = self.one_sided.positions();
+        Some(self.wins[index(home, points) * n + index(opponent, points)])
+    }
+
+    /// Returns the evaluation for the player on roll, if both players have all checkers in the
+    /// home board, have borne off at least one checker so that no gammon is possible anymore,
+    /// and the database holds both positions.
+    pub fn evaluate(&self, board: &Board, player: Player) -> Option<Evaluation> {
+        let home = home_board(board, player)?;
+        let opponent = home_board(board, player.other())?;
+        if board.off.contains(&0) {
+            return None;
+        }
+        Some(Evaluation {
+            win: self.win(&home, &opponent)?,
+            ..Default::default()
+        })
+    }
+
+    /// Install the database, so that the evaluators and the truncation of rollouts use it for the
+    /// positions it holds. Only one database can be installed.
+    /// ```
+    /// use backgammon::bearoff::{database, TwoSided};
+    ///
+    /// let _ = TwoSided::new(6, 2).unwrap().install();
+    /// assert!(database().is_some());
+    /// ```
+    pub fn install(self) -> Result<(), Error> {
+        DATABASE.set(self).map_err(|_| Error::DatabaseInvalid)
+    }
+}
+
+/// Returns the expected number of rolls of a distribution
+fn mean(distribution: &[f64]) -> f64 {
+    distribution
+        .iter()
+        .enumerate()
+        .map(|(rolls, p)| rolls as f64 * p)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{Evaluator, Heuristic};
+    use crate::rollout::{rollout, RolloutSettings};
+    use crate::rules::Point;
+    use crate::Game;
+
+    #[test]
+    fn test_index() {
+        let mut positions = positions(HOME, 3);
+        assert_eq!(positions.len(), binomial(9, 6));
+        positions.sort_by_key(|home| index(home, HOME));
+        for (i, home) in positions.iter().enumerate() {
+            assert_eq!(index(home, HOME), i);
+        }
+        assert_eq!(index(&[0; HOME], HOME), 0);
+    }
+
+    #[test]
+    fn test_plays() {
+        // with 6-1 the six bears off, and the one moves either checker down
+        assert_eq!(
+            plays(&[0, 0, 0, 0, 1, 1], DiceRoll(6, 1)),
+            vec![[0, 0, 0, 0, 1, 0], [0, 0, 0, 1, 0, 0]]
+        );
+        // a die larger than the highest point bears off from the highest point
+        assert_eq!(plays(&[1, 1, 0, 0, 0, 0], DiceRoll(6, 5)), vec![[0; HOME]]);
+        assert_eq!(plays(&[2, 0, 0, 0, 0, 0], DiceRoll(2, 2)), vec![[0; HOME]]);
+    }
+
+    #[test]
+    fn test_one_sided() -> Result<(), Error> {
+        let db = OneSided::new(6, 3)?;
+        assert_eq!(db.positions(), 84);
+        assert_eq!(db.points(), 6);
+        assert_eq!(db.checkers(), 3);
+        assert_eq!(db.distribution(&[0; HOME]), Some(&[1.0][..]));
+
+        // a checker on the six point is borne off at once with a six, a sum of at least six, or
+        // 2-2, and with any roll afterwards
+        let six = db.distribution(&[0, 0, 0, 0, 0, 1]).unwrap_or_default();
+        assert_eq!(six.len(), 3);
+        assert!((six[1] - 0.75).abs() < 1e-9);
+        assert!((db.expected_rolls(&[0, 0, 0, 0, 0, 1]).unwrap_or_default() - 1.25).abs() < 1e-9);
+
+        for home in positions(6, 3) {
+            let distribution = db.distribution(&home).unwrap_or_default();
+            assert!((distribution.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+        // more checkers take longer
+        assert!(db.expected_rolls(&[3, 0, 0, 0, 0, 0]) > db.expected_rolls(&[2, 0, 0, 0, 0, 0]));
+
+        assert!(!db.contains(&[4, 0, 0, 0, 0, 0]));
+        assert_eq!(db.expected_rolls(&[4, 0, 0, 0, 0, 0]), None);
+        assert!(matches!(OneSided::new(7, 3), Err(Error::DatabaseInvalid)));
+        assert!(matches!(OneSided::new(6, 16), Err(Error::DatabaseInvalid)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_two_sided() -> Result<(), Error> {
+        let db = TwoSided::new(6, 2)?;
+        assert_eq!(db.one_sided().positions(), 28);
+        let (ace, six) = ([1, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 1]);
+        assert!((db.win(&six, &ace).unwrap_or_default() - 0.75).abs() < 1e-9);
+        // if the player on roll misses, the opponent misses as well with a quarter
+        let win = db.win(&six, &six).unwrap_or_default();
+        assert!((win - (0.75 + 0.25 * 0.25)).abs() < 1e-9);
+        assert_eq!(db.win(&[0; HOME], &six), Some(1.0));
+        assert_eq!(db.win(&six, &[0; HOME]), Some(0.0));
+
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.set(Player::Player0, 5, 1)?;
+        board.set_off(Player::Player0, 14)?;
+        board.set(Player::Player1, 0, 1)?;
+        // player 1 has not borne off any checker and could still lose a gammon
+        assert_eq!(db.evaluate(&board, Player::Player0), None);
+        board.set(Player::Player1, 0, -1)?;
+        board.set(Player::Player1, 5, 1)?;
+        board.set_off(Player::Player1, 14)?;
+        let evaluation = db.evaluate(&board, Player::Player1).unwrap_or_default();
+        assert!((evaluation.win - win).abs() < 1e-9);
+        assert_eq!(evaluation.win_gammon, 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_install() -> Result<(), Error> {
+        let _ = TwoSided::new(6, 2)?.install();
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.set(Player::Player0, 5, 1)?;
+        board.set_off(Player::Player0, 14)?;
+        board.set(Player::Player1, 0, 1)?;
+        board.set_off(Player::Player1, 14)?;
+        let evaluation = Heuristic.evaluate(&board, Player::Player0)?;
+        assert!((evaluation.win - 0.75).abs() < 1e-9);
+
+        // the rollout is truncated at once and looks the position up
+        let mut g = Game::new();
+        g.board = board;
+        g.who_plays = Player::Player0;
+        let settings = RolloutSettings::default().with_trials(2).with_truncation(0);
+        let result = rollout(&g, &Heuristic, &settings)?;
+        assert!((result.evaluation.win - 0.75).abs() < 1e-9);
+        assert!(matches!(
+            TwoSided::new(6, 1)?.install(),
+            Err(Error::DatabaseInvalid)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_effective_pip_count() -> Result<(), Error> {
+        let db = OneSided::new(6, 3)?;
+        let epc = db
+            .effective_pip_count(&[1, 0, 0, 0, 0, 0])
+            .unwrap_or_default();
+        assert!((epc - PIPS_PER_ROLL).abs() < 1e-9);
+
+        // 15 checkers spread over the home board waste about 13 pips
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        for (field, count) in [3, 3, 3, 2, 2, 2].into_iter().enumerate() {
+            board.points[field] = Point::new(Player::Player0, count);
+        }
+        let epc = effective_pip_count(&board, Player::Player0)?;
+        assert!((epc - 48.0 - 13.7).abs() < 1.0);
+        // stacks and checkers on low points waste more
+        board.points[..6].fill(Point::EMPTY);
+        board.points[0] = Point::new(Player::Player0, 8);
+        board.points[1] = Point::new(Player::Player0, 7);
+        let stacked = effective_pip_count(&board, Player::Player0)?;
+        assert!(stacked - 22.0 > epc - 48.0);
+
+        assert!((wastage(&board, Player::Player0)? - (stacked - 22.0)).abs() < 1e-9);
+
+        board.points = [Point::EMPTY; 24];
+        assert_eq!(effective_pip_count(&board, Player::Player0)?, 0.0);
+        assert_eq!(wastage(&board, Player::Player0)?, 0.0);
+        assert!(wastage(&board, Player::Nobody).is_err());
+        assert!(effective_pip_count(&board, Player::Nobody).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_home_board() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.points = board.points.map(|p| match p.owner {
+            Player::Player0 => Point::EMPTY,
+            _ => p,
+        });
+        board.set(Player::Player0, 2, 3)?;
+        board.set_off(Player::Player0, 12)?;
+        assert_eq!(
+            home_board(&board, Player::Player0),
+            Some([0, 0, 3, 0, 0, 0])
+        );
+        assert_eq!(home_board(&board, Player::Player1), None);
+        assert_eq!(home_board(&board, Player::Nobody), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+//! # Opening Book
+//!
+//! The plays of the 15 opening rolls have been rolled out extensively, and the best plays are
+//! well established. The book holds them together with their equities, which are rounded
+//! cubeless money equities of published rollouts. Where several plays are practically equal,
+//! all of them are listed, best first.
+//!
+//! [`Game::best_move`](crate::Game::best_move) plays the first move of a game from the book, so
+//! that even a weak evaluator never suggests a bad opening play.
+//! ```
+//! use backgammon::book::opening;
+//! use backgammon::rules::DiceRoll;
+//!
+//! let plays = opening(DiceRoll(3, 1));
+//! assert_eq!(plays[0].0.to_string(), "8/5 6/5");
+//! assert!(plays[0].1 > 0.1);
+//! // doubles cannot be rolled at the opening
+//! assert!(opening(DiceRoll(4, 4)).is_empty());
+//! ```
+use crate::rules::{DiceRoll, Play};
+use alloc::vec::Vec;
+
+/// The plays of an opening roll in the conventional notation, with their equities
+type Entry = ((u8, u8), &'static [(&'static str, f64)]);
+
+/// The best plays and their equities for each opening roll, with the larger value first
+const BOOK: [Entry; 15] = [
+    ((2, 1), &[("13/11 6/5", -0.005), ("24/23 13/11", -0.010)]),
+    ((3, 1), &[("8/5 6/5", 0.166)]),
+    ((3, 2), &[("24/21 13/11", -0.004), ("13/10 13/11", -0.011)]),
+    ((4, 1), &[("24/23 13/9", -0.013)]),
+    ((4, 2), &[("8/4 6/4", 0.127)]),
+    ((4, 3), &[("24/20 13/10", -0.007), ("13/9 13/10", -0.010)]),
+    ((5, 1), &[("24/23 13/8", -0.020), ("13/8 6/5", -0.026)]),
+    ((5, 2), &[("13/8 13/11", -0.014)]),
+    ((5, 3), &[("8/3 6/3", 0.078)]),
+    ((5, 4), &[("24/20 13/8", -0.015), ("13/8 13/9", -0.018)]),
+    ((6, 1), &[("13/7 8/7", 0.133)]),
+    ((6, 2), &[("24/18 13/11", -0.010)]),
+    ((6, 3), &[("24/18 13/10", -0.004)]),
+    (
+        (6, 4),
+        &[
+            ("24/18 13/9", 0.002),
+            ("8/2 6/2", 0.001),
+            ("24/18 18/14", -0.001),
+        ],
+    ),
+    ((6, 5), &[("24/18 18/13", 0.058)]),
+];
+
+/// Returns the best plays of an opening roll together with their equities for the player on
+/// roll, best first, or none for doubles
+pub fn opening(roll: DiceRoll) -> Vec<(Play, f64)> {
+    let roll = roll.normalized();
+    BOOK.iter()
+        .filter(|(values, _)| *values == (roll.0, roll.1))
+        .flat_map(|(_, plays)| plays.iter())
+        .filter_map(|(play, equity)| Some((play.parse().ok()?, *equity)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Board, Player};
+
+    #[test]
+    fn test_book() {
+        // every play of the book is legal for both players
+        let board = Board::new();
+        for (roll, _) in DiceRoll::all_distinct().filter(|(r, _)| !r.is_double()) {
+            let plays = opening(roll);
+            assert!(!plays.is_empty(), "{:?}", roll);
+            assert!(plays.windows(2).all(|w| w[0].1 >= w[1].1));
+            for (play, _) in plays {
+                for player in [Player::Player0, Player::Player1] {
+                    let mut after = board.clone();
+                    for m in &play.moves {
+                        assert!(after.apply(player, m).is_ok(), "{}", play);
+                    }
+                    let steps: usize = play.moves.iter().map(|m| m.steps.len()).sum();
+                    assert_eq!(steps, 2);
+                }
+            }
+        }
+        assert_eq!(opening(DiceRoll(1, 2)), opening(DiceRoll(2, 1)));
+    }
+}
@@ -0,0 +1,274 @@
+//! # Cache Evaluations of Positions
+//!
+//! Rollouts and searches evaluate the same positions again and again, e.g. after different orders
+//! of the same checker moves. [`Cached`](crate::cache::Cached) wraps any evaluator and remembers
+//! its evaluations keyed by the [Zobrist hash](crate::zobrist) of the position and the player on
+//! roll.
+//!
+//! Like the cache of GNU Backgammon, the cache is a table of fixed size in which every position
+//! has a single slot, so a new evaluation replaces the one of another position sharing its slot.
+//! Hence the memory is bounded and no bookkeeping of the least recently used entries is needed.
+//! ```
+//! use backgammon::cache::Cached;
+//! use backgammon::eval::{Evaluator, Heuristic};
+//! use backgammon::rules::{Board, Player};
+//!
+//! let cached = Cached::new(Heuristic, 1024);
+//! let first = cached.evaluate(&Board::new(), Player::Player0)?;
+//! let second = cached.evaluate(&Board::new(), Player::Player0)?;
+//! assert_eq!(first, second);
+//! assert_eq!(cached.stats().hits, 1);
+//! assert_eq!(cached.stats().misses, 1);
+//! # Ok::<(), backgammon::Error>(())
+//! ```
+use crate::eval::{Evaluation, Evaluator};
+use crate::rules::{Board, Player};
+use crate::zobrist;
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// The number of independently locked parts of the table, so that threads evaluating different
+/// positions rarely wait for each other
+const SHARDS: usize = 16;
+
+/// Represents the statistics of the lookups in a cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CacheStats {
+    /// The number of evaluations found in the cache
+    pub hits: u64,
+    /// The number of evaluations not found in the cache
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Returns the number of lookups
+    pub fn lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// Returns the share of the lookups found in the cache, which is zero without lookups
+    pub fn hit_rate(&self) -> f64 {
+        match self.lookups() {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}%)",
+            self.hits,
+            self.misses,
+            100.0 * self.hit_rate()
+        )
+    }
+}
+
+/// A slot of the table, holding the key of the position and its evaluation
+type Slot = Option<(u64, Evaluation)>;
+
+/// An evaluator caching the evaluations of another evaluator. The cache can be shared between
+/// threads, e.g. by the parallel [rollouts](crate::rollout).
+#[derive(Debug)]
+pub struct Cached<E> {
+    evaluator: E,
+    shards: Vec<Mutex<Vec<Slot>>>,
+    slots: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<E: Evaluator> Cached<E> {
+    /// Wraps an evaluator with a cache of the given number of evaluations, which is rounded up to
+    /// a multiple of the number of internal shards. A capacity of zero disables the cache.
+    pub fn new(evaluator: E, capacity: usize) -> Self {
+        let slots = capacity.div_ceil(SHARDS);
+        let shards = match slots {
+            0 => Vec::new(),
+            _ => (0..SHARDS).map(|_| Mutex::new(vec![None; slots])).collect(),
+        };
+        Cached {
+            evaluator,
+            shards,
+            slots,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the wrapped evaluator
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    /// Returns the wrapped evaluator, dropping the cache
+    pub fn into_inner(self) -> E {
+        self.evaluator
+    }
+
+    /// Returns the number of evaluations the cache holds at most
+    pub fn capacity(&self) -> usize {
+        self.shards.len() * self.slots
+    }
+
+    /// Returns the number of evaluations currently in the cache
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|s| {
+                let shard = s.lock().unwrap_or_else(PoisonError::into_inner);
+                shard.iter().filter(|slot| slot.is_some()).count()
+            })
+            .sum()
+    }
+
+    /// Returns true if the cache holds no evaluations
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the statistics of the lookups since the creation or the last reset
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets the statistics of the lookups
+    pub fn reset_stats(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Removes all evaluations from the cache, which is needed if the wrapped evaluator changes
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .fill(None);
+        }
+    }
+
+    /// Returns the shard and the index of the slot of a key, or none if the cache is disabled
+    fn slot(&self, key: u64) -> Option<(&Mutex<Vec<Slot>>, usize)> {
+        let shard = self.shards.get(key as usize % SHARDS)?;
+        Some((shard, (key / SHARDS as u64) as usize % self.slots))
+    }
+}
+
+impl<E: Evaluator> Evaluator for Cached<E> {
+    fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+        let key = zobrist::hash_board(board) ^ zobrist::turn(player);
+        let Some((shard, index)) = self.slot(key) else {
+            return self.evaluator.evaluate(board, player);
+        };
+
+        let cached = shard.lock().unwrap_or_else(PoisonError::into_inner)[index];
+        if let Some((k, evaluation)) = cached {
+            if k == key {
+                let _ = self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(evaluation);
+            }
+        }
+        let _ = self.misses.fetch_add(1, Ordering::Relaxed);
+
+        // evaluate without holding the lock, errors are not cached
+        let evaluation = self.evaluator.evaluate(board, player)?;
+        shard.lock().unwrap_or_else(PoisonError::into_inner)[index] = Some((key, evaluation));
+        Ok(evaluation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Heuristic;
+    use crate::rules::Point;
+    use std::sync::atomic::AtomicUsize;
+
+    /// An evaluator counting its evaluations
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    impl Evaluator for Counting {
+        fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+            let _ = self.0.fetch_add(1, Ordering::Relaxed);
+            Heuristic.evaluate(board, player)
+        }
+    }
+
+    #[test]
+    fn test_cached() -> Result<(), Error> {
+        let cached = Cached::new(Counting::default(), 100);
+        assert_eq!(cached.capacity(), 112);
+        assert!(cached.is_empty());
+
+        let board = Board::new();
+        let evaluation = cached.evaluate(&board, Player::Player0)?;
+        assert_eq!(cached.evaluate(&board, Player::Player0)?, evaluation);
+        // the player on roll is part of the key
+        let other = cached.evaluate(&board, Player::Player1)?;
+        assert_eq!(other, Heuristic.evaluate(&board, Player::Player1)?);
+        assert_eq!(cached.evaluator().0.load(Ordering::Relaxed), 2);
+        assert_eq!(cached.len(), 2);
+        assert_eq!(cached.stats(), CacheStats { hits: 1, misses: 2 });
+        assert_eq!(cached.stats().to_string(), "1 hits, 2 misses (33.3%)");
+
+        cached.reset_stats();
+        cached.clear();
+        assert!(cached.is_empty());
+        assert_eq!(cached.stats().hit_rate(), 0.0);
+        let _ = cached.evaluate(&board, Player::Player0)?;
+        assert_eq!(cached.into_inner().0.load(Ordering::Relaxed), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounded() -> Result<(), Error> {
+        // more positions than slots replace each other
+        let cached = Cached::new(Heuristic, 16);
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.bar[1] = 15;
+        for field in 0..24 {
+            board.points[field] = Point::new(Player::Player0, 15);
+            let _ = cached.evaluate(&board, Player::Player0)?;
+            board.points[field] = Point::EMPTY;
+        }
+        assert!(cached.len() <= 16);
+        assert_eq!(cached.stats().misses, 24);
+
+        // a cache without capacity only forwards
+        let cached = Cached::new(Heuristic, 0);
+        assert_eq!(cached.capacity(), 0);
+        let _ = cached.evaluate(&Board::new(), Player::Player0)?;
+        assert!(cached.is_empty());
+        assert_eq!(cached.stats(), CacheStats::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_threads() -> Result<(), Error> {
+        let cached = Cached::new(Heuristic, 64);
+        let board = Board::new();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let _ = s.spawn(|| cached.evaluate(&board, Player::Player0));
+            }
+        });
+        assert_eq!(cached.stats().lookups(), 4);
+        assert!(cached.stats().hits <= 3);
+        Ok(())
+    }
+}
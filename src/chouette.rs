@@ -0,0 +1,413 @@
+use crate::game::{share, Game, GameResult, Phase, SharedDiceSource, Victory};
+use crate::rules::{Cube, DiceSource, GameRules, Player, Rules, Variant};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Represents a chouette, i.e. money games of three or more players
+///
+/// In each game, one player in the box plays against a team of all other players. The captain
+/// moves the checkers of the team, while each member of the team has an own cube against the box.
+/// Consulting on the plays of the team is left to the players.
+///
+/// After a game, the line of the players rotates: if the captain has beaten the box, the captain
+/// takes over the box and the former box goes to the end of the line. Otherwise the box stays,
+/// the captain goes to the end of the line, and the next member of the team becomes captain.
+/// ```
+/// use backgammon::{Chouette, Victory};
+/// use backgammon::rules::Player;
+///
+/// let mut c = Chouette::new(&["Alice", "Bob", "Carol"]).unwrap();
+/// let game = c.new_game().unwrap();
+/// // the box doubles Carol, who passes, then the box wins against the captain Bob
+/// game.pass(1, Player::Player1).unwrap();
+/// game.game.resign(Player::Player1, Victory::Single).unwrap();
+/// assert_eq!(c.scores(), vec![2, -1, -1]);
+/// // Alice stays in the box, Carol becomes captain
+/// assert_eq!(c.line(), vec![0, 2, 1]);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Chouette {
+    /// The rules set for the games of the chouette
+    pub rules: Rules,
+    /// The games played in the chouette
+    pub games: Vec<ChouetteGame>,
+    /// The money won or lost per point
+    pub stake: u64,
+    /// The names of the players
+    players: Vec<String>,
+    /// The indices of the players in the order of the last game started: the box, the captain,
+    /// and the other members of the team
+    line: Vec<usize>,
+    /// The source of the dices rolled in all games, random dices if none
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dice_source: Option<SharedDiceSource>,
+}
+
+/// Represents a game of a chouette
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChouetteGame {
+    /// The game of the box as player 0 against the captain as player 1. The cube of the game is
+    /// not used, as every member of the team has an own cube.
+    pub game: Game,
+    /// The index of the player in the box
+    pub box_player: usize,
+    /// The members of the team, starting with the captain
+    pub team: Vec<Member>,
+}
+
+/// Represents a member of the team in a chouette game
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Member {
+    /// The index of the player
+    pub player: usize,
+    /// The cube between the box as player 0 and the member as player 1
+    pub cube: Cube,
+    /// The result once a double between the box and the member has been passed
+    passed: Option<GameResult>,
+}
+
+// implement Display trait
+impl fmt::Display for Chouette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scores = self
+            .players
+            .iter()
+            .zip(self.scores())
+            .map(|(name, score)| format!("{} {:+}", name, score))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "Chouette with stake {} per point, Games: {}, Scores: {}",
+            self.stake,
+            self.games.len(),
+            scores.join(", ")
+        )
+    }
+}
+
+/// Implements methods for the Chouette struct
+impl Chouette {
+    /// Start a new chouette of at least three players. The first player is in the box and the
+    /// second is the captain of the first game.
+    pub fn new(players: &[&str]) -> Result<Self, Error> {
+        if players.len() < 3 {
+            return Err(Error::PlayerInvalid);
+        }
+        Ok(Chouette {
+            rules: Rules::default(),
+            games: Vec::new(),
+            stake: 1,
+            players: players.iter().map(|p| p.to_string()).collect(),
+            line: (0..players.len()).collect(),
+            dice_source: None,
+        })
+    }
+
+    /// Set the money won or lost per point
+    pub fn with_stake(mut self, stake: u64) -> Self {
+        self.stake = stake;
+        self
+    }
+
+    /// Roll the dices of all games of the chouette from the given source, see
+    /// [`DiceSource`]
+    pub fn with_dice_source(mut self, source: impl DiceSource + 'static) -> Self {
+        self.dice_source = Some(share(source));
+        self
+    }
+
+    /// Returns the names of the players
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    /// Returns the indices of the players in the order of the next game, or of the game currently
+    /// played: the box, the captain, and the other members of the team
+    pub fn line(&self) -> Vec<usize> {
+        let mut line = self.line.clone();
+        let Some(game) = self.games.last().filter(|g| g.is_finished()) else {
+            return line;
+        };
+        let captain = line.remove(1);
+        match game.result(0).map(|r| r.winner) {
+            Some(Player::Player1) => {
+                let box_player = line.remove(0);
+                line.insert(0, captain);
+                line.push(box_player);
+            }
+            _ => line.push(captain),
+        }
+        line
+    }
+
+    /// Start the next game of the chouette, once the results of all members of the team in the
+    /// previous game are decided.
+    pub fn new_game(&mut self) -> Result<&mut ChouetteGame, Error> {
+        if self.games.last().is_some_and(|g| !g.is_finished()) {
+            return Err(Error::GameStarted);
+        }
+        self.line = self.line();
+        let mut game = Game::new();
+        game.rules = self.rules;
+        game.set_start();
+        game.set_dice_source(self.dice_source.clone());
+        self.games.push(ChouetteGame {
+            game,
+            box_player: self.line[0],
+            team: self.line[1..]
+                .iter()
+                .map(|player| Member {
+                    player: *player,
+                    cube: Cube::default(),
+                    passed: None,
+                })
+                .collect(),
+        });
+        self.game().ok_or(Error::GameEnded)
+    }
+
+    /// Returns the game currently played, i.e. the last game started
+    pub fn game(&mut self) -> Option<&mut ChouetteGame> {
+        self.games.last_mut()
+    }
+
+    /// Returns the points won so far by each player, which are negative for points lost. All
+    /// decided results count, also those of a game still played.
+    pub fn scores(&self) -> Vec<i64> {
+        let mut scores = vec![0; self.players.len()];
+        for game in &self.games {
+            for (i, member) in game.team.iter().enumerate() {
+                if let Some(result) = game.result(i) {
+                    let points = result.points as i64;
+                    let (won, lost) = match result.winner {
+                        Player::Player0 => (game.box_player, member.player),
+                        _ => (member.player, game.box_player),
+                    };
+                    scores[won] += points;
+                    scores[lost] -= points;
+                }
+            }
+        }
+        scores
+    }
+
+    /// Returns the money won by a player, which is negative if the player lost money
+    pub fn balance(&self, player: usize) -> Result<i128, Error> {
+        let score = self
+            .scores()
+            .get(player)
+            .copied()
+            .ok_or(Error::PlayerInvalid)?;
+        Ok(i128::from(score) * i128::from(self.stake))
+    }
+}
+
+/// Implements methods for the ChouetteGame struct
+impl ChouetteGame {
+    /// Returns a member of the team, the captain being member 0
+    fn member(&self, member: usize) -> Result<&Member, Error> {
+        self.team.get(member).ok_or(Error::PlayerInvalid)
+    }
+
+    /// Check that the cube between the box and a member can still be turned
+    fn check_cube(&self, member: usize, player: Player) -> Result<(), Error> {
+        if player == Player::Nobody {
+            return Err(Error::PlayerInvalid);
+        }
+        if self.game.phase == Phase::GameOver || self.member(member)?.passed.is_some() {
+            return Err(Error::GameEnded);
+        }
+        if !self.game.rules.variant.has_cube() {
+            return Err(Error::DoublingNotPermitted);
+        }
+        Ok(())
+    }
+
+    /// Double the cube between the box as player 0 and a member of the team as player 1, which is
+    /// taken by the other side. The cube of each member is turned independently, e.g. the box may
+    /// double the whole team or only some of its members.
+    pub fn double(&mut self, member: usize, player: Player) -> Result<(), Error> {
+        self.check_cube(member, player)?;
+        let cube = &mut self.team[member].cube;
+        let value = cube.offer(player.other())?;
+        cube.set(value)?;
+        cube.set_owner(player.other());
+        Ok(())
+    }
+
+    /// Pass a double of the other side instead of taking it, which ends the game between the box
+    /// as player 0 and a member of the team as player 1. The side passing loses the value of the
+    /// cube before the double.
+    pub fn pass(&mut self, member: usize, player: Player) -> Result<(), Error> {
+        self.check_cube(member, player)?;
+        let m = &mut self.team[member];
+        let _ = m.cube.offer(player)?;
+        m.passed = Some(GameResult {
+            winner: player.other(),
+            victory: Victory::Single,
+            points: m.cube.value(),
+        });
+        Ok(())
+    }
+
+    /// Returns the result between the box as player 0 and a member of the team as player 1, once
+    /// it is decided by a pass or by the end of the game.
+    ///
+    /// With the [`jacoby`](Rules::jacoby) rule, gammons and backgammons only count as a single
+    /// game unless the cube of the member has been turned.
+    pub fn result(&self, member: usize) -> Option<GameResult> {
+        let m = self.team.get(member)?;
+        if m.passed.is_some() {
+            return m.passed;
+        }
+        let result = self.game.result()?;
+        let jacoby = self.game.rules.jacoby && m.cube.value() == 1;
+        let multiplier = if jacoby {
+            1
+        } else {
+            result.victory.multiplier()
+        };
+        Some(GameResult {
+            points: m.cube.value() * multiplier,
+            ..result
+        })
+    }
+
+    /// Returns true once the results of all members of the team are decided
+    pub fn is_finished(&self) -> bool {
+        (0..self.team.len()).all(|member| self.result(member).is_some())
+    }
+}
+
+/// Implements SetRules for Chouette
+impl GameRules for Chouette {
+    fn with_beaver(mut self) -> Self {
+        self.rules.beaver = true;
+        self
+    }
+
+    fn with_raccoon(mut self) -> Self {
+        self.rules.raccoon = true;
+        self
+    }
+
+    fn with_murphy(mut self, limit: u8) -> Self {
+        self.rules.murphy = true;
+        self.rules.murphy_limit = limit;
+        self
+    }
+
+    fn with_jacoby(mut self) -> Self {
+        self.rules.jacoby = true;
+        self
+    }
+
+    fn with_holland(mut self) -> Self {
+        self.rules.holland = true;
+        self
+    }
+
+    fn with_variant(mut self, variant: Variant) -> Self {
+        self.rules.variant = variant;
+        self
+    }
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_chouette() {
+        assert!(matches!(
+            Chouette::new(&["Alice", "Bob"]),
+            Err(Error::PlayerInvalid)
+        ));
+        let c = Chouette::new(&["Alice", "Bob", "Carol", "Dave"]);
+        assert!(c.is_ok_and(|c| c.line() == vec![0, 1, 2, 3] && c.players()[3] == "Dave"));
+    }
+
+    #[test]
+    fn test_cubes() -> Result<(), Error> {
+        let mut c = Chouette::new(&["Alice", "Bob", "Carol", "Dave"])?.with_stake(5);
+        let game = c.new_game()?;
+        assert_eq!(game.box_player, 0);
+        assert_eq!(game.team.len(), 3);
+
+        // the box doubles the captain, who redoubles
+        game.double(0, Player::Player0)?;
+        game.double(0, Player::Player1)?;
+        assert_eq!(game.team[0].cube.value(), 4);
+        assert_eq!(game.team[0].cube.owner(), Player::Player0);
+        assert!(matches!(
+            game.double(0, Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+        // the box doubles Dave, who cannot pass a double of the owned cube
+        game.double(2, Player::Player0)?;
+        assert!(matches!(
+            game.pass(2, Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+        // Carol doubles the box, who passes
+        game.pass(1, Player::Player0)?;
+        assert!(matches!(
+            game.double(1, Player::Player0),
+            Err(Error::GameEnded)
+        ));
+        assert!(!game.is_finished());
+        assert!(matches!(c.new_game(), Err(Error::GameStarted)));
+        assert_eq!(c.scores(), vec![-1, 0, 1, 0]);
+
+        // the captain wins a gammon
+        let game = c.game().ok_or(Error::GameEnded)?;
+        game.game.resign(Player::Player0, Victory::Gammon)?;
+        assert!(game.is_finished());
+        assert_eq!(game.result(0).map(|r| r.points), Some(8));
+        assert_eq!(game.result(2).map(|r| r.points), Some(4));
+        assert_eq!(c.scores(), vec![-13, 8, 1, 4]);
+        assert_eq!(c.balance(0)?, -65);
+        assert!(c.balance(4).is_err());
+
+        // the captain takes over the box
+        assert_eq!(c.line(), vec![1, 2, 3, 0]);
+        assert_eq!(c.new_game()?.box_player, 1);
+        assert_eq!(
+            c.to_string(),
+            "Chouette with stake 5 per point, Games: 2, Scores: Alice -13, Bob +8, Carol +1, Dave +4"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_jacoby() -> Result<(), Error> {
+        let mut c = Chouette::new(&["Alice", "Bob", "Carol"])?.with_jacoby();
+        let game = c.new_game()?;
+        game.double(1, Player::Player0)?;
+        game.game.resign(Player::Player1, Victory::Gammon)?;
+        assert_eq!(game.result(0).map(|r| r.points), Some(1));
+        assert_eq!(game.result(1).map(|r| r.points), Some(4));
+        assert_eq!(c.scores(), vec![5, -1, -4]);
+        assert_eq!(c.line(), vec![0, 2, 1]);
+
+        // no cube in Tavli
+        let mut c = Chouette::new(&["Alice", "Bob", "Carol"])?.with_variant(Variant::Portes);
+        assert!(matches!(
+            c.new_game()?.double(0, Player::Player0),
+            Err(Error::DoublingNotPermitted)
+        ));
+        Ok(())
+    }
+}
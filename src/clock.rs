@@ -0,0 +1,273 @@
+use crate::rules::Player;
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use core::fmt;
+use core::time::Duration;
+
+/// Represents how the time a player takes for a turn is charged to the reserve of the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TimeControl {
+    /// Simple delay: the reserve only starts to run down once the delay of the turn has passed
+    Delay(Duration),
+    /// Bronstein delay: the reserve runs down at once, and the time used is given back at the end
+    /// of the turn, at most the delay
+    Bronstein(Duration),
+    /// Fischer increment: the reserve runs down at once, and the increment is added at the end of
+    /// every turn
+    Fischer(Duration),
+}
+
+// implement Display trait
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeControl::Delay(d) => write!(f, "{}s delay", d.as_secs()),
+            TimeControl::Bronstein(d) => write!(f, "{}s Bronstein delay", d.as_secs()),
+            TimeControl::Fischer(d) => write!(f, "{}s Fischer increment", d.as_secs()),
+        }
+    }
+}
+
+/// Represents the clocks of both players of a match.
+///
+/// The clock does not measure time itself: the caller reports the time elapsed with
+/// [`run`](Clock::run), charged to the player who has to act. A turn of a player lasts as long as
+/// time is charged to that player, e.g. for a roll and the following move, and ends when time is
+/// charged to the opponent or the clock is [`stop`](Clock::stop)ped. Once the reserve of a player
+/// has run out, the player has lost on time and the clock can not be run anymore.
+/// ```
+/// use backgammon::{Clock, TimeControl};
+/// use backgammon::rules::Player;
+/// use core::time::Duration;
+///
+/// let mut clock = Clock::new(Duration::from_secs(60), TimeControl::Delay(Duration::from_secs(12)));
+/// clock.run(Player::Player0, Duration::from_secs(20)).unwrap();
+/// assert_eq!(clock.remaining(Player::Player0), Duration::from_secs(52));
+///
+/// assert!(clock.run(Player::Player1, Duration::from_secs(80)).is_err());
+/// assert_eq!(clock.flagged(), Some(Player::Player1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Clock {
+    /// How the time of a turn is charged
+    pub control: TimeControl,
+    /// The reserves of player 0 and player 1, not counting the current turn
+    pub(crate) reserve: (Duration, Duration),
+    /// The player whose turn is timed, if any
+    pub(crate) running: Player,
+    /// The time used in the current turn
+    pub(crate) used: Duration,
+}
+
+impl Clock {
+    /// Create a clock with the same reserve for both players
+    pub fn new(reserve: Duration, control: TimeControl) -> Self {
+        Clock {
+            control,
+            reserve: (reserve, reserve),
+            running: Player::Nobody,
+            used: Duration::ZERO,
+        }
+    }
+
+    /// Returns the player whose turn is timed, or [`Player::Nobody`] if the clock is stopped
+    pub fn running(&self) -> Player {
+        self.running
+    }
+
+    /// Returns the reserve of a player before the current turn
+    fn reserve(&self, player: Player) -> Duration {
+        match player {
+            Player::Player0 => self.reserve.0,
+            Player::Player1 => self.reserve.1,
+            Player::Nobody => Duration::ZERO,
+        }
+    }
+
+    /// Returns the time charged to the reserve for the current turn so far
+    fn charged(&self) -> Duration {
+        match self.control {
+            TimeControl::Delay(delay) => self.used.saturating_sub(delay),
+            TimeControl::Bronstein(_) | TimeControl::Fischer(_) => self.used,
+        }
+    }
+
+    /// Returns the time left to a player, including the current turn
+    pub fn remaining(&self, player: Player) -> Duration {
+        let reserve = self.reserve(player);
+        match player == self.running {
+            true => reserve.saturating_sub(self.charged()),
+            false => reserve,
+        }
+    }
+
+    /// Returns the player who has run out of time, if any
+    pub fn flagged(&self) -> Option<Player> {
+        [Player::Player0, Player::Player1]
+            .into_iter()
+            .find(|p| self.reserve(*p).is_zero())
+    }
+
+    /// Charge the time elapsed to a player, ending the turn of the opponent first if the opponent
+    /// was timed. Fails if a player has run out of time.
+    pub fn run(&mut self, player: Player, elapsed: Duration) -> Result<(), Error> {
+        if player == Player::Nobody {
+            return Err(Error::PlayerInvalid);
+        }
+        if self.flagged().is_some() {
+            return Err(Error::TimeExpired);
+        }
+        if player != self.running {
+            self.stop();
+            self.running = player;
+        }
+        self.used = self.used.saturating_add(elapsed);
+        if self.remaining(player).is_zero() {
+            // the player has lost on time, no delay or increment is given anymore
+            self.set_reserve(player, Duration::ZERO);
+            self.running = Player::Nobody;
+            self.used = Duration::ZERO;
+            return Err(Error::TimeExpired);
+        }
+        Ok(())
+    }
+
+    /// End the turn of the timed player, if any, applying the delay or increment of the turn
+    pub fn stop(&mut self) {
+        let player = self.running;
+        if player == Player::Nobody {
+            return;
+        }
+        let left = self.remaining(player);
+        let left = match self.control {
+            TimeControl::Delay(_) => left,
+            TimeControl::Bronstein(delay) => left.saturating_add(self.used.min(delay)),
+            TimeControl::Fischer(increment) => left.saturating_add(increment),
+        };
+        self.set_reserve(player, left);
+        self.running = Player::Nobody;
+        self.used = Duration::ZERO;
+    }
+
+    /// Set the reserve of a player
+    fn set_reserve(&mut self, player: Player, reserve: Duration) {
+        match player {
+            Player::Player0 => self.reserve.0 = reserve,
+            Player::Player1 => self.reserve.1 = reserve,
+            Player::Nobody => {}
+        }
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (p0, p1) = (
+            self.remaining(Player::Player0).as_secs(),
+            self.remaining(Player::Player1).as_secs(),
+        );
+        write!(
+            f,
+            "{}:{:02} - {}:{:02} ({})",
+            p0 / 60,
+            p0 % 60,
+            p1 / 60,
+            p1 % 60,
+            self.control
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn test_delay() -> Result<(), Error> {
+        let mut clock = Clock::new(secs(60), TimeControl::Delay(secs(12)));
+        clock.run(Player::Player0, secs(10))?;
+        assert_eq!(clock.remaining(Player::Player0), secs(60));
+        clock.run(Player::Player0, secs(5))?;
+        assert_eq!(clock.remaining(Player::Player0), secs(57));
+        assert_eq!(clock.running(), Player::Player0);
+
+        // the delay of every turn starts afresh
+        clock.run(Player::Player1, secs(12))?;
+        assert_eq!(clock.remaining(Player::Player0), secs(57));
+        assert_eq!(clock.remaining(Player::Player1), secs(60));
+        clock.run(Player::Player0, secs(13))?;
+        assert_eq!(clock.remaining(Player::Player0), secs(56));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bronstein() -> Result<(), Error> {
+        let mut clock = Clock::new(secs(60), TimeControl::Bronstein(secs(12)));
+        clock.run(Player::Player0, secs(10))?;
+        assert_eq!(clock.remaining(Player::Player0), secs(50));
+        clock.stop();
+        assert_eq!(clock.remaining(Player::Player0), secs(60));
+        assert_eq!(clock.running(), Player::Nobody);
+
+        // at most the delay is given back
+        clock.run(Player::Player0, secs(20))?;
+        clock.stop();
+        assert_eq!(clock.remaining(Player::Player0), secs(52));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fischer() -> Result<(), Error> {
+        let mut clock = Clock::new(secs(60), TimeControl::Fischer(secs(5)));
+        clock.run(Player::Player0, secs(2))?;
+        clock.run(Player::Player1, secs(20))?;
+        assert_eq!(clock.remaining(Player::Player0), secs(63));
+        assert_eq!(clock.remaining(Player::Player1), secs(40));
+        clock.stop();
+        assert_eq!(clock.remaining(Player::Player1), secs(45));
+        Ok(())
+    }
+
+    #[test]
+    fn test_flagged() -> Result<(), Error> {
+        let mut clock = Clock::new(secs(60), TimeControl::Fischer(secs(5)));
+        assert_eq!(clock.flagged(), None);
+        assert_eq!(
+            clock.run(Player::Nobody, secs(1)),
+            Err(Error::PlayerInvalid)
+        );
+        clock.run(Player::Player1, secs(59))?;
+        assert_eq!(clock.run(Player::Player1, secs(1)), Err(Error::TimeExpired));
+        assert_eq!(clock.flagged(), Some(Player::Player1));
+        assert_eq!(clock.remaining(Player::Player1), Duration::ZERO);
+
+        // no increment is given after the time has run out
+        clock.stop();
+        assert_eq!(clock.remaining(Player::Player1), Duration::ZERO);
+        assert_eq!(clock.run(Player::Player0, secs(1)), Err(Error::TimeExpired));
+        assert_eq!(clock.remaining(Player::Player0), secs(60));
+        Ok(())
+    }
+
+    // test Display trait
+    #[test]
+    fn test_display() -> Result<(), Error> {
+        let mut clock = Clock::new(secs(600), TimeControl::Delay(secs(12)));
+        clock.run(Player::Player1, secs(75))?;
+        assert_eq!(format!("{}", clock), "10:00 - 8:57 (12s delay)");
+        assert_eq!(
+            format!("{}", TimeControl::Bronstein(secs(8))),
+            "8s Bronstein delay"
+        );
+        Ok(())
+    }
+}
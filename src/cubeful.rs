@@ -0,0 +1,560 @@
+//! # Cubeful Equities and Cube Decisions
+use crate::eval::Evaluation;
+use crate::met::Met;
+use crate::rules::{Cube, Player};
+use crate::{Error, Game};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The default share of the value of a fully live cube which is realized
+const EFFICIENCY: f64 = 0.68;
+
+/// How many redoubles are looked ahead to find the points at which the cube is taken or cashed
+const DEPTH: u8 = 4;
+
+/// Holds everything besides the position of the checkers that matters for the cube, seen from
+/// the player on roll
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CubeContext {
+    /// The value and owner of the cube
+    pub cube: Cube,
+    /// The player on roll, who considers doubling
+    pub player: Player,
+    /// The points the player on roll and the opponent still need to win the match, none for a
+    /// money game
+    pub away: Option<(u64, u64)>,
+    /// Whether this is the Crawford game of a match, in which the cube is dead
+    pub crawford: bool,
+    /// Whether the game is played after the Crawford game of a match
+    pub post_crawford: bool,
+    /// Whether gammons only count after the cube has been turned, in a money game
+    pub jacoby: bool,
+    /// The share of the value of a fully live cube which is realized, 0 for a dead cube and 1
+    /// for a fully live cube. Default is 0.68.
+    pub efficiency: f64,
+}
+
+impl CubeContext {
+    /// Create the context of a money game with a centered cube
+    pub fn money(player: Player) -> Self {
+        CubeContext {
+            cube: Cube::default(),
+            player,
+            away: None,
+            crawford: false,
+            post_crawford: false,
+            jacoby: false,
+            efficiency: EFFICIENCY,
+        }
+    }
+
+    /// Create the context of a match game with a centered cube, in which the player on roll and
+    /// the opponent still need the given points
+    pub fn for_match(player: Player, away: (u64, u64)) -> Self {
+        CubeContext {
+            away: Some(away),
+            ..CubeContext::money(player)
+        }
+    }
+
+    /// Create the context of the player on roll in a game
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::cubeful::CubeContext;
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player1;
+    /// let context = CubeContext::from_game(&g);
+    /// assert_eq!(context.player, Player::Player1);
+    /// assert_eq!(context.away, None);
+    /// ```
+    pub fn from_game(game: &Game) -> Self {
+        let away = game.away().map(|(a, b)| match game.who_plays {
+            Player::Player1 => (b, a),
+            _ => (a, b),
+        });
+        CubeContext {
+            cube: game.cube.clone(),
+            player: game.who_plays,
+            away,
+            crawford: game.is_crawford(),
+            post_crawford: game.is_post_crawford(),
+            jacoby: game.rules.jacoby,
+            efficiency: EFFICIENCY,
+        }
+    }
+
+    /// Returns the value for the player on roll of winning the given points, or of losing them
+    /// if negative, i.e. the points in a money game and the probability to win the match in a
+    /// match game
+    fn value(&self, met: &Met, points: i64) -> Result<f64, Error> {
+        let Some((away, opponent)) = self.away else {
+            return Ok(points as f64);
+        };
+        let (away, opponent) = match points >= 0 {
+            true => (away.saturating_sub(points.unsigned_abs()), opponent),
+            false => (away, opponent.saturating_sub(points.unsigned_abs())),
+        };
+        let narrow = |n: u64| u32::try_from(n).unwrap_or(u32::MAX);
+        met.win(narrow(away), narrow(opponent), self.post_crawford)
+            .ok_or(Error::ScoreInvalid)
+    }
+
+    /// Returns true if a player could double a cube of the given value owned by the given player
+    /// and make any difference
+    fn can_double(&self, player: Player, value: u64, owner: Player) -> bool {
+        let away = match (self.away, player == self.player) {
+            (None, _) => u64::MAX,
+            (Some((away, _)), true) => away,
+            (Some((_, opponent)), false) => opponent,
+        };
+        !self.crawford && owner != player.other() && value < away
+    }
+
+    /// Returns the expected values for the player on roll of winning and of losing at the given
+    /// cube value, weighing single games, gammons, and backgammons
+    fn outcomes(
+        &self,
+        e: &Evaluation,
+        met: &Met,
+        value: u64,
+        centered: bool,
+    ) -> Result<(f64, f64), Error> {
+        let v = value as i64;
+        // with the Jacoby rule, gammons do not count before the cube has been turned
+        let factors = match self.jacoby && self.away.is_none() && centered {
+            true => [1, 1, 1],
+            false => [1, 2, 3],
+        };
+        let weigh = |total: f64, gammon: f64, backgammon: f64, sign: i64| {
+            let shares = [total - gammon, gammon - backgammon, backgammon];
+            let values = factors
+                .iter()
+                .map(|f| self.value(met, sign * f * v))
+                .collect::<Result<Vec<_>, Error>>()?;
+            Ok::<f64, Error>(match total > 0.0 {
+                true => shares.iter().zip(&values).map(|(s, v)| s * v).sum::<f64>() / total,
+                false => values[0],
+            })
+        };
+        Ok((
+            weigh(e.win, e.win_gammon, e.win_backgammon, 1)?,
+            weigh(e.lose(), e.lose_gammon, e.lose_backgammon, -1)?,
+        ))
+    }
+
+    /// Returns the value for the player on roll depending on the probability to win, as a line
+    /// through the points at which the cube would be passed or cashed, looking ahead the given
+    /// number of redoubles
+    fn curve(
+        &self,
+        e: &Evaluation,
+        met: &Met,
+        value: u64,
+        owner: Player,
+        depth: u8,
+    ) -> Result<Vec<(f64, f64)>, Error> {
+        let (win, lose) = self.outcomes(e, met, value, owner == Player::Nobody)?;
+        let mut points = vec![(0.0, lose), (1.0, win)];
+        if depth == 0 {
+            return Ok(points);
+        }
+        let opponent = self.player.other();
+        // the opponent cashes where the player is indifferent between taking and passing
+        if self.can_double(opponent, value, owner) {
+            let taken = self.curve(e, met, 2 * value, self.player, depth - 1)?;
+            let pass = self.value(met, -(value as i64))?;
+            points.push((solve(&taken, pass), pass));
+        }
+        // the player cashes where the opponent is indifferent between taking and passing
+        if self.can_double(self.player, value, owner) {
+            let taken = self.curve(e, met, 2 * value, opponent, depth - 1)?;
+            let cash = self.value(met, value as i64)?;
+            points.push((solve(&taken, cash), cash));
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(points)
+    }
+
+    /// Returns the cubeful value for the player on roll with the cube at the given value and
+    /// owner, mixing the fully live and the dead cube according to the cube efficiency
+    fn cubeful(&self, e: &Evaluation, met: &Met, value: u64, owner: Player) -> Result<f64, Error> {
+        let live = interpolate(&self.curve(e, met, value, owner, DEPTH)?, e.win);
+        let dead = interpolate(&self.curve(e, met, value, owner, 0)?, e.win);
+        Ok(self.efficiency * live + (1.0 - self.efficiency) * dead)
+    }
+
+    /// Returns a value for the player on roll per point of the current cube value, i.e. the
+    /// equity in a money game and the normalized match winning chances in a match game
+    fn normalized(&self, met: &Met, value: f64) -> Result<f64, Error> {
+        let v = self.cube.value() as i64;
+        let (win, lose) = (self.value(met, v)?, self.value(met, -v)?);
+        Ok(2.0 * (value - lose) / (win - lose) - 1.0)
+    }
+}
+
+/// Returns the value of a line through the given points, which are sorted by the probability
+fn interpolate(points: &[(f64, f64)], p: f64) -> f64 {
+    points
+        .windows(2)
+        .find(|w| p <= w[1].0)
+        .map(|w| {
+            let dp = w[1].0 - w[0].0;
+            match dp > 0.0 {
+                true => w[0].1 + (p - w[0].0) * (w[1].1 - w[0].1) / dp,
+                false => w[1].1,
+            }
+        })
+        .unwrap_or(points[points.len() - 1].1)
+}
+
+/// Returns the probability at which a rising line through the given points reaches the value
+fn solve(points: &[(f64, f64)], value: f64) -> f64 {
+    points
+        .windows(2)
+        .find(|w| value <= w[1].1)
+        .map(|w| match w[1].1 - w[0].1 {
+            d if d <= 0.0 => w[1].0,
+            d => (w[0].0 + (value - w[0].1) * (w[1].0 - w[0].0) / d).max(0.0),
+        })
+        .unwrap_or(1.0)
+}
+
+/// Returns the cubeful equity of the player on roll per point of the current cube value, i.e.
+/// the equity in a money game and the normalized match winning chances in a match game.
+/// ```
+/// use backgammon::cubeful::{cubeful_equity, CubeContext};
+/// use backgammon::eval::Evaluation;
+/// use backgammon::met::Met;
+/// use backgammon::rules::Player;
+///
+/// let even = Evaluation { win: 0.5, ..Default::default() };
+/// let context = CubeContext::money(Player::Player0);
+/// let equity = cubeful_equity(&even, &context, &Met::default()).unwrap();
+/// assert!(equity.abs() < 1e-9);
+/// ```
+pub fn cubeful_equity(
+    evaluation: &Evaluation,
+    context: &CubeContext,
+    met: &Met,
+) -> Result<f64, Error> {
+    if context.player == Player::Nobody {
+        return Err(Error::PlayerInvalid);
+    }
+    let value = context.cubeful(evaluation, met, context.cube.value(), context.cube.owner())?;
+    context.normalized(met, value)
+}
+
+/// Holds the cubeful equities of the player on roll after every cube action, per point of the
+/// current cube value
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CubeDecision {
+    /// The equity if the player does not double
+    pub no_double: f64,
+    /// The equity if the player doubles and the opponent takes
+    pub double_take: f64,
+    /// The equity if the player doubles and the opponent passes
+    pub double_pass: f64,
+}
+
+impl CubeDecision {
+    /// Returns true if the player should double
+    pub fn double(&self) -> bool {
+        self.double_take.min(self.double_pass) > self.no_double
+    }
+
+    /// Returns true if the opponent should take a double
+    pub fn take(&self) -> bool {
+        self.double_take <= self.double_pass
+    }
+
+    /// Returns the equity of the player after the proper cube actions
+    pub fn equity(&self) -> f64 {
+        match self.double() {
+            true => self.double_take.min(self.double_pass),
+            false => self.no_double,
+        }
+    }
+
+    /// Returns the proper cube actions of both players
+    pub fn action(&self) -> &'static str {
+        match (self.double(), self.take()) {
+            (true, true) => "Double, take",
+            (true, false) => "Double, pass",
+            (false, true) => "No double, take",
+            (false, false) => "Too good to double, pass",
+        }
+    }
+}
+
+// implement Display trait
+impl fmt::Display for CubeDecision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "No double:   {:+.3}", self.no_double)?;
+        writeln!(f, "Double/Take: {:+.3}", self.double_take)?;
+        writeln!(f, "Double/Pass: {:+.3}", self.double_pass)?;
+        write!(f, "Proper cube action: {}", self.action())
+    }
+}
+
+/// Returns the cubeful equities of the player on roll after every cube action, given the
+/// cubeless probabilities of the position.
+/// ```
+/// use backgammon::cubeful::{cube_decision, CubeContext};
+/// use backgammon::eval::Evaluation;
+/// use backgammon::met::Met;
+/// use backgammon::rules::Player;
+///
+/// let strong = Evaluation { win: 0.9, ..Default::default() };
+/// let context = CubeContext::money(Player::Player0);
+/// let decision = cube_decision(&strong, &context, &Met::default()).unwrap();
+/// assert_eq!(decision.action(), "Double, pass");
+/// assert_eq!(decision.equity(), 1.0);
+/// ```
+pub fn cube_decision(
+    evaluation: &Evaluation,
+    context: &CubeContext,
+    met: &Met,
+) -> Result<CubeDecision, Error> {
+    let (player, value, owner) = (context.player, context.cube.value(), context.cube.owner());
+    if player == Player::Nobody {
+        return Err(Error::PlayerInvalid);
+    }
+    if !context.can_double(player, value, owner) {
+        return Err(Error::DoublingNotPermitted);
+    }
+    let no_double = context.cubeful(evaluation, met, value, owner)?;
+    let double_take = context.cubeful(evaluation, met, 2 * value, player.other())?;
+    let double_pass = context.value(met, value as i64)?;
+    Ok(CubeDecision {
+        no_double: context.normalized(met, no_double)?,
+        double_take: context.normalized(met, double_take)?,
+        double_pass: context.normalized(met, double_pass)?,
+    })
+}
+
+/// Holds the take points of the opponent of the player on roll, i.e. the lowest probabilities to
+/// win the game with which the opponent can take a double, if the cube is dead after the take
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TakePoints {
+    /// The take point if no gammons are won or lost
+    pub raw: f64,
+    /// The take point with the gammons and backgammons of the evaluation
+    pub gammon_adjusted: f64,
+}
+
+// implement Display trait
+impl fmt::Display for TakePoints {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Take point: {:.1}% (gammon-adjusted {:.1}%)",
+            100.0 * self.raw,
+            100.0 * self.gammon_adjusted
+        )
+    }
+}
+
+/// Returns the take points of the opponent of the player on roll if the player doubles, at the
+/// score and cube value of the context. The gammon-adjusted take point weighs the outcomes with
+/// the share of gammons and backgammons among the wins and losses of the evaluation.
+/// ```
+/// use backgammon::cubeful::{take_points, CubeContext};
+/// use backgammon::eval::Evaluation;
+/// use backgammon::met::Met;
+/// use backgammon::rules::Player;
+///
+/// let gammonish = Evaluation { win: 0.7, win_gammon: 0.35, ..Default::default() };
+/// let context = CubeContext::money(Player::Player0);
+/// let points = take_points(&gammonish, &context, &Met::default()).unwrap();
+/// assert_eq!(points.raw, 0.25);
+/// assert!((points.gammon_adjusted - 0.4).abs() < 1e-9);
+/// ```
+pub fn take_points(
+    evaluation: &Evaluation,
+    context: &CubeContext,
+    met: &Met,
+) -> Result<TakePoints, Error> {
+    let (player, value, owner) = (context.player, context.cube.value(), context.cube.owner());
+    if player == Player::Nobody {
+        return Err(Error::PlayerInvalid);
+    }
+    if !context.can_double(player, value, owner) {
+        return Err(Error::DoublingNotPermitted);
+    }
+    let cash = context.value(met, value as i64)?;
+    // the opponent takes as long as the player is below the point to cash at the doubled cube
+    let take_point = |e: &Evaluation| {
+        let (win, lose) = context.outcomes(e, met, 2 * value, false)?;
+        Ok::<f64, Error>(1.0 - (cash - lose) / (win - lose))
+    };
+    let raw = Evaluation {
+        win: evaluation.win,
+        ..Default::default()
+    };
+    Ok(TakePoints {
+        raw: take_point(&raw)?,
+        gammon_adjusted: take_point(evaluation)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An evaluation without gammons
+    fn win(p: f64) -> Evaluation {
+        Evaluation {
+            win: p,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_money() -> Result<(), Error> {
+        let met = Met::default();
+        let context = CubeContext::money(Player::Player0);
+        assert_eq!(
+            cube_decision(&win(0.5), &context, &met)?.action(),
+            "No double, take"
+        );
+        assert_eq!(
+            cube_decision(&win(0.75), &context, &met)?.action(),
+            "Double, take"
+        );
+        assert_eq!(
+            cube_decision(&win(0.9), &context, &met)?.action(),
+            "Double, pass"
+        );
+        // gammons make it too good to double
+        let gammonish = Evaluation {
+            win: 0.9,
+            win_gammon: 0.6,
+            ..Default::default()
+        };
+        assert_eq!(
+            cube_decision(&gammonish, &context, &met)?.action(),
+            "Too good to double, pass"
+        );
+
+        // owning the cube is worth something, the opponent owning it costs something
+        let centered = cubeful_equity(&win(0.6), &context, &met)?;
+        let mut owned = context.clone();
+        owned.cube.set(2)?;
+        owned.cube.set_owner(Player::Player0);
+        assert!(cubeful_equity(&win(0.6), &owned, &met)? > centered);
+        owned.cube.set_owner(Player::Player1);
+        assert!(cubeful_equity(&win(0.6), &owned, &met)? < centered);
+        assert!(matches!(
+            cube_decision(&win(0.6), &owned, &met),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        // without any cube efficiency, the equity is the cubeless equity
+        let dead = CubeContext {
+            efficiency: 0.0,
+            ..context
+        };
+        assert!((cubeful_equity(&win(0.6), &dead, &met)? - 0.2).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_match() -> Result<(), Error> {
+        let met = Met::default();
+        // the trailer after the Crawford game has nothing to lose by doubling
+        let trailer = CubeContext {
+            post_crawford: true,
+            ..CubeContext::for_match(Player::Player0, (4, 1))
+        };
+        assert!(cube_decision(&win(0.5), &trailer, &met)?.double());
+
+        let crawford = CubeContext {
+            crawford: true,
+            ..CubeContext::for_match(Player::Player0, (3, 1))
+        };
+        assert!(matches!(
+            cube_decision(&win(0.5), &crawford, &met),
+            Err(Error::DoublingNotPermitted)
+        ));
+        // at double match point, the cube is dead
+        let dmp = CubeContext::for_match(Player::Player0, (1, 1));
+        assert!(matches!(
+            cube_decision(&win(0.5), &dmp, &met),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        let context = CubeContext::for_match(Player::Player0, (5, 5));
+        let decision = cube_decision(&win(0.85), &context, &met)?;
+        assert!(decision.double());
+        assert_eq!(decision.double_pass, 1.0);
+        let far = CubeContext::for_match(Player::Player0, (5, 30));
+        assert!(matches!(
+            cube_decision(&win(0.5), &far, &met),
+            Err(Error::ScoreInvalid)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_take_points() -> Result<(), Error> {
+        let met = Met::default();
+        let money = CubeContext::money(Player::Player0);
+        let gammonish = Evaluation {
+            win: 0.7,
+            win_gammon: 0.15,
+            ..Default::default()
+        };
+        let points = take_points(&gammonish, &money, &met)?;
+        assert_eq!(points.raw, 0.25);
+        // the taker can lose a gammon and needs more to take
+        assert!(points.gammon_adjusted > points.raw);
+        assert_eq!(
+            format!("{}", points),
+            format!(
+                "Take point: 25.0% (gammon-adjusted {:.1}%)",
+                100.0 * points.gammon_adjusted
+            )
+        );
+
+        // at 2-away 2-away the game decides the match, and passing leaves the taker 2-away
+        // against 1-away in the Crawford game
+        let match_game = CubeContext::for_match(Player::Player0, (2, 2));
+        let points = take_points(&win(0.6), &match_game, &met)?;
+        let pass = met.win(2, 1, false).unwrap_or_default();
+        assert!((points.raw - pass).abs() < 1e-9);
+        assert_eq!(points.raw, points.gammon_adjusted);
+
+        // a redouble to 4 at 4-away 4-away
+        let mut redouble = CubeContext::for_match(Player::Player0, (4, 4));
+        redouble.cube.set(2)?;
+        redouble.cube.set_owner(Player::Player0);
+        let points = take_points(&win(0.6), &redouble, &met)?;
+        assert!((points.raw - met.win(4, 2, false).unwrap_or_default()).abs() < 1e-9);
+        redouble.cube.set_owner(Player::Player1);
+        assert!(matches!(
+            take_points(&win(0.6), &redouble, &met),
+            Err(Error::DoublingNotPermitted)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() {
+        let decision = CubeDecision {
+            no_double: 0.5,
+            double_take: 0.62,
+            double_pass: 1.0,
+        };
+        assert_eq!(
+            format!("{}", decision),
+            "No double:   +0.500\nDouble/Take: +0.620\nDouble/Pass: +1.000\nProper cube action: Double, take"
+        );
+    }
+}
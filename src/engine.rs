@@ -0,0 +1,348 @@
+//! # Text Engine Protocol
+//!
+//! A line protocol modeled on the Universal Chess Interface, which lets graphical frontends run
+//! engines built on this crate as interchangeable processes talking over stdin and stdout. The
+//! frontend sends commands, one per line:
+//!
+//! | Command                 | Answer                                                        |
+//! |-------------------------|---------------------------------------------------------------|
+//! | `bgi`                   | `id name NAME` and `bgiok`                                    |
+//! | `isready`               | `readyok`                                                     |
+//! | `position startpos`     | none, sets the starting position with player 0 on roll       |
+//! | `position xgid=XGID`    | none, sets the position, see [`Xgid`](crate::formats::Xgid)   |
+//! | `go [movetime MS]`      | `info equity E` and `bestmove DECISION`                       |
+//! | `quit`                  | none, stops the engine                                        |
+//!
+//! The decision of `bestmove` depends on the position: a play in the conventional notation
+//! (`none` if no checker can move), `roll` or `double` before rolling, and `take` or `pass` after
+//! a double. Plays are chosen by the equities of the evaluator, and with a `movetime` the best
+//! candidates are refined by truncated rollouts for that many milliseconds. Commands which cannot
+//! be executed are answered with `error MESSAGE`.
+//! ```
+//! use backgammon::engine::Engine;
+//! use backgammon::eval::Heuristic;
+//!
+//! let mut engine = Engine::new("heuristic", Heuristic);
+//! let input = "position xgid=XGID=-b----E-C---eE---c-e----B-:0:0:1:31:0:0:0:0:10\ngo\nquit\n";
+//! let mut output = Vec::new();
+//! engine.run(input.as_bytes(), &mut output).unwrap();
+//! assert!(String::from_utf8(output).unwrap().ends_with("bestmove 8/5 6/5\n"));
+//! ```
+use crate::cubeful::{cube_decision, CubeContext};
+use crate::eval::{rank_plays, Evaluator};
+use crate::formats::Xgid;
+use crate::game::{Game, Phase};
+use crate::met::Met;
+use crate::rollout::{rollout, RolloutSettings};
+use crate::rules::{Play, Player, Rules};
+use crate::Error;
+
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+/// The most candidates refined by rollouts
+const CANDIDATES: usize = 3;
+/// The equity behind the best play up to which plays are refined by rollouts
+const CANDIDATE_WINDOW: f64 = 0.1;
+/// The number of trials rolled out for each candidate at once
+const BATCH_TRIALS: u32 = 36;
+/// The number of rolls after which the trials of the rollouts are evaluated
+const TRUNCATION: u32 = 7;
+
+/// Runs an evaluator as an engine speaking the text engine protocol
+#[derive(Debug, Clone)]
+pub struct Engine<E> {
+    /// The name reported to the frontend
+    pub name: String,
+    /// The evaluator deciding the plays and the cube actions
+    pub evaluator: E,
+    /// The match equity table used for cube decisions in matches
+    pub met: Met,
+    /// The position set by the frontend
+    game: Game,
+}
+
+impl<E: Evaluator + Sync> Engine<E> {
+    /// Create an engine reporting the given name and deciding with the given evaluator, in the
+    /// starting position with player 0 on roll
+    pub fn new(name: &str, evaluator: E) -> Self {
+        Engine {
+            name: name.to_string(),
+            evaluator,
+            met: Met::default(),
+            game: start(),
+        }
+    }
+
+    /// Set the match equity table used for cube decisions in matches
+    pub fn with_met(mut self, met: Met) -> Self {
+        self.met = met;
+        self
+    }
+
+    /// Returns the position set by the frontend
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Execute a command of the frontend and return the lines to answer.
+    /// ```
+    /// use backgammon::engine::Engine;
+    /// use backgammon::eval::Heuristic;
+    ///
+    /// let mut engine = Engine::new("heuristic", Heuristic);
+    /// assert_eq!(engine.handle("isready").unwrap(), vec!["readyok"]);
+    /// assert!(engine.handle("position xgid=nonsense").is_err());
+    /// ```
+    pub fn handle(&mut self, line: &str) -> Result<Vec<String>, Error> {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None | Some("quit") => Ok(Vec::new()),
+            Some("bgi") => Ok(vec![format!("id name {}", self.name), "bgiok".to_string()]),
+            Some("isready") => Ok(vec!["readyok".to_string()]),
+            Some("position") => {
+                self.game = match words.next() {
+                    Some("startpos") => start(),
+                    Some(word) => {
+                        let (key, id) = word.split_once('=').ok_or(Error::NotationInvalid)?;
+                        if !key.eq_ignore_ascii_case("xgid") {
+                            return Err(Error::NotationInvalid);
+                        }
+                        game(&id.parse()?)?
+                    }
+                    None => return Err(Error::NotationInvalid),
+                };
+                Ok(Vec::new())
+            }
+            Some("go") => {
+                let movetime = match (words.next(), words.next()) {
+                    (None, _) => Duration::ZERO,
+                    (Some("movetime"), Some(ms)) => {
+                        Duration::from_millis(ms.parse().map_err(|_| Error::NotationInvalid)?)
+                    }
+                    _ => return Err(Error::NotationInvalid),
+                };
+                let (decision, equity) = self.go(movetime)?;
+                Ok(vec![
+                    format!("info equity {:+.3}", equity),
+                    format!("bestmove {}", decision),
+                ])
+            }
+            Some(_) => Err(Error::NotationInvalid),
+        }
+    }
+
+    /// Answer the commands read from the frontend until it quits or closes the input. Commands
+    /// which cannot be executed are answered with the error.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim() == "quit" {
+                break;
+            }
+            let answer = self
+                .handle(&line)
+                .unwrap_or_else(|e| vec![format!("error {}", e)]);
+            for line in answer {
+                writeln!(output, "{}", line)?;
+            }
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the decision in the position together with its equity for the player deciding
+    fn go(&self, movetime: Duration) -> Result<(String, f64), Error> {
+        let game = &self.game;
+        let context = CubeContext::from_game(game);
+        match game.phase {
+            Phase::AwaitingMove => {
+                let (play, equity) = self.best_play(movetime)?;
+                match play.moves.is_empty() {
+                    true => Ok(("none".to_string(), equity)),
+                    false => Ok((play.to_string(), equity)),
+                }
+            }
+            Phase::AwaitingRoll => {
+                let evaluation = self.evaluator.evaluate_game(game)?;
+                let double = game.cube_is_live(game.who_plays)
+                    && game.cube.owner() != game.who_plays.other()
+                    && cube_decision(&evaluation, &context, &self.met).is_ok_and(|d| d.double());
+                let decision = if double { "double" } else { "roll" };
+                Ok((decision.to_string(), evaluation.equity()))
+            }
+            Phase::AwaitingCubeDecision => {
+                let evaluation = self.evaluator.evaluate_game(game)?;
+                let take =
+                    cube_decision(&evaluation, &context, &self.met).map_or(true, |d| d.take());
+                let decision = if take { "take" } else { "pass" };
+                Ok((decision.to_string(), -evaluation.equity()))
+            }
+            Phase::GameOver => Err(Error::GameEnded),
+        }
+    }
+
+    /// Returns the best play together with its equity, refining the best candidates by rollouts
+    /// for the given time
+    fn best_play(&self, movetime: Duration) -> Result<(Play, f64), Error> {
+        let ranked = rank_plays(&self.game, &self.evaluator)?;
+        let Some(best) = ranked.first().map(|(_, e)| e.equity()) else {
+            return Ok((
+                Play::default(),
+                self.evaluator.evaluate_game(&self.game)?.equity(),
+            ));
+        };
+        let mut plays: Vec<Play> = ranked
+            .into_iter()
+            .take(CANDIDATES)
+            .take_while(|(_, e)| best - e.equity() <= CANDIDATE_WINDOW)
+            .map(|(play, _)| play)
+            .collect();
+        if plays.len() == 1 || movetime.is_zero() {
+            return Ok((plays.swap_remove(0), best));
+        }
+        let candidates = plays
+            .into_iter()
+            .map(|play| {
+                let mut game = self.game.clone();
+                game.apply_play(&play)?;
+                Ok((play, game))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // the candidates are rolled out with the same dices batch by batch
+        let start = Instant::now();
+        let mut sums = vec![0.0; candidates.len()];
+        let mut batches: u32 = 0;
+        while batches == 0 || start.elapsed() < movetime {
+            let settings = RolloutSettings::default()
+                .with_trials(BATCH_TRIALS)
+                .with_truncation(TRUNCATION)
+                .with_seed(u64::from(batches) * u64::from(BATCH_TRIALS));
+            for ((_, game), sum) in candidates.iter().zip(sums.iter_mut()) {
+                // the opponent is on roll after the play
+                *sum -= rollout(game, &self.evaluator, &settings)?.equity;
+            }
+            batches += 1;
+        }
+        let (index, sum) = sums
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .ok_or(Error::MoveInvalid)?;
+        let play = candidates
+            .into_iter()
+            .nth(index)
+            .ok_or(Error::MoveInvalid)?
+            .0;
+        Ok((play, sum / f64::from(batches)))
+    }
+}
+
+/// Returns the starting position with player 0 on roll
+fn start() -> Game {
+    let mut game = Game::new();
+    game.who_plays = Player::Player0;
+    game
+}
+
+/// Returns the game described by an XGID, including the score of the match
+fn game(xgid: &Xgid) -> Result<Game, Error> {
+    let mut game = match xgid.match_length {
+        0 => Game::new(),
+        length => {
+            let away = |score: u32| u64::from(length.saturating_sub(score).max(1));
+            let away = (away(xgid.score.0), away(xgid.score.1));
+            Game::for_match(Rules::default(), xgid.crawford, 0, away)
+        }
+    };
+    xgid.apply(&mut game)?;
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Heuristic;
+    use crate::rules::Point;
+
+    #[test]
+    fn test_handle() -> Result<(), Error> {
+        let mut engine = Engine::new("heuristic", Heuristic);
+        assert_eq!(engine.handle("bgi")?, vec!["id name heuristic", "bgiok"]);
+        assert!(engine.handle("")?.is_empty());
+        assert!(matches!(engine.handle("fly"), Err(Error::NotationInvalid)));
+        assert!(matches!(
+            engine.handle("position"),
+            Err(Error::NotationInvalid)
+        ));
+        assert!(matches!(
+            engine.handle("position fen=1"),
+            Err(Error::NotationInvalid)
+        ));
+        assert!(matches!(
+            engine.handle("go fast"),
+            Err(Error::NotationInvalid)
+        ));
+
+        // the opening position is not worth a double
+        assert!(engine.handle("position startpos")?.is_empty());
+        assert_eq!(engine.handle("go")?[1], "bestmove roll");
+
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:D:0:0:0:0:10";
+        let _ = engine.handle(&format!("position xgid={}", xgid))?;
+        assert_eq!(engine.game().phase, Phase::AwaitingCubeDecision);
+        assert_eq!(engine.handle("go")?[1], "bestmove take");
+
+        // a match at 1-away, in which the cube is dead
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:00:4:0:0:5:10";
+        let _ = engine.handle(&format!("position xgid={}", xgid))?;
+        assert!(!engine.game().cube_is_live(Player::Player0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plays() -> Result<(), Error> {
+        let mut engine = Engine::new("heuristic", Heuristic);
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:65:0:0:0:0:10";
+        let _ = engine.handle(&format!("position XGID={}", xgid))?;
+        let answer = engine.handle("go")?;
+        assert!(answer[0].starts_with("info equity +"));
+        assert_eq!(answer[1], "bestmove 24/18 18/13");
+
+        // the rollouts choose among the best plays by the evaluator
+        let ranked = rank_plays(engine.game(), &Heuristic)?;
+        let answer = engine.handle("go movetime 1")?;
+        let play = answer[1].trim_start_matches("bestmove ");
+        assert!(ranked[..CANDIDATES]
+            .iter()
+            .any(|(p, _)| p.to_string() == play));
+
+        // player 0 is closed out and cannot move
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:65:0:0:0:0:10";
+        let mut game: Xgid = xgid.parse()?;
+        game.board.points = game.board.points.map(|p| match p.owner {
+            Player::Player1 => Point::EMPTY,
+            _ => p,
+        });
+        game.board.points[18..].fill(Point::new(Player::Player1, 2));
+        game.board.points[11] = Point::new(Player::Player1, 3);
+        game.board.bar[0] = 2;
+        let _ = engine.handle(&format!("position xgid={}", game))?;
+        assert_eq!(engine.handle("go")?[1], "bestmove none");
+        Ok(())
+    }
+
+    #[test]
+    fn test_run() -> io::Result<()> {
+        let mut engine = Engine::new("heuristic", Heuristic);
+        let input = "bgi\nisready\nposition xgid=1\nquit\nisready\n";
+        let mut output = Vec::new();
+        engine.run(input.as_bytes(), &mut output)?;
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "id name heuristic\nbgiok\nreadyok\nerror Invalid format\n"
+        );
+        Ok(())
+    }
+}
@@ -1,13 +1,36 @@
 /// This module contains the error definition for the Backgammon game.
-use std::fmt;
+use crate::rules::Player;
+
+use core::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Holds all possible errors that can occur during a Backgammon game.
-#[derive(Debug)]
+///
+/// Every fallible function of the crate returns this error. Its [`kind`](Error::kind) groups the
+/// errors into a few categories, and its [`code`](Error::code) is a stable identifier for wire
+/// protocols and logs.
+/// ```
+/// use backgammon::{Error, ErrorKind, Game};
+///
+/// let mut game = Game::new();
+/// // the cube cannot be offered before the opening roll
+/// let e = game.offer_double(backgammon::rules::Player::Player0).unwrap_err();
+/// assert_eq!(e, Error::DoublingNotPermitted);
+/// assert_eq!(e.kind(), ErrorKind::CubeNotAvailable);
+/// assert_eq!(e.code(), "doubling_not_permitted");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Error {
     /// Game has already started
     GameStarted,
     /// Game has already ended
     GameEnded,
+    /// Match has already ended
+    MatchEnded,
+    /// Session has already been settled
+    SessionEnded,
     /// Opponent offered doubling cube. Need to react on this event first.
     CubeReceived,
     /// Doubling not permitted
@@ -26,16 +49,198 @@ pub enum Error {
     MoveInvalid,
     /// Invalid move, checker on bar
     MoveInvalidBar,
+    /// Invalid move, as many dice values as possible have to be played
+    MoveInvalidMaxDice,
+    /// Invalid move, if only one dice value can be played, it has to be the larger one
+    MoveInvalidLargerDice,
+    /// Invalid move, bearing off is not permitted
+    MoveInvalidBearOff,
+    /// Invalid move, the checker is pinned by the opponent
+    MoveInvalidPinned,
+    /// Invalid move, too many checkers have been moved from the starting field in this turn
+    MoveInvalidHead,
+    /// Invalid move, the player has no checker on the field
+    MoveInvalidChecker,
+    /// Invalid move, the dice value is not left to be played
+    MoveInvalidDice,
     /// Move first
     MoveFirst,
     /// Roll first
     RollFirst,
     /// Dice Invalid
     DiceInvalid,
+    /// A revealed secret does not match its commitment
+    CommitmentInvalid,
+    /// Invalid limits of a bearoff database, or a database is installed already
+    DatabaseInvalid,
+    /// Score not covered by the match equity table
+    ScoreInvalid,
+    /// Invalid move notation
+    NotationInvalid,
+    /// Invalid format of an ID, a position, or a match file
+    FormatInvalid,
+    /// Nothing to undo
+    UndoInvalid,
+    /// Nothing to redo
+    RedoInvalid,
+    /// Unknown variant of Backgammon
+    VariantInvalid,
+    /// Invalid position
+    PositionInvalid(PositionError),
+    /// The time of a player has run out
+    TimeExpired,
+    /// Unknown pairing of a tournament, or its players are not known yet
+    PairingInvalid,
+    /// Match has not ended yet
+    MatchNotEnded,
+}
+
+/// Groups the errors by what went wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ErrorKind {
+    /// A move breaks the rules of the game
+    IllegalMove,
+    /// A player acted while it is the opponent's turn
+    NotYourTurn,
+    /// The cube cannot be offered, taken or passed now, or has an impossible value
+    CubeNotAvailable,
+    /// A position, or a database or table describing positions, is inconsistent
+    InvalidPosition,
+    /// A notation, an ID or a file could not be parsed
+    ParseError,
+    /// The game, match or session is not in a state which allows the action
+    InvalidState,
+    /// An argument is out of range, e.g. a player, field or dice value
+    InvalidArgument,
+}
+
+impl Error {
+    /// Returns the category of the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::FieldBlocked
+            | Error::MoveInvalid
+            | Error::MoveInvalidBar
+            | Error::MoveInvalidMaxDice
+            | Error::MoveInvalidLargerDice
+            | Error::MoveInvalidBearOff
+            | Error::MoveInvalidPinned
+            | Error::MoveInvalidHead
+            | Error::MoveInvalidChecker
+            | Error::MoveInvalidDice => ErrorKind::IllegalMove,
+            Error::NotYourTurn => ErrorKind::NotYourTurn,
+            Error::CubeReceived | Error::DoublingNotPermitted | Error::CubeValueInvalid => {
+                ErrorKind::CubeNotAvailable
+            }
+            Error::PositionInvalid(_) | Error::DatabaseInvalid => ErrorKind::InvalidPosition,
+            Error::NotationInvalid | Error::FormatInvalid | Error::VariantInvalid => {
+                ErrorKind::ParseError
+            }
+            Error::GameStarted
+            | Error::GameEnded
+            | Error::MatchEnded
+            | Error::SessionEnded
+            | Error::MoveFirst
+            | Error::RollFirst
+            | Error::UndoInvalid
+            | Error::RedoInvalid
+            | Error::TimeExpired
+            | Error::MatchNotEnded => ErrorKind::InvalidState,
+            Error::PlayerInvalid
+            | Error::PairingInvalid
+            | Error::FieldInvalid
+            | Error::DiceInvalid
+            | Error::CommitmentInvalid
+            | Error::ScoreInvalid => ErrorKind::InvalidArgument,
+        }
+    }
+
+    /// Returns a stable identifier of the error in snake case, e.g. `not_your_turn`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::GameStarted => "game_started",
+            Error::GameEnded => "game_ended",
+            Error::MatchEnded => "match_ended",
+            Error::SessionEnded => "session_ended",
+            Error::CubeReceived => "cube_received",
+            Error::DoublingNotPermitted => "doubling_not_permitted",
+            Error::CubeValueInvalid => "cube_value_invalid",
+            Error::PlayerInvalid => "player_invalid",
+            Error::FieldBlocked => "field_blocked",
+            Error::FieldInvalid => "field_invalid",
+            Error::NotYourTurn => "not_your_turn",
+            Error::MoveInvalid => "move_invalid",
+            Error::MoveInvalidBar => "move_invalid_bar",
+            Error::MoveInvalidMaxDice => "move_invalid_max_dice",
+            Error::MoveInvalidLargerDice => "move_invalid_larger_dice",
+            Error::MoveInvalidBearOff => "move_invalid_bear_off",
+            Error::MoveInvalidPinned => "move_invalid_pinned",
+            Error::MoveInvalidHead => "move_invalid_head",
+            Error::MoveInvalidChecker => "move_invalid_checker",
+            Error::MoveInvalidDice => "move_invalid_dice",
+            Error::MoveFirst => "move_first",
+            Error::RollFirst => "roll_first",
+            Error::DiceInvalid => "dice_invalid",
+            Error::CommitmentInvalid => "commitment_invalid",
+            Error::DatabaseInvalid => "database_invalid",
+            Error::ScoreInvalid => "score_invalid",
+            Error::NotationInvalid => "notation_invalid",
+            Error::FormatInvalid => "format_invalid",
+            Error::UndoInvalid => "undo_invalid",
+            Error::RedoInvalid => "redo_invalid",
+            Error::VariantInvalid => "variant_invalid",
+            Error::PositionInvalid(PositionError::CheckerCount(..)) => "checker_count_invalid",
+            Error::PositionInvalid(PositionError::FieldShared(_)) => "field_shared",
+            Error::PositionInvalid(PositionError::PinsInvalid) => "pins_invalid",
+            Error::PositionInvalid(PositionError::TurnInvalid) => "turn_invalid",
+            Error::PositionInvalid(PositionError::DicesInvalid) => "dices_invalid",
+            Error::PositionInvalid(PositionError::CubeInvalid) => "cube_invalid",
+            Error::TimeExpired => "time_expired",
+            Error::PairingInvalid => "pairing_invalid",
+            Error::MatchNotEnded => "match_not_ended",
+        }
+    }
+}
+
+/// Describes why a position is invalid
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PositionError {
+    /// A player does not have exactly 15 checkers on the board, on the bar, and off
+    CheckerCount(Player, u8),
+    /// Both players have checkers on the same field, given from the point-of-view of player 0
+    FieldShared(u8),
+    /// Checkers are marked as pinned which are not pinned by the opponent
+    PinsInvalid,
+    /// The player whose turn it is does not match the phase of the game
+    TurnInvalid,
+    /// The dices do not match the phase of the game
+    DicesInvalid,
+    /// The cube value or owner is not possible
+    CubeInvalid,
+}
+
+// implement Display trait
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionError::CheckerCount(player, count) => {
+                write!(f, "{} has {} instead of 15 checkers", player, count)
+            }
+            PositionError::FieldShared(field) => {
+                write!(f, "Both players have checkers on field {}", field)
+            }
+            PositionError::PinsInvalid => write!(f, "Invalid pinned checkers"),
+            PositionError::TurnInvalid => write!(f, "Invalid player on turn"),
+            PositionError::DicesInvalid => write!(f, "Invalid dices"),
+            PositionError::CubeInvalid => write!(f, "Invalid cube"),
+        }
+    }
 }
 
 // implement Error trait
-impl std::error::Error for Error {}
+impl core::error::Error for Error {}
 
 // implement Display trait
 impl fmt::Display for Error {
@@ -43,6 +248,8 @@ impl fmt::Display for Error {
         match self {
             Error::GameStarted => write!(f, "Game has already started"),
             Error::GameEnded => write!(f, "Game has already ended"),
+            Error::MatchEnded => write!(f, "Match has already ended"),
+            Error::SessionEnded => write!(f, "Session has already been settled"),
             Error::PlayerInvalid => write!(f, "Invalid player"),
             Error::CubeReceived => {
                 write!(
@@ -59,7 +266,37 @@ impl fmt::Display for Error {
             Error::MoveFirst => write!(f, "Move first"),
             Error::RollFirst => write!(f, "Roll first"),
             Error::DiceInvalid => write!(f, "Invalid dice"),
+            Error::CommitmentInvalid => write!(f, "Secret does not match its commitment"),
+            Error::DatabaseInvalid => write!(f, "Invalid bearoff database"),
+            Error::ScoreInvalid => write!(f, "Score not covered by the match equity table"),
             Error::MoveInvalidBar => write!(f, "Invalid move, checker on bar"),
+            Error::MoveInvalidChecker => {
+                write!(f, "Invalid move, no checker of yours on the field")
+            }
+            Error::MoveInvalidDice => write!(f, "Invalid move, dice value not available"),
+            Error::MoveInvalidMaxDice => {
+                write!(
+                    f,
+                    "Invalid move, as many dice as possible have to be played"
+                )
+            }
+            Error::MoveInvalidLargerDice => {
+                write!(f, "Invalid move, the larger dice has to be played")
+            }
+            Error::MoveInvalidBearOff => write!(f, "Invalid move, bearing off not permitted"),
+            Error::MoveInvalidPinned => write!(f, "Invalid move, checker is pinned"),
+            Error::MoveInvalidHead => {
+                write!(f, "Invalid move, too many checkers moved from the head")
+            }
+            Error::NotationInvalid => write!(f, "Invalid move notation"),
+            Error::FormatInvalid => write!(f, "Invalid format"),
+            Error::UndoInvalid => write!(f, "Nothing to undo"),
+            Error::RedoInvalid => write!(f, "Nothing to redo"),
+            Error::VariantInvalid => write!(f, "Unknown variant"),
+            Error::PositionInvalid(e) => write!(f, "Invalid position: {}", e),
+            Error::TimeExpired => write!(f, "Time has run out"),
+            Error::PairingInvalid => write!(f, "Invalid pairing"),
+            Error::MatchNotEnded => write!(f, "Match has not ended yet"),
         }
     }
 }
@@ -75,6 +312,11 @@ mod tests {
             "Game has already started"
         );
         assert_eq!(format!("{}", Error::GameEnded), "Game has already ended");
+        assert_eq!(format!("{}", Error::MatchEnded), "Match has already ended");
+        assert_eq!(
+            format!("{}", Error::SessionEnded),
+            "Session has already been settled"
+        );
         assert_eq!(format!("{}", Error::PlayerInvalid), "Invalid player");
         assert_eq!(
             format!("{}", Error::CubeReceived),
@@ -92,9 +334,115 @@ mod tests {
         assert_eq!(format!("{}", Error::MoveFirst), "Move first");
         assert_eq!(format!("{}", Error::RollFirst), "Roll first");
         assert_eq!(format!("{}", Error::DiceInvalid), "Invalid dice");
+        assert_eq!(
+            format!("{}", Error::CommitmentInvalid),
+            "Secret does not match its commitment"
+        );
+        assert_eq!(
+            format!("{}", Error::DatabaseInvalid),
+            "Invalid bearoff database"
+        );
+        assert_eq!(
+            format!("{}", Error::ScoreInvalid),
+            "Score not covered by the match equity table"
+        );
         assert_eq!(
             format!("{}", Error::MoveInvalidBar),
             "Invalid move, checker on bar"
         );
+        assert_eq!(
+            format!("{}", Error::MoveInvalidChecker),
+            "Invalid move, no checker of yours on the field"
+        );
+        assert_eq!(
+            format!("{}", Error::MoveInvalidDice),
+            "Invalid move, dice value not available"
+        );
+        assert_eq!(
+            format!("{}", Error::MoveInvalidMaxDice),
+            "Invalid move, as many dice as possible have to be played"
+        );
+        assert_eq!(
+            format!("{}", Error::MoveInvalidLargerDice),
+            "Invalid move, the larger dice has to be played"
+        );
+        assert_eq!(
+            format!("{}", Error::MoveInvalidBearOff),
+            "Invalid move, bearing off not permitted"
+        );
+        assert_eq!(
+            format!("{}", Error::MoveInvalidPinned),
+            "Invalid move, checker is pinned"
+        );
+        assert_eq!(
+            format!("{}", Error::MoveInvalidHead),
+            "Invalid move, too many checkers moved from the head"
+        );
+        assert_eq!(
+            format!("{}", Error::NotationInvalid),
+            "Invalid move notation"
+        );
+        assert_eq!(format!("{}", Error::FormatInvalid), "Invalid format");
+        assert_eq!(format!("{}", Error::UndoInvalid), "Nothing to undo");
+        assert_eq!(format!("{}", Error::RedoInvalid), "Nothing to redo");
+        assert_eq!(format!("{}", Error::VariantInvalid), "Unknown variant");
+        assert_eq!(format!("{}", Error::TimeExpired), "Time has run out");
+        assert_eq!(format!("{}", Error::PairingInvalid), "Invalid pairing");
+        assert_eq!(
+            format!("{}", Error::MatchNotEnded),
+            "Match has not ended yet"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                Error::PositionInvalid(PositionError::CheckerCount(Player::Player1, 14))
+            ),
+            "Invalid position: Player 1 has 14 instead of 15 checkers"
+        );
+        assert_eq!(
+            format!("{}", Error::PositionInvalid(PositionError::FieldShared(3))),
+            "Invalid position: Both players have checkers on field 3"
+        );
+        assert_eq!(
+            format!("{}", PositionError::PinsInvalid),
+            "Invalid pinned checkers"
+        );
+        assert_eq!(
+            format!("{}", PositionError::TurnInvalid),
+            "Invalid player on turn"
+        );
+        assert_eq!(format!("{}", PositionError::DicesInvalid), "Invalid dices");
+        assert_eq!(format!("{}", PositionError::CubeInvalid), "Invalid cube");
+    }
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(Error::MoveInvalidBar.kind(), ErrorKind::IllegalMove);
+        assert_eq!(Error::FieldBlocked.kind(), ErrorKind::IllegalMove);
+        assert_eq!(Error::MoveInvalidDice.kind(), ErrorKind::IllegalMove);
+        assert_eq!(Error::NotYourTurn.kind(), ErrorKind::NotYourTurn);
+        assert_eq!(
+            Error::DoublingNotPermitted.kind(),
+            ErrorKind::CubeNotAvailable
+        );
+        assert_eq!(
+            Error::PositionInvalid(PositionError::PinsInvalid).kind(),
+            ErrorKind::InvalidPosition
+        );
+        assert_eq!(Error::NotationInvalid.kind(), ErrorKind::ParseError);
+        assert_eq!(Error::GameEnded.kind(), ErrorKind::InvalidState);
+        assert_eq!(Error::DiceInvalid.kind(), ErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(Error::NotYourTurn.code(), "not_your_turn");
+        assert_eq!(Error::MoveInvalidMaxDice.code(), "move_invalid_max_dice");
+        assert_eq!(
+            Error::PositionInvalid(PositionError::CheckerCount(Player::Player0, 16)).code(),
+            "checker_count_invalid"
+        );
+        assert_eq!(Error::GameEnded, Error::GameEnded);
+        assert_ne!(Error::GameEnded, Error::GameStarted);
     }
 }
@@ -0,0 +1,601 @@
+//! # Evaluate Backgammon Positions
+use crate::bearoff::{database, wastage};
+use crate::game::{Game, Phase};
+use crate::rules::{Board, Play, Player, PlayerBoard};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+/// Represents the estimated probabilities of the outcomes of a game for one player. The
+/// probabilities of gammons include the backgammons, and the probabilities of losing are the
+/// probabilities of the opponent winning.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Evaluation {
+    /// The probability to win the game
+    pub win: f64,
+    /// The probability to win a gammon or a backgammon
+    pub win_gammon: f64,
+    /// The probability to win a backgammon
+    pub win_backgammon: f64,
+    /// The probability to lose a gammon or a backgammon
+    pub lose_gammon: f64,
+    /// The probability to lose a backgammon
+    pub lose_backgammon: f64,
+}
+
+impl Evaluation {
+    /// Returns the probability to lose the game
+    pub fn lose(&self) -> f64 {
+        1.0 - self.win
+    }
+
+    /// Returns the expected points won per point of the cube value, ignoring the cube
+    pub fn equity(&self) -> f64 {
+        2.0 * self.win - 1.0 + self.win_gammon - self.lose_gammon + self.win_backgammon
+            - self.lose_backgammon
+    }
+
+    /// Returns the evaluation from the point-of-view of the opponent
+    pub fn inverted(&self) -> Self {
+        Evaluation {
+            win: self.lose(),
+            win_gammon: self.lose_gammon,
+            win_backgammon: self.lose_backgammon,
+            lose_gammon: self.win_gammon,
+            lose_backgammon: self.win_backgammon,
+        }
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Win: {:.1}% (G {:.1}%, BG {:.1}%), Lose: {:.1}% (G {:.1}%, BG {:.1}%), Equity: {:+.3}",
+            100.0 * self.win,
+            100.0 * self.win_gammon,
+            100.0 * self.win_backgammon,
+            100.0 * self.lose(),
+            100.0 * self.lose_gammon,
+            100.0 * self.lose_backgammon,
+            self.equity()
+        )
+    }
+}
+
+/// Trait to estimate the outcome of a position.
+///
+/// Everything choosing plays or cube actions, i.e. [`rank_plays`], [`Game::best_move`], and the
+/// [`rollout`](crate::rollout::rollout) of positions, consumes an evaluator, so a neural network,
+/// an external engine, or a custom heuristic can be plugged in by implementing this trait. The
+/// crate ships [`Heuristic`] as the default. References, boxes, and `Arc`s of evaluators are
+/// evaluators too, so they can also be chosen at runtime as trait objects.
+/// ```
+/// use backgammon::{Error, Game};
+/// use backgammon::eval::{Evaluation, Evaluator, Heuristic};
+/// use backgammon::rules::{Board, Player};
+///
+/// /// Only counts the pips
+/// struct Race;
+///
+/// impl Evaluator for Race {
+///     fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+///         let lead = board.pip_count(player.other())? as f64 - board.pip_count(player)? as f64;
+///         let win = 1.0 / (1.0 + (-lead / 10.0).exp());
+///         Ok(Evaluation { win, ..Default::default() })
+///     }
+/// }
+///
+/// let evaluators: Vec<Box<dyn Evaluator>> = vec![Box::new(Heuristic), Box::new(Race)];
+/// let mut g = Game::new();
+/// g.roll_with((6, 5)).unwrap();
+/// for evaluator in &evaluators {
+///     let (_, evaluation) = g.best_move((6, 5), evaluator).unwrap();
+///     assert!(evaluation.win > 0.5);
+/// }
+/// ```
+pub trait Evaluator {
+    /// Estimate the probabilities of the outcomes of the game for a player who is on roll
+    fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error>;
+
+    /// Estimate the probabilities of the outcomes of a game for the player whose turn it is
+    fn evaluate_game(&self, game: &Game) -> Result<Evaluation, Error> {
+        self.evaluate(&game.board, game.who_plays)
+    }
+}
+
+impl<T: Evaluator + ?Sized> Evaluator for &T {
+    fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+        (**self).evaluate(board, player)
+    }
+}
+
+impl<T: Evaluator + ?Sized> Evaluator for Box<T> {
+    fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+        (**self).evaluate(board, player)
+    }
+}
+
+impl<T: Evaluator + ?Sized> Evaluator for Arc<T> {
+    fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+        (**self).evaluate(board, player)
+    }
+}
+
+/// A simple static evaluator weighing the pip count, blots, points made, home board strength,
+/// checkers on the bar, and checkers off. Races the installed two-sided bearoff database holds
+/// are looked up instead. It plays reasonably, but far from the strength of a
+/// neural network, and serves as a baseline opponent and as default to rank plays.
+/// ```
+/// use backgammon::eval::{Evaluator, Heuristic};
+/// use backgammon::rules::{Board, Player};
+///
+/// let evaluation = Heuristic.evaluate(&Board::new(), Player::Player0).unwrap();
+/// assert!(evaluation.win > 0.5 && evaluation.win < 0.6);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Heuristic;
+
+/// Returns the boards of a player and of the opponent
+pub(crate) fn sides(board: &Board, player: Player) -> Result<(PlayerBoard, PlayerBoard), Error> {
+    Ok((board.side(player)?, board.side(player.other())?))
+}
+
+/// Returns the pip count of one side
+fn pips(side: &PlayerBoard) -> f64 {
+    let board: u32 = (0..24)
+        .map(|f| u32::from(side.board[f]) * (f as u32 + 1))
+        .sum();
+    f64::from(board + 25 * u32::from(side.bar))
+}
+
+/// Returns the amount of points made in the home board
+fn home_points(side: &PlayerBoard) -> f64 {
+    f64::from(side.board[..6].iter().filter(|c| **c >= 2).count() as u8)
+}
+
+/// Returns the length of the longest sequence of consecutive points made
+fn prime(side: &PlayerBoard) -> f64 {
+    let mut longest = 0;
+    let mut length = 0;
+    for checkers in side.board {
+        length = if checkers >= 2 { length + 1 } else { 0 };
+        longest = longest.max(length);
+    }
+    f64::from(longest)
+}
+
+/// Returns the amount of single checkers of a side which checkers of the opponent can hit
+fn exposed_blots(side: &PlayerBoard, opponent: &PlayerBoard) -> f64 {
+    let mut blots = 0;
+    for f in (0..24).filter(|f| side.board[*f] == 1) {
+        // checkers of the opponent entering from the bar land in the home board of the side
+        let from_bar = opponent.bar > 0 && f < 6;
+        // a checker of the opponent on field g is on field 23 - g of the side and moves upwards
+        let behind = (0..24)
+            .filter(|g| opponent.board[*g] > 0)
+            .any(|g| (23 - g) < f && f - (23 - g) <= 12);
+        if from_bar || behind {
+            blots += 1;
+        }
+    }
+    f64::from(blots)
+}
+
+/// Returns the share of the games a side wins as gammons, given the position of the opponent
+fn gammon_rate(side: &PlayerBoard, opponent: &PlayerBoard) -> (f64, f64) {
+    if opponent.off > 0 {
+        return (0.0, 0.0);
+    }
+    // pips the opponent needs to bring all checkers home
+    let outside: u32 = (6..24)
+        .map(|g| u32::from(opponent.board[g]) * (g as u32 - 5))
+        .sum::<u32>()
+        + u32::from(opponent.bar) * 20;
+    if outside == 0 {
+        return (0.0, 0.0);
+    }
+    let ahead = pips(opponent) - pips(side);
+    let gammon = (f64::from(outside) / 300.0 + ahead.max(0.0) / 400.0).clamp(0.0, 0.9);
+    // checkers in the home board of the side or on the bar may be backgammoned
+    let trapped = (18..24).map(|g| opponent.board[g]).sum::<u8>() + opponent.bar;
+    let backgammon = gammon * (f64::from(trapped) / 15.0).min(0.3);
+    (gammon, backgammon)
+}
+
+/// Returns the outcome of a game which has ended, from the point-of-view of a player
+pub(crate) fn finished(side: &PlayerBoard, opponent: &PlayerBoard) -> Option<Evaluation> {
+    let outcome = |loser: &PlayerBoard| {
+        let gammon = loser.off == 0;
+        let backgammon = gammon && (loser.bar > 0 || loser.board[18..].iter().any(|c| *c > 0));
+        Evaluation {
+            win: 1.0,
+            win_gammon: f64::from(u8::from(gammon)),
+            win_backgammon: f64::from(u8::from(backgammon)),
+            lose_gammon: 0.0,
+            lose_backgammon: 0.0,
+        }
+    };
+    match (side.off, opponent.off) {
+        (15, _) => Some(outcome(opponent)),
+        (_, 15) => Some(outcome(side).inverted()),
+        _ => None,
+    }
+}
+
+impl Evaluator for Heuristic {
+    fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+        board.validate()?;
+        let (side, opponent) = &sides(board, player)?;
+        if let Some(evaluation) = finished(side, opponent) {
+            return Ok(evaluation);
+        }
+        if let Some(evaluation) = database().and_then(|db| db.evaluate(board, player)) {
+            return Ok(evaluation);
+        }
+
+        // the player on roll is about 4 pips ahead, and in a race the difference of the pips
+        // wasted while bearing off counts as well
+        let contact = !board.is_race();
+        let (own, other) = (pips(side), pips(opponent));
+        let wasted = match contact {
+            true => 0.0,
+            false => wastage(board, player.other())? - wastage(board, player)?,
+        };
+        let mut score = 1.2 * (other - own + wasted + 4.0) / (own + other).sqrt();
+
+        if contact {
+            score += 0.12 * (home_points(side) - home_points(opponent));
+            score += 0.08 * (prime(side) - prime(opponent));
+            score -= 0.15 * exposed_blots(side, opponent);
+            // blots of the opponent can be hit at once
+            score += 0.2 * exposed_blots(opponent, side);
+            score -= 0.25 * f64::from(side.bar) * (1.0 + home_points(opponent) / 3.0);
+            score += 0.25 * f64::from(opponent.bar) * (1.0 + home_points(side) / 3.0);
+        }
+
+        let win = 1.0 / (1.0 + (-score).exp());
+        let (win_gammon, win_backgammon) = gammon_rate(side, opponent);
+        let (lose_gammon, lose_backgammon) = gammon_rate(opponent, side);
+        // without contact, backgammons are rare
+        let bg = if contact { 1.0 } else { 0.2 };
+        Ok(Evaluation {
+            win,
+            win_gammon: win * win_gammon,
+            win_backgammon: win * win_backgammon * bg,
+            lose_gammon: (1.0 - win) * lose_gammon,
+            lose_backgammon: (1.0 - win) * lose_backgammon * bg,
+        })
+    }
+}
+
+/// Represents the class of a position, as GNU Backgammon classifies positions to select an
+/// evaluator for them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PositionClass {
+    /// A player has borne off all checkers
+    Over,
+    /// No checker can be hit anymore and all checkers of both players are in their home boards
+    Bearoff,
+    /// No checker can be hit anymore
+    Race,
+    /// A player has only few checkers left in play, which hardly ever hold points
+    Crashed,
+    /// Checkers can still be hit
+    Contact,
+}
+
+impl fmt::Display for PositionClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionClass::Over => write!(f, "Over"),
+            PositionClass::Bearoff => write!(f, "Bearoff"),
+            PositionClass::Race => write!(f, "Race"),
+            PositionClass::Crashed => write!(f, "Crashed"),
+            PositionClass::Contact => write!(f, "Contact"),
+        }
+    }
+}
+
+/// Returns true if a side has so few checkers left in play that it is crashed, following gnubg:
+/// at most six checkers, not counting the checkers piled on the ace point and the deuce point
+fn crashed(side: &PlayerBoard) -> bool {
+    const N: u32 = 6;
+    let total = side.board.iter().map(|c| u32::from(*c)).sum::<u32>() + u32::from(side.bar);
+    let (ace, deuce) = (u32::from(side.board[0]), u32::from(side.board[1]));
+    if total <= N {
+        return true;
+    }
+    if ace > 1 {
+        total <= N + ace || (deuce > 1 && 1 + total - (ace + deuce) <= N)
+    } else {
+        total < N + deuce
+    }
+}
+
+/// Returns the class of a position, with the same semantics as GNU Backgammon
+/// ```
+/// use backgammon::eval::{classify, PositionClass};
+/// use backgammon::rules::Board;
+///
+/// assert_eq!(classify(&Board::new()), PositionClass::Contact);
+/// ```
+pub fn classify(board: &Board) -> PositionClass {
+    let backs = (
+        board.back_checker(Player::Player0),
+        board.back_checker(Player::Player1),
+    );
+    match backs {
+        (Ok(Some(a)), Ok(Some(b))) if a + b <= 23 => match a.max(b) < 6 {
+            true => PositionClass::Bearoff,
+            false => PositionClass::Race,
+        },
+        (Ok(Some(_)), Ok(Some(_))) => {
+            let crashed = |player| board.side(player).is_ok_and(|side| crashed(&side));
+            match crashed(Player::Player0) || crashed(Player::Player1) {
+                true => PositionClass::Crashed,
+                false => PositionClass::Contact,
+            }
+        }
+        _ => PositionClass::Over,
+    }
+}
+
+/// Returns the distinct legal plays of the player on roll, ranked from the best to the worst by
+/// the equity of the position after the play, in which the opponent is on roll.
+/// ```
+/// use backgammon::{Game, Phase};
+/// use backgammon::eval::{rank_plays, Heuristic};
+///
+/// let mut g = Game::new();
+/// g.roll_with((3, 1)).unwrap();
+/// let ranked = rank_plays(&g, &Heuristic).unwrap();
+/// assert_eq!(ranked[0].0.to_string(), "8/5 6/5");
+/// ```
+pub fn rank_plays(
+    game: &Game,
+    evaluator: &(impl Evaluator + ?Sized),
+) -> Result<Vec<(Play, Evaluation)>, Error> {
+    if game.phase != Phase::AwaitingMove {
+        return Err(Error::MoveInvalid);
+    }
+    let mut ranked = game
+        .legal_plays()
+        .into_iter()
+        .map(|(board, play)| {
+            let evaluation = evaluator.evaluate(&board, game.who_plays.other())?;
+            Ok((play, evaluation.inverted()))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    ranked.sort_by(|a, b| {
+        b.1.equity()
+            .partial_cmp(&a.1.equity())
+            .unwrap_or(Ordering::Equal)
+    });
+    Ok(ranked)
+}
+
+/// Represents a play suggested to the player on roll, as returned by
+/// [`Game::hints`](crate::Game::hints)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Hint {
+    /// The play
+    pub play: Play,
+    /// The evaluation of the position after the play, from the point-of-view of the player
+    pub evaluation: Evaluation,
+    /// The cubeless equity of the play, see [`Evaluation::equity`]
+    pub equity: f64,
+    /// The equity lost compared to the best play, 0 for the best play
+    pub loss: f64,
+}
+
+// implement Display trait
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:<24} {:+.3}", self.play.to_string(), self.equity)?;
+        if self.loss > 0.0 {
+            write!(f, " ({:+.3})", -self.loss)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Point;
+
+    /// A race in which player 0 needs the given pips with checkers on the 6 point and the ace
+    /// point, and player 1 has all checkers on the 6 point
+    fn race(ones: i8) -> Result<Board, Error> {
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.set(Player::Player0, 5, 15 - ones)?;
+        board.set(Player::Player0, 0, ones)?;
+        board.set(Player::Player1, 5, 15)?;
+        Ok(board)
+    }
+
+    #[test]
+    fn test_race() -> Result<(), Error> {
+        let even = Heuristic.evaluate(&race(0)?, Player::Player0)?;
+        let ahead = Heuristic.evaluate(&race(10)?, Player::Player0)?;
+        assert!(even.win > 0.5);
+        assert!(ahead.win > even.win);
+        assert!(ahead.win_gammon < 0.01 && ahead.lose_gammon < 0.01);
+        // the opponent on roll is behind
+        let behind = Heuristic.evaluate(&race(10)?, Player::Player1)?;
+        assert!(behind.win < 0.5);
+        // with the same pip count, a smooth position wastes fewer pips than a stack
+        let mut smooth = race(0)?;
+        smooth.points[4..7].fill(Point::new(Player::Player0, 5));
+        assert_eq!(smooth.pip_count(Player::Player0)?, 90);
+        let smooth = Heuristic.evaluate(&smooth, Player::Player0)?;
+        assert!(smooth.win > even.win);
+        Ok(())
+    }
+
+    #[test]
+    fn test_finished() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.points = board.points.map(|p| match p.owner {
+            Player::Player0 => Point::EMPTY,
+            _ => p,
+        });
+        board.off[0] = 15;
+        let e = Heuristic.evaluate(&board, Player::Player1)?;
+        assert_eq!(e.win, 0.0);
+        // player 1 is backgammoned with checkers in the home board of player 0
+        assert_eq!((e.lose_gammon, e.lose_backgammon), (1.0, 1.0));
+        assert_eq!(e.equity(), -3.0);
+
+        board.set(Player::Player1, 23, -2)?;
+        board.set(Player::Player1, 0, 2)?;
+        let e = Heuristic.evaluate(&board, Player::Player0)?;
+        assert_eq!((e.win, e.win_gammon, e.win_backgammon), (1.0, 1.0, 0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_contact() -> Result<(), Error> {
+        let start = Heuristic.evaluate(&Board::new(), Player::Player0)?;
+        assert!(start.win_gammon > 0.05 && start.win_gammon < start.win);
+        assert!((start.equity() + start.inverted().equity()).abs() < 1e-9);
+
+        // a checker on the bar is a disadvantage
+        let mut board = Board::new();
+        board.set(Player::Player0, 23, -1)?;
+        board.set_bar(Player::Player0, 1)?;
+        let hit = Heuristic.evaluate(&board, Player::Player0)?;
+        assert!(hit.win < start.win);
+        assert!(Heuristic.evaluate(&board, Player::Nobody).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rank_plays() -> Result<(), Error> {
+        let mut g = Game::new();
+        assert!(rank_plays(&g, &Heuristic).is_err());
+        let _ = g.roll_with((6, 1))?;
+        let ranked = rank_plays(&g, &Heuristic)?;
+        assert_eq!(ranked.len(), g.legal_plays().len());
+        assert!(ranked
+            .windows(2)
+            .all(|w| w[0].1.equity() >= w[1].1.equity()));
+        assert_eq!(ranked[0].0.to_string(), "13/7 8/7");
+        Ok(())
+    }
+
+    /// Prefers to have checkers on the bar, the opposite of any sensible evaluator
+    struct Reckless;
+
+    impl Evaluator for Reckless {
+        fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+            let (side, _) = &sides(board, player)?;
+            Ok(Evaluation {
+                win: f64::from(side.bar) / 15.0,
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_evaluator() -> Result<(), Error> {
+        let mut g = Game::new();
+        assert!(Heuristic.evaluate_game(&g).is_err());
+        let _ = g.roll_with((6, 1))?;
+        assert_eq!(
+            Heuristic.evaluate_game(&g)?,
+            Heuristic.evaluate(&g.board, Player::Player0)?
+        );
+
+        // evaluators can be chosen at runtime
+        let shared: Arc<dyn Evaluator + Send + Sync> = Arc::new(Heuristic);
+        let boxed: Box<dyn Evaluator> = Box::new(Reckless);
+        assert_eq!(
+            rank_plays(&g, &shared)?[0].0.to_string(),
+            rank_plays(&g, &Heuristic)?[0].0.to_string()
+        );
+        // with a blot of the opponent on the bar point, the opponent is best off with no checker
+        // on the bar, hence a hit is avoided
+        g.board.set(Player::Player1, 23, -1)?;
+        g.board.set(Player::Player1, 17, 1)?;
+        let hits =
+            |ranked: Vec<(Play, Evaluation)>| !ranked[0].0.moves.iter().all(|m| m.hits.is_empty());
+        assert!(hits(rank_plays(&g, &Heuristic)?));
+        assert!(!hits(rank_plays(&g, boxed.as_ref())?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_classify() -> Result<(), Error> {
+        assert_eq!(classify(&Board::new()), PositionClass::Contact);
+        assert_eq!(classify(&race(0)?), PositionClass::Bearoff);
+
+        let mut board = race(1)?;
+        board.set(Player::Player0, 0, -1)?;
+        board.set(Player::Player0, 10, 1)?;
+        assert_eq!(classify(&board), PositionClass::Race);
+
+        // player 1 has six checkers left in play, besides the ones on the ace point, and the back
+        // checkers of player 0 are on the 3 point of player 1
+        let mut board = Board::new();
+        board.points = board.points.map(|p| match p.owner {
+            Player::Player1 => Point::EMPTY,
+            _ => p,
+        });
+        board.points.swap(21, 23);
+        board.points[23] = Point::new(Player::Player1, 4);
+        board.points[9] = Point::new(Player::Player1, 6);
+        board.off[1] = 5;
+        assert_eq!(classify(&board), PositionClass::Crashed);
+        board.points[23].count = 1;
+        board.points[9].count = 5;
+        board.off[1] = 9;
+        assert_eq!(classify(&board), PositionClass::Crashed);
+        board.points[9].count = 7;
+        board.off[1] = 7;
+        assert_eq!(classify(&board), PositionClass::Contact);
+
+        board.points[23] = Point::EMPTY;
+        board.points[9] = Point::EMPTY;
+        board.off[1] = 15;
+        assert_eq!(classify(&board), PositionClass::Over);
+        assert_eq!(PositionClass::Over.to_string(), "Over");
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() {
+        let e = Evaluation {
+            win: 0.6,
+            win_gammon: 0.2,
+            win_backgammon: 0.01,
+            lose_gammon: 0.1,
+            lose_backgammon: 0.0,
+        };
+        assert_eq!(
+            e.to_string(),
+            "Win: 60.0% (G 20.0%, BG 1.0%), Lose: 40.0% (G 10.0%, BG 0.0%), Equity: +0.310"
+        );
+
+        let mut hint = Hint {
+            play: "8/5 6/5".parse().unwrap_or_default(),
+            evaluation: e,
+            equity: e.equity(),
+            loss: 0.0,
+        };
+        assert_eq!(hint.to_string(), "8/5 6/5                  +0.310");
+        hint.loss = 0.05;
+        assert_eq!(hint.to_string(), "8/5 6/5                  +0.310 (-0.050)");
+    }
+}
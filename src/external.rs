@@ -0,0 +1,208 @@
+//! # GNU Backgammon External Player
+//!
+//! GNU Backgammon can leave the decisions of a player to an external program with
+//! `set player 1 external localhost:10000`. It then connects to the program, sends the position
+//! as a FIBS board line (see [`Fibs`](crate::formats::Fibs)) whenever the external player has to
+//! decide, and reads the answer: "roll" or "double" before rolling, "take" or "drop" after a
+//! double of the opponent, or the play in the conventional notation, e.g. "24/18 13/11".
+//!
+//! An [`ExternalPlayer`](crate::external::ExternalPlayer) answers with the decisions of an
+//! [`Evaluator`](crate::eval::Evaluator), which lets any bot built on this crate play against GNU
+//! Backgammon:
+//! ```no_run
+//! use backgammon::eval::Heuristic;
+//! use backgammon::external::ExternalPlayer;
+//! use std::io::BufReader;
+//! use std::net::TcpListener;
+//!
+//! let player = ExternalPlayer::new(Heuristic);
+//! let (stream, _) = TcpListener::bind("localhost:10000").unwrap().accept().unwrap();
+//! player.serve(BufReader::new(stream.try_clone().unwrap()), stream).unwrap();
+//! ```
+use crate::cubeful::{cube_decision, CubeContext};
+use crate::eval::{rank_plays, Evaluator};
+use crate::formats::Fibs;
+use crate::game::{Game, Phase};
+use crate::met::Met;
+use crate::rules::{Play, Player, Rules};
+use crate::Error;
+
+use std::io::{self, BufRead, Write};
+
+/// The match length of FIBS board lines of unlimited matches
+const UNLIMITED: u32 = 9999;
+
+/// Answers the requests of GNU Backgammon for the decisions of an external player
+#[derive(Debug, Clone)]
+pub struct ExternalPlayer<E> {
+    /// The evaluator deciding the plays and the cube actions
+    pub evaluator: E,
+    /// The match equity table used for cube decisions in matches
+    pub met: Met,
+}
+
+impl<E: Evaluator> ExternalPlayer<E> {
+    /// Create an external player deciding with the given evaluator
+    pub fn new(evaluator: E) -> Self {
+        ExternalPlayer {
+            evaluator,
+            met: Met::default(),
+        }
+    }
+
+    /// Set the match equity table used for cube decisions in matches
+    pub fn with_met(mut self, met: Met) -> Self {
+        self.met = met;
+        self
+    }
+
+    /// Returns the answer to a FIBS board line, in which the external player is "You".
+    /// ```
+    /// use backgammon::eval::Heuristic;
+    /// use backgammon::external::ExternalPlayer;
+    ///
+    /// let player = ExternalPlayer::new(Heuristic);
+    /// let board = "board:You:gnubg:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:3:1:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0";
+    /// assert_eq!(player.respond(board).unwrap(), "8/5 6/5");
+    /// ```
+    pub fn respond(&self, line: &str) -> Result<String, Error> {
+        let board: Fibs = line.parse()?;
+        let game = game(&board)?;
+        match (game.phase, game.who_plays) {
+            (Phase::AwaitingCubeDecision, Player::Player1) => {
+                let evaluation = self.evaluator.evaluate_game(&game)?;
+                let context = CubeContext::from_game(&game);
+                let take =
+                    cube_decision(&evaluation, &context, &self.met).map_or(true, |d| d.take());
+                Ok(if take { "take" } else { "drop" }.to_string())
+            }
+            (Phase::AwaitingRoll, Player::Player0) => {
+                if !board.may_double.0 || !game.cube_is_live(Player::Player0) {
+                    return Ok("roll".to_string());
+                }
+                let evaluation = self.evaluator.evaluate_game(&game)?;
+                let context = CubeContext::from_game(&game);
+                let double =
+                    cube_decision(&evaluation, &context, &self.met).is_ok_and(|d| d.double());
+                Ok(if double { "double" } else { "roll" }.to_string())
+            }
+            (Phase::AwaitingMove, Player::Player0) => {
+                let best = rank_plays(&game, &self.evaluator)?
+                    .into_iter()
+                    .next()
+                    .map(|(play, _)| play)
+                    .unwrap_or_default();
+                Ok(notation(best))
+            }
+            (Phase::GameOver, _) => Err(Error::GameEnded),
+            _ => Err(Error::NotYourTurn),
+        }
+    }
+
+    /// Answer every board line read from GNU Backgammon until the connection is closed. Lines
+    /// which cannot be answered are answered with the error.
+    pub fn serve(&self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let answer = self
+                .respond(&line)
+                .unwrap_or_else(|e| format!("Error: {}", e));
+            write!(output, "{}\r\n", answer)?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the game described by a board line, including the score of the match
+fn game(board: &Fibs) -> Result<Game, Error> {
+    let mut game = match board.match_length {
+        0 | UNLIMITED => Game::new(),
+        length => {
+            let away = |score: u32| u64::from(length.saturating_sub(score).max(1));
+            let since_crawford = u8::from(board.did_crawford);
+            let away = (away(board.score.0), away(board.score.1));
+            Game::for_match(Rules::default(), false, since_crawford, away)
+        }
+    };
+    board.apply(&mut game)?;
+    Ok(game)
+}
+
+/// Returns a play in the conventional notation without marking hits
+fn notation(mut play: Play) -> String {
+    for m in play.moves.iter_mut() {
+        m.hits.clear();
+    }
+    play.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Heuristic;
+    use crate::rules::Point;
+
+    /// The starting position with player 0 on roll, with the given dices, cube value, may
+    /// double flags, and was doubled flag
+    fn board(dices: (u8, u8), cube: u32, may_double: (u8, u8), doubled: u8) -> String {
+        format!(
+            "board:You:gnubg:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:{}:{}:0:0:{}:{}:{}:{}:1:-1:0:25:0:0:0:0:2:0:0:0",
+            dices.0, dices.1, cube, may_double.0, may_double.1, doubled
+        )
+    }
+
+    #[test]
+    fn test_respond() -> Result<(), Error> {
+        let player = ExternalPlayer::new(Heuristic);
+        assert_eq!(player.respond(&board((6, 5), 1, (1, 1), 0))?, "24/18 18/13");
+        // the opening position is not worth a double
+        assert_eq!(player.respond(&board((0, 0), 1, (1, 1), 0))?, "roll");
+        assert_eq!(player.respond(&board((0, 0), 2, (0, 1), 0))?, "roll");
+        // a double in the opening position is taken
+        assert_eq!(player.respond(&board((0, 0), 1, (1, 1), 1))?, "take");
+        assert!(player.respond("board:You:gnubg").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cube() -> Result<(), Error> {
+        // a race in which player 0 needs 40 pips and player 1 needs 90 pips
+        let mut g = Game::new();
+        g.board.points = [Point::EMPTY; 24];
+        g.board.set(Player::Player0, 5, 5)?;
+        g.board.set(Player::Player0, 0, 10)?;
+        g.board.set(Player::Player1, 5, 15)?;
+        g.who_plays = Player::Player0;
+        let player = ExternalPlayer::new(Heuristic);
+        let line = Fibs::from_game(&g, 0, (0, 0)).to_string();
+        assert_eq!(player.respond(&line)?, "double");
+        // the cube is dead for the leader of a match at 1-away
+        let line = Fibs::from_game(&g, 5, (4, 0)).to_string();
+        assert_eq!(player.respond(&line)?, "roll");
+
+        // the same race with the roles of the players swapped, and player 1 doubling
+        g.board = g.board.flipped();
+        g.who_plays = Player::Player1;
+        g.offer_double(Player::Player1)?;
+        let line = Fibs::from_game(&g, 0, (0, 0)).to_string();
+        assert_eq!(player.respond(&line)?, "drop");
+        Ok(())
+    }
+
+    #[test]
+    fn test_serve() -> Result<(), io::Error> {
+        let player = ExternalPlayer::new(Heuristic);
+        let input = format!("{}\n\nboard:You\n", board((3, 1), 1, (1, 1), 0));
+        let mut output = Vec::new();
+        player.serve(input.as_bytes(), &mut output)?;
+        assert_eq!(
+            String::from_utf8_lossy(&output),
+            "8/5 6/5\r\nError: Invalid format\r\n"
+        );
+        Ok(())
+    }
+}
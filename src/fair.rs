@@ -0,0 +1,358 @@
+//! # Verifiable Fair Dices
+//!
+//! Networked games usually roll the dices on the server, and the players have to trust the server
+//! not to cheat. This module implements a commit-reveal scheme which lets the players verify the
+//! dices after the game:
+//!
+//! 1. The server creates a secret and sends its [`Commitment`](crate::fair::Commitment), the
+//!    SHA-256 hash of the secret, to the client.
+//! 2. The client creates a secret of its own and sends it to the server. As the server is bound to
+//!    its commitment, it can no longer choose dices in its favor.
+//! 3. The dices of the game are rolled by [`FairDice`](crate::fair::FairDice), derived from both
+//!    secrets.
+//! 4. After the game, the server reveals its secret, and the client verifies the commitment and
+//!    all dices rolled with [`verify_record`](crate::fair::verify_record).
+//!
+//! ```
+//! use backgammon::Game;
+//! use backgammon::fair::{verify_record, FairDice, Secret};
+//! use backgammon::rules::Roll;
+//!
+//! // the server commits to its secret
+//! let server = Secret::random();
+//! let commitment = server.commitment();
+//!
+//! // the client contributes its secret, which the server uses to roll the dices
+//! let client = Secret::random();
+//! let mut g = Game::new().with_dice_source(FairDice::new(server, client));
+//! g.roll().unwrap();
+//!
+//! // after the game, the client verifies the dices with the revealed secret of the server
+//! assert!(verify_record(&commitment, &server, &client, g.record()).is_ok());
+//! ```
+use crate::record::{Event, GameRecord};
+use crate::rules::DiceSource;
+use crate::Error;
+use rand::Rng;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The round constants of SHA-256
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Returns the SHA-256 hash of a message
+fn sha256(message: &[u8]) -> [u8; 32] {
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend((message.len() as u64 * 8).to_be_bytes());
+
+    for block in padded.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut hash = [0; 32];
+    for (bytes, word) in hash.chunks_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    hash
+}
+
+/// Write bytes as lower case hexadecimal digits
+fn write_hex(f: &mut fmt::Formatter, bytes: &[u8; 32]) -> fmt::Result {
+    for b in bytes {
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+/// Parse 32 bytes written as hexadecimal digits
+fn parse_hex(s: &str) -> Result<[u8; 32], Error> {
+    if s.len() != 64 || !s.is_ascii() {
+        return Err(Error::FormatInvalid);
+    }
+    let mut bytes = [0; 32];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).map_err(|_| Error::FormatInvalid)?;
+    }
+    Ok(bytes)
+}
+
+/// Represents the secret entropy one side contributes to the dices, written as 64 hexadecimal
+/// digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Secret(pub [u8; 32]);
+
+impl Secret {
+    /// Create a secret with the operating system's random number generator
+    pub fn random() -> Self {
+        Secret(rand::thread_rng().gen())
+    }
+
+    /// Returns the commitment to this secret, which can be published before the secret is revealed
+    pub fn commitment(&self) -> Commitment {
+        Commitment(sha256(&self.0))
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+impl FromStr for Secret {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Secret(parse_hex(s)?))
+    }
+}
+
+/// Represents the commitment to a secret, i.e. its SHA-256 hash, written as 64 hexadecimal digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment(pub [u8; 32]);
+
+impl Commitment {
+    /// Verify that a revealed secret is the one committed to
+    pub fn verify(&self, secret: &Secret) -> Result<(), Error> {
+        if secret.commitment() != *self {
+            return Err(Error::CommitmentInvalid);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+impl FromStr for Commitment {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Commitment(parse_hex(s)?))
+    }
+}
+
+/// Returns the dices of a roll, counted from 0, derived from the secrets of both sides
+pub fn derive_roll(server: &Secret, client: &Secret, roll: u64) -> (u8, u8) {
+    let mut message = [0; 72];
+    message[..32].copy_from_slice(&server.0);
+    message[32..64].copy_from_slice(&client.0);
+    message[64..].copy_from_slice(&roll.to_be_bytes());
+    let mut hash = sha256(&message);
+    loop {
+        // every byte below 252 is one of the 36 rolls with the same probability
+        if let Some(b) = hash.iter().find(|b| **b < 252) {
+            return (b % 36 / 6 + 1, b % 6 + 1);
+        }
+        hash = sha256(&hash);
+    }
+}
+
+/// Rolls the dices derived from the secrets of the server and the client, see the
+/// [module documentation](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FairDice {
+    /// The secret of the server
+    server: Secret,
+    /// The secret of the client
+    client: Secret,
+    /// The number of dices rolled so far
+    rolls: u64,
+}
+
+impl FairDice {
+    /// Create dices derived from the secrets of the server and the client
+    pub fn new(server: Secret, client: Secret) -> Self {
+        FairDice {
+            server,
+            client,
+            rolls: 0,
+        }
+    }
+}
+
+impl DiceSource for FairDice {
+    fn roll(&mut self) -> (u8, u8) {
+        let roll = derive_roll(&self.server, &self.client, self.rolls);
+        self.rolls += 1;
+        roll
+    }
+}
+
+/// Verify that the server revealed the secret it committed to, and that the dices rolled, in the
+/// order they were rolled, are derived from the secrets of the server and the client
+pub fn verify_rolls(
+    commitment: &Commitment,
+    server: &Secret,
+    client: &Secret,
+    rolls: &[(u8, u8)],
+) -> Result<(), Error> {
+    commitment.verify(server)?;
+    for (i, roll) in (0..).zip(rolls) {
+        if derive_roll(server, client, i) != *roll {
+            return Err(Error::DiceInvalid);
+        }
+    }
+    Ok(())
+}
+
+/// Verify all dices rolled in a game, see [`verify_rolls`]
+pub fn verify_record(
+    commitment: &Commitment,
+    server: &Secret,
+    client: &Secret,
+    record: &GameRecord,
+) -> Result<(), Error> {
+    let rolls: Vec<(u8, u8)> = record
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            Event::Roll { dices, .. } => Some(*dices),
+            _ => None,
+        })
+        .collect();
+    verify_rolls(commitment, server, client, &rolls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, Phase};
+    use crate::rules::Roll;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            Commitment(sha256(b"")).to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            Commitment(sha256(b"abc")).to_string(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // a message longer than one block
+        assert_eq!(
+            Commitment(sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            ))
+            .to_string(),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_hex() -> Result<(), Error> {
+        let secret = Secret::random();
+        assert_eq!(secret.to_string().parse::<Secret>()?, secret);
+        let commitment = secret.commitment();
+        assert_eq!(commitment.to_string().parse::<Commitment>()?, commitment);
+        assert!("00".parse::<Secret>().is_err());
+        assert!("zz".repeat(32).parse::<Commitment>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_roll() {
+        let (server, client) = (Secret([1; 32]), Secret([2; 32]));
+        let mut counts = [0; 36];
+        for i in 0..3600 {
+            let roll = derive_roll(&server, &client, i);
+            assert_eq!(roll, derive_roll(&server, &client, i));
+            counts[usize::from((roll.0 - 1) * 6 + roll.1 - 1)] += 1;
+        }
+        // every roll shows up
+        assert!(counts.iter().all(|c| *c > 0));
+        // the client's secret changes the dices
+        let other = Secret([3; 32]);
+        assert!(
+            (0..10).any(|i| derive_roll(&server, &client, i) != derive_roll(&server, &other, i))
+        );
+    }
+
+    #[test]
+    fn test_verify() -> Result<(), Error> {
+        let (server, client) = (Secret::random(), Secret::random());
+        let commitment = server.commitment();
+        let mut g = Game::new().with_dice_source(FairDice::new(server, client));
+        while g.phase == Phase::AwaitingRoll {
+            let _ = g.roll()?;
+        }
+        verify_record(&commitment, &server, &client, g.record())?;
+
+        // another secret than the one committed to
+        assert!(matches!(
+            verify_record(&commitment, &client, &client, g.record()),
+            Err(Error::CommitmentInvalid)
+        ));
+        // dices other than the ones derived
+        let roll = derive_roll(&server, &client, 0);
+        let other = (roll.0 % 6 + 1, roll.1);
+        assert!(matches!(
+            verify_rolls(&commitment, &server, &client, &[other]),
+            Err(Error::DiceInvalid)
+        ));
+        Ok(())
+    }
+}
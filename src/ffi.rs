@@ -0,0 +1,388 @@
+//! # C Interface
+//!
+//! With the `ffi` feature, the crate exports a small C ABI to embed the rules engine in C, C++,
+//! Swift, or Unity applications. The declarations are in `include/backgammon.h`, and a library
+//! to link is built by e.g.
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+//! A game is an opaque handle created by [`bg_game_new`](crate::ffi::bg_game_new) and released by
+//! [`bg_game_free`](crate::ffi::bg_game_free). Functions changing a game return
+//! [`BG_OK`](crate::ffi::BG_OK) or one of the error codes of
+//! [`error_code`](crate::ffi::error_code). Plays and positions are exchanged as UTF-8 strings in
+//! the conventional notation and as [XGIDs](crate::formats::Xgid), and strings returned by the
+//! library are released by [`bg_string_free`](crate::ffi::bg_string_free).
+//!
+//! The cube is offered by the player whose turn it is and taken or passed by the opponent, so
+//! the functions need no player argument.
+#![allow(unsafe_code)]
+
+use crate::formats::Xgid;
+use crate::rules::{Player, Roll};
+use crate::{Error, Game, Phase};
+
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+/// The code returned by a successful call
+pub const BG_OK: c_int = 0;
+/// The code returned for a null pointer or a string which is not valid UTF-8
+pub const BG_ERROR_ARGUMENT: c_int = -1;
+
+/// Returns the code of an error returned by the C interface, which is stable across versions
+pub fn error_code(error: &Error) -> c_int {
+    match error {
+        Error::GameStarted => 1,
+        Error::GameEnded => 2,
+        Error::MatchEnded => 3,
+        Error::SessionEnded => 4,
+        Error::CubeReceived => 5,
+        Error::DoublingNotPermitted => 6,
+        Error::CubeValueInvalid => 7,
+        Error::PlayerInvalid => 8,
+        Error::FieldBlocked => 9,
+        Error::FieldInvalid => 10,
+        Error::NotYourTurn => 11,
+        Error::MoveInvalid => 12,
+        Error::MoveInvalidBar => 13,
+        Error::MoveInvalidMaxDice => 14,
+        Error::MoveInvalidLargerDice => 15,
+        Error::MoveInvalidBearOff => 16,
+        Error::MoveInvalidPinned => 17,
+        Error::MoveInvalidHead => 18,
+        Error::MoveFirst => 19,
+        Error::RollFirst => 20,
+        Error::DiceInvalid => 21,
+        Error::CommitmentInvalid => 22,
+        Error::DatabaseInvalid => 23,
+        Error::ScoreInvalid => 24,
+        Error::NotationInvalid => 25,
+        Error::FormatInvalid => 26,
+        Error::UndoInvalid => 27,
+        Error::RedoInvalid => 28,
+        Error::VariantInvalid => 29,
+        Error::PositionInvalid(_) => 30,
+        Error::TimeExpired => 31,
+        Error::MoveInvalidChecker => 32,
+        Error::MoveInvalidDice => 33,
+        Error::PairingInvalid => 34,
+        Error::MatchNotEnded => 35,
+    }
+}
+
+/// Converts the result of an action to a code
+fn code(result: Result<(), Error>) -> c_int {
+    match result {
+        Ok(()) => BG_OK,
+        Err(e) => error_code(&e),
+    }
+}
+
+/// Returns a string to be released by [`bg_string_free`], or null if it contains a null byte
+fn string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Returns the number of a player, or -1 for nobody
+fn player(player: Player) -> c_int {
+    match player {
+        Player::Player0 => 0,
+        Player::Player1 => 1,
+        Player::Nobody => -1,
+    }
+}
+
+/// Creates a new game with the default rules, to be released by [`bg_game_free`]
+#[no_mangle]
+pub extern "C" fn bg_game_new() -> *mut Game {
+    Box::into_raw(Box::new(Game::new()))
+}
+
+/// Creates a game from an XGID, or returns null if the XGID is invalid
+///
+/// # Safety
+/// `xgid` has to be null or a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_from_xgid(xgid: *const c_char) -> *mut Game {
+    if xgid.is_null() {
+        return ptr::null_mut();
+    }
+    let parsed = CStr::from_ptr(xgid)
+        .to_str()
+        .map_err(|_| Error::FormatInvalid)
+        .and_then(|s| s.parse::<Xgid>());
+    let mut game = Game::new();
+    match parsed.and_then(|xgid| xgid.apply(&mut game)) {
+        Ok(()) => Box::into_raw(Box::new(game)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Releases a game
+///
+/// # Safety
+/// `game` has to be null or a game created by this library which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_free(game: *mut Game) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// Releases a string returned by this library
+///
+/// # Safety
+/// `s` has to be null or a string returned by this library which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn bg_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Returns the player whose turn it is, 0 or 1, or -1 before the opening roll
+///
+/// # Safety
+/// `game` has to be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_turn(game: *const Game) -> c_int {
+    game.as_ref().map_or(-1, |g| player(g.who_plays))
+}
+
+/// Rolls the dices and writes their two values to `dices` unless it is null
+///
+/// # Safety
+/// `game` has to be null or a valid game, and `dices` null or an array of two values.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_roll(game: *mut Game, dices: *mut u8) -> c_int {
+    let Some(game) = game.as_mut() else {
+        return BG_ERROR_ARGUMENT;
+    };
+    if let Err(e) = game.roll() {
+        return error_code(&e);
+    }
+    if !dices.is_null() {
+        *dices = game.dices.values.0;
+        *dices.add(1) = game.dices.values.1;
+    }
+    BG_OK
+}
+
+/// Sets the dices to the given values instead of rolling them, e.g. for dices rolled by the
+/// application
+///
+/// # Safety
+/// `game` has to be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_roll_with(game: *mut Game, a: u8, b: u8) -> c_int {
+    match game.as_mut() {
+        Some(game) => code(game.roll_with((a, b)).map(|_| ())),
+        None => BG_ERROR_ARGUMENT,
+    }
+}
+
+/// Returns the distinct legal plays of the player whose turn it is in the conventional notation,
+/// one per line, or null for a null game. The string has to be released by [`bg_string_free`].
+///
+/// # Safety
+/// `game` has to be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_legal_moves(game: *const Game) -> *mut c_char {
+    let Some(game) = game.as_ref() else {
+        return ptr::null_mut();
+    };
+    let plays: Vec<String> = game
+        .legal_plays()
+        .iter()
+        .map(|(_, play)| play.to_string())
+        .collect();
+    string(plays.join("\n"))
+}
+
+/// Plays the dices rolled as given in the conventional notation, e.g. "24/18 13/9". The game is
+/// only changed if the whole play is legal.
+///
+/// # Safety
+/// `game` has to be null or a valid game, and `play` null or a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_apply(game: *mut Game, play: *const c_char) -> c_int {
+    let (Some(game), false) = (game.as_mut(), play.is_null()) else {
+        return BG_ERROR_ARGUMENT;
+    };
+    let Ok(play) = CStr::from_ptr(play).to_str() else {
+        return BG_ERROR_ARGUMENT;
+    };
+    let result = game.check_phase(Phase::AwaitingMove);
+    let result = result.and_then(|()| game.parse_play(play));
+    code(result.and_then(|play| game.apply_play(&play)))
+}
+
+/// Offers the cube on behalf of the player whose turn it is
+///
+/// # Safety
+/// `game` has to be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_double(game: *mut Game) -> c_int {
+    match game.as_mut() {
+        Some(game) => code(game.offer_double(game.who_plays)),
+        None => BG_ERROR_ARGUMENT,
+    }
+}
+
+/// Takes the cube offered by the player whose turn it is
+///
+/// # Safety
+/// `game` has to be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_take(game: *mut Game) -> c_int {
+    match game.as_mut() {
+        Some(game) => code(game.take(game.who_plays.other())),
+        None => BG_ERROR_ARGUMENT,
+    }
+}
+
+/// Passes the cube offered by the player whose turn it is, which ends the game
+///
+/// # Safety
+/// `game` has to be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_pass(game: *mut Game) -> c_int {
+    match game.as_mut() {
+        Some(game) => code(game.pass(game.who_plays.other())),
+        None => BG_ERROR_ARGUMENT,
+    }
+}
+
+/// Returns the points won once the game has ended and writes the winner, 0 or 1, to `winner`
+/// unless it is null. Returns 0 while the game is running.
+///
+/// # Safety
+/// `game` has to be null or a valid game, and `winner` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_result(game: *const Game, winner: *mut c_int) -> u64 {
+    let Some(result) = game.as_ref().and_then(Game::result) else {
+        return 0;
+    };
+    if !winner.is_null() {
+        *winner = player(result.winner);
+    }
+    result.points
+}
+
+/// Returns the position as an XGID, to be released by [`bg_string_free`], or null for a null game
+///
+/// # Safety
+/// `game` has to be null or a valid game.
+#[no_mangle]
+pub unsafe extern "C" fn bg_game_xgid(game: *const Game) -> *mut c_char {
+    match game.as_ref() {
+        Some(game) => string(Xgid::from_game(game, 0, (0, 0)).to_string()),
+        None => ptr::null_mut(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a string returned by the library, releasing it
+    unsafe fn take_string(s: *mut c_char) -> String {
+        let string = CStr::from_ptr(s).to_string_lossy().into_owned();
+        bg_string_free(s);
+        string
+    }
+
+    #[test]
+    fn test_game() {
+        unsafe {
+            let game = bg_game_new();
+            assert_eq!(bg_game_turn(game), -1);
+            assert_eq!(bg_game_roll_with(game, 3, 1), BG_OK);
+            assert_eq!(bg_game_turn(game), 0);
+            let moves = take_string(bg_game_legal_moves(game));
+            assert!(moves.lines().any(|m| m == "8/5 6/5"));
+
+            let play = CString::new("8/5").unwrap_or_default();
+            assert_eq!(bg_game_apply(game, play.as_ptr()), 14);
+            let play = CString::new("8/5 6/5").unwrap_or_default();
+            assert_eq!(bg_game_apply(game, play.as_ptr()), BG_OK);
+            assert_eq!(bg_game_turn(game), 1);
+            assert_eq!(bg_game_apply(game, play.as_ptr()), 20);
+
+            let mut dices = [0u8; 2];
+            assert_eq!(bg_game_roll(game, dices.as_mut_ptr()), BG_OK);
+            assert!(dices.iter().all(|d| (1..=6).contains(d)));
+
+            let xgid = take_string(bg_game_xgid(game));
+            let xgid = CString::new(xgid).unwrap_or_default();
+            let copy = bg_game_from_xgid(xgid.as_ptr());
+            assert!(!copy.is_null());
+            assert_eq!((*copy).board, (*game).board);
+            bg_game_free(copy);
+            bg_game_free(game);
+
+            assert_eq!(
+                bg_game_apply(ptr::null_mut(), play.as_ptr()),
+                BG_ERROR_ARGUMENT
+            );
+            assert!(bg_game_legal_moves(ptr::null()).is_null());
+            let invalid = CString::new("XGID=").unwrap_or_default();
+            assert!(bg_game_from_xgid(invalid.as_ptr()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_cube() {
+        unsafe {
+            let game = bg_game_new();
+            assert_eq!(bg_game_double(game), 8);
+            assert_eq!(bg_game_roll_with(game, 3, 1), BG_OK);
+            let play = CString::new("8/5 6/5").unwrap_or_default();
+            assert_eq!(bg_game_apply(game, play.as_ptr()), BG_OK);
+            assert_eq!(bg_game_double(game), BG_OK);
+            assert_eq!(bg_game_take(game), BG_OK);
+            assert_eq!(bg_game_result(game, ptr::null_mut()), 0);
+            assert_eq!(bg_game_roll_with(game, 6, 5), BG_OK);
+            let play = CString::new("24/13").unwrap_or_default();
+            assert_eq!(bg_game_apply(game, play.as_ptr()), BG_OK);
+            assert_eq!(bg_game_double(game), BG_OK);
+            assert_eq!(bg_game_pass(game), BG_OK);
+            let mut winner = -1;
+            assert_eq!(bg_game_result(game, &mut winner), 2);
+            assert_eq!(winner, 0);
+            bg_game_free(game);
+        }
+    }
+
+    #[test]
+    fn test_header() {
+        // every function is declared in the header
+        let header = include_str!("../include/backgammon.h");
+        for function in [
+            "bg_game_new(",
+            "bg_game_from_xgid(",
+            "bg_game_free(",
+            "bg_string_free(",
+            "bg_game_turn(",
+            "bg_game_roll(",
+            "bg_game_roll_with(",
+            "bg_game_legal_moves(",
+            "bg_game_apply(",
+            "bg_game_double(",
+            "bg_game_take(",
+            "bg_game_pass(",
+            "bg_game_result(",
+            "bg_game_xgid(",
+        ] {
+            assert!(header.contains(function), "{}", function);
+        }
+        for e in [
+            Error::GameStarted,
+            Error::PositionInvalid(crate::PositionError::TurnInvalid),
+            Error::TimeExpired,
+        ] {
+            let define = format!(" {}\n", error_code(&e));
+            assert!(header.contains(&define));
+        }
+    }
+}
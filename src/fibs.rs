@@ -0,0 +1,774 @@
+//! # FIBS Client Protocol
+//!
+//! The First Internet Backgammon Server (FIBS) is played over a telnet connection. This module
+//! implements the protocol without doing any I/O: lines received from the server are parsed into
+//! [`Message`](crate::fibs::Message)s, and [`Command`](crate::fibs::Command)s are written as lines
+//! to send, each terminated by "\r\n". Clients should log in with the FIBS Client Interface
+//! Protocol (CLIP) and enable "boardstyle 3" to receive the board lines described by
+//! [`Fibs`](crate::formats::Fibs).
+//!
+//! A [`Client`](crate::fibs::Client) keeps the last board line received, to describe the position
+//! as a [`Game`] and to translate plays into the numbering of points used by FIBS:
+//! ```
+//! use backgammon::fibs::{Client, Command, Message};
+//! use backgammon::rules::Play;
+//!
+//! let mut client = Client::new("alice", "secret");
+//! assert_eq!(client.handle("login: ").unwrap(), Message::LoginPrompt);
+//! assert_eq!(client.login().to_string(), "login backgammon-rs 1008 alice secret");
+//! assert_eq!(Command::BoardStyle.to_string(), "set boardstyle 3");
+//!
+//! let line = "board:You:bob:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:6:2:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0";
+//! let _ = client.handle(line).unwrap();
+//! let game = client.game().unwrap();
+//! assert_eq!(game.dices.values, (6, 2));
+//!
+//! let play = game.parse_play("24/18 13/11").unwrap();
+//! let command = client.move_command(&play).unwrap();
+//! assert_eq!(command.to_string(), "move 24-18 13-11");
+//! ```
+use crate::formats::Fibs;
+use crate::game::{Game, Victory};
+use crate::rules::{Field, Play};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The version of CLIP spoken by this module
+const CLIP_VERSION: u32 = 1008;
+
+/// The kind of a chat message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ChatKind {
+    /// A message to a single player, sent by "tell"
+    Says,
+    /// A message to all players, sent by "shout"
+    Shouts,
+    /// A message to the watchers of a game, sent by "whisper"
+    Whispers,
+    /// A message to the players and watchers of a game, sent by "kibitz"
+    Kibitzes,
+}
+
+/// Represents a player listed by the "rawwho" command or announced when their status changes
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WhoInfo {
+    /// The name of the player
+    pub name: String,
+    /// The opponent of the player, if playing
+    pub opponent: Option<String>,
+    /// The player watched by the player, if watching
+    pub watching: Option<String>,
+    /// Whether the player is ready to play
+    pub ready: bool,
+    /// Whether the player is away
+    pub away: bool,
+    /// The rating of the player
+    pub rating: f64,
+    /// The number of points the player has played in rated matches
+    pub experience: u32,
+    /// The seconds since the last command of the player
+    pub idle: u64,
+    /// The time of login of the player in seconds since the Unix epoch
+    pub login: u64,
+    /// The host the player is connected from
+    pub hostname: String,
+    /// The client of the player, if known
+    pub client: Option<String>,
+    /// The email address of the player, if known
+    pub email: Option<String>,
+}
+
+/// Represents a line received from FIBS
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Message {
+    /// The server asks to log in, answer with [`Client::login`]
+    LoginPrompt,
+    /// The login has succeeded, with the time and host of the previous login
+    Welcome {
+        /// The name of the user
+        name: String,
+        /// The time of the previous login in seconds since the Unix epoch
+        last_login: u64,
+        /// The host of the previous login
+        last_host: String,
+    },
+    /// The settings of the user, sent after the login
+    OwnInfo {
+        /// The name of the user
+        name: String,
+        /// Whether the user is away
+        away: bool,
+        /// The number of points the user has played in rated matches
+        experience: u32,
+        /// The rating of the user
+        rating: f64,
+        /// Whether the user is ready to play
+        ready: bool,
+    },
+    /// The message of the day follows
+    MotdStart,
+    /// The message of the day has ended
+    MotdEnd,
+    /// A player as listed by "rawwho", or whose status has changed
+    Who(WhoInfo),
+    /// The list of players has ended
+    WhoEnd,
+    /// A player has logged in
+    Login {
+        /// The name of the player
+        name: String,
+        /// The message announcing the login
+        message: String,
+    },
+    /// A player has logged out
+    Logout {
+        /// The name of the player
+        name: String,
+        /// The message announcing the logout
+        message: String,
+    },
+    /// A message left for the user while logged out
+    Message {
+        /// The sender of the message
+        from: String,
+        /// The time the message was sent in seconds since the Unix epoch
+        time: u64,
+        /// The text of the message
+        message: String,
+    },
+    /// A message left by the user has been delivered to the given player
+    Delivered(String),
+    /// A message left by the user has been saved for the given player
+    Saved(String),
+    /// A chat message of another player
+    Chat {
+        /// The kind of the message
+        kind: ChatKind,
+        /// The sender of the message
+        from: String,
+        /// The text of the message
+        message: String,
+    },
+    /// The echo of a chat message of the user
+    ChatSent {
+        /// The kind of the message
+        kind: ChatKind,
+        /// The recipient of a message sent by "tell"
+        to: Option<String>,
+        /// The text of the message
+        message: String,
+    },
+    /// The state of the game played or watched
+    Board(Fibs),
+    /// A player invites the user to a match, join with [`Command::Join`]
+    Invitation {
+        /// The name of the player
+        name: String,
+        /// The length of the match, `None` for an unlimited match
+        match_length: Option<u32>,
+    },
+    /// A player invites the user to resume a saved match
+    Resume {
+        /// The name of the player
+        name: String,
+    },
+    /// A player, or "You", has rolled the dices
+    Rolls {
+        /// The name of the player
+        name: String,
+        /// The dices rolled
+        dices: (u8, u8),
+    },
+    /// A player has moved, in the numbering of points of the server, e.g. "8-5 6-5"
+    Moves {
+        /// The name of the player
+        name: String,
+        /// The moves of single checkers
+        moves: String,
+    },
+    /// A player has doubled, answer with [`Command::Accept`] or [`Command::Reject`]
+    Doubles {
+        /// The name of the player
+        name: String,
+    },
+    /// A player has accepted the double
+    Accepts {
+        /// The name of the player
+        name: String,
+    },
+    /// The user has to move the given number of checkers
+    PleaseMove(u8),
+    /// A player, or "You", has won a game
+    WinsGame {
+        /// The name of the player
+        name: String,
+        /// The points won
+        points: u64,
+    },
+    /// The server has rejected a command, e.g. "** You can't double."
+    Error(String),
+    /// Any other line
+    Other(String),
+}
+
+impl FromStr for Message {
+    type Err = Error;
+
+    /// Parse a line received from FIBS. Lines which are not understood are returned as
+    /// [`Message::Other`], but malformed CLIP lines and board lines are an error.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = s.trim_end_matches(['\r', '\n']);
+        if line.starts_with("login:") {
+            return Ok(Message::LoginPrompt);
+        }
+        if line.starts_with("board:") {
+            return Ok(Message::Board(line.parse()?));
+        }
+        if let Some(error) = line.strip_prefix("** ") {
+            return Ok(Message::Error(error.to_string()));
+        }
+        match line.split_once(' ').unwrap_or((line, "")) {
+            (code, rest) if code.len() <= 2 => match code.parse::<u8>() {
+                Ok(code @ 1..=19) => parse_clip(code, rest),
+                _ => Ok(parse_game(line)),
+            },
+            _ => Ok(parse_game(line)),
+        }
+    }
+}
+
+/// Parse a line of CLIP with the given code
+fn parse_clip(code: u8, rest: &str) -> Result<Message, Error> {
+    let tokens: Vec<&str> = rest.split_whitespace().collect();
+    let token = |i: usize| tokens.get(i).copied().ok_or(Error::FormatInvalid);
+    let number = |i: usize| token(i)?.parse::<u64>().map_err(|_| Error::FormatInvalid);
+    let flag = |i: usize| match token(i)? {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(Error::FormatInvalid),
+    };
+    let optional = |i: usize| token(i).map(|t| (t != "-").then(|| t.to_string()));
+    // the name and the message following it
+    let (name, text) = rest.split_once(' ').unwrap_or((rest, ""));
+    let named = || match name.is_empty() {
+        true => Err(Error::FormatInvalid),
+        false => Ok((name.to_string(), text.to_string())),
+    };
+    let chat = |kind: ChatKind| {
+        let (from, message) = named()?;
+        Ok(Message::Chat {
+            kind,
+            from,
+            message,
+        })
+    };
+    let sent = |kind: ChatKind| Message::ChatSent {
+        kind,
+        to: None,
+        message: rest.to_string(),
+    };
+
+    match code {
+        1 => Ok(Message::Welcome {
+            name: token(0)?.to_string(),
+            last_login: number(1)?,
+            last_host: token(2)?.to_string(),
+        }),
+        2 => Ok(Message::OwnInfo {
+            name: token(0)?.to_string(),
+            away: flag(5)?,
+            experience: u32::try_from(number(9)?).map_err(|_| Error::FormatInvalid)?,
+            rating: token(14)?.parse().map_err(|_| Error::FormatInvalid)?,
+            ready: flag(16)?,
+        }),
+        3 => Ok(Message::MotdStart),
+        4 => Ok(Message::MotdEnd),
+        5 => Ok(Message::Who(WhoInfo {
+            name: token(0)?.to_string(),
+            opponent: optional(1)?,
+            watching: optional(2)?,
+            ready: flag(3)?,
+            away: flag(4)?,
+            rating: token(5)?.parse().map_err(|_| Error::FormatInvalid)?,
+            experience: u32::try_from(number(6)?).map_err(|_| Error::FormatInvalid)?,
+            idle: number(7)?,
+            login: number(8)?,
+            hostname: token(9)?.to_string(),
+            client: optional(10)?,
+            email: optional(11)?,
+        })),
+        6 => Ok(Message::WhoEnd),
+        7 => named().map(|(name, message)| Message::Login { name, message }),
+        8 => named().map(|(name, message)| Message::Logout { name, message }),
+        9 => {
+            let (time, message) = text.split_once(' ').unwrap_or((text, ""));
+            Ok(Message::Message {
+                from: named()?.0,
+                time: time.parse().map_err(|_| Error::FormatInvalid)?,
+                message: message.to_string(),
+            })
+        }
+        10 => named().map(|(name, _)| Message::Delivered(name)),
+        11 => named().map(|(name, _)| Message::Saved(name)),
+        12 => chat(ChatKind::Says),
+        13 => chat(ChatKind::Shouts),
+        14 => chat(ChatKind::Whispers),
+        15 => chat(ChatKind::Kibitzes),
+        16 => {
+            let (to, message) = named()?;
+            Ok(Message::ChatSent {
+                kind: ChatKind::Says,
+                to: Some(to),
+                message,
+            })
+        }
+        17 => Ok(sent(ChatKind::Shouts)),
+        18 => Ok(sent(ChatKind::Whispers)),
+        _ => Ok(sent(ChatKind::Kibitzes)),
+    }
+}
+
+/// Parse a line in plain text announcing an event of a game, or return it as
+/// [`Message::Other`]
+fn parse_game(line: &str) -> Message {
+    let other = || Message::Other(line.to_string());
+    let text = line.trim_end();
+    let (name, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let name = name.to_string();
+
+    if let Some(invitation) = rest.strip_prefix("wants to play ") {
+        let match_length = match invitation {
+            "an unlimited match with you." => None,
+            _ => match invitation
+                .strip_prefix("a ")
+                .and_then(|i| i.strip_suffix(" point match with you."))
+                .and_then(|n| n.parse().ok())
+            {
+                Some(length) => Some(length),
+                None => return other(),
+            },
+        };
+        return Message::Invitation { name, match_length };
+    }
+    if rest == "wants to resume a saved match with you." {
+        return Message::Resume { name };
+    }
+    if let Some(dices) = rest
+        .strip_prefix("rolls ")
+        .or_else(|| rest.strip_prefix("roll "))
+    {
+        let dices = dices.trim_end_matches('.').split_once(" and ");
+        return match dices.map(|(a, b)| (a.parse::<u8>(), b.parse::<u8>())) {
+            Some((Ok(a), Ok(b))) if (1..=6).contains(&a) && (1..=6).contains(&b) => {
+                Message::Rolls {
+                    name,
+                    dices: (a, b),
+                }
+            }
+            _ => other(),
+        };
+    }
+    if let Some(moves) = rest.strip_prefix("moves ") {
+        let moves = moves.trim_end_matches('.').trim_end();
+        return Message::Moves {
+            name,
+            moves: moves.to_string(),
+        };
+    }
+    if rest.starts_with("doubles.") {
+        return Message::Doubles { name };
+    }
+    if rest.starts_with("accepts the double") {
+        return Message::Accepts { name };
+    }
+    if let Some(points) = rest
+        .strip_prefix("wins the game and gets ")
+        .or_else(|| rest.strip_prefix("win the game and get "))
+    {
+        return match points.split_once(' ').map(|(n, _)| n.parse()) {
+            Some(Ok(points)) => Message::WinsGame { name, points },
+            _ => other(),
+        };
+    }
+    if name == "Please" {
+        if let Some(Ok(checkers)) = rest
+            .strip_prefix("move ")
+            .and_then(|m| m.split_once(' '))
+            .map(|(n, _)| n.parse())
+        {
+            return Message::PleaseMove(checkers);
+        }
+    }
+    other()
+}
+
+/// Represents a command sent to FIBS
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Command {
+    /// Log in with CLIP
+    Login {
+        /// The name of the client software
+        client: String,
+        /// The name of the user
+        name: String,
+        /// The password of the user
+        password: String,
+    },
+    /// Enable the board lines described by [`Fibs`]
+    BoardStyle,
+    /// List the players logged in, answered by [`Message::Who`] lines
+    Who,
+    /// Invite a player to a match, unlimited if the length is `None`
+    Invite {
+        /// The name of the player
+        name: String,
+        /// The length of the match
+        match_length: Option<u32>,
+    },
+    /// Accept the invitation of a player
+    Join(String),
+    /// Request the board line of the current game
+    Board,
+    /// Roll the dices
+    Roll,
+    /// Move checkers, in the numbering of points of the server, see [`Client::move_command`]
+    Move(String),
+    /// Offer the cube
+    Double,
+    /// Accept a double, or the resignation of the opponent
+    Accept,
+    /// Reject a double, or the resignation of the opponent
+    Reject,
+    /// Offer to resign the game with the given victory of the opponent
+    Resign(Victory),
+    /// Send a message to a single player
+    Tell {
+        /// The name of the player
+        name: String,
+        /// The text of the message
+        message: String,
+    },
+    /// Send a message to the opponent
+    Say(String),
+}
+
+// Display commands as the lines sent to the server, without the terminating "\r\n"
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Login {
+                client,
+                name,
+                password,
+            } => write!(f, "login {} {} {} {}", client, CLIP_VERSION, name, password),
+            Command::BoardStyle => write!(f, "set boardstyle 3"),
+            Command::Who => write!(f, "rawwho"),
+            Command::Invite {
+                name,
+                match_length: Some(length),
+            } => write!(f, "invite {} {}", name, length),
+            Command::Invite {
+                name,
+                match_length: None,
+            } => write!(f, "invite {} unlimited", name),
+            Command::Join(name) => write!(f, "join {}", name),
+            Command::Board => write!(f, "board"),
+            Command::Roll => write!(f, "roll"),
+            Command::Move(moves) => write!(f, "move {}", moves),
+            Command::Double => write!(f, "double"),
+            Command::Accept => write!(f, "accept"),
+            Command::Reject => write!(f, "reject"),
+            Command::Resign(victory) => match victory {
+                Victory::Single => write!(f, "resign n"),
+                Victory::Gammon => write!(f, "resign g"),
+                Victory::Backgammon => write!(f, "resign b"),
+            },
+            Command::Tell { name, message } => write!(f, "tell {} {}", name, message),
+            Command::Say(message) => write!(f, "say {}", message),
+        }
+    }
+}
+
+/// Represents the state of a connection to FIBS
+#[derive(Clone)]
+pub struct Client {
+    /// The name of the user
+    pub name: String,
+    /// The name of the client software sent when logging in
+    pub client: String,
+    /// The password of the user
+    password: String,
+    /// The last board line received and the direction of the user in it
+    board: Option<(Fibs, i32)>,
+}
+
+// The password is not shown
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("name", &self.name)
+            .field("client", &self.client)
+            .field("board", &self.board)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Create a client for the given user
+    pub fn new(name: &str, password: &str) -> Self {
+        Client {
+            name: name.to_string(),
+            client: "backgammon-rs".to_string(),
+            password: password.to_string(),
+            board: None,
+        }
+    }
+
+    /// Set the name of the client software sent when logging in
+    pub fn with_client(mut self, client: &str) -> Self {
+        self.client = client.to_string();
+        self
+    }
+
+    /// Returns the command to log in, to be sent after the [`Message::LoginPrompt`]
+    pub fn login(&self) -> Command {
+        Command::Login {
+            client: self.client.clone(),
+            name: self.name.clone(),
+            password: self.password.clone(),
+        }
+    }
+
+    /// Parse a line received from the server and keep the board lines.
+    pub fn handle(&mut self, line: &str) -> Result<Message, Error> {
+        let message: Message = line.parse()?;
+        if let Message::Board(board) = &message {
+            // the direction is not kept by the board, but needed to number the points
+            let direction = match line.trim().split(':').nth(42) {
+                Some("1") => 1,
+                _ => -1,
+            };
+            self.board = Some((board.clone(), direction));
+        }
+        Ok(message)
+    }
+
+    /// Returns the last board line received
+    pub fn board(&self) -> Option<&Fibs> {
+        self.board.as_ref().map(|(board, _)| board)
+    }
+
+    /// Returns the game described by the last board line received, as seen by the user
+    pub fn game(&self) -> Option<Game> {
+        let (board, _) = self.board.as_ref()?;
+        let mut game = Game::new();
+        board.apply(&mut game).ok()?;
+        Some(game)
+    }
+
+    /// Returns the command to make a play of the user, with a single dice value per checker
+    /// moved as expected by the server.
+    ///
+    /// The points of FIBS depend on the direction of the user, hence this fails with
+    /// [`Error::FormatInvalid`] if no board line has been received yet.
+    pub fn move_command(&self, play: &Play) -> Result<Command, Error> {
+        let (_, direction) = self.board.as_ref().ok_or(Error::FormatInvalid)?;
+        let point = |field: Field| match field {
+            Field::Bar => "bar".to_string(),
+            Field::Off => "off".to_string(),
+            Field::Point(field) if *direction < 0 => (field + 1).to_string(),
+            Field::Point(field) => (24 - field).to_string(),
+        };
+        let mut moves = Vec::new();
+        for m in play.moves.iter() {
+            let mut from = m.from;
+            for to in m.path()? {
+                moves.push(format!("{}-{}", point(from), point(to)));
+                from = to;
+            }
+        }
+        Ok(Command::Move(moves.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Phase;
+    use crate::rules::Board;
+
+    #[test]
+    fn test_clip() -> Result<(), Error> {
+        assert_eq!(
+            "1 alice 1041253132 192.168.1.308\r\n".parse::<Message>()?,
+            Message::Welcome {
+                name: "alice".to_string(),
+                last_login: 1041253132,
+                last_host: "192.168.1.308".to_string(),
+            }
+        );
+        let own = "2 alice 1 1 0 0 0 0 1 1 2396 0 1 0 1 3457.85 0 0 0 0 0 Australia/Melbourne";
+        match own.parse::<Message>()? {
+            Message::OwnInfo {
+                name,
+                experience,
+                rating,
+                ..
+            } => {
+                assert_eq!(name, "alice");
+                assert_eq!(experience, 2396);
+                assert!((rating - 3457.85).abs() < 1e-9);
+            }
+            m => panic!("{:?}", m),
+        }
+        let who = "5 bob alice - 1 0 1673.23 1257 21 1040459524 host.example.com - -";
+        match who.parse::<Message>()? {
+            Message::Who(info) => {
+                assert_eq!(info.opponent.as_deref(), Some("alice"));
+                assert_eq!(info.watching, None);
+                assert!(info.ready);
+                assert_eq!(info.experience, 1257);
+                assert_eq!(info.client, None);
+            }
+            m => panic!("{:?}", m),
+        }
+        assert_eq!("6".parse::<Message>()?, Message::WhoEnd);
+        assert_eq!(
+            "9 bob 1041253132 see you later".parse::<Message>()?,
+            Message::Message {
+                from: "bob".to_string(),
+                time: 1041253132,
+                message: "see you later".to_string(),
+            }
+        );
+        assert_eq!(
+            "12 bob hello there".parse::<Message>()?,
+            Message::Chat {
+                kind: ChatKind::Says,
+                from: "bob".to_string(),
+                message: "hello there".to_string(),
+            }
+        );
+        assert_eq!(
+            "17 hello all".parse::<Message>()?,
+            Message::ChatSent {
+                kind: ChatKind::Shouts,
+                to: None,
+                message: "hello all".to_string(),
+            }
+        );
+        assert!("5 bob alice".parse::<Message>().is_err());
+        assert!("1 alice yesterday host".parse::<Message>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_game_messages() -> Result<(), Error> {
+        let parse = |s: &str| s.parse::<Message>();
+        assert_eq!(
+            parse("bob wants to play a 5 point match with you.")?,
+            Message::Invitation {
+                name: "bob".to_string(),
+                match_length: Some(5),
+            }
+        );
+        assert_eq!(
+            parse("bob wants to play an unlimited match with you.")?,
+            Message::Invitation {
+                name: "bob".to_string(),
+                match_length: None,
+            }
+        );
+        assert_eq!(
+            parse("You roll 3 and 1.")?,
+            Message::Rolls {
+                name: "You".to_string(),
+                dices: (3, 1),
+            }
+        );
+        assert_eq!(
+            parse("bob moves 8-5 6-5 .")?,
+            Message::Moves {
+                name: "bob".to_string(),
+                moves: "8-5 6-5".to_string(),
+            }
+        );
+        assert_eq!(
+            parse("bob doubles. Type 'accept' or 'reject'.")?,
+            Message::Doubles {
+                name: "bob".to_string()
+            }
+        );
+        assert_eq!(parse("Please move 2 pieces.")?, Message::PleaseMove(2));
+        assert_eq!(
+            parse("You win the game and get 2 points. Congratulations!")?,
+            Message::WinsGame {
+                name: "You".to_string(),
+                points: 2,
+            }
+        );
+        assert_eq!(
+            parse("** You can't double.")?,
+            Message::Error("You can't double.".to_string())
+        );
+        assert_eq!(
+            parse("bob rolls the dice")?,
+            Message::Other("bob rolls the dice".to_string())
+        );
+        assert_eq!(
+            parse("20 points is a lot")?,
+            Message::Other("20 points is a lot".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_commands() {
+        let invite = Command::Invite {
+            name: "bob".to_string(),
+            match_length: Some(5),
+        };
+        assert_eq!(invite.to_string(), "invite bob 5");
+        let invite = Command::Invite {
+            name: "bob".to_string(),
+            match_length: None,
+        };
+        assert_eq!(invite.to_string(), "invite bob unlimited");
+        assert_eq!(Command::Who.to_string(), "rawwho");
+        assert_eq!(Command::Join("bob".to_string()).to_string(), "join bob");
+        assert_eq!(Command::Resign(Victory::Gammon).to_string(), "resign g");
+        let client = Client::new("alice", "secret").with_client("bot");
+        assert_eq!(client.login().to_string(), "login bot 1008 alice secret");
+        assert!(!format!("{:?}", client).contains("secret"));
+    }
+
+    #[test]
+    fn test_client() -> Result<(), Error> {
+        let mut client = Client::new("alice", "secret");
+        let play: Play = "bar/22".parse()?;
+        assert!(client.move_command(&play).is_err());
+        assert!(client.game().is_none());
+
+        // the user has the negative checkers and moves from point 1 to 24
+        let line = "board:You:bob:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:-1:0:0:5:3:2:1:0:1:-1:1:25:0:0:0:0:0:0:0:0:0";
+        assert!(matches!(client.handle(line)?, Message::Board(_)));
+        let game = client.game().ok_or(Error::FormatInvalid)?;
+        assert_eq!(game.board, Board::new());
+        assert_eq!(game.phase, Phase::AwaitingCubeDecision);
+
+        let play: Play = "24/18/13 bar/22 6/off".parse()?;
+        assert_eq!(
+            client.move_command(&play)?,
+            Command::Move("1-7 7-12 bar-3 19-off".to_string())
+        );
+        Ok(())
+    }
+}
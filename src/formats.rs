@@ -0,0 +1,170 @@
+/// Implements the compact binary encoding of positions and match states
+mod binary;
+pub use binary::Binary;
+pub(crate) use binary::{bits_player, player_bits};
+/// Implements the board lines of the First Internet Backgammon Server
+mod fibs;
+pub use fibs::Fibs;
+/// Implements the GNU Backgammon IDs
+mod gnubg;
+pub use gnubg::{GameState, MatchId, PositionId};
+/// Implements the Jellyfish match files
+mod jellyfish;
+pub use jellyfish::Mat;
+/// Implements the Smart Game Format of GNU Backgammon
+mod sgf;
+pub use sgf::Sgf;
+/// Implements the Snowie text positions
+mod snowie;
+pub use snowie::Snowie;
+/// Implements the human-readable transcripts of games
+mod transcript;
+pub use transcript::Transcript;
+/// Implements the matches exported by eXtreme Gammon as text
+mod xg;
+pub use xg::XgMatch;
+/// Implements the eXtreme Gammon IDs
+mod xgid;
+pub use xgid::{CubeAction, Xgid};
+
+use crate::Error;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// The alphabet of the base64 encoding used by the IDs of GNU Backgammon
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes in base64 without padding
+pub(crate) fn base64_encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+        for i in 0..=chunk.len() {
+            s.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    s
+}
+
+/// Decode base64 without padding
+pub(crate) fn base64_decode(s: &str) -> Result<Vec<u8>, Error> {
+    let digits = s
+        .trim_end_matches('=')
+        .bytes()
+        .map(|c| {
+            BASE64
+                .iter()
+                .position(|b| *b == c)
+                .map(|p| p as u32)
+                .ok_or(Error::FormatInvalid)
+        })
+        .collect::<Result<Vec<u32>, Error>>()?;
+    if digits.len() % 4 == 1 {
+        return Err(Error::FormatInvalid);
+    }
+
+    let mut bytes = Vec::with_capacity(digits.len() * 3 / 4);
+    for chunk in digits.chunks(4) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |n, (i, d)| n | d << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Writes values bit by bit, starting with the least significant bit of the first byte
+#[derive(Debug, Default)]
+pub(crate) struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    /// Append the lowest bits of a value, least significant bit first
+    pub(crate) fn push(&mut self, value: u32, bits: usize) {
+        for i in 0..bits {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if value >> i & 1 == 1 {
+                self.bytes[self.len / 8] |= 1 << (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+
+    /// Returns the bytes written, padded to the given length with zeros
+    pub(crate) fn into_bytes(mut self, len: usize) -> Vec<u8> {
+        self.bytes.resize(len, 0);
+        self.bytes
+    }
+}
+
+/// Reads values bit by bit, in the order written by a [`BitWriter`]
+#[derive(Debug)]
+pub(crate) struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Read from the given bytes
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, pos: 0 }
+    }
+
+    /// Read a value of the given amount of bits
+    pub(crate) fn read(&mut self, bits: usize) -> Result<u32, Error> {
+        let mut value = 0;
+        for i in 0..bits {
+            let byte = self.bytes.get(self.pos / 8).ok_or(Error::FormatInvalid)?;
+            value |= u32::from(byte >> (self.pos % 8) & 1) << i;
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() -> Result<(), Error> {
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert_eq!(base64_encode(b"Ma"), "TWE");
+        assert_eq!(base64_decode("TWFu")?, b"Man");
+        assert_eq!(base64_decode("TWE=")?, b"Ma");
+        assert!(base64_decode("TW!u").is_err());
+
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(base64_decode(&base64_encode(&bytes))?, bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bits() -> Result<(), Error> {
+        let mut writer = BitWriter::default();
+        writer.push(0b101, 3);
+        writer.push(0x1ff, 9);
+        writer.push(0, 1);
+        let bytes = writer.into_bytes(3);
+        assert_eq!(bytes, vec![0b1111_1101, 0b0000_1111, 0]);
+
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read(3)?, 0b101);
+        assert_eq!(reader.read(9)?, 0x1ff);
+        assert_eq!(reader.read(12)?, 0);
+        assert!(reader.read(1).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+//! # Compact Binary Encoding
+use crate::game::{Game, Phase};
+use crate::rules::{Board, Cube, Dices, Player};
+use crate::Error;
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The version of the encoding written by [`to_bytes`](Binary::to_bytes)
+const VERSION: u8 = 1;
+
+/// The length of the encoding of version 1
+const LENGTH: usize = 26;
+
+/// Represents the state of a game in a match as a compact, deterministic, and versioned binary
+/// encoding of 26 bytes, e.g. to store positions in a database or to send them over a network.
+///
+/// The bytes are, in this order:
+///
+/// 1. the version of the encoding, currently 1
+/// 2. 10 bytes with the position of the checkers, see [`Board::to_bytes`]
+/// 3. the logarithm of the cube value to the base 2
+/// 4. the owner of the cube (bits 0-1), the player on roll (bits 2-3), the phase of the game
+///    (bits 4-5), and the Crawford flag (bit 6), where players are encoded as 0 for nobody, 1 for
+///    player 0, and 2 for player 1
+/// 5. the first dice (bits 4-7) and the second dice (bits 0-3)
+/// 6. the match length, the score of player 0, and the score of player 1 as little-endian 32-bit
+///    integers
+///
+/// Decoding never panics, any unknown version, invalid value, or invalid position is an error.
+/// ```
+/// use backgammon::Game;
+/// use backgammon::formats::Binary;
+///
+/// let state = Binary::from_game(&Game::new(), 7, (2, 3));
+/// let bytes = state.to_bytes();
+/// assert_eq!(bytes.len(), 26);
+/// assert_eq!(Binary::from_bytes(&bytes).unwrap(), state);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Binary {
+    /// The checkers of both players
+    pub board: Board,
+    /// The value of the cube
+    pub cube_value: u64,
+    /// The owner of the cube, nobody if centered
+    pub cube_owner: Player,
+    /// The player on roll, nobody before the opening roll
+    pub who_plays: Player,
+    /// The phase of the game
+    pub phase: Phase,
+    /// The dices rolled, (0, 0) if not rolled yet
+    pub dices: (u8, u8),
+    /// Whether this is the Crawford game
+    pub crawford: bool,
+    /// The length of the match, 0 for a money game
+    pub match_length: u32,
+    /// The score of player 0 and player 1
+    pub score: (u32, u32),
+}
+
+/// Maps a player to its two bits
+pub(crate) fn player_bits(player: Player) -> u8 {
+    match player {
+        Player::Nobody => 0,
+        Player::Player0 => 1,
+        Player::Player1 => 2,
+    }
+}
+
+/// Maps two bits to a player
+pub(crate) fn bits_player(bits: u8) -> Result<Player, Error> {
+    match bits {
+        0 => Ok(Player::Nobody),
+        1 => Ok(Player::Player0),
+        2 => Ok(Player::Player1),
+        _ => Err(Error::FormatInvalid),
+    }
+}
+
+impl Binary {
+    /// Describe a game of a match with the given length and score. Use a length of 0 for a money
+    /// game.
+    pub fn from_game(game: &Game, match_length: u32, score: (u32, u32)) -> Self {
+        Binary {
+            board: game.board.clone(),
+            cube_value: game.cube.value(),
+            cube_owner: game.cube.owner(),
+            who_plays: game.who_plays,
+            phase: game.phase,
+            dices: match game.phase {
+                Phase::AwaitingMove => game.dices.values,
+                _ => (0, 0),
+            },
+            crawford: game.is_crawford(),
+            match_length,
+            score,
+        }
+    }
+
+    /// Set the board, the cube, the dices, the player on roll, and the phase of a game as
+    /// described by this state.
+    pub fn apply(&self, game: &mut Game) -> Result<(), Error> {
+        self.board.validate()?;
+        let mut cube = Cube::default();
+        cube.set(self.cube_value)?;
+        cube.set_owner(self.cube_owner);
+        game.board = self.board.clone();
+        game.cube = cube;
+        game.crawford = self.crawford;
+        game.dices = Dices::new(self.dices);
+        game.who_plays = self.who_plays;
+        game.phase = self.phase;
+        Ok(())
+    }
+
+    /// Encode the state in the latest version of the encoding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let phase = match self.phase {
+            Phase::AwaitingRoll => 0,
+            Phase::AwaitingCubeDecision => 1,
+            Phase::AwaitingMove => 2,
+            Phase::GameOver => 3,
+        };
+        let mut bytes = Vec::with_capacity(LENGTH);
+        bytes.push(VERSION);
+        bytes.extend(self.board.to_bytes());
+        bytes.push(self.cube_value.trailing_zeros() as u8);
+        bytes.push(
+            player_bits(self.cube_owner)
+                | player_bits(self.who_plays) << 2
+                | phase << 4
+                | u8::from(self.crawford) << 6,
+        );
+        bytes.push(self.dices.0 << 4 | self.dices.1);
+        bytes.extend(self.match_length.to_le_bytes());
+        bytes.extend(self.score.0.to_le_bytes());
+        bytes.extend(self.score.1.to_le_bytes());
+        bytes
+    }
+
+    /// Decode a state, unknown versions of the encoding are an error
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != LENGTH || bytes[0] != VERSION {
+            return Err(Error::FormatInvalid);
+        }
+        let board = Board::from_bytes(&bytes[1..11])?;
+        let exponent = bytes[11];
+        if exponent > 63 {
+            return Err(Error::FormatInvalid);
+        }
+        let flags = bytes[12];
+        if flags >> 7 != 0 {
+            return Err(Error::FormatInvalid);
+        }
+        let phase = match flags >> 4 & 3 {
+            0 => Phase::AwaitingRoll,
+            1 => Phase::AwaitingCubeDecision,
+            2 => Phase::AwaitingMove,
+            _ => Phase::GameOver,
+        };
+        let dices = (bytes[13] >> 4, bytes[13] & 0xf);
+        if dices.0 > 6 || dices.1 > 6 || (dices.0 == 0) != (dices.1 == 0) {
+            return Err(Error::FormatInvalid);
+        }
+        let number = |i: usize| {
+            let mut le = [0; 4];
+            le.copy_from_slice(&bytes[i..i + 4]);
+            u32::from_le_bytes(le)
+        };
+
+        Ok(Binary {
+            board,
+            cube_value: 1 << exponent,
+            cube_owner: bits_player(flags & 3)?,
+            who_plays: bits_player(flags >> 2 & 3)?,
+            phase,
+            dices,
+            crawford: flags >> 6 & 1 == 1,
+            match_length: number(14),
+            score: (number(18), number(22)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Field;
+
+    #[test]
+    fn test_round_trip() -> Result<(), Error> {
+        let mut g = Game::new();
+        g.board.set(Player::Player0, 23, -1)?;
+        g.board.set_bar(Player::Player0, 1)?;
+        g.board.set_off(Player::Player1, 3)?;
+        g.board.set(Player::Player1, 5, -3)?;
+        g.cube.set(64)?;
+        g.cube.set_owner(Player::Player1);
+        g.who_plays = Player::Player0;
+        g.phase = Phase::AwaitingMove;
+        g.dices = Dices::new((6, 6));
+        let state = Binary::from_game(&g, 25, (24, 13));
+        let bytes = state.to_bytes();
+        assert_eq!(bytes[0], 1);
+        assert_eq!(bytes[11], 6);
+        assert_eq!(bytes[13], 0x66);
+        assert_eq!(Binary::from_bytes(&bytes)?, state);
+
+        let mut g = Game::new();
+        state.apply(&mut g)?;
+        assert_eq!(g.board.get_checkers(Player::Player0, Field::Bar)?, 1);
+        assert_eq!(g.board.get_checkers(Player::Player1, Field::Off)?, 3);
+        assert_eq!(g.cube.value(), 64);
+        assert_eq!(g.dices.values, (6, 6));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_bytes_invalid() {
+        let bytes = Binary::from_game(&Game::new(), 0, (0, 0)).to_bytes();
+        assert!(Binary::from_bytes(&bytes[..25]).is_err());
+        for (i, value) in [
+            (0, 2),
+            (1, 0xff),
+            (10, 0x31),
+            (11, 64),
+            (12, 0x80),
+            (12, 3),
+            (13, 7),
+        ] {
+            let mut invalid = bytes.clone();
+            invalid[i] = value;
+            assert!(Binary::from_bytes(&invalid).is_err(), "byte {}", i);
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_exhaustive() {
+        // decoding any single corrupted byte never panics
+        let bytes = Binary::from_game(&Game::new(), 5, (1, 2)).to_bytes();
+        for i in 0..bytes.len() {
+            for value in 0..=u8::MAX {
+                let mut corrupted = bytes.clone();
+                corrupted[i] = value;
+                let _ = Binary::from_bytes(&corrupted);
+            }
+        }
+    }
+}
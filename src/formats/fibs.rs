@@ -0,0 +1,341 @@
+//! # FIBS Board
+use crate::game::{Game, Phase};
+use crate::rules::{Board, Cube, Dices, Occupancy, Player, PlayerBoard};
+use crate::Error;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The number of fields of a board line, including the leading "board"
+const FIELDS: usize = 53;
+
+/// Represents the raw board line sent by the First Internet Backgammon Server (FIBS) with
+/// "boardstyle 3", e.g.:
+///
+/// ```text
+/// board:You:someplayer:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:6:2:0:0:
+/// 1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0
+/// ```
+///
+/// The board is always seen by one of the players, called "You", who is player 0. To describe a
+/// game as seen by player 1, use [`Game::from_perspective`]. When writing, the checkers of player
+/// 0 are positive and move from point 24 to point 1. When parsing, the color and the direction of
+/// the line are taken into account.
+/// ```
+/// use backgammon::formats::Fibs;
+/// use backgammon::rules::{Board, Player};
+///
+/// let s = "board:You:someplayer:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:6:2:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0";
+/// let board: Fibs = s.parse().unwrap();
+/// assert_eq!(board.board, Board::new());
+/// assert_eq!(board.turn, Player::Player0);
+/// assert_eq!(board.dices, (6, 2));
+/// assert_eq!(board.to_string(), s);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Fibs {
+    /// The names of player 0 and player 1
+    pub names: (String, String),
+    /// The length of the match, 9999 for an unlimited match on FIBS
+    pub match_length: u32,
+    /// The score of player 0 and player 1
+    pub score: (u32, u32),
+    /// The checkers of both players
+    pub board: Board,
+    /// The player whose turn it is, nobody if the game is over
+    pub turn: Player,
+    /// The dices rolled by the player whose turn it is, (0, 0) if not rolled yet
+    pub dices: (u8, u8),
+    /// The value of the cube
+    pub cube_value: u64,
+    /// Whether player 0 and player 1 may double
+    pub may_double: (bool, bool),
+    /// Whether player 1 has doubled and player 0 has to take or pass
+    pub was_doubled: bool,
+    /// The number of checkers player 0 can move
+    pub can_move: u8,
+    /// Whether the Crawford game has already been played
+    pub did_crawford: bool,
+    /// The maximum number of redoubles in unlimited matches, 0 if unlimited
+    pub redoubles: u32,
+}
+
+impl Fibs {
+    /// Describe a game of a match with the given length and score, as seen by player 0.
+    pub fn from_game(game: &Game, match_length: u32, score: (u32, u32)) -> Self {
+        let may_double = |player: Player| {
+            game.phase != Phase::GameOver
+                && game.cube_is_live(player)
+                && game.cube.owner() != player.other()
+        };
+        Fibs {
+            names: ("You".to_string(), Player::Player1.to_string()),
+            match_length,
+            score,
+            board: game.board.clone(),
+            turn: match game.phase {
+                Phase::GameOver => Player::Nobody,
+                _ => game.who_plays,
+            },
+            dices: match game.phase {
+                Phase::AwaitingMove => game.dices.values,
+                _ => (0, 0),
+            },
+            cube_value: game.cube.value(),
+            may_double: (may_double(Player::Player0), may_double(Player::Player1)),
+            was_doubled: game.phase == Phase::AwaitingCubeDecision
+                && game.who_plays == Player::Player1,
+            can_move: match game.who_plays {
+                Player::Player0 => game
+                    .legal_moves()
+                    .iter()
+                    .map(|p| p.moves.len() as u8)
+                    .max()
+                    .unwrap_or(0),
+                _ => 0,
+            },
+            did_crawford: game.is_crawford() || game.is_post_crawford(),
+            redoubles: 0,
+        }
+    }
+
+    /// Set the board, the cube, the dices, the player on roll, and the phase of a game as
+    /// described by this board line.
+    pub fn apply(&self, game: &mut Game) -> Result<(), Error> {
+        self.board.validate()?;
+        let mut cube = Cube::default();
+        cube.set(self.cube_value)?;
+        cube.set_owner(match self.may_double {
+            _ if self.cube_value == 1 => Player::Nobody,
+            (true, false) => Player::Player0,
+            (false, true) => Player::Player1,
+            _ => Player::Nobody,
+        });
+        let last_point = self.match_length.saturating_sub(1);
+        game.board = self.board.clone();
+        game.cube = cube;
+        game.crawford = self.did_crawford
+            && self.match_length > 0
+            && self.may_double == (false, false)
+            && (self.score.0 == last_point || self.score.1 == last_point);
+        game.dices = Dices::new(self.dices);
+        game.who_plays = self.turn;
+        game.phase = match self.turn {
+            _ if self.was_doubled => {
+                game.who_plays = Player::Player1;
+                Phase::AwaitingCubeDecision
+            }
+            Player::Nobody => Phase::GameOver,
+            _ if self.dices != (0, 0) => Phase::AwaitingMove,
+            _ => Phase::AwaitingRoll,
+        };
+        Ok(())
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Fibs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (bar, off) = (self.board.bar, self.board.off);
+        write!(
+            f,
+            "board:{}:{}:{}:{}:{}:{}:",
+            self.names.0,
+            self.names.1,
+            self.match_length,
+            self.score.0,
+            self.score.1,
+            -i32::from(bar[1])
+        )?;
+        for point in self.board.points {
+            match point.owner {
+                Player::Player1 => write!(f, "-{}:", point.count)?,
+                _ => write!(f, "{}:", point.count)?,
+            }
+        }
+        let (turn, dices) = match self.turn {
+            Player::Player0 => (1, (self.dices, (0, 0))),
+            Player::Player1 => (-1, ((0, 0), self.dices)),
+            Player::Nobody => (0, ((0, 0), (0, 0))),
+        };
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:1:-1:0:25:",
+            bar[0],
+            turn,
+            dices.0 .0,
+            dices.0 .1,
+            dices.1 .0,
+            dices.1 .1,
+            self.cube_value,
+            u8::from(self.may_double.0),
+            u8::from(self.may_double.1),
+            u8::from(self.was_doubled),
+        )?;
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:0:{}:{}",
+            off[0],
+            off[1],
+            bar[0],
+            bar[1],
+            self.can_move,
+            u8::from(self.did_crawford),
+            self.redoubles
+        )
+    }
+}
+
+impl FromStr for Fibs {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.trim().split(':').collect();
+        if fields.len() != FIELDS || fields[0] != "board" {
+            return Err(Error::FormatInvalid);
+        }
+        let number = |i: usize| fields[i].parse::<i32>().map_err(|_| Error::FormatInvalid);
+        let unsigned = |i: usize| fields[i].parse::<u32>().map_err(|_| Error::FormatInvalid);
+        let small = |i: usize| u8::try_from(unsigned(i)?).map_err(|_| Error::FormatInvalid);
+        let flag = |i: usize| match fields[i] {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(Error::FormatInvalid),
+        };
+
+        let color = number(41)?;
+        let direction = number(42)?;
+        if color.abs() != 1 || direction.abs() != 1 {
+            return Err(Error::FormatInvalid);
+        }
+        let mut me = PlayerBoard {
+            board: [0; 24],
+            bar: small(47)?,
+            off: small(45)?,
+        };
+        let mut opponent = PlayerBoard {
+            board: [0; 24],
+            bar: small(48)?,
+            off: small(46)?,
+        };
+        for point in 1..=24 {
+            let n = number(6 + point)? * color;
+            let amount = u8::try_from(n.unsigned_abs()).map_err(|_| Error::FormatInvalid)?;
+            let field = if direction < 0 { point - 1 } else { 24 - point };
+            if n > 0 {
+                me.board[field] = amount;
+            } else {
+                opponent.board[23 - field] = amount;
+            }
+        }
+        let board = Board::from_sides([me, opponent], Occupancy::default(), [0, 0])?;
+        board.validate()?;
+
+        let turn = match number(32)? * color {
+            1 => Player::Player0,
+            -1 => Player::Player1,
+            0 => Player::Nobody,
+            _ => return Err(Error::FormatInvalid),
+        };
+        let dices = match turn {
+            Player::Player1 => (small(35)?, small(36)?),
+            _ => (small(33)?, small(34)?),
+        };
+        if dices.0 > 6 || dices.1 > 6 || (dices.0 == 0) != (dices.1 == 0) {
+            return Err(Error::FormatInvalid);
+        }
+        let cube_value = u64::from(unsigned(37)?);
+        if !cube_value.is_power_of_two() {
+            return Err(Error::FormatInvalid);
+        }
+
+        Ok(Fibs {
+            names: (fields[1].to_string(), fields[2].to_string()),
+            match_length: unsigned(3)?,
+            score: (unsigned(4)?, unsigned(5)?),
+            board,
+            turn,
+            dices,
+            cube_value,
+            may_double: (flag(38)?, flag(39)?),
+            was_doubled: flag(40)?,
+            can_move: small(49)?,
+            did_crawford: flag(51)?,
+            redoubles: unsigned(52)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Field;
+
+    const START: &str = "board:You:someplayer:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:1:6:2:0:0:1:1:1:0:1:-1:0:25:0:0:0:0:2:0:0:0";
+
+    #[test]
+    fn test_parse_other_color() -> Result<(), Error> {
+        // the same position seen by a player with negative checkers moving from 1 to 24
+        let s = "board:You:someplayer:3:0:0:0:-2:0:0:0:0:5:0:3:0:0:0:-5:5:0:0:0:-3:0:-5:0:0:0:0:2:0:-1:0:0:5:3:2:1:0:1:-1:1:25:0:0:0:0:0:0:0:0:0";
+        let board: Fibs = s.parse()?;
+        // the player with the negative checkers is "You"
+        assert_eq!(board.board, Board::new());
+        assert_eq!(board.turn, Player::Player0);
+        assert_eq!(board.dices, (0, 0));
+        assert_eq!(board.cube_value, 2);
+        assert_eq!(board.may_double, (true, false));
+        assert!(board.was_doubled);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("board:You:someplayer:3".parse::<Fibs>().is_err());
+        assert!(START
+            .replacen("board", "boards", 1)
+            .parse::<Fibs>()
+            .is_err());
+        // too many checkers
+        assert!(START.replacen(":5:", ":6:", 1).parse::<Fibs>().is_err());
+        // invalid turn
+        assert!(START
+            .replacen(":0:1:6:2:", ":0:2:6:2:", 1)
+            .parse::<Fibs>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_game() -> Result<(), Error> {
+        let board: Fibs = START.parse()?;
+        let mut g = Game::new();
+        board.apply(&mut g)?;
+        assert_eq!(g.who_plays, Player::Player0);
+        assert_eq!(g.phase, Phase::AwaitingMove);
+        assert_eq!(g.dices.values, (6, 2));
+
+        let mut written = Fibs::from_game(&g, 3, (0, 0));
+        assert_eq!(written.can_move, 2);
+        written.names.1 = "someplayer".to_string();
+        assert_eq!(written, board);
+
+        // player 1 is hit and doubles
+        g.board.set_bar(Player::Player1, 1)?;
+        g.board.set(Player::Player1, 23, -1)?;
+        g.who_plays = Player::Player1;
+        g.phase = Phase::AwaitingRoll;
+        g.offer_double(Player::Player1)?;
+        let written = Fibs::from_game(&g, 0, (0, 0));
+        assert!(written.was_doubled);
+        assert_eq!(written.board.get_checkers(Player::Player1, Field::Bar)?, 1);
+        let mut g = Game::new();
+        written.to_string().parse::<Fibs>()?.apply(&mut g)?;
+        assert_eq!(g.phase, Phase::AwaitingCubeDecision);
+        assert_eq!(g.who_plays, Player::Player1);
+        Ok(())
+    }
+}
@@ -0,0 +1,469 @@
+//! # GNU Backgammon Match ID and Position ID
+use super::{base64_decode, base64_encode, BitReader, BitWriter};
+use crate::game::{Game, Phase, Victory};
+use crate::r#match::Match;
+use crate::record::Event;
+use crate::rules::{Board, Cube, Dices, Player};
+use crate::Error;
+
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents the state of a game as encoded in a GNU Backgammon Match ID
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameState {
+    /// No game has been started
+    #[default]
+    NoGame,
+    /// The game is being played
+    Playing,
+    /// The game is over
+    Over,
+    /// The game has been resigned
+    Resigned,
+    /// The game has ended by passing the cube
+    Dropped,
+}
+
+/// Represents a GNU Backgammon Match ID, which describes everything about a match except the
+/// position of the checkers, i.e. the cube, the dices, the player on roll, and the score.
+///
+/// The ID is a string of 12 characters, e.g. "cAgAAAAAAAAA", which is parsed with
+/// [`from_str`](MatchId::from_str) and written with [`Display`](fmt::Display).
+/// ```
+/// use backgammon::formats::MatchId;
+/// use backgammon::rules::Player;
+///
+/// let id: MatchId = "QYkqASAAIAAA".parse().unwrap();
+/// assert_eq!(id.cube_value, 2);
+/// assert_eq!(id.cube_owner, Player::Player0);
+/// assert_eq!(id.dices, (5, 2));
+/// assert_eq!(id.match_length, 9);
+/// assert_eq!(id.score, (2, 4));
+/// assert_eq!(id.to_string(), "QYkqASAAIAAA");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MatchId {
+    /// The value of the cube
+    pub cube_value: u64,
+    /// The owner of the cube, nobody if centered
+    pub cube_owner: Player,
+    /// The player on roll
+    pub player_on_roll: Player,
+    /// Whether this is the Crawford game
+    pub crawford: bool,
+    /// The state of the game
+    pub game_state: GameState,
+    /// The player who has to take a decision, e.g. the opponent of the player who doubled
+    pub turn: Player,
+    /// Whether the cube has been offered
+    pub double_offered: bool,
+    /// The resignation offered, if any
+    pub resignation: Option<Victory>,
+    /// The dices rolled, (0, 0) if not rolled yet
+    pub dices: (u8, u8),
+    /// The length of the match, 0 for a money game
+    pub match_length: u32,
+    /// The score of player 0 and player 1
+    pub score: (u32, u32),
+}
+
+impl Default for MatchId {
+    fn default() -> Self {
+        MatchId {
+            cube_value: 1,
+            cube_owner: Player::Nobody,
+            player_on_roll: Player::Player0,
+            crawford: false,
+            game_state: GameState::NoGame,
+            turn: Player::Player0,
+            double_offered: false,
+            resignation: None,
+            dices: (0, 0),
+            match_length: 0,
+            score: (0, 0),
+        }
+    }
+}
+
+/// Maps a player to the bit used in the Match ID
+fn player_bit(player: Player) -> u32 {
+    match player {
+        Player::Player1 => 1,
+        _ => 0,
+    }
+}
+
+/// Maps a bit of the Match ID to a player
+fn bit_player(bit: u32) -> Player {
+    match bit {
+        0 => Player::Player0,
+        _ => Player::Player1,
+    }
+}
+
+impl MatchId {
+    /// Describe a game of a match with the given length and score. Use a length of 0 for a money
+    /// game.
+    pub fn from_game(game: &Game, match_length: u32, score: (u32, u32)) -> Self {
+        let game_state = match (game.phase, game.record().last()) {
+            (Phase::GameOver, Some(Event::Resign { .. })) => GameState::Resigned,
+            (Phase::GameOver, Some(Event::CubeDrop { .. })) => GameState::Dropped,
+            (Phase::GameOver, _) => GameState::Over,
+            _ if game.who_plays == Player::Nobody => GameState::NoGame,
+            _ => GameState::Playing,
+        };
+        let double_offered = game.phase == Phase::AwaitingCubeDecision;
+        let turn = if double_offered {
+            game.who_plays.other()
+        } else {
+            game.who_plays
+        };
+
+        MatchId {
+            cube_value: game.cube.value(),
+            cube_owner: game.cube.owner(),
+            player_on_roll: bit_player(player_bit(game.who_plays)),
+            crawford: game.is_crawford(),
+            game_state,
+            turn: bit_player(player_bit(turn)),
+            double_offered,
+            resignation: None,
+            dices: match game.phase {
+                Phase::AwaitingMove => game.dices.values,
+                _ => (0, 0),
+            },
+            match_length,
+            score,
+        }
+    }
+
+    /// Describe the game currently played in a match
+    pub fn from_match(m: &Match) -> Result<Self, Error> {
+        let score = m.score();
+        let score = (
+            u32::try_from(score.0).map_err(|_| Error::FormatInvalid)?,
+            u32::try_from(score.1).map_err(|_| Error::FormatInvalid)?,
+        );
+        Ok(match m.games.last() {
+            Some(game) => MatchId::from_game(game, m.rules.points, score),
+            None => MatchId {
+                match_length: m.rules.points,
+                score,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Set the cube, the dices, the player on roll, and the phase of a game as described by this
+    /// Match ID. The checkers on the board are left unchanged.
+    pub fn apply(&self, game: &mut Game) -> Result<(), Error> {
+        let mut cube = Cube::default();
+        cube.set(self.cube_value)?;
+        cube.set_owner(self.cube_owner);
+        game.cube = cube;
+        game.crawford = self.crawford;
+        game.dices = Dices::new(self.dices);
+        game.who_plays = match self.game_state {
+            GameState::NoGame => Player::Nobody,
+            _ => self.player_on_roll,
+        };
+        game.phase = match self.game_state {
+            GameState::Over | GameState::Resigned | GameState::Dropped => Phase::GameOver,
+            _ if self.double_offered => Phase::AwaitingCubeDecision,
+            _ if self.dices != (0, 0) => Phase::AwaitingMove,
+            _ => Phase::AwaitingRoll,
+        };
+        Ok(())
+    }
+}
+
+// implement Display trait
+impl fmt::Display for MatchId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut bits = BitWriter::default();
+        bits.push(self.cube_value.trailing_zeros(), 4);
+        bits.push(
+            match self.cube_owner {
+                Player::Player0 => 0,
+                Player::Player1 => 1,
+                Player::Nobody => 3,
+            },
+            2,
+        );
+        bits.push(player_bit(self.player_on_roll), 1);
+        bits.push(u32::from(self.crawford), 1);
+        bits.push(
+            match self.game_state {
+                GameState::NoGame => 0,
+                GameState::Playing => 1,
+                GameState::Over => 2,
+                GameState::Resigned => 3,
+                GameState::Dropped => 4,
+            },
+            3,
+        );
+        bits.push(player_bit(self.turn), 1);
+        bits.push(u32::from(self.double_offered), 1);
+        bits.push(
+            match self.resignation {
+                None => 0,
+                Some(Victory::Single) => 1,
+                Some(Victory::Gammon) => 2,
+                Some(Victory::Backgammon) => 3,
+            },
+            2,
+        );
+        bits.push(u32::from(self.dices.0), 3);
+        bits.push(u32::from(self.dices.1), 3);
+        bits.push(self.match_length, 15);
+        bits.push(self.score.0, 15);
+        bits.push(self.score.1, 15);
+        write!(f, "{}", base64_encode(&bits.into_bytes(9)))
+    }
+}
+
+impl FromStr for MatchId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("Match ID:").map_or(s, str::trim);
+        if s.len() != 12 {
+            return Err(Error::FormatInvalid);
+        }
+        let bytes = base64_decode(s)?;
+        let mut bits = BitReader::new(&bytes);
+
+        let cube_value = 2u64.pow(bits.read(4)?);
+        let cube_owner = match bits.read(2)? {
+            0 => Player::Player0,
+            1 => Player::Player1,
+            3 => Player::Nobody,
+            _ => return Err(Error::FormatInvalid),
+        };
+        let player_on_roll = bit_player(bits.read(1)?);
+        let crawford = bits.read(1)? == 1;
+        let game_state = match bits.read(3)? {
+            0 => GameState::NoGame,
+            1 => GameState::Playing,
+            2 => GameState::Over,
+            3 => GameState::Resigned,
+            4 => GameState::Dropped,
+            _ => return Err(Error::FormatInvalid),
+        };
+        let turn = bit_player(bits.read(1)?);
+        let double_offered = bits.read(1)? == 1;
+        let resignation = match bits.read(2)? {
+            0 => None,
+            1 => Some(Victory::Single),
+            2 => Some(Victory::Gammon),
+            _ => Some(Victory::Backgammon),
+        };
+        let dices = (bits.read(3)? as u8, bits.read(3)? as u8);
+        if dices.0 > 6 || dices.1 > 6 || (dices.0 == 0) != (dices.1 == 0) {
+            return Err(Error::FormatInvalid);
+        }
+        let match_length = bits.read(15)?;
+        let score = (bits.read(15)?, bits.read(15)?);
+
+        Ok(MatchId {
+            cube_value,
+            cube_owner,
+            player_on_roll,
+            crawford,
+            game_state,
+            turn,
+            double_offered,
+            resignation,
+            dices,
+            match_length,
+            score,
+        })
+    }
+}
+
+/// Represents a GNU Backgammon Position ID, which describes the checkers on the board. The
+/// checkers of the player on roll are encoded first, so the same board has a different ID
+/// depending on who is on roll, and the player on roll has to be known to decode it, e.g. from the
+/// [`MatchId`].
+///
+/// The ID is a string of 14 characters, e.g. "4HPwATDgc/ABMA", which is parsed with
+/// [`from_str`](PositionId::from_str) and written with [`Display`](fmt::Display).
+/// ```
+/// use backgammon::formats::PositionId;
+/// use backgammon::rules::{Board, Player};
+///
+/// let id: PositionId = "4HPwATDgc/ABMA".parse().unwrap();
+/// assert_eq!(id.board(Player::Player1).unwrap(), Board::new());
+/// assert_eq!(PositionId::new(&Board::new(), Player::Player0).to_string(), "4HPwATDgc/ABMA");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PositionId([u8; 10]);
+
+impl PositionId {
+    /// Describe a board with the given player on roll
+    pub fn new(board: &Board, player_on_roll: Player) -> Self {
+        match player_on_roll {
+            Player::Player1 => PositionId(board.flipped().to_bytes()),
+            _ => PositionId(board.to_bytes()),
+        }
+    }
+
+    /// Describe the board of a game, with the player who plays on roll
+    pub fn from_game(game: &Game) -> Self {
+        PositionId::new(&game.board, game.who_plays)
+    }
+
+    /// Returns the board described by this Position ID with the given player on roll
+    pub fn board(&self, player_on_roll: Player) -> Result<Board, Error> {
+        let board = Board::from_bytes(&self.0)?;
+        Ok(match player_on_roll {
+            Player::Player1 => board.flipped(),
+            _ => board,
+        })
+    }
+}
+
+impl fmt::Display for PositionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", base64_encode(&self.0))
+    }
+}
+
+impl FromStr for PositionId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("Position ID:").map_or(s, str::trim);
+        if s.len() != 14 {
+            return Err(Error::FormatInvalid);
+        }
+        let bytes = base64_decode(s)?;
+        let _ = Board::from_bytes(&bytes)?;
+        let bytes = bytes.try_into().map_err(|_| Error::FormatInvalid)?;
+        Ok(PositionId(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::MatchRules;
+
+    #[test]
+    fn test_parse_default() -> Result<(), Error> {
+        let id: MatchId = "cAgAAAAAAAAA".parse()?;
+        assert_eq!(id.cube_value, 1);
+        assert_eq!(id.cube_owner, Player::Nobody);
+        assert_eq!(id.player_on_roll, Player::Player1);
+        assert_eq!(id.game_state, GameState::NoGame);
+        assert_eq!(id.turn, Player::Player1);
+        assert_eq!(id.match_length, 0);
+        assert_eq!(id.to_string(), "cAgAAAAAAAAA");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), Error> {
+        let id: MatchId = "Match ID: QYkqASAAIAAA".parse()?;
+        assert_eq!(
+            id,
+            MatchId {
+                cube_value: 2,
+                cube_owner: Player::Player0,
+                player_on_roll: Player::Player1,
+                crawford: false,
+                game_state: GameState::Playing,
+                turn: Player::Player1,
+                double_offered: false,
+                resignation: None,
+                dices: (5, 2),
+                match_length: 9,
+                score: (2, 4),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("cAgAAAAAAAA".parse::<MatchId>().is_err());
+        assert!("cAgAAAAAAAA!".parse::<MatchId>().is_err());
+        let id = MatchId {
+            dices: (7, 1),
+            ..Default::default()
+        };
+        assert!(id.to_string().parse::<MatchId>().is_err());
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), Error> {
+        let id = MatchId {
+            cube_value: 64,
+            cube_owner: Player::Player1,
+            player_on_roll: Player::Player0,
+            crawford: true,
+            game_state: GameState::Dropped,
+            turn: Player::Player1,
+            double_offered: true,
+            resignation: Some(Victory::Backgammon),
+            dices: (6, 1),
+            match_length: 25,
+            score: (24, 17),
+        };
+        assert_eq!(id.to_string().parse::<MatchId>()?, id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_game() -> Result<(), Error> {
+        let mut m = Match::new().with_points(5);
+        let g = m.new_game()?;
+        g.who_plays = Player::Player1;
+        g.offer_double(Player::Player1)?;
+        let id = MatchId::from_match(&m)?;
+        assert_eq!(id.player_on_roll, Player::Player1);
+        assert_eq!(id.turn, Player::Player0);
+        assert!(id.double_offered);
+        assert_eq!(id.game_state, GameState::Playing);
+        assert_eq!(id.match_length, 5);
+
+        let mut g = Game::new();
+        id.apply(&mut g)?;
+        assert_eq!(g.phase, Phase::AwaitingCubeDecision);
+        assert_eq!(g.who_plays, Player::Player1);
+        assert_eq!(MatchId::from_game(&g, 5, (0, 0)), id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_id() -> Result<(), Error> {
+        let id: PositionId = "Position ID: 4HPwATDgc/ABMA".parse()?;
+        assert_eq!(id.board(Player::Player0)?, Board::new());
+        assert_eq!(id.to_string(), "4HPwATDgc/ABMA");
+
+        let mut board = Board::new();
+        board.set(Player::Player0, 23, -1)?;
+        board.set(Player::Player0, 20, 1)?;
+        let mut game = Game::new();
+        game.board = board.clone();
+        game.who_plays = Player::Player1;
+        let id = PositionId::from_game(&game);
+        assert_ne!(id, PositionId::new(&board, Player::Player0));
+        assert_eq!(id.to_string().parse::<PositionId>()?, id);
+        assert_eq!(id.board(Player::Player1)?, board);
+        assert_ne!(id.board(Player::Player0)?, board);
+
+        assert!("4HPwATDgc/AB".parse::<PositionId>().is_err());
+        assert!("//////////////".parse::<PositionId>().is_err());
+        assert!("4HPwATDgc/AB==".parse::<PositionId>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+//! # Jellyfish Match Files
+use crate::game::{Game, Phase, Victory};
+use crate::r#match::{score, Match};
+use crate::record::Event;
+use crate::rules::{CheckerMove, MatchRules, Player};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// Actions starting at this column or later belong to the player in the right column
+const RIGHT_COLUMN: usize = 30;
+
+/// Represents a match in the Jellyfish `.mat` format, which is the most common format to store
+/// Backgammon matches, e.g.:
+///
+/// ```text
+///  3 point match
+///
+///  Game 1
+///  Alice : 0                          Bob : 0
+///   1) 31: 8/5 6/5                    52: 13/11 13/8
+///   2) Doubles => 2                   Drops
+///       Wins 1 point
+/// ```
+///
+/// The player in the left column is player 0, the player in the right column is player 1. Every
+/// player's moves are given from the player's own point-of-view, the bar is 25 and off is 0.
+/// Reading a match replays all games, therefore every roll, move and cube action has to be legal.
+/// ```
+/// use backgammon::formats::Mat;
+/// use backgammon::rules::Player;
+///
+/// let s = " 3 point match\n\n Game 1\n Alice : 0                         Bob : 0\n  1) 31: 8/5 6/5                   52: 13/11 13/8\n";
+/// let mat: Mat = s.parse().unwrap();
+/// assert_eq!(mat.names.1, "Bob");
+/// assert_eq!(mat.r#match.rules.points, 3);
+/// assert_eq!(mat.r#match.games[0].who_plays, Player::Player0);
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Mat {
+    /// The names of player 0 and player 1
+    pub names: (String, String),
+    /// The match with all its games
+    pub r#match: Match,
+}
+
+/// Returns the victory for the points won at the given cube value
+pub(super) fn victory(points: u64, cube: u64) -> Result<Victory, Error> {
+    match points.checked_div(cube) {
+        Some(1) if points.is_multiple_of(cube) => Ok(Victory::Single),
+        Some(2) if points.is_multiple_of(cube) => Ok(Victory::Gammon),
+        Some(3) if points.is_multiple_of(cube) => Ok(Victory::Backgammon),
+        _ => Err(Error::FormatInvalid),
+    }
+}
+
+/// Apply a single action of a player, e.g. "31: 8/5 6/5" or "Doubles => 2", to a game
+pub(super) fn apply(game: &mut Game, player: Player, action: &str) -> Result<(), Error> {
+    let (keyword, rest) = action
+        .split_once(char::is_whitespace)
+        .unwrap_or((action, ""));
+    match keyword {
+        "Doubles" => game.offer_double(player),
+        "Takes" => game.take(player),
+        "Drops" | "Passes" => game.pass(player),
+        "Beavers" => game.beaver(player),
+        "Raccoons" => game.raccoon(player),
+        "Wins" => {
+            if game.phase == Phase::GameOver {
+                return Ok(());
+            }
+            let points = rest
+                .split_whitespace()
+                .next()
+                .and_then(|p| p.parse().ok())
+                .ok_or(Error::FormatInvalid)?;
+            game.resign(player.other(), victory(points, game.cube.value())?)
+        }
+        roll => {
+            let dices = match roll.as_bytes() {
+                [a @ b'1'..=b'6', b @ b'1'..=b'6', b':'] => (a - b'0', b - b'0'),
+                _ => return Err(Error::FormatInvalid),
+            };
+            let dices = match (game.who_plays, player) {
+                (Player::Nobody, _) if dices.0 == dices.1 => return Err(Error::FormatInvalid),
+                (Player::Nobody, Player::Player0) => (dices.0.max(dices.1), dices.0.min(dices.1)),
+                (Player::Nobody, _) => (dices.0.min(dices.1), dices.0.max(dices.1)),
+                (who, _) if who != player => return Err(Error::NotYourTurn),
+                _ => dices,
+            };
+            let _ = game.roll_with(dices)?;
+            if !rest.trim().is_empty() {
+                for mv in game.parse_play(rest)?.moves {
+                    game.apply_move(&mv)?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Split a line of a game into the actions of the players, each with the column it starts at
+pub(super) fn actions(line: &str) -> Vec<(usize, &str)> {
+    // skip the number of the line, e.g. " 12)"
+    let start = match line.trim_start().split_once(')') {
+        Some((n, _)) if !n.is_empty() && n.bytes().all(|c| c.is_ascii_digit()) => {
+            line.find(')').map_or(0, |i| i + 1)
+        }
+        _ => 0,
+    };
+
+    let mut starts = Vec::new();
+    let mut column = start;
+    for token in line[start..].split_inclusive(char::is_whitespace) {
+        let word = token.trim();
+        let is_roll = matches!(word.as_bytes(), [b'0'..=b'9', b'0'..=b'9', b':']);
+        let is_keyword = matches!(
+            word,
+            "Doubles" | "Takes" | "Drops" | "Passes" | "Beavers" | "Raccoons" | "Wins"
+        );
+        if is_roll || is_keyword {
+            starts.push(column);
+        }
+        column += token.len();
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let end = starts.get(i + 1).copied().unwrap_or(line.len());
+            (*s, line[*s..end].trim())
+        })
+        .collect()
+}
+
+impl Mat {
+    /// Read the games following the header of a match, i.e. the lines starting with " Game 1"
+    pub(super) fn read_games(&mut self, s: &str) -> Result<(), Error> {
+        let mut names_expected = false;
+        for line in s.lines().map(str::trim_end) {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if trimmed.starts_with("Game ") {
+                let _ = self.r#match.new_game()?;
+                names_expected = true;
+            } else if names_expected {
+                let mut parts = trimmed.splitn(3, ':');
+                let name0 = parts.next().ok_or(Error::FormatInvalid)?.trim();
+                let (_, name1) = parts
+                    .next()
+                    .and_then(|p| p.trim().split_once(char::is_whitespace))
+                    .ok_or(Error::FormatInvalid)?;
+                self.names = (name0.to_string(), name1.trim().to_string());
+                names_expected = false;
+            } else {
+                let game = self.r#match.game().ok_or(Error::FormatInvalid)?;
+                for (column, action) in actions(line) {
+                    let player = if column >= RIGHT_COLUMN {
+                        Player::Player1
+                    } else {
+                        Player::Player0
+                    };
+                    apply(game, player, action)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the points of a match header, e.g. " 7 point match"
+pub(super) fn match_points(line: &str) -> Result<u32, Error> {
+    line.trim()
+        .strip_suffix("point match")
+        .and_then(|p| p.trim().parse().ok())
+        .ok_or(Error::FormatInvalid)
+}
+
+impl FromStr for Mat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_start();
+        let (header, games) = s.split_once('\n').unwrap_or((s, ""));
+        let mut mat = Mat {
+            names: Default::default(),
+            r#match: Match::new().with_points(match_points(header)?),
+        };
+        mat.read_games(games)?;
+        Ok(mat)
+    }
+}
+
+/// Write a move with the bar as 25 and off as 0
+fn mat_move(mv: &CheckerMove) -> String {
+    mv.to_string()
+        .split('/')
+        .map(|field| match field {
+            "bar" => "25",
+            "off" => "0",
+            field => field,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns the actions of both players in a game, in the order they happened, with the moves
+/// written by the given function
+pub(super) fn game_actions(
+    game: &Game,
+    write_move: fn(&CheckerMove) -> String,
+) -> Vec<(Player, String)> {
+    let mut actions: Vec<(Player, String)> = Vec::new();
+    for event in game.record().events() {
+        match event {
+            Event::Roll {
+                player: Player::Nobody,
+                ..
+            }
+            | Event::AutomaticDouble { .. }
+            | Event::Resign { .. } => {}
+            Event::Roll { player, dices } => actions.push((
+                *player,
+                format!("{}{}:", dices.0.max(dices.1), dices.0.min(dices.1)),
+            )),
+            Event::Move { mv, .. } => {
+                if let Some((_, action)) = actions.last_mut() {
+                    action.push(' ');
+                    action.push_str(&write_move(mv));
+                }
+            }
+            Event::CubeOffer { player, value } => {
+                actions.push((*player, format!("Doubles => {}", value)))
+            }
+            Event::CubeTake { player } => actions.push((*player, "Takes".to_string())),
+            Event::CubeDrop { player } => actions.push((*player, "Drops".to_string())),
+            Event::Beaver { player, value } => {
+                actions.push((*player, format!("Beavers => {}", value)))
+            }
+            Event::Raccoon { player, value } => {
+                actions.push((*player, format!("Raccoons => {}", value)))
+            }
+        }
+    }
+    actions
+}
+
+/// Returns the points won, e.g. "1 point" or "2 points"
+pub(super) fn points(points: u64) -> String {
+    match points {
+        1 => "1 point".to_string(),
+        n => format!("{} points", n),
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Mat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, " {} point match", self.r#match.rules.points)?;
+        for (i, game) in self.r#match.games.iter().enumerate() {
+            let score = score(&self.r#match.games[..i]);
+            let crawford = if game.is_crawford() {
+                " (Crawford)"
+            } else {
+                ""
+            };
+            writeln!(f, "\n Game {}{}", i + 1, crawford)?;
+            writeln!(
+                f,
+                " {:<33}{} : {}",
+                format!("{} : {}", self.names.0, score.0),
+                self.names.1,
+                score.1
+            )?;
+
+            let mut line = 0;
+            let mut left: Option<String> = None;
+            for (player, action) in game_actions(game, mat_move) {
+                if player == Player::Player0 {
+                    if let Some(left) = left.take() {
+                        line += 1;
+                        writeln!(f, "{:>3}) {}", line, left)?;
+                    }
+                    left = Some(action);
+                } else {
+                    line += 1;
+                    let left = left.take().unwrap_or_default();
+                    writeln!(f, "{:>3}) {:<28} {}", line, left, action)?;
+                }
+            }
+            if let Some(left) = left {
+                writeln!(f, "{:>3}) {}", line + 1, left)?;
+            }
+
+            if let Some(result) = game.result() {
+                let points = points(result.points);
+                let column = match result.winner {
+                    Player::Player1 => 34,
+                    _ => 6,
+                };
+                writeln!(f, "{:column$}Wins {}", "", points, column = column)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Field;
+
+    const MAT: &str = " 3 point match
+
+ Game 1
+ Alice : 0                        Bob : 0
+  1) 31: 8/5 6/5                  52: 24/22 13/8
+  2) 64: 24/18 13/9               Doubles => 2
+  3) Takes                        61: 13/7* 8/7
+  4) 21: 25/23 24/23              31: 8/5 6/5
+  5) Doubles => 4                 Drops
+      Wins 2 points
+
+ Game 2 (Crawford)
+ Alice : 2                        Bob : 0
+  1)                              52: 13/11 13/8
+  2) 66: 24/18 24/18 13/7 13/7
+                                  Wins 1 point
+";
+
+    #[test]
+    fn test_parse() -> Result<(), Error> {
+        let mat: Mat = MAT.parse()?;
+        assert_eq!(mat.names, ("Alice".to_string(), "Bob".to_string()));
+        let m = &mat.r#match;
+        assert_eq!(m.rules.points, 3);
+        assert_eq!(m.games.len(), 2);
+        assert_eq!(m.score(), (2, 1));
+        assert_eq!(m.winner(), None);
+        assert!(m.games[1].is_crawford());
+
+        let g = &m.games[0];
+        assert_eq!(g.cube.value(), 2);
+        // the checker of Alice has been hit and entered from the bar
+        assert_eq!(g.board.get_checkers(Player::Player0, Field::Bar)?, 0);
+        assert_eq!(g.board.get_checkers(Player::Player0, Field::Point(22))?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), Error> {
+        let mat: Mat = MAT.parse()?;
+        assert_eq!(mat.to_string(), MAT);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("".parse::<Mat>().is_err());
+        assert!(
+            " 3 point match\n\n Game 1\n A : 0  B : 0\n  1) 31: 8/4 6/5\n"
+                .parse::<Mat>()
+                .is_err()
+        );
+        // opening rolls can not be doubles
+        assert!(
+            " 3 point match\n\n Game 1\n A : 0  B : 0\n  1) 33: 8/5(2)\n"
+                .parse::<Mat>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_actions() {
+        let line = "  3) Takes                         61: 13/7* 8/7";
+        assert_eq!(actions(line), vec![(5, "Takes"), (35, "61: 13/7* 8/7")]);
+        assert_eq!(actions("      Wins 1 point"), vec![(6, "Wins 1 point")]);
+        assert_eq!(victory(4, 2).ok(), Some(Victory::Gammon));
+        assert!(victory(3, 2).is_err());
+    }
+}
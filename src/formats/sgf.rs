@@ -0,0 +1,411 @@
+//! # Smart Game Format
+use super::jellyfish::{apply, victory};
+use crate::game::Phase;
+use crate::r#match::{score, Match};
+use crate::record::Event;
+use crate::rules::{Field, MatchRules, Player, Variant};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// The application written to the files
+const APPLICATION: &str = concat!("backgammon:", env!("CARGO_PKG_VERSION"));
+
+/// A node of a game tree, i.e. the properties with their values
+type Node = Vec<(String, Vec<String>)>;
+
+/// Represents a match in the Smart Game Format (SGF) for Backgammon (`GM[6]`), as written by GNU
+/// Backgammon, e.g.:
+///
+/// ```text
+/// (;FF[4]GM[6]CA[UTF-8]MI[length:3][game:0][ws:0][bs:0]PB[Alice]PW[Bob]RU[Crawford]
+/// ;B[31qtst]
+/// ;W[52xvmh]
+/// ;B[double]
+/// ;W[drop]
+/// )
+/// ```
+///
+/// Every game is a game tree of its own. Black is player 0 and white is player 1. A move consists
+/// of the dices followed by the fields a checker moves from and to for every dice used, where the
+/// points are the letters "a" to "x" counted from the home board of white, "y" is the bar and
+/// "z" is off. The result of a game is written as `RE[B+2]`, with a trailing "R" if the game has
+/// been resigned.
+///
+/// Reading a match replays all games, therefore every roll, move and cube action has to be legal.
+/// Variations are not supported, and properties which are not needed to replay the games, e.g.
+/// comments and analysis, are ignored.
+/// ```
+/// use backgammon::formats::Sgf;
+/// use backgammon::rules::Player;
+///
+/// let s = "(;FF[4]GM[6]MI[length:3]PB[Alice]PW[Bob];B[31qtst];W[52xvmh])";
+/// let sgf: Sgf = s.parse().unwrap();
+/// assert_eq!(sgf.names.1, "Bob");
+/// assert_eq!(sgf.r#match.games[0].who_plays, Player::Player0);
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sgf {
+    /// The names of player 0 and player 1
+    pub names: (String, String),
+    /// The match with all its games
+    pub r#match: Match,
+}
+
+/// Parse a collection of game trees into the nodes of every tree
+fn parse_trees(s: &str) -> Result<Vec<Vec<Node>>, Error> {
+    let mut trees = Vec::new();
+    let mut chars = s.chars();
+    loop {
+        match chars.next() {
+            None => break,
+            Some(c) if c.is_whitespace() => continue,
+            Some('(') => {}
+            Some(_) => return Err(Error::FormatInvalid),
+        }
+
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut id = String::new();
+        loop {
+            match chars.next().ok_or(Error::FormatInvalid)? {
+                ')' => break,
+                ';' => nodes.push(Vec::new()),
+                '[' => {
+                    let mut value = String::new();
+                    loop {
+                        match chars.next().ok_or(Error::FormatInvalid)? {
+                            ']' => break,
+                            '\\' => value.push(chars.next().ok_or(Error::FormatInvalid)?),
+                            c => value.push(c),
+                        }
+                    }
+                    let node = nodes.last_mut().ok_or(Error::FormatInvalid)?;
+                    if id.is_empty() {
+                        // another value of the previous property
+                        node.last_mut().ok_or(Error::FormatInvalid)?.1.push(value);
+                    } else {
+                        node.push((core::mem::take(&mut id), vec![value]));
+                    }
+                }
+                c if c.is_ascii_alphabetic() => id.push(c),
+                c if c.is_whitespace() => {}
+                _ => return Err(Error::FormatInvalid),
+            }
+        }
+        trees.push(nodes);
+    }
+    Ok(trees)
+}
+
+/// Returns the values of a property of a node
+fn property<'a>(node: &'a Node, id: &str) -> Option<&'a [String]> {
+    node.iter()
+        .find(|(i, _)| i == id)
+        .map(|(_, values)| values.as_slice())
+}
+
+/// Returns the field of a player encoded by a letter
+fn field(player: Player, letter: u8) -> Result<Field, Error> {
+    match (letter, player) {
+        (b'y', _) => Ok(Field::Bar),
+        (b'z', _) => Ok(Field::Off),
+        (b'a'..=b'x', Player::Player1) => Ok(Field::Point(usize::from(letter - b'a'))),
+        (b'a'..=b'x', _) => Ok(Field::Point(usize::from(b'x' - letter))),
+        _ => Err(Error::FormatInvalid),
+    }
+}
+
+/// Returns the letter encoding a field of a player
+fn letter(player: Player, field: Field) -> char {
+    match (field, player) {
+        (Field::Bar, _) => 'y',
+        (Field::Off, _) => 'z',
+        (Field::Point(p), Player::Player1) => (b'a' + p as u8) as char,
+        (Field::Point(p), _) => (b'x' - p as u8) as char,
+    }
+}
+
+/// Translate the value of a move node, e.g. "31qtst", to an action of a Jellyfish match file,
+/// e.g. "31: 8/5 6/5"
+fn action(player: Player, value: &str) -> Result<String, Error> {
+    match value {
+        "double" => return Ok("Doubles".to_string()),
+        "take" => return Ok("Takes".to_string()),
+        "drop" => return Ok("Drops".to_string()),
+        "beaver" => return Ok("Beavers".to_string()),
+        "raccoon" => return Ok("Raccoons".to_string()),
+        _ => {}
+    }
+    let bytes = value.as_bytes();
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return Err(Error::FormatInvalid);
+    }
+    let mut action = format!("{}{}:", &value[..1], &value[1..2]);
+    for pair in bytes[2..].chunks(2) {
+        let from = field(player, pair[0])?;
+        let to = field(player, pair[1])?;
+        action.push_str(&format!(" {}/{}", from, to));
+    }
+    Ok(action)
+}
+
+impl FromStr for Sgf {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sgf = Sgf::default();
+        for (i, tree) in parse_trees(s)?.iter().enumerate() {
+            let root = tree.first().ok_or(Error::FormatInvalid)?;
+            if property(root, "GM").is_some_and(|gm| gm != ["6"]) {
+                return Err(Error::FormatInvalid);
+            }
+            if i == 0 {
+                let points = property(root, "MI")
+                    .and_then(|mi| mi.iter().find_map(|v| v.strip_prefix("length:")))
+                    .map_or(Ok(0), str::parse)
+                    .map_err(|_| Error::FormatInvalid)?;
+                let rules = property(root, "RU").map_or("", |ru| ru[0].as_str());
+                sgf.r#match = Match::new().with_points(points);
+                sgf.r#match.rules.crawford = rules.split(':').any(|r| r == "Crawford");
+                sgf.r#match.rules.jacoby = rules.split(':').any(|r| r == "Jacoby");
+                if rules.split(':').any(|r| r == "Nackgammon") {
+                    sgf.r#match.rules.variant = Variant::Nackgammon;
+                }
+                let name = |id| property(root, id).map(|n| n[0].clone()).unwrap_or_default();
+                sgf.names = (name("PB"), name("PW"));
+            }
+
+            let game = sgf.r#match.new_game()?;
+            for node in tree.iter().skip(1) {
+                for (id, values) in node {
+                    let player = match id.as_str() {
+                        "B" => Player::Player0,
+                        "W" => Player::Player1,
+                        _ => continue,
+                    };
+                    let value = values.first().ok_or(Error::FormatInvalid)?;
+                    apply(game, player, &action(player, value)?)?;
+                }
+            }
+
+            // a game which has not been played to its end has been resigned
+            if game.phase != Phase::GameOver {
+                if let Some(result) = property(root, "RE") {
+                    let (winner, points) = result[0].split_once('+').ok_or(Error::FormatInvalid)?;
+                    let loser = match winner {
+                        "B" => Player::Player1,
+                        "W" => Player::Player0,
+                        _ => return Err(Error::FormatInvalid),
+                    };
+                    let points = points
+                        .trim_end_matches('R')
+                        .parse()
+                        .map_err(|_| Error::FormatInvalid)?;
+                    game.resign(loser, victory(points, game.cube.value())?)?;
+                }
+            }
+        }
+        Ok(sgf)
+    }
+}
+
+/// Escape a value of a property
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+/// Returns the color of a player
+fn color(player: Player) -> char {
+    match player {
+        Player::Player1 => 'W',
+        _ => 'B',
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Sgf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rules = &self.r#match.rules;
+        for (i, game) in self.r#match.games.iter().enumerate() {
+            let score = score(&self.r#match.games[..i]);
+            write!(
+                f,
+                "(;FF[4]GM[6]CA[UTF-8]AP[{}]MI[length:{}][game:{}][ws:{}][bs:{}]PB[{}]PW[{}]",
+                APPLICATION,
+                rules.points,
+                i,
+                score.1,
+                score.0,
+                escape(&self.names.0),
+                escape(&self.names.1)
+            )?;
+            let mut ru = Vec::new();
+            if rules.crawford {
+                ru.push("Crawford");
+            }
+            if game.is_crawford() {
+                ru.push("CrawfordGame");
+            }
+            if rules.jacoby {
+                ru.push("Jacoby");
+            }
+            if rules.variant == Variant::Nackgammon {
+                ru.push("Nackgammon");
+            }
+            if !ru.is_empty() {
+                write!(f, "RU[{}]", ru.join(":"))?;
+            }
+            if let Some(result) = game.result() {
+                let resigned = matches!(game.record().last(), Some(Event::Resign { .. }));
+                write!(
+                    f,
+                    "RE[{}+{}{}]",
+                    color(result.winner),
+                    result.points,
+                    if resigned { "R" } else { "" }
+                )?;
+            }
+
+            let mut open = false;
+            for event in game.record().events() {
+                let node = match event {
+                    Event::Roll {
+                        player: Player::Nobody,
+                        ..
+                    }
+                    | Event::AutomaticDouble { .. }
+                    | Event::Resign { .. } => continue,
+                    Event::Move { player, mv } => {
+                        let mut from = mv.from;
+                        for to in mv.path().unwrap_or_else(|_| vec![mv.to]) {
+                            write!(f, "{}{}", letter(*player, from), letter(*player, to))?;
+                            from = to;
+                        }
+                        continue;
+                    }
+                    Event::Roll { player, dices } => {
+                        format!("{}[{}{}", color(*player), dices.0, dices.1)
+                    }
+                    Event::CubeOffer { player, .. } => format!("{}[double", color(*player)),
+                    Event::CubeTake { player } => format!("{}[take", color(*player)),
+                    Event::CubeDrop { player } => format!("{}[drop", color(*player)),
+                    Event::Beaver { player, .. } => format!("{}[beaver", color(*player)),
+                    Event::Raccoon { player, .. } => format!("{}[raccoon", color(*player)),
+                };
+                if open {
+                    write!(f, "]")?;
+                }
+                write!(f, "\n;{}", node)?;
+                open = true;
+            }
+            if open {
+                write!(f, "]")?;
+            }
+            writeln!(f, "\n)")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Victory;
+
+    /// The first game of the Jellyfish match file used in the tests of the format
+    const GAME: &str = "MI[length:3][game:0][ws:0][bs:0]PB[Alice]PW[Bob]RU[Crawford]RE[B+2]
+;B[31qtst]
+;W[52xvmh]
+;B[64aglp]
+;W[double]
+;B[take]
+;W[61mghg]
+;B[21ybab]
+;W[31hefe]
+;B[double]
+;W[drop]
+)
+";
+
+    #[test]
+    fn test_parse() -> Result<(), Error> {
+        let sgf: Sgf = format!("(;FF[4]GM[6]{}", GAME).parse()?;
+        assert_eq!(sgf.names, ("Alice".to_string(), "Bob".to_string()));
+        let m = &sgf.r#match;
+        assert_eq!(m.rules.points, 3);
+        assert!(m.rules.crawford);
+        assert_eq!(m.games.len(), 1);
+        assert_eq!(m.score(), (2, 0));
+        assert_eq!(m.games[0].cube.value(), 2);
+        assert_eq!(
+            m.games[0]
+                .board
+                .get_checkers(Player::Player0, Field::Point(22))?,
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), Error> {
+        let s = format!("(;FF[4]GM[6]CA[UTF-8]AP[{}]{}", APPLICATION, GAME);
+        let mut sgf: Sgf = s.parse()?;
+        assert_eq!(sgf.to_string(), s);
+
+        // a resigned game with a name to escape
+        sgf.names.1 = "[Bob]".to_string();
+        sgf.r#match
+            .new_game()?
+            .resign(Player::Player0, Victory::Single)?;
+        let s = sgf.to_string();
+        assert!(s.ends_with(&format!(
+            "(;FF[4]GM[6]CA[UTF-8]AP[{}]MI[length:3][game:1][ws:0][bs:2]\
+             PB[Alice]PW[[Bob\\]]RU[Crawford:CrawfordGame]RE[W+1R]\n)\n",
+            APPLICATION
+        )));
+        let sgf: Sgf = s.parse()?;
+        assert_eq!(sgf.names.1, "[Bob]");
+        assert_eq!(sgf.r#match.score(), (2, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nackgammon() -> Result<(), Error> {
+        use crate::rules::GameRules;
+
+        let mut sgf = Sgf {
+            r#match: Match::new()
+                .with_points(1)
+                .with_variant(Variant::Nackgammon),
+            ..Default::default()
+        };
+        let game = sgf.r#match.new_game()?;
+        let _ = game.roll_with((2, 1))?;
+        game.play_die(Field::Point(22), 2)?;
+        game.play_die(Field::Point(23), 1)?;
+        let s = sgf.to_string();
+        assert!(s.contains("RU[Crawford:Nackgammon]"));
+        let parsed: Sgf = s.parse()?;
+        assert_eq!(parsed.r#match.rules.variant, Variant::Nackgammon);
+        assert_eq!(parsed.r#match.games[0].board, sgf.r#match.games[0].board);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("(;FF[4]GM[1])".parse::<Sgf>().is_err());
+        assert!("(;FF[4]GM[6]".parse::<Sgf>().is_err());
+        assert!("(;FF[4]GM[6];B[31qtsu])".parse::<Sgf>().is_err());
+        assert!("(;FF[4]GM[6];B[3])".parse::<Sgf>().is_err());
+        assert!("x".parse::<Sgf>().is_err());
+    }
+}
@@ -0,0 +1,296 @@
+//! # Snowie Text Position
+use crate::game::{Game, Phase};
+use crate::rules::{Board, Cube, Dices, Occupancy, Player, PlayerBoard};
+use crate::Error;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents a position in the Snowie text format, a single line of 40 fields separated by
+/// semicolons, which is used by Snowie, GammonEmpire, and other programs for the clipboard.
+///
+/// The fields are, in this order: the match length (0 for money games), the Crawford flag, the
+/// Jacoby flag, the beaver flag, an unused field, the names of the player on roll and of the
+/// opponent, an unused field, the scores of the player on roll and of the opponent, the cube
+/// value, the cube owner (1 for the player on roll, -1 for the opponent, 0 if centered), the
+/// checkers of the player on roll on the bar, the 24 points from the point-of-view of the player on
+/// roll (negative for the checkers of the opponent), the checkers of the opponent on the bar, and
+/// the two dices.
+///
+/// The format does not say which player is on roll, therefore the player on roll becomes player 0
+/// when parsing.
+/// ```
+/// use backgammon::formats::Snowie;
+/// use backgammon::rules::{Board, Player};
+///
+/// let s = "0;0;1;0;0;Alice;Bob;0;0;0;1;0;0;-2;0;0;0;0;5;0;3;0;0;0;-5;5;0;0;0;-3;0;-5;0;0;0;0;2;0;3;1;";
+/// let position: Snowie = s.parse().unwrap();
+/// assert_eq!(position.board, Board::new());
+/// assert_eq!(position.names.0, "Alice");
+/// assert_eq!(position.dices, (3, 1));
+/// assert_eq!(position.to_string(), s);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snowie {
+    /// The checkers of both players
+    pub board: Board,
+    /// The player on roll
+    pub player_on_roll: Player,
+    /// The names of player 0 and player 1
+    pub names: (String, String),
+    /// The value of the cube
+    pub cube_value: u64,
+    /// The owner of the cube, nobody if centered
+    pub cube_owner: Player,
+    /// The dices rolled, (0, 0) if not rolled yet
+    pub dices: (u8, u8),
+    /// The score of player 0 and player 1
+    pub score: (u32, u32),
+    /// The length of the match, 0 for a money game
+    pub match_length: u32,
+    /// Whether this is the Crawford game
+    pub crawford: bool,
+    /// Whether the Jacoby rule applies
+    pub jacoby: bool,
+    /// Whether the beaver rule applies
+    pub beaver: bool,
+}
+
+impl Snowie {
+    /// Describe a game of a match with the given length and score. Use a length of 0 for a money
+    /// game.
+    pub fn from_game(game: &Game, match_length: u32, score: (u32, u32)) -> Self {
+        Snowie {
+            board: game.board.clone(),
+            player_on_roll: match game.who_plays {
+                Player::Nobody => Player::Player0,
+                player => player,
+            },
+            names: (Player::Player0.to_string(), Player::Player1.to_string()),
+            cube_value: game.cube.value(),
+            cube_owner: game.cube.owner(),
+            dices: match game.phase {
+                Phase::AwaitingMove => game.dices.values,
+                _ => (0, 0),
+            },
+            score,
+            match_length,
+            crawford: game.is_crawford(),
+            jacoby: game.rules.jacoby,
+            beaver: game.rules.beaver,
+        }
+    }
+
+    /// Set the board, the cube, the dices, the player on roll, the phase, and the rules of a game
+    /// as described by this position.
+    pub fn apply(&self, game: &mut Game) -> Result<(), Error> {
+        self.board.validate()?;
+        let mut cube = Cube::default();
+        cube.set(self.cube_value)?;
+        cube.set_owner(self.cube_owner);
+        game.board = self.board.clone();
+        game.cube = cube;
+        game.crawford = self.crawford;
+        game.rules.jacoby = self.jacoby;
+        game.rules.beaver = self.beaver;
+        game.dices = Dices::new(self.dices);
+        game.who_plays = self.player_on_roll;
+        game.phase = if self.dices == (0, 0) {
+            Phase::AwaitingRoll
+        } else {
+            Phase::AwaitingMove
+        };
+        Ok(())
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Snowie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let on_roll = self.player_on_roll;
+        // the position is seen from the player on roll
+        let board = match on_roll {
+            Player::Player1 => self.board.flipped(),
+            _ => self.board.clone(),
+        };
+        let (names, score) = match on_roll {
+            Player::Player1 => ((&self.names.1, &self.names.0), (self.score.1, self.score.0)),
+            _ => ((&self.names.0, &self.names.1), self.score),
+        };
+        let owner = match self.cube_owner {
+            Player::Nobody => 0,
+            player if player == on_roll => 1,
+            _ => -1,
+        };
+
+        write!(
+            f,
+            "{};{};{};{};0;{};{};0;{};{};{};{};{};",
+            self.match_length,
+            u8::from(self.crawford),
+            u8::from(self.jacoby),
+            u8::from(self.beaver),
+            names.0,
+            names.1,
+            score.0,
+            score.1,
+            self.cube_value,
+            owner,
+            board.bar[0]
+        )?;
+        for point in board.points {
+            match point.owner {
+                Player::Player1 => write!(f, "-{};", point.count)?,
+                _ => write!(f, "{};", point.count)?,
+            }
+        }
+        write!(f, "{};{};{};", board.bar[1], self.dices.0, self.dices.1)
+    }
+}
+
+impl FromStr for Snowie {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.trim().split(';').map(str::trim).collect();
+        if fields.len() < 40 {
+            return Err(Error::FormatInvalid);
+        }
+        let number = |s: &str| s.parse::<i32>().map_err(|_| Error::FormatInvalid);
+        let unsigned = |s: &str| s.parse::<u32>().map_err(|_| Error::FormatInvalid);
+        let checkers =
+            |s: &str| u8::try_from(number(s)?.unsigned_abs()).map_err(|_| Error::FormatInvalid);
+        let flag = |s: &str| match s {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(Error::FormatInvalid),
+        };
+
+        let mut me = PlayerBoard {
+            board: [0; 24],
+            bar: checkers(fields[12])?,
+            off: 0,
+        };
+        let mut opponent = PlayerBoard {
+            board: [0; 24],
+            bar: checkers(fields[37])?,
+            off: 0,
+        };
+        for field in 0..24 {
+            let n = number(fields[13 + field])?;
+            let amount = checkers(fields[13 + field])?;
+            if n > 0 {
+                me.board[field] = amount;
+            } else {
+                opponent.board[23 - field] = amount;
+            }
+        }
+        for p in [&mut me, &mut opponent] {
+            let on_board = p.board.iter().map(|n| u32::from(*n)).sum::<u32>() + u32::from(p.bar);
+            p.off = u8::try_from(15u32.checked_sub(on_board).ok_or(Error::FormatInvalid)?)
+                .map_err(|_| Error::FormatInvalid)?;
+        }
+        let board = Board::from_sides([me, opponent], Occupancy::default(), [0, 0])?;
+        board.validate()?;
+
+        let cube_value = u64::from(unsigned(fields[10])?);
+        if !cube_value.is_power_of_two() {
+            return Err(Error::FormatInvalid);
+        }
+        let dices = (
+            u8::try_from(unsigned(fields[38])?).map_err(|_| Error::FormatInvalid)?,
+            u8::try_from(unsigned(fields[39])?).map_err(|_| Error::FormatInvalid)?,
+        );
+        if dices.0 > 6 || dices.1 > 6 || (dices.0 == 0) != (dices.1 == 0) {
+            return Err(Error::FormatInvalid);
+        }
+
+        Ok(Snowie {
+            board,
+            player_on_roll: Player::Player0,
+            names: (fields[5].to_string(), fields[6].to_string()),
+            cube_value,
+            cube_owner: match number(fields[11])? {
+                1 => Player::Player0,
+                -1 => Player::Player1,
+                0 => Player::Nobody,
+                _ => return Err(Error::FormatInvalid),
+            },
+            dices,
+            score: (unsigned(fields[8])?, unsigned(fields[9])?),
+            match_length: unsigned(fields[0])?,
+            crawford: flag(fields[1])?,
+            jacoby: flag(fields[2])?,
+            beaver: flag(fields[3])?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Field;
+
+    const START: &str =
+        "0;0;1;0;0;Alice;Bob;0;0;0;1;0;0;-2;0;0;0;0;5;0;3;0;0;0;-5;5;0;0;0;-3;0;-5;0;0;0;0;2;0;3;1;";
+
+    #[test]
+    fn test_parse() -> Result<(), Error> {
+        let s = "7;1;0;0;0;Alice;Bob;0;6;3;2;-1;1;-1;0;0;0;0;4;0;3;0;0;0;-5;5;0;0;0;-3;0;-5;0;0;0;0;2;1;0;0;";
+        let position: Snowie = s.parse()?;
+        assert_eq!(position.match_length, 7);
+        assert!(position.crawford);
+        assert_eq!(position.score, (6, 3));
+        assert_eq!(position.cube_value, 2);
+        assert_eq!(position.cube_owner, Player::Player1);
+        assert_eq!(position.dices, (0, 0));
+        let board = &position.board;
+        assert_eq!(board.get_checkers(Player::Player0, Field::Bar)?, 1);
+        assert_eq!(board.get_checkers(Player::Player0, Field::Point(5))?, 4);
+        assert_eq!(board.get_checkers(Player::Player1, Field::Bar)?, 1);
+        assert_eq!(board.get_checkers(Player::Player1, Field::Point(23))?, 1);
+        assert_eq!(position.to_string(), s);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("0;0;1;0".parse::<Snowie>().is_err());
+        // too many checkers
+        assert!(START
+            .replace(";5;0;3;", ";6;0;3;")
+            .parse::<Snowie>()
+            .is_err());
+        // invalid cube value
+        assert!(START
+            .replace("0;0;1;0;0;-2", "0;0;3;0;0;-2")
+            .parse::<Snowie>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_game() -> Result<(), Error> {
+        let position: Snowie = START.parse()?;
+        let mut g = Game::new();
+        position.apply(&mut g)?;
+        assert_eq!(g.who_plays, Player::Player0);
+        assert_eq!(g.phase, Phase::AwaitingMove);
+        assert!(g.rules.jacoby);
+
+        // the position is written from the point-of-view of the player on roll
+        g.who_plays = Player::Player1;
+        g.cube.set(2)?;
+        g.cube.set_owner(Player::Player1);
+        let position = Snowie::from_game(&g, 0, (0, 0));
+        assert_eq!(
+            position.to_string(),
+            "0;0;1;0;0;Player 1;Player 0;0;0;0;2;1;0;-2;0;0;0;0;5;0;3;0;0;0;-5;5;0;0;0;-3;0;-5;0;0;0;0;2;0;3;1;"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+//! # Game Transcripts
+use super::jellyfish::{actions, apply, game_actions, points, victory};
+use crate::game::Game;
+use crate::rules::Player;
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+/// Represents a human-readable transcript of a game as found in match books, e.g.:
+///
+/// ```text
+///      Alice               Bob
+///   1) 31: 8/5 6/5         52: 24/22 13/8
+///   2) 64: 24/18 13/9      Doubles => 2
+///   3) Takes               61: 13/7* 8/7
+///   4) 21: bar/23 24/23    31: 8/5 6/5
+///   5) Doubles => 4        Drops
+///      Alice wins 2 points
+/// ```
+///
+/// The first line names the players, the player in the left column is player 0 and the player in
+/// the right column is player 1. The right column starts where the name of player 1 starts, which
+/// is why names must not contain two consecutive spaces. Every player's moves are given from the
+/// player's own point-of-view in the notation of [`CheckerMove`](crate::rules::CheckerMove). The
+/// last line tells the winner, if the game is over. Reading a transcript replays the game with the
+/// default rules, therefore every roll, move and cube action has to be legal.
+/// ```
+/// use backgammon::formats::Transcript;
+/// use backgammon::rules::Player;
+///
+/// let s = "     Alice          Bob\n  1)                52: 13/11 13/8\n  2) 31: 8/5 6/5\n";
+/// let transcript: Transcript = s.parse().unwrap();
+/// assert_eq!(transcript.names.1, "Bob");
+/// assert_eq!(transcript.game.who_plays, Player::Player1);
+/// assert_eq!(transcript.to_string(), s);
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transcript {
+    /// The names of player 0 and player 1
+    pub names: (String, String),
+    /// The game with all its actions
+    pub game: Game,
+}
+
+/// Returns the names of the players in the first line of a transcript, and the column the name
+/// of player 1 starts at
+fn parse_names(line: &str) -> Result<(String, String, usize), Error> {
+    let start0 = line.find(|c: char| !c.is_whitespace()).unwrap_or(0);
+    let end0 = line[start0..]
+        .find("  ")
+        .map(|i| start0 + i)
+        .ok_or(Error::FormatInvalid)?;
+    let start1 = line[end0..]
+        .find(|c: char| !c.is_whitespace())
+        .map(|i| end0 + i)
+        .ok_or(Error::FormatInvalid)?;
+    Ok((
+        line[start0..end0].to_string(),
+        line[start1..].trim().to_string(),
+        start1,
+    ))
+}
+
+/// Returns whether a line of a transcript is numbered, e.g. "  3) Takes"
+fn is_numbered(line: &str) -> bool {
+    match line.trim_start().split_once(')') {
+        Some((n, _)) => !n.is_empty() && n.bytes().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+impl Transcript {
+    /// Apply the last line of a transcript, e.g. "Bob wins 2 points", to the game
+    fn finish(&mut self, line: &str) -> Result<(), Error> {
+        let (winner, rest) = [
+            (Player::Player0, &self.names.0),
+            (Player::Player1, &self.names.1),
+        ]
+        .into_iter()
+        .find_map(|(player, name)| {
+            line.strip_prefix(name.as_str())
+                .and_then(|rest| rest.strip_prefix(" wins "))
+                .map(|rest| (player, rest))
+        })
+        .ok_or(Error::FormatInvalid)?;
+        let won = rest
+            .split_whitespace()
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or(Error::FormatInvalid)?;
+
+        let game = &mut self.game;
+        if game.result().is_none() {
+            game.resign(winner.other(), victory(won, game.cube.value())?)?;
+        }
+        match game.result() {
+            Some(result) if result.winner == winner && result.points == won => Ok(()),
+            _ => Err(Error::FormatInvalid),
+        }
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.trim().is_empty());
+        let header = lines.next().ok_or(Error::FormatInvalid)?;
+        let (name0, name1, right_column) = parse_names(header)?;
+        let mut transcript = Transcript {
+            names: (name0, name1),
+            game: Game::new(),
+        };
+
+        let mut finished = false;
+        for line in lines {
+            if finished {
+                return Err(Error::FormatInvalid);
+            }
+            if !is_numbered(line) {
+                transcript.finish(line.trim())?;
+                finished = true;
+                continue;
+            }
+            for (column, action) in actions(line) {
+                let player = if column >= right_column {
+                    Player::Player1
+                } else {
+                    Player::Player0
+                };
+                apply(&mut transcript.game, player, action)?;
+            }
+        }
+        Ok(transcript)
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let actions = game_actions(&self.game, ToString::to_string);
+        // the left column is wide enough for the longest action of player 0
+        let width = actions
+            .iter()
+            .filter(|(player, _)| *player == Player::Player0)
+            .map(|(_, action)| action.len())
+            .chain([self.names.0.len()])
+            .max()
+            .unwrap_or_default()
+            + 4;
+        writeln!(f, "     {:<width$}{}", self.names.0, self.names.1)?;
+
+        let mut line = 0;
+        let mut left: Option<String> = None;
+        for (player, action) in actions {
+            if player == Player::Player0 {
+                if let Some(left) = left.take() {
+                    line += 1;
+                    writeln!(f, "{:>3}) {}", line, left)?;
+                }
+                left = Some(action);
+            } else {
+                line += 1;
+                let left = left.take().unwrap_or_default();
+                writeln!(f, "{:>3}) {:<width$}{}", line, left, action)?;
+            }
+        }
+        if let Some(left) = left {
+            writeln!(f, "{:>3}) {}", line + 1, left)?;
+        }
+
+        if let Some(result) = self.game.result() {
+            let winner = match result.winner {
+                Player::Player1 => &self.names.1,
+                _ => &self.names.0,
+            };
+            writeln!(f, "     {} wins {}", winner, points(result.points))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Phase;
+    use crate::rules::Field;
+
+    const TRANSCRIPT: &str = "     Alice               Bob Smith
+  1) 31: 8/5 6/5         52: 24/22 13/8
+  2) 64: 24/18 13/9      Doubles => 2
+  3) Takes               61: 13/7* 8/7
+  4) 21: bar/23 24/23    31: 8/5 6/5
+  5) Doubles => 4        Drops
+     Alice wins 2 points
+";
+
+    #[test]
+    fn test_parse() -> Result<(), Error> {
+        let transcript: Transcript = TRANSCRIPT.parse()?;
+        assert_eq!(
+            transcript.names,
+            ("Alice".to_string(), "Bob Smith".to_string())
+        );
+        let g = &transcript.game;
+        assert_eq!(g.phase, Phase::GameOver);
+        assert_eq!(g.cube.value(), 2);
+        assert_eq!(g.board.get_checkers(Player::Player0, Field::Bar)?, 0);
+        assert_eq!(g.board.get_checkers(Player::Player0, Field::Point(22))?, 2);
+        let result = g.result().ok_or(Error::FormatInvalid)?;
+        assert_eq!(result.winner, Player::Player0);
+        assert_eq!(result.points, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), Error> {
+        let transcript: Transcript = TRANSCRIPT.parse()?;
+        assert_eq!(transcript.to_string(), TRANSCRIPT);
+
+        // the columns are aligned to the longest action of player 0
+        let s =
+            "     A          B\n  1)            52: 13/11 13/8\n  2) 66: 24/18 24/18 13/7 13/7\n";
+        let transcript: Transcript = s.parse()?;
+        let written = transcript.to_string();
+        assert!(written.starts_with(&format!("     A{:28}B\n", "")));
+        assert_eq!(written.parse::<Transcript>()?.to_string(), written);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("".parse::<Transcript>().is_err());
+        assert!("     Alice\n".parse::<Transcript>().is_err());
+        assert!("     A    B\n  1) 31: 8/4 6/5\n"
+            .parse::<Transcript>()
+            .is_err());
+        // the winner has to match the game
+        assert!(TRANSCRIPT
+            .replace("Alice wins 2", "Bob Smith wins 2")
+            .parse::<Transcript>()
+            .is_err());
+        assert!(TRANSCRIPT
+            .replace("Alice wins 2", "Alice wins 4")
+            .parse::<Transcript>()
+            .is_err());
+        // nothing follows the winner
+        assert!(format!("{}  6) Takes\n", TRANSCRIPT)
+            .parse::<Transcript>()
+            .is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! # eXtreme Gammon Text Match Export
+use super::jellyfish::{match_points, Mat};
+use crate::r#match::Match;
+use crate::rules::MatchRules;
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+/// Represents a match exported by eXtreme Gammon or XG Mobile as text, e.g.:
+///
+/// ```text
+/// ; [Site "XG Mobile"]
+/// ; [Player 1 "Alice"]
+/// ; [Player 2 "Bob"]
+/// ; [Crawford "On"]
+///
+/// 5 point match
+///
+///  Game 1
+///  Alice : 0                            Bob : 0
+///   1) 31: 8/5 6/5                      52: 13/11 13/8
+///   2) Doubles => 2                     Takes
+/// ```
+///
+/// The export starts with tags, followed by the games in the format of the
+/// [Jellyfish match files](Mat). Player 1 of eXtreme Gammon is player 0 of the match. The tags
+/// "Crawford", "Jacoby", and "Beaver" set the rules of the match, and the tag "Variation" the
+/// variant, of which "Backgammon" and "Nackgammon" are supported.
+/// ```
+/// use backgammon::formats::XgMatch;
+///
+/// let s = "; [Player 1 \"Alice\"]\n; [Crawford \"Off\"]\n\n5 point match\n";
+/// let xg: XgMatch = s.parse().unwrap();
+/// assert_eq!(xg.tag("Player 1"), Some("Alice"));
+/// assert!(!xg.mat.r#match.rules.crawford);
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct XgMatch {
+    /// The tags of the export, e.g. ("Site", "XG Mobile"), in the order they appear
+    pub tags: Vec<(String, String)>,
+    /// The names of the players and the match with all its games
+    pub mat: Mat,
+}
+
+impl XgMatch {
+    /// Returns the value of a tag, if present
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parse a tag, e.g. `; [Player 1 "Alice"]`
+fn parse_tag(line: &str) -> Result<(String, String), Error> {
+    let tag = line
+        .trim_start_matches(';')
+        .trim()
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .ok_or(Error::FormatInvalid)?;
+    let (key, value) = tag.split_once('"').ok_or(Error::FormatInvalid)?;
+    let value = value.strip_suffix('"').ok_or(Error::FormatInvalid)?;
+    Ok((key.trim().to_string(), value.to_string()))
+}
+
+/// Parse the value of a tag switching a rule on or off
+fn switch(value: &str) -> Result<bool, Error> {
+    match value {
+        "On" | "on" | "Yes" | "yes" => Ok(true),
+        "Off" | "off" | "No" | "no" => Ok(false),
+        _ => Err(Error::FormatInvalid),
+    }
+}
+
+impl FromStr for XgMatch {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut xg = XgMatch::default();
+        let mut rest = s;
+        loop {
+            let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+            let trimmed = line.trim();
+            if trimmed.starts_with(';') {
+                // lines starting with a semicolon are tags or comments
+                if trimmed.contains('[') {
+                    xg.tags.push(parse_tag(trimmed)?);
+                }
+            } else if !trimmed.is_empty() {
+                xg.mat.r#match = Match::new().with_points(match_points(trimmed)?);
+                rest = next;
+                break;
+            }
+            if next.is_empty() {
+                return Err(Error::FormatInvalid);
+            }
+            rest = next;
+        }
+
+        let rules = &mut xg.mat.r#match.rules;
+        for (key, value) in xg.tags.iter() {
+            match key.as_str() {
+                "Crawford" => rules.crawford = switch(value)?,
+                "Jacoby" => rules.jacoby = switch(value)?,
+                "Beaver" => rules.beaver = switch(value)?,
+                "Variation" => {
+                    rules.variant = value.parse().map_err(|_| Error::FormatInvalid)?;
+                }
+                _ => {}
+            }
+        }
+        xg.mat.read_games(rest)?;
+        Ok(xg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Player, Variant};
+
+    const XG: &str = "; [Site \"XG Mobile\"]
+; [Match ID \"12345\"]
+; [Player 1 \"Alice\"]
+; [Player 2 \"Bob\"]
+; [EventDate \"2024.05.01\"]
+; [Variation \"Backgammon\"]
+; [Crawford \"On\"]
+; [CubeLimit \"1024\"]
+
+3 point match
+
+ Game 1
+ Alice : 0                            Bob : 0
+  1)                                  52: 13/11 13/8
+  2) 31: 24/21 6/5                    Doubles => 2
+  3) Takes                            64: 24/18 8/4*
+  4) 21: Bar/23 24/23
+      Wins 2 points
+
+ Game 2
+ Alice : 2                            Bob : 0
+  1) 41: 24/23 13/9
+                                      Wins 1 point
+";
+
+    #[test]
+    fn test_parse() -> Result<(), Error> {
+        let xg: XgMatch = XG.parse()?;
+        assert_eq!(xg.tags.len(), 8);
+        assert_eq!(xg.tag("Site"), Some("XG Mobile"));
+        assert_eq!(xg.tag("Player 2"), Some("Bob"));
+        assert_eq!(xg.tag("Unknown"), None);
+        assert_eq!(xg.mat.names, ("Alice".to_string(), "Bob".to_string()));
+
+        let m = &xg.mat.r#match;
+        assert_eq!(m.rules.points, 3);
+        assert_eq!(m.games.len(), 2);
+        assert!(m.games[1].is_crawford());
+        assert_eq!(m.score(), (2, 1));
+        assert_eq!(m.games[0].cube.value(), 2);
+        assert_eq!(m.games[0].cube.owner(), Player::Player0);
+
+        // the back checkers of Nackgammon
+        let s = "; [Variation \"Nackgammon\"]\n1 point match\n\n Game 1\n Alice : 0    Bob : 0\n  1) 21: 23/21 24/23\n";
+        let xg: XgMatch = s.parse()?;
+        assert_eq!(xg.mat.r#match.rules.variant, Variant::Nackgammon);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("".parse::<XgMatch>().is_err());
+        assert!("; [Site \"XG\"]\n".parse::<XgMatch>().is_err());
+        assert!("; [Variation \"Acey-deucey\"]\n3 point match\n"
+            .parse::<XgMatch>()
+            .is_err());
+        assert!("; [Crawford \"Maybe\"]\n3 point match\n"
+            .parse::<XgMatch>()
+            .is_err());
+        assert!("; [Site XG]\n3 point match\n".parse::<XgMatch>().is_err());
+    }
+}
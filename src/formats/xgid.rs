@@ -0,0 +1,355 @@
+//! # eXtreme Gammon XGID
+use crate::game::{Game, Phase};
+use crate::rules::{Board, Cube, Dices, Occupancy, Player, PlayerBoard};
+use crate::Error;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents a pending cube action as encoded in the dice field of an XGID
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CubeAction {
+    /// The cube has been offered
+    Double,
+    /// The offered cube has been beavered
+    Beaver,
+    /// The beavered cube has been raccooned
+    Raccoon,
+}
+
+/// Represents an eXtreme Gammon ID, which describes the position of the checkers together with
+/// the cube, the dices, and the score, e.g. "XGID=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:3:0:10".
+///
+/// Player 0 is the player called X in eXtreme Gammon, whose points are numbered in the position,
+/// player 1 is called O.
+/// ```
+/// use backgammon::formats::Xgid;
+/// use backgammon::rules::{Board, Player};
+///
+/// let id: Xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:3:0:10".parse().unwrap();
+/// assert_eq!(id.board, Board::new());
+/// assert_eq!(id.player_on_roll, Player::Player0);
+/// assert_eq!(id.dices, (5, 2));
+/// assert!(id.jacoby);
+/// assert!(id.beaver);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Xgid {
+    /// The checkers of both players
+    pub board: Board,
+    /// The value of the cube
+    pub cube_value: u64,
+    /// The owner of the cube, nobody if centered
+    pub cube_owner: Player,
+    /// The player on roll
+    pub player_on_roll: Player,
+    /// The dices rolled, (0, 0) if not rolled yet or if a cube action is pending
+    pub dices: (u8, u8),
+    /// The cube action the opponent of the player on roll has to react on, if any
+    pub cube_action: Option<CubeAction>,
+    /// The score of player 0 and player 1
+    pub score: (u32, u32),
+    /// Whether this is the Crawford game, only used in match play
+    pub crawford: bool,
+    /// Whether the Jacoby rule applies, only used in money games
+    pub jacoby: bool,
+    /// Whether the beaver rule applies, only used in money games
+    pub beaver: bool,
+    /// The length of the match, 0 for a money game
+    pub match_length: u32,
+    /// The maximum value of the cube
+    pub max_cube: u64,
+}
+
+impl Default for Xgid {
+    fn default() -> Self {
+        Xgid {
+            board: Board::new(),
+            cube_value: 1,
+            cube_owner: Player::Nobody,
+            player_on_roll: Player::Player0,
+            dices: (0, 0),
+            cube_action: None,
+            score: (0, 0),
+            crawford: false,
+            jacoby: false,
+            beaver: false,
+            match_length: 0,
+            max_cube: 1024,
+        }
+    }
+}
+
+/// Maps a player to the number used in an XGID
+fn player_sign(player: Player) -> i8 {
+    match player {
+        Player::Player0 => 1,
+        Player::Player1 => -1,
+        Player::Nobody => 0,
+    }
+}
+
+/// Maps a number used in an XGID to a player
+fn sign_player(s: &str) -> Result<Player, Error> {
+    match s {
+        "1" => Ok(Player::Player0),
+        "-1" => Ok(Player::Player1),
+        "0" => Ok(Player::Nobody),
+        _ => Err(Error::FormatInvalid),
+    }
+}
+
+/// Maps an amount of checkers to a letter, uppercase for player 0
+fn checkers(amount: u8, player: Player) -> char {
+    match (amount, player) {
+        (0, _) => '-',
+        (n, Player::Player0) => (b'A' + n - 1) as char,
+        (n, _) => (b'a' + n - 1) as char,
+    }
+}
+
+impl Xgid {
+    /// Describe a game of a match with the given length and score. Use a length of 0 for a money
+    /// game.
+    pub fn from_game(game: &Game, match_length: u32, score: (u32, u32)) -> Self {
+        Xgid {
+            board: game.board.clone(),
+            cube_value: game.cube.value(),
+            cube_owner: game.cube.owner(),
+            player_on_roll: match game.who_plays {
+                Player::Nobody => Player::Player0,
+                player => player,
+            },
+            dices: match game.phase {
+                Phase::AwaitingMove => game.dices.values,
+                _ => (0, 0),
+            },
+            cube_action: match game.phase {
+                Phase::AwaitingCubeDecision => Some(CubeAction::Double),
+                _ => None,
+            },
+            score,
+            crawford: game.is_crawford(),
+            jacoby: game.rules.jacoby,
+            beaver: game.rules.beaver,
+            match_length,
+            ..Default::default()
+        }
+    }
+
+    /// Set the board, the cube, the dices, the player on roll, and the phase of a game as
+    /// described by this XGID. In money games, the Jacoby and beaver rules are set as well.
+    pub fn apply(&self, game: &mut Game) -> Result<(), Error> {
+        let mut cube = Cube::default();
+        cube.set(self.cube_value)?;
+        cube.set_owner(self.cube_owner);
+        game.board = self.board.clone();
+        game.cube = cube;
+        game.crawford = self.crawford;
+        game.dices = Dices::new(self.dices);
+        game.who_plays = self.player_on_roll;
+        game.phase = match self.cube_action {
+            Some(_) => Phase::AwaitingCubeDecision,
+            None if self.dices != (0, 0) => Phase::AwaitingMove,
+            None => Phase::AwaitingRoll,
+        };
+        if self.match_length == 0 {
+            game.rules.jacoby = self.jacoby;
+            game.rules.beaver = self.beaver;
+        }
+        Ok(())
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Xgid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let board = &self.board;
+        let mut position = String::with_capacity(26);
+        position.push(checkers(board.bar[1], Player::Player1));
+        for point in board.points {
+            position.push(checkers(point.count, point.owner));
+        }
+        position.push(checkers(board.bar[0], Player::Player0));
+
+        let dices = match self.cube_action {
+            Some(CubeAction::Double) => "D".to_string(),
+            Some(CubeAction::Beaver) => "B".to_string(),
+            Some(CubeAction::Raccoon) => "R".to_string(),
+            None => format!("{}{}", self.dices.0, self.dices.1),
+        };
+        let rules = if self.match_length > 0 {
+            u8::from(self.crawford)
+        } else {
+            u8::from(self.jacoby) + 2 * u8::from(self.beaver)
+        };
+
+        write!(
+            f,
+            "XGID={}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            position,
+            self.cube_value.trailing_zeros(),
+            player_sign(self.cube_owner),
+            player_sign(self.player_on_roll),
+            dices,
+            self.score.0,
+            self.score.1,
+            rules,
+            self.match_length,
+            self.max_cube.trailing_zeros()
+        )
+    }
+}
+
+impl FromStr for Xgid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("XGID=").unwrap_or(s);
+        let fields: Vec<&str> = s.split(':').collect();
+        if fields.len() < 9 || fields[0].len() != 26 {
+            return Err(Error::FormatInvalid);
+        }
+
+        let mut p0 = PlayerBoard {
+            board: [0; 24],
+            bar: 0,
+            off: 0,
+        };
+        let mut p1 = p0.clone();
+        for (i, c) in fields[0].bytes().enumerate() {
+            let (player, amount) = match c {
+                b'-' => continue,
+                b'A'..=b'O' => (Player::Player0, c - b'A' + 1),
+                b'a'..=b'o' => (Player::Player1, c - b'a' + 1),
+                _ => return Err(Error::FormatInvalid),
+            };
+            match (i, player) {
+                (0, Player::Player1) => p1.bar = amount,
+                (25, Player::Player0) => p0.bar = amount,
+                (0, _) | (25, _) => return Err(Error::FormatInvalid),
+                (i, Player::Player0) => p0.board[i - 1] = amount,
+                (i, _) => p1.board[24 - i] = amount,
+            }
+        }
+        for p in [&mut p0, &mut p1] {
+            let on_board = p.board.iter().sum::<u8>() + p.bar;
+            p.off = 15u8.checked_sub(on_board).ok_or(Error::FormatInvalid)?;
+        }
+        let board = Board::from_sides([p0, p1], Occupancy::default(), [0, 0])?;
+        board.validate()?;
+
+        let number = |s: &str| s.parse::<u32>().map_err(|_| Error::FormatInvalid);
+        let exponent = |s: &str| {
+            let e = number(s)?;
+            2u64.checked_pow(e).ok_or(Error::FormatInvalid)
+        };
+
+        let (dices, cube_action) = match fields[4] {
+            "D" => ((0, 0), Some(CubeAction::Double)),
+            "B" => ((0, 0), Some(CubeAction::Beaver)),
+            "R" => ((0, 0), Some(CubeAction::Raccoon)),
+            "00" => ((0, 0), None),
+            d => match d.as_bytes() {
+                [a @ b'1'..=b'6', b @ b'1'..=b'6'] => ((a - b'0', b - b'0'), None),
+                _ => return Err(Error::FormatInvalid),
+            },
+        };
+        let match_length = number(fields[8])?;
+        let rules = number(fields[7])?;
+
+        Ok(Xgid {
+            board,
+            cube_value: exponent(fields[1])?,
+            cube_owner: sign_player(fields[2])?,
+            player_on_roll: match sign_player(fields[3])? {
+                Player::Nobody => return Err(Error::FormatInvalid),
+                player => player,
+            },
+            dices,
+            cube_action,
+            score: (number(fields[5])?, number(fields[6])?),
+            crawford: match_length > 0 && rules & 1 == 1,
+            jacoby: match_length == 0 && rules & 1 == 1,
+            beaver: match_length == 0 && rules & 2 == 2,
+            match_length,
+            max_cube: match fields.get(9) {
+                Some(e) => exponent(e)?,
+                None => 1024,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Field;
+
+    const START: &str = "XGID=-b----E-C---eE---c-e----B-:0:0:1:52:0:0:3:0:10";
+
+    #[test]
+    fn test_round_trip() -> Result<(), Error> {
+        let id: Xgid = START.parse()?;
+        assert_eq!(id.to_string(), START);
+
+        let s = "XGID=a-B---CBB---cB--bb-Bbb-a-A:1:-1:-1:D:3:6:1:9:10";
+        let id: Xgid = s.parse()?;
+        assert_eq!(id.to_string(), s);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), Error> {
+        let id: Xgid = "XGID=a-B---CBB---cB--bb-Bbb-a-A:1:-1:-1:D:3:6:1:9:10".parse()?;
+        assert_eq!(id.board.get_checkers(Player::Player0, Field::Bar)?, 1);
+        assert_eq!(id.board.get_checkers(Player::Player1, Field::Bar)?, 1);
+        assert_eq!(id.board.get_checkers(Player::Player0, Field::Point(1))?, 2);
+        assert_eq!(id.board.get_checkers(Player::Player1, Field::Point(12))?, 3);
+        assert_eq!(id.board.get_checkers(Player::Player1, Field::Off)?, 2);
+        assert_eq!(id.cube_value, 2);
+        assert_eq!(id.cube_owner, Player::Player1);
+        assert_eq!(id.player_on_roll, Player::Player1);
+        assert_eq!(id.cube_action, Some(CubeAction::Double));
+        assert_eq!(id.score, (3, 6));
+        assert!(id.crawford);
+        assert!(!id.jacoby);
+        assert_eq!(id.match_length, 9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        // too many checkers
+        assert!("XGID=-b----E-C---eE---c-e---BB-:0:0:1:52:0:0:3:0:10"
+            .parse::<Xgid>()
+            .is_err());
+        // dice value of 7
+        assert!("XGID=-b----E-C---eE---c-e----B-:0:0:1:72:0:0:3:0:10"
+            .parse::<Xgid>()
+            .is_err());
+        assert!("XGID=-b----E-C---eE---c-e----B-:0:0:1"
+            .parse::<Xgid>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_game() -> Result<(), Error> {
+        let mut g = Game::new();
+        let id: Xgid = START.parse()?;
+        id.apply(&mut g)?;
+        assert_eq!(g.phase, Phase::AwaitingMove);
+        assert_eq!(g.who_plays, Player::Player0);
+        assert!(g.rules.jacoby);
+        assert_eq!(Xgid::from_game(&g, 0, (0, 0)).to_string(), START);
+        Ok(())
+    }
+}
@@ -1,16 +1,143 @@
 //! # Play a Backgammon Game
+#[cfg(feature = "std")]
+use crate::book;
+#[cfg(feature = "std")]
+use crate::cubeful::{cube_decision, CubeContext, CubeDecision};
+#[cfg(feature = "std")]
+use crate::eval::{classify, rank_plays, Evaluation, Evaluator, Hint, PositionClass};
+#[cfg(feature = "std")]
+use crate::met::Met;
+use crate::record::{Event, GameObserver, GameRecord};
+use crate::rules::moves;
 use crate::rules::Cube;
 use crate::rules::Player;
-use crate::rules::{Board, Move};
-use crate::rules::{Dices, Roll};
-use crate::rules::{GameRules, Rules};
-use crate::Error;
+#[cfg(feature = "std")]
+use crate::rules::RandomDices;
+use crate::rules::{Board, Move, Point, RelativePoint};
+use crate::rules::{CheckerMove, Field, Play};
+use crate::rules::{DiceRoll, DiceSource, Dices, Roll};
+use crate::rules::{GameRules, Rules, Variant};
+use crate::{Error, PositionError};
+use rand::Rng;
 
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use core::cell::RefCell;
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::fmt;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Implements the builder of games set up from arbitrary positions
+mod builder;
+pub use builder::GameBuilder;
+
+/// Represents the phase of a game, which defines what can happen next.
+///
+/// A turn starts with [`AwaitingRoll`](Phase::AwaitingRoll), where the player may offer the cube
+/// before rolling. An offered cube leads to [`AwaitingCubeDecision`](Phase::AwaitingCubeDecision)
+/// until the opponent takes or passes it. Once the dices are rolled, the game is in
+/// [`AwaitingMove`](Phase::AwaitingMove) until all dice values are played and the turn passes to
+/// the other player. The game ends in [`GameOver`](Phase::GameOver).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Phase {
+    /// The player has to roll the dices, or may offer the cube before rolling
+    #[default]
+    AwaitingRoll,
+    /// The cube has been offered, the opponent has to take or pass it
+    AwaitingCubeDecision,
+    /// The dices have been rolled, the player has to move the checkers
+    AwaitingMove,
+    /// The game has ended
+    GameOver,
+}
+
+// implement Display trait
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Phase::AwaitingRoll => write!(f, "Awaiting roll"),
+            Phase::AwaitingCubeDecision => write!(f, "Awaiting cube decision"),
+            Phase::AwaitingMove => write!(f, "Awaiting move"),
+            Phase::GameOver => write!(f, "Game over"),
+        }
+    }
+}
+
+/// Represents how a game has been won.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Victory {
+    /// The loser has borne off at least one checker, the game counts once the cube value
+    Single,
+    /// The loser has not borne off any checker, the game counts twice the cube value
+    Gammon,
+    /// The loser has not borne off any checker and still has checkers on the bar or in the
+    /// winner's home board, the game counts three times the cube value
+    Backgammon,
+}
+
+impl Victory {
+    /// Returns the factor the cube value is multiplied with
+    pub fn multiplier(&self) -> u64 {
+        match *self {
+            Victory::Single => 1,
+            Victory::Gammon => 2,
+            Victory::Backgammon => 3,
+        }
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Victory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Victory::Single => write!(f, "Single game"),
+            Victory::Gammon => write!(f, "Gammon"),
+            Victory::Backgammon => write!(f, "Backgammon"),
+        }
+    }
+}
+
+/// Represents the result of a finished game
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameResult {
+    /// The player who has won the game
+    pub winner: Player,
+    /// How the game has been won
+    pub victory: Victory,
+    /// The points won, i.e. the cube value multiplied by the victory's multiplier
+    pub points: u64,
+}
+
+/// Holds the state of a game which can be restored by undoing an action
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Snapshot {
+    dices: Dices,
+    who_plays: Player,
+    phase: Phase,
+    board: Board,
+    cube: Cube,
+    moves: Vec<CheckerMove>,
+    record: GameRecord,
+}
 
 /// Represents a Backgammon game
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Game {
     /// rules of the game
     pub rules: Rules,
@@ -18,244 +145,3382 @@ pub struct Game {
     pub dices: Dices,
     /// whose turn is it?
     pub who_plays: Player,
+    /// phase of the game, i.e. what has to happen next
+    pub phase: Phase,
     /// board for player 0 and 1
     pub board: Board,
     /// cube value and owner
     pub cube: Cube,
+    /// moves played with the last dices rolled, including the checkers hit
+    pub moves: Vec<CheckerMove>,
     /// Crawford rule: if crawford game, no doubling allowed
-    crawford: bool,
-    /// Holland rule: if <4 rolls of crawford game, no doubling allowed
+    pub(crate) crawford: bool,
+    /// Holland rule: games played since the Crawford game, 0 for the Crawford game and before
     since_crawford: u8,
-    /// true if player needs to roll first
-    roll_first: bool,
-    /// if cube was offered, player has to accept first and only then can move on
-    cube_received: bool,
+    /// game played as part of a match, where beaver and raccoon are not allowed
+    match_play: bool,
+    /// points player 0 and player 1 still need to win the match, if played as part of a match
+    away: (u64, u64),
+    /// all events of the game
+    record: GameRecord,
+    /// states before the actions which can be undone
+    undo_stack: Vec<Snapshot>,
+    /// states before the actions which have been undone and can be redone
+    redo_stack: Vec<Snapshot>,
+    /// source of the dices rolled, random dices if none
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dice_source: Option<SharedDiceSource>,
+    /// observers receiving the events of the game
+    #[cfg_attr(feature = "serde", serde(skip))]
+    observers: Observers,
 }
 
-// implement Display trait
-impl fmt::Display for Game {
+/// Holds the observers of a game. Clones of a game, e.g. to look ahead, start without observers.
+#[derive(Default)]
+struct Observers(Vec<Box<dyn GameObserver>>);
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Observers::default()
+    }
+}
+
+// observers are not Debug
+impl fmt::Debug for Observers {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut s = String::new();
-        s.push_str(&format!("Rules: {}\n", self.rules));
-        s.push_str(&format!("Dices: {:?}\n", self.dices));
-        s.push_str(&format!("Cube: {}\n", self.cube.value()));
-        s.push_str(&format!("Cube owner: {}\n", self.cube.owner()));
-        s.push_str(&format!("Who plays: {}\n", self.who_plays));
-        s.push_str(&format!("Board: {:?}\n", self.board.get()));
-        s.push_str(&format!("Crawford game: {}\n", self.crawford));
-        s.push_str(&format!("Since Crawford game: {}\n", self.since_crawford));
-        write!(f, "{}", s)
+        write!(f, "{} observers", self.0.len())
     }
 }
 
-impl Game {
-    /// Create a new default game
-    pub fn new() -> Self {
-        Game::default()
+/// A source of dices shared by the games of a match or session, and by the clones of a game
+#[cfg(feature = "std")]
+pub(crate) type SharedDiceSource = Arc<Mutex<dyn DiceSource>>;
+
+/// A source of dices shared by the games of a match or session, and by the clones of a game.
+/// Without the standard library, there are no threads to share it with.
+#[cfg(not(feature = "std"))]
+pub(crate) type SharedDiceSource = Rc<RefCell<dyn DiceSource>>;
+
+/// Returns a source of dices which can be shared
+#[cfg(feature = "std")]
+pub(crate) fn share(source: impl DiceSource + 'static) -> SharedDiceSource {
+    Arc::new(Mutex::new(source))
+}
+
+/// Returns a source of dices which can be shared
+#[cfg(not(feature = "std"))]
+pub(crate) fn share(source: impl DiceSource + 'static) -> SharedDiceSource {
+    Rc::new(RefCell::new(source))
+}
+
+/// Counts the distinct positions reached by legal plays over the given number of plies, starting
+/// with a player playing a roll, see [`Game::perft`]
+fn perft(variant: Variant, board: &Board, player: Player, roll: DiceRoll, depth: usize) -> u64 {
+    let mut seen = BTreeSet::new();
+    for play in variant.plays(board, player, &roll.dies(), &[]) {
+        let mut after = board.clone();
+        if play.iter().all(|m| after.apply(player, m).is_ok()) {
+            let _ = seen.insert(after);
+        }
     }
+    if depth == 1 {
+        return seen.len() as u64;
+    }
+    seen.iter()
+        .map(|after| match after.get_checkers(player, Field::Off) {
+            Ok(15) => 1,
+            _ => DiceRoll::all_distinct()
+                .map(|(roll, _)| perft(variant, after, player.other(), roll, depth - 1))
+                .sum(),
+        })
+        .sum()
 }
 
-impl Roll for Game {
-    fn roll(&mut self) -> Result<&mut Self, Error> {
-        if !self.dices.consumed.0
-            && !self.dices.consumed.1
-            && !self.dices.consumed.2
-            && !self.dices.consumed.3
-        {
-            return Err(Error::MoveFirst);
+/// Returns the mark of the checkers of a player in the board diagram
+fn mark(player: Player) -> &'static str {
+    match player {
+        Player::Player0 => "X",
+        Player::Player1 => "O",
+        Player::Nobody => "-",
+    }
+}
+
+/// Returns the player of a mark in the board diagram
+fn marked(mark: &str) -> Result<Player, Error> {
+    match mark {
+        "X" => Ok(Player::Player0),
+        "O" => Ok(Player::Player1),
+        "-" => Ok(Player::Nobody),
+        _ => Err(Error::FormatInvalid),
+    }
+}
+
+/// The points of the top and the bottom half of the board diagram, from left to right
+pub(crate) const DIAGRAM_POINTS: [[usize; 12]; 2] = [
+    [13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24],
+    [12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+];
+
+/// Returns the border of a half of the board diagram, labelled with its points
+fn border(points: &[usize; 12]) -> String {
+    let labels: Vec<String> = points.iter().map(|p| format!("{:>2}", p)).collect();
+    format!(
+        "+{}------{}-+",
+        labels[..6].join("-").replace(' ', "-"),
+        labels[6..].join("-").replace(' ', "-")
+    )
+}
+
+// Display the game as a board diagram from the point-of-view of player 0, who plays the checkers
+// marked X from point 24 to point 1, followed by the bar, the checkers off, the cube, the dices,
+// the player on roll, and the phase
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let board = &self.board;
+        let checkers =
+            |point: usize| (board.points[point - 1].owner, board.points[point - 1].count);
+        for (half, points) in DIAGRAM_POINTS.iter().enumerate() {
+            if half == 0 {
+                writeln!(f, " {}", border(points))?;
+            } else {
+                writeln!(f, " |                  |BAR|                  |")?;
+            }
+            for row in 0..5 {
+                // the rows of the bottom half are stacked from the bottom
+                let row = if half == 0 { row } else { 4 - row };
+                let cells: Vec<String> = points
+                    .iter()
+                    .map(|p| match checkers(*p) {
+                        (_, n) if n > 5 && row == 4 => format!("{:>2} ", n),
+                        (player, n) if usize::from(n) > row => format!(" {} ", mark(player)),
+                        _ => "   ".to_string(),
+                    })
+                    .collect();
+                writeln!(f, " |{}|   |{}|", cells[..6].concat(), cells[6..].concat())?;
+            }
         }
-        if self.cube_received {
-            return Err(Error::CubeReceived);
+        writeln!(f, " {}", border(&DIAGRAM_POINTS[1]))?;
+        writeln!(f, " Bar: X {}, O {}", board.bar[0], board.bar[1])?;
+        writeln!(f, " Off: X {}, O {}", board.off[0], board.off[1])?;
+        writeln!(
+            f,
+            " Cube: {} {}",
+            self.cube.value(),
+            mark(self.cube.owner())
+        )?;
+        match self.phase {
+            Phase::AwaitingMove => {
+                writeln!(f, " Dices: {} {}", self.dices.values.0, self.dices.values.1)?
+            }
+            _ => writeln!(f, " Dices: -")?,
         }
+        writeln!(f, " Turn: {}", mark(self.who_plays))?;
+        writeln!(f, " Phase: {}", self.phase)
+    }
+}
 
-        self.dices = self.dices.roll();
-        if self.who_plays == Player::Nobody {
-            let diff = self.dices.values.0 - self.dices.values.1;
-            match diff {
-                0 => {
-                    self.who_plays = Player::Nobody;
+impl FromStr for Game {
+    type Err = Error;
+
+    /// Parse a board diagram as written by [`Display`](fmt::Display), e.g. to paste a position
+    /// into a test. The game gets the default rules, and the dices rolled are not played yet.
+    /// ```
+    /// use backgammon::{Game, Phase};
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player1;
+    /// let parsed: Game = g.to_string().parse().unwrap();
+    /// assert_eq!(parsed.board, g.board);
+    /// assert_eq!(parsed.who_plays, Player::Player1);
+    /// assert_eq!(parsed.phase, Phase::AwaitingRoll);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s
+            .lines()
+            .map(str::trim)
+            .skip_while(|l| !l.starts_with('+'))
+            .collect();
+        if lines.len() < 19 || lines[0] != border(&DIAGRAM_POINTS[0]) {
+            return Err(Error::FormatInvalid);
+        }
+        if lines[12] != border(&DIAGRAM_POINTS[1]) {
+            return Err(Error::FormatInvalid);
+        }
+
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        for (half, points) in DIAGRAM_POINTS.iter().enumerate() {
+            let rows = &lines[1 + 6 * half..6 + 6 * half];
+            for (column, point) in points.iter().enumerate() {
+                let start = 1 + 3 * column + if column < 6 { 0 } else { 5 };
+                let mut player = Player::Nobody;
+                let mut amount = 0;
+                for row in 0..5 {
+                    let line = rows[if half == 0 { row } else { 4 - row }];
+                    let cell = line
+                        .get(start..start + 3)
+                        .ok_or(Error::FormatInvalid)?
+                        .trim();
+                    match (cell, cell.parse::<u8>()) {
+                        ("", _) => break,
+                        (_, Ok(n)) if row == 4 && n > 5 => amount = n,
+                        (cell, _) => {
+                            let p = marked(cell)?;
+                            if p == Player::Nobody || (player != Player::Nobody && p != player) {
+                                return Err(Error::FormatInvalid);
+                            }
+                            player = p;
+                            amount += 1;
+                        }
+                    }
                 }
-                _ if diff > 0 => {
-                    self.who_plays = Player::Player0;
+                board.points[point - 1] = Point::new(player, amount);
+            }
+        }
+
+        let value = |label: &str, line: &str| -> Result<Vec<String>, Error> {
+            let values = line
+                .strip_prefix(label)
+                .and_then(|l| l.strip_prefix(':'))
+                .ok_or(Error::FormatInvalid)?;
+            Ok(values
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect())
+        };
+        let number = |v: &str| v.parse::<u8>().map_err(|_| Error::FormatInvalid);
+        for (label, line) in [("Bar", lines[13]), ("Off", lines[14])] {
+            match value(label, line)?.as_slice() {
+                [x, a, o, b] if x == "X" && o == "O" => {
+                    let (a, b) = (number(a)?, number(b)?);
+                    if label == "Bar" {
+                        board.bar = [a, b];
+                    } else {
+                        board.off = [a, b];
+                    }
                 }
-                _ => {
-                    self.who_plays = Player::Player1;
+                _ => return Err(Error::FormatInvalid),
+            }
+        }
+        board.validate()?;
+
+        let mut game = Game::new();
+        game.board = board;
+        match value("Cube", lines[15])?.as_slice() {
+            [v, owner] => {
+                game.cube
+                    .set(v.parse().map_err(|_| Error::FormatInvalid)?)?;
+                game.cube.set_owner(marked(owner)?);
+            }
+            _ => return Err(Error::FormatInvalid),
+        }
+        game.dices = match value("Dices", lines[16])?.as_slice() {
+            [none] if none == "-" => Dices::default(),
+            [a, b] => {
+                let values = (number(a)?, number(b)?);
+                if !(1..=6).contains(&values.0) || !(1..=6).contains(&values.1) {
+                    return Err(Error::DiceInvalid);
                 }
+                Dices::new(values)
             }
+            _ => return Err(Error::FormatInvalid),
+        };
+        game.who_plays = match value("Turn", lines[17])?.as_slice() {
+            [player] => marked(player)?,
+            _ => return Err(Error::FormatInvalid),
+        };
+        let phase = lines[18]
+            .strip_prefix("Phase:")
+            .ok_or(Error::FormatInvalid)?
+            .trim();
+        game.phase = [
+            Phase::AwaitingRoll,
+            Phase::AwaitingCubeDecision,
+            Phase::AwaitingMove,
+            Phase::GameOver,
+        ]
+        .into_iter()
+        .find(|p| p.to_string() == phase)
+        .ok_or(Error::FormatInvalid)?;
+        if (game.phase == Phase::AwaitingMove) != (game.dices.values != (0, 0)) {
+            return Err(Error::FormatInvalid);
         }
-        Ok(self)
+        Ok(game)
     }
 }
 
-impl Move for Game {
-    fn move_checker(&mut self, player: Player, dice: u8, from: usize) -> Result<&mut Self, Error> {
-        // check if move is permitted
-        let _ = self.move_permitted(player, dice)?;
+impl Game {
+    /// Create a new default game
+    pub fn new() -> Self {
+        Game::default()
+    }
 
-        // check if player has to move checker from bar first
-        if ((player == Player::Player0) && (self.board.get().bar.0 > 0))
-            || ((player == Player::Player1) && (self.board.get().bar.1 > 0))
-        {
-            return Err(Error::MoveInvalidBar);
+    /// Roll the dices from the given source instead of the operating system's random number
+    /// generator, see [`DiceSource`]. Clones of the game share the source. The seed of the source,
+    /// if any, is recorded. Without the `std` feature, rolling fails with [`Error::DiceInvalid`]
+    /// unless a source is set.
+    pub fn with_dice_source(mut self, source: impl DiceSource + 'static) -> Self {
+        self.record.set_seed(source.seed());
+        self.dice_source = Some(share(source));
+        self
+    }
+
+    /// Pass the events of the game to an observer as they happen, see [`GameObserver`]. Clones of
+    /// the game do not pass their events to the observers of the original.
+    pub fn subscribe(&mut self, observer: impl GameObserver + 'static) {
+        self.observers.0.push(Box::new(observer));
+    }
+
+    /// Record an event and pass it to the observers, together with the checkers hit by a move
+    fn emit(&mut self, event: Event) {
+        self.record.push(event.clone());
+        self.notify(&event);
+    }
+
+    /// Pass an event recorded already to the observers
+    fn notify(&mut self, event: &Event) {
+        if self.observers.0.is_empty() {
+            return;
+        }
+        let mut observers = core::mem::take(&mut self.observers);
+        for observer in observers.0.iter_mut() {
+            observer.on_event(self, event);
+            if let Event::Move { player, mv } = event {
+                for hit in &mv.hits {
+                    let point = RelativePoint::try_from(*hit)
+                        .and_then(|point| self.board.absolute(*player, point));
+                    if let Ok(point) = point {
+                        observer.on_hit(self, player.other(), point);
+                    }
+                }
+            }
         }
+        self.observers = observers;
+    }
 
-        // check if the dice value has been consumed
-        if (dice == self.dices.values.0 && self.dices.consumed.0)
-            || (dice == self.dices.values.1 && self.dices.consumed.1)
-            || (dice == self.dices.values.1 && self.dices.consumed.2)
-            || (dice == self.dices.values.1 && self.dices.consumed.3)
-        {
-            return Err(Error::MoveInvalid);
+    /// Tell the observers that the game has ended
+    fn notify_game_over(&mut self) {
+        let Some(result) = self.result() else {
+            return;
+        };
+        let mut observers = core::mem::take(&mut self.observers);
+        for observer in observers.0.iter_mut() {
+            observer.on_game_over(self, &result);
         }
+        self.observers = observers;
+    }
 
-        // remove checker from old position
-        self.board.set(player, from, -1)?;
+    /// Roll the dices from a source shared with other games
+    pub(crate) fn set_dice_source(&mut self, source: Option<SharedDiceSource>) {
+        self.dice_source = source;
+    }
 
-        // move checker to new position, in case it is reaching the off position, set it off
-        let new_position = from as i8 - dice as i8;
-        if new_position < 0 {
-            self.board.set_off(player, 1)?;
-        } else {
-            self.board.set(player, new_position as usize, 1)?;
+    /// Create a new game played as part of a match with the given rules, which might be the
+    /// Crawford game of the match or be played some games after it. Away are the points each
+    /// player still needs to win the match.
+    pub(crate) fn for_match(
+        rules: Rules,
+        crawford: bool,
+        since_crawford: u8,
+        away: (u64, u64),
+    ) -> Self {
+        let mut game = Game {
+            rules,
+            crawford,
+            since_crawford,
+            match_play: true,
+            away,
+            ..Default::default()
+        };
+        game.set_start();
+        game
+    }
+
+    /// Start the game from another position than the starting position of the variant, e.g. a
+    /// proposition position. The fields occupied by the opponent follow the rules of the variant.
+    /// The position has to be valid, and the game must not have started yet.
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::rules::{Board, Player, Point};
+    ///
+    /// let mut board = Board::new();
+    /// board.points[23] = Point::EMPTY;
+    /// board.bar[0] = 2;
+    /// let g = Game::new().with_starting_position(board.clone()).unwrap();
+    /// assert_eq!(g.board, board);
+    /// assert_eq!(g.record().start(), &board);
+    ///
+    /// board.bar[0] = 1;
+    /// assert!(Game::new().with_starting_position(board).is_err());
+    /// ```
+    pub fn with_starting_position(mut self, mut board: Board) -> Result<Self, Error> {
+        if !self.record.events().is_empty() {
+            return Err(Error::GameStarted);
         }
+        board.occupancy = self.rules.variant.occupancy();
+        board.validate()?;
+        self.board = board;
+        self.record = GameRecord::new(self.board.clone());
+        Ok(self)
+    }
+
+    /// Set up the starting position of the variant of the rules
+    pub(crate) fn set_start(&mut self) {
+        self.board = self.rules.variant.start();
+        self.record = GameRecord::new(self.board.clone());
+    }
 
-        // set dice value to consumed
-        if dice == self.dices.values.0 && !self.dices.consumed.0 {
-            self.dices.consumed.0 = true;
-        } else if dice == self.dices.values.1 && !self.dices.consumed.1 {
-            self.dices.consumed.1 = true;
-        } else if dice == self.dices.values.1 && !self.dices.consumed.2 {
-            self.dices.consumed.2 = true;
-        } else if dice == self.dices.values.1 && !self.dices.consumed.3 {
-            self.dices.consumed.3 = true;
+    /// Returns the points player 0 and player 1 still need to win the match, if the game is played
+    /// as part of a match
+    pub fn away(&self) -> Option<(u64, u64)> {
+        match self.match_play {
+            true => Some(self.away),
+            false => None,
         }
+    }
 
-        // switch to other player if all dices have been consumed
-        if self.dices.consumed.0
-            && self.dices.consumed.1
-            && self.dices.consumed.2
-            && self.dices.consumed.3
-        {
-            self.who_plays = self.who_plays.other();
-            self.roll_first = true;
+    /// Returns true if this is the Crawford game of a match, in which doubling is not allowed
+    pub fn is_crawford(&self) -> bool {
+        self.crawford
+    }
+
+    /// Returns true if doubling could make any difference for a player.
+    ///
+    /// The cube is dead in the Crawford game, and in match play for a player who would already win
+    /// the match by winning a single game at the current cube value, e.g. at double match point.
+    /// There is no cube in the variants of Tavli.
+    /// ```
+    /// use backgammon::Match;
+    /// use backgammon::rules::{MatchRules, Player};
+    ///
+    /// let mut m = Match::new().with_points(1);
+    /// let g = m.new_game().unwrap();
+    /// assert!(!g.cube_is_live(Player::Player0));
+    /// ```
+    pub fn cube_is_live(&self, player: Player) -> bool {
+        let away = match player {
+            Player::Player0 => self.away.0,
+            Player::Player1 => self.away.1,
+            Player::Nobody => return false,
+        };
+        self.rules.variant.has_cube()
+            && !(self.crawford || self.match_play && self.cube.value() >= away)
+    }
+
+    /// Returns true if this game is played after the Crawford game of a match
+    pub fn is_post_crawford(&self) -> bool {
+        self.since_crawford > 0
+    }
+
+    /// Returns the trailing player in a game after the Crawford game, i.e. the player who needs
+    /// more than one point to win the match while the leader needs a single point.
+    pub fn trailer(&self) -> Option<Player> {
+        if !self.is_post_crawford() {
+            return None;
+        }
+        match self.away {
+            (1, a) if a > 1 => Some(Player::Player1),
+            (a, 1) if a > 1 => Some(Player::Player0),
+            _ => None,
         }
+    }
 
-        Ok(self)
+    /// Returns true if the trailer has made the double, which is mandatory after the Crawford
+    /// game, as the trailer has nothing to lose by doubling.
+    pub fn trailer_doubled(&self) -> bool {
+        self.trailer().is_some_and(|trailer| {
+            self.record
+                .events()
+                .iter()
+                .any(|e| matches!(e, Event::CubeOffer { player, .. } if *player == trailer))
+        })
     }
 
-    fn move_checker_from_bar(&mut self, player: Player, dice: u8) -> Result<&mut Self, Error> {
-        // check if move is permitted
-        let _ = self.move_permitted(player, dice)?;
+    /// Returns true if the leader can pass the trailer's double without any loss of match winning
+    /// chances, which is the case while the trailer needs an even number of points.
+    /// ```
+    /// use backgammon::{Match, Victory};
+    /// use backgammon::rules::{MatchRules, Player};
+    ///
+    /// let mut m = Match::new().with_points(3);
+    /// m.new_game().unwrap().resign(Player::Player1, Victory::Gammon).unwrap();
+    /// m.new_game().unwrap().resign(Player::Player0, Victory::Single).unwrap();
+    ///
+    /// // player 0 is 1-away, player 1 is 2-away
+    /// let g = m.new_game().unwrap();
+    /// assert_eq!(g.trailer(), Some(Player::Player1));
+    /// assert!(g.free_drop());
+    /// ```
+    pub fn free_drop(&self) -> bool {
+        let away = match self.trailer() {
+            Some(Player::Player0) => self.away.0,
+            Some(Player::Player1) => self.away.1,
+            _ => return false,
+        };
+        away % 2 == 0 && !self.trailer_doubled()
+    }
 
-        // check if the dice value has been consumed
-        if (dice == self.dices.values.0 && self.dices.consumed.0)
-            || (dice == self.dices.values.1 && self.dices.consumed.1)
-            || (dice == self.dices.values.1 && self.dices.consumed.2)
-            || (dice == self.dices.values.1 && self.dices.consumed.3)
-        {
-            return Err(Error::MoveInvalid);
+    /// Returns how often a player has rolled the dices in this game, including the opening roll
+    /// for the player who won it
+    pub fn rolls(&self, player: Player) -> usize {
+        self.record
+            .events()
+            .iter()
+            .filter(|e| matches!(e, Event::Roll { player: p, .. } if *p == player))
+            .count()
+    }
+
+    /// Returns the game seen from the point-of-view of a player, such that this player becomes
+    /// player 0, moving from field 23 towards field 0 on the displayed board.
+    ///
+    /// For player 1, the board, the player whose turn it is, and the cube owner are swapped. For
+    /// player 0 and nobody, the game is returned unchanged. The undo history is not carried over
+    /// to a swapped game.
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player1;
+    /// let view = g.from_perspective(Player::Player1);
+    /// assert_eq!(view.who_plays, Player::Player0);
+    /// ```
+    pub fn from_perspective(&self, player: Player) -> Self {
+        let mut game = self.clone();
+        if player != Player::Player1 {
+            return game;
         }
+        game.board = self.board.flipped();
+        game.who_plays = self.who_plays.other();
+        game.cube.set_owner(self.cube.owner().other());
+        game.undo_stack.clear();
+        game.redo_stack.clear();
+        game
+    }
+
+    /// Returns the record of all events of the game
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
 
-        // set the checker from bar
-        self.board.set_bar(player, -1)?;
-        self.board.set(player, 24 - dice as usize, 1)?;
+    /// Replace the record of the game, e.g. when resuming a saved game
+    pub(crate) fn set_record(&mut self, record: GameRecord) {
+        self.record = record;
+    }
 
-        // set dice value to consumed
-        if dice == self.dices.values.0 && !self.dices.consumed.0 {
-            self.dices.consumed.0 = true;
-        } else if dice == self.dices.values.1 && !self.dices.consumed.1 {
-            self.dices.consumed.1 = true;
-        } else if dice == self.dices.values.1 && !self.dices.consumed.2 {
-            self.dices.consumed.2 = true;
-        } else if dice == self.dices.values.1 && !self.dices.consumed.3 {
-            self.dices.consumed.3 = true;
+    /// Resign the game, granting the opponent a single game, a gammon, or a backgammon.
+    /// ```
+    /// use backgammon::{Game, Phase, Victory};
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.resign(Player::Player1, Victory::Gammon).unwrap();
+    /// assert_eq!(g.phase, Phase::GameOver);
+    /// assert_eq!(g.result().unwrap().winner, Player::Player0);
+    /// assert_eq!(g.result().unwrap().points, 2);
+    /// ```
+    pub fn resign(&mut self, player: Player, victory: Victory) -> Result<(), Error> {
+        if player == Player::Nobody {
+            return Err(Error::PlayerInvalid);
         }
+        if self.phase == Phase::GameOver {
+            return Err(Error::GameEnded);
+        }
+        self.save_undo();
+        self.who_plays = player.other();
+        self.phase = Phase::GameOver;
+        self.emit(Event::Resign { player, victory });
+        self.notify_game_over();
+        Ok(())
+    }
 
-        // switch to other player if all dices have been consumed
-        if self.dices.consumed.0
-            && self.dices.consumed.1
-            && self.dices.consumed.2
-            && self.dices.consumed.3
+    /// Murphy rule: double the cube automatically after an equal opening roll, leaving it
+    /// centered, at most [`murphy_limit`](Rules::murphy_limit) times unless the limit is 0.
+    fn murphy(&mut self) -> Result<(), Error> {
+        let doubled = self
+            .record
+            .events()
+            .iter()
+            .filter(|e| matches!(e, Event::AutomaticDouble { .. }))
+            .count();
+        if !self.rules.murphy
+            || (self.rules.murphy_limit > 0 && doubled >= usize::from(self.rules.murphy_limit))
         {
-            self.who_plays = self.who_plays.other();
-            self.roll_first = true;
+            return Ok(());
         }
-
-        Ok(self)
+        let value = 2 * self.cube.value();
+        self.cube.set(value)?;
+        self.emit(Event::AutomaticDouble { value });
+        Ok(())
     }
 
-    /// Implements checks to validate if the player is allowed to move
-    fn move_permitted(&mut self, player: Player, dice: u8) -> Result<&mut Self, Error> {
-        // check if player is allowed to move
+    /// Offer the cube to the opponent, doubling the value of the game.
+    ///
+    /// The cube may only be offered by the player whose turn it is, before rolling the dices, and
+    /// only if the cube is centered or owned by this player. The opponent then has to
+    /// [`take`](Game::take) or [`pass`](Game::pass) it.
+    ///
+    /// Doubling is not allowed if the cube is [dead](Game::cube_is_live) for the player, e.g. in
+    /// the Crawford game of a match. With the
+    /// [`holland`](Rules::holland) rule, doubling after the Crawford game is only allowed once both
+    /// players have rolled at least twice.
+    /// ```
+    /// use backgammon::{Game, Phase};
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// g.offer_double(Player::Player0).unwrap();
+    /// assert_eq!(g.phase, Phase::AwaitingCubeDecision);
+    /// g.take(Player::Player1).unwrap();
+    /// assert_eq!(g.cube.value(), 2);
+    /// assert_eq!(g.cube.owner(), Player::Player1);
+    /// assert_eq!(g.phase, Phase::AwaitingRoll);
+    /// ```
+    pub fn offer_double(&mut self, player: Player) -> Result<(), Error> {
+        if player == Player::Nobody {
+            return Err(Error::PlayerInvalid);
+        }
+        self.check_phase(Phase::AwaitingRoll)?;
+        // the cube can not be offered before the opening roll
+        if self.who_plays == Player::Nobody || !self.cube_is_live(player) {
+            return Err(Error::DoublingNotPermitted);
+        }
+        if self.rules.holland
+            && self.since_crawford > 0
+            && (self.rolls(Player::Player0) < 2 || self.rolls(Player::Player1) < 2)
+        {
+            return Err(Error::DoublingNotPermitted);
+        }
         if player != self.who_plays {
             return Err(Error::NotYourTurn);
         }
+        let value = self.cube.offer(player.other())?;
 
-        // if player is nobody, you can not play and have to roll first
-        if self.who_plays == Player::Nobody {
-            return Err(Error::RollFirst);
+        self.save_undo();
+        self.emit(Event::CubeOffer { player, value });
+        self.phase = Phase::AwaitingCubeDecision;
+        Ok(())
+    }
+
+    /// Returns the value the cube has been offered at, if the game awaits a cube decision
+    fn offered_value(&self) -> Result<u64, Error> {
+        self.check_phase(Phase::AwaitingCubeDecision)?;
+        match self.record.last() {
+            Some(Event::CubeOffer { value, .. }) => Ok(*value),
+            _ => Err(Error::CubeValueInvalid),
         }
+    }
 
-        // check if player has to take or reject cube first
-        if self.cube_received {
-            return Err(Error::CubeReceived);
+    /// Take the cube offered by the opponent. The cube is doubled and owned by the taker, and the
+    /// player who offered the cube continues by rolling the dices.
+    pub fn take(&mut self, player: Player) -> Result<(), Error> {
+        let value = self.offered_value()?;
+        if player != self.who_plays.other() {
+            return Err(Error::NotYourTurn);
         }
 
-        // check if player has to roll first
-        if self.roll_first {
-            return Err(Error::RollFirst);
+        self.save_undo();
+        self.cube.set(value)?;
+        self.cube.set_owner(player);
+        self.emit(Event::CubeTake { player });
+        self.phase = Phase::AwaitingRoll;
+        Ok(())
+    }
+
+    /// Beaver the cube offered by the opponent, i.e. take it and immediately redouble while keeping
+    /// the cube. Only allowed if the [`beaver`](Rules::beaver) rule is set and the game is not
+    /// played as part of a match.
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::rules::{GameRules, Player};
+    ///
+    /// let mut g = Game::new().with_beaver();
+    /// g.who_plays = Player::Player0;
+    /// g.offer_double(Player::Player0).unwrap();
+    /// g.beaver(Player::Player1).unwrap();
+    /// assert_eq!(g.cube.value(), 4);
+    /// assert_eq!(g.cube.owner(), Player::Player1);
+    /// ```
+    pub fn beaver(&mut self, player: Player) -> Result<(), Error> {
+        let value = self.offered_value()?;
+        if !self.rules.beaver || self.match_play {
+            return Err(Error::DoublingNotPermitted);
+        }
+        if player != self.who_plays.other() {
+            return Err(Error::NotYourTurn);
         }
 
-        // check if dice value has actually been rolled
-        if dice != self.dices.values.0 && dice != self.dices.values.1 {
+        self.save_undo();
+        self.cube.set(2 * value)?;
+        self.cube.set_owner(player);
+        self.emit(Event::Beaver {
+            player,
+            value: 2 * value,
+        });
+        self.phase = Phase::AwaitingRoll;
+        Ok(())
+    }
+
+    /// Raccoon the beavered cube, i.e. double it once more right after the opponent beavered,
+    /// letting the opponent keep the cube. Only allowed if the [`raccoon`](Rules::raccoon) rule is
+    /// set and the game is not played as part of a match.
+    pub fn raccoon(&mut self, player: Player) -> Result<(), Error> {
+        self.check_phase(Phase::AwaitingRoll)?;
+        if !self.rules.raccoon
+            || self.match_play
+            || !matches!(self.record.last(), Some(Event::Beaver { .. }))
+        {
+            return Err(Error::DoublingNotPermitted);
+        }
+        if player != self.who_plays {
+            return Err(Error::NotYourTurn);
+        }
+
+        let value = 2 * self.cube.value();
+        self.save_undo();
+        self.cube.set(value)?;
+        self.emit(Event::Raccoon { player, value });
+        Ok(())
+    }
+
+    /// Pass the cube offered by the opponent, which ends the game. The opponent wins a single game
+    /// at the value of the cube before it was offered.
+    /// ```
+    /// use backgammon::{Game, Phase, Victory};
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// g.offer_double(Player::Player0).unwrap();
+    /// g.pass(Player::Player1).unwrap();
+    /// assert_eq!(g.phase, Phase::GameOver);
+    /// assert_eq!(g.result().unwrap().winner, Player::Player0);
+    /// assert_eq!(g.result().unwrap().points, 1);
+    /// ```
+    pub fn pass(&mut self, player: Player) -> Result<(), Error> {
+        let _ = self.offered_value()?;
+        if player != self.who_plays.other() {
+            return Err(Error::NotYourTurn);
+        }
+
+        self.save_undo();
+        self.who_plays = player.other();
+        self.phase = Phase::GameOver;
+        self.emit(Event::CubeDrop { player });
+        self.notify_game_over();
+        Ok(())
+    }
+
+    /// Enter the dices rolled by hand, e.g. on a physical board or from a transcript, whatever the
+    /// source of the dices of the game is, see also [`ManualDice`](crate::rules::ManualDice). Both
+    /// values must be between 1 and 6.
+    /// ```
+    /// use backgammon::{Error, Game};
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// assert!(matches!(g.set_roll((0, 7)), Err(Error::DiceInvalid)));
+    /// g.set_roll((6, 4)).unwrap();
+    /// assert_eq!(g.who_plays, Player::Player0);
+    /// assert_eq!(g.dices.values, (6, 4));
+    /// ```
+    pub fn set_roll(&mut self, values: (u8, u8)) -> Result<&mut Self, Error> {
+        self.roll_with(values)
+    }
+
+    /// Roll the dices with the given random number generator instead of the source of the dices of
+    /// the game, e.g. to reuse the state of a generator across many games of a simulation.
+    /// ```
+    /// use backgammon::Game;
+    /// use rand::SeedableRng;
+    /// use rand::rngs::StdRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(3);
+    /// let mut g = Game::new();
+    /// g.roll_with_rng(&mut rng).unwrap();
+    /// assert_ne!(g.dices.values, (0, 0));
+    /// ```
+    pub fn roll_with_rng(&mut self, rng: &mut impl Rng) -> Result<&mut Self, Error> {
+        self.check_phase(Phase::AwaitingRoll)?;
+        let dices = self.dices.roll_with_rng(rng);
+        self.roll_with(dices.values)
+    }
+
+    /// Roll the dices with the given values instead of random ones, e.g. when replaying a recorded
+    /// game or when playing with physical dices.
+    ///
+    /// In the opening roll, the player with the larger dice value, i.e. player 0 if the first value
+    /// is larger, starts the game.
+    /// ```
+    /// use backgammon::{Game, Phase};
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.roll_with((1, 3)).unwrap();
+    /// assert_eq!(g.who_plays, Player::Player1);
+    /// assert_eq!(g.phase, Phase::AwaitingMove);
+    /// ```
+    pub fn roll_with(&mut self, values: (u8, u8)) -> Result<&mut Self, Error> {
+        self.check_phase(Phase::AwaitingRoll)?;
+        if !(1..=6).contains(&values.0) || !(1..=6).contains(&values.1) {
             return Err(Error::DiceInvalid);
         }
 
+        self.dices = Dices::new(values);
+        self.moves.clear();
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        if self.who_plays == Player::Nobody {
+            match self.dices.values.0.cmp(&self.dices.values.1) {
+                Ordering::Equal => {
+                    self.who_plays = Player::Nobody;
+                }
+                Ordering::Greater => {
+                    self.who_plays = Player::Player0;
+                    self.phase = Phase::AwaitingMove;
+                }
+                Ordering::Less => {
+                    self.who_plays = Player::Player1;
+                    self.phase = Phase::AwaitingMove;
+                }
+            }
+        }
+        self.emit(Event::Roll {
+            player: self.who_plays,
+            dices: self.dices.values,
+        });
+        match self.who_plays {
+            // a tied opening roll is rolled again
+            Player::Nobody => self.murphy()?,
+            // the player on roll passes if no checker can be moved, also after the opening roll
+            _ => self.finish_turn(),
+        }
         Ok(self)
     }
-}
 
-/// Implements SetRules for Game
-impl GameRules for Game {
-    fn with_beaver(mut self) -> Self {
-        self.rules.beaver = true;
-        self
+    /// Take a snapshot of the current state
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            dices: self.dices,
+            who_plays: self.who_plays,
+            phase: self.phase,
+            board: self.board.clone(),
+            cube: self.cube.clone(),
+            moves: self.moves.clone(),
+            record: self.record.clone(),
+        }
     }
 
-    fn with_raccoon(mut self) -> Self {
-        self.rules.raccoon = true;
-        self
+    /// Restore a snapshot and return the state replaced
+    fn restore(&mut self, snapshot: Snapshot) -> Snapshot {
+        let current = self.snapshot();
+        self.dices = snapshot.dices;
+        self.who_plays = snapshot.who_plays;
+        self.phase = snapshot.phase;
+        self.board = snapshot.board;
+        self.cube = snapshot.cube;
+        self.moves = snapshot.moves;
+        self.record = snapshot.record;
+        current
     }
 
-    fn with_murphy(mut self, limit: u8) -> Self {
-        self.rules.murphy = true;
-        self.rules.murphy_limit = limit;
-        self
+    /// Remember the current state before an action, so that the action can be undone
+    fn save_undo(&mut self) {
+        self.undo_stack.push(self.snapshot());
+        self.redo_stack.clear();
     }
 
-    fn with_jacoby(mut self) -> Self {
-        self.rules.jacoby = true;
-        self
+    /// Take back the last checker move or cube action, including checkers hit by the move.
+    ///
+    /// Rolling the dices can not be undone, therefore actions before the last roll can not be
+    /// taken back.
+    /// ```
+    /// use backgammon::{Game, Phase};
+    /// use backgammon::rules::{Dices, Player};
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// g.phase = Phase::AwaitingMove;
+    /// g.dices = Dices { values: (3, 1), consumed: (false, false, true, true) };
+    ///
+    /// g.apply_move(&"8/5".parse().unwrap()).unwrap();
+    /// g.undo().unwrap();
+    /// assert_eq!(g.board.get().points[7].count, 3);
+    /// g.redo().unwrap();
+    /// assert_eq!(g.board.get().points[7].count, 2);
+    /// ```
+    pub fn undo(&mut self) -> Result<(), Error> {
+        let snapshot = self.undo_stack.pop().ok_or(Error::UndoInvalid)?;
+        let current = self.restore(snapshot);
+        self.redo_stack.push(current);
+        let mut observers = core::mem::take(&mut self.observers);
+        for observer in observers.0.iter_mut() {
+            observer.on_undo(self);
+        }
+        self.observers = observers;
+        Ok(())
     }
 
-    fn with_holland(mut self) -> Self {
-        self.rules.holland = true;
-        self
+    /// Play again the last checker move or cube action which has been taken back.
+    pub fn redo(&mut self) -> Result<(), Error> {
+        let snapshot = self.redo_stack.pop().ok_or(Error::RedoInvalid)?;
+        let current = self.restore(snapshot);
+        self.undo_stack.push(current);
+        let mut observers = core::mem::take(&mut self.observers);
+        for observer in observers.0.iter_mut() {
+            observer.on_redo(self);
+        }
+        self.observers = observers;
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Check if the game is in the given phase, otherwise return the error explaining what has to
+    /// happen first.
+    pub(crate) fn check_phase(&self, phase: Phase) -> Result<(), Error> {
+        if self.phase == phase {
+            return Ok(());
+        }
+        match self.phase {
+            Phase::AwaitingRoll => Err(Error::RollFirst),
+            Phase::AwaitingCubeDecision => Err(Error::CubeReceived),
+            Phase::AwaitingMove => Err(Error::MoveFirst),
+            Phase::GameOver => Err(Error::GameEnded),
+        }
+    }
 
-    // Test Display trait for Game
-    #[test]
-    fn test_display() {
-        let g = Game::new();
+    /// Parse a play in the conventional notation, e.g. "24/13 6/off", resolving every segment
+    /// against the dice values rolled.
+    ///
+    /// In contrast to [`Play::from_str`](core::str::FromStr), this also resolves compound moves
+    /// like "24/13" for a 6-5 and bearing off with a larger dice value than needed.
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::rules::{Dices, Player};
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// g.dices = Dices { values: (6, 5), consumed: (false, false, true, true) };
+    ///
+    /// let play = g.parse_play("24/13").unwrap();
+    /// assert_eq!(play.moves[0].steps, vec![6, 5]);
+    /// ```
+    pub fn parse_play(&self, s: &str) -> Result<Play, Error> {
+        let mut available = self.dices.available();
+        let mut moves = Vec::new();
+        for token in moves::split_play(s)? {
+            let (fields, hits) = moves::parse_fields(token)?;
+            let mut steps = Vec::new();
+            for pair in fields.windows(2) {
+                let found = (1..=available.len())
+                    .find_map(|len| self.resolve_steps(pair[0], pair[1], &available, len))
+                    .ok_or(Error::NotationInvalid)?;
+                for dice in found.iter() {
+                    let pos = available.iter().position(|d| d == dice);
+                    let _ = available.remove(pos.ok_or(Error::NotationInvalid)?);
+                }
+                steps.extend(found);
+            }
+            let mut m = CheckerMove::new(fields[0], steps)?;
+            m.hits = hits;
+            moves.push(m);
+        }
+        Ok(Play { moves })
+    }
+
+    /// Find a sequence of at most `len` dice values leading a checker from one field to
+    /// another, without touching fields blocked by the opponent.
+    fn resolve_steps(&self, from: Field, to: Field, dices: &[u8], len: usize) -> Option<Vec<u8>> {
+        if len == 0 {
+            return None;
+        }
+        for (i, dice) in dices.iter().enumerate() {
+            let next = match from.step(*dice) {
+                Ok(next) => next,
+                Err(_) => continue,
+            };
+            if next == to {
+                return Some(vec![*dice]);
+            }
+            if let Field::Point(field) = next {
+                if next.index() <= to.index()
+                    || self.board.blocked(self.who_plays, field).unwrap_or(true)
+                {
+                    continue;
+                }
+                let mut rest = dices.to_vec();
+                let _ = rest.remove(i);
+                if let Some(mut steps) = self.resolve_steps(next, to, &rest, len - 1) {
+                    steps.insert(0, *dice);
+                    return Some(steps);
+                }
+            }
+        }
+        None
+    }
+
+    /// Apply the move of a single checker for the player whose turn it is.
+    ///
+    /// The board is only modified if the move is legal. Once all dice values have been consumed,
+    /// it is the other player's turn.
+    ///
+    /// An illegal move is rejected with the reason, e.g. for explaining it to the player:
+    /// [`Error::MoveInvalidChecker`] if the player has no checker to move on the field,
+    /// [`Error::MoveInvalidDice`] if a dice value is not left to be played,
+    /// [`Error::FieldBlocked`] if the checker would land on a field held by the opponent,
+    /// [`Error::MoveInvalidBar`] if a checker on the bar has to enter first, and
+    /// [`Error::MoveInvalidLargerDice`] or [`Error::MoveInvalidMaxDice`] if the move does not
+    /// allow to play the larger or as many dice values as possible.
+    /// ```
+    /// use backgammon::{Game, Phase};
+    /// use backgammon::rules::{CheckerMove, Dices, Field, Player};
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// g.phase = Phase::AwaitingMove;
+    /// g.dices = Dices { values: (3, 1), consumed: (false, false, true, true) };
+    ///
+    /// g.apply_move(&CheckerMove::new(Field::Point(7), vec![3]).unwrap()).unwrap();
+    /// g.apply_move(&CheckerMove::new(Field::Point(5), vec![1]).unwrap()).unwrap();
+    /// assert_eq!(g.board.get().points[4].count, 2);
+    /// assert_eq!(g.who_plays, Player::Player1);
+    /// ```
+    pub fn apply_move(&mut self, mv: &CheckerMove) -> Result<(), Error> {
+        self.check_phase(Phase::AwaitingMove)?;
+        let player = self.who_plays;
+        if player == Player::Nobody {
+            return Err(Error::RollFirst);
+        }
+
+        // check if the dice values are available
+        let mut dices = self.dices;
+        for dice in mv.steps.iter() {
+            dices.consume(*dice).map_err(|_| Error::MoveInvalidDice)?;
+        }
+
+        // check every single step of the move
+        let mut board = self.board.clone();
+        let mut steps = Vec::with_capacity(mv.steps.len());
+        let mut from = mv.from;
+        for dice in mv.steps.iter() {
+            let to = board.legal_step(player, from, *dice)?;
+            let mut step = CheckerMove {
+                from,
+                to,
+                steps: vec![*dice],
+                hits: Vec::new(),
+            };
+            step.hits = board.hits(player, &step)?;
+            board.apply(player, &step)?;
+            steps.push(step);
+            from = to;
+        }
+        if from != mv.to {
+            return Err(Error::MoveInvalid);
+        }
+
+        // check if the move allows to play as many dice values as possible
+        let available = self.dices.available();
+        let plays = self.plays(&available);
+        if !plays.iter().any(|p| p.starts_with(&steps)) {
+            if self
+                .board
+                .plays(player, &available)
+                .iter()
+                .any(|p| p.starts_with(&steps))
+            {
+                return Err(Error::MoveInvalidHead);
+            }
+            let larger = available.iter().max().copied().unwrap_or(0);
+            if plays.iter().all(|p| p.len() == 1) && mv.steps[0] != larger {
+                return Err(Error::MoveInvalidLargerDice);
+            }
+            return Err(Error::MoveInvalidMaxDice);
+        }
+
+        self.save_undo();
+        self.board = board;
+        self.dices = dices;
+        let played = CheckerMove {
+            from: mv.from,
+            to: mv.to,
+            steps: mv.steps.clone(),
+            hits: steps.into_iter().flat_map(|s| s.hits).collect(),
+        };
+        self.emit(Event::Move {
+            player,
+            mv: played.clone(),
+        });
+        self.moves.push(played);
+        self.finish_turn();
+
+        Ok(())
+    }
+
+    /// Apply a complete play of the player whose turn it is, e.g. as parsed by
+    /// [`parse_play`](Self::parse_play).
+    ///
+    /// The game is only modified if all moves are legal and no dice value is left which could
+    /// still be played, so a server or a frontend can accept or reject a play as a whole.
+    /// ```
+    /// use backgammon::{Error, Game};
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.roll_with((3, 1)).unwrap();
+    /// let half = g.parse_play("8/5").unwrap();
+    /// assert!(matches!(g.apply_play(&half), Err(Error::MoveInvalidMaxDice)));
+    /// assert_eq!(g.board.get().points[7].count, 3);
+    ///
+    /// let play = g.parse_play("8/5 6/5").unwrap();
+    /// g.apply_play(&play).unwrap();
+    /// assert_eq!(g.who_plays, Player::Player1);
+    /// ```
+    pub fn apply_play(&mut self, play: &Play) -> Result<(), Error> {
+        self.check_phase(Phase::AwaitingMove)?;
+        let mut next = self.clone();
+        for m in &play.moves {
+            next.apply_move(m)?;
+        }
+        if next.phase == Phase::AwaitingMove {
+            return Err(Error::MoveInvalidMaxDice);
+        }
+        // the clone has played the moves without observers, pass its events on now
+        let played = self.record.events().len();
+        next.observers = core::mem::take(&mut self.observers);
+        *self = next;
+        let events = self.record.events()[played..].to_vec();
+        for event in &events {
+            self.notify(event);
+        }
+        if self.phase == Phase::GameOver {
+            self.notify_game_over();
+        }
+        Ok(())
+    }
+
+    /// Move a single checker by a single dice value, for playing a turn one checker at a time.
+    ///
+    /// Each dice value played has to leave a way to play as many dice values as possible with the
+    /// remaining ones, otherwise the move is rejected. The turn passes to the other player once
+    /// all dice values are played or none of the remaining ones can be played, so a turn can only
+    /// end after the full legal maximum has been played.
+    /// ```
+    /// use backgammon::{Game, Phase};
+    /// use backgammon::rules::{Dices, Field, Player};
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// g.phase = Phase::AwaitingMove;
+    /// g.dices = Dices { values: (3, 1), consumed: (false, false, true, true) };
+    ///
+    /// g.play_die(Field::Point(7), 3).unwrap();
+    /// assert_eq!(g.remaining_dice(), vec![1]);
+    /// assert_eq!(g.playable_dice(Field::Point(5)), vec![1]);
+    /// g.play_die(Field::Point(5), 1).unwrap();
+    /// assert_eq!(g.who_plays, Player::Player1);
+    /// ```
+    pub fn play_die(&mut self, from: Field, die: u8) -> Result<(), Error> {
+        self.apply_move(&CheckerMove::new(from, vec![die])?)
+    }
+
+    /// Returns the dice values which are left to be played in the current turn.
+    pub fn remaining_dice(&self) -> Vec<u8> {
+        if self.phase != Phase::AwaitingMove {
+            return Vec::new();
+        }
+        self.dices.available()
+    }
+
+    /// Returns the dice values with which the checker on a field may be moved, e.g. for
+    /// highlighting possible moves in a graphical interface.
+    pub fn playable_dice(&self, from: Field) -> Vec<u8> {
+        let mut dice: Vec<u8> = self
+            .legal_moves()
+            .into_iter()
+            .filter_map(|p| p.moves.first().cloned())
+            .filter(|m| m.from == from)
+            .map(|m| m.steps[0])
+            .collect();
+        dice.sort_unstable();
+        dice.dedup();
+        dice
+    }
+
+    /// Returns the amount of checkers a player has borne off.
+    pub fn borne_off(&self, player: Player) -> Result<u8, Error> {
+        self.board.get_checkers(player, Field::Off)
+    }
+
+    /// Check that the game holds a valid state: the board holds a valid position, a player is on
+    /// turn whenever the phase requires one, rolled dices have valid values, and the cube only has
+    /// an owner once it has been turned.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.board.validate()?;
+
+        if matches!(
+            self.phase,
+            Phase::AwaitingMove | Phase::AwaitingCubeDecision
+        ) && self.who_plays == Player::Nobody
+        {
+            return Err(Error::PositionInvalid(PositionError::TurnInvalid));
+        }
+
+        let values = [self.dices.values.0, self.dices.values.1];
+        if self.phase == Phase::AwaitingMove && values.iter().any(|v| !(1..=6).contains(v)) {
+            return Err(Error::PositionInvalid(PositionError::DicesInvalid));
+        }
+
+        if self.cube.value() == 1 && self.cube.owner() != Player::Nobody {
+            return Err(Error::PositionInvalid(PositionError::CubeInvalid));
+        }
+        Ok(())
+    }
+
+    /// Render the game as the classic board diagram for logs and terminals without colors, from
+    /// the point-of-view of player 0, who plays the checkers marked X from point 24 to point 1.
+    ///
+    /// Unlike the [`Display`](fmt::Display) output, which can be parsed back into a game, the
+    /// rendering shows the checkers on the bar in the middle of the board, and next to the board
+    /// the pip counts and checkers off of both players, the cube on the side of its owner, and
+    /// who has to act next.
+    /// ```
+    /// use backgammon::Game;
+    ///
+    /// let mut g = Game::new();
+    /// g.roll_with((3, 1)).unwrap();
+    /// let board = g.render_ascii();
+    /// assert!(board.contains("O  pips 167  off 0"));
+    /// assert!(board.contains("X to play 3-1"));
+    /// ```
+    pub fn render_ascii(&self) -> String {
+        let display = self.board.get();
+        let cell = |point: Point, row: usize| match (point.owner, point.count) {
+            (_, n) if n > 5 && row == 4 => format!("{:>2} ", n),
+            (player, n) if usize::from(n) > row => format!(" {} ", mark(player)),
+            _ => "   ".to_string(),
+        };
+        // player 0 enters on the top half and player 1 on the bottom half
+        let bar = [
+            Point::new(Player::Player0, display.bar[0]),
+            Point::new(Player::Player1, display.bar[1]),
+        ];
+
+        let mut board = Vec::with_capacity(13);
+        board.push(border(&DIAGRAM_POINTS[0]));
+        for (half, points) in DIAGRAM_POINTS.iter().enumerate() {
+            if half == 1 {
+                board.push("|                  |BAR|                  |".to_string());
+            }
+            for row in 0..5 {
+                // the rows of the bottom half are stacked from the bottom
+                let row = if half == 0 { row } else { 4 - row };
+                let cells: Vec<String> = points
+                    .iter()
+                    .map(|p| cell(display.points[p - 1], row))
+                    .collect();
+                board.push(format!(
+                    "|{}|{}|{}|",
+                    cells[..6].concat(),
+                    cell(bar[half], row),
+                    cells[6..].concat()
+                ));
+            }
+        }
+        board.push(border(&DIAGRAM_POINTS[1]));
+
+        let side = self.side_panel();
+        let mut rendered = String::new();
+        for (line, note) in board.iter().zip(side.iter()) {
+            rendered.push_str(format!(" {}    {}", line, note).trim_end());
+            rendered.push('\n');
+        }
+        rendered
+    }
+
+    /// Returns the notes next to each of the 13 lines of the board diagram: the pip counts and
+    /// checkers off of both players on their side, the cube on the side of its owner, and who
+    /// has to act next
+    pub(crate) fn side_panel(&self) -> Vec<String> {
+        // player 1 bears off on the top half, player 0 on the bottom half
+        let mut side = vec![String::new(); 13];
+        let row = |player: Player| match player {
+            Player::Player1 => 0,
+            _ => 12,
+        };
+        for (player, off) in [
+            (Player::Player1, self.board.off[1]),
+            (Player::Player0, self.board.off[0]),
+        ] {
+            side[row(player)] = format!(
+                "{}  pips {}  off {}",
+                mark(player),
+                self.pip_count(player),
+                off
+            );
+        }
+        let cube = format!("Cube: {}", self.cube.value());
+        match self.cube.owner() {
+            Player::Player1 => side[2] = cube,
+            Player::Player0 => side[10] = cube,
+            Player::Nobody => side[6] = cube,
+        }
+        let status = match self.phase {
+            Phase::AwaitingRoll if self.who_plays == Player::Nobody => {
+                (Player::Nobody, "Opening roll".to_string())
+            }
+            Phase::AwaitingRoll => (self.who_plays, format!("{} to roll", mark(self.who_plays))),
+            Phase::AwaitingMove => {
+                let (a, b) = self.dices.values;
+                let player = self.who_plays;
+                (player, format!("{} to play {}-{}", mark(player), a, b))
+            }
+            Phase::AwaitingCubeDecision => {
+                let player = self.who_plays.other();
+                (player, format!("{} to take or pass", mark(player)))
+            }
+            Phase::GameOver => match self.result() {
+                Some(result) => (
+                    Player::Nobody,
+                    format!(
+                        "{} wins {} point{}",
+                        mark(result.winner),
+                        result.points,
+                        if result.points == 1 { "" } else { "s" }
+                    ),
+                ),
+                None => (Player::Nobody, "Game over".to_string()),
+            },
+        };
+        match status {
+            (Player::Player1, text) => side[4] = text,
+            (Player::Player0, text) => side[8] = text,
+            (Player::Nobody, text) => side[5] = text,
+        }
+        side
+    }
+
+    /// Returns the pip count of a player, including checkers on the bar, or 0 for nobody.
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::rules::Player;
+    ///
+    /// let g = Game::new();
+    /// assert_eq!(g.pip_count(Player::Player0), 167);
+    /// ```
+    pub fn pip_count(&self, player: Player) -> u32 {
+        self.board.pip_count(player).unwrap_or(0)
+    }
+
+    /// Returns true if no checker can be hit anymore, i.e. the checkers of both players have
+    /// passed each other and the game is a pure race
+    /// ```
+    /// use backgammon::Game;
+    ///
+    /// let g = Game::new();
+    /// assert!(!g.is_race());
+    /// ```
+    pub fn is_race(&self) -> bool {
+        self.board.is_race()
+    }
+
+    /// Returns the class of the position, see [`classify`](crate::eval::classify)
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::eval::PositionClass;
+    ///
+    /// assert_eq!(Game::new().classify(), PositionClass::Contact);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn classify(&self) -> PositionClass {
+        classify(&self.board)
+    }
+
+    /// Returns the Zobrist hash of the position, i.e. of the checkers, the player on roll, and
+    /// the cube, see [`zobrist`](crate::zobrist)
+    pub fn hash64(&self) -> u64 {
+        crate::zobrist::hash(self)
+    }
+
+    /// Returns the effective pip count of a player, i.e. the pip count plus the pips expected to
+    /// be wasted while bearing off, see
+    /// [`effective_pip_count`](crate::bearoff::effective_pip_count)
+    #[cfg(feature = "std")]
+    pub fn effective_pip_count(&self, player: Player) -> f64 {
+        crate::bearoff::effective_pip_count(&self.board, player).unwrap_or(0.0)
+    }
+
+    /// Returns the pips a player is expected to waste while bearing off, see
+    /// [`wastage`](crate::bearoff::wastage)
+    #[cfg(feature = "std")]
+    pub fn wastage(&self, player: Player) -> f64 {
+        crate::bearoff::wastage(&self.board, player).unwrap_or(0.0)
+    }
+
+    /// Returns the pip count of the opponent minus the pip count of the player whose turn it is,
+    /// i.e. a positive value means the player on roll leads the race. If it is nobody's turn,
+    /// the difference is given from the point-of-view of player 0.
+    pub fn pip_difference(&self) -> i32 {
+        let player = match self.who_plays {
+            Player::Nobody => Player::Player0,
+            player => player,
+        };
+        self.pip_count(player.other()) as i32 - self.pip_count(player) as i32
+    }
+
+    /// Returns the result of the game once a player has borne off all checkers, resigned, or passed
+    /// the cube, otherwise `None`. In Plakoto, a player who ends the turn with the last checker of
+    /// the opponent on its starting field pinned wins a gammon.
+    ///
+    /// With the [`jacoby`](Rules::jacoby) rule in a game not played as part of a match, gammons
+    /// and backgammons only count as a single game unless the cube has been offered or doubled
+    /// automatically during the game.
+    /// ```
+    /// use backgammon::{Game, Victory};
+    /// use backgammon::rules::{Player, Point};
+    ///
+    /// let mut g = Game::new();
+    /// assert_eq!(g.result(), None);
+    ///
+    /// g.board.points = g.board.points.map(|p| match p.owner {
+    ///     Player::Player0 => Point::EMPTY,
+    ///     _ => p,
+    /// });
+    /// g.board.off[0] = 15;
+    /// let result = g.result().unwrap();
+    /// assert_eq!(result.winner, Player::Player0);
+    /// assert_eq!(result.victory, Victory::Backgammon);
+    /// assert_eq!(result.points, 3);
+    /// ```
+    pub fn result(&self) -> Option<GameResult> {
+        let (winner, victory) = match self.record.last() {
+            Some(Event::Resign { player, victory }) => (player.other(), *victory),
+            Some(Event::CubeDrop { player }) => (player.other(), Victory::Single),
+            _ => match self.pinned_mother() {
+                Some(loser) if self.phase == Phase::GameOver => (loser.other(), Victory::Gammon),
+                _ => {
+                    let winner = [Player::Player0, Player::Player1]
+                        .into_iter()
+                        .find(|p| self.borne_off(*p).unwrap_or(0) == 15)?;
+                    (winner, self.victory(winner))
+                }
+            },
+        };
+
+        let jacoby = self.rules.jacoby
+            && !self.match_play
+            && !self
+                .record
+                .events()
+                .iter()
+                .any(|e| matches!(e, Event::CubeOffer { .. } | Event::AutomaticDouble { .. }));
+        let multiplier = if jacoby { 1 } else { victory.multiplier() };
+
+        Some(GameResult {
+            winner,
+            victory,
+            points: self.cube.value() * multiplier,
+        })
+    }
+
+    /// Classify the victory of a player who has borne off all checkers. Checkers which are off
+    /// from the start of the variant, as in Hypergammon, do not save the gammon.
+    fn victory(&self, winner: Player) -> Victory {
+        let loser = winner.other();
+        let start = self.rules.variant.start();
+        if self.borne_off(loser).unwrap_or(0) > start.get_checkers(loser, Field::Off).unwrap_or(0) {
+            Victory::Single
+        } else if self.rules.variant.has_backgammons()
+            && (self.board.get_checkers(loser, Field::Bar).unwrap_or(0) > 0
+                || (18..24)
+                    .any(|f| self.board.get_checkers(loser, Field::Point(f)).unwrap_or(0) > 0))
+        {
+            Victory::Backgammon
+        } else {
+            Victory::Gammon
+        }
+    }
+
+    /// Returns all legal plays for the player whose turn it is with the dice values left.
+    ///
+    /// Every play consists of moves of single checkers by one dice value each and uses as many
+    /// dice values as possible.
+    pub fn legal_moves(&self) -> Vec<Play> {
+        if self.who_plays == Player::Nobody || self.phase != Phase::AwaitingMove {
+            return Vec::new();
+        }
+        self.plays(&self.dices.available())
+            .into_iter()
+            .map(|moves| Play { moves })
+            .collect()
+    }
+
+    /// Returns all distinct positions the player whose turn it is can reach with the dice values
+    /// left, each paired with one move sequence leading there.
+    ///
+    /// Many legal move orders lead to the same position, e.g. "8/5 6/5" and "6/5 8/5". Of these,
+    /// only the first one found by [`legal_moves`](Game::legal_moves) is returned.
+    /// ```
+    /// use backgammon::{Game, Phase};
+    /// use backgammon::rules::{Dices, Player};
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// g.phase = Phase::AwaitingMove;
+    /// g.dices = Dices { values: (3, 1), consumed: (false, false, true, true) };
+    ///
+    /// assert!(g.legal_plays().len() < g.legal_moves().len());
+    /// ```
+    pub fn legal_plays(&self) -> Vec<(Board, Play)> {
+        let mut seen = BTreeSet::new();
+        let mut plays = Vec::new();
+        for play in self.legal_moves() {
+            let mut board = self.board.clone();
+            if play
+                .moves
+                .iter()
+                .any(|m| board.apply(self.who_plays, m).is_err())
+            {
+                continue;
+            }
+            if seen.insert(board.clone()) {
+                plays.push((board, play));
+            }
+        }
+        plays
+    }
+
+    /// Counts the distinct positions reached by legal plays over the given number of plies,
+    /// summed over all 21 rolls in each ply, like the perft of chess engines.
+    ///
+    /// The count starts from the board and the player whose turn it is, ignoring the dice values
+    /// already rolled. If it is nobody's turn, the first ply is the opening roll, i.e. each
+    /// player plays the 15 rolls without doubles. Rolls which cannot be played count as a single
+    /// empty play, and finished games are not continued. The known counts verify the move
+    /// generator, and counting them measures its speed.
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// assert_eq!(g.perft(0), 1);
+    /// g.who_plays = Player::Player0;
+    /// assert_eq!(g.perft(1), 447);
+    /// ```
+    pub fn perft(&self, depth: usize) -> u64 {
+        match self.who_plays {
+            _ if depth == 0 => 1,
+            Player::Nobody => [Player::Player0, Player::Player1]
+                .iter()
+                .flat_map(|player| {
+                    DiceRoll::all_distinct()
+                        .filter(|(roll, _)| !roll.is_double())
+                        .map(move |(roll, _)| {
+                            perft(self.rules.variant, &self.board, *player, roll, depth)
+                        })
+                })
+                .sum(),
+            player => DiceRoll::all_distinct()
+                .map(|(roll, _)| perft(self.rules.variant, &self.board, player, roll, depth))
+                .sum(),
+        }
+    }
+
+    /// Returns the distinct legal plays of the player whose turn it is, ranked from the best to
+    /// the worst by the evaluator, see [`rank_plays`](crate::eval::rank_plays)
+    #[cfg(feature = "std")]
+    pub fn ranked_moves(
+        &self,
+        evaluator: &(impl Evaluator + ?Sized),
+    ) -> Result<Vec<(Play, Evaluation)>, Error> {
+        rank_plays(self, evaluator)
+    }
+
+    /// Returns the best play of the player whose turn it is with the given dice values, together
+    /// with the evaluation of the position after the play from the point-of-view of the player.
+    ///
+    /// If the game awaits a roll, the play is found as if the dices had been rolled with these
+    /// values, without changing the game. If the dices have been rolled already, the values must
+    /// match them. If no dice value can be played, the best play is empty. The first move of a
+    /// game is taken from the [opening book](crate::book).
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::eval::Heuristic;
+    ///
+    /// let mut g = Game::new();
+    /// g.roll_with((3, 1)).unwrap();
+    /// let (play, evaluation) = g.best_move((3, 1), &Heuristic).unwrap();
+    /// assert_eq!(play.to_string(), "8/5 6/5");
+    /// assert!(evaluation.win > 0.5);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn best_move(
+        &self,
+        values: (u8, u8),
+        evaluator: &(impl Evaluator + ?Sized),
+    ) -> Result<(Play, Evaluation), Error> {
+        let game = self.rolled(values)?;
+        // the first move of a game is taken from the opening book
+        let opening = game.board == Board::new() && game.dices.available().len() == 2;
+        if let Some((play, _)) = opening
+            .then(|| book::opening(game.dices.rolled()).into_iter().next())
+            .flatten()
+        {
+            let mut board = game.board.clone();
+            for m in &play.moves {
+                board.apply(game.who_plays, m)?;
+            }
+            let evaluation = evaluator.evaluate(&board, game.who_plays.other())?;
+            return Ok((play, evaluation.inverted()));
+        }
+        game.ranked_moves(evaluator)?
+            .into_iter()
+            .next()
+            .ok_or(Error::MoveInvalid)
+    }
+
+    /// Returns the best plays of the player whose turn it is with the given dice values, at most
+    /// `n` of them ranked from the best, with their equities and the equity each loses compared
+    /// to the best play.
+    ///
+    /// The dice values are handled like in [`best_move`](Self::best_move), but all plays are
+    /// ranked by the evaluator, also for the first move of a game.
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::eval::Heuristic;
+    ///
+    /// let g = Game::new();
+    /// let hints = g.hints((3, 1), &Heuristic, 3).unwrap();
+    /// assert_eq!(hints.len(), 3);
+    /// assert_eq!(hints[0].play.to_string(), "8/5 6/5");
+    /// assert_eq!(hints[0].loss, 0.0);
+    /// assert!(hints[1].loss >= 0.0 && hints[2].loss >= hints[1].loss);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn hints(
+        &self,
+        values: (u8, u8),
+        evaluator: &(impl Evaluator + ?Sized),
+        n: usize,
+    ) -> Result<Vec<Hint>, Error> {
+        let ranked = self.rolled(values)?.ranked_moves(evaluator)?;
+        let best = ranked.first().map_or(0.0, |(_, e)| e.equity());
+        Ok(ranked
+            .into_iter()
+            .take(n)
+            .map(|(play, evaluation)| Hint {
+                play,
+                evaluation,
+                equity: evaluation.equity(),
+                loss: best - evaluation.equity(),
+            })
+            .collect())
+    }
+
+    /// Returns the cube decision of the player whose turn it is before rolling, with the
+    /// cubeful equities of not doubling, of doubling and the opponent taking, and of doubling and
+    /// the opponent passing, see [`cube_decision`](crate::cubeful::cube_decision).
+    /// ```
+    /// use backgammon::Game;
+    /// use backgammon::eval::Heuristic;
+    /// use backgammon::met::Met;
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// let decision = g.cube_hint(&Heuristic, &Met::default()).unwrap();
+    /// assert!(!decision.double());
+    /// assert!(decision.take());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn cube_hint(
+        &self,
+        evaluator: &(impl Evaluator + ?Sized),
+        met: &Met,
+    ) -> Result<CubeDecision, Error> {
+        self.check_phase(Phase::AwaitingRoll)?;
+        if self.who_plays == Player::Nobody {
+            return Err(Error::RollFirst);
+        }
+        let evaluation = evaluator.evaluate_game(self)?;
+        cube_decision(&evaluation, &CubeContext::from_game(self), met)
+    }
+
+    /// Returns a copy of the game in which the player whose turn it is has rolled the given dice
+    /// values, which have to match the dices if rolled already
+    #[cfg(feature = "std")]
+    fn rolled(&self, values: (u8, u8)) -> Result<Game, Error> {
+        let mut game = self.clone();
+        match self.phase {
+            Phase::AwaitingRoll => {
+                let _ = game.roll_with(values)?;
+            }
+            Phase::AwaitingMove if self.dices.rolled().same_as(&values.into()) => {}
+            Phase::AwaitingMove => return Err(Error::DiceInvalid),
+            _ => return Err(Error::MoveInvalid),
+        }
+        Ok(game)
+    }
+
+    /// Returns all legal ways for the player whose turn it is to play the given dice values after
+    /// the moves already played in the turn, following the rules of the variant
+    fn plays(&self, dices: &[u8]) -> Vec<Vec<CheckerMove>> {
+        self.rules
+            .variant
+            .plays(&self.board, self.who_plays, dices, &self.moves)
+    }
+
+    /// Returns the player whose last checker on the starting field is pinned in Plakoto, the
+    /// mother checker, which loses the game
+    fn pinned_mother(&self) -> Option<Player> {
+        if self.rules.variant != Variant::Plakoto {
+            return None;
+        }
+        [Player::Player0, Player::Player1]
+            .into_iter()
+            .find(|p| self.board.is_pinned(*p, 23).unwrap_or(false))
+    }
+
+    /// Switch to the other player if all dice values have been consumed or none of the
+    /// remaining ones can be played, and end the game once a player has borne off all checkers
+    /// or, in Plakoto, has pinned the mother checker of the opponent at the end of the turn.
+    fn finish_turn(&mut self) {
+        if self.borne_off(self.who_plays).unwrap_or(0) == 15 {
+            self.phase = Phase::GameOver;
+            self.notify_game_over();
+        } else if self.dices.all_consumed() || self.plays(&self.dices.available()) == vec![vec![]] {
+            if self.pinned_mother() == Some(self.who_plays.other()) {
+                self.phase = Phase::GameOver;
+                self.notify_game_over();
+                return;
+            }
+            self.who_plays = self.who_plays.other();
+            self.phase = Phase::AwaitingRoll;
+        } else {
+            self.phase = Phase::AwaitingMove;
+        }
+    }
+}
+
+impl Roll for Game {
+    fn roll(&mut self) -> Result<&mut Self, Error> {
+        self.check_phase(Phase::AwaitingRoll)?;
+        let values = match &self.dice_source {
+            #[cfg(feature = "std")]
+            Some(source) => source.lock().unwrap_or_else(PoisonError::into_inner).roll(),
+            #[cfg(not(feature = "std"))]
+            Some(source) => source.borrow_mut().roll(),
+            #[cfg(feature = "std")]
+            None => RandomDices.roll(),
+            // without the standard library, there is no random number generator to fall back to
+            #[cfg(not(feature = "std"))]
+            None => return Err(Error::DiceInvalid),
+        };
+        self.roll_with(values)
+    }
+}
+
+impl Move for Game {
+    fn move_checker(&mut self, player: Player, dice: u8, from: usize) -> Result<&mut Self, Error> {
+        // check if move is permitted
+        let _ = self.move_permitted(player, dice)?;
+
+        self.apply_move(&CheckerMove::new(Field::Point(from), vec![dice])?)?;
+        Ok(self)
+    }
+
+    fn move_checker_from_bar(&mut self, player: Player, dice: u8) -> Result<&mut Self, Error> {
+        // check if move is permitted
+        let _ = self.move_permitted(player, dice)?;
+
+        self.apply_move(&CheckerMove::new(Field::Bar, vec![dice])?)?;
+        Ok(self)
+    }
+
+    /// Implements checks to validate if the player is allowed to move
+    fn move_permitted(&mut self, player: Player, dice: u8) -> Result<&mut Self, Error> {
+        // check if player is allowed to move
+        if player != self.who_plays {
+            return Err(Error::NotYourTurn);
+        }
+
+        // if player is nobody, you can not play and have to roll first
+        if self.who_plays == Player::Nobody {
+            return Err(Error::RollFirst);
+        }
+
+        // check if player has to roll or take or reject cube first
+        self.check_phase(Phase::AwaitingMove)?;
+
+        // check if dice value has actually been rolled
+        if dice != self.dices.values.0 && dice != self.dices.values.1 {
+            return Err(Error::DiceInvalid);
+        }
+
+        Ok(self)
+    }
+}
+
+/// Implements SetRules for Game
+impl GameRules for Game {
+    fn with_beaver(mut self) -> Self {
+        self.rules.beaver = true;
+        self
+    }
+
+    fn with_raccoon(mut self) -> Self {
+        self.rules.raccoon = true;
+        self
+    }
+
+    fn with_murphy(mut self, limit: u8) -> Self {
+        self.rules.murphy = true;
+        self.rules.murphy_limit = limit;
+        self
+    }
+
+    fn with_jacoby(mut self) -> Self {
+        self.rules.jacoby = true;
+        self
+    }
+
+    fn with_holland(mut self) -> Self {
+        self.rules.holland = true;
+        self
+    }
+
+    /// Play a variant of Backgammon, which also sets up the starting position of the variant.
+    /// The game must not have started yet.
+    fn with_variant(mut self, variant: Variant) -> Self {
+        self.rules.variant = variant;
+        self.set_start();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Point;
+
+    const START: &str = " +13-14-15-16-17-18------19-20-21-22-23-24-+
+ | X           O    |   | O              X |
+ | X           O    |   | O              X |
+ | X           O    |   | O                |
+ | X                |   | O                |
+ | X                |   | O                |
+ |                  |BAR|                  |
+ | O                |   | X                |
+ | O                |   | X                |
+ | O           X    |   | X                |
+ | O           X    |   | X              O |
+ | O           X    |   | X              O |
+ +12-11-10--9--8--7-------6--5--4--3--2--1-+
+ Bar: X 0, O 0
+ Off: X 0, O 0
+ Cube: 1 -
+ Dices: -
+ Turn: -
+ Phase: Awaiting roll
+";
+
+    // Test Display trait for Game
+    #[test]
+    fn test_display() {
+        let g = Game::new();
+        assert_eq!(format!("{}", g), START);
+    }
+
+    /// Logs what observers are told
+    struct Log(std::sync::mpsc::Sender<String>);
+
+    impl Log {
+        fn push(&self, line: String) {
+            let _ = self.0.send(line);
+        }
+    }
+
+    impl GameObserver for Log {
+        fn on_event(&mut self, _: &Game, event: &Event) {
+            self.push(event.to_string());
+        }
+
+        fn on_hit(&mut self, _: &Game, player: Player, point: crate::rules::AbsolutePoint) {
+            self.push(format!("{} hit on {}", player, point));
+        }
+
+        fn on_game_over(&mut self, _: &Game, result: &GameResult) {
+            self.push(format!("{} wins {}", result.winner, result.points));
+        }
+
+        fn on_undo(&mut self, _: &Game) {
+            self.push("undo".to_string());
+        }
+
+        fn on_redo(&mut self, _: &Game) {
+            self.push("redo".to_string());
+        }
+    }
+
+    #[test]
+    fn test_observers() -> Result<(), Error> {
+        let (sender, log) = std::sync::mpsc::channel();
+        let mut g = Game::new();
+        // a blot of player 0 on the 3 point
+        g.board.set(Player::Player0, 5, -1)?;
+        g.board.set(Player::Player0, 2, 1)?;
+        g.subscribe(Log(sender));
+
+        let _ = g.roll_with((2, 5))?;
+        g.apply_move(&"24/22".parse()?)?;
+        g.undo()?;
+        g.redo()?;
+        // the clone does not notify the observers of the original
+        let mut clone = g.clone();
+        clone.resign(Player::Player1, Victory::Single)?;
+        g.apply_move(&"13/8".parse()?)?;
+        g.offer_double(Player::Player0)?;
+        g.pass(Player::Player1)?;
+        assert_eq!(
+            log.try_iter().collect::<Vec<_>>(),
+            vec![
+                "Player 1 rolls 25",
+                "Player 1 moves 24/22*",
+                "Player 0 hit on 3",
+                "undo",
+                "redo",
+                "Player 1 moves 13/8",
+                "Player 0 doubles to 2",
+                "Player 1 drops",
+                "Player 0 wins 1",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_ascii() -> Result<(), Error> {
+        let rendered = " +13-14-15-16-17-18------19-20-21-22-23-24-+    O  pips 167  off 0
+ | X           O    |   | O              X |
+ | X           O    |   | O              X |
+ | X           O    |   | O                |
+ | X                |   | O                |
+ | X                |   | O                |    Opening roll
+ |                  |BAR|                  |    Cube: 1
+ | O                |   | X                |
+ | O                |   | X                |
+ | O           X    |   | X                |
+ | O           X    |   | X              O |
+ | O           X    |   | X              O |
+ +12-11-10--9--8--7-------6--5--4--3--2--1-+    X  pips 167  off 0
+";
+        let mut g = Game::new();
+        assert_eq!(g.render_ascii(), rendered);
+
+        let _ = g.roll_with((3, 1))?;
+        g.board.set_bar(Player::Player1, 1)?;
+        g.board.set(Player::Player1, 2, 9)?;
+        g.cube.set(2)?;
+        g.cube.set_owner(Player::Player1);
+        let lines: Vec<String> = g.render_ascii().lines().map(String::from).collect();
+        assert!(lines[0].ends_with("O  pips 219  off 0"));
+        assert!(lines[2].ends_with("Cube: 2"));
+        assert!(lines[5].contains("| O        9       |"));
+        assert!(lines[8].ends_with("X to play 3-1"));
+        assert!(lines[11].contains("| O | X              O |"));
+
+        g.who_plays = Player::Player1;
+        g.phase = Phase::AwaitingRoll;
+        g.offer_double(Player::Player1)?;
+        assert!(g
+            .render_ascii()
+            .lines()
+            .nth(8)
+            .unwrap_or("")
+            .ends_with("X to take or pass"));
+        g.pass(Player::Player0)?;
+        assert!(g.render_ascii().contains("O wins 2 points"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagram_round_trip() -> Result<(), Error> {
+        let g: Game = START.parse()?;
+        assert_eq!(g.board, Board::new());
+        assert_eq!(g.to_string(), START);
+
+        let mut g = game_with_dices(Player::Player1, (6, 6));
+        g.board.set(Player::Player0, 5, 3)?;
+        g.board.set(Player::Player0, 7, -3)?;
+        g.board.set_bar(Player::Player0, 1)?;
+        g.board.set(Player::Player0, 12, -1)?;
+        g.board.set_off(Player::Player1, 2)?;
+        g.board.set(Player::Player1, 23, -2)?;
+        g.cube.set(4)?;
+        g.cube.set_owner(Player::Player0);
+        let s = g.to_string();
+        assert!(s.contains(" |                  |   | O                |\n"));
+        assert!(s.contains(" | O                |   | 8                |\n"));
+        assert!(s.ends_with(
+            " Bar: X 1, O 0\n Off: X 0, O 2\n Cube: 4 X\n Dices: 6 6\n Turn: O\n Phase: Awaiting move\n"
+        ));
+        let parsed: Game = s.parse()?;
+        assert_eq!(parsed.board, g.board);
+        assert_eq!(parsed.cube, g.cube);
+        assert_eq!(parsed.dices.values, (6, 6));
+        assert_eq!(parsed.who_plays, Player::Player1);
+        assert_eq!(parsed.phase, Phase::AwaitingMove);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagram_invalid() {
+        assert!("".parse::<Game>().is_err());
+        // a checker too many
+        assert!(START
+            .replacen("Off: X 0", "Off: X 1", 1)
+            .parse::<Game>()
+            .is_err());
+        // checkers of both players on a point
+        assert!(START
+            .replacen(" | X           O    |", " | O           O    |", 1)
+            .parse::<Game>()
+            .is_err());
+        // dices rolled while awaiting the roll
+        assert!(START
+            .replacen("Dices: -", "Dices: 3 1", 1)
+            .parse::<Game>()
+            .is_err());
+        assert!(START
+            .replacen("Turn: -", "Turn: Y", 1)
+            .parse::<Game>()
+            .is_err());
+    }
+
+    fn game_with_dices(player: Player, values: (u8, u8)) -> Game {
+        let mut g = Game::new();
+        g.who_plays = player;
+        g.phase = Phase::AwaitingMove;
+        g.dices = Dices {
+            values,
+            consumed: (false, false, values.0 != values.1, values.0 != values.1),
+        };
+        g
+    }
+
+    #[test]
+    #[cfg(not(feature = "std"))]
+    fn test_roll_without_std() -> Result<(), Error> {
+        use crate::rules::SeededDice;
+
+        // there are no random dices to fall back to
+        let mut g = Game::new();
+        assert!(matches!(g.roll(), Err(Error::DiceInvalid)));
+        let mut g = Game::new().with_dice_source(SeededDice::new(3));
+        let _ = g.roll()?;
+        assert_ne!(g.who_plays, Player::Nobody);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_roll_opening() -> Result<(), Error> {
+        let mut g = Game::new();
+        while g.who_plays == Player::Nobody {
+            let _ = g.roll()?;
+        }
+        assert_ne!(g.dices.values.0, g.dices.values.1);
+        assert!(g.roll().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nackgammon() -> Result<(), Error> {
+        let mut g = Game::new().with_variant(Variant::Nackgammon);
+        assert_eq!(g.board, Variant::Nackgammon.start());
+        assert_eq!(g.record().start(), &g.board);
+        let _ = g.roll_with((2, 1))?;
+        // the extra back checker on the 23 point can be moved
+        g.play_die(Field::Point(22), 2)?;
+        g.play_die(Field::Point(23), 1)?;
+        assert_eq!(g.board.get_checkers(Player::Player0, Field::Point(22))?, 2);
+        assert_eq!(g.board.get_checkers(Player::Player0, Field::Point(20))?, 1);
+
+        let mut session = crate::Session::new().with_variant(Variant::Nackgammon);
+        assert_eq!(session.new_game()?.board, Variant::Nackgammon.start());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hypergammon() -> Result<(), Error> {
+        let mut g = Game::new().with_variant(Variant::Hypergammon);
+        assert_eq!(g.pip_count(Player::Player0), 24 + 23 + 22);
+        assert!(g.board.validate().is_ok());
+        g.who_plays = Player::Player0;
+        let _ = g.roll_with((2, 1))?;
+        assert!(g.legal_plays().len() > 1);
+
+        // the checkers off from the start do not save the gammon
+        g.board.points = [Point::EMPTY; 24];
+        g.board.points[23] = Point::new(Player::Player1, 3);
+        g.board.off[0] = 15;
+        let result = g.result().ok_or(Error::GameEnded)?;
+        assert_eq!(result.victory, Victory::Gammon);
+        g.board.points[23].count = 2;
+        g.board.off[1] = 13;
+        let result = g.result().ok_or(Error::GameEnded)?;
+        assert_eq!(result.victory, Victory::Single);
+        Ok(())
+    }
+
+    #[test]
+    fn test_starting_position() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.points[0] = Point::new(Player::Player0, 15);
+        let g = Game::new().with_variant(Variant::Plakoto);
+        assert!(g.clone().with_starting_position(board.clone()).is_err());
+        board.points[23] = Point::new(Player::Player1, 15);
+        let g = g.with_starting_position(board)?;
+        assert_eq!(g.board.occupancy, crate::rules::Occupancy::Pinning);
+        assert_eq!(g.record().start(), &g.board);
+
+        let mut g = Game::new();
+        let _ = g.roll_with((3, 1))?;
+        assert!(matches!(
+            g.with_starting_position(Board::new()),
+            Err(Error::GameStarted)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tavli() -> Result<(), Error> {
+        let mut g = Game::new().with_variant(Variant::Plakoto);
+        g.who_plays = Player::Player1;
+        g.board.set(Player::Player1, 23, -1)?;
+        g.board.set(Player::Player1, 6, 1)?;
+        // no cube in Tavli
+        assert!(!g.cube_is_live(Player::Player1));
+        assert!(matches!(
+            g.offer_double(Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        // landing on the single checker pins it instead of hitting it
+        g.who_plays = Player::Player0;
+        let _ = g.roll_with((6, 1))?;
+        g.play_die(Field::Point(23), 6)?;
+        assert!(g.board.is_pinned(Player::Player1, 6)?);
+        assert_eq!(g.board.get_checkers(Player::Player1, Field::Bar)?, 0);
+
+        // a backgammon only counts as a gammon
+        g.board.points = g.board.points.map(|p| match p.owner {
+            Player::Player0 => Point::EMPTY,
+            _ => p,
+        });
+        g.board.off[0] = 15;
+        g.board.pins = [0, 0];
+        let result = g.result().ok_or(Error::GameEnded)?;
+        assert_eq!(result.victory, Victory::Gammon);
+        assert_eq!(result.points, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plakoto_mother() -> Result<(), Error> {
+        let mut g = Game::new().with_variant(Variant::Plakoto);
+        g.board.set(Player::Player1, 23, -14)?;
+        g.board.set(Player::Player1, 5, 14)?;
+        g.board.set(Player::Player0, 23, -1)?;
+        g.board.set(Player::Player0, 2, 1)?;
+        g.who_plays = Player::Player0;
+        let _ = g.roll_with((2, 1))?;
+
+        // pinning the mother checker only ends the game at the end of the turn
+        g.play_die(Field::Point(2), 2)?;
+        assert!(g.board.is_pinned(Player::Player1, 23)?);
+        assert_eq!(g.phase, Phase::AwaitingMove);
+        assert_eq!(g.result(), None);
+        g.play_die(Field::Point(23), 1)?;
+        assert_eq!(g.phase, Phase::GameOver);
+        let result = g.result().ok_or(Error::GameEnded)?;
+        assert_eq!(result.winner, Player::Player0);
+        assert_eq!(result.victory, Victory::Gammon);
+        assert_eq!(result.points, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_narde() -> Result<(), Error> {
+        let mut g = Game::new().with_variant(Variant::Narde);
+        g.who_plays = Player::Player0;
+        let _ = g.roll_with((5, 2))?;
+        g.play_die(Field::Point(23), 5)?;
+        // a second checker may not leave the head
+        assert!(matches!(
+            g.play_die(Field::Point(23), 2),
+            Err(Error::MoveInvalidHead)
+        ));
+        assert_eq!(g.playable_dice(Field::Point(18)), vec![2]);
+        g.play_die(Field::Point(18), 2)?;
+        assert_eq!(g.who_plays, Player::Player1);
+
+        // two checkers leave the head with 6-6 in the first turn, the head of the opponent blocks
+        // the other sixes
+        let _ = g.roll_with((6, 6))?;
+        g.play_die(Field::Point(23), 6)?;
+        g.play_die(Field::Point(23), 6)?;
+        assert_eq!(g.who_plays, Player::Player0);
+        assert_eq!(g.board.get_checkers(Player::Player1, Field::Point(17))?, 2);
+
+        // but not later
+        let _ = g.roll_with((3, 3))?;
+        assert!(g.legal_moves().iter().all(|p| p
+            .moves
+            .iter()
+            .filter(|m| m.from == Field::Point(23))
+            .count()
+            <= 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (6, 5));
+        g.apply_move(&CheckerMove::new(Field::Point(23), vec![6, 5])?)?;
+        assert_eq!(g.board.get().points[23], Point::new(Player::Player0, 1));
+        assert_eq!(g.board.get().points[12], Point::new(Player::Player0, 6));
+        assert_eq!(g.who_plays, Player::Player1);
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_doubles() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player1, (2, 2));
+        g.apply_move(&CheckerMove::new(Field::Point(12), vec![2, 2])?)?;
+        assert_eq!(g.who_plays, Player::Player1);
+        g.apply_move(&CheckerMove::new(Field::Point(5), vec![2])?)?;
+        g.apply_move(&CheckerMove::new(Field::Point(5), vec![2])?)?;
+        assert_eq!(g.who_plays, Player::Player0);
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Point(5), vec![2])?)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_invalid() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (6, 5));
+        let before = g.clone();
+        // dice not rolled
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Point(7), vec![4])?)
+            .is_err());
+        // blocked field
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Point(5), vec![5])?)
+            .is_err());
+        // no checker
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Point(9), vec![6])?)
+            .is_err());
+        // not bearing off with exact dice
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Point(4), vec![6])?)
+            .is_err());
+        assert_eq!(g.board, before.board);
+        assert_eq!(g.dices, before.dices);
+
+        let mut g = Game::new();
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Point(7), vec![4])?)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_bar() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (6, 5));
+        g.board.set_bar(Player::Player0, 1)?;
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Point(12), vec![5])?)
+            .is_err());
+        // field 18 is blocked
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Bar, vec![6])?)
+            .is_err());
+        g.apply_move(&CheckerMove::new(Field::Bar, vec![5])?)?;
+        g.apply_move(&CheckerMove::new(Field::Point(12), vec![6])?)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_reasons() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (6, 5));
+        // field 0 holds checkers of player 1, field 10 is empty
+        for from in [Field::Point(0), Field::Point(10)] {
+            assert!(matches!(
+                g.apply_move(&CheckerMove::new(from, vec![6])?),
+                Err(Error::MoveInvalidChecker)
+            ));
+        }
+        assert!(matches!(
+            g.apply_move(&CheckerMove::new(Field::Point(12), vec![4])?),
+            Err(Error::MoveInvalidDice)
+        ));
+        assert!(matches!(
+            g.apply_move(&CheckerMove::new(Field::Point(12), vec![6, 6])?),
+            Err(Error::MoveInvalidDice)
+        ));
+        assert!(matches!(
+            g.apply_move(&CheckerMove::new(Field::Point(23), vec![5])?),
+            Err(Error::FieldBlocked)
+        ));
+        assert_eq!(g.board, Game::new().board);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_bar_two_checkers() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (4, 2));
+        g.board.set_bar(Player::Player0, 2)?;
+        // the second checker on the bar has to enter before the first one may move on
+        assert!(matches!(
+            g.apply_move(&CheckerMove::new(Field::Bar, vec![4, 2])?),
+            Err(Error::MoveInvalidBar)
+        ));
+        g.apply_move(&CheckerMove::new(Field::Bar, vec![4])?)?;
+        assert!(matches!(
+            g.apply_move(&CheckerMove::new(Field::Point(20), vec![2])?),
+            Err(Error::MoveInvalidBar)
+        ));
+        g.apply_move(&CheckerMove::new(Field::Bar, vec![2])?)?;
+        assert_eq!(g.board.get_checkers(Player::Player0, Field::Bar)?, 0);
+        assert_eq!(g.who_plays, Player::Player1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_bar_hit() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (4, 2));
+        g.board.set_bar(Player::Player0, 1)?;
+        g.board.set(Player::Player1, 3, 1)?;
+        g.apply_move(&CheckerMove::new(Field::Bar, vec![4])?)?;
+        assert_eq!(g.board.get_checkers(Player::Player1, Field::Bar)?, 1);
+        assert_eq!(g.board.get().points[20], Point::new(Player::Player0, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_hit() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (6, 1));
+        g.board.points = [Point::EMPTY; 24];
+        g.board.points[12] = Point::new(Player::Player0, 15);
+        g.board.points[23] = Point::new(Player::Player1, 13);
+        g.board.points[11] = Point::new(Player::Player1, 1);
+        g.board.points[5] = Point::new(Player::Player1, 1);
+        g.apply_move(&CheckerMove::new(Field::Point(12), vec![1, 6])?)?;
+        assert_eq!(g.moves.len(), 1);
+        assert_eq!(g.moves[0].to_string(), "13/12*/6*");
+        assert_eq!(g.board.get_checkers(Player::Player1, Field::Bar)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_redo() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (6, 1));
+        g.board.set(Player::Player1, 17, 1)?;
+        let start = g.clone();
+        assert!(matches!(g.undo(), Err(Error::UndoInvalid)));
+
+        g.apply_move(&"13/7*".parse()?)?;
+        g.apply_move(&"8/7".parse()?)?;
+        assert_eq!(g.who_plays, Player::Player1);
+        let end = g.clone();
+
+        g.undo()?;
+        assert_eq!(g.who_plays, Player::Player0);
+        assert_eq!(g.phase, Phase::AwaitingMove);
+        assert_eq!(g.moves.len(), 1);
+        g.undo()?;
+        // the hit checker is back on its field
+        assert_eq!(g.board, start.board);
+        assert_eq!(g.dices, start.dices);
+        assert!(g.moves.is_empty());
+        assert!(g.undo().is_err());
+
+        g.redo()?;
+        g.redo()?;
+        assert_eq!(g.board, end.board);
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+        assert!(matches!(g.redo(), Err(Error::RedoInvalid)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_new_move_clears_redo() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (3, 1));
+        g.apply_move(&"8/5".parse()?)?;
+        g.undo()?;
+        g.apply_move(&"6/5".parse()?)?;
+        assert!(g.redo().is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_record() -> Result<(), Error> {
+        let mut g = Game::new();
+        while g.who_plays == Player::Nobody {
+            let _ = g.roll()?;
+        }
+        assert_eq!(
+            g.record().last(),
+            Some(&Event::Roll {
+                player: g.who_plays,
+                dices: g.dices.values
+            })
+        );
+
+        let mut g = game_with_dices(Player::Player0, (3, 1));
+        g.apply_move(&CheckerMove::new(Field::Point(7), vec![3])?)?;
+        g.apply_move(&CheckerMove::new(Field::Point(5), vec![1])?)?;
+        let events = g.record().events().to_vec();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[1],
+            Event::Move {
+                player: Player::Player0,
+                mv: CheckerMove::new(Field::Point(5), vec![1])?
+            }
+        );
+
+        g.undo()?;
+        assert_eq!(g.record().events(), &events[..1]);
+        g.redo()?;
+        assert_eq!(g.record().events(), &events[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roll_with() -> Result<(), Error> {
+        let mut g = Game::new();
+        assert!(matches!(g.roll_with((0, 3)), Err(Error::DiceInvalid)));
+        assert!(matches!(g.roll_with((7, 3)), Err(Error::DiceInvalid)));
+        let _ = g.roll_with((2, 2))?;
+        assert_eq!(g.who_plays, Player::Nobody);
+        let _ = g.roll_with((3, 1))?;
+        assert_eq!(g.who_plays, Player::Player0);
+        assert_eq!(g.dices.available(), vec![3, 1]);
+        assert!(matches!(g.roll_with((3, 1)), Err(Error::MoveFirst)));
+        Ok(())
+    }
+
+    /// Rolls the given dices in turn
+    #[derive(Debug)]
+    struct Sequence(Vec<(u8, u8)>, usize);
+
+    impl DiceSource for Sequence {
+        fn roll(&mut self) -> (u8, u8) {
+            self.1 += 1;
+            self.0[(self.1 - 1) % self.0.len()]
+        }
+    }
+
+    #[test]
+    fn test_dice_source() -> Result<(), Error> {
+        let mut g = Game::new().with_dice_source(Sequence(vec![(2, 2), (1, 4), (6, 6)], 0));
+        let _ = g.roll()?;
+        assert_eq!(g.who_plays, Player::Nobody);
+        let _ = g.roll()?;
+        assert_eq!(g.who_plays, Player::Player1);
+        // clones share the source
+        let mut clone = g.clone();
+        clone.phase = Phase::AwaitingRoll;
+        let _ = clone.roll()?;
+        assert_eq!(clone.dices.values, (6, 6));
+        g.phase = Phase::AwaitingRoll;
+        let _ = g.roll()?;
+        assert_eq!(g.dices.values, (2, 2));
+
+        // invalid dices of a source are rejected
+        let mut g = Game::new().with_dice_source(Sequence(vec![(0, 7)], 0));
+        assert!(matches!(g.roll(), Err(Error::DiceInvalid)));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_roll_murphy() -> Result<(), Error> {
+        let mut g = Game::new().with_murphy(2);
+        let mut ties = 0;
+        while g.who_plays == Player::Nobody {
+            let _ = g.roll()?;
+            if g.who_plays == Player::Nobody {
+                ties += 1;
+            }
+        }
+        assert_eq!(g.cube.value(), 2u64.pow(ties.min(2)));
+        assert_eq!(g.cube.owner(), Player::Nobody);
+
+        // the limit of automatic doubles is respected
+        let mut g = Game::new().with_murphy(1);
+        g.murphy()?;
+        g.murphy()?;
+        assert_eq!(g.cube.value(), 2);
+        assert_eq!(
+            g.record().last(),
+            Some(&Event::AutomaticDouble { value: 2 })
+        );
+
+        // 0 means no limit
+        let mut g = Game::new().with_murphy(0);
+        for _ in 0..3 {
+            g.murphy()?;
+        }
+        assert_eq!(g.cube.value(), 8);
+
+        let mut g = Game::new();
+        g.murphy()?;
+        assert_eq!(g.cube.value(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_offer_double() -> Result<(), Error> {
+        let mut g = Game::new();
+        // no doubling before the opening roll
+        assert!(matches!(
+            g.offer_double(Player::Player0),
+            Err(Error::DoublingNotPermitted)
+        ));
+        g.who_plays = Player::Player0;
+        assert!(matches!(
+            g.offer_double(Player::Player1),
+            Err(Error::NotYourTurn)
+        ));
+        assert!(matches!(
+            g.offer_double(Player::Nobody),
+            Err(Error::PlayerInvalid)
+        ));
+
+        g.offer_double(Player::Player0)?;
+        assert_eq!(
+            g.record().last(),
+            Some(&Event::CubeOffer {
+                player: Player::Player0,
+                value: 2
+            })
+        );
+        assert!(matches!(g.roll(), Err(Error::CubeReceived)));
+        assert!(matches!(
+            g.offer_double(Player::Player0),
+            Err(Error::CubeReceived)
+        ));
+        assert!(matches!(g.take(Player::Player0), Err(Error::NotYourTurn)));
+        g.take(Player::Player1)?;
+        assert_eq!(g.cube.value(), 2);
+        assert_eq!(g.cube.owner(), Player::Player1);
+        assert_eq!(g.who_plays, Player::Player0);
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+
+        // the cube owned by the opponent can not be offered
+        assert!(matches!(
+            g.offer_double(Player::Player0),
+            Err(Error::DoublingNotPermitted)
+        ));
+        // but the owner may redouble
+        g.who_plays = Player::Player1;
+        g.offer_double(Player::Player1)?;
+        g.take(Player::Player0)?;
+        assert_eq!(g.cube.value(), 4);
+        assert_eq!(g.cube.owner(), Player::Player0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_offer_double_after_roll() {
+        let mut g = game_with_dices(Player::Player0, (3, 1));
+        assert!(matches!(
+            g.offer_double(Player::Player0),
+            Err(Error::MoveFirst)
+        ));
+        assert!(matches!(g.take(Player::Player1), Err(Error::MoveFirst)));
+        assert!(matches!(g.pass(Player::Player1), Err(Error::MoveFirst)));
+    }
+
+    #[test]
+    fn test_beaver_raccoon() -> Result<(), Error> {
+        let mut g = Game::new();
+        g.who_plays = Player::Player0;
+        g.offer_double(Player::Player0)?;
+        assert!(matches!(
+            g.beaver(Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        g.rules.beaver = true;
+        assert!(matches!(g.beaver(Player::Player0), Err(Error::NotYourTurn)));
+        g.beaver(Player::Player1)?;
+        assert_eq!(g.cube.value(), 4);
+        assert_eq!(g.cube.owner(), Player::Player1);
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+        assert!(matches!(
+            g.raccoon(Player::Player0),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        g.rules.raccoon = true;
+        assert!(matches!(
+            g.raccoon(Player::Player1),
+            Err(Error::NotYourTurn)
+        ));
+        g.raccoon(Player::Player0)?;
+        assert_eq!(g.cube.value(), 8);
+        assert_eq!(g.cube.owner(), Player::Player1);
+        assert_eq!(
+            g.record().last(),
+            Some(&Event::Raccoon {
+                player: Player::Player0,
+                value: 8
+            })
+        );
+        // only one raccoon
+        assert!(matches!(
+            g.raccoon(Player::Player0),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        g.undo()?;
+        assert_eq!(g.cube.value(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_offer_double_crawford() {
+        let mut g = Game::for_match(Rules::default(), true, 0, (1, 2));
+        assert!(g.is_crawford());
+        g.who_plays = Player::Player0;
+        assert!(matches!(
+            g.offer_double(Player::Player0),
+            Err(Error::DoublingNotPermitted)
+        ));
+    }
+
+    #[test]
+    fn test_offer_double_holland() -> Result<(), Error> {
+        let mut g = Game::for_match(Rules::default().with_holland(), false, 1, (2, 2));
+        g.who_plays = Player::Player0;
+        for player in [Player::Player0, Player::Player1, Player::Player0] {
+            g.record.push(Event::Roll {
+                player,
+                dices: (3, 1),
+            });
+        }
+        assert_eq!(g.rolls(Player::Player0), 2);
+        assert_eq!(g.rolls(Player::Player1), 1);
+        assert!(matches!(
+            g.offer_double(Player::Player0),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        g.record.push(Event::Roll {
+            player: Player::Player1,
+            dices: (3, 1),
+        });
+        g.offer_double(Player::Player0)?;
+
+        // before the Crawford game, the Holland rule does not apply
+        let mut g = Game::for_match(Rules::default().with_holland(), false, 0, (2, 2));
+        g.who_plays = Player::Player0;
+        g.offer_double(Player::Player0)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_cube_is_live() -> Result<(), Error> {
+        let mut g = Game::for_match(Rules::default(), false, 0, (1, 4));
+        assert!(!g.cube_is_live(Player::Player0));
+        assert!(g.cube_is_live(Player::Player1));
+        assert!(!g.cube_is_live(Player::Nobody));
+        g.who_plays = Player::Player0;
+        assert!(matches!(
+            g.offer_double(Player::Player0),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        g.cube.set(4)?;
+        assert!(!g.cube_is_live(Player::Player1));
+
+        // money games have no match score
+        let g = Game::new();
+        assert!(g.cube_is_live(Player::Player0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_post_crawford() -> Result<(), Error> {
+        let g = Game::for_match(Rules::default(), true, 0, (1, 4));
+        assert!(!g.is_post_crawford());
+        assert_eq!(g.trailer(), None);
+        assert!(!g.free_drop());
+
+        let mut g = Game::for_match(Rules::default(), false, 1, (4, 1));
+        assert!(g.is_post_crawford());
+        assert_eq!(g.trailer(), Some(Player::Player0));
+        assert!(!g.trailer_doubled());
+        assert!(g.free_drop());
+
+        g.who_plays = Player::Player0;
+        g.offer_double(Player::Player0)?;
+        assert!(g.trailer_doubled());
+        assert!(!g.free_drop());
+
+        let g = Game::for_match(Rules::default(), false, 2, (3, 1));
+        assert_eq!(g.trailer(), Some(Player::Player0));
+        assert!(!g.free_drop());
+
+        // double match point
+        let g = Game::for_match(Rules::default(), false, 2, (1, 1));
+        assert_eq!(g.trailer(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_beaver_match_play() -> Result<(), Error> {
+        let mut g = Game::for_match(Rules::default().with_beaver(), false, 0, (7, 7));
+        g.who_plays = Player::Player0;
+        g.offer_double(Player::Player0)?;
+        assert!(matches!(
+            g.beaver(Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pass() -> Result<(), Error> {
+        let mut g = Game::new();
+        g.who_plays = Player::Player1;
+        g.cube.set(2)?;
+        g.cube.set_owner(Player::Player1);
+        g.offer_double(Player::Player1)?;
+        assert!(matches!(g.pass(Player::Player1), Err(Error::NotYourTurn)));
+        g.pass(Player::Player0)?;
+        assert_eq!(g.phase, Phase::GameOver);
+        assert_eq!(
+            g.result(),
+            Some(GameResult {
+                winner: Player::Player1,
+                victory: Victory::Single,
+                points: 2
+            })
+        );
+
+        g.undo()?;
+        assert_eq!(g.phase, Phase::AwaitingCubeDecision);
+        assert_eq!(g.result(), None);
+        g.undo()?;
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+        assert_eq!(g.record().last(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_result_jacoby() -> Result<(), Error> {
+        let mut g = Game::new().with_jacoby();
+        g.board.points = g.board.points.map(|p| match p.owner {
+            Player::Player0 => Point::EMPTY,
+            _ => p,
+        });
+        g.board.off[0] = 15;
+        let result = g.result().ok_or(Error::GameEnded)?;
+        assert_eq!(result.victory, Victory::Backgammon);
+        assert_eq!(result.points, 1);
+
+        // the cube has been turned
+        g.who_plays = Player::Player1;
+        g.offer_double(Player::Player1)?;
+        g.take(Player::Player0)?;
+        assert_eq!(g.result().ok_or(Error::GameEnded)?.points, 6);
+
+        // not in match play
+        let mut g = Game::for_match(Rules::default().with_jacoby(), false, 0, (7, 7));
+        g.resign(Player::Player0, Victory::Gammon)?;
+        assert_eq!(g.result().ok_or(Error::GameEnded)?.points, 2);
+
+        // an automatic double turns the cube as well
+        let mut g = Game::new().with_murphy(1).with_jacoby();
+        g.murphy()?;
+        g.board.points = g.board.points.map(|_| Point::EMPTY);
+        g.board.set(Player::Player1, 5, 15)?;
+        g.board.off[0] = 15;
+        let result = g.result().ok_or(Error::GameEnded)?;
+        assert_eq!(result.victory, Victory::Gammon);
+        assert_eq!(result.points, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resign() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (3, 1));
+        g.cube.set(2)?;
+        assert!(g.resign(Player::Nobody, Victory::Single).is_err());
+        g.resign(Player::Player0, Victory::Backgammon)?;
+        assert_eq!(
+            g.result(),
+            Some(GameResult {
+                winner: Player::Player1,
+                victory: Victory::Backgammon,
+                points: 6
+            })
+        );
+        assert!(g.resign(Player::Player0, Victory::Single).is_err());
+        assert!(g.roll().is_err());
+        g.undo()?;
+        assert_eq!(g.result(), None);
+        assert_eq!(g.phase, Phase::AwaitingMove);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_perspective() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player1, (6, 5));
+        g.cube.set_owner(Player::Player0);
+        g.board.set(Player::Player1, 10, 1)?;
+
+        let view = g.from_perspective(Player::Player1);
+        assert_eq!(view.who_plays, Player::Player0);
+        assert_eq!(view.cube.owner(), Player::Player1);
+        assert_eq!(
+            view.board.get_checkers(Player::Player0, Field::Point(10))?,
+            1
+        );
+        assert_eq!(view.from_perspective(Player::Player1).board, g.board);
+
+        // moves generated from the flipped view are the same as for player 1
+        let moves = g.legal_moves();
+        assert_eq!(view.legal_moves(), moves);
+
+        assert_eq!(g.from_perspective(Player::Player0).board, g.board);
+        assert_eq!(
+            g.from_perspective(Player::Nobody).who_plays,
+            Player::Player1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() -> Result<(), Error> {
+        let mut g = Game::new();
+        g.validate()?;
+
+        g.phase = Phase::AwaitingMove;
+        assert!(matches!(
+            g.validate(),
+            Err(Error::PositionInvalid(PositionError::TurnInvalid))
+        ));
+
+        g.who_plays = Player::Player0;
+        assert!(matches!(
+            g.validate(),
+            Err(Error::PositionInvalid(PositionError::DicesInvalid))
+        ));
+
+        let mut g = game_with_dices(Player::Player0, (3, 1));
+        g.validate()?;
+        g.cube.set_owner(Player::Player1);
+        assert!(matches!(
+            g.validate(),
+            Err(Error::PositionInvalid(PositionError::CubeInvalid))
+        ));
+        g.cube.set(2)?;
+        g.validate()?;
+
+        g.board.set_off(Player::Player0, 1)?;
+        assert!(matches!(
+            g.validate(),
+            Err(Error::PositionInvalid(PositionError::CheckerCount(
+                Player::Player0,
+                16
+            )))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pip_count() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player1, (6, 5));
+        assert_eq!(g.pip_difference(), 0);
+        g.apply_move(&g.parse_play("24/13")?.moves[0])?;
+        assert_eq!(g.pip_count(Player::Player1), 156);
+        assert_eq!(g.pip_count(Player::Nobody), 0);
+        // it is player 0's turn now, trailing by 11 pips
+        assert_eq!(g.pip_difference(), -11);
+        g.who_plays = Player::Nobody;
+        assert_eq!(g.pip_difference(), -11);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bear_off() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (6, 2));
+        g.board.points = g.board.points.map(|p| match p.owner {
+            Player::Player0 => Point::EMPTY,
+            _ => p,
+        });
+        g.board.points[1] = Point::new(Player::Player0, 2);
+        g.board.points[3] = Point::new(Player::Player0, 3);
+        g.board.off[0] = 10;
+        assert_eq!(g.borne_off(Player::Player0)?, 10);
+
+        // the checkers on field 1 may only be borne off once field 3 is empty
+        assert!(g
+            .apply_move(&CheckerMove::new(Field::Point(1), vec![6])?)
+            .is_err());
+        g.apply_move(&CheckerMove::new(Field::Point(3), vec![6])?)?;
+        g.apply_move(&CheckerMove::new(Field::Point(1), vec![2])?)?;
+        assert_eq!(g.borne_off(Player::Player0)?, 12);
+        assert!(g.borne_off(Player::Nobody).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_play_overshoot() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (6, 5));
+        g.board.points = g.board.points.map(|p| match p.owner {
+            Player::Player0 => Point::EMPTY,
+            _ => p,
+        });
+        g.board.points[1] = Point::new(Player::Player0, 2);
+        g.board.off[0] = 13;
+        let play = g.parse_play("2/off(2)")?;
+        for m in play.moves.iter() {
+            g.apply_move(m)?;
+        }
+        assert_eq!(g.borne_off(Player::Player0)?, 15);
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_checker() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (3, 1));
+        let _ = g.move_checker(Player::Player0, 3, 7)?;
+        let _ = g.move_checker(Player::Player0, 1, 5)?;
+        assert_eq!(g.board.get().points[4], Point::new(Player::Player0, 2));
+        assert!(g.move_checker(Player::Player0, 1, 5).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_max_dice() -> Result<(), Error> {
+        // player 0 has checkers on fields 12 and 20, fields 1, 7, and 9 are blocked
+        let mut g = game_with_dices(Player::Player0, (5, 6));
+        g.board.points = [Point::EMPTY; 24];
+        g.board.points[12] = Point::new(Player::Player0, 1);
+        g.board.points[20] = Point::new(Player::Player0, 1);
+        for field in [1, 7, 9] {
+            g.board.points[field] = Point::new(Player::Player1, 2);
+        }
+
+        // the checker on field 20 could only play the 6 and would lose the 5
+        assert!(matches!(
+            g.apply_move(&CheckerMove::new(Field::Point(20), vec![6])?),
+            Err(Error::MoveInvalidMaxDice)
+        ));
+        g.apply_move(&CheckerMove::new(Field::Point(20), vec![5])?)?;
+        assert_eq!(g.legal_moves().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_move_larger_dice() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (5, 6));
+        g.board.points = [Point::EMPTY; 24];
+        g.board.points[12] = Point::new(Player::Player0, 1);
+        g.board.points[1] = Point::new(Player::Player1, 2);
+
+        assert!(matches!(
+            g.apply_move(&CheckerMove::new(Field::Point(12), vec![5])?),
+            Err(Error::MoveInvalidLargerDice)
+        ));
+        g.apply_move(&CheckerMove::new(Field::Point(12), vec![6])?)?;
+        // the 5 can not be played anymore, so it is the other player's turn
+        assert_eq!(g.who_plays, Player::Player1);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_roll_no_moves() -> Result<(), Error> {
+        let mut g = Game::new();
+        g.who_plays = Player::Player0;
+        g.phase = Phase::AwaitingRoll;
+        g.board.points = g.board.points.map(|p| match p.owner {
+            Player::Player1 => Point::EMPTY,
+            _ => p,
+        });
+        g.board.points[18..].fill(Point::new(Player::Player1, 2));
+        g.board.bar[0] = 2;
+        let _ = g.roll()?;
+        assert_eq!(g.who_plays, Player::Player1);
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+        Ok(())
+    }
+
+    #[test]
+    fn test_roll_opening_no_moves() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.points[5] = Point::new(Player::Player0, 14);
+        board.points[18..].fill(Point::new(Player::Player1, 2));
+        board.bar[0] = 1;
+        board.off[1] = 3;
+        let mut g = Game::new().with_starting_position(board)?;
+        // player 0 wins the opening roll but cannot enter, so player 1 rolls next
+        let _ = g.roll_with((3, 1))?;
+        assert_eq!(g.who_plays, Player::Player1);
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+        let _ = g.roll_with((6, 5))?;
+        assert_eq!(g.phase, Phase::AwaitingMove);
+        Ok(())
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        let g = game_with_dices(Player::Player0, (3, 1));
+        let moves = g.legal_moves();
+        assert!(!moves.is_empty());
+        assert!(moves.iter().all(|p| p.moves.len() == 2));
+        assert!(Game::new().legal_moves().is_empty());
+    }
+
+    #[test]
+    fn test_legal_plays() -> Result<(), Error> {
+        let g = game_with_dices(Player::Player0, (3, 1));
+        let plays = g.legal_plays();
+        // all resulting positions are distinct
+        for (i, (board, _)) in plays.iter().enumerate() {
+            assert!(plays[i + 1..].iter().all(|(b, _)| b != board));
+        }
+        // the classic opening play 8/5 6/5 is among them, found only once
+        let mut expected = g.board.clone();
+        expected.set(Player::Player0, 7, -1)?;
+        expected.set(Player::Player0, 5, -1)?;
+        expected.set(Player::Player0, 4, 2)?;
+        assert_eq!(plays.iter().filter(|(b, _)| *b == expected).count(), 1);
+
+        // every play of a double with four checkers appears only once
+        let g = game_with_dices(Player::Player0, (1, 1));
+        assert!(g.legal_plays().len() * 4 < g.legal_moves().len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_perft() -> Result<(), Error> {
+        // the opening rolls of both players, and all rolls of either player at the start
+        let mut g = Game::new();
+        assert_eq!(g.perft(1), 380);
+        g.who_plays = Player::Player0;
+        assert_eq!(g.perft(1), 447);
+        g.who_plays = Player::Player1;
+        assert_eq!(g.perft(1), 447);
+
+        // every roll bears off the last checker, which ends the game
+        g.board.points = [Point::EMPTY; 24];
+        g.board.set(Player::Player0, 0, 1)?;
+        g.board.set_off(Player::Player0, 14)?;
+        g.board.set(Player::Player1, 0, 1)?;
+        g.board.set_off(Player::Player1, 14)?;
+        g.who_plays = Player::Player0;
+        assert_eq!(g.perft(1), 21);
+        assert_eq!(g.perft(3), 21);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_best_move() -> Result<(), Error> {
+        use crate::eval::Heuristic;
+
+        let mut g = Game::new();
+        g.who_plays = Player::Player0;
+        // the game awaiting the roll is not changed
+        let (play, evaluation) = g.best_move((6, 5), &Heuristic)?;
+        assert_eq!(play.to_string(), "24/18 18/13");
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+
+        let g = game_with_dices(Player::Player0, (6, 5));
+        let ranked = g.ranked_moves(&Heuristic)?;
+        assert_eq!(ranked.len(), g.legal_plays().len());
+        assert!(ranked
+            .windows(2)
+            .all(|w| w[0].1.equity() >= w[1].1.equity()));
+        assert_eq!(g.best_move((5, 6), &Heuristic)?, (play, evaluation));
+        assert!(matches!(
+            g.best_move((6, 4), &Heuristic),
+            Err(Error::DiceInvalid)
+        ));
+        assert!(matches!(
+            Game::new().ranked_moves(&Heuristic),
+            Err(Error::MoveInvalid)
+        ));
+
+        // the opening move is taken from the book, for either player
+        for values in [(6, 4), (4, 6)] {
+            let mut g = Game::new();
+            let _ = g.roll_with(values)?;
+            let (play, _) = g.best_move(values, &Heuristic)?;
+            assert_eq!(play.to_string(), "24/18 13/9");
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hints() -> Result<(), Error> {
+        use crate::eval::Heuristic;
+        use crate::met::Met;
+
+        let g = game_with_dices(Player::Player0, (6, 5));
+        let hints = g.hints((6, 5), &Heuristic, usize::MAX)?;
+        assert_eq!(hints.len(), g.legal_plays().len());
+        assert_eq!(hints[0].play, g.best_move((6, 5), &Heuristic)?.0);
+        assert!(hints.windows(2).all(|w| w[0].loss <= w[1].loss));
+        for hint in &hints {
+            assert_eq!(hint.equity, hint.evaluation.equity());
+            assert!((hints[0].equity - hint.equity - hint.loss).abs() < 1e-12);
+        }
+        assert!(g.hints((6, 5), &Heuristic, 0)?.is_empty());
+        assert!(matches!(
+            g.hints((6, 4), &Heuristic, 3),
+            Err(Error::DiceInvalid)
+        ));
+
+        // the cube is decided before rolling
+        assert!(matches!(
+            g.cube_hint(&Heuristic, &Met::default()),
+            Err(Error::MoveFirst)
+        ));
+        assert!(matches!(
+            Game::new().cube_hint(&Heuristic, &Met::default()),
+            Err(Error::RollFirst)
+        ));
+        let mut g = Game::new();
+        g.who_plays = Player::Player1;
+        g.cube.set(2)?;
+        g.cube.set_owner(Player::Player0);
+        assert!(matches!(
+            g.cube_hint(&Heuristic, &Met::default()),
+            Err(Error::DoublingNotPermitted)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_play_die() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player0, (5, 5));
+        assert_eq!(g.remaining_dice(), vec![5, 5, 5, 5]);
+        assert_eq!(g.playable_dice(Field::Point(12)), vec![5]);
+        // fields 18 and 0 are blocked
+        assert!(g.playable_dice(Field::Point(23)).is_empty());
+        assert!(g.playable_dice(Field::Point(5)).is_empty());
+        assert!(g.play_die(Field::Point(5), 5).is_err());
+
+        g.play_die(Field::Point(12), 5)?;
+        g.play_die(Field::Point(12), 5)?;
+        assert_eq!(g.remaining_dice(), vec![5, 5]);
+        g.play_die(Field::Point(7), 5)?;
+        g.play_die(Field::Point(7), 5)?;
+        assert!(g.remaining_dice().is_empty());
+        assert_eq!(g.who_plays, Player::Player1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_play_die_max_dice() -> Result<(), Error> {
+        // player 0 has checkers on fields 12 and 20, fields 1, 7, and 9 are blocked
+        let mut g = game_with_dices(Player::Player0, (5, 6));
+        g.board.points = [Point::EMPTY; 24];
+        g.board.points[12] = Point::new(Player::Player0, 1);
+        g.board.points[20] = Point::new(Player::Player0, 1);
+        for field in [1, 7, 9] {
+            g.board.points[field] = Point::new(Player::Player1, 2);
+        }
+
+        assert_eq!(g.playable_dice(Field::Point(20)), vec![5]);
+        assert_eq!(g.playable_dice(Field::Point(12)), vec![6]);
+        assert!(matches!(
+            g.play_die(Field::Point(20), 6),
+            Err(Error::MoveInvalidMaxDice)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_play() -> Result<(), Error> {
+        let g = game_with_dices(Player::Player0, (6, 5));
+        let play = g.parse_play("24/13")?;
+        assert_eq!(play.moves[0].steps, vec![6, 5]);
+        let play = g.parse_play("24/18 13/8")?;
+        assert_eq!(play.moves[0].steps, vec![6]);
+        assert_eq!(play.moves[1].steps, vec![5]);
+        assert!(g.parse_play("24/18 13/7").is_err());
+        assert!(g.parse_play("24/20").is_err());
+
+        // the intermediate point 19 is blocked, so the 1 has to be played first
+        let g = game_with_dices(Player::Player0, (5, 1));
+        let play = g.parse_play("24/18")?;
+        assert_eq!(play.moves[0].steps, vec![1, 5]);
+        let g = game_with_dices(Player::Player0, (6, 4));
+        assert!(g.parse_play("23/13").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_play_doubles() -> Result<(), Error> {
+        let g = game_with_dices(Player::Player0, (3, 3));
+        let play = g.parse_play("8/5(2) 6/3(2)")?;
+        assert_eq!(play.moves.len(), 4);
+        assert!(g.parse_play("8/5(3) 6/3(2)").is_err());
+        let play = g.parse_play("24/15")?;
+        assert_eq!(play.moves[0].steps, vec![3, 3, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_play_bear_off() -> Result<(), Error> {
+        let g = game_with_dices(Player::Player0, (6, 4));
+        let play = g.parse_play("2/off 4/off")?;
+        assert_eq!(play.moves[0].steps, vec![6]);
+        assert_eq!(play.moves[1].steps, vec![4]);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_phase_sequence() -> Result<(), Error> {
+        let mut g = Game::new();
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+        assert!(matches!(
+            g.apply_move(&CheckerMove::new(Field::Point(7), vec![3])?),
+            Err(Error::RollFirst)
+        ));
+        while g.who_plays == Player::Nobody {
+            let _ = g.roll()?;
+        }
+        assert_eq!(g.phase, Phase::AwaitingMove);
+        assert!(matches!(g.roll(), Err(Error::MoveFirst)));
+
+        let play = g.legal_moves().remove(0);
+        for m in play.moves.iter() {
+            g.apply_move(m)?;
+        }
+        assert_eq!(g.phase, Phase::AwaitingRoll);
+
+        g.phase = Phase::AwaitingCubeDecision;
+        assert!(matches!(g.roll(), Err(Error::CubeReceived)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_game_over() -> Result<(), Error> {
+        let mut g = game_with_dices(Player::Player1, (6, 2));
+        g.board.points = [Point::EMPTY; 24];
+        g.board.points[12] = Point::new(Player::Player0, 15);
+        g.board.points[18] = Point::new(Player::Player1, 1);
+        g.board.points[23] = Point::new(Player::Player1, 1);
+        g.board.off[1] = 13;
+        g.apply_move(&CheckerMove::new(Field::Point(5), vec![6])?)?;
+        g.apply_move(&CheckerMove::new(Field::Point(0), vec![2])?)?;
+        assert_eq!(g.phase, Phase::GameOver);
+        assert_eq!(g.who_plays, Player::Player1);
+        assert!(matches!(g.roll(), Err(Error::GameEnded)));
+        assert!(g.legal_moves().is_empty());
+        Ok(())
+    }
+
+    /// Returns a game won by player 0, with the given checkers of player 1 left
+    fn won_game(board: [u8; 24], bar: u8, off: u8) -> Game {
+        let mut g = Game::new();
+        g.board.points = core::array::from_fn(|f| Point::new(Player::Player1, board[23 - f]));
+        g.board.bar = [0, bar];
+        g.board.off = [15, off];
+        g
+    }
+
+    #[test]
+    fn test_result_single() {
+        let mut board = [0; 24];
+        board[0] = 14;
+        let g = won_game(board, 0, 1);
+        assert_eq!(
+            g.result(),
+            Some(GameResult {
+                winner: Player::Player0,
+                victory: Victory::Single,
+                points: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_result_gammon() -> Result<(), Error> {
+        let mut board = [0; 24];
+        board[17] = 15;
+        let mut g = won_game(board, 0, 0);
+        g.cube.set(4)?;
+        let result = g.result().ok_or(Error::GameStarted)?;
+        assert_eq!(result.victory, Victory::Gammon);
+        assert_eq!(result.points, 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_result_backgammon() {
+        let mut board = [0; 24];
+        board[18] = 15;
+        let g = won_game(board, 0, 0);
+        assert_eq!(g.result().map(|r| r.victory), Some(Victory::Backgammon));
+
+        let mut board = [0; 24];
+        board[0] = 14;
+        let g = won_game(board, 1, 0);
+        assert_eq!(g.result().map(|r| r.victory), Some(Victory::Backgammon));
+    }
+
+    #[test]
+    fn test_result_player1() {
+        let mut g = Game::new();
+        g.board.points = g.board.points.map(|p| match p.owner {
+            Player::Player1 => Point::EMPTY,
+            _ => p,
+        });
+        g.board.off = [2, 15];
+        assert_eq!(g.result().map(|r| r.winner), Some(Player::Player1));
+        assert_eq!(g.result().map(|r| r.points), Some(1));
+    }
+
+    #[test]
+    fn test_victory_display() {
+        assert_eq!(Victory::Single.to_string(), "Single game");
+        assert_eq!(Victory::Gammon.to_string(), "Gammon");
+        assert_eq!(Victory::Backgammon.to_string(), "Backgammon");
+    }
+
+    #[test]
+    fn test_phase_display() {
+        assert_eq!(Phase::AwaitingRoll.to_string(), "Awaiting roll");
         assert_eq!(
-            format!("{}", g),
-            "Rules: Points: 7, Beaver: false, Raccoon: false, Murphy: false, Murphy Limit: 0, Jacoby: false, Crawford: true, Holland: false\nDices: Dices { values: (0, 0), consumed: (false, false, false, false) }\nCube: 1\nCube owner: Nobody\nWho plays: Nobody\nBoard: BoardDisplay { board: [-2, 0, 0, 0, 0, 5, 0, 3, 0, 0, 0, -5, 5, 0, 0, 0, -3, 0, -5, 0, 0, 0, 0, 2], bar: (0, 0), off: (0, 0) }\nCrawford game: false\nSince Crawford game: 0\n"
+            Phase::AwaitingCubeDecision.to_string(),
+            "Awaiting cube decision"
         );
+        assert_eq!(Phase::AwaitingMove.to_string(), "Awaiting move");
+        assert_eq!(Phase::GameOver.to_string(), "Game over");
     }
 
     // Test GameRules trait for Game
@@ -0,0 +1,412 @@
+use super::{share, Game, SharedDiceSource};
+use crate::record::GameRecord;
+use crate::rules::{Board, Cube, DiceSource, Field, GameRules, Player, Rules, Variant};
+use crate::{Error, PositionError};
+use core::fmt;
+
+/// Builds a game from an arbitrary position, e.g. to analyze a position in the middle of a game.
+///
+/// Without any settings, the builder creates the same game as [`Game::new`]. The settings are
+/// checked by [`build`](GameBuilder::build): the board has to hold a valid position of the
+/// variant, the cube has to be a power of 2 with no owner while centered, a player has to be on
+/// roll for dices rolled or a cube offered, and the score has to allow the Crawford game.
+/// ```
+/// use backgammon::{Game, Phase};
+/// use backgammon::rules::{Board, Player, Point};
+///
+/// let mut board = Board::new();
+/// board.points = [Point::EMPTY; 24];
+/// board.points[..6].fill(Point::new(Player::Player0, 2));
+/// board.points[18..].fill(Point::new(Player::Player1, 2));
+/// board.off = [3, 3];
+/// let game = Game::builder()
+///     .with_board(board.clone())
+///     .with_cube(2, Player::Player1)
+///     .with_player(Player::Player0)
+///     .with_dices((6, 5))
+///     .with_away((3, 2))
+///     .build()
+///     .unwrap();
+/// assert_eq!(game.board, board);
+/// assert_eq!(game.phase, Phase::AwaitingMove);
+/// assert_eq!(game.away(), Some((3, 2)));
+///
+/// // player 0 owns the cube, so player 1 cannot double
+/// assert!(Game::builder()
+///     .with_cube(2, Player::Player0)
+///     .with_player(Player::Player1)
+///     .with_cube_offered()
+///     .build()
+///     .is_err());
+/// ```
+#[derive(Clone)]
+pub struct GameBuilder {
+    /// rules of the game
+    rules: Rules,
+    /// position, the starting position of the variant if none
+    board: Option<Board>,
+    /// value and owner of the cube
+    cube: (u64, Player),
+    /// player on roll, nobody before the opening roll
+    who_plays: Player,
+    /// dices rolled by the player on roll
+    dices: Option<(u8, u8)>,
+    /// whether the player on roll has offered the cube
+    cube_offered: bool,
+    /// points player 0 and player 1 still need to win the match, if played as part of a match
+    away: Option<(u64, u64)>,
+    /// whether the game is the Crawford game
+    crawford: bool,
+    /// whether the game is played after the Crawford game
+    post_crawford: bool,
+    /// source of the dices rolled, and its seed
+    dice_source: Option<(SharedDiceSource, Option<u64>)>,
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        GameBuilder {
+            rules: Rules::default(),
+            board: None,
+            cube: (1, Player::Nobody),
+            who_plays: Player::Nobody,
+            dices: None,
+            cube_offered: false,
+            away: None,
+            crawford: false,
+            post_crawford: false,
+            dice_source: None,
+        }
+    }
+}
+
+// the dice source is not Debug
+impl fmt::Debug for GameBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GameBuilder")
+            .field("rules", &self.rules)
+            .field("board", &self.board)
+            .field("cube", &self.cube)
+            .field("who_plays", &self.who_plays)
+            .field("dices", &self.dices)
+            .field("cube_offered", &self.cube_offered)
+            .field("away", &self.away)
+            .field("crawford", &self.crawford)
+            .field("post_crawford", &self.post_crawford)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Game {
+    /// Returns a builder to set up a game from an arbitrary position, see [`GameBuilder`]
+    pub fn builder() -> GameBuilder {
+        GameBuilder::new()
+    }
+}
+
+impl GameBuilder {
+    /// Create a builder of the default game
+    pub fn new() -> Self {
+        GameBuilder::default()
+    }
+
+    /// Play by the given rules
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Start from the given position instead of the starting position of the variant. The fields
+    /// occupied by the opponent follow the rules of the variant.
+    pub fn with_board(mut self, board: Board) -> Self {
+        self.board = Some(board);
+        self
+    }
+
+    /// Set the value and the owner of the cube, nobody for a centered cube
+    pub fn with_cube(mut self, value: u64, owner: Player) -> Self {
+        self.cube = (value, owner);
+        self
+    }
+
+    /// Set the player on roll
+    pub fn with_player(mut self, player: Player) -> Self {
+        self.who_plays = player;
+        self
+    }
+
+    /// Set the dices the player on roll has rolled, so that the game awaits the move. Without a
+    /// player on roll, the dices are the opening roll.
+    pub fn with_dices(mut self, dices: (u8, u8)) -> Self {
+        self.dices = Some(dices);
+        self
+    }
+
+    /// Let the player on roll offer the cube, so that the game awaits the cube decision of the
+    /// opponent
+    pub fn with_cube_offered(mut self) -> Self {
+        self.cube_offered = true;
+        self
+    }
+
+    /// Play the game as part of a match, in which player 0 and player 1 still need the given
+    /// points to win
+    pub fn with_away(mut self, away: (u64, u64)) -> Self {
+        self.away = Some(away);
+        self
+    }
+
+    /// Play the Crawford game of a match, in which doubling is not allowed
+    pub fn with_crawford(mut self) -> Self {
+        self.crawford = true;
+        self
+    }
+
+    /// Play a game of a match after the Crawford game
+    pub fn with_post_crawford(mut self) -> Self {
+        self.post_crawford = true;
+        self
+    }
+
+    /// Roll the dices from the given source, see [`Game::with_dice_source`]
+    pub fn with_dice_source(mut self, source: impl DiceSource + 'static) -> Self {
+        let seed = source.seed();
+        self.dice_source = Some((share(source), seed));
+        self
+    }
+
+    /// Check the settings and create the game
+    pub fn build(self) -> Result<Game, Error> {
+        let mut game = Game {
+            rules: self.rules,
+            ..Default::default()
+        };
+        if let Some(away) = self.away {
+            let leader = away.0 == 1 || away.1 == 1;
+            if away.0 == 0 || away.1 == 0 || (self.crawford || self.post_crawford) && !leader {
+                return Err(Error::ScoreInvalid);
+            }
+            game.match_play = true;
+            game.away = away;
+        } else if self.crawford || self.post_crawford {
+            return Err(Error::ScoreInvalid);
+        }
+        if self.crawford && self.post_crawford {
+            return Err(Error::ScoreInvalid);
+        }
+        game.crawford = self.crawford;
+        game.since_crawford = u8::from(self.post_crawford);
+
+        let mut board = self.board.unwrap_or_else(|| game.rules.variant.start());
+        board.occupancy = game.rules.variant.occupancy();
+        board.validate()?;
+        for player in [Player::Player0, Player::Player1] {
+            if board.get_checkers(player, Field::Off)? == 15 {
+                return Err(Error::GameEnded);
+            }
+        }
+        game.board = board;
+        game.record = GameRecord::new(game.board.clone());
+        if let Some((source, seed)) = self.dice_source {
+            game.record.set_seed(seed);
+            game.dice_source = Some(source);
+        }
+
+        let (value, owner) = self.cube;
+        let mut cube = Cube::default();
+        cube.set(value)?;
+        cube.set_owner(owner);
+        game.cube = cube;
+        game.who_plays = self.who_plays;
+        game.validate()?;
+
+        // replay the actions so that the game checks and records them
+        match (self.dices, self.cube_offered) {
+            (Some(_), true) => return Err(Error::MoveFirst),
+            (Some(dices), false) => {
+                let _ = game.roll_with(dices)?;
+            }
+            (None, true) if self.who_plays == Player::Nobody => {
+                return Err(Error::PositionInvalid(PositionError::TurnInvalid));
+            }
+            (None, true) => {
+                game.offer_double(self.who_plays)?;
+                game.undo_stack.clear();
+            }
+            (None, false) => {}
+        }
+        Ok(game)
+    }
+}
+
+/// Implements the rules for the builder
+impl GameRules for GameBuilder {
+    fn with_beaver(mut self) -> Self {
+        self.rules.beaver = true;
+        self
+    }
+
+    fn with_raccoon(mut self) -> Self {
+        self.rules.raccoon = true;
+        self
+    }
+
+    fn with_murphy(mut self, limit: u8) -> Self {
+        self.rules.murphy = true;
+        self.rules.murphy_limit = limit;
+        self
+    }
+
+    fn with_jacoby(mut self) -> Self {
+        self.rules.jacoby = true;
+        self
+    }
+
+    fn with_holland(mut self) -> Self {
+        self.rules.holland = true;
+        self
+    }
+
+    fn with_variant(mut self, variant: Variant) -> Self {
+        self.rules.variant = variant;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Point, Roll, ScriptedDice};
+    use crate::Phase;
+
+    #[test]
+    fn test_default() -> Result<(), Error> {
+        let game = Game::builder().build()?;
+        assert_eq!(game.board, Game::new().board);
+        assert_eq!(game.who_plays, Player::Nobody);
+        assert_eq!(game.phase, Phase::AwaitingRoll);
+        assert_eq!(game.away(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dices() -> Result<(), Error> {
+        let game = Game::builder()
+            .with_player(Player::Player1)
+            .with_dices((3, 1))
+            .build()?;
+        assert_eq!(game.who_plays, Player::Player1);
+        assert_eq!(game.phase, Phase::AwaitingMove);
+        assert_eq!(game.dices.values, (3, 1));
+
+        // the opening roll decides who plays
+        let game = Game::builder().with_dices((2, 5)).build()?;
+        assert_eq!(game.who_plays, Player::Player1);
+
+        let invalid = Game::builder()
+            .with_player(Player::Player0)
+            .with_dices((0, 7));
+        assert!(matches!(invalid.build(), Err(Error::DiceInvalid)));
+        let invalid = Game::builder()
+            .with_player(Player::Player0)
+            .with_dices((3, 1))
+            .with_cube_offered();
+        assert!(matches!(invalid.build(), Err(Error::MoveFirst)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cube() -> Result<(), Error> {
+        let mut game = Game::builder()
+            .with_cube(4, Player::Player1)
+            .with_player(Player::Player1)
+            .with_cube_offered()
+            .build()?;
+        assert_eq!(game.phase, Phase::AwaitingCubeDecision);
+        assert!(matches!(game.undo(), Err(Error::UndoInvalid)));
+        game.take(Player::Player0)?;
+        assert_eq!(game.cube.value(), 8);
+        assert_eq!(game.cube.owner(), Player::Player0);
+
+        let invalid = Game::builder().with_cube(3, Player::Nobody);
+        assert!(matches!(invalid.build(), Err(Error::CubeValueInvalid)));
+        let invalid = Game::builder().with_cube(1, Player::Player0);
+        assert!(invalid.build().is_err());
+        let invalid = Game::builder().with_cube_offered();
+        assert!(matches!(
+            invalid.build(),
+            Err(Error::PositionInvalid(PositionError::TurnInvalid))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_board() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.bar[1] = 1;
+        board.points[18].count = 4;
+        let game = Game::builder()
+            .with_board(board.clone())
+            .with_player(Player::Player0)
+            .build()?;
+        assert_eq!(game.board, board);
+        assert_eq!(game.record().start(), &board);
+
+        board.bar[1] = 2;
+        assert!(Game::builder().with_board(board).build().is_err());
+
+        let mut board = Board::new();
+        board.points = board.points.map(|p| match p.owner {
+            Player::Player0 => Point::EMPTY,
+            _ => p,
+        });
+        board.off[0] = 15;
+        let ended = Game::builder().with_board(board);
+        assert!(matches!(ended.build(), Err(Error::GameEnded)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_score() -> Result<(), Error> {
+        let mut game = Game::builder()
+            .with_away((1, 4))
+            .with_crawford()
+            .with_player(Player::Player1)
+            .build()?;
+        assert!(game.is_crawford());
+        assert!(matches!(
+            game.offer_double(Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+
+        let game = Game::builder()
+            .with_away((1, 4))
+            .with_post_crawford()
+            .build()?;
+        assert!(game.is_post_crawford());
+        assert_eq!(game.trailer(), Some(Player::Player1));
+
+        let invalid = Game::builder().with_away((2, 4)).with_crawford();
+        assert!(matches!(invalid.build(), Err(Error::ScoreInvalid)));
+        let invalid = Game::builder().with_crawford();
+        assert!(matches!(invalid.build(), Err(Error::ScoreInvalid)));
+        let invalid = Game::builder().with_away((0, 4));
+        assert!(matches!(invalid.build(), Err(Error::ScoreInvalid)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules() -> Result<(), Error> {
+        let mut game = Game::builder()
+            .with_variant(Variant::Nackgammon)
+            .with_beaver()
+            .with_player(Player::Player0)
+            .with_dice_source(ScriptedDice::new(vec![(4, 4)])?)
+            .build()?;
+        assert_eq!(game.board, Variant::Nackgammon.start());
+        assert!(game.rules.beaver);
+        let _ = game.roll()?;
+        assert_eq!(game.dices.values, (4, 4));
+        Ok(())
+    }
+}
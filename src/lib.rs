@@ -57,6 +57,48 @@
 //! // roll dices
 //! let g = g.roll();
 //! ```
+//! ## Features
+//! * `std` (enabled by default): links the standard library, which random dices, the evaluation
+//!   of positions, and all modules doing I/O rely on. Without it, the rules engine, i.e. the
+//!   board, the rules, the generation of moves, games, and matches, builds with `no_std` and
+//!   `alloc` only, e.g. for embedded targets; the dices then have to be rolled from a seed, a
+//!   random number generator, or given values.
+//! * `serde` (enabled by default): implements `Serialize` and `Deserialize` of
+//!   [serde](https://serde.rs) for all public types, e.g. to store games and matches as JSON.
+//! * `parallel`: spreads the trials of rollouts across all cores with
+//!   [rayon](https://docs.rs/rayon).
+//! * `term`: renders games for terminals with Unicode box drawing characters and ANSI colors,
+//!   highlighting the fields a selected checker can be moved to, see the `term` module.
+//! * `tui`: plays games in the terminal on the interactive board drawn with ratatui and
+//!   crossterm, see the `tui` module.
+//! * `cli`: builds the `backgammon` binary to play a match in the terminal, against another
+//!   human or against a bot, e.g. `cargo run --features cli -- --points 5 --bot`.
+//! * `server`: hosts matches over WebSocket with tungstenite, the engine validating every action,
+//!   see the `server` module.
+//! * `tokio`: runs game sessions awaiting remote players as tasks of the
+//!   [tokio](https://tokio.rs) runtime, with a time to act, see the `live` module.
+//! * `sqlite`: archives matches in SQLite databases, see the `store` module.
+//! * `ffi`: exports a C interface declared in `include/backgammon.h`, see the `ffi` module.
+//! * `wasm`: exports games to JavaScript through `wasm-bindgen`, see the `wasm` module.
+//!
+//! ## WebAssembly
+//! With the `wasm` feature, the crate exports games to JavaScript, see the `wasm` module. The
+//! feature enables the `js` feature of `getrandom`, so the dices are rolled from
+//! `crypto.getRandomValues` on `wasm32-unknown-unknown`. Without it, either enable that feature
+//! in the depending crate, or disable the `std` feature and roll the dices from a seed or from the
+//! frontend:
+//! ```
+//! use backgammon::Game;
+//! use backgammon::rules::{Roll, SeededDice};
+//!
+//! let mut g = Game::new().with_dice_source(SeededDice::new(7));
+//! g.roll().unwrap();
+//!
+//! // e.g. dices rolled by a JavaScript frontend
+//! let mut g = Game::new();
+//! g.roll_with((3, 1)).unwrap();
+//! ```
+//!
 //! ## Design Philosophy
 //! This library is designed to offer completely stateless game functions. This means that it
 //! should be easy to implement wrappers using this library in combination with some databases to
@@ -71,6 +113,7 @@
 //! IRC: [#backgammon @ libera.chat](ircs://irc.libera.chat/#backgammon) (via
 //! [webchat](https://web.libera.chat/#backgammon))
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(future_incompatible)]
 #![deny(
     rustdoc::broken_intra_doc_links,
@@ -93,14 +136,165 @@
     variant_size_differences
 )] // be tough on code quality
 
+extern crate alloc;
+
+/// Implements the clocks of timed matches
+mod clock;
+pub use clock::{Clock, TimeControl};
 /// Implements all possible Backgammon errors
 mod error;
-pub use error::Error;
+pub use error::{Error, ErrorKind, PositionError};
 /// Implements a Backgammon game
 mod game;
-pub use game::Game;
+pub use game::{Game, GameBuilder, GameResult, Phase, Victory};
+/// Implements the record of a Backgammon game
+mod record;
+pub use record::{Event, GameObserver, GameRecord, Position, Replay};
 /// Implements a Backgammon match
 mod r#match;
 pub use r#match::Match;
+/// Implements a session of Backgammon money games
+mod session;
+pub use session::Session;
+/// Implements a chouette of Backgammon money games among three or more players
+mod chouette;
+pub use chouette::{Chouette, ChouetteGame, Member};
+#[cfg(feature = "std")]
+/// Implements tactical analysis of positions
+pub mod analysis;
+#[cfg(feature = "std")]
+/// Implements databases of exact bearoff probabilities
+pub mod bearoff;
+/// Implements the opening book
+pub mod book;
+#[cfg(feature = "std")]
+/// Implements a cache of evaluations of positions
+pub mod cache;
+#[cfg(feature = "std")]
+/// Implements cubeful equities and cube decisions
+pub mod cubeful;
+#[cfg(feature = "std")]
+/// Implements a text protocol to run engines as separate processes
+pub mod engine;
+#[cfg(feature = "std")]
+/// Implements the evaluation of positions and the ranking of plays
+pub mod eval;
+#[cfg(feature = "std")]
+/// Implements the external player interface of GNU Backgammon
+pub mod external;
+#[cfg(feature = "std")]
+/// Implements verifiable fair dices for networked games
+pub mod fair;
+#[cfg(feature = "ffi")]
+/// Exports a C interface to embed the engine in other languages
+pub mod ffi;
+#[cfg(feature = "std")]
+/// Implements the client protocol of the First Internet Backgammon Server
+pub mod fibs;
+/// Implements the exchange of positions and matches with other Backgammon software
+pub mod formats;
+#[cfg(feature = "std")]
+/// Implements asynchronous game sessions awaiting the actions of remote players
+pub mod live;
+#[cfg(feature = "std")]
+/// Implements the match equity tables
+pub mod met;
+#[cfg(feature = "std")]
+/// Implements the neural networks of GNU Backgammon
+pub mod neuralnet;
+/// Implements a library of reference positions
+pub mod positions;
+#[cfg(feature = "std")]
+/// Implements the ratings of players across matches
+pub mod rating;
+#[cfg(feature = "std")]
+/// Implements Monte Carlo rollouts of positions
+pub mod rollout;
 /// Implements the board, the dices, the cube, and all other Backgammon rules
 pub mod rules;
+#[cfg(feature = "server")]
+/// Implements a server hosting matches over WebSocket
+pub mod server;
+#[cfg(feature = "std")]
+/// Implements archives of played matches
+pub mod store;
+#[cfg(feature = "term")]
+/// Implements the rendering of games for terminals with Unicode and colors
+pub mod term;
+/// Implements knockout and Swiss tournaments
+pub mod tournament;
+#[cfg(feature = "std")]
+/// Implements an interactive board for terminal frontends
+pub mod tui;
+#[cfg(feature = "wasm")]
+/// Exports games to JavaScript through wasm-bindgen
+pub mod wasm;
+/// Implements Zobrist hashing of positions
+pub mod zobrist;
+
+#[cfg(all(test, feature = "std", feature = "serde"))]
+mod tests {
+    use serde::{de::DeserializeOwned, Serialize};
+
+    fn serde<T: Serialize + DeserializeOwned>() {}
+
+    #[test]
+    fn test_serde() {
+        serde::<crate::Chouette>();
+        serde::<crate::ChouetteGame>();
+        serde::<crate::Clock>();
+        serde::<crate::Error>();
+        serde::<crate::ErrorKind>();
+        serde::<crate::Game>();
+        serde::<crate::GameRecord>();
+        serde::<crate::Match>();
+        serde::<crate::Member>();
+        serde::<crate::Session>();
+        serde::<crate::TimeControl>();
+        serde::<crate::rules::AbsolutePoint>();
+        serde::<crate::rules::RelativePoint>();
+        serde::<crate::rules::Rules>();
+        serde::<crate::rules::Player>();
+        serde::<crate::rules::Play>();
+        serde::<crate::rules::Occupancy>();
+        serde::<crate::rules::Point>();
+        serde::<crate::rules::Variant>();
+        serde::<crate::analysis::ErrorRate>();
+        serde::<crate::analysis::ErrorRates>();
+        serde::<crate::analysis::Exposure>();
+        serde::<crate::analysis::Luck>();
+        serde::<crate::analysis::MatchErrorRates>();
+        serde::<crate::analysis::RollLuck>();
+        serde::<crate::analysis::Shots>();
+        serde::<crate::cache::CacheStats>();
+        serde::<crate::cubeful::CubeContext>();
+        serde::<crate::cubeful::CubeDecision>();
+        serde::<crate::cubeful::TakePoints>();
+        serde::<crate::eval::Evaluation>();
+        serde::<crate::eval::Hint>();
+        serde::<crate::eval::PositionClass>();
+        serde::<crate::fair::Commitment>();
+        serde::<crate::fair::Secret>();
+        serde::<crate::fibs::Command>();
+        serde::<crate::fibs::Message>();
+        serde::<crate::formats::Mat>();
+        serde::<crate::formats::PositionId>();
+        serde::<crate::formats::Sgf>();
+        serde::<crate::formats::Transcript>();
+        serde::<crate::formats::XgMatch>();
+        serde::<crate::live::Action>();
+        serde::<crate::met::Met>();
+        serde::<crate::neuralnet::Gnubg>();
+        serde::<crate::neuralnet::NeuralNet>();
+        serde::<crate::positions::Theme>();
+        serde::<crate::rating::Rating>();
+        serde::<crate::rollout::Rollout>();
+        serde::<crate::rollout::RolloutSettings>();
+        serde::<crate::store::Entry>();
+        serde::<crate::store::Query>();
+        serde::<crate::tournament::Knockout>();
+        serde::<crate::tournament::Pairing>();
+        serde::<crate::tournament::Standing>();
+        serde::<crate::tournament::Swiss>();
+    }
+}
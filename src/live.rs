@@ -0,0 +1,522 @@
+//! # Asynchronous Game Sessions
+//!
+//! A [`GameSession`](crate::live::GameSession) runs a game between two players who act from
+//! elsewhere, e.g. over a network connection handled by an async server. Each player gets a
+//! [`Seat`](crate::live::Seat) to send its [`Action`](crate::live::Action)s, and the session awaits
+//! them one after the other, lets the engine validate them, and reports each of them as an
+//! [`Update`](crate::live::Update). Actions of the player whose turn it is not, or which the rules
+//! reject, are reported and leave the game unchanged.
+//!
+//! The session does not depend on an async runtime. A timeout is any future which completes once
+//! the player has run out of time, e.g. `tokio::time::sleep(Duration::from_secs(30))`.
+//! ```
+//! use backgammon::Game;
+//! use backgammon::live::{Action, GameSession, Update};
+//! use backgammon::rules::{Player, ScriptedDice};
+//! use std::future::Future;
+//! use std::pin::pin;
+//! use std::task::{Context, Poll, Waker};
+//!
+//! let game = Game::new().with_dice_source(ScriptedDice::new(vec![(3, 1)]).unwrap());
+//! let (mut session, (x, o)) = GameSession::new(game);
+//! x.send(Action::Roll);
+//! o.send("8/5 6/5".parse().unwrap());
+//! x.send("8/5 6/5".parse().unwrap());
+//!
+//! // an executor would await the updates, the actions are queued already here
+//! let mut cx = Context::from_waker(Waker::noop());
+//! let mut updates = Vec::new();
+//! for _ in 0..3 {
+//!     if let Poll::Ready(update) = pin!(session.next()).poll(&mut cx) {
+//!         updates.push(update.unwrap());
+//!     }
+//! }
+//! assert!(matches!(updates[0], Update::Rolled(Player::Player0, (3, 1))));
+//! assert!(matches!(updates[1], Update::Moved(Player::Player0, _)));
+//! // player 1 has not rolled yet
+//! assert!(matches!(updates[2], Update::Rejected(Player::Player1, _)));
+//! assert_eq!(session.game.who_plays, Player::Player1);
+//! ```
+//!
+//! With the `tokio` feature, `GameSession::next_within` gives the player a time to act, and
+//! `GameSession::spawn` runs the session as a task of the tokio runtime, e.g. one per table of a
+//! server, sending the updates through a channel:
+//! ```
+//! # #[cfg(feature = "tokio")]
+//! # tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(async {
+//! use backgammon::Game;
+//! use backgammon::live::{Action, GameSession, Update};
+//! use std::time::Duration;
+//!
+//! let (session, (x, o)) = GameSession::new(Game::new());
+//! let (task, mut updates) = session.spawn(Duration::from_secs(30));
+//! x.send(Action::Roll);
+//! assert!(matches!(updates.recv().await, Some(Update::Rolled(..))));
+//! o.send("resign single".parse().unwrap());
+//! assert!(matches!(updates.recv().await, Some(Update::Resigned(..))));
+//! assert!(task.await.unwrap().result().is_some());
+//! # });
+//! ```
+use crate::game::{Game, GameResult, Phase, Victory};
+use crate::rules::{Play, Player, Roll};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::{self, Future};
+use std::pin::pin;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+#[cfg(feature = "tokio")]
+use std::time::Duration;
+#[cfg(feature = "tokio")]
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+#[cfg(feature = "tokio")]
+use tokio::task::JoinHandle;
+
+/// Represents an action of a player
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Action {
+    /// Roll the dices, including the opening roll, which either player may roll
+    Roll,
+    /// Offer the cube
+    Double,
+    /// Take the cube offered
+    Take,
+    /// Pass the cube offered, which ends the game
+    Pass,
+    /// Play the dices rolled
+    Move(Play),
+    /// Resign the game, at any time
+    Resign(Victory),
+}
+
+// implement Display trait
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Roll => write!(f, "roll"),
+            Action::Double => write!(f, "double"),
+            Action::Take => write!(f, "take"),
+            Action::Pass => write!(f, "pass"),
+            Action::Move(play) => write!(f, "{}", play),
+            Action::Resign(Victory::Single) => write!(f, "resign single"),
+            Action::Resign(Victory::Gammon) => write!(f, "resign gammon"),
+            Action::Resign(Victory::Backgammon) => write!(f, "resign backgammon"),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = Error;
+
+    /// Parse an action as sent by a client, e.g. "roll", "double", "take", "pass",
+    /// "resign gammon", or a play like "24/18 13/11".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "roll" => Ok(Action::Roll),
+            "double" => Ok(Action::Double),
+            "take" => Ok(Action::Take),
+            "pass" | "drop" => Ok(Action::Pass),
+            "resign" | "resign single" => Ok(Action::Resign(Victory::Single)),
+            "resign gammon" => Ok(Action::Resign(Victory::Gammon)),
+            "resign backgammon" => Ok(Action::Resign(Victory::Backgammon)),
+            play => Ok(Action::Move(play.parse()?)),
+        }
+    }
+}
+
+/// Represents what has happened in a session
+#[derive(Debug)]
+pub enum Update {
+    /// A player has rolled the dices, for the opening roll the values of player 0 and player 1
+    Rolled(Player, (u8, u8)),
+    /// A player has offered the cube
+    Doubled(Player),
+    /// A player has taken the cube
+    Taken(Player),
+    /// A player has passed the cube, which ends the game
+    Passed(Player, GameResult),
+    /// A player has played the dices
+    Moved(Player, Play),
+    /// A player has resigned, which ends the game
+    Resigned(Player, GameResult),
+    /// A player has borne off all checkers, which ends the game
+    Won(GameResult),
+    /// The action of a player has been rejected, and the game is unchanged
+    Rejected(Player, Error),
+    /// The player who has to act has run out of time, nobody for the opening roll. The game is
+    /// unchanged, e.g. to let the player resign or to keep waiting.
+    TimedOut(Player),
+}
+
+/// Holds the actions sent by a player and not applied yet
+#[derive(Debug, Default)]
+struct Inbox {
+    /// The actions, in the order sent
+    actions: VecDeque<Action>,
+    /// The session waiting for an action
+    waker: Option<Waker>,
+}
+
+/// The inbox of a player, shared by the seat and the session
+type SharedInbox = Arc<Mutex<Inbox>>;
+
+/// Sends the actions of a player to a session. Clones send for the same player.
+#[derive(Debug, Clone)]
+pub struct Seat {
+    /// The player sending
+    player: Player,
+    /// Where the actions are queued
+    inbox: SharedInbox,
+}
+
+impl Seat {
+    /// Returns the player whose actions this seat sends
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Send an action, which the session applies once it has applied all actions sent before
+    pub fn send(&self, action: Action) {
+        let mut inbox = self.inbox.lock().unwrap_or_else(PoisonError::into_inner);
+        inbox.actions.push_back(action);
+        if let Some(waker) = inbox.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Holds a game together with the actions the players have sent
+#[derive(Debug)]
+pub struct GameSession {
+    /// The game played
+    pub game: Game,
+    /// The inboxes of player 0 and player 1
+    inboxes: (SharedInbox, SharedInbox),
+}
+
+impl GameSession {
+    /// Start a session of a game, and return it with the seats of player 0 and player 1
+    pub fn new(game: Game) -> (Self, (Seat, Seat)) {
+        let inboxes: (SharedInbox, SharedInbox) = Default::default();
+        let seats = (
+            Seat {
+                player: Player::Player0,
+                inbox: Arc::clone(&inboxes.0),
+            },
+            Seat {
+                player: Player::Player1,
+                inbox: Arc::clone(&inboxes.1),
+            },
+        );
+        (GameSession { game, inboxes }, seats)
+    }
+
+    /// Returns the player who has to act next, i.e. the opponent of a player who has doubled,
+    /// nobody before the opening roll or after the game
+    pub fn deciding(&self) -> Player {
+        match self.game.phase {
+            Phase::AwaitingCubeDecision => self.game.who_plays.other(),
+            Phase::GameOver => Player::Nobody,
+            _ => self.game.who_plays,
+        }
+    }
+
+    /// Wait for the next action of a player, apply it, and return what has happened. Fails once
+    /// the game has ended.
+    pub async fn next(&mut self) -> Result<Update, Error> {
+        self.next_timeout(future::pending()).await
+    }
+
+    /// Wait for the next action of a player until the timeout completes, apply it, and return
+    /// what has happened. Fails once the game has ended.
+    pub async fn next_timeout(
+        &mut self,
+        timeout: impl Future<Output = ()>,
+    ) -> Result<Update, Error> {
+        if self.game.phase == Phase::GameOver {
+            return Err(Error::GameEnded);
+        }
+        let mut timeout = pin!(timeout);
+        let received = future::poll_fn(|cx| match self.poll_action(cx) {
+            Poll::Ready(received) => Poll::Ready(Some(received)),
+            Poll::Pending => timeout.as_mut().poll(cx).map(|()| None),
+        })
+        .await;
+        Ok(match received {
+            Some((player, action)) => self
+                .apply(player, action)
+                .unwrap_or_else(|e| Update::Rejected(player, e)),
+            None => Update::TimedOut(self.deciding()),
+        })
+    }
+
+    /// Wait for the next action of a player for at most the given time on the tokio runtime,
+    /// apply it, and return what has happened. Fails once the game has ended.
+    #[cfg(feature = "tokio")]
+    pub async fn next_within(&mut self, time: Duration) -> Result<Update, Error> {
+        self.next_timeout(tokio::time::sleep(time)).await
+    }
+
+    /// Run the session as a task of the tokio runtime until the game has ended, giving the player
+    /// the time to act for every action, and send every update to the receiver returned. Once the
+    /// receiver is dropped, the session ends after the next update. The task returns the game.
+    #[cfg(feature = "tokio")]
+    pub fn spawn(mut self, time: Duration) -> (JoinHandle<Game>, UnboundedReceiver<Update>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            while let Ok(update) = self.next_within(time).await {
+                if sender.send(update).is_err() {
+                    break;
+                }
+            }
+            self.game
+        });
+        (task, receiver)
+    }
+
+    /// Take the next action sent by either player, player 0 first, or register to be woken once one
+    /// is sent
+    fn poll_action(&self, cx: &mut Context<'_>) -> Poll<(Player, Action)> {
+        for (player, inbox) in [
+            (Player::Player0, &self.inboxes.0),
+            (Player::Player1, &self.inboxes.1),
+        ] {
+            let mut inbox = inbox.lock().unwrap_or_else(PoisonError::into_inner);
+            match inbox.actions.pop_front() {
+                Some(action) => return Poll::Ready((player, action)),
+                None => inbox.waker = Some(cx.waker().clone()),
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Apply the action of a player to the game
+    fn apply(&mut self, player: Player, action: Action) -> Result<Update, Error> {
+        let opening = self.game.who_plays == Player::Nobody;
+        match &action {
+            Action::Resign(_) => {}
+            Action::Roll if opening => {}
+            _ if self.deciding() != player => return Err(Error::NotYourTurn),
+            _ => {}
+        }
+        let game = &mut self.game;
+        let result = |game: &Game| game.result().ok_or(Error::GameEnded);
+        Ok(match action {
+            Action::Roll => {
+                let _ = game.roll()?;
+                Update::Rolled(player, game.dices.values)
+            }
+            Action::Double => {
+                game.offer_double(player)?;
+                Update::Doubled(player)
+            }
+            Action::Take => {
+                game.take(player)?;
+                Update::Taken(player)
+            }
+            Action::Pass => {
+                game.pass(player)?;
+                Update::Passed(player, result(game)?)
+            }
+            Action::Move(play) => {
+                game.apply_play(&play)?;
+                match game.phase {
+                    Phase::GameOver => Update::Won(result(game)?),
+                    _ => Update::Moved(player, play),
+                }
+            }
+            Action::Resign(victory) => {
+                game.resign(player, victory)?;
+                Update::Resigned(player, result(game)?)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::ScriptedDice;
+    use std::sync::mpsc;
+    use std::task::Wake;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Wakes a thread blocked on a future
+    struct Unpark(thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Run a future to completion on the current thread
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    fn game(rolls: Vec<(u8, u8)>) -> Result<Game, Error> {
+        Ok(Game::new().with_dice_source(ScriptedDice::new(rolls)?))
+    }
+
+    #[test]
+    fn test_actions() -> Result<(), Error> {
+        for s in [
+            "roll",
+            "double",
+            "take",
+            "pass",
+            "resign gammon",
+            "24/18 13/11",
+        ] {
+            assert_eq!(s.parse::<Action>()?.to_string(), s);
+        }
+        assert_eq!("drop".parse::<Action>()?, Action::Pass);
+        assert_eq!("resign".parse::<Action>()?, Action::Resign(Victory::Single));
+        assert!("fly".parse::<Action>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_turns() -> Result<(), Error> {
+        let (mut session, (x, o)) = GameSession::new(game(vec![(6, 5), (3, 1)])?);
+        assert_eq!((x.player(), o.player()), (Player::Player0, Player::Player1));
+        assert_eq!(session.deciding(), Player::Nobody);
+
+        // the opening roll is rolled by player 1, and player 0 plays it
+        o.send(Action::Roll);
+        assert!(matches!(
+            block_on(session.next())?,
+            Update::Rolled(Player::Player1, (6, 5))
+        ));
+        o.send(Action::Double);
+        assert!(matches!(
+            block_on(session.next())?,
+            Update::Rejected(Player::Player1, Error::NotYourTurn)
+        ));
+        x.send("24/18 18/13".parse()?);
+        match block_on(session.next())? {
+            Update::Moved(Player::Player0, play) => assert_eq!(play.to_string(), "24/18 18/13"),
+            update => panic!("unexpected {:?}", update),
+        }
+
+        o.send(Action::Double);
+        assert!(matches!(block_on(session.next())?, Update::Doubled(_)));
+        x.send(Action::Pass);
+        match block_on(session.next())? {
+            Update::Passed(Player::Player0, result) => {
+                assert_eq!(result.winner, Player::Player1);
+                assert_eq!(result.points, 1);
+            }
+            update => panic!("unexpected {:?}", update),
+        }
+        assert!(matches!(block_on(session.next()), Err(Error::GameEnded)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_threads() -> Result<(), Error> {
+        let (mut session, (x, o)) = GameSession::new(game(vec![(3, 1)])?);
+        let (done, finished) = mpsc::channel();
+        let player = thread::spawn(move || {
+            x.send(Action::Roll);
+            let _ = finished.recv();
+            o.send(Action::Resign(Victory::Gammon));
+        });
+        assert!(matches!(block_on(session.next())?, Update::Rolled(..)));
+        let _ = done.send(());
+        match block_on(session.next())? {
+            Update::Resigned(Player::Player1, result) => assert_eq!(result.points, 2),
+            update => panic!("unexpected {:?}", update),
+        }
+        assert!(player.join().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout() -> Result<(), Error> {
+        let (mut session, (x, _)) = GameSession::new(game(vec![(3, 1)])?);
+        x.send(Action::Roll);
+        // actions sent already are applied before the timeout
+        assert!(matches!(
+            block_on(session.next_timeout(async {}))?,
+            Update::Rolled(..)
+        ));
+        assert!(matches!(
+            block_on(session.next_timeout(async {}))?,
+            Update::TimedOut(Player::Player0)
+        ));
+
+        // a timer completing from another thread
+        let (fire, fired) = mpsc::channel::<()>();
+        let timer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            let _ = fire.send(());
+        });
+        let timeout = future::poll_fn(|cx| {
+            let waker = cx.waker().clone();
+            match fired.try_recv() {
+                Ok(()) => Poll::Ready(()),
+                Err(_) => {
+                    let _ = thread::spawn(move || {
+                        thread::sleep(Duration::from_millis(1));
+                        waker.wake();
+                    });
+                    Poll::Pending
+                }
+            }
+        });
+        assert!(matches!(
+            block_on(session.next_timeout(timeout))?,
+            Update::TimedOut(Player::Player0)
+        ));
+        assert!(timer.join().is_ok());
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn test_tokio() -> Result<(), Error> {
+        let (mut session, (x, o)) = GameSession::new(game(vec![(3, 1)])?);
+        let time = Duration::from_secs(30);
+        assert!(matches!(
+            session.next_within(time).await?,
+            Update::TimedOut(Player::Nobody)
+        ));
+
+        let (task, mut updates) = session.spawn(time);
+        x.send(Action::Roll);
+        assert!(matches!(
+            updates.recv().await,
+            Some(Update::Rolled(Player::Player0, (3, 1)))
+        ));
+        assert!(matches!(
+            updates.recv().await,
+            Some(Update::TimedOut(Player::Player0))
+        ));
+        o.send(Action::Resign(Victory::Gammon));
+        assert!(matches!(
+            updates.recv().await,
+            Some(Update::Resigned(Player::Player1, _))
+        ));
+        assert!(updates.recv().await.is_none());
+        let game = task.await.map_err(|_| Error::GameEnded)?;
+        assert_eq!(game.result().map(|r| r.points), Some(2));
+        Ok(())
+    }
+}
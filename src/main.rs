@@ -0,0 +1,372 @@
+//! # Backgammon in the Terminal
+//!
+//! Plays a match in the terminal, between two humans or against a bot deciding with the
+//! heuristic evaluator:
+//! ```text
+//! cargo run --features cli -- --points 5 --bot
+//! ```
+use backgammon::cubeful::{cube_decision, CubeContext};
+use backgammon::eval::{Evaluator, Heuristic};
+use backgammon::met::Met;
+use backgammon::rules::{MatchRules, Player, Roll, SeededDice};
+use backgammon::term::Renderer;
+use backgammon::{Error, Game, Match, Phase, Victory};
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+/// The usage of the command line
+const USAGE: &str = "Usage: backgammon [--points N] [--bot | --bots] [--seed N]
+
+  --points N  play a match to N points, 0 for unlimited (default 5)
+  --bot       let the bot play O
+  --bots      let the bot play both X and O
+  --seed N    roll the dices from the given seed";
+
+/// The help shown to human players
+const HELP: &str = "Commands: roll, double, take, pass, a play like 24/18 13/11, moves, hint,
+resign [single|gammon|backgammon], board, help, quit";
+
+/// Holds the options given on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+    /// The points of the match
+    points: u32,
+    /// Whether player 0 (X) and player 1 (O) are played by the bot
+    bots: (bool, bool),
+    /// The seed of the dices, random dices if none
+    seed: Option<u64>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            points: 5,
+            bots: (false, false),
+            seed: None,
+        }
+    }
+}
+
+/// Parse the arguments of the command line, without the name of the program
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .and_then(|v| v.parse::<u64>().ok())
+                .ok_or(format!("{} needs a number", name))
+        };
+        match arg.as_str() {
+            "--points" => {
+                options.points = u32::try_from(value("--points")?).map_err(|e| e.to_string())?
+            }
+            "--seed" => options.seed = Some(value("--seed")?),
+            "--bot" => options.bots = (false, true),
+            "--bots" => options.bots = (true, true),
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+/// Returns the mark of a player's checkers in the board diagram
+fn mark(player: Player) -> &'static str {
+    match player {
+        Player::Player0 => "X",
+        Player::Player1 => "O",
+        Player::Nobody => "-",
+    }
+}
+
+/// Returns whether a player is played by the bot
+fn is_bot(options: &Options, player: Player) -> bool {
+    match player {
+        Player::Player0 => options.bots.0,
+        Player::Player1 => options.bots.1,
+        Player::Nobody => false,
+    }
+}
+
+/// Returns the player who has to decide next, i.e. the opponent of a player who has doubled
+fn deciding(game: &Game) -> Player {
+    match game.phase {
+        Phase::AwaitingCubeDecision => game.who_plays.other(),
+        _ => game.who_plays,
+    }
+}
+
+/// Returns the cube decision of the bot for the player whose turn it is, if the cube is live
+fn bot_cube(game: &Game) -> Option<(bool, bool)> {
+    let evaluation = Heuristic.evaluate_game(game).ok()?;
+    let context = CubeContext::from_game(game);
+    let decision = cube_decision(&evaluation, &context, &Met::default()).ok()?;
+    Some((decision.double(), decision.take()))
+}
+
+/// Let the bot take the next action of the game and describe it
+fn bot_action(game: &mut Game) -> Result<String, Error> {
+    let player = deciding(game);
+    match game.phase {
+        Phase::AwaitingRoll => {
+            let may_double = game.cube_is_live(player) && game.cube.owner() != player.other();
+            if may_double && bot_cube(game).is_some_and(|(double, _)| double) {
+                game.offer_double(player)?;
+                return Ok(format!("{} doubles", mark(player)));
+            }
+            let _ = game.roll()?;
+            Ok(format!(
+                "{} rolls {}-{}",
+                mark(player),
+                game.dices.values.0,
+                game.dices.values.1
+            ))
+        }
+        Phase::AwaitingCubeDecision => match !matches!(bot_cube(game), Some((_, false))) {
+            true => {
+                game.take(player)?;
+                Ok(format!("{} takes", mark(player)))
+            }
+            false => {
+                game.pass(player)?;
+                Ok(format!("{} passes", mark(player)))
+            }
+        },
+        Phase::AwaitingMove => {
+            let (play, _) = game.best_move(game.dices.values, &Heuristic)?;
+            for m in &play.moves {
+                game.apply_move(m)?;
+            }
+            Ok(format!("{} plays {}", mark(player), play))
+        }
+        Phase::GameOver => Err(Error::GameEnded),
+    }
+}
+
+/// The outcome of a command of a human player
+enum Outcome {
+    /// The game goes on, with a message to show
+    Continue(String),
+    /// The player quits the match
+    Quit,
+}
+
+/// Execute a command of a human player
+fn command(game: &mut Game, line: &str) -> Result<Outcome, Error> {
+    let player = deciding(game);
+    let mut words = line.split_whitespace();
+    let message = match (words.next().unwrap_or("roll"), game.phase) {
+        ("quit" | "exit", _) => return Ok(Outcome::Quit),
+        ("help", _) => HELP.to_string(),
+        ("board", _) => Renderer::new().render(game),
+        ("roll", _) => {
+            let _ = game.roll()?;
+            format!(
+                "{} rolls {}-{}",
+                mark(player),
+                game.dices.values.0,
+                game.dices.values.1
+            )
+        }
+        ("double", _) => {
+            game.offer_double(player)?;
+            format!("{} doubles", mark(player))
+        }
+        ("take", _) => {
+            game.take(player)?;
+            format!("{} takes", mark(player))
+        }
+        ("pass" | "drop", _) => {
+            game.pass(player)?;
+            format!("{} passes", mark(player))
+        }
+        ("resign", _) => {
+            let victory = match words.next().unwrap_or("single") {
+                "single" => Victory::Single,
+                "gammon" => Victory::Gammon,
+                "backgammon" => Victory::Backgammon,
+                _ => return Err(Error::NotationInvalid),
+            };
+            game.resign(player, victory)?;
+            format!("{} resigns", mark(player))
+        }
+        ("moves", Phase::AwaitingMove) => {
+            let plays: Vec<String> = game
+                .legal_plays()
+                .iter()
+                .map(|(_, play)| play.to_string())
+                .collect();
+            plays.join("\n")
+        }
+        ("hint", Phase::AwaitingMove) => {
+            let (play, evaluation) = game.best_move(game.dices.values, &Heuristic)?;
+            format!("{}  ({})", play, evaluation)
+        }
+        ("hint", _) => match bot_cube(game) {
+            Some((double, take)) => format!(
+                "{}, {}",
+                if double { "Double" } else { "No double" },
+                if take { "take" } else { "pass" }
+            ),
+            None => "The cube is dead".to_string(),
+        },
+        (_, Phase::AwaitingMove) => {
+            let play = game.parse_play(line)?;
+            game.apply_play(&play)?;
+            format!("{} plays {}", mark(player), line.trim())
+        }
+        _ => return Err(Error::NotationInvalid),
+    };
+    Ok(Outcome::Continue(message))
+}
+
+/// Play a match with the given options, reading the commands of human players from the input.
+/// Returns the winner of the match, or none if it has been quit.
+fn play(options: &Options, mut input: impl BufRead, mut output: impl Write) -> io::Result<Player> {
+    let mut m = Match::new().with_points(options.points);
+    if let Some(seed) = options.seed {
+        m = m.with_dice_source(SeededDice::new(seed));
+    }
+    let error = |e: Error| io::Error::other(e.to_string());
+
+    while m.winner().is_none() {
+        let score = m.score();
+        let game = m.new_game().map_err(error)?;
+        writeln!(output, "New game, score X {} - O {}", score.0, score.1)?;
+        // the opening roll decides who plays first
+        while game.who_plays == Player::Nobody {
+            let _ = game.roll().map_err(error)?;
+            let (x, o) = game.dices.values;
+            writeln!(output, "Opening roll: X {}, O {}", x, o)?;
+        }
+
+        while game.phase != Phase::GameOver {
+            let player = deciding(game);
+            if is_bot(options, player) {
+                let message = bot_action(game).map_err(error)?;
+                writeln!(output, "{}", message)?;
+                continue;
+            }
+            if game.phase != Phase::AwaitingCubeDecision {
+                write!(output, "{}", Renderer::new().render(game))?;
+            }
+            write!(output, "{} ({})> ", mark(player), game.phase)?;
+            output.flush()?;
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(Player::Nobody);
+            }
+            match command(game, &line) {
+                Ok(Outcome::Continue(message)) => writeln!(output, "{}", message)?,
+                Ok(Outcome::Quit) => return Ok(Player::Nobody),
+                Err(e) => writeln!(output, "{}", e)?,
+            }
+        }
+        if let Some(result) = game.result() {
+            writeln!(
+                output,
+                "{} wins {} point(s) by a {:?}",
+                mark(result.winner),
+                result.points,
+                result.victory
+            )?;
+        }
+        if options.points == 0 {
+            break;
+        }
+    }
+    let winner = m.winner().unwrap_or(Player::Nobody);
+    let score = m.score();
+    writeln!(
+        output,
+        "{} wins the match, score X {} - O {}",
+        mark(winner),
+        score.0,
+        score.1
+    )?;
+    Ok(winner)
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    println!("{}", HELP);
+    if let Err(e) = play(&options, io::stdin().lock(), io::stdout()) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Result<Options, String> {
+        parse_args(s.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(args(""), Ok(Options::default()));
+        assert_eq!(
+            args("--points 7 --bot --seed 3"),
+            Ok(Options {
+                points: 7,
+                bots: (false, true),
+                seed: Some(3),
+            })
+        );
+        assert!(args("--points").is_err());
+        assert!(args("--points seven").is_err());
+        assert!(args("--fast").is_err());
+    }
+
+    #[test]
+    fn test_bots() -> io::Result<()> {
+        let options = Options {
+            points: 3,
+            bots: (true, true),
+            seed: Some(7),
+        };
+        let mut output = Vec::new();
+        let winner = play(&options, io::empty(), &mut output)?;
+        assert_ne!(winner, Player::Nobody);
+        let output = String::from_utf8_lossy(&output);
+        assert!(output.contains("plays"));
+        assert!(output.contains(&format!("{} wins the match", mark(winner))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_humans() -> io::Result<()> {
+        let options = Options {
+            points: 3,
+            bots: (false, false),
+            seed: Some(7),
+        };
+        let input = "double\nfly\nmoves\nhint\n24/23\nresign gammon\nresign gammon\n";
+        let mut output = Vec::new();
+        let winner = play(&options, input.as_bytes(), &mut output)?;
+        assert_ne!(winner, Player::Nobody);
+        let output = String::from_utf8_lossy(&output);
+        // no doubling before the first move, and unknown commands are rejected
+        assert!(output.contains(&Error::MoveFirst.to_string()));
+        assert!(output.contains(&Error::NotationInvalid.to_string()));
+        assert!(output.contains("resigns"));
+
+        // the input ends before the match
+        let mut output = Vec::new();
+        assert_eq!(
+            play(&options, "quit\n".as_bytes(), &mut output)?,
+            Player::Nobody
+        );
+        Ok(())
+    }
+}
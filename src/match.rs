@@ -1,15 +1,36 @@
-use crate::game::Game;
-use crate::rules::{GameRules, MatchRules, Rules};
+use crate::game::{share, Game, Phase, SharedDiceSource};
+use crate::rules::{DiceSource, GameRules, MatchRules, Player, Rules, Variant};
+use crate::{Clock, Error, Victory};
 
-use std::fmt;
+/// Implements the versioned encoding of matches
+mod file;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
 
 /// Represents a Backgammon match
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Match {
     /// The rules set for the match
     pub rules: Rules,
     /// The games played in the match
     pub games: Vec<Game>,
+    /// The source of the dices rolled in all games, random dices if none
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dice_source: Option<SharedDiceSource>,
+    /// The clocks of the players, if the match is timed
+    clock: Option<Clock>,
 }
 
 // implement Display trait
@@ -37,6 +58,225 @@ impl Match {
     pub fn new() -> Self {
         Match::default()
     }
+
+    /// Roll the dices of all games of the match from the given source, see [`DiceSource`]
+    pub fn with_dice_source(mut self, source: impl DiceSource + 'static) -> Self {
+        self.dice_source = Some(share(source));
+        self
+    }
+
+    /// Time the match with the given clock, see [`tick`](Match::tick)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Returns the clocks of the players, if the match is timed
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// Charge the time elapsed since the last tick to the player who has to act in the current
+    /// game, i.e. the player on turn or the opponent deciding on the cube. No time is charged
+    /// before the opening roll or between games. Call it before every action of a player, so
+    /// that the time of the action is charged to that player.
+    ///
+    /// Per tournament rules, a player whose time runs out loses the match: the current game ends
+    /// with the player resigning a single game, the opponent becomes the [`winner`](Match::winner)
+    /// and [`Error::TimeExpired`] is returned.
+    /// ```
+    /// use backgammon::{Clock, Error, Match, Phase, TimeControl};
+    /// use backgammon::rules::{MatchRules, Player};
+    /// use core::time::Duration;
+    ///
+    /// let clock = Clock::new(Duration::from_secs(60), TimeControl::Fischer(Duration::from_secs(5)));
+    /// let mut m = Match::new().with_points(5).with_clock(clock);
+    /// let g = m.new_game().unwrap();
+    /// g.roll_with((3, 1)).unwrap();
+    ///
+    /// assert_eq!(m.tick(Duration::from_secs(61)), Err(Error::TimeExpired));
+    /// assert_eq!(m.game().unwrap().phase, Phase::GameOver);
+    /// assert_eq!(m.winner(), Some(Player::Player1));
+    /// ```
+    pub fn tick(&mut self, elapsed: Duration) -> Result<(), Error> {
+        if self.winner().is_some() {
+            return Err(Error::MatchEnded);
+        }
+        let Some(clock) = self.clock.as_mut() else {
+            return Ok(());
+        };
+        let player = match self.games.last() {
+            Some(g) => match g.phase {
+                Phase::AwaitingCubeDecision => g.who_plays.other(),
+                Phase::GameOver => Player::Nobody,
+                _ => g.who_plays,
+            },
+            None => Player::Nobody,
+        };
+        if player == Player::Nobody {
+            clock.stop();
+            return Ok(());
+        }
+        if let Err(e) = clock.run(player, elapsed) {
+            if let Some(g) = self.games.last_mut() {
+                g.resign(player, Victory::Single)?;
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Start the next game of the match, once the previous game has ended.
+    ///
+    /// If the [`crawford`](Rules::crawford) rule is set, the game after a player first reached
+    /// match point, i.e. one point less than needed to win the match, is the Crawford game in
+    /// which no doubling is allowed.
+    /// ```
+    /// use backgammon::{Match, Victory};
+    /// use backgammon::rules::{MatchRules, Player};
+    ///
+    /// let mut m = Match::new().with_points(3);
+    /// let g = m.new_game().unwrap();
+    /// g.resign(Player::Player1, Victory::Gammon).unwrap();
+    /// assert_eq!(m.score(), (2, 0));
+    ///
+    /// let g = m.new_game().unwrap();
+    /// g.resign(Player::Player1, Victory::Single).unwrap();
+    /// assert_eq!(m.winner(), Some(Player::Player0));
+    /// assert!(m.new_game().is_err());
+    /// ```
+    pub fn new_game(&mut self) -> Result<&mut Game, Error> {
+        if self.winner().is_some() {
+            return Err(Error::MatchEnded);
+        }
+        if self.game().is_some_and(|g| g.phase != Phase::GameOver) {
+            return Err(Error::GameStarted);
+        }
+        let mut game = self.next_game();
+        game.set_dice_source(self.dice_source.clone());
+        self.games.push(game);
+        self.game().ok_or(Error::GameEnded)
+    }
+
+    /// Set up the next game of the match, which is the Crawford game if a player has just
+    /// reached match point
+    fn next_game(&self) -> Game {
+        let score = self.score();
+        let match_point = u64::from(self.rules.points.saturating_sub(1));
+        let crawford = self.rules.crawford
+            && self.rules.points > 1
+            && self.crawford_game().is_none()
+            && (score.0 == match_point || score.1 == match_point);
+        let since_crawford = self
+            .crawford_game()
+            .map_or(0, |i| u8::try_from(self.games.len() - i).unwrap_or(u8::MAX));
+        // without points to reach, the cube is never dead
+        let points = match self.rules.points {
+            0 => u64::MAX,
+            points => u64::from(points),
+        };
+        let away = (
+            points.saturating_sub(score.0),
+            points.saturating_sub(score.1),
+        );
+        Game::for_match(self.rules, crawford, since_crawford, away)
+    }
+
+    /// Returns the game currently played, i.e. the last game started
+    pub fn game(&mut self) -> Option<&mut Game> {
+        self.games.last_mut()
+    }
+
+    /// Returns true if the player on roll in the current game may still make a meaningful double,
+    /// see [`Game::cube_is_live`]. Before the opening roll, the cube is live if it is live for any
+    /// player.
+    pub fn cube_is_live(&self) -> bool {
+        match self.games.last() {
+            Some(g) if g.phase != Phase::GameOver => match g.who_plays {
+                Player::Nobody => {
+                    g.cube_is_live(Player::Player0) || g.cube_is_live(Player::Player1)
+                }
+                player => g.cube_is_live(player),
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the index of the Crawford game in the games of the match, if it has been played
+    /// already or is being played
+    pub fn crawford_game(&self) -> Option<usize> {
+        self.games.iter().position(|g| g.is_crawford())
+    }
+
+    /// Returns the points won so far by player 0 and player 1, counting all finished games
+    pub fn score(&self) -> (u64, u64) {
+        score(&self.games)
+    }
+
+    /// Returns the winner of the match, i.e. the player who reached the points of the match first,
+    /// or the opponent of a player who ran out of time. Otherwise, a match over 0 points has no
+    /// end.
+    pub fn winner(&self) -> Option<Player> {
+        if let Some(player) = self.clock.and_then(|c| c.flagged()) {
+            return Some(player.other());
+        }
+        if self.rules.points == 0 {
+            return None;
+        }
+        let points = u64::from(self.rules.points);
+        match self.score() {
+            (p, _) if p >= points => Some(Player::Player0),
+            (_, p) if p >= points => Some(Player::Player1),
+            _ => None,
+        }
+    }
+
+    /// Encode the full state of the match, including the records of all games, the moves of a
+    /// turn in progress, a pending cube offer, and the clocks. The encoding is versioned and
+    /// decoding never panics. Neither the dice source nor the observers and the undo history of
+    /// the games are encoded.
+    /// ```
+    /// use backgammon::Match;
+    /// use backgammon::rules::{Field, MatchRules};
+    ///
+    /// let mut m = Match::new().with_points(5);
+    /// let g = m.new_game().unwrap();
+    /// g.roll_with((3, 1)).unwrap();
+    /// g.play_die(Field::Point(7), 3).unwrap();
+    ///
+    /// let resumed = Match::from_bytes(&m.to_bytes()).unwrap();
+    /// assert_eq!(resumed.games[0].moves, m.games[0].moves);
+    /// assert_eq!(resumed.games[0].dices.available(), vec![1]);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        file::encode(self)
+    }
+
+    /// Decode a match encoded by [`to_bytes`](Match::to_bytes), unknown versions of the encoding
+    /// are an error
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        file::decode(bytes)
+    }
+
+    /// Save the match to a file, see [`to_bytes`](Match::to_bytes). The file is replaced at once
+    /// by renaming a temporary file written next to it, so that a crash never leaves a partially
+    /// written match behind.
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, self.to_bytes())?;
+        fs::rename(&temporary, path)
+    }
+
+    /// Load a match saved by [`save`](Match::save), e.g. to resume it after a restart. Files
+    /// which cannot be decoded fail with [`io::ErrorKind::InvalidData`].
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Match::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
 }
 
 /// Implements SetRules for Match
@@ -79,11 +319,54 @@ impl GameRules for Match {
         self.rules.holland = true;
         self
     }
+
+    fn with_variant(mut self, variant: Variant) -> Self {
+        self.rules.variant = variant;
+        self
+    }
 }
+/// Sums up the points won by player 0 and player 1 in all finished games
+pub(crate) fn score(games: &[Game]) -> (u64, u64) {
+    games
+        .iter()
+        .filter_map(|g| g.result())
+        .fold((0, 0), |score, r| match r.winner {
+            Player::Player0 => (score.0 + r.points, score.1),
+            Player::Player1 => (score.0, score.1 + r.points),
+            Player::Nobody => score,
+        })
+}
+
 // Unit tests
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Victory;
+
+    /// Always rolls the same dices
+    #[derive(Debug)]
+    struct Fixed(u8, u8);
+
+    impl DiceSource for Fixed {
+        fn roll(&mut self) -> (u8, u8) {
+            (self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn test_dice_source() -> Result<(), Error> {
+        use crate::rules::Roll;
+
+        let mut m = Match::new().with_points(3).with_dice_source(Fixed(5, 3));
+        for _ in 0..2 {
+            let g = m.new_game()?;
+            let _ = g.roll()?;
+            assert_eq!(g.who_plays, Player::Player0);
+            assert_eq!(g.dices.values, (5, 3));
+            g.resign(Player::Player1, Victory::Single)?;
+        }
+        Ok(())
+    }
 
     #[test]
     fn test_default_match() {
@@ -125,13 +408,273 @@ mod tests {
         assert_eq!(m.rules.points, 3);
     }
 
+    #[test]
+    fn test_play_match() -> Result<(), Error> {
+        let mut m = Match::new().with_points(5);
+        assert_eq!(m.score(), (0, 0));
+        assert!(m.game().is_none());
+
+        let g = m.new_game()?;
+        assert_eq!(g.rules.points, 5);
+        g.who_plays = Player::Player0;
+        g.offer_double(Player::Player0)?;
+        g.take(Player::Player1)?;
+        assert!(matches!(m.new_game(), Err(Error::GameStarted)));
+
+        let g = m.game().ok_or(Error::GameEnded)?;
+        g.resign(Player::Player0, Victory::Gammon)?;
+        assert_eq!(m.score(), (0, 4));
+        assert_eq!(m.winner(), None);
+
+        let g = m.new_game()?;
+        g.resign(Player::Player1, Victory::Single)?;
+        assert_eq!(m.score(), (1, 4));
+
+        let g = m.new_game()?;
+        g.resign(Player::Player0, Victory::Single)?;
+        assert_eq!(m.score(), (1, 5));
+        assert_eq!(m.winner(), Some(Player::Player1));
+        assert_eq!(m.games.len(), 3);
+        assert!(matches!(m.new_game(), Err(Error::MatchEnded)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_crawford() -> Result<(), Error> {
+        let mut m = Match::new().with_points(3);
+        let g = m.new_game()?;
+        assert!(!g.is_crawford());
+        g.resign(Player::Player1, Victory::Gammon)?;
+        assert_eq!(m.crawford_game(), None);
+
+        // player 0 reached match point, no doubling allowed
+        let g = m.new_game()?;
+        assert!(g.is_crawford());
+        g.who_plays = Player::Player1;
+        assert!(matches!(
+            g.offer_double(Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+        g.resign(Player::Player0, Victory::Single)?;
+        assert_eq!(m.crawford_game(), Some(1));
+
+        // after the Crawford game, doubling is allowed again
+        let g = m.new_game()?;
+        assert!(!g.is_crawford());
+        g.who_plays = Player::Player1;
+        g.offer_double(Player::Player1)?;
+        assert_eq!(m.crawford_game(), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_holland() -> Result<(), Error> {
+        let mut m = Match::new().with_points(3).with_holland();
+        m.new_game()?.resign(Player::Player1, Victory::Gammon)?;
+        m.new_game()?.resign(Player::Player0, Victory::Single)?;
+
+        // post-Crawford, doubling only after both players rolled twice
+        let g = m.new_game()?;
+        g.who_plays = Player::Player1;
+        assert!(matches!(
+            g.offer_double(Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cube_is_live() -> Result<(), Error> {
+        let mut m = Match::new().with_points(3);
+        assert!(!m.cube_is_live());
+        m.rules.crawford = false;
+        let _ = m.new_game()?;
+        assert!(m.cube_is_live());
+        m.new_game().map(|_| ()).or_else(|_| {
+            m.game()
+                .ok_or(Error::GameEnded)?
+                .resign(Player::Player1, Victory::Gammon)
+        })?;
+        m.new_game()?.resign(Player::Player0, Victory::Single)?;
+        assert_eq!(m.score(), (2, 1));
+
+        // player 0 only needs a single game, player 1 may still double
+        let g = m.new_game()?;
+        g.who_plays = Player::Player0;
+        assert!(!m.cube_is_live());
+        let g = m.game().ok_or(Error::GameEnded)?;
+        g.who_plays = Player::Player1;
+        g.offer_double(Player::Player1)?;
+        g.take(Player::Player0)?;
+        assert!(!m.cube_is_live());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unlimited_match() -> Result<(), Error> {
+        let mut m = Match::new().with_points(0);
+        m.new_game()?.resign(Player::Player1, Victory::Backgammon)?;
+        assert_eq!(m.winner(), None);
+        let g = m.new_game()?;
+        assert!(!g.is_crawford());
+        assert!(m.cube_is_live());
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_crawford() -> Result<(), Error> {
+        let mut m = Match::new().with_points(3);
+        m.rules.crawford = false;
+        m.new_game()?.resign(Player::Player1, Victory::Gammon)?;
+        assert!(!m.new_game()?.is_crawford());
+        assert_eq!(m.crawford_game(), None);
+
+        // in a one point match, every game is played at match point
+        let mut m = Match::new().with_points(1);
+        assert!(!m.new_game()?.is_crawford());
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_no_beaver() -> Result<(), Error> {
+        let mut m = Match::new().with_beaver();
+        let g = m.new_game()?;
+        g.who_plays = Player::Player0;
+        g.offer_double(Player::Player0)?;
+        assert!(matches!(
+            g.beaver(Player::Player1),
+            Err(Error::DoublingNotPermitted)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_clock() -> Result<(), Error> {
+        use crate::TimeControl;
+
+        let clock = Clock::new(Duration::from_secs(60), TimeControl::Delay(Duration::ZERO));
+        let mut m = Match::new().with_points(0).with_clock(clock);
+        assert_eq!(m.clock(), Some(&clock));
+
+        // no time is charged before the opening roll
+        let _ = m.new_game()?;
+        m.tick(Duration::from_secs(100))?;
+        let g = m.game().ok_or(Error::GameEnded)?;
+        let _ = g.roll_with((5, 3))?;
+        assert_eq!(g.who_plays, Player::Player0);
+        m.tick(Duration::from_secs(10))?;
+
+        // the opponent decides on the cube
+        let g = m.game().ok_or(Error::GameEnded)?;
+        g.resign(Player::Player1, Victory::Single)?;
+        m.tick(Duration::from_secs(100))?;
+        let g = m.new_game()?;
+        g.who_plays = Player::Player1;
+        g.offer_double(Player::Player1)?;
+        m.tick(Duration::from_secs(20))?;
+        let clock = m.clock().ok_or(Error::GameEnded)?;
+        assert_eq!(clock.remaining(Player::Player0), Duration::from_secs(30));
+        assert_eq!(clock.remaining(Player::Player1), Duration::from_secs(60));
+
+        // player 0 loses on time even in a match without end
+        assert_eq!(m.tick(Duration::from_secs(30)), Err(Error::TimeExpired));
+        let g = m.game().ok_or(Error::GameEnded)?;
+        assert_eq!(g.phase, Phase::GameOver);
+        assert_eq!(m.winner(), Some(Player::Player1));
+        assert_eq!(m.tick(Duration::from_secs(1)), Err(Error::MatchEnded));
+        assert!(matches!(m.new_game(), Err(Error::MatchEnded)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes() -> Result<(), Error> {
+        use crate::TimeControl;
+
+        let clock = Clock::new(
+            Duration::from_secs(90),
+            TimeControl::Bronstein(Duration::from_secs(8)),
+        );
+        let mut m = Match::new()
+            .with_points(3)
+            .with_variant(Variant::Plakoto)
+            .with_clock(clock);
+        let g = m.new_game()?;
+        g.resign(Player::Player0, Victory::Gammon)?;
+        // the Crawford game, in the middle of a turn
+        let g = m.new_game()?;
+        let _ = g.roll_with((6, 5))?;
+        g.play_die(crate::rules::Field::Point(23), 6)?;
+        m.tick(Duration::from_millis(1500))?;
+
+        let resumed = Match::from_bytes(&m.to_bytes())?;
+        assert_eq!(resumed.rules, m.rules);
+        assert_eq!(resumed.clock(), m.clock());
+        assert_eq!(resumed.score(), (0, 2));
+        assert_eq!(resumed.crawford_game(), Some(1));
+        for (a, b) in resumed.games.iter().zip(m.games.iter()) {
+            assert_eq!(a.board, b.board);
+            assert_eq!(a.dices, b.dices);
+            assert_eq!(a.moves, b.moves);
+            assert_eq!(a.phase, b.phase);
+            assert_eq!(a.record(), b.record());
+            assert_eq!(a.away(), b.away());
+        }
+        let mut resumed = resumed;
+        let g = resumed.game().ok_or(Error::GameEnded)?;
+        assert_eq!(g.dices.available(), vec![5]);
+
+        // a pending cube offer
+        let mut m = Match::new().with_points(0);
+        let g = m.new_game()?;
+        g.who_plays = Player::Player1;
+        g.offer_double(Player::Player1)?;
+        let mut resumed = Match::from_bytes(&m.to_bytes())?;
+        let g = resumed.game().ok_or(Error::GameEnded)?;
+        assert_eq!(g.phase, Phase::AwaitingCubeDecision);
+        g.take(Player::Player0)?;
+        assert_eq!(g.cube.value(), 2);
+
+        let bytes = m.to_bytes();
+        assert!(Match::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut version = bytes.clone();
+        version[4] = 2;
+        assert!(matches!(
+            Match::from_bytes(&version),
+            Err(Error::FormatInvalid)
+        ));
+        for i in 0..bytes.len() {
+            for value in [0x00, 0x07, 0x80, 0xff] {
+                let mut corrupted = bytes.clone();
+                corrupted[i] = value;
+                let _ = Match::from_bytes(&corrupted);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_save_load() -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("backgammon-{}.match", std::process::id()));
+        let mut m = Match::new().with_points(5);
+        let _ = m.new_game().map_err(io::Error::other)?;
+        m.save(&path)?;
+        let loaded = Match::load(&path)?;
+        assert_eq!(loaded.to_bytes(), m.to_bytes());
+
+        fs::write(&path, b"BGMF")?;
+        let e = Match::load(&path).map(|_| ()).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path)
+    }
+
     // test Display trait
     #[test]
     fn test_display() {
         let m = Match::new();
         assert_eq!(
             format!("{}", m),
-            "Match with rules: Rules { points: 7, beaver: false, raccoon: false, murphy: false, murphy_limit: 0, jacoby: false, crawford: true, holland: false } and Games: []"
+            "Match with rules: Rules { points: 7, beaver: false, raccoon: false, murphy: false, murphy_limit: 0, jacoby: false, crawford: true, holland: false, variant: Backgammon } and Games: []"
         );
     }
 }
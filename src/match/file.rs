@@ -0,0 +1,467 @@
+//! # Versioned Encoding of Matches
+use super::Match;
+use crate::formats::{bits_player, player_bits};
+use crate::game::{Game, Phase, Victory};
+use crate::record::{Event, GameRecord};
+use crate::rules::{Board, CheckerMove, Cube, Dices, Field, Player, PlayerBoard, Rules, Variant};
+use crate::{Clock, Error, TimeControl};
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// The bytes every encoded match starts with
+const MAGIC: &[u8; 4] = b"BGMF";
+
+/// The version of the encoding written by [`encode`]
+const VERSION: u8 = 1;
+
+/// The variants in the order of their encoding
+const VARIANTS: [Variant; 7] = [
+    Variant::Backgammon,
+    Variant::Nackgammon,
+    Variant::Hypergammon,
+    Variant::Portes,
+    Variant::Plakoto,
+    Variant::Fevga,
+    Variant::Narde,
+];
+
+/// Encode a match in the latest version of the encoding. All integers are little-endian.
+///
+/// After the magic bytes and the version follow the rules, the clock if any, and every game
+/// with its position, cube, dices, moves of the current turn, and record.
+pub(super) fn encode(m: &Match) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend(MAGIC);
+    bytes.push(VERSION);
+    write_rules(&mut bytes, &m.rules);
+    match &m.clock {
+        Some(clock) => {
+            bytes.push(1);
+            write_clock(&mut bytes, clock);
+        }
+        None => bytes.push(0),
+    }
+    bytes.extend((m.games.len() as u32).to_le_bytes());
+    for game in m.games.iter() {
+        write_game(&mut bytes, game);
+    }
+    bytes
+}
+
+/// Decode a match, unknown versions of the encoding are an error. The games are set up as if
+/// started by [`Match::new_game`], hence their Crawford flags and scores follow from the games
+/// before them.
+pub(super) fn decode(bytes: &[u8]) -> Result<Match, Error> {
+    let mut r = Reader { bytes };
+    if r.take(4)? != MAGIC || r.u8()? != VERSION {
+        return Err(Error::FormatInvalid);
+    }
+    let mut m = Match {
+        rules: r.rules()?,
+        ..Default::default()
+    };
+    m.clock = match r.u8()? {
+        0 => None,
+        1 => Some(r.clock()?),
+        _ => return Err(Error::FormatInvalid),
+    };
+    for _i in 0..r.u32()? {
+        if m.games.last().is_some_and(|g| g.phase != Phase::GameOver) {
+            return Err(Error::FormatInvalid);
+        }
+        let mut game = m.next_game();
+        r.game(&mut game)?;
+        m.games.push(game);
+    }
+    if !r.bytes.is_empty() {
+        return Err(Error::FormatInvalid);
+    }
+    Ok(m)
+}
+
+fn write_rules(bytes: &mut Vec<u8>, rules: &Rules) {
+    bytes.extend(rules.points.to_le_bytes());
+    bytes.push(
+        u8::from(rules.beaver)
+            | u8::from(rules.raccoon) << 1
+            | u8::from(rules.murphy) << 2
+            | u8::from(rules.jacoby) << 3
+            | u8::from(rules.crawford) << 4
+            | u8::from(rules.holland) << 5,
+    );
+    bytes.push(rules.murphy_limit);
+    bytes.push(
+        VARIANTS
+            .iter()
+            .position(|v| *v == rules.variant)
+            .unwrap_or_default() as u8,
+    );
+}
+
+fn write_duration(bytes: &mut Vec<u8>, duration: Duration) {
+    bytes.extend(duration.as_secs().to_le_bytes());
+    bytes.extend(duration.subsec_nanos().to_le_bytes());
+}
+
+fn write_clock(bytes: &mut Vec<u8>, clock: &Clock) {
+    let (kind, duration) = match clock.control {
+        TimeControl::Delay(d) => (0, d),
+        TimeControl::Bronstein(d) => (1, d),
+        TimeControl::Fischer(d) => (2, d),
+    };
+    bytes.push(kind);
+    write_duration(bytes, duration);
+    write_duration(bytes, clock.reserve.0);
+    write_duration(bytes, clock.reserve.1);
+    bytes.push(player_bits(clock.running));
+    write_duration(bytes, clock.used);
+}
+
+/// Write the checkers of both players as raw counts, which also holds the positions of the
+/// variants not covered by [`Board::to_bytes`]
+fn write_board(bytes: &mut Vec<u8>, board: &Board) {
+    let sides = [Player::Player0, Player::Player1].map(|player| board.side(player));
+    for side in sides.iter().flatten() {
+        bytes.extend(side.board);
+        bytes.push(side.bar);
+        bytes.push(side.off);
+    }
+    for pins in board.pins {
+        bytes.extend(pins.to_le_bytes());
+    }
+}
+
+/// Write a field as 0 for off, 1 to 24 for the points, and 25 for the bar
+fn write_field(bytes: &mut Vec<u8>, field: Field) {
+    bytes.push((field.index() + 1) as u8);
+}
+
+fn write_move(bytes: &mut Vec<u8>, mv: &CheckerMove) {
+    write_field(bytes, mv.from);
+    write_field(bytes, mv.to);
+    bytes.push(mv.steps.len() as u8);
+    bytes.extend(mv.steps.iter());
+    bytes.push(mv.hits.len() as u8);
+    for hit in mv.hits.iter() {
+        write_field(bytes, *hit);
+    }
+}
+
+fn write_event(bytes: &mut Vec<u8>, event: &Event) {
+    match event {
+        Event::Roll { player, dices } => {
+            bytes.extend([0, player_bits(*player), dices.0, dices.1]);
+        }
+        Event::Move { player, mv } => {
+            bytes.extend([1, player_bits(*player)]);
+            write_move(bytes, mv);
+        }
+        Event::AutomaticDouble { value } => {
+            bytes.extend([2, value.trailing_zeros() as u8]);
+        }
+        Event::CubeOffer { player, value } => {
+            bytes.extend([3, player_bits(*player), value.trailing_zeros() as u8]);
+        }
+        Event::CubeTake { player } => bytes.extend([4, player_bits(*player)]),
+        Event::Beaver { player, value } => {
+            bytes.extend([5, player_bits(*player), value.trailing_zeros() as u8]);
+        }
+        Event::Raccoon { player, value } => {
+            bytes.extend([6, player_bits(*player), value.trailing_zeros() as u8]);
+        }
+        Event::CubeDrop { player } => bytes.extend([7, player_bits(*player)]),
+        Event::Resign { player, victory } => {
+            let victory = match victory {
+                Victory::Single => 0,
+                Victory::Gammon => 1,
+                Victory::Backgammon => 2,
+            };
+            bytes.extend([8, player_bits(*player), victory]);
+        }
+    }
+}
+
+fn write_game(bytes: &mut Vec<u8>, game: &Game) {
+    write_board(bytes, &game.board);
+    bytes.push(game.cube.value().trailing_zeros() as u8);
+    bytes.push(player_bits(game.cube.owner()));
+    let consumed = game.dices.consumed;
+    bytes.extend([
+        game.dices.values.0,
+        game.dices.values.1,
+        u8::from(consumed.0)
+            | u8::from(consumed.1) << 1
+            | u8::from(consumed.2) << 2
+            | u8::from(consumed.3) << 3,
+    ]);
+    bytes.push(player_bits(game.who_plays));
+    bytes.push(match game.phase {
+        Phase::AwaitingRoll => 0,
+        Phase::AwaitingCubeDecision => 1,
+        Phase::AwaitingMove => 2,
+        Phase::GameOver => 3,
+    });
+    bytes.push(game.moves.len() as u8);
+    for mv in game.moves.iter() {
+        write_move(bytes, mv);
+    }
+
+    let record = game.record();
+    write_board(bytes, record.start());
+    match record.seed() {
+        Some(seed) => {
+            bytes.push(1);
+            bytes.extend(seed.to_le_bytes());
+        }
+        None => bytes.push(0),
+    }
+    bytes.extend((record.events().len() as u32).to_le_bytes());
+    for event in record.events() {
+        write_event(bytes, event);
+    }
+}
+
+/// Reads the values of an encoded match in the order written, failing at the end of the bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::FormatInvalid);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut le = [0; 4];
+        le.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(le))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        let mut le = [0; 8];
+        le.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(le))
+    }
+
+    fn flag(&mut self) -> Result<bool, Error> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::FormatInvalid),
+        }
+    }
+
+    fn player(&mut self) -> Result<Player, Error> {
+        bits_player(self.u8()?)
+    }
+
+    /// Read a cube value written as the logarithm to the base 2
+    fn cube_value(&mut self) -> Result<u64, Error> {
+        match self.u8()? {
+            exponent @ 0..=63 => Ok(1 << exponent),
+            _ => Err(Error::FormatInvalid),
+        }
+    }
+
+    fn duration(&mut self) -> Result<Duration, Error> {
+        let secs = self.u64()?;
+        let nanos = self.u32()?;
+        if nanos >= 1_000_000_000 {
+            return Err(Error::FormatInvalid);
+        }
+        Ok(Duration::new(secs, nanos))
+    }
+
+    fn rules(&mut self) -> Result<Rules, Error> {
+        let points = self.u32()?;
+        let flags = self.u8()?;
+        if flags >> 6 != 0 {
+            return Err(Error::FormatInvalid);
+        }
+        let murphy_limit = self.u8()?;
+        let variant = *VARIANTS
+            .get(usize::from(self.u8()?))
+            .ok_or(Error::FormatInvalid)?;
+        Ok(Rules {
+            points,
+            beaver: flags & 1 == 1,
+            raccoon: flags >> 1 & 1 == 1,
+            murphy: flags >> 2 & 1 == 1,
+            murphy_limit,
+            jacoby: flags >> 3 & 1 == 1,
+            crawford: flags >> 4 & 1 == 1,
+            holland: flags >> 5 & 1 == 1,
+            variant,
+        })
+    }
+
+    fn clock(&mut self) -> Result<Clock, Error> {
+        let control = match (self.u8()?, self.duration()?) {
+            (0, d) => TimeControl::Delay(d),
+            (1, d) => TimeControl::Bronstein(d),
+            (2, d) => TimeControl::Fischer(d),
+            _ => return Err(Error::FormatInvalid),
+        };
+        let mut clock = Clock::new(Duration::ZERO, control);
+        clock.reserve = (self.duration()?, self.duration()?);
+        clock.running = self.player()?;
+        clock.used = self.duration()?;
+        Ok(clock)
+    }
+
+    /// Read a board of the given variant, which has to be a valid position
+    fn board(&mut self, variant: Variant) -> Result<Board, Error> {
+        let mut side = || -> Result<PlayerBoard, Error> {
+            let mut board = [0; 24];
+            board.copy_from_slice(self.take(24)?);
+            Ok(PlayerBoard {
+                board,
+                bar: self.u8()?,
+                off: self.u8()?,
+            })
+        };
+        let sides = [side()?, side()?];
+        let pins = [self.u32()?, self.u32()?];
+        let board = Board::from_sides(sides, variant.occupancy(), pins)?;
+        board.validate()?;
+        Ok(board)
+    }
+
+    fn field(&mut self) -> Result<Field, Error> {
+        match self.u8()? {
+            0 => Ok(Field::Off),
+            25 => Ok(Field::Bar),
+            point @ 1..=24 => Ok(Field::Point(usize::from(point - 1))),
+            _ => Err(Error::FormatInvalid),
+        }
+    }
+
+    fn dice(&mut self) -> Result<u8, Error> {
+        match self.u8()? {
+            dice @ 1..=6 => Ok(dice),
+            _ => Err(Error::FormatInvalid),
+        }
+    }
+
+    fn checker_move(&mut self) -> Result<CheckerMove, Error> {
+        let from = self.field()?;
+        let to = self.field()?;
+        let steps = (0..self.u8()?)
+            .map(|_| self.dice())
+            .collect::<Result<Vec<u8>, Error>>()?;
+        let hits = (0..self.u8()?)
+            .map(|_| self.field())
+            .collect::<Result<Vec<Field>, Error>>()?;
+        if steps.is_empty() || steps.len() > 4 || hits.len() > 4 {
+            return Err(Error::FormatInvalid);
+        }
+        Ok(CheckerMove {
+            from,
+            to,
+            steps,
+            hits,
+        })
+    }
+
+    fn event(&mut self) -> Result<Event, Error> {
+        Ok(match self.u8()? {
+            0 => Event::Roll {
+                player: self.player()?,
+                dices: (self.dice()?, self.dice()?),
+            },
+            1 => Event::Move {
+                player: self.player()?,
+                mv: self.checker_move()?,
+            },
+            2 => Event::AutomaticDouble {
+                value: self.cube_value()?,
+            },
+            3 => Event::CubeOffer {
+                player: self.player()?,
+                value: self.cube_value()?,
+            },
+            4 => Event::CubeTake {
+                player: self.player()?,
+            },
+            5 => Event::Beaver {
+                player: self.player()?,
+                value: self.cube_value()?,
+            },
+            6 => Event::Raccoon {
+                player: self.player()?,
+                value: self.cube_value()?,
+            },
+            7 => Event::CubeDrop {
+                player: self.player()?,
+            },
+            8 => Event::Resign {
+                player: self.player()?,
+                victory: match self.u8()? {
+                    0 => Victory::Single,
+                    1 => Victory::Gammon,
+                    2 => Victory::Backgammon,
+                    _ => return Err(Error::FormatInvalid),
+                },
+            },
+            _ => return Err(Error::FormatInvalid),
+        })
+    }
+
+    /// Read the state of a game into a game set up for the match
+    fn game(&mut self, game: &mut Game) -> Result<(), Error> {
+        let variant = game.rules.variant;
+        game.board = self.board(variant)?;
+        let mut cube = Cube::default();
+        cube.set(self.cube_value()?)?;
+        cube.set_owner(self.player()?);
+        game.cube = cube;
+        let values = (self.u8()?, self.u8()?);
+        if values.0 > 6 || values.1 > 6 || (values.0 == 0) != (values.1 == 0) {
+            return Err(Error::FormatInvalid);
+        }
+        let consumed = self.u8()?;
+        if consumed >> 4 != 0 {
+            return Err(Error::FormatInvalid);
+        }
+        game.dices = Dices {
+            values,
+            consumed: (
+                consumed & 1 == 1,
+                consumed >> 1 & 1 == 1,
+                consumed >> 2 & 1 == 1,
+                consumed >> 3 & 1 == 1,
+            ),
+        };
+        game.who_plays = self.player()?;
+        game.phase = match self.u8()? {
+            0 => Phase::AwaitingRoll,
+            1 => Phase::AwaitingCubeDecision,
+            2 => Phase::AwaitingMove,
+            3 => Phase::GameOver,
+            _ => return Err(Error::FormatInvalid),
+        };
+        game.moves = (0..self.u8()?)
+            .map(|_| self.checker_move())
+            .collect::<Result<Vec<CheckerMove>, Error>>()?;
+
+        let mut record = GameRecord::new(self.board(variant)?);
+        if self.flag()? {
+            record.set_seed(Some(self.u64()?));
+        }
+        for _i in 0..self.u32()? {
+            record.push(self.event()?);
+        }
+        game.set_record(record);
+        Ok(())
+    }
+}
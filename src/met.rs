@@ -0,0 +1,477 @@
+//! # Match Equity Tables
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The match length covered by the default table
+const LENGTH: usize = 15;
+
+/// The Kazaross-XG2 table before the Crawford game in percent, as published by Neil Kazaross,
+/// rounded to tenths
+#[rustfmt::skip]
+const KAZAROSS_XG2: [[f64; LENGTH]; LENGTH] = [
+    [50.0, 67.9, 75.0, 81.5, 84.2, 89.0, 90.9, 93.6, 94.6, 96.2, 96.9, 97.8, 98.2, 98.8, 99.0],
+    [32.1, 50.0, 59.9, 66.9, 74.4, 79.9, 84.2, 87.5, 90.4, 92.5, 94.3, 95.5, 96.6, 97.4, 98.0],
+    [25.0, 40.1, 50.0, 57.1, 64.3, 71.0, 76.0, 80.5, 84.1, 87.1, 89.5, 91.7, 93.3, 94.7, 95.9],
+    [18.5, 33.1, 42.9, 50.0, 57.4, 64.2, 69.6, 74.6, 78.8, 82.4, 85.4, 88.2, 90.3, 92.2, 93.7],
+    [15.8, 25.6, 35.7, 42.6, 50.0, 56.7, 62.5, 68.0, 72.6, 76.9, 80.4, 83.7, 86.3, 88.8, 90.7],
+    [11.0, 20.1, 29.0, 35.8, 43.3, 50.0, 56.1, 61.9, 66.9, 71.6, 75.6, 79.5, 82.6, 85.5, 87.8],
+    [9.1, 15.8, 24.0, 30.4, 37.5, 43.9, 50.0, 56.0, 61.1, 66.1, 70.4, 74.5, 78.0, 81.3, 84.0],
+    [6.4, 12.5, 19.5, 25.4, 32.0, 38.1, 44.0, 50.0, 55.2, 60.4, 64.9, 69.3, 73.1, 76.8, 79.8],
+    [5.4, 9.6, 15.9, 21.2, 27.4, 33.1, 38.9, 44.8, 50.0, 55.3, 60.0, 64.6, 68.6, 72.5, 75.8],
+    [3.8, 7.5, 12.9, 17.6, 23.1, 28.4, 33.9, 39.6, 44.7, 50.0, 54.8, 59.6, 63.8, 68.0, 71.6],
+    [3.1, 5.7, 10.5, 14.6, 19.6, 24.4, 29.6, 35.1, 40.0, 45.2, 50.0, 54.9, 59.2, 63.6, 67.3],
+    [2.2, 4.5, 8.3, 11.8, 16.3, 20.5, 25.5, 30.7, 35.4, 40.4, 45.1, 50.0, 54.4, 58.9, 62.8],
+    [1.8, 3.4, 6.7, 9.7, 13.7, 17.4, 22.0, 26.9, 31.4, 36.2, 40.8, 45.6, 50.0, 54.6, 58.6],
+    [1.2, 2.6, 5.3, 7.8, 11.2, 14.5, 18.7, 23.2, 27.5, 32.0, 36.4, 41.1, 45.4, 50.0, 54.1],
+    [1.0, 2.0, 4.1, 6.3, 9.3, 12.2, 16.0, 20.2, 24.2, 28.4, 32.7, 37.2, 41.4, 45.9, 50.0],
+];
+
+/// The Kazaross-XG2 table after the Crawford game in percent, for the trailer 1-away to 15-away
+#[rustfmt::skip]
+const KAZAROSS_XG2_POST_CRAWFORD: [f64; LENGTH] = [
+    50.0, 48.8, 31.9, 30.5, 19.0, 18.2, 11.6, 11.1, 7.0, 6.7, 4.3, 4.1, 2.6, 2.5, 1.6,
+];
+
+/// Holds a match equity table, i.e. the probabilities of a player to win the match depending on
+/// the points both players still need, before the next game starts.
+/// ```
+/// use backgammon::met::Met;
+///
+/// let met = Met::default();
+/// assert_eq!(met.name, "Kazaross-XG2");
+/// assert_eq!(met.win(3, 3, false), Some(0.5));
+/// // the leader at 1-away is the favorite in the Crawford game
+/// assert!((met.win(1, 3, false).unwrap() - 0.75).abs() < 1e-9);
+/// assert_eq!(met.win(0, 3, false), Some(1.0));
+/// assert_eq!(met.win(20, 3, false), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Met {
+    /// The name of the table
+    pub name: String,
+    /// A description of the table, e.g. how it was computed
+    pub description: String,
+    /// The probabilities to win of a player a-away against a player b-away at index a - 1 and
+    /// b - 1, where the row of 1-away holds the Crawford game
+    pre_crawford: Vec<Vec<f64>>,
+    /// The probabilities to win of the trailer n-away at index n - 1 against the leader 1-away
+    /// after the Crawford game
+    post_crawford: Vec<f64>,
+}
+
+/// The default table is Kazaross-XG2, see [`Met::kazaross_xg2`]
+impl Default for Met {
+    fn default() -> Self {
+        Met::kazaross_xg2()
+    }
+}
+
+impl Met {
+    /// Create a table from the probabilities to win before and after the Crawford game. The
+    /// table before the Crawford game has to be square, with a row for every number of points
+    /// away starting at 1-away, which holds the Crawford game. The table after the Crawford game
+    /// holds the probabilities of the trailer starting at 1-away, and has the same length.
+    pub fn new(
+        name: &str,
+        pre_crawford: Vec<Vec<f64>>,
+        post_crawford: Vec<f64>,
+    ) -> Result<Self, Error> {
+        let length = pre_crawford.len();
+        if length == 0
+            || post_crawford.len() != length
+            || pre_crawford.iter().any(|row| row.len() != length)
+            || pre_crawford
+                .iter()
+                .flatten()
+                .chain(&post_crawford)
+                .any(|p| !(0.0..=1.0).contains(p))
+        {
+            return Err(Error::FormatInvalid);
+        }
+        Ok(Met {
+            name: name.to_string(),
+            description: String::new(),
+            pre_crawford,
+            post_crawford,
+        })
+    }
+
+    /// Returns the Kazaross-XG2 table up to 15 points, published by Neil Kazaross and computed
+    /// from rollouts with eXtreme Gammon, the table most players and programs use today. The
+    /// probabilities are rounded to tenths of a percent. For longer matches, load a table of GNU
+    /// Backgammon or use [`Met::generated`].
+    /// ```
+    /// use backgammon::met::Met;
+    ///
+    /// let met = Met::kazaross_xg2();
+    /// assert_eq!(met.length(), 15);
+    /// assert!((met.win(2, 4, false).unwrap() - 0.669).abs() < 1e-9);
+    /// ```
+    pub fn kazaross_xg2() -> Self {
+        let percent = |row: &[f64]| row.iter().map(|p| p / 100.0).collect::<Vec<_>>();
+        Met {
+            name: "Kazaross-XG2".to_string(),
+            description: "Match equity table by Neil Kazaross, computed from rollouts with \
+                          eXtreme Gammon 2"
+                .to_string(),
+            pre_crawford: KAZAROSS_XG2.iter().map(|row| percent(row)).collect(),
+            post_crawford: percent(&KAZAROSS_XG2_POST_CRAWFORD),
+        }
+    }
+
+    /// Compute a table for the given match length from a simple model, in which both players are
+    /// equally strong and win the given share of their games as gammon. Games before the Crawford
+    /// game are played for one point, and after the Crawford game, the trailer doubles at once.
+    /// ```
+    /// use backgammon::met::Met;
+    ///
+    /// let met = Met::generated(7, 0.0).unwrap();
+    /// // without gammons, the trailer wins after the Crawford game if winning all remaining games
+    /// assert_eq!(met.win(3, 1, true), Some(0.25));
+    /// ```
+    pub fn generated(length: u32, gammon_rate: f64) -> Result<Self, Error> {
+        let n = length as usize;
+        if n == 0 || !(0.0..=1.0).contains(&gammon_rate) {
+            return Err(Error::FormatInvalid);
+        }
+        let (single, gammon) = (0.5 * (1.0 - gammon_rate), 0.5 * gammon_rate);
+
+        // the trailer doubles at once, so every game counts two or four points
+        let mut post = vec![0.5; n + 1];
+        let post_at = |post: &[f64], away: isize| match away {
+            ..=0 => 1.0,
+            _ => post[away as usize],
+        };
+        for away in 2..=n {
+            let a = away as isize;
+            post[away] = single * post_at(&post, a - 2) + gammon * post_at(&post, a - 4);
+        }
+
+        // the probability to win of the leader 1-away in the Crawford game against the trailer
+        let crawford = |away: usize| match away {
+            1 => 0.5,
+            _ => {
+                let a = away as isize;
+                1.0 - single * post_at(&post, a - 1) - gammon * post_at(&post, a - 2)
+            }
+        };
+
+        let mut pre = vec![vec![0.5; n + 1]; n + 1];
+        for (away, row) in pre.iter_mut().enumerate().skip(1) {
+            row[1] = 1.0 - crawford(away);
+        }
+        pre[1] = (0..=n).map(crawford).collect();
+        let pre_at = |pre: &[Vec<f64>], away: isize, opponent: isize| match (away, opponent) {
+            (..=0, _) => 1.0,
+            (_, ..=0) => 0.0,
+            _ => pre[away as usize][opponent as usize],
+        };
+        for away in 2..=n {
+            for opponent in 2..=n {
+                let (a, b) = (away as isize, opponent as isize);
+                pre[away][opponent] = single * pre_at(&pre, a - 1, b)
+                    + gammon * pre_at(&pre, a - 2, b)
+                    + single * pre_at(&pre, a, b - 1)
+                    + gammon * pre_at(&pre, a, b - 2);
+            }
+        }
+
+        let mut met = Met::new(
+            &format!("Model with {:.0}% gammons", 100.0 * gammon_rate),
+            pre.into_iter()
+                .skip(1)
+                .map(|row| row[1..].to_vec())
+                .collect(),
+            post[1..].to_vec(),
+        )?;
+        met.description = "Equal players, cubeless games before the Crawford game, and the \
+                           trailer doubling at once after it"
+            .to_string();
+        Ok(met)
+    }
+
+    /// Returns the largest number of points away the table covers
+    pub fn length(&self) -> u32 {
+        self.pre_crawford.len() as u32
+    }
+
+    /// Returns the probability to win the match of a player who needs the given points against
+    /// an opponent who needs the given points, if the table covers the score. After the Crawford
+    /// game, the match equity of a player 1-away differs from the one in the Crawford game.
+    pub fn win(&self, away: u32, opponent: u32, post_crawford: bool) -> Option<f64> {
+        match (away, opponent) {
+            (0, _) => return Some(1.0),
+            (_, 0) => return Some(0.0),
+            _ => (),
+        }
+        if away > self.length() || opponent > self.length() {
+            return None;
+        }
+        let (a, b) = (away as usize - 1, opponent as usize - 1);
+        match (post_crawford, away, opponent) {
+            (true, 1, _) => Some(1.0 - self.post_crawford[b]),
+            (true, _, 1) => Some(self.post_crawford[a]),
+            _ => Some(self.pre_crawford[a][b]),
+        }
+    }
+}
+
+/// Replace the characters which are not permitted in the text of XML elements
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Replace the entities of XML by the characters they stand for
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Returns the attributes and the contents of all elements with the given tag
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<(&'a str, &'a str)> {
+    let (open, close) = (format!("<{}", tag), format!("</{}>", tag));
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        // the tag has to end here, e.g. <row> but not <rows>
+        if !rest.starts_with(|c: char| c == '>' || c.is_whitespace()) {
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let attributes = &rest[..end];
+        rest = &rest[end + 1..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        found.push((attributes.trim(), &rest[..end]));
+        rest = &rest[end + close.len()..];
+    }
+    found
+}
+
+/// Returns the rows of a table, which has to be given explicitly
+fn table(xml: &str, tag: &str) -> Result<Vec<Vec<f64>>, Error> {
+    let (attributes, contents) = elements(xml, tag)
+        .into_iter()
+        .next()
+        .ok_or(Error::FormatInvalid)?;
+    if attributes.contains("type=") && !attributes.contains("type=\"explicit\"") {
+        return Err(Error::FormatInvalid);
+    }
+    elements(contents, "row")
+        .into_iter()
+        .map(|(_, row)| {
+            elements(row, "me")
+                .into_iter()
+                .map(|(_, p)| p.trim().parse().map_err(|_| Error::FormatInvalid))
+                .collect()
+        })
+        .collect()
+}
+
+/// Parse a match equity table file of GNU Backgammon, with tables given explicitly
+impl FromStr for Met {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // drop comments, which could contain tags
+        let mut xml = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(start) = rest.find("<!--") {
+            xml.push_str(&rest[..start]);
+            let end = rest[start..].find("-->").ok_or(Error::FormatInvalid)?;
+            rest = &rest[start + end + 3..];
+        }
+        xml.push_str(rest);
+
+        let (_, root) = elements(&xml, "match-equity-table")
+            .into_iter()
+            .next()
+            .ok_or(Error::FormatInvalid)?;
+        let text = |tag| {
+            elements(root, tag)
+                .first()
+                .map(|(_, s)| unescape(s.trim()))
+                .unwrap_or_default()
+        };
+        let post_crawford = table(root, "post-crawford-table")?
+            .into_iter()
+            .next()
+            .ok_or(Error::FormatInvalid)?;
+        let mut met = Met::new(
+            &text("name"),
+            table(root, "pre-crawford-table")?,
+            post_crawford,
+        )?;
+        met.description = text("description");
+        Ok(met)
+    }
+}
+
+/// Write the table as match equity table file of GNU Backgammon
+impl fmt::Display for Met {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let row = |row: &[f64]| {
+            row.iter()
+                .map(|p| format!("<me>{:.6}</me>", p))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        writeln!(f, "<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>")?;
+        writeln!(f, "<!DOCTYPE match-equity-table SYSTEM \"met.dtd\">")?;
+        writeln!(f, "<match-equity-table>")?;
+        writeln!(f, "  <info>")?;
+        writeln!(f, "    <name>{}</name>", escape(&self.name))?;
+        writeln!(
+            f,
+            "    <description>{}</description>",
+            escape(&self.description)
+        )?;
+        writeln!(f, "    <length>{}</length>", self.length())?;
+        writeln!(f, "  </info>")?;
+        writeln!(f, "  <pre-crawford-table type=\"explicit\">")?;
+        for r in &self.pre_crawford {
+            writeln!(f, "    <row> {} </row>", row(r))?;
+        }
+        writeln!(f, "  </pre-crawford-table>")?;
+        writeln!(
+            f,
+            "  <post-crawford-table player=\"both\" type=\"explicit\">"
+        )?;
+        writeln!(f, "    <row> {} </row>", row(&self.post_crawford))?;
+        writeln!(f, "  </post-crawford-table>")?;
+        writeln!(f, "</match-equity-table>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GNUBG: &str = r#"<?xml version="1.0" encoding="ISO-8859-1"?>
+<!DOCTYPE match-equity-table SYSTEM "met.dtd">
+<match-equity-table>
+  <!-- a <row> in a comment is ignored -->
+  <info>
+    <name>Small &amp; simple</name>
+    <description>For testing</description>
+    <length>3</length>
+  </info>
+  <pre-crawford-table type="explicit">
+    <row> <me>0.5</me> <me>0.7</me> <me>0.8</me> </row>
+    <row> <me>0.3</me> <me>0.5</me> <me>0.6</me> </row>
+    <row> <me>0.2</me> <me>0.4</me> <me>0.5</me> </row>
+  </pre-crawford-table>
+  <post-crawford-table player="both" type="explicit">
+    <row> <me>0.5</me> <me>0.49</me> <me>0.32</me> </row>
+  </post-crawford-table>
+</match-equity-table>
+"#;
+
+    #[test]
+    fn test_kazaross_xg2() {
+        let met = Met::default();
+        assert_eq!(met.length(), 15);
+        assert_eq!(met.win(2, 2, false), Some(0.5));
+        // the leader 1-away wins about 68% of the Crawford game against the trailer 2-away
+        assert!((met.win(1, 2, false).unwrap() - 0.68).abs() < 0.005);
+        assert_eq!(met.win(1, 1, true), Some(0.5));
+        assert!((met.win(2, 1, true).unwrap() - 0.488).abs() < 1e-9);
+        assert_eq!(met.win(16, 1, false), None);
+        for away in 1..=15 {
+            for opponent in 1..=15 {
+                let p = met.win(away, opponent, false).unwrap_or_default();
+                let q = met.win(opponent, away, false).unwrap_or_default();
+                assert!((p + q - 1.0).abs() < 1e-9);
+                if away > 1 {
+                    assert!(met.win(away - 1, opponent, false) >= Some(p));
+                }
+            }
+            if away > 1 {
+                // the trailer is worse off the more points it needs, but better off once the
+                // Crawford game is over
+                assert!(met.win(away, 1, true) <= met.win(away - 1, 1, true));
+                assert!(met.win(away, 1, true) >= met.win(away, 1, false));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated() -> Result<(), Error> {
+        let met = Met::generated(25, 0.2)?;
+        assert_eq!(met.length(), 25);
+        for away in 1..=25 {
+            for opponent in 1..=25 {
+                let p = met.win(away, opponent, false).unwrap_or_default();
+                let q = met.win(opponent, away, false).unwrap_or_default();
+                assert!((p + q - 1.0).abs() < 1e-9);
+                // needing fewer points is better
+                if away > 1 {
+                    assert!(met.win(away - 1, opponent, false) >= Some(p));
+                }
+            }
+        }
+        assert_eq!(met.win(1, 1, true), Some(0.5));
+        assert!(met.win(3, 1, true) < met.win(2, 1, true));
+        assert_eq!(met.win(2, 1, true), met.win(1, 2, true).map(|p| 1.0 - p));
+
+        let met = Met::generated(5, 0.0)?;
+        assert_eq!(met.win(2, 1, true), Some(0.5));
+        assert_eq!(met.win(1, 2, false), Some(0.75));
+        assert_eq!(met.win(2, 2, false), Some(0.5));
+        assert!(matches!(Met::generated(0, 0.2), Err(Error::FormatInvalid)));
+        assert!(matches!(Met::generated(5, 1.2), Err(Error::FormatInvalid)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_gnubg() -> Result<(), Error> {
+        let met = GNUBG.parse::<Met>()?;
+        assert_eq!(met.name, "Small & simple");
+        assert_eq!(met.description, "For testing");
+        assert_eq!(met.length(), 3);
+        assert_eq!(met.win(1, 2, false), Some(0.7));
+        assert_eq!(met.win(3, 2, false), Some(0.4));
+        assert_eq!(met.win(3, 1, true), Some(0.32));
+        assert_eq!(met.win(4, 1, true), None);
+
+        let written = met.to_string();
+        assert!(written.contains("<name>Small &amp; simple</name>"));
+        assert_eq!(written.parse::<Met>()?, met);
+        let met = Met::default();
+        assert_eq!(met.to_string().parse::<Met>()?.to_string(), met.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            "<match-equity-table></match-equity-table>".parse::<Met>(),
+            Err(Error::FormatInvalid)
+        ));
+        let zadeh = GNUBG.replace(
+            "<pre-crawford-table type=\"explicit\">",
+            "<pre-crawford-table type=\"zadeh\">",
+        );
+        assert!(matches!(zadeh.parse::<Met>(), Err(Error::FormatInvalid)));
+        let short = GNUBG.replace("<me>0.32</me>", "");
+        assert!(matches!(short.parse::<Met>(), Err(Error::FormatInvalid)));
+        assert!(matches!(
+            Met::new("", vec![vec![0.5, 1.5], vec![0.5, 0.5]], vec![0.5, 0.5]),
+            Err(Error::FormatInvalid)
+        ));
+    }
+}
@@ -0,0 +1,1223 @@
+//! # Evaluate Positions with the Neural Networks of GNU Backgammon
+//!
+//! [GNU Backgammon](https://www.gnu.org/software/gnubg/) evaluates positions with three neural
+//! networks, one for positions with contact, one for crashed positions, in which a side has few
+//! checkers left in play, and one for races. [`Gnubg`](crate::neuralnet::Gnubg) reads their
+//! weights from the text file `gnubg.weights` or from the binary file `gnubg.wd`, encodes
+//! positions into the same inputs as gnubg, and implements [`Evaluator`](crate::eval::Evaluator).
+//! The weights are part of GNU Backgammon and are not shipped with this crate.
+//! ```no_run
+//! use backgammon::eval::Evaluator;
+//! use backgammon::neuralnet::Gnubg;
+//! use backgammon::rules::{Board, Player};
+//!
+//! let gnubg: Gnubg = std::fs::read_to_string("gnubg.weights")?.parse()?;
+//! let evaluation = gnubg.evaluate(&Board::new(), Player::Player0)?;
+//! println!("{}", evaluation);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use crate::bearoff::database;
+use crate::eval::{classify, finished, sides, Evaluation, Evaluator, PositionClass};
+use crate::rules::{Board, Player, PlayerBoard};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::{FromStr, SplitWhitespace};
+use std::sync::OnceLock;
+
+/// The version in the header of the weights in text format
+const VERSION: &str = "1.00";
+/// The magic number starting the weights in binary format
+const MAGIC: f32 = 472.3782;
+/// The version of the weights in binary format
+const VERSION_BINARY: f32 = 1.0;
+
+/// The outputs of all networks: win, win gammon, win backgammon, lose gammon, lose backgammon
+const OUTPUTS: usize = 5;
+/// The inputs encoding the checkers on the points and on the bar of one side
+const POINT_INPUTS: usize = 25 * 4;
+/// The inputs of the contact and the crashed network
+const CONTACT_INPUTS: usize = 2 * (POINT_INPUTS + MORE_INPUTS);
+/// The inputs of the race network for one side: the checkers on 23 points, the checkers off,
+/// and the crossovers
+const HALF_RACE_INPUTS: usize = 23 * 4 + 14 + 1;
+/// The inputs of the race network
+const RACE_INPUTS: usize = 2 * HALF_RACE_INPUTS;
+/// The input of the race network for one checker off
+const RACE_OFF: usize = 23 * 4;
+/// The input of the race network for the crossovers
+const RACE_CROSS: usize = RACE_OFF + 14;
+
+// The features of one side in contact and crashed positions, in the order of gnubg's eval.c
+/// The checkers off, in three ranges
+const OFF1: usize = 0;
+const OFF2: usize = 1;
+const OFF3: usize = 2;
+/// The pips needed to break contact
+const BREAK_CONTACT: usize = 3;
+/// The location of the rearmost checker
+const BACK_CHEQUER: usize = 4;
+/// The location of the rearmost anchor
+const BACK_ANCHOR: usize = 5;
+/// The location of the foremost anchor in the home board of the opponent
+const FORWARD_ANCHOR: usize = 6;
+/// The average pips the opponent loses from hits
+const PIPLOSS: usize = 7;
+/// The rolls hitting at least one checker
+const P1: usize = 8;
+/// The rolls hitting at least two checkers
+const P2: usize = 9;
+/// The rolls with which the rearmost checker of the opponent escapes
+const BACKESCAPES: usize = 10;
+/// The containment of the checkers of the opponent, from the rearmost one
+const ACONTAIN: usize = 11;
+const ACONTAIN2: usize = 12;
+/// The containment of the checkers of the opponent, up to the home board
+const CONTAIN: usize = 13;
+const CONTAIN2: usize = 14;
+/// The rolls with which the checkers out of the home board escape, weighted by their distance
+const MOBILITY: usize = 15;
+/// The one-sided second moment of the checkers
+const MOMENT2: usize = 16;
+/// The average pips lost on the bar
+const ENTER: usize = 17;
+/// The probability of not entering a checker from the bar
+const ENTER2: usize = 18;
+/// The pips which can be played without breaking the home board
+const TIMING: usize = 19;
+/// The connectivity of the points made
+const BACKBONE: usize = 20;
+/// The checkers in a backgame with two or more anchors
+const BACKG: usize = 21;
+/// The checkers in a backgame with a single anchor
+const BACKG1: usize = 22;
+/// The pips of the checkers which have passed the opponent
+const FREEPIP: usize = 23;
+/// The rolls with which the rearmost checker of the opponent escapes past the first point made
+const BACKRESCAPES: usize = 24;
+/// The features of one side
+const MORE_INPUTS: usize = 25;
+
+/// The checkers of one side on the points 0 to 23 and on the bar at index 24
+type Side = [i32; 25];
+
+/// Holds a feed-forward neural network with one hidden layer and sigmoid activations, as used by
+/// GNU Backgammon
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NeuralNet {
+    inputs: usize,
+    hidden: usize,
+    outputs: usize,
+    /// The number of positions the network was trained with
+    trained: u64,
+    beta_hidden: f32,
+    beta_output: f32,
+    /// The weights from each input to all hidden nodes
+    hidden_weights: Vec<f32>,
+    /// The weights from all hidden nodes to each output
+    output_weights: Vec<f32>,
+    hidden_thresholds: Vec<f32>,
+    output_thresholds: Vec<f32>,
+}
+
+impl NeuralNet {
+    /// Returns the number of inputs
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the number of hidden nodes
+    pub fn hidden(&self) -> usize {
+        self.hidden
+    }
+
+    /// Returns the number of outputs
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Returns the outputs of the network for the given inputs
+    pub fn evaluate(&self, input: &[f32]) -> Result<Vec<f32>, Error> {
+        if input.len() != self.inputs {
+            return Err(Error::FormatInvalid);
+        }
+        let mut hidden = self.hidden_thresholds.clone();
+        for (x, weights) in input.iter().zip(self.hidden_weights.chunks(self.hidden)) {
+            if *x != 0.0 {
+                for (h, w) in hidden.iter_mut().zip(weights) {
+                    *h += w * x;
+                }
+            }
+        }
+        for h in hidden.iter_mut() {
+            *h = sigmoid(self.beta_hidden * *h);
+        }
+        Ok(self
+            .output_thresholds
+            .iter()
+            .zip(self.output_weights.chunks(self.hidden))
+            .map(|(threshold, weights)| {
+                let r: f32 =
+                    threshold + hidden.iter().zip(weights).map(|(h, w)| h * w).sum::<f32>();
+                sigmoid(self.beta_output * r)
+            })
+            .collect())
+    }
+
+    /// Read a network in text format
+    fn read(tokens: &mut SplitWhitespace<'_>) -> Result<Self, Error> {
+        let inputs: usize = parse(tokens.next())?;
+        let hidden: usize = parse(tokens.next())?;
+        let outputs: usize = parse(tokens.next())?;
+        let trained = parse(tokens.next())?;
+        let beta_hidden = parse(tokens.next())?;
+        let beta_output = parse(tokens.next())?;
+        let mut weights = |n: Option<usize>| {
+            (0..n.ok_or(Error::FormatInvalid)?)
+                .map(|_| parse(tokens.next()))
+                .collect::<Result<Vec<f32>, Error>>()
+        };
+        NeuralNet {
+            inputs,
+            hidden,
+            outputs,
+            trained,
+            beta_hidden,
+            beta_output,
+            hidden_weights: weights(inputs.checked_mul(hidden))?,
+            output_weights: weights(hidden.checked_mul(outputs))?,
+            hidden_thresholds: weights(Some(hidden))?,
+            output_thresholds: weights(Some(outputs))?,
+        }
+        .validated()
+    }
+
+    /// Read a network in binary format
+    fn read_binary(bytes: &mut &[u8]) -> Result<Self, Error> {
+        let mut int = || {
+            let value = i32::from_le_bytes(take(bytes)?);
+            usize::try_from(value).map_err(|_| Error::FormatInvalid)
+        };
+        let inputs = int()?;
+        let hidden = int()?;
+        let outputs = int()?;
+        let trained = int()? as u64;
+        let beta_hidden = f32::from_le_bytes(take(bytes)?);
+        let beta_output = f32::from_le_bytes(take(bytes)?);
+        let mut weights = |n: Option<usize>| {
+            (0..n.ok_or(Error::FormatInvalid)?)
+                .map(|_| Ok(f32::from_le_bytes(take(bytes)?)))
+                .collect::<Result<Vec<f32>, Error>>()
+        };
+        NeuralNet {
+            inputs,
+            hidden,
+            outputs,
+            trained,
+            beta_hidden,
+            beta_output,
+            hidden_weights: weights(inputs.checked_mul(hidden))?,
+            output_weights: weights(hidden.checked_mul(outputs))?,
+            hidden_thresholds: weights(Some(hidden))?,
+            output_thresholds: weights(Some(outputs))?,
+        }
+        .validated()
+    }
+
+    /// Append the network in binary format
+    fn write_binary(&self, bytes: &mut Vec<u8>) {
+        for n in [
+            self.inputs,
+            self.hidden,
+            self.outputs,
+            self.trained as usize,
+        ] {
+            bytes.extend_from_slice(&(n as i32).to_le_bytes());
+        }
+        let betas = [self.beta_hidden, self.beta_output];
+        let weights = betas
+            .iter()
+            .chain(&self.hidden_weights)
+            .chain(&self.output_weights)
+            .chain(&self.hidden_thresholds)
+            .chain(&self.output_thresholds);
+        for w in weights {
+            bytes.extend_from_slice(&w.to_le_bytes());
+        }
+    }
+
+    /// Check that the network has at least one hidden node
+    fn validated(self) -> Result<Self, Error> {
+        match self.hidden {
+            0 => Err(Error::FormatInvalid),
+            _ => Ok(self),
+        }
+    }
+}
+
+impl fmt::Display for NeuralNet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} {} {} {} {} {}",
+            self.inputs,
+            self.hidden,
+            self.outputs,
+            self.trained,
+            self.beta_hidden,
+            self.beta_output
+        )?;
+        let weights = self
+            .hidden_weights
+            .iter()
+            .chain(&self.output_weights)
+            .chain(&self.hidden_thresholds)
+            .chain(&self.output_thresholds);
+        for w in weights {
+            writeln!(f, "{}", w)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the logistic function of a value
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Parse a token of the weights in text format
+fn parse<T: FromStr>(token: Option<&str>) -> Result<T, Error> {
+    token
+        .and_then(|t| t.parse().ok())
+        .ok_or(Error::FormatInvalid)
+}
+
+/// Take the next four bytes of the weights in binary format
+fn take(bytes: &mut &[u8]) -> Result<[u8; 4], Error> {
+    if bytes.len() < 4 {
+        return Err(Error::FormatInvalid);
+    }
+    let (value, rest) = bytes.split_at(4);
+    *bytes = rest;
+    Ok([value[0], value[1], value[2], value[3]])
+}
+
+/// Evaluates positions with the neural networks of GNU Backgammon.
+///
+/// The network is selected by the [`PositionClass`] of the position, where the race network also
+/// evaluates bearoffs. Races the installed two-sided bearoff database holds are looked up
+/// instead. As in gnubg, the outputs are adjusted to be consistent, e.g. no gammons are possible
+/// once a side has borne off a checker.
+///
+/// The activations are computed exactly, while gnubg approximates them with a table, so the
+/// outputs may differ from gnubg in the last digits.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Gnubg {
+    contact: NeuralNet,
+    race: NeuralNet,
+    crashed: NeuralNet,
+}
+
+impl Gnubg {
+    /// Create an evaluator from the contact, race, and crashed networks, which need as many inputs
+    /// as gnubg encodes for these classes of positions and five outputs each
+    pub fn new(contact: NeuralNet, race: NeuralNet, crashed: NeuralNet) -> Result<Self, Error> {
+        let sizes = [
+            (&contact, CONTACT_INPUTS),
+            (&race, RACE_INPUTS),
+            (&crashed, CONTACT_INPUTS),
+        ];
+        if sizes
+            .iter()
+            .any(|(net, inputs)| net.inputs != *inputs || net.outputs != OUTPUTS)
+        {
+            return Err(Error::FormatInvalid);
+        }
+        Ok(Gnubg {
+            contact,
+            race,
+            crashed,
+        })
+    }
+
+    /// Read the networks from the binary weights of gnubg, i.e. the file `gnubg.wd`. The version
+    /// following the magic number is not checked.
+    pub fn from_binary(mut bytes: &[u8]) -> Result<Self, Error> {
+        if f32::from_le_bytes(take(&mut bytes)?) != MAGIC {
+            return Err(Error::FormatInvalid);
+        }
+        let _ = take(&mut bytes)?;
+        let contact = NeuralNet::read_binary(&mut bytes)?;
+        let race = NeuralNet::read_binary(&mut bytes)?;
+        let crashed = NeuralNet::read_binary(&mut bytes)?;
+        Gnubg::new(contact, race, crashed)
+    }
+
+    /// Returns the networks in the binary format of gnubg
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MAGIC.to_le_bytes());
+        bytes.extend_from_slice(&VERSION_BINARY.to_le_bytes());
+        for net in [&self.contact, &self.race, &self.crashed] {
+            net.write_binary(&mut bytes);
+        }
+        bytes
+    }
+
+    /// Returns the network for positions with contact
+    pub fn contact(&self) -> &NeuralNet {
+        &self.contact
+    }
+
+    /// Returns the network for races
+    pub fn race(&self) -> &NeuralNet {
+        &self.race
+    }
+
+    /// Returns the network for crashed positions
+    pub fn crashed(&self) -> &NeuralNet {
+        &self.crashed
+    }
+}
+
+/// Reads the networks from the weights of gnubg in text format, i.e. the file `gnubg.weights`.
+/// The pruning networks which follow the contact, race, and crashed networks are ignored.
+impl FromStr for Gnubg {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (header, weights) = s
+            .trim_start()
+            .split_once('\n')
+            .ok_or(Error::FormatInvalid)?;
+        if header.trim() != format!("GNU Backgammon {}", VERSION) {
+            return Err(Error::FormatInvalid);
+        }
+        let mut tokens = weights.split_whitespace();
+        let contact = NeuralNet::read(&mut tokens)?;
+        let race = NeuralNet::read(&mut tokens)?;
+        let crashed = NeuralNet::read(&mut tokens)?;
+        Gnubg::new(contact, race, crashed)
+    }
+}
+
+impl fmt::Display for Gnubg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "GNU Backgammon {}", VERSION)?;
+        write!(f, "{}{}{}", self.contact, self.race, self.crashed)
+    }
+}
+
+impl Evaluator for Gnubg {
+    fn evaluate(&self, board: &Board, player: Player) -> Result<Evaluation, Error> {
+        board.validate()?;
+        let (side, opponent) = &sides(board, player)?;
+        if let Some(evaluation) = finished(side, opponent) {
+            return Ok(evaluation);
+        }
+        if let Some(evaluation) = database().and_then(|db| db.evaluate(board, player)) {
+            return Ok(evaluation);
+        }
+
+        let (own, other) = (points(side), points(opponent));
+        let output = match classify(board) {
+            PositionClass::Crashed => self.crashed.evaluate(&contact_inputs(&own, &other, true))?,
+            PositionClass::Contact => self
+                .contact
+                .evaluate(&contact_inputs(&own, &other, false))?,
+            _ => self.race.evaluate(&race_inputs(&own, &other))?,
+        };
+        let evaluation = Evaluation {
+            win: f64::from(output[0]),
+            win_gammon: f64::from(output[1]),
+            win_backgammon: f64::from(output[2]),
+            lose_gammon: f64::from(output[3]),
+            lose_backgammon: f64::from(output[4]),
+        };
+        Ok(consistent(evaluation, &own, &other))
+    }
+}
+
+/// Returns the checkers of a side
+fn points(side: &PlayerBoard) -> Side {
+    let mut points = [0; 25];
+    for (point, checkers) in points.iter_mut().zip(side.board) {
+        *point = i32::from(checkers);
+    }
+    points[24] = i32::from(side.bar);
+    points
+}
+
+/// Returns the field of the rearmost checker of a side, 24 for the bar, or -1 without checkers
+fn back(side: &Side) -> i32 {
+    (0..25)
+        .rev()
+        .find(|i| side[*i] > 0)
+        .map_or(-1, |i| i as i32)
+}
+
+/// Returns 1 if the condition holds and 0 otherwise
+fn flag(condition: bool) -> f32 {
+    f32::from(u8::from(condition))
+}
+
+/// Encode the checkers on a point or on the bar
+fn encode(checkers: i32, bar: bool, input: &mut [f32]) {
+    input[0] = flag(if bar { checkers >= 1 } else { checkers == 1 });
+    input[1] = flag(if bar { checkers >= 2 } else { checkers == 2 });
+    input[2] = flag(checkers >= 3);
+    input[3] = if checkers > 3 {
+        (checkers - 3) as f32 / 2.0
+    } else {
+        0.0
+    };
+}
+
+/// Returns the inputs of the race network, the opponent first
+fn race_inputs(own: &Side, other: &Side) -> Vec<f32> {
+    let mut inputs = vec![0.0; RACE_INPUTS];
+    for (side, input) in [other, own].iter().zip(inputs.chunks_mut(HALF_RACE_INPUTS)) {
+        for (checkers, point) in side[..23].iter().zip(input.chunks_mut(4)) {
+            encode(*checkers, false, point);
+        }
+        let off = 15 - side[..23].iter().sum::<i32>();
+        for k in 0..14 {
+            input[RACE_OFF + k] = flag(off == k as i32 + 1);
+        }
+        let cross: i32 = (6..24).map(|i| side[i] * (i as i32 / 6)).sum();
+        input[RACE_CROSS] = cross as f32 / 10.0;
+    }
+    inputs
+}
+
+/// Returns the inputs of the contact or of the crashed network, with the checkers of the opponent
+/// first but the features of the player first, as gnubg's networks were trained
+fn contact_inputs(own: &Side, other: &Side, crashed: bool) -> Vec<f32> {
+    let mut inputs = vec![0.0; CONTACT_INPUTS];
+    let (points, features) = inputs.split_at_mut(2 * POINT_INPUTS);
+    for (side, input) in [other, own].iter().zip(points.chunks_mut(POINT_INPUTS)) {
+        for (i, (checkers, point)) in side.iter().zip(input.chunks_mut(4)).enumerate() {
+            encode(*checkers, i == 24, point);
+        }
+    }
+    let (first, second) = features.split_at_mut(MORE_INPUTS);
+    half_inputs(own, other, first);
+    off_inputs(own, crashed, first);
+    half_inputs(other, own, second);
+    off_inputs(other, crashed, second);
+    inputs
+}
+
+/// Encode the checkers a side has borne off, in steps of five checkers in crashed positions and
+/// of three checkers otherwise
+fn off_inputs(side: &Side, crashed: bool, input: &mut [f32]) {
+    let off = (15 - side.iter().sum::<i32>()) as f32;
+    let step = if crashed { 5.0 } else { 3.0 };
+    input[OFF1] = (off / step).min(1.0);
+    input[OFF2] = ((off - step) / step).clamp(0.0, 1.0);
+    input[OFF3] = ((off - 2.0 * step) / step).max(0.0);
+}
+
+/// A way to hit a checker with some dice of a roll
+#[derive(Debug)]
+struct Shot {
+    /// True if all intermediate points have to be open, false if one of two suffices
+    all: bool,
+    /// The pips from the hitter to the intermediate points
+    intermediate: &'static [i32],
+    /// The number of dice used
+    faces: usize,
+    /// The value of the dice used
+    pips: i32,
+}
+
+/// Create a way to hit
+const fn shot(all: bool, intermediate: &'static [i32], faces: usize, pips: i32) -> Shot {
+    Shot {
+        all,
+        intermediate,
+        faces,
+        pips,
+    }
+}
+
+/// All ways to hit
+const SHOTS: [Shot; 39] = [
+    shot(true, &[], 1, 1),          // 1x hits 1
+    shot(true, &[], 1, 2),          // 2x hits 2
+    shot(true, &[1], 2, 1),         // 11 hits 2
+    shot(true, &[], 1, 3),          // 3x hits 3
+    shot(false, &[1, 2], 2, 2),     // 21 hits 3
+    shot(true, &[1, 2], 3, 1),      // 11 hits 3
+    shot(true, &[], 1, 4),          // 4x hits 4
+    shot(false, &[1, 3], 2, 3),     // 31 hits 4
+    shot(true, &[2], 2, 2),         // 22 hits 4
+    shot(true, &[1, 2, 3], 4, 1),   // 11 hits 4
+    shot(true, &[], 1, 5),          // 5x hits 5
+    shot(false, &[1, 4], 2, 4),     // 41 hits 5
+    shot(false, &[2, 3], 2, 3),     // 32 hits 5
+    shot(true, &[], 1, 6),          // 6x hits 6
+    shot(false, &[1, 5], 2, 5),     // 51 hits 6
+    shot(false, &[2, 4], 2, 4),     // 42 hits 6
+    shot(true, &[3], 2, 3),         // 33 hits 6
+    shot(true, &[2, 4], 3, 2),      // 22 hits 6
+    shot(false, &[1, 6], 2, 6),     // 61 hits 7
+    shot(false, &[2, 5], 2, 5),     // 52 hits 7
+    shot(false, &[3, 4], 2, 4),     // 43 hits 7
+    shot(false, &[2, 6], 2, 6),     // 62 hits 8
+    shot(false, &[3, 5], 2, 5),     // 53 hits 8
+    shot(true, &[4], 2, 4),         // 44 hits 8
+    shot(true, &[2, 4, 6], 4, 2),   // 22 hits 8
+    shot(false, &[3, 6], 2, 6),     // 63 hits 9
+    shot(false, &[4, 5], 2, 5),     // 54 hits 9
+    shot(true, &[3, 6], 3, 3),      // 33 hits 9
+    shot(false, &[4, 6], 2, 6),     // 64 hits 10
+    shot(true, &[5], 2, 5),         // 55 hits 10
+    shot(false, &[5, 6], 2, 6),     // 65 hits 11
+    shot(true, &[6], 2, 6),         // 66 hits 12
+    shot(true, &[4, 8], 3, 4),      // 44 hits 12
+    shot(true, &[3, 6, 9], 4, 3),   // 33 hits 12
+    shot(true, &[5, 10], 3, 5),     // 55 hits 15
+    shot(true, &[4, 8, 12], 4, 4),  // 44 hits 16
+    shot(true, &[6, 12], 3, 6),     // 66 hits 18
+    shot(true, &[5, 10, 15], 4, 5), // 55 hits 20
+    shot(true, &[6, 12, 18], 4, 6), // 66 hits 24
+];
+
+/// The ways to hit from a distance of 1 to 24 pips, as indices into [`SHOTS`]
+const DISTANCES: [&[usize]; 24] = [
+    &[0],
+    &[1, 2],
+    &[3, 4, 5],
+    &[6, 7, 8, 9],
+    &[10, 11, 12],
+    &[13, 14, 15, 16, 17],
+    &[18, 19, 20],
+    &[21, 22, 23, 24],
+    &[25, 26, 27],
+    &[28, 29],
+    &[30],
+    &[31, 32, 33],
+    &[],
+    &[],
+    &[34],
+    &[35],
+    &[],
+    &[36],
+    &[],
+    &[37],
+    &[],
+    &[],
+    &[],
+    &[38],
+];
+
+/// The ways to hit with each of the 21 distinct rolls from 11 to 66, as indices into [`SHOTS`],
+/// the direct shots first. Doubles have four ways to hit.
+const ROLLS: [&[usize]; 21] = [
+    &[0, 2, 5, 9],
+    &[0, 1, 4],
+    &[1, 8, 17, 24],
+    &[0, 3, 7],
+    &[1, 3, 12],
+    &[3, 16, 27, 33],
+    &[0, 6, 11],
+    &[1, 6, 15],
+    &[3, 6, 20],
+    &[6, 23, 32, 35],
+    &[0, 10, 14],
+    &[1, 10, 19],
+    &[3, 10, 22],
+    &[6, 10, 26],
+    &[10, 29, 34, 37],
+    &[0, 13, 18],
+    &[1, 13, 21],
+    &[3, 13, 25],
+    &[6, 13, 28],
+    &[10, 13, 30],
+    &[13, 31, 36, 38],
+];
+
+/// Returns the tables of the rolls with which a checker escapes past the points made on the next
+/// 12 fields, given as bits, counting all rolls and only rolls passing the first point made
+fn escape_tables() -> &'static [[u8; 4096]; 2] {
+    static TABLES: OnceLock<[[u8; 4096]; 2]> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut tables = [[0; 4096]; 2];
+        let [all, passing] = &mut tables;
+        for (points, (all, passing)) in all.iter_mut().zip(passing.iter_mut()).enumerate() {
+            let low = points.trailing_zeros() as usize;
+            for n0 in 0..6 {
+                for n1 in 0..=n0 {
+                    let blocked = points & (1 << (n0 + n1 + 1)) != 0
+                        || (points & (1 << n0) != 0 && points & (1 << n1) != 0);
+                    if blocked {
+                        continue;
+                    }
+                    let rolls = if n0 == n1 { 1 } else { 2 };
+                    *all += rolls;
+                    if points != 0 && n0 + n1 + 1 > low {
+                        *passing += rolls;
+                    }
+                }
+            }
+        }
+        tables
+    })
+}
+
+/// Returns the rolls with which a checker of the opponent n fields in front of the bar of a side
+/// escapes past the points of the side
+fn escapes(side: &Side, n: i32, table: usize) -> i32 {
+    let mut points = 0;
+    for i in 0..n.min(12) {
+        if side[(24 + i - n) as usize] >= 2 {
+            points |= 1 << i;
+        }
+    }
+    i32::from(escape_tables()[table][points])
+}
+
+/// Compute the features of a side, except the checkers off, following gnubg's eval.c
+fn half_inputs(board: &Side, opp: &Side, input: &mut [f32]) {
+    let at = |side: &Side, i: i32| side[i as usize];
+
+    // the field of the rearmost checker of the opponent, from the point-of-view of the side
+    let opp_back = 23 - back(opp);
+
+    let contact: i32 = ((opp_back + 1).max(0)..25)
+        .map(|i| (i + 1 - opp_back) * at(board, i))
+        .sum();
+    input[BREAK_CONTACT] = contact as f32 / (15.0 + 152.0);
+
+    let free: i32 = (0..opp_back.max(0)).map(|i| (i + 1) * at(board, i)).sum();
+    input[FREEPIP] = free as f32 / 100.0;
+
+    // timing
+    let mut t = 24 * board[24];
+    let mut no = board[24];
+    let mut i = 23;
+    while i >= 12 && i > opp_back {
+        let checkers = at(board, i);
+        if checkers != 0 && checkers != 2 {
+            let n = if checkers > 2 { checkers - 2 } else { 1 };
+            no += n;
+            t += i * n;
+        }
+        i -= 1;
+    }
+    while i >= 6 {
+        no += at(board, i);
+        t += i * at(board, i);
+        i -= 1;
+    }
+    for i in (0..6).rev() {
+        let checkers = at(board, i);
+        if checkers > 2 {
+            t += i * (checkers - 2);
+            no += checkers - 2;
+        } else if checkers < 2 && no >= 2 - checkers {
+            t -= i * (2 - checkers);
+            no -= 2 - checkers;
+        }
+    }
+    input[TIMING] = t.max(0) as f32 / 100.0;
+
+    // back checker and anchors
+    let own_back = back(board);
+    input[BACK_CHEQUER] = own_back as f32 / 24.0;
+    let anchor = (0..=own_back.min(23))
+        .rev()
+        .find(|i| at(board, *i) >= 2)
+        .unwrap_or(-1);
+    input[BACK_ANCHOR] = anchor as f32 / 24.0;
+    let forward = (18..=anchor)
+        .find(|j| at(board, *j) >= 2)
+        .or_else(|| (12..=17).rev().find(|j| at(board, *j) >= 2))
+        .map_or(0, |j| 24 - j);
+    input[FORWARD_ANCHOR] = if forward == 0 {
+        2.0
+    } else {
+        forward as f32 / 6.0
+    };
+
+    pip_loss(board, opp, input);
+
+    input[BACKESCAPES] = escapes(board, 23 - opp_back, 0) as f32 / 36.0;
+    input[BACKRESCAPES] = escapes(board, 23 - opp_back, 1) as f32 / 36.0;
+
+    let mut n = 36;
+    let mut i = 15;
+    while i < 24 - opp_back {
+        n = n.min(escapes(board, i, 0));
+        i += 1;
+    }
+    input[ACONTAIN] = (36 - n) as f32 / 36.0;
+    input[ACONTAIN2] = input[ACONTAIN] * input[ACONTAIN];
+    if opp_back < 0 {
+        // the field of the bar is not included
+        i = 15;
+        n = 36;
+    }
+    while i < 24 {
+        n = n.min(escapes(board, i, 0));
+        i += 1;
+    }
+    input[CONTAIN] = (36 - n) as f32 / 36.0;
+    input[CONTAIN2] = input[CONTAIN] * input[CONTAIN];
+
+    let mobility: i32 = (6..25)
+        .map(|i| (i - 5) * at(board, i) * escapes(opp, i, 0))
+        .sum();
+    input[MOBILITY] = mobility as f32 / 3600.0;
+
+    // the one-sided moment beyond the rounded up average field
+    let checkers: i32 = board.iter().sum();
+    let pips: i32 = (0..25).map(|i| i * at(board, i)).sum();
+    let average = if checkers > 0 {
+        (pips + checkers - 1) / checkers
+    } else {
+        pips
+    };
+    let beyond: i32 = (average + 1..25).map(|i| at(board, i)).sum();
+    let moment: i32 = (average + 1..25)
+        .map(|i| at(board, i) * (i - average) * (i - average))
+        .sum();
+    let moment = if beyond > 0 {
+        (moment + beyond - 1) / beyond
+    } else {
+        moment
+    };
+    input[MOMENT2] = moment as f32 / 400.0;
+
+    input[ENTER] = 0.0;
+    if board[24] > 0 {
+        let two = board[24] > 1;
+        let closed = |i: usize| opp[i] > 1;
+        let mut loss = 0;
+        for i in 0..6 {
+            if closed(i) {
+                // any double loses
+                loss += 4 * (i + 1);
+                for j in i + 1..6 {
+                    if closed(j) {
+                        loss += 2 * (i + j + 2);
+                    } else if two {
+                        loss += 2 * (i + 1);
+                    }
+                }
+            } else if two {
+                loss += (i + 1..6)
+                    .filter(|j| closed(*j))
+                    .map(|j| 2 * (j + 1))
+                    .sum::<usize>();
+            }
+        }
+        input[ENTER] = loss as f32 / (36.0 * (49.0 / 6.0));
+    }
+    let closed = opp[..6].iter().filter(|c| **c > 1).count() as i32;
+    input[ENTER2] = (36 - (closed - 6) * (closed - 6)) as f32 / 36.0;
+
+    // as in gnubg, the distances are measured from the rearmost point made
+    let mut rearmost = None;
+    let (mut w, mut total) = (0, 0);
+    for np in (1..24).rev().filter(|np| board[*np] >= 2) {
+        match rearmost {
+            None => rearmost = Some(np),
+            Some(pa) => {
+                let d = pa - np;
+                let c = match d {
+                    0..=6 => 11,
+                    7..=11 => 13 - d as i32,
+                    _ => 0,
+                };
+                w += c * board[pa];
+                total += board[pa];
+            }
+        }
+    }
+    input[BACKBONE] = if total > 0 {
+        1.0 - w as f32 / (total as f32 * 11.0)
+    } else {
+        0.0
+    };
+
+    let anchors = board[18..24].iter().filter(|c| **c > 1).count();
+    let back_checkers: i32 = board[18..25].iter().sum();
+    input[BACKG] = 0.0;
+    input[BACKG1] = 0.0;
+    if anchors > 1 {
+        input[BACKG] = (back_checkers - 3) as f32 / 4.0;
+    } else if anchors == 1 {
+        input[BACKG1] = back_checkers as f32 / 8.0;
+    }
+}
+
+/// Compute the average pips the opponent loses from hits, and the rolls hitting one and two
+/// checkers, estimated from the checkers in direct and indirect range without finding the best
+/// play of each roll
+fn pip_loss(board: &Side, opp: &Side, input: &mut [f32]) {
+    let home = board[..6].iter().filter(|c| **c > 0).count();
+
+    // the fields of the hitters for each way to hit, as bits
+    let mut hit = [0_u32; 39];
+    let top = if home > 2 { 23 } else { 21 };
+    for i in (0..=top).rev().filter(|i| opp[*i] == 1) {
+        for j in 24 - i..25 {
+            if board[j] == 0 || (j < 6 && board[j] == 2) {
+                continue;
+            }
+            for &s in DISTANCES[j + i - 24] {
+                let blocked = |d: &i32| opp[i - *d as usize] > 1;
+                let intermediate = SHOTS[s].intermediate;
+                let open = if SHOTS[s].all {
+                    !intermediate.iter().any(blocked)
+                } else {
+                    !intermediate.iter().all(blocked)
+                };
+                if open {
+                    hit[s] |= 1 << j;
+                }
+            }
+        }
+    }
+    let hits = |s: usize, k: i32| hit[s] & (1 << k) != 0;
+
+    // the pips lost and the checkers hit with each roll
+    let mut rolls = [(0, 0); 21];
+    if board[24] == 0 {
+        for (roll, shots) in rolls.iter_mut().zip(ROLLS) {
+            // the field of the last hitter used
+            let mut last = -1;
+            for &s in shots.iter().filter(|s| hit[**s] != 0) {
+                let shot = &SHOTS[s];
+                if shot.faces == 1 {
+                    // hit the most advanced blot, with another checker if one is left
+                    if let Some(k) = (1..24).rev().find(|k| hits(s, *k)) {
+                        if last != k || board[k as usize] > 1 {
+                            roll.1 += 1;
+                        }
+                        last = k;
+                        roll.0 = roll.0.max(k - shot.pips + 1);
+                        // doubles may hit directly more than once
+                        if shots.len() == 4 && hit[s] & !(1 << k) != 0 {
+                            roll.1 += 1;
+                        }
+                    }
+                } else {
+                    if roll.1 == 0 {
+                        roll.1 = 1;
+                    }
+                    let k = (0..24).rev().find(|k| hits(s, *k)).unwrap_or(-1);
+                    roll.0 = roll.0.max(k - shot.pips + 1);
+                    // blots hit on the intermediate points
+                    if shot
+                        .intermediate
+                        .iter()
+                        .any(|d| opp[(23 - k + d) as usize] == 1)
+                    {
+                        roll.1 += 1;
+                    }
+                }
+            }
+        }
+    } else if board[24] == 1 {
+        for (roll, shots) in rolls.iter_mut().zip(ROLLS) {
+            // true once the other dice is needed to enter
+            let mut entered = false;
+            for (j, &s) in shots.iter().enumerate().filter(|(_, s)| hit[**s] != 0) {
+                let shot = &SHOTS[s];
+                if shot.faces == 1 {
+                    for k in (1..25).rev().filter(|k| hits(s, *k)) {
+                        if entered && k != 24 {
+                            break;
+                        }
+                        if k != 24 {
+                            // the other dice has to enter
+                            let pips = SHOTS[shots[1 - j]].pips;
+                            if opp[pips as usize - 1] > 1 {
+                                break;
+                            }
+                            entered = true;
+                        }
+                        roll.1 += 1;
+                        roll.0 = roll.0.max(k - shot.pips + 1);
+                    }
+                } else if hits(s, 24) {
+                    // indirect shots only from the bar
+                    if roll.1 == 0 {
+                        roll.1 = 1;
+                    }
+                    roll.0 = roll.0.max(25 - shot.pips);
+                    if shot.intermediate.iter().any(|d| opp[*d as usize - 1] == 1) {
+                        roll.1 += 1;
+                    }
+                }
+            }
+        }
+    } else {
+        // with more than one checker on the bar, only direct shots from the bar count
+        for (roll, shots) in rolls.iter_mut().zip(ROLLS) {
+            for &s in shots[..2].iter().filter(|s| hits(**s, 24)) {
+                if SHOTS[s].faces == 1 {
+                    roll.1 += 1;
+                    roll.0 = roll.0.max(25 - SHOTS[s].pips);
+                }
+            }
+        }
+    }
+
+    let (mut pips, mut one, mut two) = (0, 0, 0);
+    for (roll, shots) in rolls.iter().zip(ROLLS) {
+        let weight = if shots.len() == 4 { 1 } else { 2 };
+        pips += roll.0 * weight;
+        if roll.1 > 0 {
+            one += weight;
+        }
+        if roll.1 > 1 {
+            two += weight;
+        }
+    }
+    input[PIPLOSS] = pips as f32 / (12.0 * 36.0);
+    input[P1] = one as f32 / 36.0;
+    input[P2] = two as f32 / 36.0;
+}
+
+/// Make the outputs of a network consistent with each other and with the position
+fn consistent(mut evaluation: Evaluation, own: &Side, other: &Side) -> Evaluation {
+    let off = |side: &Side| side.iter().sum::<i32>() < 15;
+    // checkers in the home board of the opponent or on the bar may be backgammoned
+    let trapped = |side: &Side| side[18..25].iter().any(|c| *c > 0);
+
+    evaluation.win = evaluation.win.clamp(0.0, 1.0);
+    if off(other) {
+        evaluation.win_gammon = 0.0;
+    }
+    if off(other) || !trapped(other) {
+        evaluation.win_backgammon = 0.0;
+    }
+    if off(own) {
+        evaluation.lose_gammon = 0.0;
+    }
+    if off(own) || !trapped(own) {
+        evaluation.lose_backgammon = 0.0;
+    }
+    evaluation.win_gammon = evaluation.win_gammon.clamp(0.0, evaluation.win);
+    evaluation.win_backgammon = evaluation.win_backgammon.clamp(0.0, evaluation.win_gammon);
+    evaluation.lose_gammon = evaluation.lose_gammon.clamp(0.0, evaluation.lose());
+    evaluation.lose_backgammon = evaluation
+        .lose_backgammon
+        .clamp(0.0, evaluation.lose_gammon);
+    evaluation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Point;
+
+    /// A network whose outputs do not depend on the inputs, but only on the output thresholds
+    fn constant(inputs: usize, outputs: &[f32]) -> NeuralNet {
+        NeuralNet {
+            inputs,
+            hidden: 1,
+            outputs: outputs.len(),
+            trained: 0,
+            beta_hidden: 0.1,
+            beta_output: 1.0,
+            hidden_weights: vec![0.0; inputs],
+            output_weights: vec![0.0; outputs.len()],
+            hidden_thresholds: vec![0.0],
+            // the inverse of the sigmoid
+            output_thresholds: outputs.iter().map(|p| (p / (1.0 - p)).ln()).collect(),
+        }
+    }
+
+    /// Networks which tell apart the class of the position by the probability to win
+    fn gnubg() -> Gnubg {
+        let outputs = |win| [win, 0.3, 0.1, 0.2, 0.05];
+        Gnubg::new(
+            constant(CONTACT_INPUTS, &outputs(0.6)),
+            constant(RACE_INPUTS, &outputs(0.7)),
+            constant(CONTACT_INPUTS, &outputs(0.8)),
+        )
+        .unwrap()
+    }
+
+    /// Returns the checkers of player 0 and player 1 on a board
+    fn both(board: &Board) -> (Side, Side) {
+        let side = |player| board.side(player).unwrap_or_default();
+        (
+            points(&side(Player::Player0)),
+            points(&side(Player::Player1)),
+        )
+    }
+
+    #[test]
+    fn test_neural_net() -> Result<(), Error> {
+        let net = NeuralNet {
+            inputs: 2,
+            hidden: 2,
+            outputs: 1,
+            trained: 0,
+            beta_hidden: 0.5,
+            beta_output: 2.0,
+            hidden_weights: vec![1.0, -1.0, 0.5, 2.0],
+            output_weights: vec![1.0, -1.0],
+            hidden_thresholds: vec![0.0, 1.0],
+            output_thresholds: vec![0.25],
+        };
+        let h0 = sigmoid(0.5 * (1.0 + 0.5 * 2.0));
+        let h1 = sigmoid(0.5 * (1.0 - 1.0 + 2.0 * 2.0));
+        let output = net.evaluate(&[1.0, 2.0])?;
+        assert!((output[0] - sigmoid(2.0 * (0.25 + h0 - h1))).abs() < 1e-6);
+        assert!(net.evaluate(&[1.0]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_formats() -> Result<(), Error> {
+        let gnubg = gnubg();
+        let text = gnubg.to_string();
+        assert!(text.starts_with("GNU Backgammon 1.00\n250 1 5 0 0.1 1\n"));
+        assert_eq!(text.parse::<Gnubg>()?, gnubg);
+        assert_eq!(Gnubg::from_binary(&gnubg.to_binary())?, gnubg);
+
+        // pruning networks following the three networks are ignored
+        let pruning = format!("{}{}", text, constant(200, &[0.5]));
+        assert_eq!(pruning.parse::<Gnubg>()?, gnubg);
+
+        assert!("GNU Backgammon 0.99\n".parse::<Gnubg>().is_err());
+        assert!(text[..text.len() - 10].parse::<Gnubg>().is_err());
+        let binary = gnubg.to_binary();
+        assert!(Gnubg::from_binary(&binary[..binary.len() - 1]).is_err());
+        assert!(Gnubg::from_binary(&binary[4..]).is_err());
+        // the race network is expected second
+        assert!(Gnubg::new(
+            gnubg.race.clone(),
+            gnubg.contact.clone(),
+            gnubg.crashed.clone()
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_escapes() {
+        let tables = escape_tables();
+        assert_eq!(tables[0][0], 36);
+        assert_eq!(tables[1][0], 0);
+        // a full prime can not be passed
+        assert_eq!(tables[0][0b111111], 0);
+        // a single point 6 pips ahead blocks 6-6 and the rolls summing up to 6
+        assert_eq!(tables[0][1 << 5], 30);
+    }
+
+    #[test]
+    fn test_contact_inputs() {
+        let (own, other) = both(&Board::new());
+        assert_eq!(classify(&Board::new()), PositionClass::Contact);
+        let inputs = contact_inputs(&own, &other, false);
+        assert_eq!(inputs.len(), CONTACT_INPUTS);
+        // both sides are encoded alike at the start
+        assert_eq!(
+            inputs[..POINT_INPUTS],
+            inputs[POINT_INPUTS..2 * POINT_INPUTS]
+        );
+        // two checkers on the 24 point
+        assert_eq!(inputs[23 * 4..24 * 4], [0.0, 1.0, 0.0, 0.0]);
+        // five checkers on the 6 point
+        assert_eq!(inputs[5 * 4..6 * 4], [0.0, 0.0, 1.0, 1.0]);
+
+        let features = &inputs[2 * POINT_INPUTS..2 * POINT_INPUTS + MORE_INPUTS];
+        // the degree of contact of the starting position is the norm
+        assert!((features[BREAK_CONTACT] - 1.0).abs() < 1e-6);
+        assert_eq!(features[BACK_CHEQUER], 23.0 / 24.0);
+        assert_eq!(features[FORWARD_ANCHOR], 1.0 / 6.0);
+        assert_eq!([features[OFF1], features[P1], features[ENTER]], [0.0; 3]);
+        // only the 6 point is closed
+        assert_eq!(features[ENTER2], 11.0 / 36.0);
+    }
+
+    #[test]
+    fn test_shots() {
+        // a blot of the opponent 6 pips in front of a checker is hit with 17 rolls
+        let mut own = [0; 25];
+        own[10] = 1;
+        own[0] = 14;
+        let mut other = [0; 25];
+        other[23 - 4] = 1;
+        other[23] = 14;
+        let mut input = [0.0; MORE_INPUTS];
+        pip_loss(&own, &other, &mut input);
+        assert_eq!(input[P1], 17.0 / 36.0);
+        assert_eq!(input[P2], 0.0);
+        assert!(input[PIPLOSS] > 0.0);
+
+        // a point in the middle blocks 3-3
+        other[23 - 7] = 2;
+        other[23] = 12;
+        pip_loss(&own, &other, &mut input);
+        assert_eq!(input[P1], 16.0 / 36.0);
+    }
+
+    #[test]
+    fn test_race_inputs() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.set(Player::Player0, 5, 13)?;
+        board.set(Player::Player0, 12, 1)?;
+        board.set_off(Player::Player0, 1)?;
+        board.set(Player::Player1, 5, 15)?;
+        let (own, other) = both(&board);
+        assert_eq!(classify(&board), PositionClass::Race);
+
+        let inputs = race_inputs(&own, &other);
+        assert_eq!(inputs.len(), RACE_INPUTS);
+        let half = &inputs[HALF_RACE_INPUTS..];
+        assert_eq!(half[5 * 4..6 * 4], [0.0, 0.0, 1.0, 5.0]);
+        assert_eq!(half[RACE_OFF..RACE_OFF + 2], [1.0, 0.0]);
+        // the checker on the 13 point crosses into two quarters
+        assert_eq!(half[RACE_CROSS], 0.2);
+        assert_eq!(inputs[RACE_OFF..RACE_CROSS], [0.0; 14]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_evaluate() -> Result<(), Error> {
+        let gnubg = gnubg();
+        let start = gnubg.evaluate(&Board::new(), Player::Player0)?;
+        assert!((start.win - 0.6).abs() < 1e-6);
+        assert!((start.win_gammon - 0.3).abs() < 1e-6);
+
+        // a race, without backgammons
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.set(Player::Player0, 8, 15)?;
+        board.set(Player::Player1, 8, 15)?;
+        let race = gnubg.evaluate(&board, Player::Player0)?;
+        assert!((race.win - 0.7).abs() < 1e-6);
+        assert_eq!((race.win_backgammon, race.lose_backgammon), (0.0, 0.0));
+
+        // player 1 has borne off most checkers, hence no gammons for player 0
+        let mut board = Board::new();
+        board.points = board.points.map(|p| match p.owner {
+            Player::Player1 => Point::EMPTY,
+            _ => p,
+        });
+        board.points[0] = Point::new(Player::Player1, 2);
+        board.points[22] = Point::new(Player::Player1, 3);
+        board.off[1] = 10;
+        let crashed = gnubg.evaluate(&board, Player::Player0)?;
+        assert!((crashed.win - 0.8).abs() < 1e-6);
+        assert_eq!(crashed.win_gammon, 0.0);
+        assert!(crashed.lose_gammon > 0.0);
+
+        assert!(gnubg.evaluate(&board, Player::Nobody).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+//! # Reference Positions
+//!
+//! A small library of well-known positions, each typical for a theme of Backgammon strategy, for
+//! trainers, benchmarks of evaluators, and tests. The positions are money games without the
+//! Jacoby rule, with player 0 on roll and the cube centered. Positions with dices rolled ask for
+//! a checker play, all others but the starting position for a cube decision.
+//! ```
+//! use backgammon::positions::{find, library, Theme};
+//! use backgammon::rules::Player;
+//!
+//! assert!(library().iter().all(|p| !p.themes.is_empty()));
+//!
+//! let race = find("Race cube").unwrap();
+//! assert!(race.has(Theme::Race) && race.has(Theme::Cube));
+//! let game = race.game().unwrap();
+//! assert_eq!(game.board.pip_count(Player::Player0).unwrap(), 71);
+//! ```
+use crate::formats::Xgid;
+use crate::{Error, Game};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Represents a theme of Backgammon strategy a position is typical for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Theme {
+    /// The position before the opening roll
+    Opening,
+    /// Attacking blots in the home board while the opponent is on the bar
+    Blitz,
+    /// Trapping checkers of the opponent behind a prime
+    Priming,
+    /// Holding an advanced anchor while being behind in the race
+    Holding,
+    /// Holding two or more anchors in the home board of the opponent while far behind
+    Backgame,
+    /// Holding only the ace point of the opponent while the opponent bears off
+    AcePoint,
+    /// No contact is left, the position is a pure race
+    Race,
+    /// Both players bear off their last checkers
+    BearOff,
+    /// The player on roll has to decide whether to double
+    Cube,
+}
+
+// implement Display trait
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Theme::Opening => write!(f, "opening"),
+            Theme::Blitz => write!(f, "blitz"),
+            Theme::Priming => write!(f, "priming"),
+            Theme::Holding => write!(f, "holding game"),
+            Theme::Backgame => write!(f, "backgame"),
+            Theme::AcePoint => write!(f, "ace-point game"),
+            Theme::Race => write!(f, "race"),
+            Theme::BearOff => write!(f, "bear-off"),
+            Theme::Cube => write!(f, "cube decision"),
+        }
+    }
+}
+
+/// A position of the library as its name, description, themes, and XGID
+type Entry = (&'static str, &'static str, &'static [Theme], &'static str);
+
+/// The positions of the library
+const LIBRARY: [Entry; 8] = [
+    (
+        "Starting position",
+        "The position of every game before the opening roll",
+        &[Theme::Opening],
+        "XGID=-b----E-C---eE---c-e----B-:0:0:1:00:0:0:0:0:10",
+    ),
+    (
+        "Blitz",
+        "Two checkers of the opponent are on the bar against a four-point board with a builder \
+         on the 8-point",
+        &[Theme::Blitz, Theme::Cube],
+        "XGID=ba-BBCC-B---cC---c-db-----:0:0:1:00:0:0:0:0:10",
+    ),
+    (
+        "Prime versus prime",
+        "Both players have a five-point prime, with two checkers of the opponent trapped behind",
+        &[Theme::Priming],
+        "XGID=-b--BBCBB---bB---bbcbb--B-:0:0:1:61:0:0:0:0:10",
+    ),
+    (
+        "Holding game",
+        "Holding the 20-point anchor against the midpoint, 36 pips behind in the race",
+        &[Theme::Holding],
+        "XGID=-----BD-C---dD---c-dBbb---:0:0:1:64:0:0:0:0:10",
+    ),
+    (
+        "Backgame",
+        "Holding the 22-point and the 24-point, 52 pips behind in the race",
+        &[Theme::Backgame],
+        "XGID=----BBC-B---bBb-bb-cbbB-B-:0:0:1:43:0:0:0:0:10",
+    ),
+    (
+        "Ace-point game",
+        "Holding only the 24-point while the opponent has borne off five checkers",
+        &[Theme::AcePoint],
+        "XGID=--ABCCD------------ccbb-B-:0:0:1:62:0:0:0:0:10",
+    ),
+    (
+        "Race cube",
+        "A race of 71 against 82 pips, the first decision whether to double",
+        &[Theme::Race, Theme::Cube],
+        "XGID=--BBCCC-B-------ab-dccb---:0:0:1:00:0:0:0:0:10",
+    ),
+    (
+        "Two checkers each",
+        "A bear-off with one checker on the 5-point and one on the 4-point against the 6-point \
+         and the 5-point of the opponent",
+        &[Theme::BearOff, Theme::Race, Theme::Cube],
+        "XGID=----AA-------------aa-----:0:0:1:00:0:0:0:0:10",
+    ),
+];
+
+/// Represents a position of the library
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    /// The name of the position, unique within the library
+    pub name: &'static str,
+    /// What the position shows
+    pub description: &'static str,
+    /// The themes the position is typical for
+    pub themes: &'static [Theme],
+    /// The position, the cube, the dices, and the player on roll
+    pub xgid: Xgid,
+}
+
+impl Position {
+    /// Returns whether the position is typical for a theme
+    pub fn has(&self, theme: Theme) -> bool {
+        self.themes.contains(&theme)
+    }
+
+    /// Returns a game set up with the position, e.g. to ask an evaluator for the best play
+    pub fn game(&self) -> Result<Game, Error> {
+        let mut game = Game::new();
+        self.xgid.apply(&mut game)?;
+        Ok(game)
+    }
+}
+
+/// Returns all positions of the library, in the order of a game from the opening to the
+/// bear-off
+pub fn library() -> Vec<Position> {
+    LIBRARY
+        .iter()
+        .filter_map(|(name, description, themes, xgid)| {
+            Some(Position {
+                name,
+                description,
+                themes,
+                xgid: xgid.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the positions of the library typical for a theme
+pub fn with_theme(theme: Theme) -> Vec<Position> {
+    library().into_iter().filter(|p| p.has(theme)).collect()
+}
+
+/// Returns the position of the library with the given name
+pub fn find(name: &str) -> Option<Position> {
+    library().into_iter().find(|p| p.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Board, Player};
+    use crate::Phase;
+
+    #[test]
+    fn test_library() {
+        // every position can be parsed and has a unique name
+        let library = library();
+        assert_eq!(library.len(), LIBRARY.len());
+        for (i, position) in library.iter().enumerate() {
+            assert!(library[..i].iter().all(|p| p.name != position.name));
+            assert!(position.xgid.board.validate().is_ok(), "{}", position.name);
+            let game = position.game().unwrap();
+            assert_eq!(game.who_plays, Player::Player0);
+            match position.has(Theme::Cube) || position.has(Theme::Opening) {
+                true => assert_eq!(game.phase, Phase::AwaitingRoll, "{}", position.name),
+                false => assert_eq!(game.phase, Phase::AwaitingMove, "{}", position.name),
+            }
+            assert_eq!(
+                position.has(Theme::Race),
+                position.xgid.board.is_race(),
+                "{}",
+                position.name
+            );
+        }
+        assert_eq!(library[0].xgid.board, Board::new());
+    }
+
+    #[test]
+    fn test_descriptions() {
+        let pips = |name| {
+            let board = find(name).unwrap().xgid.board;
+            (
+                board.pip_count(Player::Player0).unwrap(),
+                board.pip_count(Player::Player1).unwrap(),
+            )
+        };
+        assert_eq!(pips("Holding game"), (150, 114));
+        assert_eq!(pips("Backgame"), (170, 118));
+        assert_eq!(pips("Race cube"), (71, 82));
+
+        let ace = find("Ace-point game").unwrap().xgid.board;
+        assert_eq!(ace.off[1], 5);
+        let blitz = find("Blitz").unwrap().xgid.board;
+        assert_eq!(blitz.bar[1], 2);
+        assert_eq!(find("Two checkers each").unwrap().xgid.board.off[0], 13);
+    }
+
+    #[test]
+    fn test_themes() {
+        let names = |theme| with_theme(theme).iter().map(|p| p.name).collect::<Vec<_>>();
+        assert_eq!(names(Theme::Race), vec!["Race cube", "Two checkers each"]);
+        assert_eq!(
+            names(Theme::Cube),
+            vec!["Blitz", "Race cube", "Two checkers each"]
+        );
+        assert!(find("Nonexistent").is_none());
+        assert_eq!(Theme::AcePoint.to_string(), "ace-point game");
+    }
+}
@@ -0,0 +1,182 @@
+//! # Ratings of Players
+//!
+//! Implements the ratings of the First Internet Backgammon Server, the de facto standard of
+//! Backgammon servers and clubs. The longer a match, the more likely the stronger player wins it,
+//! hence both the winning probability and the points exchanged grow with the square root of the
+//! match length.
+//!
+//! ```
+//! use backgammon::rating::Rating;
+//!
+//! let mut a = Rating::default();
+//! let mut b = Rating::default();
+//! let points = Rating::update(&mut a, &mut b, 7);
+//! assert!((points - 2.0 * 7f64.sqrt()).abs() < 1e-9);
+//!
+//! // new players gain and lose ratings faster
+//! assert!(a.rating > 1500.0 + points);
+//! assert_eq!(a.experience, 7);
+//! ```
+use crate::rules::Player;
+use crate::Match;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// The rating of new players
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// The experience from which on ratings change at the normal rate
+const EXPERIENCED: u64 = 400;
+
+/// Represents the rating of a player and the experience, i.e. the total length of all matches
+/// the player has been rated for
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rating {
+    /// The rating, starting at [`INITIAL_RATING`]
+    pub rating: f64,
+    /// The sum of the lengths of all rated matches
+    pub experience: u64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating::new(INITIAL_RATING, 0)
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} ({} experience)", self.rating, self.experience)
+    }
+}
+
+impl Rating {
+    /// Create a rating with the given experience
+    pub fn new(rating: f64, experience: u64) -> Self {
+        Rating { rating, experience }
+    }
+
+    /// Returns the probability that the player wins a match of the given length against the
+    /// opponent. The probability of the underdog is `1 / (10^(D * sqrt(N) / 2000) + 1)`, where
+    /// `D` is the difference of the ratings and `N` the match length.
+    /// ```
+    /// use backgammon::rating::Rating;
+    ///
+    /// let strong = Rating::new(1700.0, 1000);
+    /// let weak = Rating::new(1500.0, 1000);
+    /// assert!(strong.win_probability(&weak, 11) > strong.win_probability(&weak, 1));
+    /// assert_eq!(weak.win_probability(&weak, 5), 0.5);
+    /// ```
+    pub fn win_probability(&self, opponent: &Rating, length: u32) -> f64 {
+        let difference = (self.rating - opponent.rating).abs();
+        let underdog = 1.0 / (10f64.powf(difference * f64::from(length).sqrt() / 2000.0) + 1.0);
+        match self.rating < opponent.rating {
+            true => underdog,
+            false => 1.0 - underdog,
+        }
+    }
+
+    /// Returns the factor by which the rating of the player changes faster while the player is
+    /// new, falling from 5 to 1 over the first 400 points of experience
+    pub fn boost(&self) -> f64 {
+        match self.experience < EXPERIENCED {
+            true => 5.0 - self.experience as f64 / 100.0,
+            false => 1.0,
+        }
+    }
+
+    /// Update the ratings of the winner and the loser of a match of the given length, and add
+    /// the length to their experience. Returns the points exchanged before the boost of new
+    /// players, i.e. `4 * sqrt(N)` times the probability that the loser would have won. A match
+    /// length of 0, e.g. for an unlimited match, leaves the ratings unchanged.
+    pub fn update(winner: &mut Rating, loser: &mut Rating, length: u32) -> f64 {
+        let points = 4.0 * f64::from(length).sqrt() * loser.win_probability(winner, length);
+        winner.rating += points * winner.boost();
+        loser.rating -= points * loser.boost();
+        winner.experience += u64::from(length);
+        loser.experience += u64::from(length);
+        points
+    }
+
+    /// Update the ratings of player 0 and player 1 after they finished a match, see
+    /// [`update`](Rating::update). Returns `None` and leaves the ratings unchanged as long as the
+    /// match has no winner.
+    /// ```
+    /// use backgammon::{Match, Victory};
+    /// use backgammon::rating::Rating;
+    /// use backgammon::rules::{MatchRules, Player};
+    ///
+    /// let mut m = Match::new().with_points(1);
+    /// let mut ratings = (Rating::default(), Rating::default());
+    /// assert_eq!(Rating::update_match(&mut ratings, &m), None);
+    ///
+    /// m.new_game().unwrap().resign(Player::Player0, Victory::Single).unwrap();
+    /// Rating::update_match(&mut ratings, &m).unwrap();
+    /// assert!(ratings.1.rating > ratings.0.rating);
+    /// ```
+    pub fn update_match(ratings: &mut (Rating, Rating), m: &Match) -> Option<f64> {
+        let (p0, p1) = ratings;
+        match m.winner()? {
+            Player::Player0 => Some(Rating::update(p0, p1, m.rules.points)),
+            Player::Player1 => Some(Rating::update(p1, p0, m.rules.points)),
+            Player::Nobody => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_win_probability() {
+        let a = Rating::new(1800.0, 0);
+        let b = Rating::new(1600.0, 0);
+        // a difference of 200 points in a 1-point match
+        let p = a.win_probability(&b, 1);
+        assert!((p - 1.0 / (1.0 + 10f64.powf(-0.1))).abs() < 1e-12);
+        assert!((p + b.win_probability(&a, 1) - 1.0).abs() < 1e-12);
+        // the longer the match, the more likely the favorite wins
+        assert!(a.win_probability(&b, 25) > a.win_probability(&b, 5));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut favorite = Rating::new(1800.0, 1000);
+        let mut underdog = Rating::new(1600.0, 1000);
+        let (mut winner, mut loser) = (underdog, favorite);
+        let upset = Rating::update(&mut winner, &mut loser, 9);
+        let expected = Rating::update(&mut favorite, &mut underdog, 9);
+        // an upset is worth more than the expected result
+        assert!(upset > expected);
+        assert!((favorite.rating + underdog.rating - 3400.0).abs() < 1e-9);
+        assert_eq!(favorite.experience, 1009);
+
+        // new players are boosted
+        let mut new = Rating::default();
+        let mut old = Rating::new(INITIAL_RATING, 1000);
+        assert_eq!(new.boost(), 5.0);
+        let points = Rating::update(&mut new, &mut old, 1);
+        assert!((new.rating - INITIAL_RATING - 5.0 * points).abs() < 1e-9);
+        assert!((INITIAL_RATING - old.rating - points).abs() < 1e-9);
+        assert_eq!(Rating::new(INITIAL_RATING, 250).boost(), 2.5);
+
+        // unlimited matches are not rated
+        let rating = new.rating;
+        assert_eq!(Rating::update(&mut new, &mut old, 0), 0.0);
+        assert_eq!(new.rating, rating);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Rating::new(1612.3456, 42).to_string(),
+            "1612.35 (42 experience)"
+        );
+    }
+}
@@ -0,0 +1,531 @@
+//! # Record the Events of a Backgammon Game
+use crate::game::{Game, GameResult, Victory};
+use crate::rules::{AbsolutePoint, Board, CheckerMove, Cube, Dices, Player};
+use crate::Error;
+
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents a single event happening during a game
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Event {
+    /// The dices have been rolled by a player. For the opening roll, the player is the one who
+    /// won it, or nobody if both dices are equal.
+    Roll {
+        /// The player who rolled
+        player: Player,
+        /// The dice values rolled
+        dices: (u8, u8),
+    },
+    /// A checker has been moved
+    Move {
+        /// The player who moved
+        player: Player,
+        /// The move played, including the checkers hit
+        mv: CheckerMove,
+    },
+    /// The cube has been doubled automatically after an equal opening roll and remains centered
+    AutomaticDouble {
+        /// The value of the cube after doubling
+        value: u64,
+    },
+    /// The cube has been offered to the opponent
+    CubeOffer {
+        /// The player who offered the cube
+        player: Player,
+        /// The value the cube has been offered at
+        value: u64,
+    },
+    /// The offered cube has been taken
+    CubeTake {
+        /// The player who took the cube
+        player: Player,
+    },
+    /// The offered cube has been taken and immediately redoubled, the taker keeps the cube
+    Beaver {
+        /// The player who beavered
+        player: Player,
+        /// The value of the cube after redoubling
+        value: u64,
+    },
+    /// The beavered cube has been doubled once more, the opponent keeps the cube
+    Raccoon {
+        /// The player who raccooned
+        player: Player,
+        /// The value of the cube after doubling
+        value: u64,
+    },
+    /// The offered cube has been dropped, which ends the game
+    CubeDrop {
+        /// The player who dropped the cube
+        player: Player,
+    },
+    /// A player has resigned, which ends the game
+    Resign {
+        /// The player who resigned
+        player: Player,
+        /// The victory granted to the opponent
+        victory: Victory,
+    },
+}
+
+// implement Display trait
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Roll { player, dices } => {
+                write!(f, "{} rolls {}{}", player, dices.0, dices.1)
+            }
+            Event::Move { player, mv } => write!(f, "{} moves {}", player, mv),
+            Event::AutomaticDouble { value } => {
+                write!(f, "Cube doubles automatically to {}", value)
+            }
+            Event::CubeOffer { player, value } => write!(f, "{} doubles to {}", player, value),
+            Event::CubeTake { player } => write!(f, "{} takes", player),
+            Event::Beaver { player, value } => write!(f, "{} beavers to {}", player, value),
+            Event::Raccoon { player, value } => write!(f, "{} raccoons to {}", player, value),
+            Event::CubeDrop { player } => write!(f, "{} drops", player),
+            Event::Resign { player, victory } => write!(f, "{} resigns {}", player, victory),
+        }
+    }
+}
+
+/// Receives the events of a game as they happen, see [`Game::subscribe`](crate::Game::subscribe)
+///
+/// Every event added to the [`GameRecord`] is passed to [`on_event`](GameObserver::on_event),
+/// after the game has been changed by it. The other methods single out what frontends commonly
+/// animate. All methods do nothing by default, and closures taking the game and the event are
+/// observers as well.
+/// ```
+/// use backgammon::{Event, Game, GameObserver};
+/// use backgammon::rules::{AbsolutePoint, Player};
+/// use std::sync::mpsc;
+///
+/// struct Hits(mpsc::Sender<(Player, u8)>);
+///
+/// impl GameObserver for Hits {
+///     fn on_hit(&mut self, _: &Game, player: Player, point: AbsolutePoint) {
+///         self.0.send((player, point.number())).unwrap();
+///     }
+/// }
+///
+/// // a blot of player 1 on the 5 point
+/// let mut g = Game::new();
+/// g.board.set(Player::Player1, 5, -1).unwrap();
+/// g.board.set(Player::Player1, 19, 1).unwrap();
+///
+/// let (sender, hits) = mpsc::channel();
+/// g.subscribe(Hits(sender));
+/// let (sender, events) = mpsc::channel();
+/// g.subscribe(move |_: &Game, event: &Event| sender.send(event.to_string()).unwrap());
+///
+/// g.roll_with((3, 1)).unwrap();
+/// g.apply_play(&g.parse_play("8/5 6/5").unwrap()).unwrap();
+/// assert_eq!(events.try_iter().count(), 3);
+/// assert_eq!(hits.try_iter().collect::<Vec<_>>(), vec![(Player::Player1, 5)]);
+/// ```
+pub trait GameObserver: Send + Sync {
+    /// Called for every event recorded
+    fn on_event(&mut self, game: &Game, event: &Event) {
+        let _ = (game, event);
+    }
+
+    /// Called for every checker hit by a move, after [`on_event`](GameObserver::on_event) of the
+    /// move. The player is the owner of the checker hit.
+    fn on_hit(&mut self, game: &Game, player: Player, point: AbsolutePoint) {
+        let _ = (game, player, point);
+    }
+
+    /// Called once the game has ended
+    fn on_game_over(&mut self, game: &Game, result: &GameResult) {
+        let _ = (game, result);
+    }
+
+    /// Called after an action has been taken back, see [`Game::undo`]
+    fn on_undo(&mut self, game: &Game) {
+        let _ = game;
+    }
+
+    /// Called after an action taken back has been played again, see [`Game::redo`]
+    fn on_redo(&mut self, game: &Game) {
+        let _ = game;
+    }
+}
+
+/// Closures receive every event
+impl<F: FnMut(&Game, &Event) + Send + Sync> GameObserver for F {
+    fn on_event(&mut self, game: &Game, event: &Event) {
+        self(game, event)
+    }
+}
+
+/// Holds all events of a game in the order they happened, starting from an initial position.
+///
+/// The record is append-only, the [`Game`](crate::Game) adds an event for every roll, checker
+/// move, cube action and resignation. Undoing an action removes its events again.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameRecord {
+    /// The position the game started from
+    start: Board,
+    /// The events in the order they happened
+    events: Vec<Event>,
+    /// The seed of the dices rolled, if they can be reproduced
+    #[cfg_attr(feature = "serde", serde(default))]
+    seed: Option<u64>,
+}
+
+impl GameRecord {
+    /// Create a new record for a game starting from the given position
+    pub fn new(start: Board) -> Self {
+        GameRecord {
+            start,
+            events: Vec::new(),
+            seed: None,
+        }
+    }
+
+    /// Returns the position the game started from
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    /// Returns the seed the dices of the game have been rolled from, if the game has been played
+    /// with reproducible dices like [`SeededDice`](crate::rules::SeededDice)
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Record the seed the dices of the game are rolled from
+    pub(crate) fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// Returns all events in the order they happened
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Returns the last event, if any
+    pub fn last(&self) -> Option<&Event> {
+        self.events.last()
+    }
+
+    /// Add an event to the record
+    pub(crate) fn push(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Replay the game position by position, starting with the position the game started from
+    /// and followed by one position after each event.
+    ///
+    /// All positions are computed upfront, so an error is returned if an event cannot be applied,
+    /// e.g. because a move is not possible on the board. The replay can be walked forward and
+    /// backward.
+    /// ```
+    /// use backgammon::{Game, Phase};
+    /// use backgammon::rules::{Dices, Field, Player};
+    ///
+    /// let mut g = Game::new();
+    /// g.who_plays = Player::Player0;
+    /// g.dices = Dices::new((3, 1));
+    /// g.phase = Phase::AwaitingMove;
+    /// g.play_die(Field::Point(7), 3).unwrap();
+    ///
+    /// let mut replay = g.record().replay().unwrap();
+    /// assert_eq!(replay.len(), 2);
+    /// assert_eq!(replay.next_back().unwrap().board, g.board);
+    /// ```
+    pub fn replay(&self) -> Result<Replay, Error> {
+        let mut position = Position {
+            board: self.start.clone(),
+            dices: Dices::default(),
+            cube: Cube::default(),
+            who_plays: Player::Nobody,
+            event: None,
+        };
+        let mut offered = None;
+        let mut positions = Vec::with_capacity(self.events.len() + 1);
+        positions.push(position.clone());
+
+        for event in self.events.iter() {
+            match event {
+                Event::Roll { player, dices } => {
+                    position.dices = Dices::new(*dices);
+                    position.who_plays = *player;
+                }
+                Event::Move { player, mv } => {
+                    position.board.apply(*player, mv)?;
+                    for step in mv.steps.iter() {
+                        // dices set by hand are not recorded, hence they might be unknown
+                        if position.dices.values != (0, 0) {
+                            position.dices.consume(*step)?;
+                        }
+                    }
+                    position.who_plays = *player;
+                }
+                Event::AutomaticDouble { value } => position.cube.set(*value)?,
+                Event::CubeOffer { value, .. } => offered = Some(*value),
+                Event::CubeTake { player } => {
+                    position
+                        .cube
+                        .set(offered.take().ok_or(Error::CubeValueInvalid)?)?;
+                    position.cube.set_owner(*player);
+                }
+                Event::Beaver { player, value } => {
+                    offered = None;
+                    position.cube.set(*value)?;
+                    position.cube.set_owner(*player);
+                }
+                Event::Raccoon { value, .. } => position.cube.set(*value)?,
+                Event::CubeDrop { .. } => offered = None,
+                Event::Resign { .. } => {}
+            }
+            position.event = Some(event.clone());
+            positions.push(position.clone());
+        }
+
+        Ok(Replay {
+            positions: positions.into_iter(),
+        })
+    }
+}
+
+/// Represents the state of a game at one step of a [`Replay`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Position {
+    /// board for player 0 and 1
+    pub board: Board,
+    /// last dice pair rolled, with the dice values played so far consumed
+    pub dices: Dices,
+    /// cube value and owner
+    pub cube: Cube,
+    /// the player who acted last, or nobody before the first roll
+    pub who_plays: Player,
+    /// the event which led to this position, or none for the starting position
+    pub event: Option<Event>,
+}
+
+/// Iterates over all positions of a recorded game, see [`GameRecord::replay`]
+#[derive(Debug, Clone)]
+pub struct Replay {
+    positions: alloc::vec::IntoIter<Position>,
+}
+
+impl Iterator for Replay {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.positions.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Replay {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.positions.next_back()
+    }
+}
+
+impl ExactSizeIterator for Replay {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Field;
+
+    #[test]
+    fn test_record() {
+        let mut record = GameRecord::default();
+        assert_eq!(record.start(), &Board::default());
+        assert!(record.last().is_none());
+
+        record.push(Event::Roll {
+            player: Player::Player0,
+            dices: (3, 1),
+        });
+        record.push(Event::CubeTake {
+            player: Player::Player1,
+        });
+        assert_eq!(record.events().len(), 2);
+        assert_eq!(
+            record.last(),
+            Some(&Event::CubeTake {
+                player: Player::Player1
+            })
+        );
+    }
+
+    #[test]
+    fn test_replay() -> Result<(), Error> {
+        let mut record = GameRecord::new(Board::new());
+        record.push(Event::Roll {
+            player: Player::Player0,
+            dices: (3, 1),
+        });
+        record.push(Event::Move {
+            player: Player::Player0,
+            mv: CheckerMove::new(Field::Point(7), vec![3])?,
+        });
+        record.push(Event::Move {
+            player: Player::Player0,
+            mv: CheckerMove::new(Field::Point(5), vec![1])?,
+        });
+        record.push(Event::CubeOffer {
+            player: Player::Player1,
+            value: 2,
+        });
+        record.push(Event::CubeTake {
+            player: Player::Player0,
+        });
+
+        let positions: Vec<Position> = record.replay()?.collect();
+        assert_eq!(positions.len(), 6);
+        assert_eq!(positions[0].board, Board::new());
+        assert_eq!(positions[0].event, None);
+        assert_eq!(positions[1].dices.available(), vec![3, 1]);
+        assert_eq!(positions[2].dices.available(), vec![1]);
+        assert!(positions[3].dices.all_consumed());
+        assert_eq!(
+            positions[3]
+                .board
+                .get_checkers(Player::Player0, Field::Point(4))?,
+            2
+        );
+        assert_eq!(
+            positions[3]
+                .board
+                .get_checkers(Player::Player0, Field::Point(7))?,
+            2
+        );
+        assert_eq!(positions[4].cube.value(), 1);
+        assert_eq!(positions[5].cube.value(), 2);
+        assert_eq!(positions[5].cube.owner(), Player::Player0);
+        assert_eq!(positions[5].event, record.last().cloned());
+
+        let backward: Vec<Position> = record.replay()?.rev().collect();
+        assert_eq!(backward[0], positions[5]);
+        assert_eq!(backward[5], positions[0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay_beaver() -> Result<(), Error> {
+        let mut record = GameRecord::default();
+        record.push(Event::AutomaticDouble { value: 2 });
+        record.push(Event::CubeOffer {
+            player: Player::Player0,
+            value: 4,
+        });
+        record.push(Event::Beaver {
+            player: Player::Player1,
+            value: 4,
+        });
+        record.push(Event::Raccoon {
+            player: Player::Player0,
+            value: 8,
+        });
+        let last = record
+            .replay()?
+            .next_back()
+            .ok_or(Error::CubeValueInvalid)?;
+        assert_eq!(last.cube.value(), 8);
+        assert_eq!(last.cube.owner(), Player::Player1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay_invalid() -> Result<(), Error> {
+        let mut record = GameRecord::new(Board::new());
+        record.push(Event::Move {
+            player: Player::Player0,
+            mv: CheckerMove::new(Field::Point(10), vec![3])?,
+        });
+        assert!(record.replay().is_err());
+
+        let mut record = GameRecord::new(Board::new());
+        record.push(Event::CubeTake {
+            player: Player::Player0,
+        });
+        assert!(record.replay().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> Result<(), Error> {
+        assert_eq!(
+            Event::Roll {
+                player: Player::Player0,
+                dices: (3, 1)
+            }
+            .to_string(),
+            "Player 0 rolls 31"
+        );
+        assert_eq!(
+            Event::Move {
+                player: Player::Player1,
+                mv: CheckerMove::new(Field::Point(7), vec![3])?
+            }
+            .to_string(),
+            "Player 1 moves 8/5"
+        );
+        assert_eq!(
+            Event::AutomaticDouble { value: 2 }.to_string(),
+            "Cube doubles automatically to 2"
+        );
+        assert_eq!(
+            Event::CubeOffer {
+                player: Player::Player0,
+                value: 2
+            }
+            .to_string(),
+            "Player 0 doubles to 2"
+        );
+        assert_eq!(
+            Event::CubeTake {
+                player: Player::Player1
+            }
+            .to_string(),
+            "Player 1 takes"
+        );
+        assert_eq!(
+            Event::Beaver {
+                player: Player::Player1,
+                value: 4
+            }
+            .to_string(),
+            "Player 1 beavers to 4"
+        );
+        assert_eq!(
+            Event::Raccoon {
+                player: Player::Player0,
+                value: 8
+            }
+            .to_string(),
+            "Player 0 raccoons to 8"
+        );
+        assert_eq!(
+            Event::CubeDrop {
+                player: Player::Player1
+            }
+            .to_string(),
+            "Player 1 drops"
+        );
+        assert_eq!(
+            Event::Resign {
+                player: Player::Player0,
+                victory: Victory::Gammon
+            }
+            .to_string(),
+            "Player 0 resigns Gammon"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,551 @@
+//! # Monte Carlo Rollouts
+use crate::bearoff::database;
+use crate::eval::{rank_plays, Evaluation, Evaluator};
+use crate::game::{Game, Phase, Victory};
+use crate::rules::{DiceRoll, DiceSource, Player, RandomDices, SeededDice};
+use crate::Error;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The cubeless equity from which the player on roll doubles during a cubeful rollout
+const DOUBLE_EQUITY: f64 = 0.4;
+
+/// The cubeless equity of the player who doubled from which the opponent passes
+const PASS_EQUITY: f64 = 0.5;
+
+/// Holds the settings of a rollout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RolloutSettings {
+    /// The number of games played, default is 144
+    pub trials: u32,
+    /// Whether the players use the cube, in which case the equity counts the points won with the
+    /// cube. Default is false.
+    pub cubeful: bool,
+    /// The number of rolls after which a game is stopped and the position is evaluated instead of
+    /// played to completion, if any. Default is none.
+    pub truncation: Option<u32>,
+    /// The seed the dices of the games are derived from, which makes the rollout reproducible, if
+    /// any. Default is none.
+    pub seed: Option<u64>,
+    /// Whether the first roll of each game is stratified over all 36 outcomes and the luck of
+    /// every roll is subtracted from the outcome, which reduces the variance of the rollout
+    /// considerably at the cost of evaluating all rolls at every turn. Default is false.
+    pub variance_reduction: bool,
+}
+
+impl Default for RolloutSettings {
+    fn default() -> Self {
+        RolloutSettings {
+            trials: 144,
+            cubeful: false,
+            truncation: None,
+            seed: None,
+            variance_reduction: false,
+        }
+    }
+}
+
+impl RolloutSettings {
+    /// Set the number of games played
+    pub fn with_trials(mut self, trials: u32) -> Self {
+        self.trials = trials;
+        self
+    }
+
+    /// Let the players use the cube
+    pub fn with_cubeful(mut self) -> Self {
+        self.cubeful = true;
+        self
+    }
+
+    /// Stop every game after the given number of rolls and evaluate the position
+    pub fn with_truncation(mut self, rolls: u32) -> Self {
+        self.truncation = Some(rolls);
+        self
+    }
+
+    /// Derive the dices of the games from a seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Reduce the variance of the rollout, see
+    /// [`variance_reduction`](RolloutSettings::variance_reduction). The number of trials should
+    /// be a multiple of 36, so that every first roll is played equally often.
+    pub fn with_variance_reduction(mut self) -> Self {
+        self.variance_reduction = true;
+        self
+    }
+
+    /// Returns the first roll of a trial, counted from 0, if the first rolls are stratified
+    pub(crate) fn first_roll(&self, trial: u32) -> Option<(u8, u8)> {
+        let outcome = (trial % 36) as u8;
+        self.variance_reduction
+            .then_some((outcome / 6 + 1, outcome % 6 + 1))
+    }
+
+    /// Returns the source of the dices of a trial, counted from 0
+    pub(crate) fn dices(&self, trial: u32) -> Box<dyn DiceSource> {
+        match self.seed {
+            Some(seed) => Box::new(SeededDice::new(seed.wrapping_add(u64::from(trial)))),
+            None => Box::new(RandomDices),
+        }
+    }
+}
+
+/// Represents the result of a rollout from the point-of-view of the player on roll
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rollout {
+    /// The number of games played
+    pub trials: u32,
+    /// The average outcome of the games, ignoring the cube
+    pub evaluation: Evaluation,
+    /// The average points won per point of the initial cube value, counting the cube if the
+    /// rollout is cubeful
+    pub equity: f64,
+    /// The standard error of the equity
+    pub standard_error: f64,
+}
+
+// implement Display trait
+impl fmt::Display for Rollout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} trials: {}, Rollout: {:+.3} ± {:.3}",
+            self.trials, self.evaluation, self.equity, self.standard_error
+        )
+    }
+}
+
+/// Play the best play according to the evaluator
+fn play_best(game: &mut Game, evaluator: &(impl Evaluator + ?Sized)) -> Result<(), Error> {
+    let best = rank_plays(game, evaluator)?
+        .into_iter()
+        .next()
+        .ok_or(Error::MoveInvalid)?;
+    for mv in best.0.moves {
+        game.apply_move(&mv)?;
+    }
+    Ok(())
+}
+
+/// Returns the evaluation of the position for the player on roll, looked up in the installed
+/// two-sided bearoff database if it holds the position
+fn evaluate(game: &Game, evaluator: &(impl Evaluator + ?Sized)) -> Result<Evaluation, Error> {
+    match database().and_then(|db| db.evaluate(&game.board, game.who_plays)) {
+        Some(evaluation) => Ok(evaluation),
+        None => evaluator.evaluate(&game.board, game.who_plays),
+    }
+}
+
+/// Returns the evaluation after the best play of a roll, from the point-of-view of the player on
+/// roll
+fn roll_evaluation(
+    game: &Game,
+    values: (u8, u8),
+    evaluator: &(impl Evaluator + ?Sized),
+) -> Result<Evaluation, Error> {
+    let player = game.who_plays;
+    let mut g = game.clone();
+    let _ = g.roll_with(values)?;
+    match g.phase {
+        Phase::AwaitingMove => Ok(rank_plays(&g, evaluator)?
+            .into_iter()
+            .next()
+            .ok_or(Error::MoveInvalid)?
+            .1),
+        // the roll cannot be played at all, and the opponent is on roll
+        _ => {
+            let e = evaluate(&g, evaluator)?;
+            Ok(match g.who_plays == player {
+                true => e,
+                false => e.inverted(),
+            })
+        }
+    }
+}
+
+/// Returns the luck of a roll of the player on roll, i.e. the evaluation and the equity after
+/// the best play of the roll minus their averages over all rolls, from the point-of-view of the
+/// player on roll
+fn luck(
+    game: &Game,
+    values: (u8, u8),
+    evaluator: &(impl Evaluator + ?Sized),
+) -> Result<(Evaluation, f64), Error> {
+    let mut average = Evaluation::default();
+    let mut equity = 0.0;
+    for (roll, probability) in DiceRoll::all_distinct() {
+        let e = roll_evaluation(game, (roll.0, roll.1), evaluator)?;
+        average = combine(&average, &e, probability);
+        equity += probability * e.equity();
+    }
+    let actual = roll_evaluation(game, values, evaluator)?;
+    Ok((combine(&actual, &average, -1.0), actual.equity() - equity))
+}
+
+/// Returns the first evaluation plus the second one times a factor, per probability
+fn combine(a: &Evaluation, b: &Evaluation, factor: f64) -> Evaluation {
+    Evaluation {
+        win: a.win + factor * b.win,
+        win_gammon: a.win_gammon + factor * b.win_gammon,
+        win_backgammon: a.win_backgammon + factor * b.win_backgammon,
+        lose_gammon: a.lose_gammon + factor * b.lose_gammon,
+        lose_backgammon: a.lose_backgammon + factor * b.lose_backgammon,
+    }
+}
+
+/// Let the player on roll double if the position is strong enough, but not too strong
+fn double(game: &mut Game, evaluator: &(impl Evaluator + ?Sized)) -> Result<(), Error> {
+    let player = game.who_plays;
+    if !game.cube_is_live(player) || game.cube.owner() == player.other() {
+        return Ok(());
+    }
+    let e = evaluate(game, evaluator)?;
+    if e.equity() >= DOUBLE_EQUITY
+        && e.win_gammon < 0.5 * e.win
+        && game.offer_double(player).is_ok()
+    {
+        respond(game, evaluator)?;
+    }
+    Ok(())
+}
+
+/// Let the opponent of the player on roll take or pass the cube offered
+fn respond(game: &mut Game, evaluator: &(impl Evaluator + ?Sized)) -> Result<(), Error> {
+    let opponent = game.who_plays.other();
+    if evaluate(game, evaluator)?.equity() >= PASS_EQUITY {
+        game.pass(opponent)
+    } else {
+        game.take(opponent)
+    }
+}
+
+/// Play one game of a rollout to completion or until truncated, and return the outcome and the
+/// points won per point of the initial cube value, both from the point-of-view of the player.
+/// With variance reduction, the first roll is the given one and the luck of all rolls is
+/// subtracted.
+pub(crate) fn trial(
+    game: &Game,
+    player: Player,
+    evaluator: &(impl Evaluator + ?Sized),
+    settings: &RolloutSettings,
+    dices: &mut dyn DiceSource,
+    first: Option<(u8, u8)>,
+) -> Result<(Evaluation, f64), Error> {
+    let (e, points, luck, luck_points) =
+        play_trial(game, player, evaluator, settings, dices, first)?;
+    Ok((combine(&e, &luck, -1.0), points - luck_points))
+}
+
+/// Play one game of a rollout, and return the outcome and the points won as well as the luck of
+/// the rolls in the outcome and in points, all from the point-of-view of the player
+fn play_trial(
+    game: &Game,
+    player: Player,
+    evaluator: &(impl Evaluator + ?Sized),
+    settings: &RolloutSettings,
+    dices: &mut dyn DiceSource,
+    first: Option<(u8, u8)>,
+) -> Result<(Evaluation, f64, Evaluation, f64), Error> {
+    let mut g = game.clone();
+    let cube = g.cube.value() as f64;
+    let mut rolls = 0;
+    let mut luck = Evaluation::default();
+    let mut luck_points = 0.0;
+    loop {
+        let value = match settings.cubeful {
+            true => g.cube.value() as f64 / cube,
+            false => 1.0,
+        };
+        match g.phase {
+            Phase::AwaitingRoll => {
+                if settings.truncation.is_some_and(|t| rolls >= t) {
+                    let mut e = evaluate(&g, evaluator)?;
+                    if g.who_plays != player {
+                        e = e.inverted();
+                    }
+                    return Ok((e, e.equity() * value, luck, luck_points));
+                }
+                if settings.cubeful {
+                    double(&mut g, evaluator)?;
+                    if g.phase != Phase::AwaitingRoll {
+                        continue;
+                    }
+                }
+                let values = match (first, rolls) {
+                    (Some(values), 0) => values,
+                    _ => dices.roll(),
+                };
+                if settings.variance_reduction {
+                    let (l, equity) = self::luck(&g, values, evaluator)?;
+                    // the luck of the opponent is bad luck of the player, whose probabilities to
+                    // win and to lose swap
+                    let (l, equity) = match g.who_plays == player {
+                        true => (l, equity),
+                        false => (
+                            Evaluation {
+                                win: -l.win,
+                                win_gammon: l.lose_gammon,
+                                win_backgammon: l.lose_backgammon,
+                                lose_gammon: l.win_gammon,
+                                lose_backgammon: l.win_backgammon,
+                            },
+                            -equity,
+                        ),
+                    };
+                    luck = combine(&luck, &l, 1.0);
+                    luck_points += equity * value;
+                }
+                let _ = g.roll_with(values)?;
+                rolls += 1;
+            }
+            Phase::AwaitingCubeDecision => respond(&mut g, evaluator)?,
+            Phase::AwaitingMove => play_best(&mut g, evaluator)?,
+            Phase::GameOver => break,
+        }
+    }
+
+    let result = g.result().ok_or(Error::GameEnded)?;
+    let won = f64::from(u8::from(result.victory != Victory::Single));
+    let backgammon = f64::from(u8::from(result.victory == Victory::Backgammon));
+    let e = Evaluation {
+        win: 1.0,
+        win_gammon: won,
+        win_backgammon: backgammon,
+        lose_gammon: 0.0,
+        lose_backgammon: 0.0,
+    };
+    let points = match settings.cubeful {
+        true => result.points as f64 / cube,
+        false => result.victory.multiplier() as f64,
+    };
+    match result.winner == player {
+        true => Ok((e, points, luck, luck_points)),
+        false => Ok((e.inverted(), -points, luck, luck_points)),
+    }
+}
+
+/// Returns the player from whose point-of-view a game is rolled out
+pub(crate) fn rollout_player(game: &Game) -> Result<Player, Error> {
+    match (game.phase, game.who_plays) {
+        (Phase::GameOver, _) => Err(Error::GameEnded),
+        (_, Player::Nobody) => Err(Error::RollFirst),
+        (_, player) => Ok(player),
+    }
+}
+
+/// Sum up the outcomes of the trials of a rollout
+pub(crate) fn summarize(outcomes: &[(Evaluation, f64)]) -> Rollout {
+    let n = outcomes.len() as f64;
+    if outcomes.is_empty() {
+        return Rollout::default();
+    }
+    let mean = |f: fn(&Evaluation) -> f64| outcomes.iter().map(|(e, _)| f(e)).sum::<f64>() / n;
+    let equity = outcomes.iter().map(|(_, p)| p).sum::<f64>() / n;
+    let variance = match outcomes.len() {
+        1 => 0.0,
+        _ => {
+            outcomes
+                .iter()
+                .map(|(_, p)| (p - equity).powi(2))
+                .sum::<f64>()
+                / (n - 1.0)
+        }
+    };
+    Rollout {
+        trials: outcomes.len() as u32,
+        evaluation: Evaluation {
+            win: mean(|e| e.win),
+            win_gammon: mean(|e| e.win_gammon),
+            win_backgammon: mean(|e| e.win_backgammon),
+            lose_gammon: mean(|e| e.lose_gammon),
+            lose_backgammon: mean(|e| e.lose_backgammon),
+        },
+        equity,
+        standard_error: (variance / n).sqrt(),
+    }
+}
+
+/// Play the game from its current position to completion many times, every player choosing the
+/// best play according to the evaluator, and return the average outcome from the point-of-view
+/// of the player on roll. If the player has rolled already, the dices rolled are played first.
+/// ```
+/// use backgammon::Game;
+/// use backgammon::eval::Heuristic;
+/// use backgammon::rollout::{rollout, RolloutSettings};
+///
+/// let mut g = Game::new();
+/// g.roll_with((3, 1)).unwrap();
+/// let settings = RolloutSettings::default().with_trials(6).with_truncation(4).with_seed(1);
+/// let result = rollout(&g, &Heuristic, &settings).unwrap();
+/// assert_eq!(result.trials, 6);
+/// assert_eq!(result, rollout(&g, &Heuristic, &settings).unwrap());
+/// ```
+pub fn rollout(
+    game: &Game,
+    evaluator: &(impl Evaluator + Sync + ?Sized),
+    settings: &RolloutSettings,
+) -> Result<Rollout, Error> {
+    let player = rollout_player(game)?;
+    let outcomes = trials(game, player, evaluator, settings)?;
+    Ok(summarize(&outcomes))
+}
+
+/// Play the trials of a rollout, with the `parallel` feature spread across all cores. The dices
+/// of a trial only depend on the trial number, so that a seeded rollout gives the same result on
+/// any number of cores.
+fn trials(
+    game: &Game,
+    player: Player,
+    evaluator: &(impl Evaluator + Sync + ?Sized),
+    settings: &RolloutSettings,
+) -> Result<Vec<(Evaluation, f64)>, Error> {
+    #[cfg(feature = "parallel")]
+    let trials = (0..settings.trials).into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let trials = 0..settings.trials;
+    trials
+        .map(|i| {
+            let dices = &mut *settings.dices(i);
+            trial(
+                game,
+                player,
+                evaluator,
+                settings,
+                dices,
+                settings.first_roll(i),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Heuristic;
+    use crate::rules::{Board, Point};
+
+    /// A game in which player 0 is on roll in a race with the given checkers on the ace point
+    fn race(ones: i8, twos: i8) -> Result<Game, Error> {
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.set(Player::Player0, 0, ones)?;
+        board.set(Player::Player0, 1, twos)?;
+        board.set_off(Player::Player0, (15 - ones - twos) as u8)?;
+        board.set(Player::Player1, 5, 15)?;
+        let mut g = Game::new();
+        g.board = board;
+        g.who_plays = Player::Player0;
+        Ok(g)
+    }
+
+    #[test]
+    fn test_rollout() -> Result<(), Error> {
+        // player 0 bears off the last two checkers with any roll
+        let g = race(2, 0)?;
+        let result = rollout(&g, &Heuristic, &RolloutSettings::default().with_trials(10))?;
+        assert_eq!(result.trials, 10);
+        assert_eq!(result.evaluation.win, 1.0);
+        assert_eq!(result.evaluation.win_gammon, 1.0);
+        assert_eq!(result.equity, 2.0);
+        assert_eq!(result.standard_error, 0.0);
+        assert_eq!(
+            format!("{}", result),
+            "10 trials: Win: 100.0% (G 100.0%, BG 0.0%), Lose: 0.0% (G 0.0%, BG 0.0%), Equity: +2.000, Rollout: +2.000 ± 0.000"
+        );
+
+        // the same seed gives the same result
+        let g = race(3, 3)?;
+        let settings = RolloutSettings::default().with_trials(20).with_seed(7);
+        let result = rollout(&g, &Heuristic, &settings)?;
+        assert_eq!(result, rollout(&g, &Heuristic, &settings)?);
+        assert!(result.evaluation.win > 0.5);
+        assert!(result.standard_error > 0.0);
+
+        // the outcome of a trial does not depend on how the trials are spread across cores
+        let single = RolloutSettings::default().with_seed(7).with_trials(1);
+        let outcome = rollout(&g, &Heuristic, &single)?;
+        assert_eq!(
+            trials(&g, Player::Player0, &Heuristic, &settings)?[0],
+            (outcome.evaluation, outcome.equity)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncation() -> Result<(), Error> {
+        let mut g = Game::new();
+        let _ = g.roll_with((6, 5))?;
+        let settings = RolloutSettings::default()
+            .with_trials(4)
+            .with_truncation(0)
+            .with_seed(1);
+        let result = rollout(&g, &Heuristic, &settings)?;
+        // the opening play is made, then every game is evaluated at once
+        assert_eq!(result.standard_error, 0.0);
+        assert!(result.evaluation.win > 0.0 && result.evaluation.win < 1.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cubeful() -> Result<(), Error> {
+        // player 1 bears off the last two checkers with any roll and doubles player 0 out
+        let mut g = race(0, 4)?;
+        g.board.points[18] = Point::EMPTY;
+        g.board.set(Player::Player1, 0, 2)?;
+        g.board.set_off(Player::Player1, 13)?;
+        g.who_plays = Player::Player1;
+        let settings = RolloutSettings::default().with_trials(4).with_cubeful();
+        let result = rollout(&g, &Heuristic, &settings)?;
+        assert_eq!(result.equity, 1.0);
+        assert_eq!(result.evaluation.win, 1.0);
+        assert_eq!(result.evaluation.win_gammon, 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_variance_reduction() -> Result<(), Error> {
+        let settings = RolloutSettings::default().with_variance_reduction();
+        assert_eq!(settings.first_roll(0), Some((1, 1)));
+        assert_eq!(settings.first_roll(35), Some((6, 6)));
+        assert_eq!(settings.first_roll(37), Some((1, 2)));
+        assert_eq!(RolloutSettings::default().first_roll(0), None);
+
+        // no roll is lucky if every roll wins
+        let g = race(2, 0)?;
+        let result = rollout(&g, &Heuristic, &settings.with_trials(36))?;
+        assert!((result.equity - 2.0).abs() < 1e-9);
+        assert!(result.standard_error < 1e-9);
+
+        // the luck of the rolls is the largest part of the variance
+        let mut g = race(0, 0)?;
+        g.board.points[5] = Point::new(Player::Player0, 2);
+        g.board.points[18] = Point::EMPTY;
+        g.board.points[20] = Point::new(Player::Player1, 2);
+        g.board.off = [13, 13];
+        let plain = RolloutSettings::default().with_trials(36).with_seed(3);
+        let reduced = plain.with_variance_reduction();
+        let plain = rollout(&g, &Heuristic, &plain)?;
+        let reduced = rollout(&g, &Heuristic, &reduced)?;
+        assert!(reduced.standard_error < plain.standard_error / 2.0);
+        assert!((reduced.equity - plain.equity).abs() < 3.0 * plain.standard_error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            rollout(&Game::new(), &Heuristic, &RolloutSettings::default()),
+            Err(Error::RollFirst)
+        ));
+    }
+}
@@ -1,6 +1,9 @@
 /// Implements the board
 mod board;
-pub use board::{Board, BoardDisplay, Move, PlayerBoard};
+pub use board::{Board, BoardDisplay, Move, Occupancy, PlayerBoard, Point};
+/// Implements the numbering of the points for both players
+mod coordinates;
+pub use coordinates::{AbsolutePoint, RelativePoint};
 /// Implements the double dice or cube
 mod cube;
 pub use cube::Cube;
@@ -9,13 +12,222 @@ mod player;
 pub use player::Player;
 /// Implements the pair of dices
 mod dices;
-pub use dices::{Dices, Roll};
+pub use dices::{DiceRoll, DiceSource, Dices, Roll, ScriptedDice, SeededDice};
+#[cfg(feature = "std")]
+pub use dices::{ManualDice, RandomDices};
+/// Implements the moves of checkers
+pub(crate) mod moves;
+pub use moves::{CheckerMove, Field, Play};
 
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::fmt;
+
+use crate::Error;
+
+/// Represents the variant of Backgammon played, which defines the starting position, the rules
+/// for fields occupied by the opponent including the direction of movement, the legal plays, and
+/// whether the cube is used. All variants share the same board and game.
+///
+/// Besides Backgammon, Nackgammon, and Hypergammon, the three games of Greek Tavli and the Russian
+/// long Narde are supported. They are played without the cube, and a backgammon only counts as a
+/// gammon. In Plakoto, pinning the last checker of the opponent on its starting field wins a
+/// gammon. In Fevga and Narde, no play may build a prime of six consecutive fields unless a checker
+/// of the opponent is in front of it, which also keeps the starting quarter of the opponent open
+/// early in the game.
+///
+/// The variants are a closed set on purpose, there is no trait to implement for other variants:
+/// the variant is part of the [`Rules`], which are copied, compared, and serialized with matches,
+/// and it is named in match files as parsed by [`FromStr`]. A new variant is added as a case of
+/// this enum, with its rules in the methods below, which [`Game`](crate::Game) consults
+/// for the starting position, the occupancy of fields, the legal plays, the cube, and
+/// backgammons. Other starting positions of a supported variant are set up with
+/// [`Game::with_starting_position`](crate::Game::with_starting_position).
+/// ```
+/// use backgammon::rules::{Board, Field, Player, Variant};
+///
+/// assert_eq!(Variant::Backgammon.start(), Board::new());
+/// let board = Variant::Nackgammon.start();
+/// assert_eq!(board.get_checkers(Player::Player0, Field::Point(22)).unwrap(), 2);
+/// assert_eq!("Nackgammon".parse::<Variant>().unwrap(), Variant::Nackgammon);
+/// ```
+#[derive(Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Variant {
+    /// Standard Backgammon
+    #[default]
+    Backgammon,
+    /// Nackgammon, in which each player starts with two more back checkers on the 23 point,
+    /// taken from the 13 point and the 6 point
+    Nackgammon,
+    /// Hypergammon, in which each player only plays three checkers starting on the 24, 23, and 22
+    /// points. The other twelve checkers are borne off from the start.
+    Hypergammon,
+    /// Portes, which is played like Backgammon
+    Portes,
+    /// Plakoto, in which all checkers start on the 24 point and a single checker of the opponent
+    /// is pinned instead of hit
+    Plakoto,
+    /// Fevga, in which all checkers start on the 24 point, both players move in the same direction,
+    /// and a single checker holds a field. No second checker may leave the 24 point before the
+    /// first has passed the starting field of the opponent.
+    Fevga,
+    /// Long Narde, which is played like Fevga, except that only one checker may leave the 24 point
+    /// per turn, or two with 6-6, 4-4, or 3-3 in the first turn
+    Narde,
+}
+
+impl Variant {
+    /// Returns the starting position of the variant
+    pub fn start(&self) -> Board {
+        let mut side = PlayerBoard::default();
+        match self {
+            Variant::Backgammon | Variant::Portes => {}
+            Variant::Nackgammon => {
+                side.board[22] = 2;
+                side.board[12] = 4;
+                side.board[5] = 4;
+            }
+            Variant::Hypergammon => {
+                side.board = [0; 24];
+                side.board[21..].fill(1);
+                side.off = 12;
+            }
+            Variant::Plakoto | Variant::Fevga | Variant::Narde => {
+                side.board = [0; 24];
+                side.board[23] = 15;
+            }
+        }
+        Board::from_sides([side.clone(), side], self.occupancy(), [0, 0])
+            .expect("the starting positions of all variants are valid")
+    }
+
+    /// Returns the rules for fields occupied by the opponent
+    pub fn occupancy(&self) -> Occupancy {
+        match self {
+            Variant::Backgammon | Variant::Nackgammon | Variant::Hypergammon | Variant::Portes => {
+                Occupancy::Hitting
+            }
+            Variant::Plakoto => Occupancy::Pinning,
+            Variant::Fevga | Variant::Narde => Occupancy::Holding,
+        }
+    }
+
+    /// Returns all legal ways for a player to play the given dice values, after the moves already
+    /// played with the same roll, see [`Board::plays`]
+    /// ```
+    /// use backgammon::rules::{Field, Player, Variant};
+    ///
+    /// let board = Variant::Narde.start();
+    /// let plays = Variant::Narde.plays(&board, Player::Player0, &[5, 2], &[]);
+    /// // only one checker may leave the head
+    /// assert!(plays.iter().all(|p| p[0].from == Field::Point(23) && p[1].from != Field::Point(23)));
+    /// // except for a few doubles in the first turn
+    /// let plays = Variant::Narde.plays(&board, Player::Player0, &[6, 6, 6, 6], &[]);
+    /// assert!(plays.iter().all(|p| p.len() == 2 && p[1].from == Field::Point(23)));
+    /// ```
+    pub fn plays(
+        &self,
+        board: &Board,
+        player: Player,
+        dices: &[u8],
+        played: &[CheckerMove],
+    ) -> Vec<Vec<CheckerMove>> {
+        // no play may build a prime without a checker of the opponent in front of it
+        let primes = |moves: &[CheckerMove]| {
+            let mut after = board.clone();
+            moves.iter().all(|m| after.apply(player, m).is_ok()) && !after.traps(player)
+        };
+        // a checker waits on the starting field while the first one has not passed the starting
+        // field of the opponent, i.e. field 11
+        let waits = |board: &Board| {
+            board.side(player).is_ok_and(|side| {
+                side.board[..23].iter().any(|&n| n > 0) && side.board[..11].iter().all(|&n| n == 0)
+            })
+        };
+        let leaves = |moves: &[CheckerMove]| {
+            let mut after = board.clone();
+            moves.iter().all(|m| {
+                (m.from != Field::Point(23) || !waits(&after)) && after.apply(player, m).is_ok()
+            })
+        };
+        match self {
+            Variant::Fevga => {
+                return board.plays_with(player, dices, |moves| leaves(moves) && primes(moves))
+            }
+            Variant::Narde => {}
+            _ => return board.plays(player, dices),
+        }
+        let head =
+            |moves: &[CheckerMove]| moves.iter().filter(|m| m.from == Field::Point(23)).count();
+        let first =
+            board.get_checkers(player, Field::Point(23)).unwrap_or(0) as usize + head(played) == 15;
+        let steps: Vec<u8> = played
+            .iter()
+            .flat_map(|m| m.steps.iter())
+            .chain(dices)
+            .copied()
+            .collect();
+        let limit = match steps[..] {
+            [a, b, c, d] if first && a == b && b == c && c == d && [3, 4, 6].contains(&a) => 2,
+            _ => 1,
+        };
+        board.plays_with(player, dices, |moves| {
+            head(played) + head(moves) <= limit && primes(moves)
+        })
+    }
+
+    /// Returns true if the variant is played with the cube
+    pub fn has_cube(&self) -> bool {
+        matches!(
+            self,
+            Variant::Backgammon | Variant::Nackgammon | Variant::Hypergammon
+        )
+    }
+
+    /// Returns true if a backgammon counts more than a gammon
+    pub fn has_backgammons(&self) -> bool {
+        self.has_cube()
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Variant::Backgammon => write!(f, "Backgammon"),
+            Variant::Nackgammon => write!(f, "Nackgammon"),
+            Variant::Hypergammon => write!(f, "Hypergammon"),
+            Variant::Portes => write!(f, "Portes"),
+            Variant::Plakoto => write!(f, "Plakoto"),
+            Variant::Fevga => write!(f, "Fevga"),
+            Variant::Narde => write!(f, "Narde"),
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Backgammon" => Ok(Variant::Backgammon),
+            "Nackgammon" => Ok(Variant::Nackgammon),
+            "Hypergammon" => Ok(Variant::Hypergammon),
+            "Portes" => Ok(Variant::Portes),
+            "Plakoto" => Ok(Variant::Plakoto),
+            "Fevga" => Ok(Variant::Fevga),
+            "Narde" => Ok(Variant::Narde),
+            _ => Err(Error::VariantInvalid),
+        }
+    }
+}
 
 /// Holds all the rule settings
-#[derive(Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Rules {
     /// The amount of points to reach for declaring a winner of the match, default is 7.
     pub points: u32,
@@ -30,7 +242,8 @@ pub struct Rules {
     /// How often to apply automatic doubling rule. 0 means always on. Default is 0.
     pub murphy_limit: u8,
     /// Gammon and Backgammon only count for double or triple values if the cube has already been
-    /// offered. Default is false.
+    /// offered. An automatic double under the [`murphy`](Rules::murphy) rule turns the cube as
+    /// well. Default is false.
     pub jacoby: bool,
     /// When a player first reaches a score of points - 1, no doubling is allowed for the following
     /// game. Default is true.
@@ -38,6 +251,9 @@ pub struct Rules {
     /// Permits to double after Crawford game only if both players have rolled at least twice.
     /// Default is false.
     pub holland: bool,
+    /// The variant played, one of the closed set of [`Variant`], which defines the starting
+    /// position and the rules of movement. Default is standard Backgammon.
+    pub variant: Variant,
 }
 
 impl Default for Rules {
@@ -51,6 +267,7 @@ impl Default for Rules {
             jacoby: false,
             crawford: true,
             holland: false,
+            variant: Variant::Backgammon,
         }
     }
 }
@@ -60,8 +277,8 @@ impl fmt::Display for Rules {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "Points: {}, Beaver: {}, Raccoon: {}, Murphy: {}, Murphy Limit: {}, Jacoby: {}, Crawford: {}, Holland: {}",
-            self.points, self.beaver, self.raccoon, self.murphy, self.murphy_limit, self.jacoby, self.crawford, self.holland
+            "Points: {}, Beaver: {}, Raccoon: {}, Murphy: {}, Murphy Limit: {}, Jacoby: {}, Crawford: {}, Holland: {}, Variant: {}",
+            self.points, self.beaver, self.raccoon, self.murphy, self.murphy_limit, self.jacoby, self.crawford, self.holland, self.variant
         )
     }
 }
@@ -89,6 +306,8 @@ pub trait GameRules {
     fn with_jacoby(self) -> Self;
     /// Permits to double after Crawford game only if both players have rolled at least twice
     fn with_holland(self) -> Self;
+    /// Play a variant of Backgammon with another starting position or other rules
+    fn with_variant(self, variant: Variant) -> Self;
 }
 
 /// Implements SetRules for Rules
@@ -131,6 +350,11 @@ impl GameRules for Rules {
         self.holland = true;
         self
     }
+
+    fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
 }
 
 /// Test if default rule is created correctly and if the rules can be modified
@@ -149,6 +373,7 @@ mod tests {
         assert!(!rules.jacoby);
         assert!(rules.crawford);
         assert!(!rules.holland);
+        assert_eq!(rules.variant, Variant::Backgammon);
     }
 
     #[test]
@@ -183,12 +408,138 @@ mod tests {
         assert!(rules.raccoon);
     }
 
+    #[test]
+    fn test_primes() {
+        // player 0 may not complete a prime on fields 2 to 7 by moving from 9 to 7
+        let mut board = Variant::Narde.start();
+        board.points[2..7].fill(Point::new(Player::Player0, 1));
+        board.points[9] = Point::new(Player::Player0, 1);
+        board.points[23] = Point::new(Player::Player0, 9);
+        assert!(board.validate().is_ok());
+        let prime = |board: &Board, variant: Variant, fields: core::ops::Range<usize>| {
+            variant
+                .plays(board, Player::Player0, &[2, 1], &[])
+                .iter()
+                .any(|play| {
+                    let mut after = board.clone();
+                    play.iter().all(|m| after.apply(Player::Player0, m).is_ok())
+                        && after.points[fields.clone()]
+                            .iter()
+                            .all(|p| p.owner == Player::Player0)
+                })
+        };
+        assert!(!prime(&board, Variant::Narde, 2..8));
+        assert!(!prime(&board, Variant::Fevga, 2..8));
+        // unless a checker of the opponent has passed it
+        // fields 23 and 5 of player 1 are fields 11 and 17 of player 0
+        board.points[11] = Point::new(Player::Player1, 14);
+        board.points[17] = Point::new(Player::Player1, 1);
+        assert!(board.validate().is_ok());
+        assert!(prime(&board, Variant::Narde, 2..8));
+        assert!(prime(&board, Variant::Fevga, 2..8));
+
+        // nor right in front of the starting field of the opponent
+        let mut board = Variant::Fevga.start();
+        board.points[23] = Point::new(Player::Player0, 9);
+        board.points[13..18].fill(Point::new(Player::Player0, 1));
+        board.points[19] = Point::new(Player::Player0, 1);
+        assert!(board.validate().is_ok());
+        assert!(!prime(&board, Variant::Fevga, 13..19));
+        assert!(!Variant::Fevga
+            .plays(&board, Player::Player0, &[2, 1], &[])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_fevga_start() -> Result<(), Error> {
+        // the first checker has to pass the starting field of the opponent on its own
+        let board = Variant::Fevga.start();
+        let plays = Variant::Fevga.plays(&board, Player::Player0, &[6, 5], &[]);
+        assert!(!plays.is_empty());
+        assert!(plays.iter().all(|p| p[1].from != Field::Point(23)));
+
+        let mut board = Variant::Fevga.start();
+        board.set(Player::Player0, 23, -1)?;
+        board.set(Player::Player0, 13, 1)?;
+        let plays = Variant::Fevga.plays(&board, Player::Player0, &[2, 1], &[]);
+        assert!(plays.iter().flatten().all(|m| m.from != Field::Point(23)));
+        let head = CheckerMove::new(Field::Point(23), vec![1])?;
+        assert!(!plays.iter().any(|p| p.contains(&head)));
+        // 13/11/10 passes the starting field of the opponent, then the next checker may leave
+        let plays = Variant::Fevga.plays(&board, Player::Player0, &[3, 1], &[]);
+        assert!(plays
+            .iter()
+            .any(|p| p[0].from == Field::Point(13) && p[1].from == Field::Point(23)));
+
+        board.set(Player::Player0, 13, -1)?;
+        board.set(Player::Player0, 9, 1)?;
+        let plays = Variant::Fevga.plays(&board, Player::Player0, &[2, 1], &[]);
+        assert!(plays.iter().any(|p| p.contains(&head)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_variant() {
+        let rules = Rules::default().with_variant(Variant::Nackgammon);
+        assert_eq!(rules.variant, Variant::Nackgammon);
+
+        // the starting position is valid and only the back checkers differ
+        let board = Variant::Nackgammon.start();
+        let standard = Variant::Backgammon.start();
+        assert_eq!(standard, Board::new());
+        assert!(board.validate().is_ok());
+        for player in [Player::Player0, Player::Player1] {
+            for (field, checkers) in [(23, 2), (22, 2), (12, 4), (7, 3), (5, 4)] {
+                assert_eq!(
+                    board.get_checkers(player, Field::Point(field)).ok(),
+                    Some(checkers)
+                );
+            }
+            let pips = board.pip_count(player).ok();
+            assert_eq!(
+                pips,
+                standard
+                    .pip_count(player)
+                    .ok()
+                    .map(|p| p + 23 + 23 - 13 - 6)
+            );
+        }
+
+        assert_eq!(Variant::Nackgammon.to_string(), "Nackgammon");
+        for variant in [
+            Variant::Portes,
+            Variant::Plakoto,
+            Variant::Fevga,
+            Variant::Narde,
+        ] {
+            assert_eq!(variant.to_string().parse::<Variant>().ok(), Some(variant));
+            assert!(variant.start().validate().is_ok());
+            assert!(!variant.has_cube());
+        }
+        assert_eq!(Variant::Portes.start(), Board::new());
+        assert_eq!(
+            "Hypergammon".parse::<Variant>().ok(),
+            Some(Variant::Hypergammon)
+        );
+        assert!(Variant::Hypergammon.start().validate().is_ok());
+        assert!(Variant::Hypergammon.has_backgammons());
+        assert_eq!(Variant::Fevga.start().occupancy, Occupancy::Holding);
+        assert_eq!(
+            "Backgammon".parse::<Variant>().ok(),
+            Some(Variant::Backgammon)
+        );
+        assert!(matches!(
+            "Acey-deucey".parse::<Variant>(),
+            Err(Error::VariantInvalid)
+        ));
+    }
+
     #[test]
     fn test_display() {
         let rules = Rules::default();
         assert_eq!(
             format!("{}", rules),
-            "Points: 7, Beaver: false, Raccoon: false, Murphy: false, Murphy Limit: 0, Jacoby: false, Crawford: true, Holland: false"
+            "Points: 7, Beaver: false, Raccoon: false, Murphy: false, Murphy Limit: 0, Jacoby: false, Crawford: true, Holland: false, Variant: Backgammon"
         );
     }
 }
@@ -1,5 +1,10 @@
+use crate::formats::{BitReader, BitWriter};
 use crate::rules::Player;
-use crate::Error;
+use crate::rules::{AbsolutePoint, CheckerMove, Field, RelativePoint};
+use crate::{Error, PositionError};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Represents the Backgammon board
@@ -25,23 +30,128 @@ use serde::{Deserialize, Serialize};
 /// //        +13-14-15-16-17-18------19-20-21-22-23-24-+
 /// ```
 
-#[derive(Debug, Clone, Serialize, PartialEq, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Board {
-    /// Boards from the point-of-view of each player
-    pub raw_board: (PlayerBoard, PlayerBoard),
+    /// The 24 fields from the point-of-view of player 0, each with the checkers on it. A checker
+    /// pinned by the opponent is not held by its field but by [`pins`](Board::pins).
+    pub points: [Point; 24],
+    /// The checkers on the bar, indexed by [`Player::index`]
+    pub bar: [u8; 2],
+    /// The checkers borne off, indexed by [`Player::index`]
+    pub off: [u8; 2],
+    /// What happens when a checker lands on a field occupied by the opponent, which depends on
+    /// the [variant](crate::rules::Variant) played
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub occupancy: Occupancy,
+    /// Bit masks of the fields, from the point-of-view of each player and indexed by
+    /// [`Player::index`], on which a single checker of the player is pinned by the opponent. Only
+    /// used with [`Occupancy::Pinning`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pins: [u32; 2],
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        let mut points = [Point::EMPTY; 24];
+        for (field, checkers) in PlayerBoard::default().board.into_iter().enumerate() {
+            if checkers > 0 {
+                points[field] = Point::new(Player::Player0, checkers);
+                points[23 - field] = Point::new(Player::Player1, checkers);
+            }
+        }
+        Board {
+            points,
+            bar: [0; 2],
+            off: [0; 2],
+            occupancy: Occupancy::default(),
+            pins: [0; 2],
+        }
+    }
+}
+
+/// Represents the rules for fields occupied by checkers of the opponent
+///
+/// In every variant, a player cannot land on a field held by two or more checkers of the
+/// opponent. The variants differ in what happens to a single checker of the opponent.
+/// ```
+/// use backgammon::rules::{Board, Occupancy, Variant};
+///
+/// assert_eq!(Board::new().occupancy, Occupancy::Hitting);
+/// assert_eq!(Variant::Plakoto.start().occupancy, Occupancy::Pinning);
+/// ```
+#[derive(Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Occupancy {
+    /// A single checker of the opponent is hit and put on the bar, as in Backgammon and Portes
+    #[default]
+    Hitting,
+    /// A single checker of the opponent is pinned and cannot move as long as a checker of the
+    /// player stays on top of it, as in Plakoto
+    Pinning,
+    /// A single checker holds a field, and both players move in the same direction starting from
+    /// diagonally opposite corners, as in Fevga
+    Holding,
+}
+
+/// Represents the checkers on a field of the board, as seen on a graphical representation
+/// ```
+/// use backgammon::rules::{Board, Player, Point};
+///
+/// let points = Board::new().get().points;
+/// assert_eq!(points[5], Point::new(Player::Player0, 5));
+/// assert_eq!(points[11].checkers(Player::Player1), 5);
+/// assert!(points[2].is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Point {
+    /// The player whose checkers are on the field, nobody if the field is empty
+    pub owner: Player,
+    /// The amount of checkers on the field
+    pub count: u8,
+}
+
+impl Point {
+    /// An empty field
+    pub const EMPTY: Point = Point {
+        owner: Player::Nobody,
+        count: 0,
+    };
+
+    /// Create a field holding an amount of checkers of a player, which is empty for no checkers
+    pub fn new(owner: Player, count: u8) -> Self {
+        match (owner, count) {
+            (Player::Nobody, _) | (_, 0) => Point::EMPTY,
+            _ => Point { owner, count },
+        }
+    }
+
+    /// Returns true if no checkers are on the field
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the amount of checkers of a player on the field
+    pub fn checkers(&self, player: Player) -> u8 {
+        match self.owner == player {
+            true => self.count,
+            false => 0,
+        }
+    }
 }
 
 /// Represents the Backgammon board for both players (to be used for graphical representation).
-#[derive(Debug, Serialize, PartialEq, Deserialize)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BoardDisplay {
-    /// The board represented as an array of 24 fields, each of which can hold 0 or more checkers.
-    /// Positive amounts represent checkers of player 0, negative amounts represent checkers of
-    /// player 1.
-    pub board: [i8; 24],
-    /// The bar for both players
-    pub bar: (u8, u8),
-    /// The off for both players
-    pub off: (u8, u8),
+    /// The 24 fields from the point-of-view of player 0, each with the checkers on it. A checker
+    /// pinned by the opponent is hidden below the checkers on top of it.
+    pub points: [Point; 24],
+    /// The bar for both players, indexed by [`Player::index`]
+    pub bar: [u8; 2],
+    /// The off for both players, indexed by [`Player::index`]
+    pub off: [u8; 2],
 }
 
 impl Board {
@@ -52,35 +162,655 @@ impl Board {
 
     /// Get the board for both players. Use for graphical representation of the board.
     ///
-    /// This method outputs a tuple with three values:
-    ///
-    /// 1. the board represented as an array of 24 fields, each of which can hold 0 or more
-    /// checkers. Positive amounts represent checkers of player 0, negative amounts represent
-    /// checkers of player 1.
-    /// 2. the bar for both players
-    /// 3. the off for both players
+    /// This method outputs the 24 fields from the point-of-view of player 0 with the owner and
+    /// the amount of checkers on each of them, the bar for both players, and the off for both
+    /// players.
     pub fn get(&self) -> BoardDisplay {
-        let mut board: [i8; 24] = [0; 24];
+        BoardDisplay {
+            points: self.points,
+            bar: self.bar,
+            off: self.off,
+        }
+    }
 
-        for (i, val) in board.iter_mut().enumerate() {
-            *val = self.raw_board.0.board[i] as i8 - self.raw_board.1.board[23 - i] as i8;
+    /// Create a board from the checkers of player 0 and player 1, each from the point-of-view of
+    /// the player, e.g. as read from a file. A field may only hold checkers of both players if a
+    /// single checker is pinned there. The amounts of checkers are not checked, see
+    /// [`validate`](Board::validate).
+    /// ```
+    /// use backgammon::rules::{Board, Occupancy, PlayerBoard};
+    ///
+    /// let side = PlayerBoard::default();
+    /// let board = Board::from_sides([side.clone(), side], Occupancy::Hitting, [0, 0]).unwrap();
+    /// assert_eq!(board, Board::new());
+    /// ```
+    pub fn from_sides(
+        sides: [PlayerBoard; 2],
+        occupancy: Occupancy,
+        pins: [u32; 2],
+    ) -> Result<Self, Error> {
+        let [p0, p1] = sides;
+        let mut board = Board {
+            points: [Point::EMPTY; 24],
+            bar: [p0.bar, p1.bar],
+            off: [p0.off, p1.off],
+            occupancy,
+            pins,
+        };
+        for field in 0..24 {
+            let opposite = board.opposite(field);
+            let (own, other) = (p0.board[field], p1.board[opposite]);
+            let pinned = (pins[0] & 1 << field != 0, pins[1] & 1 << opposite != 0);
+            board.points[field] = match pinned {
+                (false, false) if own > 0 && other > 0 => {
+                    return Err(Error::PositionInvalid(PositionError::FieldShared(
+                        field as u8,
+                    )));
+                }
+                (false, false) if own > 0 => Point::new(Player::Player0, own),
+                (false, false) => Point::new(Player::Player1, other),
+                // a pinned checker is held by the pins, below the checkers of the opponent
+                (true, false) if own == 1 && other > 0 => Point::new(Player::Player1, other),
+                (false, true) if own > 0 && other == 1 => Point::new(Player::Player0, own),
+                _ => return Err(Error::PositionInvalid(PositionError::PinsInvalid)),
+            };
         }
+        Ok(board)
+    }
 
-        BoardDisplay {
+    /// Returns the checkers of a player from the point-of-view of the player, counting a checker
+    /// pinned by the opponent on its field
+    /// ```
+    /// use backgammon::rules::{Board, Player, PlayerBoard};
+    ///
+    /// let board = Board::new();
+    /// assert_eq!(board.side(Player::Player1).unwrap(), PlayerBoard::default());
+    /// ```
+    pub fn side(&self, player: Player) -> Result<PlayerBoard, Error> {
+        let side = player.index().ok_or(Error::PlayerInvalid)?;
+        let mut board = [0; 24];
+        for (field, checkers) in board.iter_mut().enumerate() {
+            let pinned = (self.pins[side] >> field & 1) as u8;
+            *checkers = self.points[self.index(player, field)].checkers(player) + pinned;
+        }
+        Ok(PlayerBoard {
             board,
-            bar: self.get_bar(),
-            off: self.get_off(),
+            bar: self.bar[side],
+            off: self.off[side],
+        })
+    }
+
+    /// Returns the board seen from the other side, i.e. the checkers of player 0 become the
+    /// checkers of player 1 and vice versa.
+    ///
+    /// Because each player moves from field 23 towards field 0 on the own board, the flipped
+    /// board lets player 0 play the position of player 1.
+    /// ```
+    /// use backgammon::rules::{Board, Field, Player};
+    ///
+    /// let mut board = Board::new();
+    /// board.set(Player::Player1, 20, 1).unwrap();
+    /// let flipped = board.flipped();
+    /// assert_eq!(flipped.get_checkers(Player::Player0, Field::Point(20)).unwrap(), 1);
+    /// assert_eq!(flipped.flipped(), board);
+    /// ```
+    pub fn flipped(&self) -> Self {
+        let mut points = [Point::EMPTY; 24];
+        for (field, point) in points.iter_mut().enumerate() {
+            let other = self.points[self.opposite(field)];
+            *point = Point::new(other.owner.other(), other.count);
+        }
+        Board {
+            points,
+            bar: [self.bar[1], self.bar[0]],
+            off: [self.off[1], self.off[0]],
+            occupancy: self.occupancy,
+            pins: [self.pins[1], self.pins[0]],
         }
     }
 
-    /// Get the bar for both players
-    fn get_bar(&self) -> (u8, u8) {
-        (self.raw_board.0.bar, self.raw_board.1.bar)
+    /// Returns the field of the opponent's point-of-view which is the same as a field of the
+    /// player's point-of-view
+    fn opposite(&self, field: usize) -> usize {
+        match self.occupancy {
+            Occupancy::Holding => (field + 12) % 24,
+            Occupancy::Hitting | Occupancy::Pinning => 23 - field,
+        }
     }
 
-    /// Get the off for both players
-    fn get_off(&self) -> (u8, u8) {
-        (self.raw_board.0.off, self.raw_board.1.off)
+    /// Returns the index in [`points`](Board::points) of a field of the player's point-of-view
+    fn index(&self, player: Player, field: usize) -> usize {
+        match player {
+            Player::Player1 => self.opposite(field),
+            _ => field,
+        }
+    }
+
+    /// Returns the number a player calls a point on the board, which depends on the variant, see
+    /// [`AbsolutePoint`]
+    /// ```
+    /// use backgammon::rules::{AbsolutePoint, Player, Variant};
+    ///
+    /// let board = Variant::Fevga.start();
+    /// let point = AbsolutePoint::new(24).unwrap();
+    /// assert_eq!(board.relative(Player::Player0, point).unwrap().number(), 24);
+    /// assert_eq!(board.relative(Player::Player1, point).unwrap().number(), 12);
+    /// ```
+    pub fn relative(&self, player: Player, point: AbsolutePoint) -> Result<RelativePoint, Error> {
+        let index = match player {
+            Player::Player0 => point.index(),
+            Player::Player1 => self.opposite(point.index()),
+            Player::Nobody => return Err(Error::PlayerInvalid),
+        };
+        RelativePoint::new(index as u8 + 1)
+    }
+
+    /// Returns the point on the board a player calls by the given number, see [`RelativePoint`]
+    /// ```
+    /// use backgammon::rules::{Board, Player, RelativePoint};
+    ///
+    /// let board = Board::new();
+    /// let point = RelativePoint::new(8).unwrap();
+    /// assert_eq!(board.absolute(Player::Player0, point).unwrap().number(), 8);
+    /// assert_eq!(board.absolute(Player::Player1, point).unwrap().number(), 17);
+    /// ```
+    pub fn absolute(&self, player: Player, point: RelativePoint) -> Result<AbsolutePoint, Error> {
+        let index = match player {
+            Player::Player0 => point.index(),
+            Player::Player1 => self.opposite(point.index()),
+            Player::Nobody => return Err(Error::PlayerInvalid),
+        };
+        AbsolutePoint::new(index as u8 + 1)
+    }
+
+    /// Get the amount of checkers of a player on a field, on the bar, or off the board. The
+    /// field is given from the point-of-view of the player.
+    pub fn get_checkers(&self, player: Player, field: Field) -> Result<u8, Error> {
+        let side = player.index().ok_or(Error::PlayerInvalid)?;
+        match field {
+            Field::Bar => Ok(self.bar[side]),
+            Field::Off => Ok(self.off[side]),
+            Field::Point(field) if field > 23 => Err(Error::FieldInvalid),
+            Field::Point(field) => {
+                let pinned = (self.pins[side] >> field & 1) as u8;
+                Ok(self.points[self.index(player, field)].checkers(player) + pinned)
+            }
+        }
+    }
+
+    /// Returns the checkers on a point of the board, as shown by [`get`](Board::get)
+    /// ```
+    /// use backgammon::rules::{AbsolutePoint, Board, Player, Point};
+    ///
+    /// let board = Board::new();
+    /// let point = AbsolutePoint::new(6).unwrap();
+    /// assert_eq!(board.point(point), Point::new(Player::Player0, 5));
+    /// ```
+    pub fn point(&self, point: AbsolutePoint) -> Point {
+        self.points[point.index()]
+    }
+
+    /// Returns the amount of checkers of a player still in play, i.e. on the points and on the bar
+    pub fn checkers(&self, player: Player) -> Result<u8, Error> {
+        Ok(15 - self.get_checkers(player, Field::Off)?)
+    }
+
+    /// Returns the points holding checkers of a player together with the amount of checkers,
+    /// numbered from the point-of-view of the player and starting with the point closest to
+    /// bearing off
+    /// ```
+    /// use backgammon::rules::{Board, Player};
+    ///
+    /// let board = Board::new();
+    /// let occupied = board.occupied(Player::Player1).unwrap();
+    /// let points = occupied.map(|(p, n)| (p.number(), n)).collect::<Vec<_>>();
+    /// assert_eq!(points, vec![(6, 5), (8, 3), (13, 5), (24, 2)]);
+    /// ```
+    pub fn occupied(
+        &self,
+        player: Player,
+    ) -> Result<impl Iterator<Item = (RelativePoint, u8)>, Error> {
+        Ok(self
+            .side(player)?
+            .board
+            .into_iter()
+            .enumerate()
+            .filter(|(_, checkers)| *checkers > 0)
+            .filter_map(|(field, checkers)| {
+                Some((RelativePoint::try_from(Field::Point(field)).ok()?, checkers))
+            }))
+    }
+
+    /// Returns the points on which a player has a single checker, not counting checkers pinned by
+    /// the opponent
+    /// ```
+    /// use backgammon::rules::{Board, Player};
+    ///
+    /// let mut board = Board::new();
+    /// board.set(Player::Player0, 7, -2).unwrap();
+    /// board.set(Player::Player0, 3, 2).unwrap();
+    /// let blots = board.blots(Player::Player0).unwrap().map(|p| p.number());
+    /// assert_eq!(blots.collect::<Vec<_>>(), vec![8]);
+    /// ```
+    pub fn blots(&self, player: Player) -> Result<impl Iterator<Item = RelativePoint>, Error> {
+        let pins = self.pins[player.index().ok_or(Error::PlayerInvalid)?];
+        Ok(self
+            .occupied(player)?
+            .filter(move |(point, checkers)| *checkers == 1 && pins & 1 << point.index() == 0)
+            .map(|(point, _)| point))
+    }
+
+    /// Returns the points a player has made, i.e. which hold two or more checkers of the player
+    /// ```
+    /// use backgammon::rules::{Board, Player};
+    ///
+    /// let made = Board::new().made_points(Player::Player0).unwrap().map(|p| p.number());
+    /// assert_eq!(made.collect::<Vec<_>>(), vec![6, 8, 13, 24]);
+    /// ```
+    pub fn made_points(
+        &self,
+        player: Player,
+    ) -> Result<impl Iterator<Item = RelativePoint>, Error> {
+        Ok(self
+            .occupied(player)?
+            .filter(|(_, checkers)| *checkers > 1)
+            .map(|(point, _)| point))
+    }
+
+    /// Check that the board holds a valid position: each player has exactly 15 checkers on the
+    /// board, on the bar, and off, and a checker is only pinned below checkers of the opponent.
+    /// ```
+    /// use backgammon::rules::{Board, Player};
+    ///
+    /// let mut board = Board::new();
+    /// assert!(board.validate().is_ok());
+    /// board.set_bar(Player::Player0, 1).unwrap();
+    /// assert!(board.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), Error> {
+        if (self.occupancy != Occupancy::Pinning && self.pins != [0, 0])
+            || (self.pins[0] | self.pins[1]) >> 24 != 0
+        {
+            return Err(Error::PositionInvalid(PositionError::PinsInvalid));
+        }
+        for player in [Player::Player0, Player::Player1] {
+            for field in 0..24 {
+                if self.is_pinned(player, field)?
+                    && self.points[self.index(player, field)].owner != player.other()
+                {
+                    return Err(Error::PositionInvalid(PositionError::PinsInvalid));
+                }
+            }
+        }
+        for player in [Player::Player0, Player::Player1] {
+            let side = self.side(player)?;
+            let count = side.board.iter().map(|c| *c as u32).sum::<u32>()
+                + side.bar as u32
+                + side.off as u32;
+            if count != 15 {
+                return Err(Error::PositionInvalid(PositionError::CheckerCount(
+                    player,
+                    count.min(u8::MAX as u32) as u8,
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode the position in 10 bytes, as done for the Position ID of GNU Backgammon: for player
+    /// 0 and then player 1, the checkers on every field and on the bar are written as that many
+    /// 1 bits, each followed by a 0 bit. The checkers off are not written.
+    /// ```
+    /// use backgammon::rules::Board;
+    ///
+    /// let bytes = Board::new().to_bytes();
+    /// assert_eq!(bytes, [0xe0, 0x73, 0xf0, 0x01, 0x30, 0xe0, 0x73, 0xf0, 0x01, 0x30]);
+    /// assert_eq!(Board::from_bytes(&bytes).unwrap(), Board::new());
+    /// ```
+    pub fn to_bytes(&self) -> [u8; 10] {
+        let mut bits = BitWriter::default();
+        let sides = [Player::Player0, Player::Player1].map(|player| self.side(player));
+        for board in sides.iter().flatten() {
+            for checkers in board.board.iter().chain([&board.bar]) {
+                bits.push((1u32 << checkers) - 1, usize::from(*checkers));
+                bits.push(0, 1);
+            }
+        }
+        let mut bytes = [0; 10];
+        bytes.copy_from_slice(&bits.into_bytes(10));
+        bytes
+    }
+
+    /// Decode a position encoded by [`to_bytes`](Board::to_bytes). The position has to be valid,
+    /// and all bits not needed for the position have to be 0.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 10 {
+            return Err(Error::FormatInvalid);
+        }
+        let mut bits = BitReader::new(bytes);
+        let mut read = 0;
+        let empty = PlayerBoard {
+            board: [0; 24],
+            bar: 0,
+            off: 0,
+        };
+        let mut boards = [empty.clone(), empty];
+        for board in boards.iter_mut() {
+            let mut total = 0;
+            for field in 0..25 {
+                let mut checkers = 0;
+                while bits.read(1)? == 1 {
+                    checkers += 1;
+                    total += 1;
+                    if total > 15 {
+                        return Err(Error::FormatInvalid);
+                    }
+                }
+                read += checkers + 1;
+                match field {
+                    24 => board.bar = checkers,
+                    _ => board.board[field] = checkers,
+                }
+            }
+            board.off = 15 - total;
+        }
+        for _i in read..80 {
+            if bits.read(1)? != 0 {
+                return Err(Error::FormatInvalid);
+            }
+        }
+        let board = Board::from_sides(boards, Occupancy::default(), [0, 0])?;
+        board.validate()?;
+        Ok(board)
+    }
+
+    /// Returns the pip count of a player, i.e. the total amount of pips the player needs to bear
+    /// off all checkers. Checkers on the bar count 25 pips each.
+    pub fn pip_count(&self, player: Player) -> Result<u32, Error> {
+        let mut pips = self.get_checkers(player, Field::Bar)? as u32 * 25;
+        for field in 0..24 {
+            pips += self.get_checkers(player, Field::Point(field))? as u32 * (field as u32 + 1);
+        }
+        Ok(pips)
+    }
+
+    /// Returns the field of the rearmost checker of a player, i.e. the checker farthest away from
+    /// being borne off, or 24 if the player has checkers on the bar. Returns `None` once all
+    /// checkers are borne off.
+    pub fn back_checker(&self, player: Player) -> Result<Option<usize>, Error> {
+        if self.get_checkers(player, Field::Bar)? > 0 {
+            return Ok(Some(24));
+        }
+        for field in (0..24).rev() {
+            if self.get_checkers(player, Field::Point(field))? > 0 {
+                return Ok(Some(field));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the field of the foremost checker of a player which still has to pass the rearmost
+    /// checker of the opponent, i.e. the last point of contact seen from the player. Checkers of
+    /// the player on this field and behind it can still hit or be hit. Returns `None` if no
+    /// checker of the player is in contact.
+    pub fn last_contact(&self, player: Player) -> Result<Option<usize>, Error> {
+        let opponent = match self.back_checker(player.other())? {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        let mut fields = (0..24)
+            .filter(|field| field + opponent > 23)
+            .map(|field| Ok((field, self.get_checkers(player, Field::Point(field))?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        fields.push((24, self.get_checkers(player, Field::Bar)?));
+        Ok(fields.iter().find(|(_, n)| *n > 0).map(|(field, _)| *field))
+    }
+
+    /// Returns true if the checkers of both players have passed each other, so that no checker
+    /// can be hit anymore and the game is a pure race
+    /// ```
+    /// use backgammon::rules::Board;
+    ///
+    /// assert!(!Board::new().is_race());
+    /// ```
+    pub fn is_race(&self) -> bool {
+        match (
+            self.back_checker(Player::Player0),
+            self.back_checker(Player::Player1),
+        ) {
+            // moving in the same direction, the players only pass each other in the second half
+            (Ok(Some(a)), Ok(Some(b))) if self.occupancy == Occupancy::Holding => a < 12 && b < 12,
+            (Ok(Some(a)), Ok(Some(b))) => a + b <= 23,
+            _ => true,
+        }
+    }
+
+    /// Returns true if a player holds six consecutive fields with no checker of the opponent in
+    /// front of them, i.e. on a field the opponent reaches after all fields of the prime, or off.
+    /// Such a prime is not allowed in Fevga and Narde.
+    pub(crate) fn traps(&self, player: Player) -> bool {
+        let (Ok(own), Ok(other)) = (self.side(player), self.side(player.other())) else {
+            return false;
+        };
+        if other.off > 0 {
+            return false;
+        }
+        (0..19).any(|start| {
+            let prime = start..start + 6;
+            prime.clone().all(|field| own.board[field] > 0)
+                && !(0..24).any(|field| {
+                    other.board[field] > 0 && prime.clone().all(|p| field < self.opposite(p))
+                })
+        })
+    }
+
+    /// Move a checker of a player along all the steps of a move.
+    ///
+    /// This method only checks that the checker exists and that no field it lands on is blocked,
+    /// it does not check any other rule of the game. If the move fails, the board is left
+    /// unchanged.
+    pub fn apply(&mut self, player: Player, m: &CheckerMove) -> Result<(), Error> {
+        let mut board = self.clone();
+        let mut current = m.from;
+        for field in m.path()? {
+            match current {
+                Field::Bar => board.set_bar(player, -1)?,
+                Field::Point(field) => board.set(player, field, -1)?,
+                Field::Off => return Err(Error::MoveInvalid),
+            }
+            match field {
+                Field::Bar => return Err(Error::MoveInvalid),
+                Field::Point(field) => board.set(player, field, 1)?,
+                Field::Off => board.set_off(player, 1)?,
+            }
+            current = field;
+        }
+        *self = board;
+        Ok(())
+    }
+
+    /// Returns the fields on which a player would hit a single checker of the opponent when
+    /// playing a move. Checkers are only hit with [`Occupancy::Hitting`].
+    pub fn hits(&self, player: Player, m: &CheckerMove) -> Result<Vec<Field>, Error> {
+        let opponent = player.other();
+        let mut hits = Vec::new();
+        if self.occupancy != Occupancy::Hitting {
+            return Ok(hits);
+        }
+        for field in m.path()? {
+            if let Field::Point(f) = field {
+                if self.get_checkers(opponent, Field::Point(self.opposite(f)))? == 1 {
+                    hits.push(field);
+                }
+            }
+        }
+        Ok(hits)
+    }
+
+    /// Check if all checkers of a player which are still in play are in the home board, i.e. on
+    /// fields 0 to 5. Only then the player may bear off checkers.
+    pub fn all_home(&self, player: Player) -> Result<bool, Error> {
+        if self.get_checkers(player, Field::Bar)? > 0 {
+            return Ok(false);
+        }
+        for field in 6..24 {
+            if self.get_checkers(player, Field::Point(field))? > 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Check if a checker of a player on the bar can enter the board with the given dice value.
+    ///
+    /// Checkers on the bar always enter in the opponent's home board, i.e. on fields 18 to 23 from
+    /// the point-of-view of the player, where a 1 enters on field 23 and a 6 on field 18.
+    pub fn can_enter(&self, player: Player, dice: u8) -> Result<bool, Error> {
+        match Field::Bar.step(dice)? {
+            Field::Point(field) => Ok(!self.blocked(player, field)?),
+            _ => Err(Error::FieldInvalid),
+        }
+    }
+
+    /// Check if a player may move a checker from a field by a single dice value and return the
+    /// field the checker would land on.
+    ///
+    /// As long as a player has checkers on the bar, only moves entering these checkers are legal.
+    /// Pinned checkers cannot be moved.
+    pub fn legal_step(&self, player: Player, from: Field, dice: u8) -> Result<Field, Error> {
+        if self.get_checkers(player, from)? == 0 {
+            return Err(Error::MoveInvalidChecker);
+        }
+        if let Field::Point(field) = from {
+            if self.is_pinned(player, field)? {
+                return Err(Error::MoveInvalidPinned);
+            }
+        }
+
+        if from == Field::Bar && !self.can_enter(player, dice)? {
+            return Err(Error::FieldBlocked);
+        }
+
+        // check if player has to move checker from bar first
+        if from != Field::Bar && self.get_checkers(player, Field::Bar)? > 0 {
+            return Err(Error::MoveInvalidBar);
+        }
+
+        match from.step(dice)? {
+            Field::Point(field) => {
+                if self.blocked(player, field)? {
+                    return Err(Error::FieldBlocked);
+                }
+                Ok(Field::Point(field))
+            }
+            Field::Off => {
+                if !self.all_home(player)? {
+                    return Err(Error::MoveInvalidBearOff);
+                }
+                // a larger dice value may only be used for the checker on the highest field
+                let field = from.index() as usize;
+                if field + 1 != dice as usize
+                    && (field + 1..6)
+                        .any(|f| self.get_checkers(player, Field::Point(f)).unwrap_or(0) > 0)
+                {
+                    return Err(Error::MoveInvalidBearOff);
+                }
+                Ok(Field::Off)
+            }
+            Field::Bar => Err(Error::MoveInvalid),
+        }
+    }
+
+    /// Returns all legal ways for a player to play the given dice values, as sequences of moves
+    /// of single checkers by one dice value each.
+    ///
+    /// Only sequences playing the maximum possible number of dice values are returned. If only
+    /// one of two different dice values can be played, it has to be the larger one. If no dice
+    /// value can be played at all, the only returned sequence is empty.
+    pub fn plays(&self, player: Player, dices: &[u8]) -> Vec<Vec<CheckerMove>> {
+        self.plays_with(player, dices, |_| true)
+    }
+
+    /// Returns all legal ways for a player to play the given dice values like
+    /// [`plays`](Board::plays), where every sequence and each of its beginnings also has to be
+    /// allowed by a further rule, e.g. of a [variant](crate::rules::Variant::plays).
+    /// ```
+    /// use backgammon::rules::{Board, Field, Player};
+    ///
+    /// // the checkers on field 23 may not move
+    /// let plays = Board::new().plays_with(Player::Player0, &[6, 5], |moves| {
+    ///     moves.iter().all(|m| m.from != Field::Point(23))
+    /// });
+    /// assert!(plays.iter().flatten().all(|m| m.from != Field::Point(23)));
+    /// assert!(plays.iter().all(|p| p.len() == 2));
+    /// ```
+    pub fn plays_with(
+        &self,
+        player: Player,
+        dices: &[u8],
+        allowed: impl Fn(&[CheckerMove]) -> bool,
+    ) -> Vec<Vec<CheckerMove>> {
+        let mut plays = Vec::new();
+        self.collect_plays(player, dices, &allowed, &mut Vec::new(), &mut plays);
+
+        let max = plays.iter().map(|p| p.len()).max().unwrap_or(0);
+        plays.retain(|p| p.len() == max);
+
+        if max == 1 && dices.len() == 2 && dices[0] != dices[1] {
+            let larger = dices[0].max(dices[1]);
+            if plays.iter().any(|p| p[0].steps[0] == larger) {
+                plays.retain(|p| p[0].steps[0] == larger);
+            }
+        }
+        plays
+    }
+
+    /// Recursively collect all sequences of single dice moves
+    fn collect_plays(
+        &self,
+        player: Player,
+        dices: &[u8],
+        allowed: &dyn Fn(&[CheckerMove]) -> bool,
+        current: &mut Vec<CheckerMove>,
+        plays: &mut Vec<Vec<CheckerMove>>,
+    ) {
+        let mut moved = false;
+        for (i, dice) in dices.iter().enumerate() {
+            // equal dice values lead to the same moves
+            if dices[..i].contains(dice) {
+                continue;
+            }
+            let mut rest = dices.to_vec();
+            let _ = rest.remove(i);
+
+            let fields = core::iter::once(Field::Bar).chain((0..24).map(Field::Point));
+            for from in fields {
+                let to = match self.legal_step(player, from, *dice) {
+                    Ok(to) => to,
+                    Err(_) => continue,
+                };
+                let mut m = CheckerMove {
+                    from,
+                    to,
+                    steps: vec![*dice],
+                    hits: Vec::new(),
+                };
+                let mut board = self.clone();
+                if board.apply(player, &m).is_err() {
+                    continue;
+                }
+                m.hits = self.hits(player, &m).unwrap_or_default();
+                current.push(m);
+                if allowed(current) {
+                    moved = true;
+                    board.collect_plays(player, &rest, allowed, current, plays);
+                }
+                let _ = current.pop();
+            }
+        }
+        if !moved {
+            plays.push(current.clone());
+        }
     }
 
     /// Set checkers for a player on a field
@@ -91,43 +821,48 @@ impl Board {
     ///
     /// If the field is blocked for the player, an error is returned. If the field is not blocked,
     /// but there is already one checker from the other player on the field, that checker is hit and
-    /// moved to the bar.
+    /// moved to the bar, or pinned with [`Occupancy::Pinning`]. A pinned checker cannot be removed,
+    /// and it is released once the last checker on top of it is removed.
     pub fn set(&mut self, player: Player, field: usize, amount: i8) -> Result<(), Error> {
         if field > 23 {
             return Err(Error::FieldInvalid);
         }
 
+        if amount < 0 && self.is_pinned(player, field)? {
+            return Err(Error::MoveInvalidPinned);
+        }
         if self.blocked(player, field)? {
             return Err(Error::FieldBlocked);
         }
 
-        match player {
-            Player::Player0 => {
-                let new = self.raw_board.0.board[field] as i8 + amount;
-                if new < 0 {
-                    return Err(Error::MoveInvalid);
-                }
-                self.raw_board.0.board[field] = new as u8;
+        let opponent = player.other();
+        let other = opponent.index().ok_or(Error::PlayerInvalid)?;
+        let index = self.index(player, field);
+        let point = self.points[index];
+        let new = point.checkers(player) as i8 + amount;
+        if new < 0 {
+            return Err(Error::MoveInvalid);
+        }
 
+        let pin = 1 << self.opposite(field);
+        self.points[index] = match self.occupancy {
+            Occupancy::Hitting if point.owner == opponent => {
                 // in case one opponent's checker is hit, move it to the bar
-                self.raw_board.1.bar += self.raw_board.1.board[23 - field];
-                self.raw_board.1.board[23 - field] = 0;
-                Ok(())
+                self.bar[other] += point.count;
+                Point::new(player, new as u8)
             }
-            Player::Player1 => {
-                let new = self.raw_board.1.board[field] as i8 + amount;
-                if new < 0 {
-                    return Err(Error::MoveInvalid);
-                }
-                self.raw_board.1.board[field] = new as u8;
-
-                // in case one opponent's checker is hit, move it to the bar
-                self.raw_board.0.bar += self.raw_board.0.board[23 - field];
-                self.raw_board.0.board[23 - field] = 0;
-                Ok(())
+            Occupancy::Pinning if point.owner == opponent && amount > 0 => {
+                self.pins[other] |= pin;
+                Point::new(player, new as u8)
             }
-            Player::Nobody => Err(Error::PlayerInvalid),
-        }
+            Occupancy::Pinning if new == 0 && self.pins[other] & pin != 0 => {
+                self.pins[other] &= !pin;
+                Point::new(opponent, 1)
+            }
+            _ if point.owner == opponent => point,
+            _ => Point::new(player, new as u8),
+        };
+        Ok(())
     }
 
     /// Check if a field is blocked for a player
@@ -136,70 +871,63 @@ impl Board {
             return Err(Error::FieldInvalid);
         }
 
-        match player {
-            Player::Player0 => {
-                if self.raw_board.1.board[23 - field] > 1 {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            Player::Player1 => {
-                if self.raw_board.0.board[23 - field] > 1 {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            Player::Nobody => Err(Error::PlayerInvalid),
+        let other = self.get_checkers(player.other(), Field::Point(self.opposite(field)))?;
+        Ok(match self.occupancy {
+            Occupancy::Hitting => other > 1,
+            // the field of a pinned checker is held by the opponent on top of it
+            Occupancy::Pinning => other > 1 || other == 1 && self.is_pinned(player, field)?,
+            Occupancy::Holding => other > 0,
+        })
+    }
+
+    /// Check if the single checker of a player on a field is pinned by the opponent
+    /// ```
+    /// use backgammon::rules::{Field, Player, Variant};
+    ///
+    /// let mut board = Variant::Plakoto.start();
+    /// board.set(Player::Player1, 23, -1)?;
+    /// board.set(Player::Player1, 6, 1)?;
+    /// // field 6 of player 1 is field 17 of player 0
+    /// board.set(Player::Player0, 23, -1)?;
+    /// board.set(Player::Player0, 17, 1)?;
+    /// assert!(board.is_pinned(Player::Player1, 6)?);
+    /// assert_eq!(board.get_checkers(Player::Player1, Field::Point(6))?, 1);
+    /// assert!(board.legal_step(Player::Player1, Field::Point(6), 1).is_err());
+    /// # Ok::<(), backgammon::Error>(())
+    /// ```
+    pub fn is_pinned(&self, player: Player, field: usize) -> Result<bool, Error> {
+        if field > 23 {
+            return Err(Error::FieldInvalid);
         }
+        let side = player.index().ok_or(Error::PlayerInvalid)?;
+        Ok(self.pins[side] & 1 << field != 0)
     }
 
     /// Set checkers for a player on the bar. This method adds amount to the already existing
     /// checkers there.
     pub fn set_bar(&mut self, player: Player, amount: i8) -> Result<(), Error> {
-        match player {
-            Player::Player0 => {
-                let new = self.raw_board.0.bar as i8 + amount;
-                if new < 0 {
-                    return Err(Error::MoveInvalid);
-                }
-                self.raw_board.0.bar = new as u8;
-                Ok(())
-            }
-            Player::Player1 => {
-                let new = self.raw_board.1.bar as i8 + amount;
-                if new < 0 {
-                    return Err(Error::MoveInvalid);
-                }
-                self.raw_board.1.bar = new as u8;
-                Ok(())
-            }
-            Player::Nobody => Err(Error::PlayerInvalid),
+        let side = player.index().ok_or(Error::PlayerInvalid)?;
+        let new = self.bar[side] as i8 + amount;
+        if new < 0 {
+            return Err(Error::MoveInvalid);
         }
+        self.bar[side] = new as u8;
+        Ok(())
     }
 
     /// Set checkers for a player off the board. This method adds amount to the already existing
     /// checkers there.
     pub fn set_off(&mut self, player: Player, amount: u8) -> Result<(), Error> {
-        match player {
-            Player::Player0 => {
-                let new = self.raw_board.0.off + amount;
-                self.raw_board.0.off = new;
-                Ok(())
-            }
-            Player::Player1 => {
-                let new = self.raw_board.1.off + amount;
-                self.raw_board.1.off = new;
-                Ok(())
-            }
-            Player::Nobody => Err(Error::PlayerInvalid),
-        }
+        let side = player.index().ok_or(Error::PlayerInvalid)?;
+        self.off[side] += amount;
+        Ok(())
     }
 }
 
-/// Represents the Backgammon board for one player
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Represents the checkers of one player from the point-of-view of the player, see
+/// [`Board::side`] and [`Board::from_sides`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PlayerBoard {
     /// Checkers on board
     pub board: [u8; 24],
@@ -243,6 +971,7 @@ pub trait Move {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rules::Variant;
 
     #[test]
     fn default_board() {
@@ -264,35 +993,156 @@ mod tests {
     #[test]
     fn get_board() {
         let board = Board::new();
-        assert_eq!(
-            board.get(),
-            BoardDisplay {
-                board: [
-                    -2, 0, 0, 0, 0, 5, 0, 3, 0, 0, 0, -5, 5, 0, 0, 0, -3, 0, -5, 0, 0, 0, 0, 2,
-                ],
-                bar: (0, 0),
-                off: (0, 0)
+        let display = board.get();
+        let expected: [i8; 24] = [
+            -2, 0, 0, 0, 0, 5, 0, 3, 0, 0, 0, -5, 5, 0, 0, 0, -3, 0, -5, 0, 0, 0, 0, 2,
+        ];
+        for (point, &count) in display.points.iter().zip(expected.iter()) {
+            let owner = match count.cmp(&0) {
+                core::cmp::Ordering::Greater => Player::Player0,
+                core::cmp::Ordering::Less => Player::Player1,
+                core::cmp::Ordering::Equal => Player::Nobody,
+            };
+            assert_eq!(*point, Point::new(owner, count.unsigned_abs()));
+        }
+        assert_eq!(display.bar, [0, 0]);
+        assert_eq!(display.off, [0, 0]);
+    }
+
+    #[test]
+    fn flipped() -> Result<(), Error> {
+        let mut board = Board::new();
+        assert_eq!(board.flipped(), board);
+
+        board.set(Player::Player0, 3, 1)?;
+        board.set_bar(Player::Player1, 2)?;
+        board.set_off(Player::Player0, 1)?;
+        let flipped = board.flipped();
+        assert_eq!(flipped.get_checkers(Player::Player1, Field::Point(3))?, 1);
+        assert_eq!(flipped.get_checkers(Player::Player0, Field::Bar)?, 2);
+        assert_eq!(flipped.get_checkers(Player::Player1, Field::Off)?, 1);
+        let mut display = board.get().points;
+        display.reverse();
+        let display = display.map(|p| Point::new(p.owner.other(), p.count));
+        assert_eq!(flipped.get().points, display);
+        Ok(())
+    }
+
+    #[test]
+    fn coordinates() -> Result<(), Error> {
+        for variant in [Variant::Backgammon, Variant::Fevga] {
+            let board = variant.start();
+            let display = board.get();
+            for number in 1..=24 {
+                let point = AbsolutePoint::new(number)?;
+                for player in [Player::Player0, Player::Player1] {
+                    let relative = board.relative(player, point)?;
+                    assert_eq!(board.absolute(player, relative)?, point);
+                    assert_eq!(
+                        board.get_checkers(player, Field::from(relative))?,
+                        display.points[point.index()].checkers(player)
+                    );
+                }
             }
-        );
+        }
+        let point = AbsolutePoint::new(1)?;
+        assert!(Board::new().relative(Player::Nobody, point).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn pip_count() -> Result<(), Error> {
+        let mut board = Board::new();
+        assert_eq!(board.pip_count(Player::Player0)?, 167);
+        assert_eq!(board.pip_count(Player::Player1)?, 167);
+        board.set(Player::Player1, 17, 1)?;
+        board.set(Player::Player0, 6, 1)?;
+        assert_eq!(board.pip_count(Player::Player0)?, 174);
+        assert_eq!(board.pip_count(Player::Player1)?, 167 + 18 - 18 + 25);
+        assert!(board.pip_count(Player::Nobody).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn race() -> Result<(), Error> {
+        let mut board = Board::new();
+        assert_eq!(board.back_checker(Player::Player0)?, Some(23));
+        assert_eq!(board.last_contact(Player::Player0)?, Some(5));
+        assert!(!board.is_race());
+
+        // player 0 has all checkers on the ace point, player 1 on its 6 point
+        board.points = [Point::EMPTY; 24];
+        board.set(Player::Player0, 0, 15)?;
+        board.set(Player::Player1, 5, 15)?;
+        assert!(board.is_race());
+        assert_eq!(board.last_contact(Player::Player0)?, None);
+        assert_eq!(board.back_checker(Player::Player1)?, Some(5));
+
+        // a checker of player 0 on field 20 has not passed player 1 on field 5, i.e. field 18
+        // seen from player 0
+        board.set(Player::Player0, 0, -1)?;
+        board.set(Player::Player0, 20, 1)?;
+        assert!(!board.is_race());
+        assert_eq!(board.last_contact(Player::Player0)?, Some(20));
+        assert_eq!(board.last_contact(Player::Player1)?, Some(5));
+
+        // a checker on the bar is always in contact
+        board.set(Player::Player0, 20, -1)?;
+        board.set_bar(Player::Player0, 1)?;
+        assert_eq!(board.back_checker(Player::Player0)?, Some(24));
+        assert_eq!(board.last_contact(Player::Player0)?, Some(24));
+        assert!(!board.is_race());
+
+        board.points[18] = Point::EMPTY;
+        assert_eq!(board.back_checker(Player::Player1)?, None);
+        assert!(board.is_race());
+        assert!(board.back_checker(Player::Nobody).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validate() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.validate()?;
+
+        board.points[0] = Point::EMPTY;
+        assert!(matches!(
+            board.validate(),
+            Err(Error::PositionInvalid(PositionError::CheckerCount(
+                Player::Player1,
+                13
+            )))
+        ));
+
+        // both players on field 22 of player 0
+        let mut sides = [PlayerBoard::default(), PlayerBoard::default()];
+        sides[1].board[1] = 2;
+        sides[0].board[22] = 1;
+        sides[0].board[23] = 1;
+        assert!(matches!(
+            Board::from_sides(sides, Occupancy::Hitting, [0, 0]),
+            Err(Error::PositionInvalid(PositionError::FieldShared(22)))
+        ));
+        Ok(())
     }
 
     #[test]
     fn get_bar() {
         let board = Board::new();
-        assert_eq!(board.get_bar(), (0, 0));
+        assert_eq!(board.bar, [0, 0]);
     }
 
     #[test]
     fn get_off() {
         let board = Board::new();
-        assert_eq!(board.get_off(), (0, 0));
+        assert_eq!(board.off, [0, 0]);
     }
 
     #[test]
     fn set_player0() -> Result<(), Error> {
         let mut board = Board::new();
         board.set(Player::Player0, 1, 1)?;
-        assert_eq!(board.get().board[1], 1);
+        assert_eq!(board.get().points[1], Point::new(Player::Player0, 1));
         Ok(())
     }
 
@@ -300,7 +1150,7 @@ mod tests {
     fn set_player1() -> Result<(), Error> {
         let mut board = Board::new();
         board.set(Player::Player1, 2, 1)?;
-        assert_eq!(board.get().board[21], -1);
+        assert_eq!(board.get().points[21], Point::new(Player::Player1, 1));
         Ok(())
     }
 
@@ -308,7 +1158,7 @@ mod tests {
     fn set_player0_bar() -> Result<(), Error> {
         let mut board = Board::new();
         board.set_bar(Player::Player0, 1)?;
-        assert_eq!(board.get().bar.0, 1);
+        assert_eq!(board.get().bar[0], 1);
         Ok(())
     }
 
@@ -316,7 +1166,7 @@ mod tests {
     fn set_player1_bar() -> Result<(), Error> {
         let mut board = Board::new();
         board.set_bar(Player::Player1, 1)?;
-        assert_eq!(board.get().bar.1, 1);
+        assert_eq!(board.get().bar[1], 1);
         Ok(())
     }
 
@@ -324,7 +1174,7 @@ mod tests {
     fn set_player0_off() -> Result<(), Error> {
         let mut board = Board::new();
         board.set_off(Player::Player0, 1)?;
-        assert_eq!(board.get().off.0, 1);
+        assert_eq!(board.get().off[0], 1);
         Ok(())
     }
 
@@ -332,7 +1182,7 @@ mod tests {
     fn set_player1_off() -> Result<(), Error> {
         let mut board = Board::new();
         board.set_off(Player::Player1, 1)?;
-        assert_eq!(board.get().off.1, 1);
+        assert_eq!(board.get().off[1], 1);
         Ok(())
     }
 
@@ -341,7 +1191,7 @@ mod tests {
         let mut board = Board::new();
         board.set_off(Player::Player1, 1)?;
         board.set_off(Player::Player1, 1)?;
-        assert_eq!(board.get().off.1, 2);
+        assert_eq!(board.get().off[1], 2);
         Ok(())
     }
 
@@ -400,8 +1250,8 @@ mod tests {
         let mut board = Board::new();
         board.set(Player::Player0, 1, 1)?;
         board.set(Player::Player1, 22, 1)?;
-        assert_eq!(board.get().board[1], -1);
-        assert_eq!(board.get().bar.0, 1);
+        assert_eq!(board.get().points[1], Point::new(Player::Player1, 1));
+        assert_eq!(board.get().bar[0], 1);
         Ok(())
     }
 
@@ -411,8 +1261,8 @@ mod tests {
         board.set(Player::Player0, 1, 1)?;
         board.set_bar(Player::Player0, 5)?;
         board.set(Player::Player1, 22, 1)?;
-        assert_eq!(board.get().board[1], -1);
-        assert_eq!(board.get().bar.0, 6);
+        assert_eq!(board.get().points[1], Point::new(Player::Player1, 1));
+        assert_eq!(board.get().bar[0], 6);
         Ok(())
     }
 
@@ -421,8 +1271,8 @@ mod tests {
         let mut board = Board::new();
         board.set(Player::Player1, 1, 1)?;
         board.set(Player::Player0, 22, 1)?;
-        assert_eq!(board.get().board[22], 1);
-        assert_eq!(board.get().bar.1, 1);
+        assert_eq!(board.get().points[22], Point::new(Player::Player0, 1));
+        assert_eq!(board.get().bar[1], 1);
         Ok(())
     }
 
@@ -432,8 +1282,8 @@ mod tests {
         board.set(Player::Player1, 1, 1)?;
         board.set_bar(Player::Player1, 5)?;
         board.set(Player::Player0, 22, 1)?;
-        assert_eq!(board.get().board[22], 1);
-        assert_eq!(board.get().bar.1, 6);
+        assert_eq!(board.get().points[22], Point::new(Player::Player0, 1));
+        assert_eq!(board.get().bar[1], 6);
         Ok(())
     }
 
@@ -441,7 +1291,7 @@ mod tests {
     fn set_field_with_2_checkers_player0_a() -> Result<(), Error> {
         let mut board = Board::new();
         board.set(Player::Player0, 23, 2)?;
-        assert_eq!(board.get().board[23], 4);
+        assert_eq!(board.get().points[23], Point::new(Player::Player0, 4));
         Ok(())
     }
 
@@ -449,7 +1299,7 @@ mod tests {
     fn set_field_with_2_checkers_player0_b() -> Result<(), Error> {
         let mut board = Board::new();
         board.set(Player::Player0, 23, -1)?;
-        assert_eq!(board.get().board[23], 1);
+        assert_eq!(board.get().points[23], Point::new(Player::Player0, 1));
         Ok(())
     }
 
@@ -471,6 +1321,393 @@ mod tests {
         assert!(board.set(Player::Player0, 23, -3).is_err());
     }
 
+    #[test]
+    fn get_checkers() -> Result<(), Error> {
+        let board = Board::new();
+        assert_eq!(board.get_checkers(Player::Player0, Field::Point(5))?, 5);
+        assert_eq!(board.get_checkers(Player::Player1, Field::Point(23))?, 2);
+        assert_eq!(board.get_checkers(Player::Player1, Field::Bar)?, 0);
+        assert_eq!(board.get_checkers(Player::Player0, Field::Off)?, 0);
+        assert!(board.get_checkers(Player::Nobody, Field::Off).is_err());
+        assert!(board
+            .get_checkers(Player::Player0, Field::Point(24))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn apply_move() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.apply(
+            Player::Player0,
+            &CheckerMove::new(Field::Point(23), vec![3, 1])?,
+        )?;
+        assert_eq!(board.get().points[23], Point::new(Player::Player0, 1));
+        assert_eq!(board.get().points[19], Point::new(Player::Player0, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn apply_move_blocked() -> Result<(), Error> {
+        let mut board = Board::new();
+        // the intermediate field 18 is blocked by player 1
+        let m = CheckerMove::new(Field::Point(23), vec![5, 1])?;
+        assert!(board.apply(Player::Player0, &m).is_err());
+        assert_eq!(board, Board::new());
+        Ok(())
+    }
+
+    #[test]
+    fn apply_move_no_checker() -> Result<(), Error> {
+        let mut board = Board::new();
+        let m = CheckerMove::new(Field::Point(20), vec![3])?;
+        assert!(board.apply(Player::Player0, &m).is_err());
+        let m = CheckerMove::new(Field::Bar, vec![3])?;
+        assert!(board.apply(Player::Player0, &m).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn legal_step() -> Result<(), Error> {
+        let mut board = Board::new();
+        assert_eq!(
+            board.legal_step(Player::Player0, Field::Point(23), 6)?,
+            Field::Point(17)
+        );
+        assert!(board
+            .legal_step(Player::Player0, Field::Point(23), 5)
+            .is_err());
+        assert!(board
+            .legal_step(Player::Player0, Field::Point(22), 1)
+            .is_err());
+        assert!(board
+            .legal_step(Player::Player0, Field::Point(4), 6)
+            .is_err());
+
+        board.set_bar(Player::Player0, 1)?;
+        assert!(board
+            .legal_step(Player::Player0, Field::Point(23), 6)
+            .is_err());
+        assert_eq!(
+            board.legal_step(Player::Player0, Field::Bar, 1)?,
+            Field::Point(23)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn can_enter() -> Result<(), Error> {
+        let board = Board::new();
+        assert!(board.can_enter(Player::Player0, 1)?);
+        assert!(!board.can_enter(Player::Player0, 6)?);
+        assert!(!board.can_enter(Player::Player1, 6)?);
+        assert!(board.can_enter(Player::Player1, 5)?);
+        assert!(board.can_enter(Player::Player1, 7).is_err());
+        assert!(board.can_enter(Player::Nobody, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn plays_enter_first() -> Result<(), Error> {
+        // with two checkers on the bar, both dice values have to be used to enter
+        let mut board = Board::new();
+        board.set_bar(Player::Player0, 2)?;
+        let plays = board.plays(Player::Player0, &[4, 2]);
+        assert!(!plays.is_empty());
+        for play in plays.iter() {
+            assert_eq!(play.len(), 2);
+            assert!(play.iter().all(|m| m.from == Field::Bar));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn plays_enter_one() -> Result<(), Error> {
+        // with two checkers on the bar and only one dice value to enter, the turn is over
+        let mut board = Board::new();
+        board.set_bar(Player::Player0, 2)?;
+        let plays = board.plays(Player::Player0, &[6, 2]);
+        assert_eq!(plays.len(), 1);
+        assert_eq!(plays[0].len(), 1);
+        assert_eq!(plays[0][0].to, Field::Point(22));
+        Ok(())
+    }
+
+    #[test]
+    fn plays_enter_and_move() -> Result<(), Error> {
+        // after entering the only checker on the bar, the other dice value can be used freely
+        let mut board = Board::new();
+        board.set_bar(Player::Player0, 1)?;
+        let plays = board.plays(Player::Player0, &[6, 2]);
+        assert!(plays
+            .iter()
+            .all(|p| p[0].from == Field::Bar && p.len() == 2));
+        assert!(plays.iter().any(|p| p[1].from == Field::Point(12)));
+        Ok(())
+    }
+
+    /// Returns a board where both players only have checkers in their home boards
+    fn bear_off_board(fields: [u8; 6]) -> Board {
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        for (field, checkers) in fields.into_iter().enumerate() {
+            board.points[field] = Point::new(Player::Player0, checkers);
+        }
+        board.points[23] = Point::new(Player::Player1, 15);
+        board.off[0] = 15 - fields.iter().sum::<u8>();
+        board
+    }
+
+    #[test]
+    fn hits() -> Result<(), Error> {
+        let mut board = Board::new();
+        board.set(Player::Player1, 17, 1)?;
+        let m = CheckerMove::new(Field::Point(12), vec![6])?;
+        assert_eq!(board.hits(Player::Player0, &m)?, vec![Field::Point(6)]);
+        let m = CheckerMove::new(Field::Point(12), vec![5])?;
+        assert!(board.hits(Player::Player0, &m)?.is_empty());
+        let m = CheckerMove::new(Field::Point(12), vec![1, 5])?;
+        assert_eq!(board.hits(Player::Player0, &m)?, vec![Field::Point(6)]);
+        let plays = board.plays(Player::Player0, &[6, 5]);
+        assert!(plays
+            .iter()
+            .any(|p| p.iter().any(|m| m.to_string() == "13/7*")));
+        Ok(())
+    }
+
+    #[test]
+    fn all_home() -> Result<(), Error> {
+        assert!(!Board::new().all_home(Player::Player0)?);
+        let mut board = bear_off_board([3, 3, 3, 2, 2, 2]);
+        assert!(board.all_home(Player::Player0)?);
+        assert!(board.all_home(Player::Player1)?);
+        board.set_bar(Player::Player1, 1)?;
+        assert!(!board.all_home(Player::Player1)?);
+        Ok(())
+    }
+
+    #[test]
+    fn bear_off_exact() -> Result<(), Error> {
+        let board = bear_off_board([3, 3, 3, 2, 2, 2]);
+        assert_eq!(
+            board.legal_step(Player::Player0, Field::Point(5), 6)?,
+            Field::Off
+        );
+        assert_eq!(
+            board.legal_step(Player::Player0, Field::Point(2), 3)?,
+            Field::Off
+        );
+        // moving inside the home board is still allowed
+        assert_eq!(
+            board.legal_step(Player::Player0, Field::Point(5), 3)?,
+            Field::Point(2)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bear_off_not_home() -> Result<(), Error> {
+        let mut board = bear_off_board([3, 3, 3, 2, 2, 1]);
+        board.points[6] = Point::new(Player::Player0, 1);
+        assert!(matches!(
+            board.legal_step(Player::Player0, Field::Point(5), 6),
+            Err(Error::MoveInvalidBearOff)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn bear_off_larger_dice() -> Result<(), Error> {
+        let board = bear_off_board([3, 3, 0, 0, 0, 0]);
+        assert_eq!(
+            board.legal_step(Player::Player0, Field::Point(1), 6)?,
+            Field::Off
+        );
+        // only the checkers on the highest field may be borne off with a larger dice value
+        assert!(matches!(
+            board.legal_step(Player::Player0, Field::Point(0), 6),
+            Err(Error::MoveInvalidBearOff)
+        ));
+        let plays = board.plays(Player::Player0, &[6, 5]);
+        assert!(plays.iter().all(|p| p
+            .iter()
+            .all(|m| m.from == Field::Point(1) && m.to == Field::Off)));
+        Ok(())
+    }
+
+    #[test]
+    fn bear_off_prefer_move() -> Result<(), Error> {
+        // a checker on field 5 can not be borne off with a 5 and has to be moved instead
+        let board = bear_off_board([0, 0, 0, 0, 0, 1]);
+        assert_eq!(
+            board.legal_step(Player::Player0, Field::Point(5), 5)?,
+            Field::Point(0)
+        );
+        let plays = board.plays(Player::Player0, &[5, 1]);
+        assert!(plays
+            .iter()
+            .any(|p| p[0].steps == vec![5] && p[1].steps == vec![1] && p[1].to == Field::Off));
+        Ok(())
+    }
+
+    #[test]
+    fn plays_opening() {
+        let board = Board::new();
+        let plays = board.plays(Player::Player0, &[3, 1]);
+        assert!(plays.iter().all(|p| p.len() == 2));
+        assert!(plays.iter().any(|p| p[0].from == Field::Point(7)
+            && p[0].steps == vec![3]
+            && p[1].from == Field::Point(5)
+            && p[1].steps == vec![1]));
+    }
+
+    #[test]
+    fn plays_larger_dice() -> Result<(), Error> {
+        // player 0 has a single checker on field 12 and field 1 is blocked, so either the 6 or
+        // the 5 can be played, but not both
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.points[12] = Point::new(Player::Player0, 1);
+        board.points[1] = Point::new(Player::Player1, 2);
+        let plays = board.plays(Player::Player0, &[5, 6]);
+        assert_eq!(plays.len(), 1);
+        assert_eq!(plays[0].len(), 1);
+        assert_eq!(plays[0][0].steps, vec![6]);
+        Ok(())
+    }
+
+    #[test]
+    fn plays_none() {
+        let mut board = Board::new();
+        board.points = [Point::EMPTY; 24];
+        board.bar[0] = 1;
+        for field in 18..24 {
+            board.points[field] = Point::new(Player::Player1, 2);
+        }
+        assert_eq!(board.plays(Player::Player0, &[5, 6]), vec![vec![]]);
+    }
+
+    #[test]
+    fn pinning() -> Result<(), Error> {
+        let mut board = Variant::Plakoto.start();
+        assert!(board.validate().is_ok());
+        board.set(Player::Player1, 23, -1)?;
+        board.set(Player::Player1, 6, 1)?;
+        let m = CheckerMove::new(Field::Point(23), vec![6])?;
+        assert!(board.hits(Player::Player0, &m)?.is_empty());
+        board.apply(Player::Player0, &m)?;
+        assert_eq!(board.pins, [0, 1 << 6]);
+        assert!(board.validate().is_ok());
+        assert_eq!(board.get().points[17], Point::new(Player::Player0, 1));
+        assert_eq!(board.get_checkers(Player::Player1, Field::Point(6))?, 1);
+
+        // the pinned checker can neither move nor be joined
+        assert!(board.blocked(Player::Player1, 6)?);
+        assert!(matches!(
+            board.set(Player::Player1, 6, -1),
+            Err(Error::MoveInvalidPinned)
+        ));
+        assert!(board
+            .plays(Player::Player1, &[1, 2])
+            .iter()
+            .flatten()
+            .all(|m| m.from != Field::Point(6)));
+
+        // the pinner can be joined, and the checker is released when the field is left
+        board.set(Player::Player0, 23, -1)?;
+        board.set(Player::Player0, 17, 1)?;
+        assert!(board.is_pinned(Player::Player1, 6)?);
+        board.set(Player::Player0, 17, -2)?;
+        board.set(Player::Player0, 10, 2)?;
+        assert_eq!(board.pins, [0, 0]);
+        assert!(board
+            .legal_step(Player::Player1, Field::Point(6), 1)
+            .is_ok());
+        assert!(board.validate().is_ok());
+
+        // a checker can only be pinned by the opponent
+        board.pins[0] = 1 << 10;
+        assert!(matches!(
+            board.validate(),
+            Err(Error::PositionInvalid(PositionError::PinsInvalid))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn point_iterators() -> Result<(), Error> {
+        let mut board = Variant::Plakoto.start();
+        board.set(Player::Player1, 23, -2)?;
+        board.set(Player::Player1, 6, 1)?;
+        board.set(Player::Player1, 2, 1)?;
+        board.set(Player::Player0, 23, -1)?;
+        board.set(Player::Player0, 17, 1)?;
+        assert_eq!(board.pins, [0, 1 << 6]);
+
+        let number = |p: RelativePoint| p.number();
+        let occupied = board.occupied(Player::Player1)?.collect::<Vec<_>>();
+        assert_eq!(occupied.len(), 3);
+        assert_eq!(occupied[0], (RelativePoint::new(3)?, 1));
+        // the pinned checker is no blot
+        assert_eq!(
+            board
+                .blots(Player::Player1)?
+                .map(number)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+        assert_eq!(
+            board
+                .made_points(Player::Player1)?
+                .map(number)
+                .collect::<Vec<_>>(),
+            vec![24]
+        );
+        assert_eq!(board.checkers(Player::Player1)?, 15);
+        assert_eq!(
+            board.point(AbsolutePoint::new(18)?),
+            Point::new(Player::Player0, 1)
+        );
+
+        board.set(Player::Player0, 23, -14)?;
+        board.set_off(Player::Player0, 14)?;
+        assert_eq!(board.checkers(Player::Player0)?, 1);
+        assert!(board.occupied(Player::Nobody).is_err());
+        assert!(board.blots(Player::Nobody).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn holding() -> Result<(), Error> {
+        let mut board = Variant::Fevga.start();
+        assert!(board.validate().is_ok());
+        // the players start from diagonally opposite corners
+        assert_eq!(board.get().points[23], Point::new(Player::Player0, 15));
+        assert_eq!(board.get().points[11], Point::new(Player::Player1, 15));
+        assert!(!board.is_race());
+
+        // a single checker holds a field, field 20 of player 0 is field 8 of player 1
+        board.set(Player::Player0, 23, -1)?;
+        board.set(Player::Player0, 20, 1)?;
+        assert!(board.blocked(Player::Player1, 8)?);
+        assert!(matches!(
+            board.set(Player::Player1, 8, 1),
+            Err(Error::FieldBlocked)
+        ));
+        assert!(board
+            .legal_step(Player::Player1, Field::Point(23), 3)
+            .is_ok());
+
+        // once both players have left the first half, they cannot meet anymore
+        board.points = [Point::EMPTY; 24];
+        board.points[5] = Point::new(Player::Player0, 15);
+        // field 11 of player 1 is field 23 of player 0
+        board.points[23] = Point::new(Player::Player1, 15);
+        assert!(board.validate().is_ok());
+        assert!(board.is_race());
+        Ok(())
+    }
+
     #[test]
     fn set_wrong_amount1() {
         let mut board = Board::new();
@@ -0,0 +1,148 @@
+use crate::rules::Field;
+use crate::Error;
+use core::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents a point on the board numbered from 1 to 24 as seen by player 0, the same for both
+/// players.
+///
+/// Points are numbered in the direction player 0 moves, so that point 1 is the last point of the
+/// home board of player 0, and the index of a point in [`BoardDisplay`](crate::rules::BoardDisplay)
+/// is its number minus 1. Use [`Board::relative`](crate::rules::Board::relative) to find the
+/// point a player calls it.
+/// ```
+/// use backgammon::rules::{AbsolutePoint, Board, Player, RelativePoint};
+///
+/// let board = Board::new();
+/// let point = AbsolutePoint::new(6).unwrap();
+/// assert_eq!(board.relative(Player::Player0, point).unwrap().number(), 6);
+/// assert_eq!(board.relative(Player::Player1, point).unwrap().number(), 19);
+/// assert!(AbsolutePoint::new(25).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AbsolutePoint(u8);
+
+/// Represents a point on the board numbered from 1 to 24 as seen by the player who owns the
+/// checkers on it, i.e. the number of pips a checker on the point needs to bear off.
+///
+/// This is the numbering of the conventional move notation, and the same as the
+/// [`Field::Point`] of the player plus 1. Use [`Board::absolute`](crate::rules::Board::absolute)
+/// to find the point on the board both players agree on.
+/// ```
+/// use backgammon::rules::{Field, RelativePoint};
+///
+/// let point = RelativePoint::new(24).unwrap();
+/// assert_eq!(Field::from(point), Field::Point(23));
+/// assert_eq!(RelativePoint::try_from(Field::Point(23)).unwrap(), point);
+/// assert!(RelativePoint::try_from(Field::Bar).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RelativePoint(u8);
+
+impl AbsolutePoint {
+    /// Create a point from its number between 1 and 24
+    pub fn new(number: u8) -> Result<Self, Error> {
+        match number {
+            1..=24 => Ok(AbsolutePoint(number)),
+            _ => Err(Error::FieldInvalid),
+        }
+    }
+
+    /// Returns the number of the point between 1 and 24
+    pub fn number(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns the index of the point, between 0 and 23
+    pub(crate) fn index(&self) -> usize {
+        usize::from(self.0 - 1)
+    }
+}
+
+impl RelativePoint {
+    /// Create a point from its number between 1 and 24
+    pub fn new(number: u8) -> Result<Self, Error> {
+        match number {
+            1..=24 => Ok(RelativePoint(number)),
+            _ => Err(Error::FieldInvalid),
+        }
+    }
+
+    /// Returns the number of the point between 1 and 24
+    pub fn number(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns the index of the point, between 0 and 23
+    pub(crate) fn index(&self) -> usize {
+        usize::from(self.0 - 1)
+    }
+}
+
+impl From<RelativePoint> for Field {
+    fn from(point: RelativePoint) -> Self {
+        Field::Point(point.index())
+    }
+}
+
+impl TryFrom<Field> for RelativePoint {
+    type Error = Error;
+
+    /// Convert a field on the board, failing for the bar and the off area
+    fn try_from(field: Field) -> Result<Self, Self::Error> {
+        match field {
+            Field::Point(field) if field < 24 => Ok(RelativePoint(field as u8 + 1)),
+            _ => Err(Error::FieldInvalid),
+        }
+    }
+}
+
+// implement Display trait
+impl fmt::Display for AbsolutePoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// implement Display trait
+impl fmt::Display for RelativePoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert!(AbsolutePoint::new(0).is_err());
+        assert_eq!(AbsolutePoint::new(1).map(|p| p.number()).ok(), Some(1));
+        assert_eq!(AbsolutePoint::new(24).map(|p| p.index()).ok(), Some(23));
+        assert!(RelativePoint::new(0).is_err());
+        assert!(RelativePoint::new(25).is_err());
+        assert_eq!(RelativePoint::new(13).map(|p| p.index()).ok(), Some(12));
+    }
+
+    #[test]
+    fn test_field() -> Result<(), Error> {
+        for number in 1..=24 {
+            let point = RelativePoint::new(number)?;
+            assert_eq!(RelativePoint::try_from(Field::from(point))?, point);
+        }
+        assert!(RelativePoint::try_from(Field::Off).is_err());
+        assert!(RelativePoint::try_from(Field::Point(24)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> Result<(), Error> {
+        assert_eq!(AbsolutePoint::new(7)?.to_string(), "7");
+        assert_eq!(RelativePoint::new(18)?.to_string(), "18");
+        Ok(())
+    }
+}
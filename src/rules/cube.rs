@@ -1,5 +1,6 @@
 use crate::error::Error;
 use crate::rules::Player;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Represents a Backgammon cube (doubling cube).
@@ -7,7 +8,8 @@ use serde::{Deserialize, Serialize};
 /// This cube represents an increase in the value of the current game. The cube -- a doubling of
 /// the value of the game -- can be offered by any player the first time it is used. After that, it
 /// can only be offered by the player who last took the cube.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cube {
     exponential: u8,
     /// Owner of the cube
@@ -31,8 +33,7 @@ impl Cube {
     /// technical limit of 2^64 on the value of the cube, which we believe is a reasonable limit.
     pub fn set(&mut self, value: u64) -> Result<(), Error> {
         if value.is_power_of_two() {
-            let vf = value as f64;
-            self.exponential = vf.sqrt().round() as u8;
+            self.exponential = value.trailing_zeros() as u8;
 
             Ok(())
         } else {
@@ -97,6 +98,16 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn set_value64() -> Result<(), Error> {
+        let mut cube = Cube::default();
+        cube.set(32)?;
+        assert_eq!(cube.value(), 32);
+        cube.set(64)?;
+        assert_eq!(cube.value(), 64);
+        Ok(())
+    }
+
     #[test]
     fn set_invalidvalue() -> Result<(), Error> {
         let mut cube = Cube::default();
@@ -1,11 +1,21 @@
 use crate::Error;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
 use rand::distributions::{Distribution, Uniform};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, PoisonError};
 
 /// Represents the two dices
 ///
 /// Backgammon is always played with two dices.
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Dices {
     /// The two dice values
     pub values: (u8, u8),
@@ -15,25 +25,393 @@ pub struct Dices {
     pub consumed: (bool, bool, bool, bool),
 }
 impl Dices {
+    /// Create dices showing the given values, none of them consumed yet.
+    pub fn new(values: (u8, u8)) -> Self {
+        // if both dices are equal, we have four dices to play
+        let single = values.0 != values.1;
+        Dices {
+            values,
+            consumed: (false, false, single, single),
+        }
+    }
+
     /// Roll the dices which generates two random numbers between 1 and 6, replicating a perfect
-    /// dice. We use the operating system's random number generator.
+    /// dice. We use the operating system's random number generator, see [`RandomDices`].
+    #[cfg(feature = "std")]
     pub fn roll(self) -> Self {
-        let between = Uniform::new_inclusive(1, 6);
-        let mut rng = rand::thread_rng();
+        Dices::new(RandomDices.roll())
+    }
 
-        let v = (between.sample(&mut rng), between.sample(&mut rng));
+    /// Roll the dices with the given random number generator instead of the operating system's
+    /// one, e.g. to reuse the state of a generator or on targets without one.
+    pub fn roll_with_rng(self, rng: &mut impl Rng) -> Self {
+        Dices::new(roll_rng(rng))
+    }
 
-        // if both dices are equal, we have four dices to play
-        if v.0 == v.1 {
-            Dices {
-                values: (v.0, v.1),
-                consumed: (false, false, false, false),
-            }
+    /// Returns the values of the dices as a roll
+    pub fn rolled(&self) -> DiceRoll {
+        DiceRoll(self.values.0, self.values.1)
+    }
+
+    /// Returns the dice values which have not been consumed yet.
+    pub fn available(&self) -> Vec<u8> {
+        let mut available = Vec::with_capacity(4);
+        if !self.consumed.0 {
+            available.push(self.values.0);
+        }
+        if !self.consumed.1 {
+            available.push(self.values.1);
+        }
+        if !self.consumed.2 {
+            available.push(self.values.1);
+        }
+        if !self.consumed.3 {
+            available.push(self.values.1);
+        }
+        available
+    }
+
+    /// Mark one dice value as consumed.
+    pub fn consume(&mut self, dice: u8) -> Result<(), Error> {
+        if dice == self.values.0 && !self.consumed.0 {
+            self.consumed.0 = true;
+        } else if dice == self.values.1 && !self.consumed.1 {
+            self.consumed.1 = true;
+        } else if dice == self.values.1 && !self.consumed.2 {
+            self.consumed.2 = true;
+        } else if dice == self.values.1 && !self.consumed.3 {
+            self.consumed.3 = true;
         } else {
-            Dices {
-                values: (v.0, v.1),
-                consumed: (false, false, true, true),
+            return Err(Error::DiceInvalid);
+        }
+        Ok(())
+    }
+
+    /// Returns true if all dice values have been consumed.
+    pub fn all_consumed(&self) -> bool {
+        self.consumed.0 && self.consumed.1 && self.consumed.2 && self.consumed.3
+    }
+}
+
+/// Represents the values of two dices rolled, in the order they were rolled, e.g. "6-5"
+///
+/// The roll is named `DiceRoll` rather than `Roll`, which is the trait to roll the dices of a game.
+/// ```
+/// use backgammon::rules::DiceRoll;
+///
+/// let roll: DiceRoll = "5-6".parse().unwrap();
+/// assert_eq!(roll.pips(), 11);
+/// assert!(!roll.is_double());
+/// assert_ne!(roll, DiceRoll(6, 5));
+/// assert!(roll.same_as(&DiceRoll(6, 5)));
+/// assert_eq!(roll.normalized().to_string(), "6-5");
+/// assert_eq!(DiceRoll(4, 4).dies(), vec![4, 4, 4, 4]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiceRoll(pub u8, pub u8);
+
+impl DiceRoll {
+    /// Create a roll, both values must be between 1 and 6
+    pub fn new(first: u8, second: u8) -> Result<Self, Error> {
+        if !(1..=6).contains(&first) || !(1..=6).contains(&second) {
+            return Err(Error::DiceInvalid);
+        }
+        Ok(DiceRoll(first, second))
+    }
+
+    /// Returns true if both dices show the same value, which is played four times
+    pub fn is_double(&self) -> bool {
+        self.0 == self.1
+    }
+
+    /// Returns the pips to play, i.e. twice the sum of the dices for a double
+    pub fn pips(&self) -> u8 {
+        match self.is_double() {
+            true => 4 * self.0,
+            false => self.0 + self.1,
+        }
+    }
+
+    /// Returns the dice values to play, four values for a double and two values otherwise
+    pub fn dies(&self) -> Vec<u8> {
+        match self.is_double() {
+            true => vec![self.0; 4],
+            false => vec![self.0, self.1],
+        }
+    }
+
+    /// Returns the roll with the larger value first, as rolls are usually written
+    pub fn normalized(&self) -> Self {
+        DiceRoll(self.0.max(self.1), self.0.min(self.1))
+    }
+
+    /// Returns the probability of rolling these values in any order, i.e. 1/36 for a double and
+    /// 2/36 otherwise
+    pub fn probability(&self) -> f64 {
+        match self.is_double() {
+            true => 1.0 / 36.0,
+            false => 2.0 / 36.0,
+        }
+    }
+
+    /// Returns the 21 distinct rolls, each with the larger value first, with their probability,
+    /// e.g. to weigh the positions after every possible roll
+    /// ```
+    /// use backgammon::rules::DiceRoll;
+    ///
+    /// let rolls: Vec<(DiceRoll, f64)> = DiceRoll::all_distinct().collect();
+    /// assert_eq!(rolls.len(), 21);
+    /// assert_eq!(rolls[0], (DiceRoll(1, 1), 1.0 / 36.0));
+    /// assert_eq!(rolls[1], (DiceRoll(2, 1), 2.0 / 36.0));
+    /// let total: f64 = rolls.iter().map(|(_, p)| p).sum();
+    /// assert!((total - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn all_distinct() -> impl Iterator<Item = (DiceRoll, f64)> {
+        (1..=6)
+            .flat_map(|first| (1..=first).map(move |second| DiceRoll(first, second)))
+            .map(|roll| (roll, roll.probability()))
+    }
+
+    /// Returns true if both rolls show the same values, in any order. Use `==` to compare the
+    /// values in order.
+    pub fn same_as(&self, other: &DiceRoll) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl From<(u8, u8)> for DiceRoll {
+    fn from(values: (u8, u8)) -> Self {
+        DiceRoll(values.0, values.1)
+    }
+}
+
+impl From<DiceRoll> for (u8, u8) {
+    fn from(roll: DiceRoll) -> Self {
+        (roll.0, roll.1)
+    }
+}
+
+impl fmt::Display for DiceRoll {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.0, self.1)
+    }
+}
+
+impl FromStr for DiceRoll {
+    type Err = Error;
+
+    /// Parse a roll written as "6-5" or "65"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits: Vec<u8> = s
+            .trim()
+            .bytes()
+            .filter(|b| *b != b'-')
+            .map(|b| b.wrapping_sub(b'0'))
+            .collect();
+        match (digits.as_slice(), s.trim().matches('-').count()) {
+            ([first, second], 0 | 1) => DiceRoll::new(*first, *second),
+            _ => Err(Error::DiceInvalid),
+        }
+    }
+}
+
+/// Trait for the sources of the dices rolled in a game, e.g. a random number generator, dices
+/// rolled by a server, or a fixed sequence of rolls in a test
+/// ```
+/// use backgammon::Game;
+/// use backgammon::rules::{DiceSource, Player, Roll};
+///
+/// #[derive(Debug)]
+/// struct Loaded;
+///
+/// impl DiceSource for Loaded {
+///     fn roll(&mut self) -> (u8, u8) {
+///         (6, 5)
+///     }
+/// }
+///
+/// let mut g = Game::new().with_dice_source(Loaded);
+/// g.roll().unwrap();
+/// assert_eq!(g.who_plays, Player::Player0);
+/// assert_eq!(g.dices.values, (6, 5));
+/// ```
+pub trait DiceSource: fmt::Debug + Send {
+    /// Roll two dices, each showing a value between 1 and 6
+    fn roll(&mut self) -> (u8, u8);
+
+    /// Returns the seed all dices rolled are derived from, if the source can be reproduced from a
+    /// seed. The seed is recorded in the [`GameRecord`](crate::GameRecord) of the game.
+    fn seed(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Rolls the dices with the operating system's random number generator, which is the default
+/// source of the dices. It needs the standard library.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RandomDices;
+
+#[cfg(feature = "std")]
+impl DiceSource for RandomDices {
+    fn roll(&mut self) -> (u8, u8) {
+        roll_rng(&mut rand::thread_rng())
+    }
+}
+
+/// Roll two dices with a random number generator
+fn roll_rng(rng: &mut impl Rng) -> (u8, u8) {
+    let between = Uniform::new_inclusive(1, 6);
+    (between.sample(rng), between.sample(rng))
+}
+
+/// Rolls the dices with the ChaCha8 random number generator initialized from a seed, so that a
+/// game or a rollout can be reproduced from the seed on any platform
+/// ```
+/// use backgammon::Game;
+/// use backgammon::rules::{Roll, SeededDice};
+///
+/// let mut g1 = Game::new().with_dice_source(SeededDice::new(42));
+/// let mut g2 = Game::new().with_dice_source(SeededDice::new(42));
+/// g1.roll().unwrap();
+/// g2.roll().unwrap();
+/// assert_eq!(g1.dices, g2.dices);
+/// assert_eq!(g1.record().seed(), Some(42));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeededDice {
+    /// The seed the generator has been initialized from
+    seed: u64,
+    /// The generator
+    rng: ChaCha8Rng,
+}
+
+impl SeededDice {
+    /// Create dices rolling the sequence derived from the given seed
+    pub fn new(seed: u64) -> Self {
+        SeededDice {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl DiceSource for SeededDice {
+    fn roll(&mut self) -> (u8, u8) {
+        (self.rng.gen_range(1..=6), self.rng.gen_range(1..=6))
+    }
+
+    fn seed(&self) -> Option<u64> {
+        Some(self.seed)
+    }
+}
+
+/// Rolls the dices entered by hand, e.g. when playing on a physical board. The source is a handle,
+/// its clones share the dices entered, so a clone can be kept to enter the dices of a game which
+/// rolls from this source. Rolling before any dices have been entered fails with
+/// [`Error::DiceInvalid`]. It needs the standard library.
+/// ```
+/// use backgammon::{Error, Game};
+/// use backgammon::rules::{ManualDice, Player, Roll};
+///
+/// let dices = ManualDice::new();
+/// let mut g = Game::new().with_dice_source(dices.clone());
+/// assert!(matches!(g.roll(), Err(Error::DiceInvalid)));
+/// dices.enter((3, 5)).unwrap();
+/// g.roll().unwrap();
+/// assert_eq!(g.who_plays, Player::Player1);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct ManualDice {
+    /// The dices entered and not rolled yet
+    entered: Arc<Mutex<Option<(u8, u8)>>>,
+}
+
+#[cfg(feature = "std")]
+impl ManualDice {
+    /// Create a source without any dices entered
+    pub fn new() -> Self {
+        ManualDice::default()
+    }
+
+    /// Enter the dices for the next roll, replacing any dices entered before. Both values must be
+    /// between 1 and 6.
+    pub fn enter(&self, values: (u8, u8)) -> Result<(), Error> {
+        if !(1..=6).contains(&values.0) || !(1..=6).contains(&values.1) {
+            return Err(Error::DiceInvalid);
+        }
+        *self.entered.lock().unwrap_or_else(PoisonError::into_inner) = Some(values);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl DiceSource for ManualDice {
+    fn roll(&mut self) -> (u8, u8) {
+        // invalid dices make the game reject the roll
+        self.entered
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .unwrap_or((0, 0))
+    }
+}
+
+/// Rolls a fixed sequence of dices in order, e.g. to set up a published problem or a deterministic
+/// test. Once all dices have been rolled, rolling fails with [`Error::DiceInvalid`].
+/// ```
+/// use backgammon::{Error, Game};
+/// use backgammon::rules::{Player, Roll, ScriptedDice};
+///
+/// let dices = ScriptedDice::new(vec![(5, 5), (3, 1)]).unwrap();
+/// let mut g = Game::new().with_dice_source(dices);
+/// g.roll().unwrap();
+/// g.roll().unwrap();
+/// assert_eq!(g.who_plays, Player::Player0);
+/// for mv in g.parse_play("8/5 6/5").unwrap().moves {
+///     g.apply_move(&mv).unwrap();
+/// }
+/// assert!(matches!(g.roll(), Err(Error::DiceInvalid)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScriptedDice {
+    /// The dices to roll, in order
+    rolls: Vec<(u8, u8)>,
+    /// The number of dices rolled so far
+    rolled: usize,
+}
+
+impl ScriptedDice {
+    /// Create a source rolling the given dices in order. All values must be between 1 and 6.
+    pub fn new(rolls: Vec<(u8, u8)>) -> Result<Self, Error> {
+        if rolls
+            .iter()
+            .any(|r| !(1..=6).contains(&r.0) || !(1..=6).contains(&r.1))
+        {
+            return Err(Error::DiceInvalid);
+        }
+        Ok(ScriptedDice { rolls, rolled: 0 })
+    }
+
+    /// Returns the dices which have not been rolled yet
+    pub fn remaining(&self) -> &[(u8, u8)] {
+        &self.rolls[self.rolled..]
+    }
+}
+
+impl DiceSource for ScriptedDice {
+    fn roll(&mut self) -> (u8, u8) {
+        match self.rolls.get(self.rolled) {
+            Some(roll) => {
+                self.rolled += 1;
+                *roll
             }
+            // invalid dices make the game reject the roll
+            None => (0, 0),
         }
     }
 }
@@ -49,6 +427,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "std")]
     fn test_roll() {
         let dices = Dices::default().roll();
         assert!(dices.values.0 >= 1 && dices.values.0 <= 6);
@@ -56,6 +435,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn test_roll_consumed() {
         let dices = Dices::default().roll();
         if dices.values.0 == dices.values.1 {
@@ -66,6 +446,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn test_roll_consumed1() {
         for _i in 0..100 {
             let dices = Dices::default().roll();
@@ -76,4 +457,153 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_random_dices() {
+        assert_eq!(RandomDices.seed(), None);
+        let mut source = RandomDices;
+        for _i in 0..100 {
+            let values = source.roll();
+            assert!((1..=6).contains(&values.0));
+            assert!((1..=6).contains(&values.1));
+        }
+    }
+
+    #[test]
+    fn test_seeded_dice() {
+        let rolls: Vec<(u8, u8)> = {
+            let mut source = SeededDice::new(7);
+            (0..100).map(|_| source.roll()).collect()
+        };
+        let mut source = SeededDice::new(7);
+        assert!(rolls.iter().all(|r| *r == source.roll()));
+        assert!(rolls
+            .iter()
+            .all(|r| (1..=6).contains(&r.0) && (1..=6).contains(&r.1)));
+        assert_eq!(source.seed(), Some(7));
+
+        let mut other = SeededDice::new(8);
+        assert_ne!(rolls, (0..100).map(|_| other.roll()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_manual_dice() -> Result<(), Error> {
+        let dices = ManualDice::new();
+        let mut source = dices.clone();
+        assert_eq!(source.roll(), (0, 0));
+        assert!(matches!(dices.enter((0, 3)), Err(Error::DiceInvalid)));
+        assert!(matches!(dices.enter((4, 7)), Err(Error::DiceInvalid)));
+        dices.enter((2, 2))?;
+        dices.enter((6, 1))?;
+        assert_eq!(source.roll(), (6, 1));
+        // every dices entered are rolled once
+        assert_eq!(source.roll(), (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scripted_dice() -> Result<(), Error> {
+        assert!(matches!(
+            ScriptedDice::new(vec![(1, 2), (0, 3)]),
+            Err(Error::DiceInvalid)
+        ));
+        let mut source = ScriptedDice::new(vec![(1, 2), (4, 4)])?;
+        assert_eq!(source.roll(), (1, 2));
+        assert_eq!(source.remaining(), &[(4, 4)]);
+        assert_eq!(source.roll(), (4, 4));
+        assert!(source.remaining().is_empty());
+        assert_eq!(source.roll(), (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_roll_with_rng() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let dices = Dices::default().roll_with_rng(&mut rng);
+        assert!((1..=6).contains(&dices.values.0) && (1..=6).contains(&dices.values.1));
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        assert_eq!(Dices::default().roll_with_rng(&mut rng), dices);
+    }
+
+    #[test]
+    fn test_dice_roll() -> Result<(), Error> {
+        let roll = DiceRoll::new(3, 5)?;
+        assert_eq!(roll.pips(), 8);
+        assert_eq!(roll.dies(), vec![3, 5]);
+        assert_eq!(roll.to_string(), "3-5");
+        assert_eq!(DiceRoll(6, 6).pips(), 24);
+        assert!(DiceRoll(6, 6).is_double());
+        assert!(DiceRoll::new(0, 5).is_err());
+        assert!(DiceRoll::new(2, 7).is_err());
+
+        assert_eq!("3-5".parse::<DiceRoll>()?, roll);
+        assert_eq!("53".parse::<DiceRoll>()?, DiceRoll(5, 3));
+        for invalid in ["", "3", "3--5", "3-5-", "0-1", "a-b", "123"] {
+            assert!(invalid.parse::<DiceRoll>().is_err(), "{}", invalid);
+        }
+
+        assert_eq!(<(u8, u8)>::from(roll), (3, 5));
+        assert_eq!(DiceRoll::from((3, 5)), roll);
+        assert_eq!(Dices::new((3, 5)).rolled(), roll);
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_distinct() {
+        let rolls: Vec<(DiceRoll, f64)> = DiceRoll::all_distinct().collect();
+        assert_eq!(rolls.len(), 21);
+        assert_eq!(rolls.iter().filter(|(r, _)| r.is_double()).count(), 6);
+        // every one of the 36 ordered rolls is one of the distinct rolls
+        for first in 1..=6 {
+            for second in 1..=6 {
+                let roll = DiceRoll(first, second);
+                assert_eq!(rolls.iter().filter(|(r, _)| r.same_as(&roll)).count(), 1);
+            }
+        }
+        assert!(rolls
+            .iter()
+            .all(|(r, p)| *r == r.normalized() && *p == r.probability()));
+    }
+
+    #[test]
+    fn test_available() {
+        let dices = Dices {
+            values: (3, 1),
+            consumed: (false, false, true, true),
+        };
+        assert_eq!(dices.available(), vec![3, 1]);
+
+        let dices = Dices {
+            values: (4, 4),
+            consumed: (true, false, false, false),
+        };
+        assert_eq!(dices.available(), vec![4, 4, 4]);
+    }
+
+    #[test]
+    fn test_consume() -> Result<(), Error> {
+        let mut dices = Dices {
+            values: (3, 1),
+            consumed: (false, false, true, true),
+        };
+        dices.consume(1)?;
+        assert!(dices.consume(1).is_err());
+        assert!(dices.consume(5).is_err());
+        assert!(!dices.all_consumed());
+        dices.consume(3)?;
+        assert!(dices.all_consumed());
+
+        let mut dices = Dices {
+            values: (2, 2),
+            consumed: (false, false, false, false),
+        };
+        for _i in 0..4 {
+            dices.consume(2)?;
+        }
+        assert!(dices.all_consumed());
+        assert!(dices.consume(2).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,395 @@
+use crate::Error;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Represents a place a checker can be in, seen from the point-of-view of its owner.
+///
+/// Fields on the board are numbered from 0 to 23, starting from the last field of each player in
+/// the home board. The bar is located "behind" field 23 and the off area "in front of" field 0.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Field {
+    /// The bar, where hit checkers wait to re-enter the board
+    Bar,
+    /// A field on the board, numbered from 0 to 23
+    Point(usize),
+    /// The off area, where checkers that have been removed from the board are kept
+    Off,
+}
+
+impl Field {
+    /// Returns the field as a signed index, where the bar is 24 and the off area is -1.
+    pub fn index(&self) -> i8 {
+        match *self {
+            Field::Bar => 24,
+            Field::Point(field) => field as i8,
+            Field::Off => -1,
+        }
+    }
+
+    /// Returns the field reached when moving a checker from this field by the given dice value.
+    pub fn step(&self, dice: u8) -> Result<Field, Error> {
+        if !(1..=6).contains(&dice) {
+            return Err(Error::DiceInvalid);
+        }
+        match *self {
+            Field::Off => Err(Error::FieldInvalid),
+            Field::Point(field) if field > 23 => Err(Error::FieldInvalid),
+            _ => {
+                let new = self.index() - dice as i8;
+                if new < 0 {
+                    Ok(Field::Off)
+                } else {
+                    Ok(Field::Point(new as usize))
+                }
+            }
+        }
+    }
+}
+
+// Display fields in the conventional notation, i.e. numbered from 1 to 24
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Field::Bar => write!(f, "bar"),
+            Field::Point(field) => write!(f, "{}", field + 1),
+            Field::Off => write!(f, "off"),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = Error;
+
+    /// Parse a field in the conventional notation, i.e. numbered from 1 to 24, where "bar" (or
+    /// 25) is the bar and "off" (or 0) is the off area.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("bar") {
+            return Ok(Field::Bar);
+        }
+        if s.eq_ignore_ascii_case("off") {
+            return Ok(Field::Off);
+        }
+        match s.parse::<usize>() {
+            Ok(0) => Ok(Field::Off),
+            Ok(25) => Ok(Field::Bar),
+            Ok(point) if point <= 24 => Ok(Field::Point(point - 1)),
+            _ => Err(Error::NotationInvalid),
+        }
+    }
+}
+
+/// Represents the move of a single checker, possibly using several dice values in a row.
+///
+/// ```
+/// use backgammon::rules::{CheckerMove, Field};
+///
+/// // move a checker from field 23 with a 6 and then a 5
+/// let m = CheckerMove::new(Field::Point(23), vec![6, 5]).unwrap();
+/// assert_eq!(m.to, Field::Point(12));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CheckerMove {
+    /// Field the checker is moved from
+    pub from: Field,
+    /// Field the checker is moved to
+    pub to: Field,
+    /// Dice values used for this move, in the order they are played
+    pub steps: Vec<u8>,
+    /// Fields on which a single checker of the opponent has been hit and sent to the bar
+    pub hits: Vec<Field>,
+}
+
+impl CheckerMove {
+    /// Create a new move of a checker from a field using the given dice values in order.
+    pub fn new(from: Field, steps: Vec<u8>) -> Result<Self, Error> {
+        if steps.is_empty() {
+            return Err(Error::MoveInvalid);
+        }
+        let mut to = from;
+        for dice in steps.iter() {
+            if to == Field::Off {
+                return Err(Error::MoveInvalid);
+            }
+            to = to.step(*dice)?;
+        }
+        Ok(CheckerMove {
+            from,
+            to,
+            steps,
+            hits: Vec::new(),
+        })
+    }
+
+    /// Returns the fields visited by the checker, including the final one.
+    pub fn path(&self) -> Result<Vec<Field>, Error> {
+        let mut fields = Vec::with_capacity(self.steps.len());
+        let mut current = self.from;
+        for dice in self.steps.iter() {
+            current = current.step(*dice)?;
+            fields.push(current);
+        }
+        if current != self.to {
+            return Err(Error::MoveInvalid);
+        }
+        Ok(fields)
+    }
+}
+
+// Display moves in the conventional notation, e.g. "24/18" or "13/7*", showing intermediate
+// fields only if a checker has been hit there
+impl fmt::Display for CheckerMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.from)?;
+        for field in self.path().unwrap_or_else(|_| vec![self.to]) {
+            if self.hits.contains(&field) {
+                write!(f, "/{}*", field)?;
+            } else if field == self.to {
+                write!(f, "/{}", field)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CheckerMove {
+    type Err = Error;
+
+    /// Parse the move of a single checker in the conventional notation, e.g. "24/18", "bar/20",
+    /// "6/off" or "24/18*/13". Every segment has to correspond to exactly one dice value, use
+    /// [`Game::parse_play`](crate::Game::parse_play) to resolve compound moves against the dices
+    /// rolled. Fields marked with "*" are recorded as hits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = Vec::new();
+        let (fields, hits) = parse_fields(s)?;
+        for pair in fields.windows(2) {
+            let dice = pair[0].index() - pair[1].index();
+            if !(1..=6).contains(&dice) {
+                return Err(Error::NotationInvalid);
+            }
+            steps.push(dice as u8);
+        }
+        let mut m = CheckerMove::new(fields[0], steps)?;
+        m.hits = hits;
+        Ok(m)
+    }
+}
+
+/// Parse the fields visited by a single checker, e.g. "24/18*/13", and return them together
+/// with the fields marked as hits.
+pub(crate) fn parse_fields(s: &str) -> Result<(Vec<Field>, Vec<Field>), Error> {
+    let mut fields = Vec::new();
+    let mut hits = Vec::new();
+    for f in s.split('/') {
+        let field = f.trim_end_matches('*').parse::<Field>()?;
+        if f.ends_with('*') {
+            hits.push(field);
+        }
+        fields.push(field);
+    }
+    if fields.len() < 2 {
+        return Err(Error::NotationInvalid);
+    }
+    Ok((fields, hits))
+}
+
+/// Split a play in the conventional notation into the tokens for single checkers, expanding
+/// repetitions like "13/11(2)".
+pub(crate) fn split_play(s: &str) -> Result<Vec<&str>, Error> {
+    let mut tokens = Vec::new();
+    for token in s.split(|c: char| c.is_whitespace() || c == ',') {
+        if token.is_empty() {
+            continue;
+        }
+        match token.find('(') {
+            Some(open) => {
+                let count = token[open + 1..]
+                    .strip_suffix(')')
+                    .and_then(|c| c.parse::<usize>().ok())
+                    .filter(|c| (1..=4).contains(c))
+                    .ok_or(Error::NotationInvalid)?;
+                for _i in 0..count {
+                    tokens.push(&token[..open]);
+                }
+            }
+            None => tokens.push(token),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Represents a complete play of a player, i.e. the moves of all checkers in one turn.
+///
+/// ```
+/// use backgammon::rules::{Field, Play};
+///
+/// let play: Play = "24/18 13/11*".parse().unwrap();
+/// assert_eq!(play.moves.len(), 2);
+/// assert_eq!(play.moves[1].to, Field::Point(10));
+/// assert_eq!(play.to_string(), "24/18 13/11*");
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Play {
+    /// Moves of single checkers, in the order they are played
+    pub moves: Vec<CheckerMove>,
+}
+
+// Display plays in the conventional notation, e.g. "24/18 13/11"
+impl fmt::Display for Play {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let moves: Vec<String> = self.moves.iter().map(|m| m.to_string()).collect();
+        write!(f, "{}", moves.join(" "))
+    }
+}
+
+impl FromStr for Play {
+    type Err = Error;
+
+    /// Parse a play in the conventional notation, e.g. "24/18 13/11*", "bar/20 6/off" or
+    /// "8/5(2) 6/3(2)".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let moves = split_play(s)?
+            .into_iter()
+            .map(CheckerMove::from_str)
+            .collect::<Result<Vec<CheckerMove>, Error>>()?;
+        Ok(Play { moves })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_index() {
+        assert_eq!(Field::Bar.index(), 24);
+        assert_eq!(Field::Point(5).index(), 5);
+        assert_eq!(Field::Off.index(), -1);
+    }
+
+    #[test]
+    fn field_step() -> Result<(), Error> {
+        assert_eq!(Field::Bar.step(3)?, Field::Point(21));
+        assert_eq!(Field::Point(5).step(5)?, Field::Point(0));
+        assert_eq!(Field::Point(5).step(6)?, Field::Off);
+        assert!(Field::Off.step(1).is_err());
+        assert!(Field::Point(5).step(7).is_err());
+        assert!(Field::Point(24).step(1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn new_move() -> Result<(), Error> {
+        let m = CheckerMove::new(Field::Point(23), vec![6, 5])?;
+        assert_eq!(m.to, Field::Point(12));
+        assert_eq!(m.path()?, vec![Field::Point(17), Field::Point(12)]);
+        Ok(())
+    }
+
+    #[test]
+    fn new_move_invalid() {
+        assert!(CheckerMove::new(Field::Point(3), vec![]).is_err());
+        assert!(CheckerMove::new(Field::Point(3), vec![4, 1]).is_err());
+        assert!(CheckerMove::new(Field::Off, vec![1]).is_err());
+    }
+
+    #[test]
+    fn parse_field() -> Result<(), Error> {
+        assert_eq!("24".parse::<Field>()?, Field::Point(23));
+        assert_eq!("1".parse::<Field>()?, Field::Point(0));
+        assert_eq!("bar".parse::<Field>()?, Field::Bar);
+        assert_eq!("Bar".parse::<Field>()?, Field::Bar);
+        assert_eq!("25".parse::<Field>()?, Field::Bar);
+        assert_eq!("off".parse::<Field>()?, Field::Off);
+        assert_eq!("0".parse::<Field>()?, Field::Off);
+        assert!("26".parse::<Field>().is_err());
+        assert!("x".parse::<Field>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_checker_move() -> Result<(), Error> {
+        let m: CheckerMove = "24/18".parse()?;
+        assert_eq!(m.from, Field::Point(23));
+        assert_eq!(m.to, Field::Point(17));
+        assert_eq!(m.steps, vec![6]);
+
+        let m: CheckerMove = "bar/20".parse()?;
+        assert_eq!(m.from, Field::Bar);
+        assert_eq!(m.steps, vec![5]);
+
+        let m: CheckerMove = "6/off".parse()?;
+        assert_eq!(m.to, Field::Off);
+        assert_eq!(m.steps, vec![6]);
+
+        let m: CheckerMove = "24/18*/13".parse()?;
+        assert_eq!(m.steps, vec![6, 5]);
+        assert_eq!(m.to, Field::Point(12));
+        assert_eq!(m.hits, vec![Field::Point(17)]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_checker_move_invalid() {
+        assert!("24".parse::<CheckerMove>().is_err());
+        assert!("24/13".parse::<CheckerMove>().is_err());
+        assert!("13/24".parse::<CheckerMove>().is_err());
+        assert!("24/24".parse::<CheckerMove>().is_err());
+        assert!("a/b".parse::<CheckerMove>().is_err());
+    }
+
+    #[test]
+    fn parse_play() -> Result<(), Error> {
+        let p: Play = "24/18 13/11*".parse()?;
+        assert_eq!(p.moves.len(), 2);
+        assert_eq!(p.moves[1].from, Field::Point(12));
+
+        let p: Play = "8/5(2), 6/3(2)".parse()?;
+        assert_eq!(p.moves.len(), 4);
+        assert_eq!(p.moves[1].from, Field::Point(7));
+        assert_eq!(p.moves[3].from, Field::Point(5));
+
+        assert!("8/5(5)".parse::<Play>().is_err());
+        assert!("8/5(x)".parse::<Play>().is_err());
+        assert_eq!("".parse::<Play>()?, Play::default());
+        Ok(())
+    }
+
+    #[test]
+    fn display() -> Result<(), Error> {
+        assert_eq!(Field::Bar.to_string(), "bar");
+        assert_eq!(CheckerMove::new(Field::Bar, vec![5])?.to_string(), "bar/20");
+        assert_eq!(
+            CheckerMove::new(Field::Point(5), vec![6])?.to_string(),
+            "6/off"
+        );
+        let p: Play = "24/18 13/11".parse()?;
+        assert_eq!(p.to_string(), "24/18 13/11");
+        let mut m = CheckerMove::new(Field::Point(23), vec![6, 5])?;
+        assert_eq!(m.to_string(), "24/13");
+        m.hits = vec![Field::Point(17)];
+        assert_eq!(m.to_string(), "24/18*/13");
+        m.hits = vec![Field::Point(17), Field::Point(12)];
+        assert_eq!(m.to_string(), "24/18*/13*");
+        Ok(())
+    }
+
+    #[test]
+    fn path_inconsistent() {
+        let m = CheckerMove {
+            from: Field::Point(10),
+            to: Field::Point(2),
+            steps: vec![3],
+            hits: Vec::new(),
+        };
+        assert!(m.path().is_err());
+    }
+}
@@ -1,5 +1,6 @@
+use core::fmt;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::fmt;
 
 /// Represents a player in the game.
 ///
@@ -8,9 +9,8 @@ use std::fmt;
 /// is convenient here to use 0 and 1 as labels because we sometimes use Rust tuples which we can
 /// then address the same way. There is a special case where nobody is allowed to move or act, for
 /// example when a game begins or ends, thus we define this as the default.
-#[derive(
-    Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Hash, Serialize, Deserialize, Default,
-)]
+#[derive(Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Player {
     /// None of the two players, e.g. at start or end of game.
     #[default]
@@ -30,6 +30,24 @@ impl Player {
             Player::Player1 => Player::Player0,
         }
     }
+
+    /// Returns the index of the player in arrays holding a value for each player, e.g. the bar of
+    /// a [`Board`](crate::rules::Board), or `None` for nobody
+    /// ```
+    /// use backgammon::rules::{Board, Player};
+    ///
+    /// let mut board = Board::new();
+    /// board.set_bar(Player::Player1, 1).unwrap();
+    /// assert_eq!(board.bar[Player::Player1.index().unwrap()], 1);
+    /// assert_eq!(Player::Nobody.index(), None);
+    /// ```
+    pub fn index(&self) -> Option<usize> {
+        match *self {
+            Player::Nobody => None,
+            Player::Player0 => Some(0),
+            Player::Player1 => Some(1),
+        }
+    }
 }
 
 // Implement Display trait for Player
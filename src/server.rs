@@ -0,0 +1,583 @@
+//! # WebSocket Game Server
+//!
+//! Hosts matches for clients connected over WebSocket, with the engine validating every action on
+//! the server. The protocol consists of text messages, one command per message:
+//!
+//! | Command         | Action                                                     |
+//! |-----------------|------------------------------------------------------------|
+//! | `create [N]`    | create a table with a match to N points and sit down as X  |
+//! | `join ID`       | sit down as O at a table created by someone else           |
+//! | `watch ID`      | watch a table                                              |
+//! | `roll`          | roll the dices                                             |
+//! | `move PLAY`     | make a play in the conventional notation, e.g. `24/18 13/11` |
+//! | `double`        | offer the cube                                             |
+//! | `take`, `pass`  | answer a double                                            |
+//!
+//! The server answers `created ID` to the creator of a table and `error MESSAGE` to a client whose
+//! command is rejected. Every change of a table is broadcast to its players and watchers: the
+//! event, e.g. `roll X 3 1`, `move O 24/18 13/11`, `double X`, `take O`, `pass O`,
+//! `game X 2` (the winner of a game and the points won), `match X`, `joined ID` or `left X`,
+//! followed by the state of the table as `state ID XGID`, see [`Xgid`](crate::formats::Xgid).
+//!
+//! [`Server`](crate::server::Server) implements the protocol without doing any I/O, and
+//! [`serve`](crate::server::serve) runs it on a TCP listener with
+//! [tungstenite](https://docs.rs/tungstenite) and one thread per connection:
+//! ```no_run
+//! use backgammon::server::{serve, Server};
+//! use std::net::TcpListener;
+//!
+//! let listener = TcpListener::bind("localhost:8080").unwrap();
+//! serve(listener, Server::new()).unwrap();
+//! ```
+use crate::formats::Xgid;
+use crate::game::{Game, Phase};
+use crate::r#match::Match;
+use crate::rules::{MatchRules, Player, Roll};
+use crate::Error;
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::{accept_with_config, HandshakeError, Message};
+
+/// The largest message accepted from a client
+const MAX_MESSAGE: usize = 64 * 1024;
+
+/// How long a connection waits for a message of its client before sending the messages queued
+/// for it
+const POLL: Duration = Duration::from_millis(20);
+
+/// The reason why a command of a client is rejected
+#[derive(Debug)]
+struct Rejection(String);
+
+impl From<Error> for Rejection {
+    fn from(e: Error) -> Self {
+        Rejection(e.to_string())
+    }
+}
+
+impl From<&str> for Rejection {
+    fn from(reason: &str) -> Self {
+        Rejection(reason.to_string())
+    }
+}
+
+/// Holds a match together with its players and watchers
+#[derive(Debug)]
+struct Table {
+    /// The match played
+    game: Match,
+    /// The clients playing X and O
+    players: (Option<u64>, Option<u64>),
+    /// The clients watching
+    watchers: Vec<u64>,
+}
+
+impl Table {
+    /// Returns the clients to inform about a change of the table
+    fn audience(&self) -> Vec<u64> {
+        let mut clients: Vec<u64> = [self.players.0, self.players.1]
+            .into_iter()
+            .flatten()
+            .collect();
+        clients.extend(&self.watchers);
+        clients
+    }
+
+    /// Returns the player a client plays at this table, if any
+    fn player(&self, client: u64) -> Option<Player> {
+        match self.players {
+            (Some(c), _) if c == client => Some(Player::Player0),
+            (_, Some(c)) if c == client => Some(Player::Player1),
+            _ => None,
+        }
+    }
+}
+
+/// Holds the tables of the server and the clients sitting at them
+#[derive(Debug, Default)]
+pub struct Server {
+    /// The tables by their ids
+    tables: BTreeMap<u64, Table>,
+    /// The table each client plays at or watches
+    clients: HashMap<u64, u64>,
+    /// The id of the next table created
+    next_table: u64,
+}
+
+impl Server {
+    /// Create a server without any tables
+    pub fn new() -> Self {
+        Server::default()
+    }
+
+    /// Returns the ids of the tables, e.g. to list them to clients
+    pub fn tables(&self) -> Vec<u64> {
+        self.tables.keys().copied().collect()
+    }
+
+    /// Returns the match played at a table
+    pub fn table(&self, id: u64) -> Option<&Match> {
+        self.tables.get(&id).map(|t| &t.game)
+    }
+
+    /// Handle a command of a client and return the messages to send, with the clients to send
+    /// them to.
+    /// ```
+    /// use backgammon::server::Server;
+    ///
+    /// let mut server = Server::new();
+    /// assert_eq!(server.handle(1, "create 3"), vec![(1, "created 1".to_string())]);
+    /// let messages = server.handle(2, "join 1");
+    /// assert_eq!(messages[0], (1, "joined 1".to_string()));
+    /// assert_eq!(messages[1], (2, "joined 1".to_string()));
+    /// assert!(messages[2].1.starts_with("state 1 XGID="));
+    /// ```
+    pub fn handle(&mut self, client: u64, command: &str) -> Vec<(u64, String)> {
+        match self.execute(client, command.trim()) {
+            Ok(messages) => messages,
+            Err(Rejection(e)) => vec![(client, format!("error {}", e))],
+        }
+    }
+
+    /// Remove a client which has disconnected, and return the messages to send to the clients
+    /// left at its table
+    pub fn disconnect(&mut self, client: u64) -> Vec<(u64, String)> {
+        let Some(id) = self.clients.remove(&client) else {
+            return Vec::new();
+        };
+        let Some(table) = self.tables.get_mut(&id) else {
+            return Vec::new();
+        };
+        let player = table.player(client);
+        match player {
+            Some(Player::Player0) => table.players.0 = None,
+            Some(_) => table.players.1 = None,
+            None => table.watchers.retain(|c| *c != client),
+        }
+        let audience = table.audience();
+        if table.players == (None, None) {
+            for watcher in audience {
+                let _ = self.clients.remove(&watcher);
+            }
+            let _ = self.tables.remove(&id);
+            return Vec::new();
+        }
+        match player {
+            Some(player) => broadcast(&audience, format!("left {}", mark(player))),
+            None => Vec::new(),
+        }
+    }
+
+    /// Execute a command of a client
+    fn execute(&mut self, client: u64, command: &str) -> Result<Vec<(u64, String)>, Rejection> {
+        let (verb, argument) = command.split_once(' ').unwrap_or((command, ""));
+        let argument = argument.trim();
+        let number = || argument.parse::<u64>().map_err(|_| Error::NotationInvalid);
+        match verb {
+            "create" => {
+                self.check_free(client)?;
+                let points = match argument {
+                    "" => 1,
+                    _ => u32::try_from(number()?).map_err(|_| Error::NotationInvalid)?,
+                };
+                let mut game = Match::new().with_points(points);
+                let _ = game.new_game()?;
+                self.next_table += 1;
+                let id = self.next_table;
+                let table = Table {
+                    game,
+                    players: (Some(client), None),
+                    watchers: Vec::new(),
+                };
+                let _ = self.tables.insert(id, table);
+                let _ = self.clients.insert(client, id);
+                Ok(vec![(client, format!("created {}", id))])
+            }
+            "join" | "watch" => {
+                self.check_free(client)?;
+                let id = number()?;
+                let table = self.tables.get_mut(&id).ok_or("Unknown table")?;
+                if verb == "join" {
+                    if table.players.1.is_some() {
+                        return Err("Table is full".into());
+                    }
+                    table.players.1 = Some(client);
+                } else {
+                    table.watchers.push(client);
+                }
+                let _ = self.clients.insert(client, id);
+                let mut messages = match verb {
+                    "join" => broadcast(&table.audience(), format!("joined {}", id)),
+                    _ => Vec::new(),
+                };
+                messages.extend(self.state(id, client, verb == "watch"));
+                Ok(messages)
+            }
+            _ => self.act(client, verb, argument),
+        }
+    }
+
+    /// Take the action of a player at a table
+    fn act(
+        &mut self,
+        client: u64,
+        verb: &str,
+        argument: &str,
+    ) -> Result<Vec<(u64, String)>, Rejection> {
+        let id = *self.clients.get(&client).ok_or("Not at a table")?;
+        let table = self.tables.get_mut(&id).ok_or("Unknown table")?;
+        let player = table.player(client).ok_or(Error::PlayerInvalid)?;
+        if table.players.1.is_none() {
+            return Err("Waiting for an opponent".into());
+        }
+        let audience = table.audience();
+        let game = table.game.game().ok_or(Error::GameEnded)?;
+        // the opening roll is rolled by any player
+        let deciding = match game.phase {
+            Phase::AwaitingCubeDecision => game.who_plays.other(),
+            Phase::AwaitingRoll if game.who_plays == Player::Nobody => player,
+            _ => game.who_plays,
+        };
+        if deciding != player {
+            return Err(Error::NotYourTurn.into());
+        }
+        let event = match verb {
+            "roll" => {
+                let _ = game.roll()?;
+                let (a, b) = game.dices.values;
+                format!("roll {} {} {}", mark(game.who_plays), a, b)
+            }
+            "move" => {
+                let play = game.parse_play(argument)?;
+                game.apply_play(&play)?;
+                format!("move {} {}", mark(player), play)
+            }
+            "double" => {
+                game.offer_double(player)?;
+                format!("double {}", mark(player))
+            }
+            "take" => {
+                game.take(player)?;
+                format!("take {}", mark(player))
+            }
+            "pass" => {
+                game.pass(player)?;
+                format!("pass {}", mark(player))
+            }
+            _ => return Err(Error::NotationInvalid.into()),
+        };
+
+        let mut messages = broadcast(&audience, event);
+        if let Some(result) = game.result() {
+            let message = format!("game {} {}", mark(result.winner), result.points);
+            messages.extend(broadcast(&audience, message));
+            match table.game.winner() {
+                Some(winner) => {
+                    messages.extend(broadcast(&audience, format!("match {}", mark(winner))))
+                }
+                None => {
+                    let _ = table.game.new_game()?;
+                }
+            }
+        }
+        messages.extend(self.state(id, client, false));
+        Ok(messages)
+    }
+
+    /// Fail if a client already plays at or watches a table
+    fn check_free(&self, client: u64) -> Result<(), Rejection> {
+        match self.clients.contains_key(&client) {
+            true => Err("Already at a table".into()),
+            false => Ok(()),
+        }
+    }
+
+    /// Returns the state of a table, for a single client or for everyone at the table
+    fn state(&mut self, id: u64, client: u64, single: bool) -> Vec<(u64, String)> {
+        let Some(table) = self.tables.get_mut(&id) else {
+            return Vec::new();
+        };
+        let points = table.game.rules.points;
+        let (a, b) = table.game.score();
+        let score = (
+            u32::try_from(a).unwrap_or(u32::MAX),
+            u32::try_from(b).unwrap_or(u32::MAX),
+        );
+        let audience = table.audience();
+        let Some(game) = table.game.game() else {
+            return Vec::new();
+        };
+        let message = format!("state {} {}", id, xgid(game, points, score));
+        match single {
+            true => vec![(client, message)],
+            false => broadcast(&audience, message),
+        }
+    }
+}
+
+/// Returns the XGID of a game of a match
+fn xgid(game: &Game, points: u32, score: (u32, u32)) -> Xgid {
+    Xgid::from_game(game, points, score)
+}
+
+/// Returns the same message for all clients
+fn broadcast(clients: &[u64], message: String) -> Vec<(u64, String)> {
+    clients.iter().map(|c| (*c, message.clone())).collect()
+}
+
+/// Returns the mark of a player's checkers
+fn mark(player: Player) -> &'static str {
+    match player {
+        Player::Player0 => "X",
+        Player::Player1 => "O",
+        Player::Nobody => "-",
+    }
+}
+
+/// The queues of the messages to the clients connected, by their ids
+type Connections = Arc<Mutex<HashMap<u64, Sender<String>>>>;
+
+/// Queue messages to the clients connected
+fn send(connections: &Connections, messages: Vec<(u64, String)>) {
+    let connections = connections.lock().unwrap_or_else(PoisonError::into_inner);
+    for (client, message) in messages {
+        if let Some(sender) = connections.get(&client) {
+            // a failing client is removed by its own thread
+            let _ = sender.send(message);
+        }
+    }
+}
+
+/// Returns the I/O error of a WebSocket error, or the WebSocket error as invalid data
+fn io_error(e: tungstenite::Error) -> io::Error {
+    match e {
+        tungstenite::Error::Io(e) => e,
+        e => io::Error::new(ErrorKind::InvalidData, e),
+    }
+}
+
+/// Serve a single client until it disconnects
+fn connection(
+    stream: TcpStream,
+    client: u64,
+    server: &Mutex<Server>,
+    connections: &Connections,
+) -> io::Result<()> {
+    let config = WebSocketConfig::default().max_message_size(Some(MAX_MESSAGE));
+    let mut socket = accept_with_config(stream, Some(config)).map_err(|e| match e {
+        HandshakeError::Failure(e) => io_error(e),
+        HandshakeError::Interrupted(_) => ErrorKind::WouldBlock.into(),
+    })?;
+    socket.get_ref().set_read_timeout(Some(POLL))?;
+    let (sender, receiver) = mpsc::channel();
+    let _ = connections
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(client, sender);
+
+    let result = loop {
+        match socket.read() {
+            Ok(Message::Text(command)) => {
+                let messages = server
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .handle(client, command.as_str());
+                send(connections, messages);
+            }
+            Ok(Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed) => break Ok(()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => break Err(io_error(e)),
+        }
+        if let Some(e) = receiver
+            .try_iter()
+            .find_map(|message| socket.send(Message::text(message)).err())
+        {
+            break Err(io_error(e));
+        }
+    };
+
+    let _ = connections
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&client);
+    let _ = socket.close(None);
+    let messages = server
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .disconnect(client);
+    send(connections, messages);
+    result
+}
+
+/// Serve clients connecting to a listener over WebSocket, with one thread per connection, until
+/// accepting a connection fails.
+pub fn serve(listener: TcpListener, server: Server) -> io::Result<()> {
+    let server = Arc::new(Mutex::new(server));
+    let connections: Connections = Arc::default();
+    for (client, stream) in (1..).zip(listener.incoming()) {
+        let stream = stream?;
+        let server = Arc::clone(&server);
+        let connections = Arc::clone(&connections);
+        let _ = thread::spawn(move || connection(stream, client, &server, &connections));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the messages sent to a client
+    fn to(messages: &[(u64, String)], client: u64) -> Vec<&str> {
+        messages
+            .iter()
+            .filter(|(c, _)| *c == client)
+            .map(|(_, m)| m.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_tables() {
+        let mut server = Server::new();
+        assert_eq!(
+            server.handle(1, "roll"),
+            vec![(1, "error Not at a table".to_string())]
+        );
+        assert_eq!(
+            server.handle(1, "create 3"),
+            vec![(1, "created 1".to_string())]
+        );
+        assert_eq!(
+            server.handle(1, "create"),
+            vec![(1, "error Already at a table".to_string())]
+        );
+        assert_eq!(
+            server.handle(1, "roll"),
+            vec![(1, "error Waiting for an opponent".to_string())]
+        );
+        assert_eq!(
+            server.handle(2, "join 7"),
+            vec![(2, "error Unknown table".to_string())]
+        );
+        let messages = server.handle(2, "join 1");
+        assert_eq!(to(&messages, 1).len(), 2);
+        assert_eq!(to(&messages, 2).len(), 2);
+        assert_eq!(
+            server.handle(3, "join 1"),
+            vec![(3, "error Table is full".to_string())]
+        );
+        let messages = server.handle(3, "watch 1");
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].1.starts_with("state 1 XGID="));
+        assert_eq!(server.tables(), vec![1]);
+        assert_eq!(server.table(1).map(|m| m.rules.points), Some(3));
+
+        // the watcher cannot play, and leaving is announced to the others
+        assert!(to(&server.handle(3, "roll"), 3)[0].starts_with("error"));
+        let messages = server.disconnect(1);
+        assert_eq!(to(&messages, 2), vec!["left X"]);
+        assert_eq!(to(&messages, 3), vec!["left X"]);
+        assert!(server.disconnect(2).is_empty());
+        assert!(server.tables().is_empty());
+        assert_eq!(
+            server.handle(3, "create"),
+            vec![(3, "created 2".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_play() -> Result<(), Error> {
+        let mut server = Server::new();
+        let _ = server.handle(1, "create 1");
+        let _ = server.handle(2, "join 1");
+        let _ = server.handle(1, "roll");
+        let game = server
+            .table(1)
+            .and_then(|m| m.games.last())
+            .ok_or(Error::GameEnded)?;
+        let (player, other) = match game.who_plays {
+            Player::Player0 => (1, 2),
+            Player::Player1 => (2, 1),
+            Player::Nobody => return Ok(()),
+        };
+        let plays = game.legal_plays();
+        let play = plays[0].1.to_string();
+
+        assert!(to(&server.handle(other, "double"), other)[0].starts_with("error"));
+        assert!(to(&server.handle(player, "move 24/1"), player)[0].starts_with("error"));
+        let messages = server.handle(player, &format!("move {}", play));
+        let expected = format!("move {} {}", if player == 1 { "X" } else { "O" }, play);
+        assert_eq!(to(&messages, other)[0], expected);
+        assert!(to(&messages, other)[1].starts_with("state 1 XGID="));
+
+        // the cube is dead in a match to 1 point
+        let messages = server.handle(other, "double");
+        assert!(to(&messages, other)[0].starts_with("error"));
+        let messages = server.handle(other, "roll");
+        assert!(to(&messages, player)[0].starts_with("roll"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_end() {
+        let mut server = Server::new();
+        let _ = server.handle(1, "create 0");
+        let _ = server.handle(2, "join 1");
+        let _ = server.handle(1, "roll");
+        let game = server.tables.get_mut(&1).and_then(|t| t.game.game());
+        let Some(game) = game else {
+            return;
+        };
+        // the player on roll after the opening roll passes a double of the opponent, an equal
+        // opening roll is rolled again
+        game.phase = Phase::AwaitingRoll;
+        game.who_plays = match game.who_plays {
+            Player::Nobody => Player::Player0,
+            player => player.other(),
+        };
+        let (doubler, passer) = match game.who_plays {
+            Player::Player0 => (1, 2),
+            _ => (2, 1),
+        };
+        let _ = server.handle(doubler, "double");
+        let messages = server.handle(passer, "pass");
+        let events = to(&messages, doubler);
+        assert!(events[0].starts_with("pass"));
+        assert!(events[1].starts_with("game"));
+        assert!(events[2].starts_with("state 1 XGID="));
+        // an unlimited match goes on with the next game
+        assert_eq!(server.table(1).map(|m| m.games.len()), Some(2));
+    }
+
+    #[test]
+    fn test_serve() -> Result<(), Box<dyn std::error::Error>> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let _ = thread::spawn(move || serve(listener, Server::new()));
+
+        let (mut x, _) = tungstenite::connect(&url)?;
+        x.send(Message::text("create 3"))?;
+        assert_eq!(x.read()?, Message::text("created 1"));
+        let (mut o, _) = tungstenite::connect(&url)?;
+        o.send(Message::text("join 1"))?;
+        for socket in [&mut x, &mut o] {
+            assert_eq!(socket.read()?, Message::text("joined 1"));
+            assert!(socket.read()?.to_text()?.starts_with("state 1 XGID="));
+        }
+
+        // messages larger than the limit close the connection
+        o.send(Message::text("x".repeat(MAX_MESSAGE + 1)))?;
+        assert!(!matches!(o.read(), Ok(Message::Text(_))));
+        assert_eq!(x.read()?, Message::text("left O"));
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use crate::game::{share, Game, Phase, SharedDiceSource};
+use crate::r#match::score;
+use crate::rules::{DiceSource, GameRules, Player, Rules, Variant};
+use crate::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Represents a session of Backgammon money games
+///
+/// In contrast to a [`Match`](crate::Match), a session has no points to reach. Any number of
+/// games can be played, and the points won are converted to money with a stake per point until
+/// the session is settled. The [`jacoby`](Rules::jacoby) rule is active by default, and beaver and
+/// raccoon can be enabled.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Session {
+    /// The rules set for the games of the session
+    pub rules: Rules,
+    /// The games played in the session
+    pub games: Vec<Game>,
+    /// The money won or lost per point
+    pub stake: u64,
+    /// Whether the session has been settled and ended
+    settled: bool,
+    /// The source of the dices rolled in all games, random dices if none
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dice_source: Option<SharedDiceSource>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session {
+            rules: Rules::default().with_jacoby(),
+            games: Vec::new(),
+            stake: 1,
+            settled: false,
+            dice_source: None,
+        }
+    }
+}
+
+// implement Display trait
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let score = self.score();
+        write!(
+            f,
+            "Session with stake {} per point, Games: {}, Score: {}-{}, Balance: {}",
+            self.stake,
+            self.games.len(),
+            score.0,
+            score.1,
+            self.balance()
+        )
+    }
+}
+
+/// Implements methods for the Session struct
+impl Session {
+    /// Start a new session of money games:
+    /// ```
+    /// use backgammon::Session;
+    ///
+    /// let s = Session::new().with_stake(5);
+    /// assert_eq!(s.stake, 5);
+    /// assert!(s.rules.jacoby);
+    /// ```
+    pub fn new() -> Self {
+        Session::default()
+    }
+
+    /// Set the money won or lost per point
+    pub fn with_stake(mut self, stake: u64) -> Self {
+        self.stake = stake;
+        self
+    }
+
+    /// Roll the dices of all games of the session from the given source, see
+    /// [`DiceSource`]
+    pub fn with_dice_source(mut self, source: impl DiceSource + 'static) -> Self {
+        self.dice_source = Some(share(source));
+        self
+    }
+
+    /// Start the next game of the session, once the previous game has ended.
+    pub fn new_game(&mut self) -> Result<&mut Game, Error> {
+        if self.settled {
+            return Err(Error::SessionEnded);
+        }
+        if self.game().is_some_and(|g| g.phase != Phase::GameOver) {
+            return Err(Error::GameStarted);
+        }
+        let mut game = Game::new();
+        game.rules = self.rules;
+        game.set_start();
+        game.set_dice_source(self.dice_source.clone());
+        self.games.push(game);
+        self.game().ok_or(Error::GameEnded)
+    }
+
+    /// Returns the game currently played, i.e. the last game started
+    pub fn game(&mut self) -> Option<&mut Game> {
+        self.games.last_mut()
+    }
+
+    /// Returns the points won so far by player 0 and player 1, counting all finished games
+    pub fn score(&self) -> (u64, u64) {
+        score(&self.games)
+    }
+
+    /// Returns the money won by player 0, which is negative if player 0 lost money to player 1
+    pub fn balance(&self) -> i128 {
+        let score = self.score();
+        (i128::from(score.0) - i128::from(score.1)) * i128::from(self.stake)
+    }
+
+    /// Settle and end the session, returning the money player 1 owes player 0, see
+    /// [`balance`](Session::balance). A game which has not ended yet is not counted.
+    /// ```
+    /// use backgammon::{Session, Victory};
+    /// use backgammon::rules::Player;
+    ///
+    /// let mut s = Session::new().with_stake(10);
+    /// s.new_game().unwrap().resign(Player::Player0, Victory::Gammon).unwrap();
+    /// // with the Jacoby rule, the gammon counts as a single game
+    /// assert_eq!(s.settle().unwrap(), -10);
+    /// assert!(s.new_game().is_err());
+    /// ```
+    pub fn settle(&mut self) -> Result<i128, Error> {
+        if self.settled {
+            return Err(Error::SessionEnded);
+        }
+        self.settled = true;
+        Ok(self.balance())
+    }
+
+    /// Returns true once the session has been settled
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+
+    /// Returns the player who is ahead in money, or nobody if the session is even
+    pub fn leader(&self) -> Player {
+        match self.balance() {
+            b if b > 0 => Player::Player0,
+            b if b < 0 => Player::Player1,
+            _ => Player::Nobody,
+        }
+    }
+}
+
+/// Implements SetRules for Session
+impl GameRules for Session {
+    fn with_beaver(mut self) -> Self {
+        self.rules.beaver = true;
+        self
+    }
+
+    fn with_raccoon(mut self) -> Self {
+        self.rules.raccoon = true;
+        self
+    }
+
+    fn with_murphy(mut self, limit: u8) -> Self {
+        self.rules.murphy = true;
+        self.rules.murphy_limit = limit;
+        self
+    }
+
+    fn with_jacoby(mut self) -> Self {
+        self.rules.jacoby = true;
+        self
+    }
+
+    fn with_holland(mut self) -> Self {
+        self.rules.holland = true;
+        self
+    }
+
+    fn with_variant(mut self, variant: Variant) -> Self {
+        self.rules.variant = variant;
+        self
+    }
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Victory;
+
+    #[test]
+    fn test_new_session() {
+        let s = Session::new();
+        assert_eq!(s.stake, 1);
+        assert!(s.rules.jacoby);
+        assert_eq!(s.games.len(), 0);
+        assert!(!s.is_settled());
+        assert_eq!(s.leader(), Player::Nobody);
+    }
+
+    #[test]
+    fn test_play_session() -> Result<(), Error> {
+        let mut s = Session::new().with_stake(3).with_beaver();
+        let g = s.new_game()?;
+        g.who_plays = Player::Player0;
+        g.offer_double(Player::Player0)?;
+        g.beaver(Player::Player1)?;
+        assert!(matches!(s.new_game(), Err(Error::GameStarted)));
+        s.game()
+            .ok_or(Error::GameEnded)?
+            .resign(Player::Player0, Victory::Single)?;
+        assert_eq!(s.score(), (0, 4));
+
+        // gammons count once without a turned cube
+        s.new_game()?.resign(Player::Player1, Victory::Gammon)?;
+        assert_eq!(s.score(), (1, 4));
+        assert_eq!(s.balance(), -9);
+        assert_eq!(s.leader(), Player::Player1);
+
+        let _ = s.new_game()?;
+        assert_eq!(s.settle()?, -9);
+        assert!(s.is_settled());
+        assert!(matches!(s.settle(), Err(Error::SessionEnded)));
+        assert!(matches!(s.new_game(), Err(Error::SessionEnded)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> Result<(), Error> {
+        let mut s = Session::new().with_stake(2);
+        s.new_game()?.resign(Player::Player1, Victory::Single)?;
+        assert_eq!(
+            s.to_string(),
+            "Session with stake 2 per point, Games: 1, Score: 1-0, Balance: 2"
+        );
+        Ok(())
+    }
+}
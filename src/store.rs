@@ -0,0 +1,236 @@
+//! # Archives of Matches
+//!
+//! Keeps played matches for later analysis or replay. A [`MatchStore`](crate::store::MatchStore)
+//! saves a match together with the names of its players and the date it was played, and finds it
+//! again by its id, its players, or its date. Matches are stored in the versioned encoding of
+//! [`Match::to_bytes`], hence a loaded match has its games, records, and clock, but neither a dice
+//! source nor observers.
+//!
+//! [`MemoryStore`](crate::store::MemoryStore) keeps the matches as long as the process runs. With
+//! the `sqlite` feature, `sqlite::SqliteStore` keeps them in a SQLite database.
+//! ```
+//! use backgammon::Match;
+//! use backgammon::store::{MatchStore, MemoryStore, Query};
+//!
+//! let mut store = MemoryStore::new();
+//! let id = store.save(("Alice", "Bob"), 1_700_000_000, &Match::new()).unwrap();
+//! let _ = store.save(("Carol", "Dave"), 1_700_086_400, &Match::new()).unwrap();
+//!
+//! let found = store.query(&Query::new().with_player("Bob")).unwrap();
+//! assert_eq!(found[0].id, id);
+//! assert_eq!(store.load(id).unwrap().games.len(), 0);
+//! ```
+use crate::Match;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::io;
+
+/// Stores matches in SQLite databases
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// Describes a match kept in a [`MatchStore`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Entry {
+    /// The id assigned by the store
+    pub id: u64,
+    /// The names of player 0 and player 1
+    pub players: (String, String),
+    /// The date the match was played, in seconds since the Unix epoch
+    pub date: u64,
+}
+
+/// Selects matches of a [`MatchStore`]. All conditions set have to be met, and the default query
+/// selects all matches.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Query {
+    /// The name of a player of the match
+    pub player: Option<String>,
+    /// The earliest date, in seconds since the Unix epoch
+    pub since: Option<u64>,
+    /// The date before which the match has been played, in seconds since the Unix epoch
+    pub until: Option<u64>,
+}
+
+impl Query {
+    /// Create a query selecting all matches
+    pub fn new() -> Self {
+        Query::default()
+    }
+
+    /// Select the matches of a player, be it as player 0 or player 1
+    pub fn with_player(mut self, player: &str) -> Self {
+        self.player = Some(player.to_string());
+        self
+    }
+
+    /// Select the matches played at or after a date
+    pub fn with_since(mut self, date: u64) -> Self {
+        self.since = Some(date);
+        self
+    }
+
+    /// Select the matches played before a date
+    pub fn with_until(mut self, date: u64) -> Self {
+        self.until = Some(date);
+        self
+    }
+
+    /// Returns whether a match meets all conditions of the query
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.player
+            .as_ref()
+            .is_none_or(|p| *p == entry.players.0 || *p == entry.players.1)
+            && self.since.is_none_or(|since| entry.date >= since)
+            && self.until.is_none_or(|until| entry.date < until)
+    }
+}
+
+/// Saves matches and finds them again, e.g. in memory or in a database
+pub trait MatchStore {
+    /// Save a match of two players, player 0 first, played at a date in seconds since the Unix
+    /// epoch. Returns the id of the match in the store.
+    fn save(&mut self, players: (&str, &str), date: u64, m: &Match) -> io::Result<u64>;
+
+    /// Load a match by its id. Unknown ids fail with [`io::ErrorKind::NotFound`], and matches
+    /// which cannot be decoded with [`io::ErrorKind::InvalidData`].
+    fn load(&self, id: u64) -> io::Result<Match>;
+
+    /// Returns the matches selected by the query, ordered by date and then by id
+    fn query(&self, query: &Query) -> io::Result<Vec<Entry>>;
+
+    /// Returns all matches, ordered by date and then by id
+    fn list(&self) -> io::Result<Vec<Entry>> {
+        self.query(&Query::new())
+    }
+}
+
+/// Returns the error of a match which cannot be decoded
+fn invalid(e: crate::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Returns the error of an unknown id
+fn not_found(id: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("no match with id {}", id))
+}
+
+/// Keeps matches in memory, numbering them from 1
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    /// The matches and their encodings by their ids
+    matches: BTreeMap<u64, (Entry, Vec<u8>)>,
+}
+
+impl MemoryStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    /// Returns the number of matches kept
+    pub fn len(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Returns whether no match is kept
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+}
+
+impl MatchStore for MemoryStore {
+    fn save(&mut self, players: (&str, &str), date: u64, m: &Match) -> io::Result<u64> {
+        let id = self.matches.keys().next_back().map_or(1, |id| id + 1);
+        let entry = Entry {
+            id,
+            players: (players.0.to_string(), players.1.to_string()),
+            date,
+        };
+        let _ = self.matches.insert(id, (entry, m.to_bytes()));
+        Ok(id)
+    }
+
+    fn load(&self, id: u64) -> io::Result<Match> {
+        let (_, bytes) = self.matches.get(&id).ok_or_else(|| not_found(id))?;
+        Match::from_bytes(bytes).map_err(invalid)
+    }
+
+    fn query(&self, query: &Query) -> io::Result<Vec<Entry>> {
+        let mut entries = self
+            .matches
+            .values()
+            .map(|(entry, _)| entry)
+            .filter(|entry| query.matches(entry))
+            .cloned()
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| (entry.date, entry.id));
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{MatchRules, Player};
+    use crate::Victory;
+
+    #[test]
+    fn test_query() {
+        let entry = Entry {
+            id: 1,
+            players: ("Alice".to_string(), "Bob".to_string()),
+            date: 100,
+        };
+        assert!(Query::new().matches(&entry));
+        assert!(Query::new().with_player("Alice").matches(&entry));
+        assert!(Query::new().with_player("Bob").matches(&entry));
+        assert!(!Query::new().with_player("Carol").matches(&entry));
+        assert!(Query::new().with_since(100).with_until(101).matches(&entry));
+        assert!(!Query::new().with_since(101).matches(&entry));
+        assert!(!Query::new().with_until(100).matches(&entry));
+    }
+
+    #[test]
+    fn test_memory_store() -> io::Result<()> {
+        let mut store = MemoryStore::new();
+        assert!(store.is_empty());
+        assert_eq!(
+            store.load(1).map(|_| ()).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        let mut m = Match::new().with_points(3);
+        m.new_game()
+            .map_err(invalid)?
+            .resign(Player::Player1, Victory::Gammon)
+            .map_err(invalid)?;
+        let first = store.save(("Alice", "Bob"), 300, &m)?;
+        let second = store.save(("Carol", "Alice"), 200, &Match::new())?;
+        let third = store.save(("Bob", "Carol"), 300, &Match::new())?;
+        assert_eq!((first, second, third), (1, 2, 3));
+        assert_eq!(store.len(), 3);
+
+        let loaded = store.load(first)?;
+        assert_eq!(loaded.rules.points, 3);
+        assert_eq!(loaded.score(), m.score());
+
+        let ids = |entries: Vec<Entry>| entries.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(store.list()?), vec![2, 1, 3]);
+        assert_eq!(
+            ids(store.query(&Query::new().with_player("Alice"))?),
+            vec![2, 1]
+        );
+        assert_eq!(
+            ids(store.query(&Query::new().with_player("Carol").with_since(250))?),
+            vec![3]
+        );
+        assert_eq!(ids(store.query(&Query::new().with_until(300))?), vec![2]);
+        Ok(())
+    }
+}
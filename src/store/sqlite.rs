@@ -0,0 +1,174 @@
+//! # Archive of Matches in SQLite
+use super::{invalid, not_found, Entry, MatchStore, Query};
+use crate::Match;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::io;
+use std::path::Path;
+
+/// Creates the table of matches unless it exists already
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS matches (
+    id INTEGER PRIMARY KEY,
+    player0 TEXT NOT NULL,
+    player1 TEXT NOT NULL,
+    date INTEGER NOT NULL,
+    data BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS matches_date ON matches (date, id);";
+
+/// Returns the error of a failed statement
+fn failed(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Returns a date as an integer of SQLite
+fn timestamp(date: u64) -> io::Result<i64> {
+    i64::try_from(date).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Keeps matches in a SQLite database, in the table `matches`
+/// ```
+/// use backgammon::Match;
+/// use backgammon::store::{MatchStore, Query};
+/// use backgammon::store::sqlite::SqliteStore;
+///
+/// let mut store = SqliteStore::open_in_memory().unwrap();
+/// let id = store.save(("Alice", "Bob"), 1_700_000_000, &Match::new()).unwrap();
+/// assert_eq!(store.list().unwrap()[0].id, id);
+/// assert!(store.query(&Query::new().with_since(1_800_000_000)).unwrap().is_empty());
+/// ```
+#[derive(Debug)]
+pub struct SqliteStore {
+    /// The connection to the database
+    connection: Connection,
+}
+
+impl SqliteStore {
+    /// Open the database in a file, creating the file and the table of matches if necessary
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        SqliteStore::with_connection(Connection::open(path).map_err(failed)?)
+    }
+
+    /// Open a database kept in memory, e.g. for tests
+    pub fn open_in_memory() -> io::Result<Self> {
+        SqliteStore::with_connection(Connection::open_in_memory().map_err(failed)?)
+    }
+
+    /// Use a connection opened already, creating the table of matches if necessary
+    pub fn with_connection(connection: Connection) -> io::Result<Self> {
+        connection.execute_batch(SCHEMA).map_err(failed)?;
+        Ok(SqliteStore { connection })
+    }
+}
+
+impl MatchStore for SqliteStore {
+    fn save(&mut self, players: (&str, &str), date: u64, m: &Match) -> io::Result<u64> {
+        let _ = self
+            .connection
+            .execute(
+                "INSERT INTO matches (player0, player1, date, data) VALUES (?1, ?2, ?3, ?4)",
+                params![players.0, players.1, timestamp(date)?, m.to_bytes()],
+            )
+            .map_err(failed)?;
+        // rows inserted into a table with an integer primary key start at 1
+        Ok(self.connection.last_insert_rowid() as u64)
+    }
+
+    fn load(&self, id: u64) -> io::Result<Match> {
+        let row = i64::try_from(id).map_err(|_| not_found(id))?;
+        let bytes: Vec<u8> = self
+            .connection
+            .query_row(
+                "SELECT data FROM matches WHERE id = ?1",
+                params![row],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(failed)?
+            .ok_or_else(|| not_found(id))?;
+        Match::from_bytes(&bytes).map_err(invalid)
+    }
+
+    fn query(&self, query: &Query) -> io::Result<Vec<Entry>> {
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "SELECT id, player0, player1, date FROM matches
+                 WHERE (?1 IS NULL OR player0 = ?1 OR player1 = ?1)
+                 AND (?2 IS NULL OR date >= ?2)
+                 AND (?3 IS NULL OR date < ?3)
+                 ORDER BY date, id",
+            )
+            .map_err(failed)?;
+        let since = query.since.map(timestamp).transpose()?;
+        let until = query.until.map(timestamp).transpose()?;
+        let rows = statement
+            .query_map(params![query.player, since, until], |row| {
+                Ok(Entry {
+                    id: row.get::<_, i64>(0)? as u64,
+                    players: (row.get(1)?, row.get(2)?),
+                    date: row.get::<_, i64>(3)? as u64,
+                })
+            })
+            .map_err(failed)?;
+        rows.collect::<Result<_, _>>().map_err(failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{MatchRules, Player};
+    use crate::Victory;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_sqlite_store() -> io::Result<()> {
+        let path = env::temp_dir().join(format!("backgammon-store-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut m = Match::new().with_points(5);
+        m.new_game()
+            .map_err(invalid)?
+            .resign(Player::Player0, Victory::Single)
+            .map_err(invalid)?;
+        {
+            let mut store = SqliteStore::open(&path)?;
+            assert_eq!(store.save(("Alice", "Bob"), 300, &m)?, 1);
+            assert_eq!(store.save(("Carol", "Alice"), 200, &Match::new())?, 2);
+        }
+
+        // the matches outlive the connection
+        let mut store = SqliteStore::open(&path)?;
+        assert_eq!(store.save(("Bob", "Carol"), 300, &Match::new())?, 3);
+        let loaded = store.load(1)?;
+        assert_eq!(loaded.rules.points, 5);
+        assert_eq!(loaded.score(), (0, 1));
+        assert_eq!(
+            store.load(4).map(|_| ()).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        let ids = |entries: Vec<Entry>| entries.iter().map(|e| e.id).collect::<Vec<_>>();
+        let list = store.list()?;
+        assert_eq!(ids(list.clone()), vec![2, 1, 3]);
+        assert_eq!(list[1].players, ("Alice".to_string(), "Bob".to_string()));
+        assert_eq!(list[1].date, 300);
+        assert_eq!(
+            ids(store.query(&Query::new().with_player("Carol").with_since(250))?),
+            vec![3]
+        );
+        assert_eq!(ids(store.query(&Query::new().with_until(300))?), vec![2]);
+        assert_eq!(
+            store
+                .query(&Query::new().with_since(u64::MAX))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        drop(store);
+        fs::remove_file(&path)
+    }
+}
@@ -0,0 +1,288 @@
+//! # Terminal Rendering
+//!
+//! Renders a game for terminals with Unicode box drawing characters and ANSI colors, so that
+//! frontends do not have to reinvent the board. The layout is the one of
+//! [`Game::render_ascii`](crate::Game::render_ascii): the board from the point-of-view of player
+//! 0, who plays the checkers `●` from point 24 to point 1, and the checkers `○` of player 1.
+//!
+//! A checker selected by the player on turn is highlighted together with the fields it can be
+//! moved to with the dices left.
+//! ```
+//! use backgammon::Game;
+//! use backgammon::rules::Field;
+//! use backgammon::term::Renderer;
+//!
+//! let mut g = Game::new();
+//! g.roll_with((3, 1)).unwrap();
+//! let board = Renderer::new().without_colors().render(&g);
+//! assert!(board.starts_with(" ┌13─14─15─16─17─18─┬───┬19─20─21─22─23─24─┐"));
+//!
+//! // the checkers on the 8 point can move to the 7 point and the 5 point
+//! let board = Renderer::new().with_selected(Field::Point(7)).render(&g);
+//! assert!(board.contains("\x1b[1;42m 7\x1b[0m"));
+//! assert!(board.contains("\x1b[1;42m 5\x1b[0m"));
+//! ```
+use crate::game::{Game, DIAGRAM_POINTS};
+use crate::rules::{Field, Player, Point, RelativePoint};
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// ANSI sequence for the checkers of player 0
+const PLAYER0: &str = "\x1b[1;31m";
+/// ANSI sequence for the checkers of player 1
+const PLAYER1: &str = "\x1b[1;34m";
+/// ANSI sequence for the field of the selected checker
+const SELECTED: &str = "\x1b[1;43m";
+/// ANSI sequence for the fields the selected checker can be moved to
+const DESTINATION: &str = "\x1b[1;42m";
+/// ANSI sequence to reset the style
+const RESET: &str = "\x1b[0m";
+
+/// Renders games for terminals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Renderer {
+    /// Whether to color the output with ANSI escape sequences
+    pub colors: bool,
+    /// The field of the checker selected by the player on turn, seen by that player
+    pub selected: Option<Field>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer {
+            colors: true,
+            selected: None,
+        }
+    }
+}
+
+impl Renderer {
+    /// Create a renderer with colors and no checker selected
+    pub fn new() -> Self {
+        Renderer::default()
+    }
+
+    /// Render without ANSI escape sequences, e.g. for terminals without colors
+    pub fn without_colors(mut self) -> Self {
+        self.colors = false;
+        self
+    }
+
+    /// Highlight the checker on a field of the player on turn, and the fields it can be moved to
+    pub fn with_selected(mut self, field: Field) -> Self {
+        self.selected = Some(field);
+        self
+    }
+
+    /// Wrap a text in an ANSI style, if colors are enabled
+    fn paint(&self, style: &str, text: &str) -> String {
+        match self.colors {
+            true => format!("{}{}{}", style, text, RESET),
+            false => text.to_string(),
+        }
+    }
+
+    /// Returns the point between 1 and 24 seen by player 0 of a field of the player on turn
+    fn absolute(game: &Game, field: Field) -> Option<usize> {
+        let point = RelativePoint::try_from(field).ok()?;
+        let point = game.board.absolute(game.who_plays, point).ok()?;
+        Some(usize::from(point.number()))
+    }
+
+    /// Returns the fields the selected checker can be moved to, seen by the player on turn
+    fn destinations(&self, game: &Game) -> Vec<Field> {
+        let Some(from) = self.selected else {
+            return Vec::new();
+        };
+        game.playable_dice(from)
+            .into_iter()
+            .filter_map(|die| from.step(die).ok())
+            .collect()
+    }
+
+    /// Returns the border of a half of the board, with the labels of the selected point and its
+    /// destinations highlighted
+    fn border(&self, half: usize, game: &Game, destinations: &[Field]) -> String {
+        let selected = self.selected.and_then(|field| Self::absolute(game, field));
+        let destinations: Vec<usize> = destinations
+            .iter()
+            .filter_map(|field| Self::absolute(game, *field))
+            .collect();
+        let labels: Vec<String> = DIAGRAM_POINTS[half]
+            .iter()
+            .map(|p| {
+                let label = match p {
+                    10.. => p.to_string(),
+                    _ => format!("─{}", p),
+                };
+                if selected == Some(*p) {
+                    self.paint(SELECTED, &format!("{:>2}", p))
+                } else if destinations.contains(p) {
+                    self.paint(DESTINATION, &format!("{:>2}", p))
+                } else {
+                    label
+                }
+            })
+            .collect();
+        let (left, right) = match half {
+            0 => ("┌", "┬───┬"),
+            _ => ("└", "┴───┴"),
+        };
+        let end = match half {
+            0 => "┐",
+            _ => "┘",
+        };
+        format!(
+            "{}{}─{}{}─{}",
+            left,
+            labels[..6].join("─"),
+            right,
+            labels[6..].join("─"),
+            end
+        )
+    }
+
+    /// Returns the cell of a field in a row of the board, counted from the border, with the
+    /// checkers highlighted if selected
+    fn cell(&self, point: Point, row: usize, selected: bool) -> String {
+        let (glyph, style) = match point.owner {
+            Player::Player0 => ("●", PLAYER0),
+            Player::Player1 => ("○", PLAYER1),
+            Player::Nobody => return "   ".to_string(),
+        };
+        let style = if selected { SELECTED } else { style };
+        match point.count {
+            n if n > 5 && row == 4 => format!("{} ", self.paint(style, &format!("{:>2}", n))),
+            n if usize::from(n) > row => format!(" {} ", self.paint(style, glyph)),
+            _ => "   ".to_string(),
+        }
+    }
+
+    /// Render the game
+    pub fn render(&self, game: &Game) -> String {
+        let display = game.board.get();
+        let destinations = self.destinations(game);
+        // player 0 enters on the top half and player 1 on the bottom half
+        let bar = [
+            Point::new(Player::Player0, display.bar[0]),
+            Point::new(Player::Player1, display.bar[1]),
+        ];
+
+        let mut board = Vec::with_capacity(13);
+        board.push(self.border(0, game, &destinations));
+        for (half, points) in DIAGRAM_POINTS.iter().enumerate() {
+            if half == 1 {
+                board.push("├──────────────────┤BAR├──────────────────┤".to_string());
+            }
+            for row in 0..5 {
+                // the rows of the bottom half are stacked from the bottom
+                let row = if half == 0 { row } else { 4 - row };
+                let selected =
+                    self.selected == Some(Field::Bar) && bar[half].owner == game.who_plays;
+                let cells: Vec<String> = points
+                    .iter()
+                    .map(|p| self.cell(display.points[p - 1], row, false))
+                    .collect();
+                let bar = self.cell(bar[half], row, selected);
+                board.push(format!(
+                    "│{}│{}│{}│",
+                    cells[..6].concat(),
+                    bar,
+                    cells[6..].concat()
+                ));
+            }
+        }
+        board.push(self.border(1, game, &destinations));
+
+        let mut side = game.side_panel();
+        if destinations.contains(&Field::Off) {
+            // player 1 bears off on the top half, player 0 on the bottom half
+            let row = match game.who_plays {
+                Player::Player1 => 0,
+                _ => 12,
+            };
+            side[row] = self.paint(DESTINATION, &side[row]);
+        }
+
+        let mut rendered = String::new();
+        for (line, note) in board.iter().zip(side.iter()) {
+            rendered.push_str(format!(" {}    {}", line, note).trim_end());
+            rendered.push('\n');
+        }
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Board;
+    use crate::Error;
+
+    #[test]
+    fn test_layout() {
+        let g = Game::new();
+        let ascii: Vec<String> = g.render_ascii().lines().map(String::from).collect();
+        let rendered = Renderer::new().without_colors().render(&g);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), ascii.len());
+        for (line, ascii) in lines.iter().zip(ascii.iter()) {
+            assert_eq!(line.chars().count(), ascii.chars().count());
+        }
+        assert_eq!(
+            lines[0],
+            " ┌13─14─15─16─17─18─┬───┬19─20─21─22─23─24─┐    O  pips 167  off 0"
+        );
+        assert_eq!(lines[1], " │ ●           ○    │   │ ○              ● │");
+        assert_eq!(
+            lines[6],
+            " ├──────────────────┤BAR├──────────────────┤    Cube: 1"
+        );
+        assert_eq!(
+            lines[12],
+            " └12─11─10──9──8──7─┴───┴─6──5──4──3──2──1─┘    X  pips 167  off 0"
+        );
+    }
+
+    #[test]
+    fn test_colors() {
+        let g = Game::new();
+        let rendered = Renderer::new().render(&g);
+        assert!(rendered.contains(&format!(" {}●{} ", PLAYER0, RESET)));
+        assert!(rendered.contains(&format!(" {}○{} ", PLAYER1, RESET)));
+        assert!(!Renderer::new().without_colors().render(&g).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_selected() -> Result<(), Error> {
+        let mut g = Game::new();
+        let _ = g.roll_with((2, 5))?;
+        let rendered = Renderer::new().with_selected(Field::Point(23)).render(&g);
+        // the back checkers of player 1 are on the 1 point of player 0, and cannot move to the 6
+        // point held by player 0
+        assert!(rendered.contains(&format!("{} 1{}", SELECTED, RESET)));
+        assert!(rendered.contains(&format!("{} 3{}", DESTINATION, RESET)));
+        assert_eq!(rendered.matches(DESTINATION).count(), 1);
+
+        let mut g = Game::new();
+        g.board.set_bar(Player::Player0, 1)?;
+        g.board.set(Player::Player0, 23, -1)?;
+        let _ = g.roll_with((6, 1))?;
+        let rendered = Renderer::new().with_selected(Field::Bar).render(&g);
+        assert!(rendered.contains(&format!(" {}●{} ", SELECTED, RESET)));
+
+        // bearing off highlights the checkers off
+        let mut g = Game::new();
+        g.board = Board::new();
+        g.board.points = [Point::EMPTY; 24];
+        g.board.points[1] = Point::new(Player::Player0, 15);
+        g.board.points[23] = Point::new(Player::Player1, 15);
+        let _ = g.roll_with((6, 1))?;
+        let rendered = Renderer::new().with_selected(Field::Point(1)).render(&g);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines[12].contains(&format!("{}X  pips 30  off 0{}", DESTINATION, RESET)));
+        Ok(())
+    }
+}
@@ -0,0 +1,803 @@
+//! # Tournaments
+//!
+//! Organizes the matches of club and online tournaments. Players are referred to by their index
+//! in the list of players the tournament was created with, and every pairing is played as a
+//! [`Match`], with the first player of the pairing as player 0.
+//!
+//! A [`Knockout`](crate::tournament::Knockout) eliminates the loser of every match, while all
+//! players of a [`Swiss`](crate::tournament::Swiss) tournament play every round and are ranked by
+//! their wins.
+use crate::rules::{GameRules, Player, Rules, Variant};
+use crate::{Error, Match};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Represents a pairing of two players in a round of a tournament
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pairing {
+    /// The indices of the players, none for a bye or a player not known yet
+    pub players: (Option<usize>, Option<usize>),
+    /// The match of the players, once started
+    pub r#match: Option<Match>,
+    /// The index of the winner, once the result has been recorded
+    pub winner: Option<usize>,
+}
+
+impl Pairing {
+    /// Returns the player of the match a player of the tournament plays as
+    fn side(&self, player: usize) -> Option<Player> {
+        match self.players {
+            (Some(p), _) if p == player => Some(Player::Player0),
+            (_, Some(p)) if p == player => Some(Player::Player1),
+            _ => None,
+        }
+    }
+
+    /// Returns the match of the pairing, starting it with the given rules if not started yet
+    fn start(&mut self, rules: Rules) -> &mut Match {
+        self.r#match.get_or_insert_with(|| {
+            let mut m = Match::new();
+            m.rules = rules;
+            m
+        })
+    }
+
+    /// Returns the index of the player who has won the match of the pairing
+    fn match_winner(&self) -> Result<usize, Error> {
+        match self.r#match.as_ref().and_then(|m| m.winner()) {
+            Some(Player::Player0) => self.players.0,
+            Some(Player::Player1) => self.players.1,
+            _ => None,
+        }
+        .ok_or(Error::MatchNotEnded)
+    }
+}
+
+/// Returns a pairing whose players are both known and which has not been decided yet
+fn open(rounds: &mut [Vec<Pairing>], round: usize, index: usize) -> Result<&mut Pairing, Error> {
+    let pairing = rounds
+        .get_mut(round)
+        .and_then(|pairings| pairings.get_mut(index))
+        .filter(|p| p.players.0.is_some() && p.players.1.is_some())
+        .ok_or(Error::PairingInvalid)?;
+    match pairing.winner {
+        Some(_) => Err(Error::MatchEnded),
+        None => Ok(pairing),
+    }
+}
+
+/// Represents a knockout tournament, in which the winner of each match advances to the next
+/// round and the loser is eliminated.
+///
+/// Players are seeded in the order given, so that the best seeds meet as late as possible: the
+/// first seed plays the last seed in the first round, and the first two seeds can only meet in
+/// the final. If the number of players is no power of two, the best seeds advance to the second
+/// round with a bye. Every round is played over a fixed number of points.
+/// ```
+/// use backgammon::Victory;
+/// use backgammon::rules::Player;
+/// use backgammon::tournament::Knockout;
+///
+/// let mut k = Knockout::new(&["Alice", "Bob", "Carol"], &[3, 5]).unwrap();
+/// // Alice, the first seed, has a bye
+/// assert_eq!(k.rounds()[1][0].players, (Some(0), None));
+///
+/// let m = k.start(0, 1).unwrap();
+/// assert_eq!(m.rules.points, 3);
+/// m.new_game().unwrap().resign(Player::Player0, Victory::Backgammon).unwrap();
+/// assert_eq!(k.record(0, 1).unwrap(), 2);
+///
+/// // Carol meets Alice in the final over 5 points
+/// assert_eq!(k.rounds()[1][0].players, (Some(0), Some(2)));
+/// k.record_winner(1, 0, 2).unwrap();
+/// assert_eq!(k.winner(), Some(2));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Knockout {
+    /// The rules set for all matches, except for the points which are set per round
+    pub rules: Rules,
+    /// The names of the players, in the order of their seeds
+    players: Vec<String>,
+    /// The points of the matches of each round
+    lengths: Vec<u32>,
+    /// The pairings of each round, the first round first
+    rounds: Vec<Vec<Pairing>>,
+}
+
+// implement Display trait
+impl fmt::Display for Knockout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = |player: Option<usize>| match player {
+            Some(p) => self.players[p].as_str(),
+            None => "-",
+        };
+        for (round, pairings) in self.rounds.iter().enumerate() {
+            writeln!(f, "Round {} ({} points)", round + 1, self.lengths[round])?;
+            for pairing in pairings.iter() {
+                write!(
+                    f,
+                    "  {} - {}",
+                    name(pairing.players.0),
+                    name(pairing.players.1)
+                )?;
+                match pairing.winner {
+                    Some(winner) => writeln!(f, ": {}", self.players[winner])?,
+                    None => writeln!(f)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the seeds in the order of the slots of a bracket of the given size, a power of two,
+/// such that the sum of the seeds of each pairing is the same in every round
+fn bracket(size: usize) -> Vec<usize> {
+    let mut seeds = vec![0];
+    while seeds.len() < size {
+        let slots = seeds.len() * 2;
+        seeds = seeds.iter().flat_map(|s| [*s, slots - 1 - s]).collect();
+    }
+    seeds
+}
+
+/// Implements methods for the Knockout struct
+impl Knockout {
+    /// Create the bracket of a knockout tournament of at least two players, in the order of
+    /// their seeds, with the points of the matches of each round. There have to be as many
+    /// lengths as rounds, i.e. the number of players rounded up to a power of two is 2 to the
+    /// power of the number of rounds.
+    pub fn new(players: &[&str], lengths: &[u32]) -> Result<Self, Error> {
+        if players.len() < 2 {
+            return Err(Error::PlayerInvalid);
+        }
+        let size = players.len().next_power_of_two();
+        if 1 << lengths.len() != size {
+            return Err(Error::PairingInvalid);
+        }
+        let slots = bracket(size)
+            .into_iter()
+            .map(|seed| Some(seed).filter(|s| *s < players.len()))
+            .collect::<Vec<_>>();
+        let mut rounds = vec![slots
+            .chunks(2)
+            .map(|pair| Pairing {
+                players: (pair[0], pair[1]),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()];
+        while rounds[rounds.len() - 1].len() > 1 {
+            let pairings = rounds[rounds.len() - 1].len() / 2;
+            rounds.push(vec![Pairing::default(); pairings]);
+        }
+
+        let mut knockout = Knockout {
+            rules: Rules::default(),
+            players: players.iter().map(|p| p.to_string()).collect(),
+            lengths: lengths.to_vec(),
+            rounds,
+        };
+        // the players without an opponent in the first round advance at once
+        for index in 0..size / 2 {
+            if let (Some(player), None) | (None, Some(player)) = knockout.rounds[0][index].players {
+                knockout.advance(0, index, player);
+            }
+        }
+        Ok(knockout)
+    }
+
+    /// Returns the names of the players, in the order of their seeds
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    /// Returns the pairings of all rounds, the first round first
+    pub fn rounds(&self) -> &[Vec<Pairing>] {
+        &self.rounds
+    }
+
+    /// Returns the points the matches of a round are played to
+    pub fn length(&self, round: usize) -> Option<u32> {
+        self.lengths.get(round).copied()
+    }
+
+    /// Returns the first round with a pairing which has not been decided yet, if any
+    pub fn current_round(&self) -> Option<usize> {
+        self.rounds
+            .iter()
+            .position(|pairings| pairings.iter().any(|p| p.winner.is_none()))
+    }
+
+    /// Returns the winner of the tournament, once the final has been decided
+    pub fn winner(&self) -> Option<usize> {
+        self.rounds
+            .last()
+            .and_then(|final_round| final_round[0].winner)
+    }
+
+    /// Start the match of a pairing with the rules of the tournament and the points of the
+    /// round, or return the match if it has been started already. The players of the pairing
+    /// have to be known, and the pairing must not have been decided.
+    pub fn start(&mut self, round: usize, index: usize) -> Result<&mut Match, Error> {
+        let mut rules = self.rules;
+        rules.points = self.length(round).ok_or(Error::PairingInvalid)?;
+        Ok(open(&mut self.rounds, round, index)?.start(rules))
+    }
+
+    /// Record the winner of the match of a pairing, who advances to the next round. Returns the
+    /// index of the winner, or fails with [`Error::MatchNotEnded`] as long as the match has no
+    /// winner.
+    pub fn record(&mut self, round: usize, index: usize) -> Result<usize, Error> {
+        let winner = open(&mut self.rounds, round, index)?.match_winner()?;
+        self.advance(round, index, winner);
+        Ok(winner)
+    }
+
+    /// Record the winner of a pairing whose match has not been played with this crate, e.g. when
+    /// a player withdraws
+    pub fn record_winner(
+        &mut self,
+        round: usize,
+        index: usize,
+        winner: usize,
+    ) -> Result<(), Error> {
+        if open(&mut self.rounds, round, index)?.side(winner).is_none() {
+            return Err(Error::PlayerInvalid);
+        }
+        self.advance(round, index, winner);
+        Ok(())
+    }
+
+    /// Set the winner of a pairing and move the winner to the pairing of the next round
+    fn advance(&mut self, round: usize, index: usize, winner: usize) {
+        self.rounds[round][index].winner = Some(winner);
+        if let Some(next) = self.rounds.get_mut(round + 1) {
+            let players = &mut next[index / 2].players;
+            match index % 2 {
+                0 => players.0 = Some(winner),
+                _ => players.1 = Some(winner),
+            }
+        }
+    }
+}
+
+/// Implements SetRules for Knockout
+impl GameRules for Knockout {
+    fn with_beaver(mut self) -> Self {
+        self.rules.beaver = true;
+        self
+    }
+
+    fn with_raccoon(mut self) -> Self {
+        self.rules.raccoon = true;
+        self
+    }
+
+    fn with_murphy(mut self, limit: u8) -> Self {
+        self.rules.murphy = true;
+        self.rules.murphy_limit = limit;
+        self
+    }
+
+    fn with_jacoby(mut self) -> Self {
+        self.rules.jacoby = true;
+        self
+    }
+
+    fn with_holland(mut self) -> Self {
+        self.rules.holland = true;
+        self
+    }
+
+    fn with_variant(mut self, variant: Variant) -> Self {
+        self.rules.variant = variant;
+        self
+    }
+}
+
+/// Represents the standing of a player in a Swiss tournament, see [`Swiss::standings`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Standing {
+    /// The index of the player
+    pub player: usize,
+    /// The matches won, counting a bye as a win
+    pub wins: u32,
+    /// The first tiebreak: the sum of the wins of all opponents met
+    pub buchholz: u32,
+    /// The second tiebreak: the sum of the wins of all opponents beaten
+    pub sonneborn_berger: u32,
+}
+
+/// Represents a Swiss tournament, in which all players play every round against an opponent
+/// with the same or a similar score, and nobody is eliminated.
+///
+/// Each round, the players are ranked by their wins and then by their seeds, i.e. the order
+/// given. Going down the ranking, every player is paired with the next player not met before,
+/// so that players of the same score group meet. If the number of players is odd, the lowest
+/// ranked player who has not had a bye yet gets a bye, which counts as a win. Only if the
+/// remaining players cannot be paired without rematches, rematches are allowed.
+///
+/// Like the colors in chess, the players alternate as player 0, who starts the games: of both
+/// players of a pairing, the one who has been player 0 less often is player 0 again.
+/// ```
+/// use backgammon::tournament::Swiss;
+///
+/// let mut s = Swiss::new(&["Alice", "Bob", "Carol", "Dave"], 5).unwrap();
+/// let round = s.pair_round().unwrap();
+/// assert_eq!(round[0].players, (Some(0), Some(1)));
+/// s.record_winner(0, 0, 1).unwrap();
+/// s.record_winner(0, 1, 2).unwrap();
+///
+/// // the winners meet, and so do the losers
+/// let round = s.pair_round().unwrap();
+/// assert_eq!(round[0].players, (Some(1), Some(2)));
+/// assert_eq!(round[1].players, (Some(3), Some(0)));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Swiss {
+    /// The rules set for all matches, except for the points
+    pub rules: Rules,
+    /// The names of the players, in the order of their seeds
+    players: Vec<String>,
+    /// The points of all matches
+    length: u32,
+    /// The pairings of each round, the first round first
+    rounds: Vec<Vec<Pairing>>,
+}
+
+// implement Display trait
+impl fmt::Display for Swiss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Swiss tournament over {} points after {} rounds",
+            self.length,
+            self.rounds.len()
+        )?;
+        for (rank, s) in self.standings().iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}. {} {} (Buchholz {}, Sonneborn-Berger {})",
+                rank + 1,
+                self.players[s.player],
+                s.wins,
+                s.buchholz,
+                s.sonneborn_berger
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Pair the players in the order given, each with the first player further down the order it
+/// is allowed to play, and backtrack if the players left cannot be paired
+fn pair(players: &[usize], allowed: &dyn Fn(usize, usize) -> bool) -> Option<Vec<(usize, usize)>> {
+    let Some((first, rest)) = players.split_first() else {
+        return Some(Vec::new());
+    };
+    for (i, opponent) in rest.iter().enumerate() {
+        if !allowed(*first, *opponent) {
+            continue;
+        }
+        let mut left = rest.to_vec();
+        let _ = left.remove(i);
+        if let Some(mut pairs) = pair(&left, allowed) {
+            pairs.insert(0, (*first, *opponent));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+/// Implements methods for the Swiss struct
+impl Swiss {
+    /// Create a Swiss tournament of at least two players, in the order of their seeds, with all
+    /// matches played to the given points
+    pub fn new(players: &[&str], length: u32) -> Result<Self, Error> {
+        if players.len() < 2 {
+            return Err(Error::PlayerInvalid);
+        }
+        Ok(Swiss {
+            rules: Rules::default(),
+            players: players.iter().map(|p| p.to_string()).collect(),
+            length,
+            rounds: Vec::new(),
+        })
+    }
+
+    /// Returns the names of the players, in the order of their seeds
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    /// Returns the pairings of all rounds paired so far, the first round first
+    pub fn rounds(&self) -> &[Vec<Pairing>] {
+        &self.rounds
+    }
+
+    /// Returns the pairings of a player in all rounds
+    fn pairings(&self, player: usize) -> impl Iterator<Item = &Pairing> {
+        self.rounds
+            .iter()
+            .flatten()
+            .filter(move |p| p.side(player).is_some())
+    }
+
+    /// Returns the wins of all players, counting byes as wins
+    pub fn wins(&self) -> Vec<u32> {
+        let mut wins = vec![0; self.players.len()];
+        for winner in self.rounds.iter().flatten().filter_map(|p| p.winner) {
+            wins[winner] += 1;
+        }
+        wins
+    }
+
+    /// Returns the standings of all players, ranked by their wins, then by the tiebreaks, and
+    /// finally by their seeds
+    pub fn standings(&self) -> Vec<Standing> {
+        let wins = self.wins();
+        let mut standings = (0..self.players.len())
+            .map(|player| {
+                let mut standing = Standing {
+                    player,
+                    wins: wins[player],
+                    buchholz: 0,
+                    sonneborn_berger: 0,
+                };
+                for pairing in self.pairings(player) {
+                    let opponent = match pairing.players {
+                        (Some(a), Some(b)) if a == player => b,
+                        (Some(a), Some(_)) => a,
+                        _ => continue,
+                    };
+                    standing.buchholz += wins[opponent];
+                    if pairing.winner == Some(player) {
+                        standing.sonneborn_berger += wins[opponent];
+                    }
+                }
+                standing
+            })
+            .collect::<Vec<_>>();
+        standings.sort_by(|a, b| {
+            (b.wins, b.buchholz, b.sonneborn_berger, a.player).cmp(&(
+                a.wins,
+                a.buchholz,
+                a.sonneborn_berger,
+                b.player,
+            ))
+        });
+        standings
+    }
+
+    /// Returns how much more often a player has been player 0 than player 1, and whether the
+    /// player has been player 0 in the last match
+    fn starts(&self, player: usize) -> (i64, bool) {
+        let mut balance = 0;
+        let mut last = false;
+        for pairing in self.pairings(player) {
+            if pairing.players.1.is_none() {
+                continue;
+            }
+            last = pairing.side(player) == Some(Player::Player0);
+            balance += if last { 1 } else { -1 };
+        }
+        (balance, last)
+    }
+
+    /// Pair the next round, once all matches of the previous round have been decided, see
+    /// [`Swiss`]. A bye is the last pairing of the round.
+    pub fn pair_round(&mut self) -> Result<&[Pairing], Error> {
+        if self
+            .rounds
+            .last()
+            .is_some_and(|pairings| pairings.iter().any(|p| p.winner.is_none()))
+        {
+            return Err(Error::MatchNotEnded);
+        }
+        let wins = self.wins();
+        let mut ranking = (0..self.players.len()).collect::<Vec<_>>();
+        ranking.sort_by_key(|p| (core::cmp::Reverse(wins[*p]), *p));
+
+        let mut bye = None;
+        if ranking.len() % 2 == 1 {
+            let had_bye = |player: usize| self.pairings(player).any(|p| p.players.1.is_none());
+            let index = ranking
+                .iter()
+                .rposition(|p| !had_bye(*p))
+                .unwrap_or(ranking.len() - 1);
+            bye = Some(ranking.remove(index));
+        }
+
+        let met = |a: usize, b: usize| {
+            self.pairings(a)
+                .any(|p| p.side(b).is_some() && p.players.1.is_some())
+        };
+        let pairs = pair(&ranking, &|a, b| !met(a, b))
+            .or_else(|| pair(&ranking, &|_, _| true))
+            .unwrap_or_default();
+        let mut pairings = pairs
+            .into_iter()
+            .map(|(a, b)| {
+                // the higher ranked player starts if both started equally often
+                let players = match self.starts(a) <= self.starts(b) {
+                    true => (Some(a), Some(b)),
+                    false => (Some(b), Some(a)),
+                };
+                Pairing {
+                    players,
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+        if let Some(player) = bye {
+            pairings.push(Pairing {
+                players: (Some(player), None),
+                r#match: None,
+                winner: Some(player),
+            });
+        }
+        self.rounds.push(pairings);
+        Ok(&self.rounds[self.rounds.len() - 1])
+    }
+
+    /// Start the match of a pairing with the rules and the points of the tournament, or return
+    /// the match if it has been started already
+    pub fn start(&mut self, round: usize, index: usize) -> Result<&mut Match, Error> {
+        let mut rules = self.rules;
+        rules.points = self.length;
+        Ok(open(&mut self.rounds, round, index)?.start(rules))
+    }
+
+    /// Record the winner of the match of a pairing. Returns the index of the winner, or fails
+    /// with [`Error::MatchNotEnded`] as long as the match has no winner.
+    pub fn record(&mut self, round: usize, index: usize) -> Result<usize, Error> {
+        let pairing = open(&mut self.rounds, round, index)?;
+        let winner = pairing.match_winner()?;
+        pairing.winner = Some(winner);
+        Ok(winner)
+    }
+
+    /// Record the winner of a pairing whose match has not been played with this crate
+    pub fn record_winner(
+        &mut self,
+        round: usize,
+        index: usize,
+        winner: usize,
+    ) -> Result<(), Error> {
+        let pairing = open(&mut self.rounds, round, index)?;
+        if pairing.side(winner).is_none() {
+            return Err(Error::PlayerInvalid);
+        }
+        pairing.winner = Some(winner);
+        Ok(())
+    }
+}
+
+/// Implements SetRules for Swiss
+impl GameRules for Swiss {
+    fn with_beaver(mut self) -> Self {
+        self.rules.beaver = true;
+        self
+    }
+
+    fn with_raccoon(mut self) -> Self {
+        self.rules.raccoon = true;
+        self
+    }
+
+    fn with_murphy(mut self, limit: u8) -> Self {
+        self.rules.murphy = true;
+        self.rules.murphy_limit = limit;
+        self
+    }
+
+    fn with_jacoby(mut self) -> Self {
+        self.rules.jacoby = true;
+        self
+    }
+
+    fn with_holland(mut self) -> Self {
+        self.rules.holland = true;
+        self
+    }
+
+    fn with_variant(mut self, variant: Variant) -> Self {
+        self.rules.variant = variant;
+        self
+    }
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Victory;
+
+    #[test]
+    fn test_bracket() {
+        assert_eq!(bracket(1), vec![0]);
+        assert_eq!(bracket(4), vec![0, 3, 1, 2]);
+        assert_eq!(bracket(8), vec![0, 7, 3, 4, 1, 6, 2, 5]);
+    }
+
+    #[test]
+    fn test_new_knockout() -> Result<(), Error> {
+        assert!(matches!(
+            Knockout::new(&["Alice"], &[]),
+            Err(Error::PlayerInvalid)
+        ));
+        assert!(matches!(
+            Knockout::new(&["Alice", "Bob", "Carol"], &[3]),
+            Err(Error::PairingInvalid)
+        ));
+
+        // the three best seeds of five players have a bye
+        let k = Knockout::new(&["A", "B", "C", "D", "E"], &[3, 5, 7])?;
+        assert_eq!(k.rounds().len(), 3);
+        assert_eq!(k.rounds()[0].len(), 4);
+        assert_eq!(k.rounds()[0][1].players, (Some(3), Some(4)));
+        assert_eq!(k.rounds()[1][0].players, (Some(0), None));
+        assert_eq!(k.rounds()[1][1].players, (Some(1), Some(2)));
+        assert_eq!(k.current_round(), Some(0));
+        assert_eq!(k.length(2), Some(7));
+        assert_eq!(k.winner(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_progression() -> Result<(), Error> {
+        let mut k = Knockout::new(&["A", "B", "C", "D"], &[1, 3])?.with_jacoby();
+        assert!(matches!(k.start(1, 0), Err(Error::PairingInvalid)));
+        assert!(matches!(k.start(0, 2), Err(Error::PairingInvalid)));
+
+        let m = k.start(0, 0)?;
+        assert!(m.rules.jacoby);
+        let _ = m.new_game()?;
+        assert!(matches!(k.record(0, 0), Err(Error::MatchNotEnded)));
+        // the match in progress is returned again
+        let m = k.start(0, 0)?;
+        assert_eq!(m.games.len(), 1);
+        let g = m.game().ok_or(Error::GameEnded)?;
+        g.resign(Player::Player0, Victory::Single)?;
+        assert_eq!(k.record(0, 0)?, 3);
+        assert!(matches!(k.record(0, 0), Err(Error::MatchEnded)));
+        assert!(matches!(k.start(0, 0), Err(Error::MatchEnded)));
+
+        assert!(matches!(
+            k.record_winner(0, 1, 0),
+            Err(Error::PlayerInvalid)
+        ));
+        k.record_winner(0, 1, 1)?;
+        assert_eq!(k.current_round(), Some(1));
+        assert_eq!(k.rounds()[1][0].players, (Some(3), Some(1)));
+        assert_eq!(k.start(1, 0)?.rules.points, 3);
+        k.record_winner(1, 0, 1)?;
+        assert_eq!(k.current_round(), None);
+        assert_eq!(k.winner(), Some(1));
+        Ok(())
+    }
+
+    // test Display trait
+    #[test]
+    fn test_display() -> Result<(), Error> {
+        let mut k = Knockout::new(&["Alice", "Bob", "Carol"], &[3, 5])?;
+        k.record_winner(0, 1, 1)?;
+        assert_eq!(
+            k.to_string(),
+            "Round 1 (3 points)\n  Alice - -: Alice\n  Bob - Carol: Bob\n\
+             Round 2 (5 points)\n  Alice - Bob\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_swiss_pairing() -> Result<(), Error> {
+        assert!(matches!(
+            Swiss::new(&["Alice"], 3),
+            Err(Error::PlayerInvalid)
+        ));
+        let mut s = Swiss::new(&["A", "B", "C", "D"], 3)?.with_jacoby();
+        let _ = s.pair_round()?;
+        assert!(matches!(s.pair_round(), Err(Error::MatchNotEnded)));
+
+        let m = s.start(0, 0)?;
+        assert!(m.rules.jacoby);
+        assert_eq!(m.rules.points, 3);
+        let g = m.new_game()?;
+        g.resign(Player::Player1, Victory::Backgammon)?;
+        assert_eq!(s.record(0, 0)?, 0);
+        s.record_winner(0, 1, 2)?;
+
+        // the winners meet, and the higher ranked player starts again if both started before
+        let round = s.pair_round()?;
+        assert_eq!(round[0].players, (Some(0), Some(2)));
+        assert_eq!(round[1].players, (Some(1), Some(3)));
+        s.record_winner(1, 0, 0)?;
+        s.record_winner(1, 1, 1)?;
+
+        // 0 and 1 as well as 2 and 3 have met already, and player 0 alternates
+        let round = s.pair_round()?;
+        assert_eq!(round[0].players, (Some(3), Some(0)));
+        assert_eq!(round[1].players, (Some(2), Some(1)));
+        s.record_winner(2, 0, 3)?;
+        s.record_winner(2, 1, 2)?;
+        assert_eq!(s.wins(), vec![2, 1, 2, 1]);
+
+        // everybody has met everybody, so rematches are allowed
+        let round = s.pair_round()?;
+        assert_eq!(round[0].players, (Some(0), Some(2)));
+        assert_eq!(round[1].players, (Some(1), Some(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_swiss_bye() -> Result<(), Error> {
+        let mut s = Swiss::new(&["A", "B", "C"], 1)?;
+        let round = s.pair_round()?;
+        assert_eq!(round.len(), 2);
+        assert_eq!(round[1].players, (Some(2), None));
+        assert_eq!(round[1].winner, Some(2));
+        assert!(matches!(s.start(0, 1), Err(Error::PairingInvalid)));
+        s.record_winner(0, 0, 0)?;
+
+        // the lowest ranked player without a bye gets the next bye
+        let round = s.pair_round()?;
+        assert_eq!(round[0].players, (Some(2), Some(0)));
+        assert_eq!(round[1].players, (Some(1), None));
+        s.record_winner(1, 0, 2)?;
+
+        let round = s.pair_round()?;
+        assert_eq!(round[0].players, (Some(1), Some(2)));
+        assert_eq!(round[1].players, (Some(0), None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_swiss_standings() -> Result<(), Error> {
+        let mut s = Swiss::new(&["A", "B", "C", "D"], 3)?;
+        for (round, winners) in [[0, 2], [0, 1], [3, 2]].iter().enumerate() {
+            let _ = s.pair_round()?;
+            s.record_winner(round, 0, winners[0])?;
+            s.record_winner(round, 1, winners[1])?;
+        }
+        let standings = s.standings();
+        assert_eq!(
+            standings.iter().map(|s| s.player).collect::<Vec<_>>(),
+            vec![0, 2, 3, 1]
+        );
+        assert_eq!(
+            standings[0],
+            Standing {
+                player: 0,
+                wins: 2,
+                buchholz: 4,
+                sonneborn_berger: 3,
+            }
+        );
+        assert_eq!(standings[1].buchholz, 4);
+        assert_eq!(standings[1].sonneborn_berger, 2);
+        assert_eq!(standings[2].buchholz, 5);
+        assert_eq!(standings[2].sonneborn_berger, 2);
+        assert_eq!(standings[3].sonneborn_berger, 1);
+
+        assert_eq!(
+            s.to_string(),
+            "Swiss tournament over 3 points after 3 rounds\n  \
+               1. A 2 (Buchholz 4, Sonneborn-Berger 3)\n  \
+               2. C 2 (Buchholz 4, Sonneborn-Berger 2)\n  \
+               3. D 1 (Buchholz 5, Sonneborn-Berger 2)\n  \
+               4. B 1 (Buchholz 5, Sonneborn-Berger 1)\n"
+        );
+        Ok(())
+    }
+}
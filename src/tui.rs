@@ -0,0 +1,429 @@
+//! # Interactive Board
+//!
+//! Holds the state of an interactive board on which a human moves the checkers with the keyboard,
+//! independently of the terminal library drawing it. Keys are fed to
+//! [`BoardView::handle`](crate::tui::BoardView::handle), which lets the engine validate every
+//! action, and [`BoardView::render`](crate::tui::BoardView::render) returns the lines to draw: the
+//! board diagram, the pip counts, the cube, the score, the cursor, and the last message.
+//!
+//! The cursor moves with the arrow keys over the fields of the player on turn, from the bar over
+//! the points 24 to 1 to the off area. Enter first picks up a checker and then drops it on the
+//! field under the cursor, and the digits 1 to 6 move the checker under the cursor by that dice
+//! value.
+//! ```
+//! use backgammon::Game;
+//! use backgammon::rules::Field;
+//! use backgammon::tui::{BoardView, Key};
+//!
+//! let mut g = Game::new();
+//! g.roll_with((3, 1)).unwrap();
+//! let mut view = BoardView::new(g);
+//!
+//! // pick up a checker on the 8 point and drop it on the 5 point
+//! view.set_cursor(Field::Point(7));
+//! view.handle(Key::Enter);
+//! view.handle(Key::Right);
+//! view.handle(Key::Right);
+//! view.handle(Key::Right);
+//! view.handle(Key::Enter);
+//! assert_eq!(view.game.remaining_dice(), vec![1]);
+//!
+//! // move a checker from the 6 point with the 1
+//! view.set_cursor(Field::Point(5));
+//! view.handle(Key::Char('1'));
+//! assert!(view.render().contains("Pips: X 163, O 167"));
+//! ```
+//!
+//! With the `tui` feature, `run` plays a game in the terminal, drawing the board with
+//! [ratatui](https://ratatui.rs) and reading the keys with crossterm. The board is a widget, so
+//! other frontends can draw it next to their own widgets.
+use crate::game::{Game, Phase};
+use crate::rules::{Field, Player, Roll};
+#[cfg(feature = "term")]
+use crate::term::Renderer;
+use crate::Error;
+
+#[cfg(feature = "tui")]
+use ratatui::buffer::Buffer;
+#[cfg(feature = "tui")]
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+#[cfg(feature = "tui")]
+use ratatui::layout::Rect;
+#[cfg(feature = "tui")]
+use ratatui::widgets::{Block, Paragraph, Widget};
+#[cfg(feature = "tui")]
+use ratatui::DefaultTerminal;
+#[cfg(feature = "tui")]
+use std::io;
+
+/// Represents a key pressed by the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// Move the cursor towards the bar
+    Left,
+    /// Move the cursor towards the off area
+    Right,
+    /// Pick up the checker under the cursor, or drop the picked up checker there
+    Enter,
+    /// Put the picked up checker back
+    Esc,
+    /// Take back the last checker moved
+    Backspace,
+    /// A character: a dice value to move the checker under the cursor, "r" to roll, "d" to
+    /// double, "t" to take, and "p" to pass
+    Char(char),
+}
+
+#[cfg(feature = "tui")]
+impl Key {
+    /// Returns the key of a key code read by crossterm, if the key is used on the board
+    pub fn from_code(code: KeyCode) -> Option<Key> {
+        match code {
+            KeyCode::Left => Some(Key::Left),
+            KeyCode::Right => Some(Key::Right),
+            KeyCode::Enter => Some(Key::Enter),
+            KeyCode::Esc => Some(Key::Esc),
+            KeyCode::Backspace => Some(Key::Backspace),
+            KeyCode::Char(c) => Some(Key::Char(c)),
+            _ => None,
+        }
+    }
+}
+
+/// Holds a game together with the cursor and the checker picked up by the user
+#[derive(Debug, Clone)]
+pub struct BoardView {
+    /// The game shown
+    pub game: Game,
+    /// The score of the match of player 0 and player 1
+    pub score: (u64, u64),
+    /// The field under the cursor, seen by the player on turn
+    cursor: Field,
+    /// The field of the checker picked up
+    selected: Option<Field>,
+    /// The result of the last key pressed
+    message: String,
+}
+
+impl BoardView {
+    /// Show a game, with the cursor on the bar
+    pub fn new(game: Game) -> Self {
+        BoardView {
+            game,
+            score: (0, 0),
+            cursor: Field::Bar,
+            selected: None,
+            message: String::new(),
+        }
+    }
+
+    /// Show the score of the match the game belongs to
+    pub fn with_score(mut self, score: (u64, u64)) -> Self {
+        self.score = score;
+        self
+    }
+
+    /// Returns the field under the cursor, seen by the player on turn
+    pub fn cursor(&self) -> Field {
+        self.cursor
+    }
+
+    /// Move the cursor to a field, seen by the player on turn
+    pub fn set_cursor(&mut self, field: Field) {
+        self.cursor = field;
+    }
+
+    /// Returns the field of the checker picked up, if any
+    pub fn selected(&self) -> Option<Field> {
+        self.selected
+    }
+
+    /// Returns the result of the last key pressed, e.g. the reason why an action was rejected
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Handle a key pressed by the user. Actions rejected by the engine leave the game unchanged
+    /// and are explained by the [`message`](Self::message).
+    pub fn handle(&mut self, key: Key) {
+        let result = match key {
+            Key::Left => {
+                self.cursor = step(self.cursor, -1);
+                Ok(String::new())
+            }
+            Key::Right => {
+                self.cursor = step(self.cursor, 1);
+                Ok(String::new())
+            }
+            Key::Esc => {
+                self.selected = None;
+                Ok(String::new())
+            }
+            Key::Enter => self.enter(),
+            Key::Backspace => self.game.undo().map(|()| "Move taken back".to_string()),
+            Key::Char(c @ '1'..='6') => {
+                let die = c as u8 - b'0';
+                self.play(self.cursor, die)
+            }
+            Key::Char(c) => self.action(c),
+        };
+        self.message = result.unwrap_or_else(|e| e.to_string());
+    }
+
+    /// Pick up the checker under the cursor, or drop the checker picked up
+    fn enter(&mut self) -> Result<String, Error> {
+        match self.selected {
+            None => {
+                if self.game.playable_dice(self.cursor).is_empty() {
+                    return Err(Error::MoveInvalid);
+                }
+                self.selected = Some(self.cursor);
+                Ok(format!("Picked up a checker on {}", self.cursor))
+            }
+            Some(from) => {
+                let die = self
+                    .game
+                    .playable_dice(from)
+                    .into_iter()
+                    .find(|d| from.step(*d).is_ok_and(|to| to == self.cursor))
+                    .ok_or(Error::MoveInvalid)?;
+                self.play(from, die)
+            }
+        }
+    }
+
+    /// Move a checker from a field by a dice value
+    fn play(&mut self, from: Field, die: u8) -> Result<String, Error> {
+        self.game.play_die(from, die)?;
+        self.selected = None;
+        Ok(format!("Moved {}/{}", from, from.step(die)?))
+    }
+
+    /// Take the action of the player whose turn it is given by a character
+    fn action(&mut self, c: char) -> Result<String, Error> {
+        let player = self.game.who_plays;
+        match c {
+            'r' => {
+                let _ = self.game.roll()?;
+                let (a, b) = self.game.dices.values;
+                Ok(format!("Rolled {}-{}", a, b))
+            }
+            'd' => self
+                .game
+                .offer_double(player)
+                .map(|()| "Doubled".to_string()),
+            't' => self.game.take(player.other()).map(|()| "Taken".to_string()),
+            'p' => self
+                .game
+                .pass(player.other())
+                .map(|()| "Passed".to_string()),
+            _ => Err(Error::NotationInvalid),
+        }
+    }
+
+    /// Returns the lines to draw
+    pub fn render(&self) -> String {
+        let mut lines = self.game.to_string();
+        lines.push_str(&self.status());
+        lines
+    }
+
+    /// Returns the lines to draw on a terminal with Unicode and colors, highlighting the fields
+    /// the checker picked up, or else the checker under the cursor, can be moved to
+    #[cfg(feature = "term")]
+    pub fn render_term(&self) -> String {
+        let renderer = Renderer::new().with_selected(self.selected.unwrap_or(self.cursor));
+        let mut lines = renderer.render(&self.game);
+        lines.push_str(&self.status());
+        lines
+    }
+
+    /// Returns the lines below the board: the pip counts, the score, the dices left, the cursor,
+    /// and the last message
+    fn status(&self) -> String {
+        let mut lines = String::new();
+        let pips = |player: Player| self.game.pip_count(player);
+        lines.push_str(&format!(
+            " Pips: X {}, O {}\n",
+            pips(Player::Player0),
+            pips(Player::Player1)
+        ));
+        lines.push_str(&format!(" Score: X {}, O {}\n", self.score.0, self.score.1));
+        if self.game.phase == Phase::AwaitingMove {
+            let dice: Vec<String> = self
+                .game
+                .remaining_dice()
+                .iter()
+                .map(u8::to_string)
+                .collect();
+            lines.push_str(&format!(" To play: {}\n", dice.join(" ")));
+        }
+        match self.selected {
+            Some(from) => lines.push_str(&format!(" Cursor: {} (from {})\n", self.cursor, from)),
+            None => lines.push_str(&format!(" Cursor: {}\n", self.cursor)),
+        }
+        lines.push_str(&format!(" {}\n", self.message));
+        lines
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Widget for &BoardView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(BoardView::render(self))
+            .block(Block::bordered().title(" Backgammon "))
+            .render(area, buf);
+    }
+}
+
+/// Play a game in the terminal until "q" is pressed, drawing the board with ratatui and passing
+/// the keys read by crossterm to [`BoardView::handle`]. The terminal is restored on return.
+/// ```no_run
+/// use backgammon::Game;
+/// use backgammon::tui::{run, BoardView};
+///
+/// let mut view = BoardView::new(Game::new());
+/// run(&mut view).unwrap();
+/// ```
+#[cfg(feature = "tui")]
+pub fn run(view: &mut BoardView) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = events(&mut terminal, view);
+    ratatui::restore();
+    result
+}
+
+/// Draw the board and handle the keys pressed until "q" is pressed
+#[cfg(feature = "tui")]
+fn events(terminal: &mut DefaultTerminal, view: &mut BoardView) -> io::Result<()> {
+    loop {
+        let _ = terminal.draw(|frame| frame.render_widget(&*view, frame.area()))?;
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('q') => return Ok(()),
+                code => {
+                    if let Some(key) = Key::from_code(code) {
+                        view.handle(key);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Returns the field next to a field in the order bar, 24 to 1, off
+fn step(field: Field, direction: i8) -> Field {
+    let index = (field.index() - direction).clamp(-1, 24);
+    match index {
+        24 => Field::Bar,
+        -1 => Field::Off,
+        point => Field::Point(point as usize),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor() {
+        let mut view = BoardView::new(Game::new());
+        view.handle(Key::Left);
+        assert_eq!(view.cursor(), Field::Bar);
+        view.handle(Key::Right);
+        assert_eq!(view.cursor(), Field::Point(23));
+        view.set_cursor(Field::Point(0));
+        view.handle(Key::Right);
+        view.handle(Key::Right);
+        assert_eq!(view.cursor(), Field::Off);
+    }
+
+    #[test]
+    fn test_moves() -> Result<(), Error> {
+        let mut g = Game::new();
+        let _ = g.roll_with((6, 5))?;
+        let mut view = BoardView::new(g).with_score((2, 1));
+
+        // no checker to pick up on the bar
+        view.handle(Key::Enter);
+        assert_eq!(view.message(), Error::MoveInvalid.to_string());
+        assert_eq!(view.selected(), None);
+
+        view.set_cursor(Field::Point(23));
+        view.handle(Key::Enter);
+        assert_eq!(view.selected(), Some(Field::Point(23)));
+        // the checker cannot be dropped 3 fields away
+        view.set_cursor(Field::Point(20));
+        view.handle(Key::Enter);
+        assert_eq!(view.message(), Error::MoveInvalid.to_string());
+        view.set_cursor(Field::Point(17));
+        view.handle(Key::Enter);
+        assert_eq!(view.selected(), None);
+        assert_eq!(view.message(), "Moved 24/18");
+        assert!(view.render().contains(" To play: 5\n"));
+
+        view.handle(Key::Backspace);
+        assert_eq!(view.game.remaining_dice(), vec![6, 5]);
+        view.set_cursor(Field::Point(12));
+        view.handle(Key::Char('6'));
+        view.handle(Key::Char('5'));
+        assert_eq!(view.game.who_plays, Player::Player1);
+        let render = view.render();
+        assert!(render.contains(" Pips: X 156, O 167\n"));
+        assert!(render.contains(" Score: X 2, O 1\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cube() {
+        let mut g = Game::new();
+        g.who_plays = Player::Player0;
+        let mut view = BoardView::new(g);
+        view.handle(Key::Char('d'));
+        view.handle(Key::Char('t'));
+        assert_eq!(view.game.cube.value(), 2);
+        view.handle(Key::Char('x'));
+        assert_eq!(view.message(), Error::NotationInvalid.to_string());
+        view.handle(Key::Char('d'));
+        assert_eq!(view.message(), Error::DoublingNotPermitted.to_string());
+        view.handle(Key::Char('r'));
+        assert!(view.message().starts_with("Rolled"));
+    }
+
+    #[cfg(feature = "term")]
+    #[test]
+    fn test_render_term() -> Result<(), Error> {
+        let mut g = Game::new();
+        let _ = g.roll_with((3, 1))?;
+        let mut view = BoardView::new(g);
+        view.set_cursor(Field::Point(7));
+        let rendered = view.render_term();
+        assert!(rendered.contains("\x1b[1;42m 5\x1b[0m"));
+        assert!(rendered.contains("Pips: X 167, O 167"));
+        assert!(rendered.ends_with(&format!(" {}\n", view.message())));
+        Ok(())
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn test_widget() -> Result<(), Error> {
+        assert_eq!(Key::from_code(KeyCode::Left), Some(Key::Left));
+        assert_eq!(Key::from_code(KeyCode::Char('6')), Some(Key::Char('6')));
+        assert_eq!(Key::from_code(KeyCode::Tab), None);
+
+        let mut g = Game::new();
+        let _ = g.roll_with((3, 1))?;
+        let view = BoardView::new(g).with_score((2, 1));
+        let area = Rect::new(0, 0, 60, 30);
+        let mut buf = Buffer::empty(area);
+        Widget::render(&view, area, &mut buf);
+        let lines: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        assert!(lines[0].contains(" Backgammon "));
+        assert!(lines.iter().any(|l| l.contains("Pips: X 167, O 167")));
+        assert!(lines.iter().any(|l| l.contains("Score: X 2, O 1")));
+        Ok(())
+    }
+}
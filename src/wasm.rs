@@ -0,0 +1,260 @@
+//! # WebAssembly Interface
+//!
+//! With the `wasm` feature, the crate exports games to JavaScript through
+//! [wasm-bindgen](https://rustwasm.github.io/docs/wasm-bindgen/), e.g. for web frontends. A
+//! package is built by e.g.
+//! ```text
+//! wasm-pack build --target web -- --features wasm
+//! ```
+//! and used as
+//! ```text
+//! import init, { Game } from "./pkg/backgammon.js";
+//!
+//! await init();
+//! const game = new Game();
+//! game.rollWith(3, 1);
+//! console.log(game.legalMoves());  // ["8/5 6/5", ...]
+//! game.apply("8/5 6/5");
+//! console.log(game.xgid());
+//! ```
+//! The interface follows the [C interface](crate::ffi) with types JavaScript knows: players are
+//! numbers, 0 or 1, or -1 for nobody, plays are strings in the conventional notation, and positions
+//! are [XGIDs](crate::formats::Xgid) or GNU Backgammon [Position IDs](crate::formats::PositionId)
+//! and [Match IDs](crate::formats::MatchId). Actions which fail throw an `Error` with the message
+//! of the [`Error`] of the crate.
+//!
+//! The dices are rolled from `crypto.getRandomValues` of the browser, or from a seed given by
+//! [`WasmGame::with_seed`](crate::wasm::WasmGame::with_seed), or from the frontend by
+//! [`WasmGame::roll_with`](crate::wasm::WasmGame::roll_with).
+#![allow(unsafe_code)]
+
+use crate::formats::{MatchId, PositionId, Xgid};
+use crate::rules::{Player, Roll, SeededDice};
+use crate::{Error, Game, Phase};
+
+use wasm_bindgen::prelude::*;
+
+/// Converts an error to an exception of JavaScript
+fn exception(error: Error) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+/// Returns the number of a player, or -1 for nobody
+fn player(player: Player) -> i32 {
+    match player {
+        Player::Player0 => 0,
+        Player::Player1 => 1,
+        Player::Nobody => -1,
+    }
+}
+
+/// A game exported to JavaScript as `Game`
+#[wasm_bindgen(js_name = Game)]
+#[derive(Debug)]
+pub struct WasmGame {
+    /// The game played
+    game: Game,
+}
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    /// Creates a new game with the default rules
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> WasmGame {
+        WasmGame { game: Game::new() }
+    }
+
+    /// Creates a new game rolling the sequence of dices derived from a seed, e.g. to replay it
+    #[wasm_bindgen(js_name = withSeed)]
+    pub fn with_seed(seed: u32) -> WasmGame {
+        WasmGame {
+            game: Game::new().with_dice_source(SeededDice::new(u64::from(seed))),
+        }
+    }
+
+    /// Creates a game from an XGID
+    #[wasm_bindgen(js_name = fromXgid)]
+    pub fn from_xgid(xgid: &str) -> Result<WasmGame, JsError> {
+        let mut game = Game::new();
+        xgid.parse::<Xgid>()
+            .and_then(|xgid| xgid.apply(&mut game))
+            .map_err(exception)?;
+        Ok(WasmGame { game })
+    }
+
+    /// Creates a game from a Position ID and a Match ID of GNU Backgammon, which tells the player
+    /// on roll the Position ID is relative to
+    #[wasm_bindgen(js_name = fromPositionId)]
+    pub fn from_position_id(position_id: &str, match_id: &str) -> Result<WasmGame, JsError> {
+        let mut game = Game::new();
+        let match_id = match_id.parse::<MatchId>().map_err(exception)?;
+        match_id.apply(&mut game).map_err(exception)?;
+        game.board = position_id
+            .parse::<PositionId>()
+            .and_then(|id| id.board(match_id.player_on_roll))
+            .map_err(exception)?;
+        Ok(WasmGame { game })
+    }
+
+    /// Returns the player whose turn it is, 0 or 1, or -1 before the opening roll
+    pub fn turn(&self) -> i32 {
+        player(self.game.who_plays)
+    }
+
+    /// Rolls the dices and returns their two values
+    pub fn roll(&mut self) -> Result<Vec<u8>, JsError> {
+        let _ = self.game.roll().map_err(exception)?;
+        Ok(self.dices())
+    }
+
+    /// Sets the dices to the given values instead of rolling them, e.g. for dices rolled by the
+    /// frontend
+    #[wasm_bindgen(js_name = rollWith)]
+    pub fn roll_with(&mut self, a: u8, b: u8) -> Result<(), JsError> {
+        self.game.roll_with((a, b)).map(|_| ()).map_err(exception)
+    }
+
+    /// Returns the values of the dices rolled, or zeros if they have not been rolled
+    pub fn dices(&self) -> Vec<u8> {
+        vec![self.game.dices.values.0, self.game.dices.values.1]
+    }
+
+    /// Returns the distinct legal plays of the player whose turn it is in the conventional
+    /// notation
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<String> {
+        self.game
+            .legal_plays()
+            .iter()
+            .map(|(_, play)| play.to_string())
+            .collect()
+    }
+
+    /// Plays the dices rolled as given in the conventional notation, e.g. "24/18 13/9". The game
+    /// is only changed if the whole play is legal.
+    pub fn apply(&mut self, play: &str) -> Result<(), JsError> {
+        let game = &mut self.game;
+        game.check_phase(Phase::AwaitingMove)
+            .and_then(|()| game.parse_play(play))
+            .and_then(|play| game.apply_play(&play))
+            .map_err(exception)
+    }
+
+    /// Offers the cube on behalf of the player whose turn it is
+    pub fn double(&mut self) -> Result<(), JsError> {
+        self.game
+            .offer_double(self.game.who_plays)
+            .map_err(exception)
+    }
+
+    /// Takes the cube offered by the player whose turn it is
+    pub fn take(&mut self) -> Result<(), JsError> {
+        self.game
+            .take(self.game.who_plays.other())
+            .map_err(exception)
+    }
+
+    /// Passes the cube offered by the player whose turn it is, which ends the game
+    pub fn pass(&mut self) -> Result<(), JsError> {
+        self.game
+            .pass(self.game.who_plays.other())
+            .map_err(exception)
+    }
+
+    /// Returns the pips player 0 or player 1 needs to bear off all checkers
+    #[wasm_bindgen(js_name = pipCount)]
+    pub fn pip_count(&self, player: u8) -> u32 {
+        match player {
+            0 => self.game.pip_count(Player::Player0),
+            _ => self.game.pip_count(Player::Player1),
+        }
+    }
+
+    /// Returns the winner once the game has ended, 0 or 1, or -1 while the game is running
+    pub fn winner(&self) -> i32 {
+        self.game
+            .result()
+            .map_or(-1, |result| player(result.winner))
+    }
+
+    /// Returns the points won once the game has ended, or 0 while the game is running
+    pub fn points(&self) -> u32 {
+        self.game
+            .result()
+            .map_or(0, |result| result.points.try_into().unwrap_or(u32::MAX))
+    }
+
+    /// Returns the position as an XGID
+    pub fn xgid(&self) -> String {
+        Xgid::from_game(&self.game, 0, (0, 0)).to_string()
+    }
+
+    /// Returns the cube, the dices, and the player on roll as a Match ID of GNU Backgammon
+    #[wasm_bindgen(js_name = matchId)]
+    pub fn match_id(&self) -> String {
+        MatchId::from_game(&self.game, 0, (0, 0)).to_string()
+    }
+
+    /// Returns the checkers on the board as a Position ID of GNU Backgammon, relative to the
+    /// player on roll
+    #[wasm_bindgen(js_name = positionId)]
+    pub fn position_id(&self) -> String {
+        PositionId::from_game(&self.game).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // exceptions can only be thrown in JavaScript, hence only actions which succeed are tested
+
+    #[test]
+    fn test_game() {
+        let mut game = WasmGame::new();
+        assert_eq!(game.turn(), -1);
+        assert!(game.roll_with(3, 1).is_ok());
+        assert_eq!(game.turn(), 0);
+        assert_eq!(game.dices(), vec![3, 1]);
+        assert!(game.legal_moves().iter().any(|m| m == "8/5 6/5"));
+        assert!(game.apply("8/5 6/5").is_ok());
+        assert_eq!(game.turn(), 1);
+        assert_eq!(game.pip_count(0), 163);
+
+        let copy = WasmGame::from_xgid(&game.xgid()).unwrap_or_else(|_| WasmGame::new());
+        assert_eq!(copy.game.board, game.game.board);
+        assert_eq!(copy.match_id(), game.match_id());
+
+        let copy = WasmGame::from_position_id(&game.position_id(), &game.match_id())
+            .unwrap_or_else(|_| WasmGame::new());
+        assert_eq!(copy.game.board, game.game.board);
+        assert_eq!(copy.turn(), 1);
+        assert_eq!(WasmGame::new().position_id(), "4HPwATDgc/ABMA");
+    }
+
+    #[test]
+    fn test_seed() {
+        let rolls = |seed| {
+            let mut game = WasmGame::with_seed(seed);
+            game.roll().unwrap_or_default()
+        };
+        assert_eq!(rolls(7), rolls(7));
+        assert!(rolls(7).iter().all(|d| (1..=6).contains(d)));
+    }
+
+    #[test]
+    fn test_cube() {
+        let mut game = WasmGame::new();
+        assert!(game.roll_with(3, 1).is_ok());
+        assert!(game.apply("8/5 6/5").is_ok());
+        assert!(game.double().is_ok());
+        assert!(game.take().is_ok());
+        assert_eq!((game.winner(), game.points()), (-1, 0));
+        assert!(game.roll_with(6, 5).is_ok());
+        assert!(game.apply("24/13").is_ok());
+        assert!(game.double().is_ok());
+        assert!(game.pass().is_ok());
+        assert_eq!((game.winner(), game.points()), (0, 2));
+    }
+}
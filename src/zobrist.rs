@@ -0,0 +1,256 @@
+//! # Zobrist Hashing of Positions
+//!
+//! A Zobrist hash combines a random key for every feature of a position by exclusive or, i.e. a key
+//! for every number of checkers of a player on every field, for the player on roll, and for the
+//! value and the owner of the cube. Changing a feature only toggles its keys, so the hash of the
+//! position after a move is updated with a few operations, see [`update`](crate::zobrist::update)
+//! and [`apply`](crate::zobrist::apply).
+//!
+//! The keys are generated at compile time from a fixed seed, hence the hashes are stable across
+//! runs and platforms and can serve as keys of position databases.
+//! ```
+//! use backgammon::Game;
+//! use backgammon::rules::{CheckerMove, Field, Player};
+//! use backgammon::zobrist;
+//!
+//! let mut g = Game::new();
+//! g.roll_with((3, 1)).unwrap();
+//! let before = g.hash64();
+//!
+//! // update the hash of the board incrementally
+//! let m = CheckerMove::new(Field::Point(7), vec![3])?;
+//! let hash = zobrist::apply(zobrist::hash_board(&g.board), &g.board, Player::Player0, &m)?;
+//! g.apply_move(&m)?;
+//! assert_eq!(hash, zobrist::hash_board(&g.board));
+//! assert_ne!(g.hash64(), before);
+//! # Ok::<(), backgammon::Error>(())
+//! ```
+use crate::game::Game;
+use crate::rules::{Board, CheckerMove, Cube, Field, Player};
+use crate::{Error, PositionError};
+use alloc::vec;
+
+/// The seed of the keys, spelling "backgamm"
+const SEED: u64 = 0x6261_636b_6761_6d6d;
+
+/// The fields of each player with keys: 24 points, the bar, and the off area
+const FIELDS: usize = 26;
+/// The numbers of checkers on a field with keys
+const COUNTS: usize = 16;
+/// The offset of the keys for the player on roll
+const TURN: usize = 2 * FIELDS * COUNTS;
+/// The offset of the keys for the exponent of the value of the cube
+const CUBE_VALUE: usize = TURN + 2;
+/// The offset of the keys for the owner of the cube
+const CUBE_OWNER: usize = CUBE_VALUE + 64;
+
+/// All keys
+const KEYS: [u64; CUBE_OWNER + 2] = keys();
+
+/// Generate the keys with SplitMix64
+const fn keys<const N: usize>() -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state = SEED;
+    let mut i = 0;
+    while i < N {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+/// Returns the index of a player among the keys
+fn player_index(player: Player) -> Option<usize> {
+    match player {
+        Player::Player0 => Some(0),
+        Player::Player1 => Some(1),
+        Player::Nobody => None,
+    }
+}
+
+/// Returns the key of a number of checkers of a player on a field. No checkers have no key, so
+/// empty fields do not change the hash.
+pub fn checkers(player: Player, field: Field, count: u8) -> Result<u64, Error> {
+    let index = player_index(player).ok_or(Error::PlayerInvalid)?;
+    let field = match field {
+        Field::Point(f) if f < 24 => f,
+        Field::Point(_) => return Err(Error::FieldInvalid),
+        Field::Bar => 24,
+        Field::Off => 25,
+    };
+    match count {
+        0 => Ok(0),
+        1..=15 => Ok(KEYS[(index * FIELDS + field) * COUNTS + count as usize]),
+        _ => Err(Error::PositionInvalid(PositionError::CheckerCount(
+            player, count,
+        ))),
+    }
+}
+
+/// Returns the key of the player on roll, which is none if it is nobody's turn
+pub fn turn(player: Player) -> u64 {
+    player_index(player).map_or(0, |p| KEYS[TURN + p])
+}
+
+/// Returns the key of the state of the cube. A centered cube on 1 has no key.
+pub fn cube(cube: &Cube) -> u64 {
+    let exponent = cube.value().trailing_zeros() as usize;
+    let value = if exponent == 0 {
+        0
+    } else {
+        KEYS[CUBE_VALUE + exponent]
+    };
+    value ^ player_index(cube.owner()).map_or(0, |p| KEYS[CUBE_OWNER + p])
+}
+
+/// Returns the hash of the checkers on a board
+pub fn hash_board(board: &Board) -> u64 {
+    let mut hash = 0;
+    for player in [Player::Player0, Player::Player1] {
+        let side = board.side(player).unwrap_or_default();
+        let fields = side
+            .board
+            .iter()
+            .enumerate()
+            .map(|(f, c)| (Field::Point(f), *c))
+            .chain([(Field::Bar, side.bar), (Field::Off, side.off)]);
+        for (field, count) in fields {
+            hash ^= checkers(player, field, count).unwrap_or(0);
+        }
+    }
+    hash
+}
+
+/// Returns the hash of the position of a game, i.e. of the checkers, the player on roll, and the
+/// cube
+pub fn hash(game: &Game) -> u64 {
+    hash_board(&game.board) ^ turn(game.who_plays) ^ cube(&game.cube)
+}
+
+/// Returns the hash updated for a changed number of checkers of a player on a field
+pub fn update(
+    hash: u64,
+    player: Player,
+    field: Field,
+    before: u8,
+    after: u8,
+) -> Result<u64, Error> {
+    Ok(hash ^ checkers(player, field, before)? ^ checkers(player, field, after)?)
+}
+
+/// Returns the hash of a board updated for a move of a player, including the checkers hit, given
+/// the board before the move
+pub fn apply(hash: u64, board: &Board, player: Player, m: &CheckerMove) -> Result<u64, Error> {
+    let mut after = board.clone();
+    after.apply(player, m)?;
+
+    // the fields which may have changed
+    let opponent = player.other();
+    let mut fields = vec![(player, m.from)];
+    for field in m.path()? {
+        fields.push((player, field));
+        if let Field::Point(f) = field {
+            fields.push((opponent, Field::Point(23 - f)));
+            fields.push((opponent, Field::Bar));
+        }
+    }
+    fields.sort_by_key(|(p, f)| (*p == player, f.index()));
+    fields.dedup();
+
+    let mut hash = hash;
+    for (p, field) in fields {
+        hash = update(
+            hash,
+            p,
+            field,
+            board.get_checkers(p, field)?,
+            after.get_checkers(p, field)?,
+        )?;
+    }
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Point;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_keys() -> Result<(), Error> {
+        // all keys are distinct and stable
+        assert_eq!(KEYS.iter().collect::<HashSet<_>>().len(), KEYS.len());
+        assert_eq!(hash_board(&Board::new()), 0x2361_5ec4_61f2_d744);
+        assert_eq!(checkers(Player::Player0, Field::Point(3), 0)?, 0);
+        assert!(checkers(Player::Nobody, Field::Bar, 1).is_err());
+        assert!(checkers(Player::Player1, Field::Point(24), 1).is_err());
+        assert!(checkers(Player::Player1, Field::Off, 16).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_game() -> Result<(), Error> {
+        let g = Game::new();
+        let mut other = g.clone();
+        other.who_plays = Player::Player1;
+        assert_ne!(hash(&g), hash(&other));
+
+        let mut doubled = Game::new();
+        doubled.cube.set(2)?;
+        assert_ne!(hash(&g), hash(&doubled));
+        let value = hash(&doubled);
+        doubled.cube.set_owner(Player::Player1);
+        assert_ne!(value, hash(&doubled));
+        assert_ne!(hash(&g), hash(&doubled));
+        assert_eq!(hash(&g), hash_board(&g.board));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply() -> Result<(), Error> {
+        // a hit, and moves in different orders reaching the same position
+        let mut board = Board::new();
+        board.set(Player::Player1, 23, -1)?;
+        board.set(Player::Player1, 17, 1)?;
+        let moves = [
+            CheckerMove::new(Field::Point(7), vec![1])?,
+            CheckerMove::new(Field::Point(12), vec![5])?,
+        ];
+        let mut hashes = Vec::new();
+        for order in [[0, 1], [1, 0]] {
+            let mut b = board.clone();
+            let mut h = hash_board(&b);
+            for i in order {
+                h = apply(h, &b, Player::Player0, &moves[i])?;
+                b.apply(Player::Player0, &moves[i])?;
+                assert_eq!(h, hash_board(&b));
+            }
+            hashes.push(h);
+        }
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hash_board(&board));
+
+        // entering from the bar and bearing off
+        let mut board = Board::new();
+        board.set(Player::Player0, 23, -1)?;
+        board.set_bar(Player::Player0, 1)?;
+        board.points = board.points.map(|p| match p.owner {
+            Player::Player1 => Point::EMPTY,
+            _ => p,
+        });
+        board.points[21] = Point::new(Player::Player1, 15);
+        for (player, m) in [
+            (Player::Player0, CheckerMove::new(Field::Bar, vec![4])?),
+            (Player::Player1, CheckerMove::new(Field::Point(2), vec![3])?),
+        ] {
+            let h = apply(hash_board(&board), &board, player, &m)?;
+            board.apply(player, &m)?;
+            assert_eq!(h, hash_board(&board));
+        }
+        Ok(())
+    }
+}